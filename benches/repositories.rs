@@ -0,0 +1,208 @@
+//! Benchmarks for the repository paths that grow with the size of the data files
+//!
+//! Run with `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` and `cargo bench -- --baseline main`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use shellbe::application::AliasService;
+use shellbe::domain::{
+    Alias, AliasRepository, HistoryEntry, HistoryRepository, Profile, ProfileRepository, SshConfigRepository,
+};
+use shellbe::infrastructure::{
+    EncryptedStorage, FileAliasRepository, FileHistoryRepository, FileProfileRepository, FileSshConfigRepository,
+    FileStorageConfig,
+};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+/// Profile counts for the load and save benchmarks
+const PROFILE_COUNTS: &[usize] = &[100, 1_000, 10_000];
+
+/// Host blocks in the generated SSH config
+const SSH_CONFIG_HOSTS: &[usize] = &[100, 1_000, 5_000];
+
+/// Entries in the generated history
+const HISTORY_ENTRIES: usize = 100_000;
+
+/// Profiles the history entries are spread over
+const HISTORY_PROFILES: usize = 200;
+
+/// Aliases, each pointing at the one before it in chains of this length
+const ALIAS_COUNT: usize = 1_000;
+const ALIAS_CHAIN: usize = 5;
+
+fn runtime() -> Runtime {
+    Runtime::new().expect("Failed to start tokio runtime")
+}
+
+fn profile(i: usize) -> Profile {
+    let mut profile = Profile::new(format!("host-{}", i), format!("host-{}.example.com", i), "deploy");
+    profile.tags = vec!["bench".to_string(), format!("group-{}", i % 10)];
+    profile
+}
+
+fn storage_config(dir: &Path) -> FileStorageConfig {
+    FileStorageConfig {
+        config_dir: dir.to_path_buf(),
+        profiles_file: "profiles.json".to_string(),
+        storage: EncryptedStorage::plain(),
+    }
+}
+
+/// Write a profiles file with `count` profiles
+async fn seed_profiles(dir: &Path, count: usize) {
+    let repository = FileProfileRepository::new(storage_config(dir)).await.unwrap();
+    for i in 0..count {
+        repository.add(profile(i)).await.unwrap();
+    }
+    repository.flush().await.unwrap();
+}
+
+fn profile_repository(c: &mut Criterion) {
+    let rt = runtime();
+    let mut group = c.benchmark_group("profile_repository");
+
+    for &count in PROFILE_COUNTS {
+        let dir = tempfile::tempdir().unwrap();
+        rt.block_on(seed_profiles(dir.path(), count));
+
+        group.bench_with_input(BenchmarkId::new("load", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                FileProfileRepository::new(storage_config(dir.path())).await.unwrap()
+            });
+        });
+
+        // One change followed by writing the whole file
+        let repository = rt.block_on(FileProfileRepository::new(storage_config(dir.path()))).unwrap();
+        group.bench_with_input(BenchmarkId::new("save", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                repository.update(profile(0)).await.unwrap();
+                repository.flush().await.unwrap();
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("get", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                repository.get("host-0").await.unwrap()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// An SSH config with `count` host blocks, like one generated for a large fleet
+fn ssh_config(count: usize) -> String {
+    let mut config = String::from("Host *\n    ServerAliveInterval 30\n\n");
+    for i in 0..count {
+        writeln!(config, "Host host-{} h{}", i, i).unwrap();
+        writeln!(config, "    HostName host-{}.example.com", i).unwrap();
+        writeln!(config, "    User deploy").unwrap();
+        writeln!(config, "    Port {}", 22 + i % 3).unwrap();
+        writeln!(config, "    IdentityFile ~/.ssh/id_ed25519").unwrap();
+        writeln!(config, "    ForwardAgent no\n").unwrap();
+    }
+    config
+}
+
+fn ssh_config_parse(c: &mut Criterion) {
+    let rt = runtime();
+    let mut group = c.benchmark_group("ssh_config");
+
+    for &count in SSH_CONFIG_HOSTS {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        std::fs::write(&path, ssh_config(count)).unwrap();
+        let repository = FileSshConfigRepository::new(&path);
+
+        group.bench_with_input(BenchmarkId::new("import", count), &count, |b, _| {
+            b.to_async(&rt).iter(|| async {
+                repository.import_with_aliases().await.unwrap()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn history_queries(c: &mut Criterion) {
+    let rt = runtime();
+    let mut group = c.benchmark_group("history");
+
+    let dir = tempfile::tempdir().unwrap();
+    let entries: Vec<HistoryEntry> = (0..HISTORY_ENTRIES)
+        .map(|i| HistoryEntry::new(format!("host-{}", i % HISTORY_PROFILES), format!("host-{}.example.com", i % HISTORY_PROFILES)))
+        .collect();
+    std::fs::write(dir.path().join("history.json"), serde_json::to_vec(&entries).unwrap()).unwrap();
+
+    // A fresh repository each time: every command starts with nothing loaded
+    let open = || FileHistoryRepository::new(dir.path().to_path_buf(), "history.json".to_string(), EncryptedStorage::plain());
+
+    group.bench_function("recent", |b| {
+        b.to_async(&rt).iter(|| async {
+            open().await.unwrap().get_recent(10).await.unwrap()
+        });
+    });
+
+    group.bench_function("for_profile", |b| {
+        b.to_async(&rt).iter(|| async {
+            open().await.unwrap().get_for_profile("host-7").await.unwrap()
+        });
+    });
+
+    group.bench_function("stats", |b| {
+        b.to_async(&rt).iter(|| async {
+            open().await.unwrap().get_stats().await.unwrap()
+        });
+    });
+
+    group.bench_function("list", |b| {
+        b.to_async(&rt).iter(|| async {
+            open().await.unwrap().list().await.unwrap()
+        });
+    });
+
+    group.finish();
+}
+
+fn alias_resolution(c: &mut Criterion) {
+    let rt = runtime();
+    let dir = tempfile::tempdir().unwrap();
+
+    let service = rt.block_on(async {
+        seed_profiles(dir.path(), ALIAS_COUNT / ALIAS_CHAIN).await;
+        let profiles = Arc::new(FileProfileRepository::new(storage_config(dir.path())).await.unwrap());
+        let aliases = Arc::new(FileAliasRepository::new(dir.path().to_path_buf(), "aliases.json".to_string(), EncryptedStorage::plain()).await.unwrap());
+
+        // alias-<chain>-0 -> host-<chain>, alias-<chain>-1 -> alias-<chain>-0, ...
+        for chain in 0..ALIAS_COUNT / ALIAS_CHAIN {
+            for step in 0..ALIAS_CHAIN {
+                let target = match step {
+                    0 => format!("host-{}", chain),
+                    _ => format!("alias-{}-{}", chain, step - 1),
+                };
+                aliases.add(Alias::new(format!("alias-{}-{}", chain, step), target)).await.unwrap();
+            }
+        }
+
+        AliasService::new(aliases, profiles)
+    });
+
+    let mut group = c.benchmark_group("alias");
+    group.bench_function("resolve_direct", |b| {
+        b.to_async(&rt).iter(|| async {
+            service.resolve_alias("alias-42-0").await.unwrap()
+        });
+    });
+    group.bench_function("resolve_chain", |b| {
+        b.to_async(&rt).iter(|| async {
+            service.resolve_alias(&format!("alias-42-{}", ALIAS_CHAIN - 1)).await.unwrap()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, profile_repository, ssh_config_parse, history_queries, alias_resolution);
+criterion_main!(benches);
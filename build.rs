@@ -0,0 +1,10 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use the bundled protoc unless one is configured, so building needs nothing installed
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+
+    println!("cargo:rerun-if-changed=proto/shellbe.proto");
+    tonic_build::compile_protos("proto/shellbe.proto")?;
+    Ok(())
+}
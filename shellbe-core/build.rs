@@ -0,0 +1,5 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Self-update downloads the release asset built for the same target
+    println!("cargo:rustc-env=SHELLBE_TARGET={}", std::env::var("TARGET")?);
+    Ok(())
+}
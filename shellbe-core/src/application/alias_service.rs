@@ -0,0 +1,264 @@
+use crate::domain::{
+    Alias, AliasRepository, AliasResolution, AliasStatus, ConnectionArgs, ProfileRepository,
+    DomainError, Event, EventBus, template_params, match_template,
+};
+use crate::utils::closest_matches;
+use std::sync::Arc;
+use std::collections::HashSet;
+
+/// AliasService manages SSH connection aliases
+pub struct AliasService {
+    alias_repository: Arc<dyn AliasRepository>,
+    profile_repository: Arc<dyn ProfileRepository>,
+    event_bus: Arc<EventBus>,
+}
+
+impl AliasService {
+    /// Create a new AliasService with the provided repositories
+    pub fn new(
+        alias_repository: Arc<dyn AliasRepository>,
+        profile_repository: Arc<dyn ProfileRepository>,
+    ) -> Self {
+        Self {
+            alias_repository,
+            profile_repository,
+            event_bus: Arc::new(EventBus::new()),
+        }
+    }
+
+    /// Publish alias changes on this event bus
+    pub fn set_event_bus(&mut self, event_bus: Arc<EventBus>) {
+        self.event_bus = event_bus;
+    }
+
+    /// Create a new alias for a profile
+    pub async fn create_alias(&self, alias_name: &str, profile_name: &str) -> Result<(), DomainError> {
+        self.create_alias_with_args(alias_name, profile_name, ConnectionArgs::default()).await
+    }
+
+    /// Create a new alias that applies extra connection arguments when used
+    pub async fn create_alias_with_args(
+        &self,
+        alias_name: &str,
+        profile_name: &str,
+        args: ConnectionArgs,
+    ) -> Result<(), DomainError> {
+        // Template aliases such as `web{N}` target a pattern rather than a single profile
+        if !template_params(alias_name).is_empty() {
+            return self.create_template_alias(alias_name, profile_name, args).await;
+        }
+
+        // Check if profile exists
+        if !self.profile_repository.exists(profile_name).await? {
+            return Err(DomainError::ProfileNotFound(profile_name.to_string()));
+        }
+
+        // Check if target is an alias (to detect potential circular references)
+        if let Some(target) = self.alias_repository.get_target(profile_name).await? {
+            // The target is an alias itself, check for circular reference
+            // Traverse the chain to check for cycles
+            let mut visited = HashSet::new();
+            visited.insert(alias_name.to_string());
+            visited.insert(profile_name.to_string());
+
+            let mut current = target;
+            while let Some(next) = self.alias_repository.get_target(&current).await? {
+                if visited.contains(&next) {
+                    return Err(DomainError::ConfigError(
+                        format!("Circular alias reference detected: {} -> {} -> {}",
+                                alias_name, profile_name, next)
+                    ));
+                }
+                visited.insert(next.clone());
+                current = next;
+            }
+        }
+
+        // Check if alias already exists
+        if let Some(_) = self.alias_repository.get_target(alias_name).await? {
+            return Err(DomainError::AliasAlreadyExists(alias_name.to_string()));
+        }
+
+        // Create the alias
+        let alias = Alias::new(alias_name, profile_name).with_connection_args(args);
+        self.alias_repository.add(alias.clone()).await?;
+        self.event_bus.publish(Event::AliasCreated(alias));
+
+        Ok(())
+    }
+
+    /// Register a template alias such as `web{N} -> web-{N}.prod`
+    async fn create_template_alias(
+        &self,
+        pattern: &str,
+        target: &str,
+        args: ConnectionArgs,
+    ) -> Result<(), DomainError> {
+        let params = template_params(pattern);
+
+        // Each parameter can only be captured once
+        let mut seen = HashSet::new();
+        if let Some(dup) = params.iter().find(|p| !seen.insert(p.as_str())) {
+            return Err(DomainError::ConfigError(
+                format!("Parameter '{{{}}}' appears more than once in '{}'", dup, pattern)
+            ));
+        }
+
+        // The target can only use parameters captured by the pattern
+        if let Some(unknown) = template_params(target).iter().find(|p| !params.contains(p)) {
+            return Err(DomainError::ConfigError(
+                format!("Target '{}' uses '{{{}}}', which is not defined in '{}'", target, unknown, pattern)
+            ));
+        }
+
+        // Check if alias already exists
+        if self.alias_repository.get_target(pattern).await?.is_some() {
+            return Err(DomainError::AliasAlreadyExists(pattern.to_string()));
+        }
+
+        let alias = Alias::new(pattern, target).with_connection_args(args);
+        self.alias_repository.add(alias.clone()).await?;
+        self.event_bus.publish(Event::AliasCreated(alias));
+
+        Ok(())
+    }
+
+    /// Find the template alias matching a name, returned with its target expanded
+    pub async fn match_template(&self, name: &str) -> Result<Option<Alias>, DomainError> {
+        Ok(match_template(self.alias_repository.list().await?, name))
+    }
+
+    /// Get all aliases
+    pub async fn list_aliases(&self) -> Result<Vec<Alias>, DomainError> {
+        self.alias_repository.list().await
+    }
+
+    /// Names of aliases that are close to `name`, for "did you mean" hints
+    pub async fn similar_names(&self, name: &str) -> Result<Vec<String>, DomainError> {
+        let aliases = self.alias_repository.list().await?;
+        Ok(closest_matches(name, aliases.iter().filter(|a| !a.is_template()).map(|a| a.name.as_str())))
+    }
+
+    /// Remove an alias
+    pub async fn remove_alias(&self, alias_name: &str) -> Result<(), DomainError> {
+        // Check if alias exists
+        if let None = self.alias_repository.get_target(alias_name).await? {
+            return Err(DomainError::AliasNotFound(alias_name.to_string()));
+        }
+
+        // Remove the alias
+        self.alias_repository.remove(alias_name).await?;
+        self.event_bus.publish(Event::AliasRemoved(alias_name.to_string()));
+
+        Ok(())
+    }
+
+    /// Rename an alias, repointing any aliases that refer to it
+    pub async fn rename_alias(&self, old_name: &str, new_name: &str) -> Result<(), DomainError> {
+        // Check if alias exists
+        let mut alias = match self.alias_repository.get(old_name).await? {
+            Some(alias) => alias,
+            None => return Err(DomainError::AliasNotFound(old_name.to_string())),
+        };
+
+        // Check if the new name is free
+        if self.alias_repository.get_target(new_name).await?.is_some() {
+            return Err(DomainError::AliasAlreadyExists(new_name.to_string()));
+        }
+
+        // Replace the alias, keeping its arguments
+        self.alias_repository.remove(old_name).await?;
+        alias.name = new_name.to_string();
+        self.alias_repository.add(alias.clone()).await?;
+        self.event_bus.publish(Event::AliasRemoved(old_name.to_string()));
+        self.event_bus.publish(Event::AliasCreated(alias));
+
+        // Keep alias chains intact
+        for mut alias in self.alias_repository.list_for_profile(old_name).await? {
+            self.alias_repository.remove(&alias.name).await?;
+            alias.target = new_name.to_string();
+            self.alias_repository.add(alias).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get aliases for a specific profile
+    pub async fn get_aliases_for_profile(&self, profile_name: &str) -> Result<Vec<Alias>, DomainError> {
+        // Check if profile exists
+        if !self.profile_repository.exists(profile_name).await? {
+            return Err(DomainError::ProfileNotFound(profile_name.to_string()));
+        }
+
+        // Get aliases for the profile
+        self.alias_repository.list_for_profile(profile_name).await
+    }
+
+    /// Resolve an alias to a profile name
+    pub async fn resolve_alias(&self, name: &str) -> Result<String, DomainError> {
+        let resolution = self.diagnose_alias(name).await?;
+
+        match resolution.status {
+            AliasStatus::Resolved => Ok(resolution.last().to_string()),
+            AliasStatus::Circular => Err(DomainError::ConfigError(
+                format!("Circular alias reference detected: {}", resolution.chain.join(" -> "))
+            )),
+            AliasStatus::Dangling | AliasStatus::Template => {
+                Err(DomainError::ProfileNotFound(resolution.last().to_string()))
+            },
+        }
+    }
+
+    /// Follow an alias chain and report every step and how it ended
+    pub async fn diagnose_alias(&self, name: &str) -> Result<AliasResolution, DomainError> {
+        let mut visited = HashSet::new();
+        visited.insert(name.to_string());
+
+        let mut chain = vec![name.to_string()];
+        let mut current = name.to_string();
+
+        // Template patterns only resolve once a concrete name is given
+        if !template_params(name).is_empty() {
+            if let Some(target) = self.alias_repository.get_target(name).await? {
+                chain.push(target);
+            }
+            return Ok(AliasResolution { chain, status: AliasStatus::Template });
+        }
+
+        // Follow the alias chain until we reach a profile
+        loop {
+            let target = match self.alias_repository.get_target(&current).await? {
+                Some(target) => target,
+                None => match self.match_template(&current).await? {
+                    // Each template may only expand once, so patterns can't grow forever
+                    Some(template) if visited.insert(template.name) => template.target,
+                    Some(_) => return Ok(AliasResolution { chain, status: AliasStatus::Circular }),
+                    None => break,
+                },
+            };
+
+            chain.push(target.clone());
+
+            // Check for cycles
+            if !visited.insert(target.clone()) {
+                return Ok(AliasResolution { chain, status: AliasStatus::Circular });
+            }
+
+            current = target;
+        }
+
+        // Check if the final target is a valid profile
+        let status = if self.profile_repository.exists(&current).await? {
+            AliasStatus::Resolved
+        } else {
+            AliasStatus::Dangling
+        };
+
+        Ok(AliasResolution { chain, status })
+    }
+
+    /// Check if a name is an alias
+    pub async fn is_alias(&self, name: &str) -> Result<bool, DomainError> {
+        Ok(self.alias_repository.get_target(name).await?.is_some())
+    }
+}
\ No newline at end of file
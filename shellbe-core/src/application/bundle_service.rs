@@ -0,0 +1,208 @@
+use crate::domain::{DomainError, Profile};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+/// File extension of profile bundles
+pub const BUNDLE_EXTENSION: &str = "sbp";
+
+/// Bundle format written by this version
+const BUNDLE_VERSION: u32 = 1;
+
+/// Archive entry with the bundle contents; the signature covers its exact bytes
+const MANIFEST_ENTRY: &str = "bundle.json";
+
+/// Archive entry with the signature and the signer's public key
+const SIGNATURE_ENTRY: &str = "bundle.sig";
+
+/// Largest entry read from a bundle, so a crafted archive can't exhaust memory
+const MAX_ENTRY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Profiles distributed in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle format version
+    pub version: u32,
+    /// When the bundle was created
+    pub created_at: DateTime<Utc>,
+    /// Who created it
+    pub created_by: String,
+    /// The profiles, without personal usage data
+    pub profiles: Vec<Profile>,
+}
+
+/// Signature stored next to the manifest
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleSignature {
+    /// Always `ed25519`
+    algorithm: String,
+    /// Signer's public key, base64
+    public_key: String,
+    /// Signature of the manifest bytes, base64
+    signature: String,
+}
+
+/// A bundle whose signature checked out against a trusted key
+#[derive(Debug, Clone)]
+pub struct VerifiedBundle {
+    /// The bundle contents
+    pub manifest: BundleManifest,
+    /// Trusted key file that matched the signer
+    pub signer: PathBuf,
+}
+
+/// Creates ed25519-signed profile bundles and verifies them against trusted team keys
+///
+/// Keys are PEM files as written by `openssl genpkey -algorithm ed25519` (private, PKCS#8)
+/// and `openssl pkey -pubout` (public).
+pub struct BundleService {
+    trusted_keys: Vec<PathBuf>,
+}
+
+impl BundleService {
+    /// Bundles are accepted when signed by one of these public keys
+    pub fn new(trusted_keys: Vec<PathBuf>) -> Self {
+        Self { trusted_keys }
+    }
+
+    /// Write the profiles to `out`, signed with the private key in `signing_key`
+    pub fn create(&self, profiles: &[Profile], signing_key: &Path, out: &Path) -> Result<BundleManifest, DomainError> {
+        let manifest = BundleManifest {
+            version: BUNDLE_VERSION,
+            created_at: Utc::now(),
+            created_by: whoami::username(),
+            profiles: profiles.iter()
+                .cloned()
+                .map(|mut profile| {
+                    profile.last_used = None;
+                    profile
+                })
+                .collect(),
+        };
+
+        let content = serde_json::to_vec_pretty(&manifest)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize bundle: {}", e)))?;
+
+        let signature = self.sign(&content, signing_key)?;
+
+        let file = fs::File::create(out).map_err(DomainError::IoError)?;
+        let mut archive = zip::ZipWriter::new(file);
+        let archive_error = |e: zip::result::ZipError| DomainError::ConfigError(format!("Failed to write bundle: {}", e));
+
+        for (name, bytes) in [(MANIFEST_ENTRY, &content), (SIGNATURE_ENTRY, &signature)] {
+            archive.start_file(name, SimpleFileOptions::default()).map_err(archive_error)?;
+            archive.write_all(bytes).map_err(DomainError::IoError)?;
+        }
+        archive.finish().map_err(archive_error)?;
+
+        Ok(manifest)
+    }
+
+    /// Read a bundle, refusing it unless a trusted key signed it
+    pub fn open(&self, path: &Path) -> Result<VerifiedBundle, DomainError> {
+        let file = fs::File::open(path).map_err(DomainError::IoError)?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| DomainError::ConfigError(format!("{} is not a profile bundle: {}", path.display(), e)))?;
+
+        let content = read_entry(&mut archive, MANIFEST_ENTRY)?;
+        let signer = self.verify(&content, &read_entry(&mut archive, SIGNATURE_ENTRY)?)?;
+
+        let manifest: BundleManifest = serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid bundle contents: {}", e)))?;
+
+        if manifest.version > BUNDLE_VERSION {
+            return Err(DomainError::ConfigError(format!(
+                "The bundle uses format version {}; update ShellBe to import it",
+                manifest.version,
+            )));
+        }
+
+        Ok(VerifiedBundle { manifest, signer })
+    }
+
+    /// Detached signature of `content` with the private key in `signing_key`
+    ///
+    /// Written as JSON, the same way bundles store theirs, so it can sit next to a shared file.
+    pub fn sign(&self, content: &[u8], signing_key: &Path) -> Result<Vec<u8>, DomainError> {
+        let pem = fs::read_to_string(signing_key).map_err(DomainError::IoError)?;
+        let key = SigningKey::from_pkcs8_pem(&pem)
+            .map_err(|e| DomainError::ConfigError(format!("{} is not an ed25519 private key in PEM format: {}", signing_key.display(), e)))?;
+
+        let signature = BundleSignature {
+            algorithm: "ed25519".to_string(),
+            public_key: BASE64.encode(key.verifying_key().as_bytes()),
+            signature: BASE64.encode(key.sign(content).to_bytes()),
+        };
+
+        serde_json::to_vec_pretty(&signature)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize signature: {}", e)))
+    }
+
+    /// Check a signature from [`BundleService::sign`]; returns the trusted key file that matched
+    pub fn verify(&self, content: &[u8], signature: &[u8]) -> Result<PathBuf, DomainError> {
+        let signature: BundleSignature = serde_json::from_slice(signature)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid signature: {}", e)))?;
+
+        if signature.algorithm != "ed25519" {
+            return Err(DomainError::ConfigError(format!("Unsupported signature algorithm '{}'", signature.algorithm)));
+        }
+
+        let signer = decode_public_key(&signature.public_key)?;
+        let signer_path = self.trusted_key_path(&signer)?
+            .ok_or_else(|| DomainError::ConfigError(format!(
+                "Signed by an untrusted key ({}). Add its public key to bundles.trusted_keys in the settings",
+                signature.public_key,
+            )))?;
+
+        let bytes: [u8; 64] = BASE64.decode(&signature.signature)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| DomainError::ConfigError("Invalid signature".to_string()))?;
+
+        signer.verify(content, &Signature::from_bytes(&bytes))
+            .map_err(|_| DomainError::ConfigError("The signature doesn't match the contents; they may have been tampered with".to_string()))?;
+
+        Ok(signer_path)
+    }
+
+    /// Trusted key file holding this public key
+    fn trusted_key_path(&self, key: &VerifyingKey) -> Result<Option<PathBuf>, DomainError> {
+        for path in &self.trusted_keys {
+            let pem = fs::read_to_string(path)
+                .map_err(|e| DomainError::ConfigError(format!("Failed to read trusted key {}: {}", path.display(), e)))?;
+            let trusted = VerifyingKey::from_public_key_pem(&pem)
+                .map_err(|e| DomainError::ConfigError(format!("{} is not an ed25519 public key in PEM format: {}", path.display(), e)))?;
+
+            if trusted == *key {
+                return Ok(Some(path.clone()));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Result<Vec<u8>, DomainError> {
+    let entry = archive.by_name(name)
+        .map_err(|_| DomainError::ConfigError(format!("The bundle has no {}; is it signed?", name)))?;
+
+    let mut bytes = Vec::new();
+    entry.take(MAX_ENTRY_BYTES).read_to_end(&mut bytes).map_err(DomainError::IoError)?;
+    Ok(bytes)
+}
+
+fn decode_public_key(encoded: &str) -> Result<VerifyingKey, DomainError> {
+    let bytes: [u8; 32] = BASE64.decode(encoded)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| DomainError::ConfigError("Invalid public key in the signature".to_string()))?;
+
+    VerifyingKey::from_bytes(&bytes)
+        .map_err(|_| DomainError::ConfigError("Invalid public key in the signature".to_string()))
+}
@@ -0,0 +1,497 @@
+use crate::domain::{
+    Profile, ProfileKind, Alias, ConnectionArgs, HistoryEntry, HistoryFormat, HistoryGroup, HistoryGrouping, ProfileRepository,
+    AliasRepository, HistoryRepository, Session, SshService,
+    DomainError, EventBus, Event, Hook, PluginStatus, docker_exec_command, match_template,
+};
+use crate::application::policy::Policy;
+use crate::application::{PluginService, RetryPolicy, SessionTracker, SshCredentialService};
+use crate::utils::{expand_profile, shell_join};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Where an SSH option of a resolved connection comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionSource {
+    /// Set on the profile
+    Profile,
+    /// Required by the organization policy
+    Policy,
+}
+
+impl OptionSource {
+    /// Lowercase name, e.g. for JSON output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OptionSource::Profile => "profile",
+            OptionSource::Policy => "policy",
+        }
+    }
+}
+
+/// What `connect` would use for a name, once aliases and the organization policy are applied
+#[derive(Debug, Clone)]
+pub struct ResolvedConnection {
+    /// Aliases followed to reach the profile, outermost first; template aliases by pattern
+    pub aliases: Vec<String>,
+    /// The profile with the policy applied
+    pub profile: Profile,
+    /// Source of each of the profile's SSH options
+    pub option_sources: BTreeMap<String, OptionSource>,
+    /// Arguments and remote command collected from the aliases
+    pub args: ConnectionArgs,
+    /// Enabled plugins whose hooks run around the connection
+    pub plugins: Vec<String>,
+    /// The ssh command line that would run
+    pub command_line: Vec<String>,
+}
+
+/// ConnectionService manages SSH connections
+pub struct ConnectionService {
+    profile_repository: Arc<dyn ProfileRepository>,
+    alias_repository: Arc<dyn AliasRepository>,
+    history_repository: Arc<dyn HistoryRepository>,
+    ssh_service: Arc<dyn SshService>,
+    event_bus: Arc<EventBus>,
+    plugins: Arc<PluginService>,
+    policy: Arc<Policy>,
+    credentials: Option<Arc<SshCredentialService>>,
+    retry: RetryPolicy,
+    sessions: Option<Arc<SessionTracker>>,
+}
+
+impl ConnectionService {
+    /// Create a new ConnectionService with the provided dependencies
+    pub fn new(
+        profile_repository: Arc<dyn ProfileRepository>,
+        alias_repository: Arc<dyn AliasRepository>,
+        history_repository: Arc<dyn HistoryRepository>,
+        ssh_service: Arc<dyn SshService>,
+        event_bus: Arc<EventBus>,
+        plugins: Arc<PluginService>,
+    ) -> Self {
+        Self {
+            profile_repository,
+            alias_repository,
+            history_repository,
+            ssh_service,
+            event_bus,
+            plugins,
+            policy: Arc::new(Policy::default()),
+            credentials: None,
+            retry: RetryPolicy::default(),
+            sessions: None,
+        }
+    }
+
+    /// Refuse banned hosts and add required SSH options when connecting
+    pub fn set_policy(&mut self, policy: Arc<Policy>) {
+        self.policy = policy;
+    }
+
+    /// Get certificates or one-time passwords for profiles with a Vault role before connecting
+    pub fn set_credentials(&mut self, credentials: Arc<SshCredentialService>) {
+        self.credentials = Some(credentials);
+    }
+
+    /// How connection tests are retried after transient failures such as timeouts
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    /// Give each open connection a session ID that `list_sessions` and `kill_session` see
+    pub fn set_sessions(&mut self, sessions: Arc<SessionTracker>) {
+        self.sessions = Some(sessions);
+    }
+
+    /// Connections open in any shellbe process, oldest first
+    pub fn list_sessions(&self) -> Result<Vec<Session>, DomainError> {
+        match &self.sessions {
+            Some(sessions) => Ok(sessions.list()?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// End an open connection; `None` when no session has that ID
+    pub fn kill_session(&self, id: &str) -> Result<Option<Session>, DomainError> {
+        match &self.sessions {
+            Some(sessions) => sessions.kill(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Run the ssh service, tracking the connection as a session while it is open
+    async fn run_session(&self, name: &str, profile: &Profile, target: &Profile, args: &ConnectionArgs) -> Result<i32, DomainError> {
+        let Some(sessions) = &self.sessions else {
+            return self.ssh_service.connect(target, args, &|_| {}).await;
+        };
+
+        let id = std::sync::Mutex::new(None);
+        let result = self.ssh_service.connect(target, args, &|pid| {
+            *id.lock().unwrap() = Some(sessions.start(name, &profile.target(), pid).id);
+        }).await;
+
+        if let Some(id) = id.into_inner().unwrap() {
+            sessions.end(&id);
+        }
+        result
+    }
+
+    /// Get a profile, with the organization policy applied
+    async fn get_profile(&self, name: String) -> Result<Profile, DomainError> {
+        let mut profile = match self.profile_repository.get(&name).await? {
+            Some(profile) => profile,
+            None => return Err(DomainError::ProfileNotFound(name)),
+        };
+
+        self.policy.apply(&mut profile)?;
+        expand_profile(&mut profile);
+        Ok(profile)
+    }
+
+    /// The profile ssh connects to and the arguments it gets: a Docker profile on another
+    /// profile's host runs `docker exec` there, anything else connects as it is
+    async fn connection_target(&self, profile: &Profile, mut args: ConnectionArgs) -> Result<(Profile, ConnectionArgs), DomainError> {
+        let ProfileKind::Docker { host_profile: Some(host), container } = &profile.kind else {
+            return Ok((profile.clone(), args));
+        };
+
+        let host = self.get_profile(host.clone()).await?;
+        if !host.kind.is_ssh() {
+            return Err(DomainError::ConfigError(format!(
+                "Docker profile '{}' needs an SSH profile as its host, but '{}' isn't one",
+                profile.name, host.name,
+            )));
+        }
+
+        args.command = Some(shell_join(&docker_exec_command(container, &profile.username, args.command.as_deref())));
+        Ok((host, args))
+    }
+
+    /// Execute hook on all enabled plugins, loading them on first use
+    async fn execute_plugins_hook(&self, hook: Hook, profile: Option<&Profile>) -> Result<(), DomainError> {
+        if let Err(e) = self.plugins.execute_hook(hook, profile).await {
+            tracing::warn!("Plugin error in hook {:?}: {}", hook, e);
+        }
+        Ok(())
+    }
+
+    /// Get an alias by name, falling back to a matching template alias
+    async fn lookup_alias(&self, name: &str) -> Result<Option<Alias>, DomainError> {
+        if let Some(alias) = self.alias_repository.get(name).await? {
+            return Ok(Some(alias));
+        }
+
+        Ok(match_template(self.alias_repository.list().await?, name))
+    }
+
+    /// Resolve a name through any alias chain, collecting alias arguments along the way
+    async fn resolve_target(&self, name: &str) -> Result<(String, ConnectionArgs), DomainError> {
+        let (target, chain) = self.resolve_chain(name).await?;
+        Ok((target, alias_args(&chain)))
+    }
+
+    /// Follow a name through aliases to the profile name, returning the aliases on the way
+    async fn resolve_chain(&self, name: &str) -> Result<(String, Vec<Alias>), DomainError> {
+        let mut visited = HashSet::new();
+        let mut chain = Vec::new();
+        let mut current = name.to_string();
+
+        while let Some(alias) = self.lookup_alias(&current).await? {
+            // Template aliases are tracked by pattern so they only expand once
+            if !visited.insert(alias.name.clone()) {
+                return Err(DomainError::ConfigError(
+                    format!("Circular alias reference detected: {} -> {}", current, alias.target)
+                ));
+            }
+
+            current = alias.target.clone();
+            chain.push(alias);
+        }
+
+        Ok((current, chain))
+    }
+
+    /// Everything a connection to a profile or alias would use, without connecting
+    ///
+    /// Short-lived credentials aren't fetched, so a Vault certificate or one-time password
+    /// isn't part of the command line.
+    pub async fn resolve(&self, name: &str) -> Result<ResolvedConnection, DomainError> {
+        let (profile_name, chain) = self.resolve_chain(name).await?;
+        let args = alias_args(&chain);
+
+        let stored = self.profile_repository.get(&profile_name).await?
+            .ok_or_else(|| DomainError::ProfileNotFound(profile_name.clone()))?;
+        let profile = self.get_profile(profile_name).await?;
+
+        let option_sources = profile.options.keys()
+            .map(|key| {
+                let source = if stored.options.contains_key(key) { OptionSource::Profile } else { OptionSource::Policy };
+                (key.clone(), source)
+            })
+            .collect();
+
+        let plugins = match self.plugins.list_plugins().await {
+            Ok(plugins) => plugins.into_iter()
+                .filter(|plugin| plugin.status == PluginStatus::Enabled)
+                .map(|plugin| plugin.info.name)
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to list plugins: {}", e);
+                Vec::new()
+            },
+        };
+
+        let (target, target_args) = self.connection_target(&profile, args.clone()).await?;
+        let command_line = self.ssh_service.command_line(&target, &target_args);
+
+        Ok(ResolvedConnection {
+            aliases: chain.into_iter().map(|alias| alias.name).collect(),
+            profile,
+            option_sources,
+            args,
+            plugins,
+            command_line,
+        })
+    }
+
+    /// Connect to a profile or alias
+    pub async fn connect(&self, name: &str) -> Result<i32, DomainError> {
+        // Resolve aliases and any arguments they carry
+        let (profile_name, mut args) = self.resolve_target(name).await?;
+
+        // Get the profile, and the host profile of a container
+        let mut profile = self.get_profile(profile_name).await?;
+        let (target, mut args) = self.connection_target(&profile, args).await?;
+
+        // Short-lived credentials, fetched before anything counts as a connection
+        if let Some(credentials) = &self.credentials {
+            credentials.prepare(&target, &mut args).await?;
+        }
+
+        // Create a history entry
+        let mut entry = HistoryEntry::new(&profile.name, &profile.hostname);
+
+        // Publish connection started event
+        self.event_bus.publish(Event::ConnectionStarted(profile.clone()));
+
+        // Run pre-connect plugin hooks
+        self.execute_plugins_hook(Hook::PreConnect, Some(&profile)).await?;
+
+        // Connect and measure time
+        let start = Instant::now();
+        let exit_code = match self.run_session(&profile.name, &profile, &target, &args).await {
+            Ok(code) => code,
+            Err(e) => {
+                // Run appropriate plugin hooks for failure
+                self.execute_plugins_hook(Hook::TestFailure, Some(&profile)).await?;
+                return Err(e);
+            }
+        };
+        let duration = start.elapsed();
+
+        // Update history entry with result
+        entry = entry.with_result(exit_code, duration);
+
+        // Update profile last used time
+        profile.mark_as_used();
+        self.profile_repository.update(profile.clone()).await?;
+
+        // Save history
+        self.history_repository.add(entry.clone()).await?;
+
+        // Run post-connect plugin hooks
+        self.execute_plugins_hook(Hook::PostDisconnect, Some(&profile)).await?;
+
+        // Publish connection ended event
+        self.event_bus.publish(Event::ConnectionEnded(entry));
+
+        Ok(exit_code)
+    }
+
+    /// Connect to a profile that isn't saved, e.g. one parsed from `user@host`
+    ///
+    /// History is recorded under the connection string since there is no profile name.
+    pub async fn connect_ad_hoc(&self, profile: &Profile, args: &ConnectionArgs) -> Result<i32, DomainError> {
+        let mut profile = profile.clone();
+        self.policy.apply(&mut profile)?;
+        expand_profile(&mut profile);
+        let profile = &profile;
+
+        let mut entry = HistoryEntry::new(profile.connection_string(), &profile.hostname);
+
+        self.event_bus.publish(Event::ConnectionStarted(profile.clone()));
+        self.execute_plugins_hook(Hook::PreConnect, Some(profile)).await?;
+
+        let start = Instant::now();
+        let exit_code = match self.run_session(&profile.connection_string(), profile, profile, args).await {
+            Ok(code) => code,
+            Err(e) => {
+                self.execute_plugins_hook(Hook::TestFailure, Some(profile)).await?;
+                return Err(e);
+            }
+        };
+        entry = entry.with_result(exit_code, start.elapsed());
+
+        self.history_repository.add(entry.clone()).await?;
+        self.execute_plugins_hook(Hook::PostDisconnect, Some(profile)).await?;
+        self.event_bus.publish(Event::ConnectionEnded(entry));
+
+        Ok(exit_code)
+    }
+
+    /// Get the ssh command line used to connect to a profile or alias
+    pub async fn ssh_command(&self, name: &str) -> Result<Vec<String>, DomainError> {
+        let (profile_name, args) = self.resolve_target(name).await?;
+
+        let profile = self.get_profile(profile_name).await?;
+        let (target, args) = self.connection_target(&profile, args).await?;
+
+        Ok(self.ssh_service.command_line(&target, &args))
+    }
+
+    /// Test connection to a profile or alias; the address that answered, if any did
+    pub async fn test_connection(&self, name: &str) -> Result<Option<SocketAddr>, DomainError> {
+        // Resolve aliases
+        let (profile_name, _) = self.resolve_target(name).await?;
+
+        // Get the profile; a container's host is what there is to test
+        let profile = self.get_profile(profile_name).await?;
+        let (target, _) = self.connection_target(&profile, ConnectionArgs::default()).await?;
+
+        // Test the connection, again if it timed out
+        let result = self.retry.run(&format!("Connection test for {}", profile.name), || {
+            self.ssh_service.test_connection(&target)
+        }).await?;
+
+        // Run appropriate plugin hooks based on result
+        let hook = if result.is_some() {
+            Hook::TestSuccess
+        } else {
+            Hook::TestFailure
+        };
+
+        self.execute_plugins_hook(hook, Some(&profile)).await?;
+
+        Ok(result)
+    }
+
+    /// Copy SSH key to a remote server
+    pub async fn copy_ssh_key(&self, name: &str, key_path: &std::path::Path) -> Result<(), DomainError> {
+        // Resolve aliases
+        let (profile_name, _) = self.resolve_target(name).await?;
+
+        // Get the profile; for a container, the key goes to its host
+        let profile = self.get_profile(profile_name).await?;
+        let (target, _) = self.connection_target(&profile, ConnectionArgs::default()).await?;
+
+        // Copy the key
+        self.ssh_service.copy_key(&target, key_path).await?;
+
+        self.event_bus.publish(Event::KeyCopied {
+            profile: profile.name,
+            key: key_path.to_path_buf(),
+        });
+
+        Ok(())
+    }
+
+    /// Generate a new SSH key pair in ~/.ssh, returning the private and public key paths
+    pub async fn generate_key(&self, name: &str, key_type: &str, comment: Option<&str>) -> Result<(std::path::PathBuf, std::path::PathBuf), DomainError> {
+        let (private_key, public_key) = self.ssh_service.generate_key(name, key_type, comment).await?;
+
+        self.event_bus.publish(Event::KeyGenerated {
+            name: name.to_string(),
+            public_key: public_key.clone(),
+        });
+
+        Ok((private_key, public_key))
+    }
+
+    /// Get recent connection history
+    pub async fn get_recent_history(&self, limit: usize) -> Result<Vec<HistoryEntry>, DomainError> {
+        self.history_repository.get_recent(limit).await
+    }
+
+    /// Get connection history for a specific profile
+    pub async fn get_profile_history(&self, profile_name: &str) -> Result<Vec<HistoryEntry>, DomainError> {
+        // Check if profile exists
+        if !self.profile_repository.exists(profile_name).await? {
+            return Err(DomainError::ProfileNotFound(profile_name.to_string()));
+        }
+
+        self.history_repository.get_for_profile(profile_name).await
+    }
+
+    /// Drop history entries older than the given number of days
+    pub async fn prune_history(&self, retention_days: u32) -> Result<usize, DomainError> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(i64::from(retention_days));
+        self.history_repository.prune_before(cutoff).await
+    }
+
+    /// Store the history in another format from now on
+    pub async fn convert_history(&self, format: HistoryFormat) -> Result<(), DomainError> {
+        self.history_repository.convert(format).await
+    }
+
+    /// Get the latest history entry for each of the last `limit` distinct profiles, most recent first
+    pub async fn get_recent_profiles(&self, limit: usize) -> Result<Vec<HistoryEntry>, DomainError> {
+        let history = self.history_repository.list().await?;
+        let mut seen = HashSet::new();
+
+        Ok(history.into_iter()
+            .rev()
+            .filter(|entry| seen.insert(entry.profile_name.clone()))
+            .take(limit)
+            .collect())
+    }
+
+    /// Get connection statistics
+    pub async fn get_connection_stats(&self) -> Result<Vec<(String, usize)>, DomainError> {
+        let stats = self.history_repository.get_stats().await?;
+
+        // Convert HashMap to Vec of tuples
+        let mut stats_vec: Vec<(String, usize)> = stats.into_iter().collect();
+
+        // Sort by count in descending order
+        stats_vec.sort_by(|a, b| b.1.cmp(&a.1));
+
+        Ok(stats_vec)
+    }
+
+    /// Get connection history aggregated by profile or by day, most recent group first
+    pub async fn get_grouped_history(&self, grouping: HistoryGrouping) -> Result<Vec<HistoryGroup>, DomainError> {
+        let history = self.history_repository.list().await?;
+        let mut groups: HashMap<String, HistoryGroup> = HashMap::new();
+
+        for entry in &history {
+            let key = match grouping {
+                HistoryGrouping::Profile => entry.profile_name.clone(),
+                HistoryGrouping::Day => entry.timestamp.format("%Y-%m-%d").to_string(),
+            };
+
+            groups.entry(key.clone())
+                .or_insert_with(|| HistoryGroup::new(key, entry.timestamp))
+                .add(entry);
+        }
+
+        let mut groups: Vec<HistoryGroup> = groups.into_values().collect();
+        groups.sort_by(|a, b| b.last_connection.cmp(&a.last_connection));
+
+        Ok(groups)
+    }
+}
+
+/// Arguments of an alias chain, outermost first
+fn alias_args(chain: &[Alias]) -> ConnectionArgs {
+    // Apply the innermost alias first so outer aliases can extend it
+    let mut args = ConnectionArgs::default();
+    for alias in chain.iter().rev() {
+        args.ssh_args.extend(alias.args.iter().cloned());
+        if alias.command.is_some() {
+            args.command = alias.command.clone();
+        }
+    }
+    args
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use crate::application::ImportChange;
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery, Profile};
+use crate::utils::write_private;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// File in the data directory remembering which discovered host each profile was made for
+pub const DISCOVERED_HOSTS_FILE: &str = "discovered_hosts.json";
+
+/// A profile proposed for a discovered host
+#[derive(Debug, Clone)]
+pub struct DiscoveredProfile {
+    /// Stable id of the host at its source, e.g. `aws:i-0abc...`
+    pub host_id: String,
+    /// The profile to add, or the local profile with the host's new address
+    pub profile: Profile,
+    /// `New` for unknown hosts; `Updated` or `Unchanged` for hosts discovered before
+    pub change: ImportChange,
+}
+
+/// Proposes profiles for hosts found by a discovery source, and remembers which
+/// profiles came from which host so their addresses can be refreshed later
+pub struct DiscoveryService {
+    discovery: Arc<dyn HostDiscovery>,
+    hosts_path: PathBuf,
+}
+
+impl DiscoveryService {
+    /// Propose profiles for what `discovery` finds, tracking them in the given data directory
+    pub fn new(discovery: Arc<dyn HostDiscovery>, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            discovery,
+            hosts_path: data_dir.into().join(DISCOVERED_HOSTS_FILE),
+        }
+    }
+
+    /// Name of the discovery source, for messages
+    pub fn source(&self) -> &str {
+        self.discovery.name()
+    }
+
+    /// Profiles for found hosts that no local profile points at yet, plus the local
+    /// profiles of hosts discovered before, updated when their address changed
+    ///
+    /// Names come from the hosts and get a `-2`, `-3`, ... suffix when already taken.
+    /// The username is `user`, else the one the source reports, else the current user.
+    pub async fn propose(&self, local: &[Profile], user: Option<&str>, tags: &[String]) -> Result<Vec<DiscoveredProfile>, DomainError> {
+        let hosts = self.discovery.discover().await?;
+        tracing::debug!("{} found {} hosts", self.source(), hosts.len());
+
+        let recorded = self.load()?;
+        let mut names: HashSet<String> = local.iter().map(|p| p.name.clone()).collect();
+        let mut proposals: Vec<DiscoveredProfile> = Vec::new();
+
+        for host in hosts {
+            let previous = recorded.get(&host.id)
+                .and_then(|name| local.iter().find(|p| &p.name == name));
+            if let Some(existing) = previous {
+                let mut profile = existing.clone();
+                let change = if profile.hostname == host.hostname && profile.port == host.port {
+                    ImportChange::Unchanged
+                } else {
+                    profile.hostname = host.hostname;
+                    profile.port = host.port;
+                    ImportChange::Updated
+                };
+                proposals.push(DiscoveredProfile { host_id: host.id, profile, change });
+                continue;
+            }
+
+            let known = local.iter().chain(proposals.iter().map(|p| &p.profile))
+                .any(|p| p.hostname.eq_ignore_ascii_case(&host.hostname) && p.port == host.port);
+            if known {
+                continue;
+            }
+
+            let name = unique_name(&profile_name(&host), &names);
+            names.insert(name.clone());
+
+            let username = user.map(str::to_string)
+                .or_else(|| host.username.clone())
+                .unwrap_or_else(whoami::username);
+
+            let mut profile = Profile::new(name, host.hostname, username);
+            profile.port = host.port;
+            profile.identity_file = host.identity_file;
+            profile.tags = host.tags;
+            for tag in tags {
+                if !profile.tags.contains(tag) {
+                    profile.tags.push(tag.clone());
+                }
+            }
+
+            proposals.push(DiscoveredProfile { host_id: host.id, profile, change: ImportChange::New });
+        }
+
+        Ok(proposals)
+    }
+
+    /// Remember that these profiles were made for these discovered hosts
+    pub fn record(&self, added: &[&DiscoveredProfile]) -> Result<(), DomainError> {
+        if added.is_empty() {
+            return Ok(());
+        }
+
+        let mut hosts = self.load()?;
+        for proposal in added {
+            hosts.insert(proposal.host_id.clone(), proposal.profile.name.clone());
+        }
+
+        let content = serde_json::to_vec_pretty(&hosts)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize {}: {}", DISCOVERED_HOSTS_FILE, e)))?;
+        write_private(&self.hosts_path, &content).map_err(DomainError::IoError)
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, DomainError> {
+        if !self.hosts_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let content = fs::read(&self.hosts_path).map_err(DomainError::IoError)?;
+        serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to parse {}: {}", DISCOVERED_HOSTS_FILE, e)))
+    }
+}
+
+/// A profile name for a host: its name in lower case, with spaces turned into dashes
+fn profile_name(host: &DiscoveredHost) -> String {
+    let name = host.name.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("-");
+    if name.is_empty() {
+        host.hostname.split('.').next().unwrap_or(&host.hostname).to_string()
+    } else {
+        name
+    }
+}
+
+fn unique_name(name: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(name) {
+        return name.to_string();
+    }
+
+    (2..).map(|n| format!("{}-{}", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
@@ -0,0 +1,459 @@
+use crate::application::plugin_service::{find_plugin_library, library_hash, verify_plugin_library};
+use crate::application::settings_service::{SettingsService, SETTINGS_FILE};
+use crate::domain::{Profile, SshConfigRepository};
+use crate::infrastructure::FileSshConfigRepository;
+use crate::infrastructure::repositories::file_history_repository::{decode_history, is_binary_history};
+use crate::application::vault_service::VAULT_FILE;
+use crate::utils::permissions::{check_private, PermissionIssue, PRIVATE_DIR_MODE, PRIVATE_FILE_MODE};
+use crate::utils::{expand_path, PluginSecurityValidator, ShellBePaths, SystemRequirements};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// JSON files ShellBe keeps in its data directory
+const DATA_FILES: &[&str] = &[
+    "profiles.json",
+    "aliases.json",
+    "history.json",
+    "plugins.json",
+    "shell_aliases.json",
+    "totp.json",
+];
+
+/// Outcome of a single diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// A single diagnostic check and how to fix it
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    /// Area the check belongs to (system, files, ssh-config, plugins)
+    pub category: String,
+    /// What was checked
+    pub name: String,
+    /// Result of the check
+    pub status: CheckStatus,
+    /// Details about the result
+    pub message: String,
+    /// Suggested fix when the check didn't pass
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(category: &str, name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            category: category.to_string(),
+            name: name.into(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        category: &str,
+        status: CheckStatus,
+        name: impl Into<String>,
+        message: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            category: category.to_string(),
+            name: name.into(),
+            status,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Result of all diagnostic checks
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Check if no check failed with an error
+    pub fn is_healthy(&self) -> bool {
+        !self.checks.iter().any(|c| c.status == CheckStatus::Error)
+    }
+
+    /// Number of checks with the given status
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+}
+
+/// Diagnoses a ShellBe installation without depending on it being in a working state
+pub struct DoctorService {
+    paths: ShellBePaths,
+    ssh_config_path: PathBuf,
+    system_requirements: SystemRequirements,
+    security_validator: PluginSecurityValidator,
+}
+
+impl DoctorService {
+    /// Create a new DoctorService for the given directories
+    pub fn new(paths: ShellBePaths, ssh_config_path: impl Into<PathBuf>) -> Self {
+        Self {
+            paths,
+            ssh_config_path: ssh_config_path.into(),
+            system_requirements: SystemRequirements::default(),
+            security_validator: PluginSecurityValidator::default(),
+        }
+    }
+
+    /// Run every check
+    pub async fn run(&self) -> DoctorReport {
+        let mut checks = Vec::new();
+
+        self.check_system(&mut checks);
+        self.check_ssh_client(&mut checks);
+        self.check_files(&mut checks);
+        self.check_identity_files(&mut checks);
+        self.check_ssh_config(&mut checks).await;
+        self.check_plugins(&mut checks);
+
+        DoctorReport { checks }
+    }
+
+    /// Private files and directories, with the mode each should have
+    ///
+    /// Covers the data and config directories, the data files and the vault header, and the
+    /// given identity files.
+    pub fn private_paths(&self, identity_files: &[PathBuf]) -> Vec<(PathBuf, u32)> {
+        let mut paths = vec![(self.paths.data_dir.clone(), PRIVATE_DIR_MODE)];
+        if self.paths.config_dir != self.paths.data_dir {
+            paths.push((self.paths.config_dir.clone(), PRIVATE_DIR_MODE));
+        }
+
+        for file in DATA_FILES.iter().chain([&VAULT_FILE]) {
+            paths.push((self.paths.data_dir.join(file), PRIVATE_FILE_MODE));
+        }
+
+        for identity_file in identity_files {
+            paths.push((identity_file.clone(), PRIVATE_FILE_MODE));
+        }
+
+        paths
+    }
+
+    /// Private paths that other users can access or that belong to another user
+    pub fn permission_issues(&self, identity_files: &[PathBuf]) -> Vec<PermissionIssue> {
+        self.private_paths(identity_files)
+            .into_iter()
+            .filter_map(|(path, mode)| check_private(&path, mode).ok().flatten())
+            .collect()
+    }
+
+    /// Correct the permissions of private paths, including identity files of saved profiles
+    ///
+    /// Returns every issue found and whether fixing it worked.
+    pub fn fix_permissions(&self) -> Vec<(PermissionIssue, Result<(), String>)> {
+        self.permission_issues(&self.identity_files())
+            .into_iter()
+            .map(|issue| {
+                let result = issue.fix().map_err(|e| e.to_string());
+                (issue, result)
+            })
+            .collect()
+    }
+
+    /// Identity files of saved profiles; read directly, so encrypted profiles are skipped
+    fn identity_files(&self) -> Vec<PathBuf> {
+        let profiles: HashMap<String, Profile> = std::fs::read_to_string(self.paths.data_dir.join("profiles.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut identity_files: Vec<PathBuf> = profiles.into_values()
+            .filter_map(|profile| profile.identity_file)
+            .map(expand_path)
+            .collect();
+        identity_files.sort();
+        identity_files.dedup();
+        identity_files
+    }
+
+    /// System commands, libraries and disk space
+    fn check_system(&self, checks: &mut Vec<DoctorCheck>) {
+        let mut results: Vec<_> = self.system_requirements.check_all().into_iter().collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (name, result) in results {
+            checks.push(match result {
+                Ok(()) => DoctorCheck::ok("system", name, "available"),
+                Err(e) => {
+                    let fix = match name.split_once(':') {
+                        Some(("command", command)) => format!("Install '{}' and make sure it is in PATH", command),
+                        Some(("library", library)) => format!("Install the '{}' library", library),
+                        _ => "Free up disk space in your home directory".to_string(),
+                    };
+                    // Commands like git are only needed by some features
+                    let status = match name.split_once(':') {
+                        Some(("command", command)) if self.system_requirements.is_optional_command(command) => CheckStatus::Warning,
+                        _ => CheckStatus::Error,
+                    };
+                    DoctorCheck::problem("system", status, name, e.to_string(), fix)
+                },
+            });
+        }
+    }
+
+    /// OpenSSH client version against `ssh.min_client_version` and known advisories
+    fn check_ssh_client(&self, checks: &mut Vec<DoctorCheck>) {
+        let version = match self.system_requirements.ssh_client_version() {
+            Ok(version) => version,
+            Err(e) => {
+                checks.push(DoctorCheck::problem(
+                    "system",
+                    CheckStatus::Warning,
+                    "ssh-client",
+                    e.to_string(),
+                    "Install the OpenSSH client; ShellBe runs it for interactive sessions",
+                ));
+                return;
+            },
+        };
+
+        // Settings are read here since doctor runs before they are loaded
+        let mut requirements = SystemRequirements::default();
+        requirements.set_min_ssh_version(
+            SettingsService::load(self.paths.config_dir.join(SETTINGS_FILE))
+                .ok()
+                .and_then(|settings| settings.settings().ssh.min_client_version.clone())
+                .and_then(|version| version.parse().ok()),
+        );
+
+        let issues = requirements.ssh_client_issues(&version);
+        if issues.is_empty() {
+            checks.push(DoctorCheck::ok("system", "ssh-client", format!("OpenSSH {}", version)));
+            return;
+        }
+
+        for issue in issues {
+            checks.push(DoctorCheck::problem(
+                "system",
+                CheckStatus::Warning,
+                "ssh-client",
+                issue.message,
+                format!(
+                    "Upgrade the OpenSSH client to {} or later, unless your distribution backported the fix",
+                    issue.upgrade_to,
+                ),
+            ));
+        }
+    }
+
+    /// Directory and file permissions, and JSON integrity
+    fn check_files(&self, checks: &mut Vec<DoctorCheck>) {
+        let mut dirs = vec![&self.paths.data_dir];
+        if self.paths.config_dir != self.paths.data_dir {
+            dirs.push(&self.paths.config_dir);
+        }
+
+        for dir in dirs {
+            let name = dir.display().to_string();
+
+            if !dir.exists() {
+                checks.push(DoctorCheck::ok("files", name, "not created yet"));
+                continue;
+            }
+
+            checks.push(check_permissions("files", dir, PRIVATE_DIR_MODE));
+        }
+
+        for file in DATA_FILES {
+            let path = self.paths.data_dir.join(file);
+            if !path.exists() {
+                continue;
+            }
+
+            checks.push(check_permissions("files", &path, PRIVATE_FILE_MODE));
+
+            let result = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| match *file {
+                    // History may be stored in the binary format
+                    "history.json" if is_binary_history(&content) => decode_history(&content)
+                        .map(|_| "valid binary history")
+                        .map_err(|e| e.to_string()),
+                    _ => serde_json::from_slice::<serde_json::Value>(&content)
+                        .map(|_| "valid JSON")
+                        .map_err(|e| e.to_string()),
+                });
+
+            checks.push(match result {
+                Ok(detail) => DoctorCheck::ok("files", format!("{} integrity", file), detail),
+                Err(e) => DoctorCheck::problem(
+                    "files",
+                    CheckStatus::Error,
+                    format!("{} integrity", file),
+                    e,
+                    format!("Restore {} from a backup, or move it aside to start fresh", path.display()),
+                ),
+            });
+        }
+
+        let settings_path = self.paths.config_dir.join(SETTINGS_FILE);
+        if settings_path.exists() {
+            checks.push(match SettingsService::load(&settings_path) {
+                Ok(_) => DoctorCheck::ok("files", SETTINGS_FILE, "valid settings"),
+                Err(e) => DoctorCheck::problem(
+                    "files",
+                    CheckStatus::Error,
+                    SETTINGS_FILE,
+                    e.to_string(),
+                    format!("Fix or remove {} to use the defaults", settings_path.display()),
+                ),
+            });
+        }
+    }
+
+    /// Identity files used by profiles exist and are private, as OpenSSH requires
+    fn check_identity_files(&self, checks: &mut Vec<DoctorCheck>) {
+        for identity_file in self.identity_files() {
+            if !identity_file.exists() {
+                checks.push(DoctorCheck::problem(
+                    "keys",
+                    CheckStatus::Warning,
+                    identity_file.display().to_string(),
+                    "identity file not found",
+                    "Generate it with 'shellbe generate-key' or edit the profiles that use it",
+                ));
+                continue;
+            }
+
+            checks.push(check_permissions("keys", &identity_file, PRIVATE_FILE_MODE));
+        }
+    }
+
+    /// Profiles that disagree with their SSH config entries
+    async fn check_ssh_config(&self, checks: &mut Vec<DoctorCheck>) {
+        let name = self.ssh_config_path.display().to_string();
+
+        if !self.ssh_config_path.exists() {
+            checks.push(DoctorCheck::ok("ssh-config", name, "no SSH config file"));
+            return;
+        }
+
+        let ssh_profiles = match FileSshConfigRepository::new(&self.ssh_config_path).import().await {
+            Ok(profiles) => profiles,
+            Err(e) => {
+                checks.push(DoctorCheck::problem(
+                    "ssh-config",
+                    CheckStatus::Error,
+                    name,
+                    e.to_string(),
+                    "Fix the syntax error in your SSH config",
+                ));
+                return;
+            },
+        };
+
+        // Read profiles directly so a broken repository doesn't hide the comparison
+        let profiles: HashMap<String, Profile> = std::fs::read_to_string(self.paths.data_dir.join("profiles.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut mismatches = Vec::new();
+        for ssh_profile in &ssh_profiles {
+            if let Some(profile) = profiles.get(&ssh_profile.name) {
+                if profile.hostname != ssh_profile.hostname
+                    || profile.username != ssh_profile.username
+                    || profile.port != ssh_profile.port
+                {
+                    mismatches.push(profile.name.clone());
+                }
+            }
+        }
+
+        if mismatches.is_empty() {
+            checks.push(DoctorCheck::ok("ssh-config", name, "consistent with profiles"));
+        } else {
+            mismatches.sort();
+            checks.push(DoctorCheck::problem(
+                "ssh-config",
+                CheckStatus::Warning,
+                name,
+                format!("Profiles differ from SSH config: {}", mismatches.join(", ")),
+                "Run 'shellbe export' to update the SSH config, or 'shellbe import --replace' to update the profiles",
+            ));
+        }
+    }
+
+    /// Plugin libraries can be found, pass validation and expose their entry point
+    fn check_plugins(&self, checks: &mut Vec<DoctorCheck>) {
+        let path = self.paths.data_dir.join("plugins.json");
+
+        let plugins: Vec<serde_json::Value> = match std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(plugins) => plugins,
+            // Missing or broken plugins.json is reported by the file checks
+            None => return,
+        };
+
+        for plugin in plugins {
+            let name = plugin["name"].as_str().unwrap_or("<unnamed>").to_string();
+            let plugin_dir = match plugin["path"].as_str() {
+                Some(dir) => PathBuf::from(dir),
+                None => continue,
+            };
+
+            // A changed library isn't opened, since loading it would run its code
+            let expected_hash = plugin["library_hash"].as_str();
+            let actual_hash = find_plugin_library(&plugin_dir).and_then(|lib| library_hash(&lib)).ok();
+            if let (Some(expected), Some(actual)) = (expected_hash, &actual_hash) {
+                if expected != actual {
+                    checks.push(DoctorCheck::problem(
+                        "plugins",
+                        CheckStatus::Error,
+                        name.clone(),
+                        "library changed since it was installed; the plugin is quarantined",
+                        format!("Reinstall with 'shellbe plugin update {}' or remove it with 'shellbe plugin remove {}'", name, name),
+                    ));
+                    continue;
+                }
+            }
+
+            checks.push(match verify_plugin_library(&plugin_dir, &self.security_validator) {
+                Ok(lib) => DoctorCheck::ok("plugins", name, format!("loads from {}", lib.display())),
+                Err(e) => DoctorCheck::problem(
+                    "plugins",
+                    CheckStatus::Error,
+                    name.clone(),
+                    e.to_string(),
+                    format!("Reinstall with 'shellbe plugin update {}' or remove it with 'shellbe plugin remove {}'", name, name),
+                ),
+            });
+        }
+    }
+}
+
+/// Warn when a path is accessible to other users or owned by someone else
+fn check_permissions(category: &str, path: &Path, expected: u32) -> DoctorCheck {
+    let name = format!("{} permissions", path.display());
+
+    match check_private(path, expected) {
+        Ok(None) => DoctorCheck::ok(category, name, "private"),
+        Ok(Some(issue)) => {
+            let fix = if issue.is_fixable() {
+                format!("Run 'shellbe doctor --fix', or: {}", issue.fix_command())
+            } else {
+                issue.fix_command()
+            };
+            DoctorCheck::problem(category, CheckStatus::Warning, name, issue.to_string(), fix)
+        },
+        Err(e) => DoctorCheck::problem(category, CheckStatus::Error, name, e.to_string(), "Check that the path is readable"),
+    }
+}
@@ -0,0 +1,49 @@
+pub mod profile_service;
+pub mod connection_service;
+pub mod alias_service;
+pub mod plugin_service;
+pub mod plugin_sandbox;
+pub mod ssh_config_service;
+pub mod update_service;
+pub mod doctor_service;
+pub mod settings_service;
+pub mod progress;
+pub mod vault_service;
+pub mod vault_session;
+pub mod totp_service;
+pub mod bundle_service;
+pub mod policy;
+pub mod sync_service;
+pub mod remote_import_service;
+pub mod ssh_credential_service;
+pub mod discovery_service;
+pub mod task_pool;
+pub mod retry;
+pub mod telemetry_service;
+pub mod session_tracker;
+
+// Re-export application services
+pub use profile_service::ProfileService;
+pub use connection_service::{ConnectionService, ResolvedConnection, OptionSource};
+pub use alias_service::AliasService;
+pub use plugin_service::{PluginService, PluginError, PluginSandboxSettings, PluginSources};
+pub use plugin_sandbox::PluginSandbox;
+pub use ssh_config_service::SshConfigService;
+pub use update_service::{AvailableUpdate, ReleaseNotes, UpdateService, UpdateError, UpdateNotifier, UpdateSource, UPDATE_CHECK_FILE, UPDATE_CHECK_GRACE};
+pub use doctor_service::{DoctorService, DoctorReport, DoctorCheck, CheckStatus};
+pub use settings_service::{SettingsService, Settings, SshSettings, BundleSettings, PluginSettings, SyncSettings, NetworkSettings, DaemonSettings, EventSettings, VaultSshSettings, VaultSshMode, TelemetrySettings, UpdateCheckFrequency, UpdateChannel, SETTINGS_FILE};
+pub use progress::{Progress, ProgressStep, NoProgress};
+pub use vault_service::{VaultService, VaultSecret, VaultStatus, VAULT_PASSPHRASE_ENV};
+pub use vault_session::VaultSession;
+pub use totp_service::{TotpService, TotpCode, TOTP_FILE};
+pub use bundle_service::{BundleService, BundleManifest, VerifiedBundle, BUNDLE_EXTENSION};
+pub use policy::{Policy, POLICY_FILE_ENV, DEFAULT_POLICY_FILE};
+pub use sync_service::{SyncService, SyncStatus, SYNC_PASSPHRASE_ENV, SYNC_DATA_FILES};
+pub use remote_import_service::{RemoteImportService, RemoteProfileSet, ImportChange, REMOTE_IMPORTS_FILE, SIGNATURE_SUFFIX};
+pub use ssh_credential_service::{SshCredentialService, certificate_valid_before};
+pub use discovery_service::{DiscoveryService, DiscoveredProfile, DISCOVERED_HOSTS_FILE};
+pub use task_pool::{TaskPool, TaskPoolCanceller, PoolOutcome, DEFAULT_CONCURRENCY};
+
+pub use retry::{Retryable, RetryPolicy, DEFAULT_RETRY_ATTEMPTS, DEFAULT_RETRY_BACKOFF};
+pub use session_tracker::{SessionTracker, SESSIONS_DIR};
+pub use telemetry_service::{TelemetryService, TelemetryReport, TELEMETRY_FILE, TELEMETRY_SEND_GRACE, DEFAULT_TELEMETRY_ENDPOINT, DO_NOT_TRACK_ENV};
\ No newline at end of file
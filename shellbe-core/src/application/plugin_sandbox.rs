@@ -0,0 +1,256 @@
+use crate::application::plugin_service::PluginSandboxSettings;
+use crate::errors::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// System directories a plugin process may read even without file system access
+#[cfg(target_os = "linux")]
+const SYSTEM_READ_PATHS: &[&str] = &["/usr", "/lib", "/lib64", "/bin", "/etc", "/dev/null", "/dev/urandom", "/proc/self"];
+
+/// Restrictions applied to a plugin that runs as a separate process
+///
+/// On Linux the process is confined with Landlock (which paths it can read and write)
+/// and seccomp (no IPv4 or IPv6 sockets unless network access is allowed), and its
+/// environment and address space are limited. Elsewhere only the environment and memory
+/// limits apply. Plugins loaded as shared libraries run inside ShellBe and can't be sandboxed.
+#[derive(Debug, Clone)]
+pub struct PluginSandbox {
+    settings: PluginSandboxSettings,
+    plugin_dir: PathBuf,
+}
+
+impl PluginSandbox {
+    /// Sandbox for a plugin installed in `plugin_dir`
+    pub fn new(settings: PluginSandboxSettings, plugin_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            settings,
+            plugin_dir: plugin_dir.into(),
+        }
+    }
+
+    /// Whether processes are confined beyond environment and memory limits on this platform
+    pub fn is_supported() -> bool {
+        cfg!(target_os = "linux")
+    }
+
+    /// Build a command for `program` that applies the sandbox when spawned
+    pub fn command(&self, program: &Path) -> Result<Command> {
+        let mut command = Command::new(program);
+        command.current_dir(&self.plugin_dir);
+
+        // Only pass through the allowed environment
+        command.env_clear();
+        for name in &self.settings.allowed_env_vars {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        linux::confine(&mut command, &self.settings, &self.plugin_dir, program)?;
+
+        #[cfg(all(unix, not(target_os = "linux")))]
+        limit_memory(&mut command, self.settings.max_memory_bytes);
+
+        Ok(command)
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn limit_memory(command: &mut Command, max_memory_bytes: Option<usize>) {
+    use std::os::unix::process::CommandExt;
+
+    if let Some(bytes) = max_memory_bytes {
+        unsafe {
+            command.pre_exec(move || set_memory_limit(bytes));
+        }
+    }
+}
+
+/// Cap the address space of the current process
+#[cfg(unix)]
+fn set_memory_limit(bytes: usize) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: bytes as libc::rlim_t,
+        rlim_max: bytes as libc::rlim_t,
+    };
+
+    if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{set_memory_limit, SYSTEM_READ_PATHS};
+    use crate::application::plugin_service::PluginSandboxSettings;
+    use crate::errors::{Result, ShellBeError};
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreated,
+        RulesetCreatedAttr, RulesetStatus, ABI,
+    };
+    use seccompiler::{
+        BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+        SeccompRule,
+    };
+    use std::collections::BTreeMap;
+    use std::os::unix::process::CommandExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::sync::Mutex;
+
+    /// Newest Landlock ABI used; older kernels enforce what they support
+    const LANDLOCK_ABI: ABI = ABI::V2;
+
+    /// Set up Landlock and seccomp in the parent, and apply them in the child before exec
+    pub(super) fn confine(
+        command: &mut Command,
+        settings: &PluginSandboxSettings,
+        plugin_dir: &Path,
+        program: &Path,
+    ) -> Result<()> {
+        let ruleset = Mutex::new(Some(path_ruleset(settings, plugin_dir, program)?));
+        let network_filter = if settings.allow_network_access {
+            None
+        } else {
+            Some(network_filter()?)
+        };
+        let max_memory_bytes = settings.max_memory_bytes;
+
+        // Everything is prepared above so the child only makes system calls after fork
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(bytes) = max_memory_bytes {
+                    set_memory_limit(bytes)?;
+                }
+
+                if let Some(ruleset) = ruleset.lock().ok().and_then(|mut ruleset| ruleset.take()) {
+                    let status = ruleset.restrict_self()
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e))?;
+                    // Only the errno reaches the parent, so spawning fails as Unsupported
+                    if status.ruleset == RulesetStatus::NotEnforced {
+                        return Err(std::io::Error::from_raw_os_error(libc::ENOSYS));
+                    }
+                }
+
+                if let Some(filter) = &network_filter {
+                    seccompiler::apply_filter(filter)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e))?;
+                }
+
+                Ok(())
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Read access to system directories (or everything with file system access), and
+    /// write access to the plugin's own directory and the temp directory
+    fn path_ruleset(settings: &PluginSandboxSettings, plugin_dir: &Path, program: &Path) -> Result<RulesetCreated> {
+        let sandbox_error = |e: landlock::RulesetError| {
+            ShellBeError::Security(format!("Failed to set up the plugin sandbox: {}", e))
+        };
+
+        let mut readable: Vec<PathBuf> = if settings.allow_fs_access {
+            vec![PathBuf::from("/")]
+        } else {
+            SYSTEM_READ_PATHS.iter().map(PathBuf::from).collect()
+        };
+        readable.push(program.to_path_buf());
+        readable.extend(settings.allowed_executables.iter().cloned());
+
+        let writable = vec![plugin_dir.to_path_buf(), std::env::temp_dir()];
+
+        Ruleset::default()
+            .handle_access(AccessFs::from_all(LANDLOCK_ABI))
+            .map_err(sandbox_error)?
+            .create()
+            .map_err(sandbox_error)?
+            .add_rules(path_beneath_rules(readable.iter().filter(|path| path.exists()), AccessFs::from_read(LANDLOCK_ABI)))
+            .map_err(sandbox_error)?
+            .add_rules(path_beneath_rules(writable.iter().filter(|path| path.exists()), AccessFs::from_all(LANDLOCK_ABI)))
+            .map_err(sandbox_error)
+    }
+
+    /// Seccomp filter refusing IPv4 and IPv6 sockets; Unix sockets keep working
+    fn network_filter() -> Result<BpfProgram> {
+        let sandbox_error = |e: String| {
+            ShellBeError::Security(format!("Failed to set up the plugin network filter: {}", e))
+        };
+
+        let mut rules = Vec::new();
+        for family in [libc::AF_INET, libc::AF_INET6] {
+            let condition = SeccompCondition::new(0, SeccompCmpArgLen::Dword, SeccompCmpOp::Eq, family as u64)
+                .map_err(|e| sandbox_error(e.to_string()))?;
+            rules.push(SeccompRule::new(vec![condition]).map_err(|e| sandbox_error(e.to_string()))?);
+        }
+
+        let filter = SeccompFilter::new(
+            BTreeMap::from([(libc::SYS_socket, rules)]),
+            SeccompAction::Allow,
+            SeccompAction::Errno(libc::EACCES as u32),
+            std::env::consts::ARCH.try_into().map_err(|e: seccompiler::BackendError| sandbox_error(e.to_string()))?,
+        )
+        .map_err(|e| sandbox_error(e.to_string()))?;
+
+        filter.try_into().map_err(|e: seccompiler::BackendError| sandbox_error(e.to_string()))
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// Set in the sandboxed copy of the test binary to run the checks inside the sandbox
+    const CHILD_ENV: &str = "SHELLBE_SANDBOX_TEST_CHILD";
+
+    #[test]
+    fn test_sandboxed_child_has_no_network_or_outside_paths() {
+        let outside = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+
+        if std::env::var_os(CHILD_ENV).is_some() {
+            assert!(std::net::TcpListener::bind("127.0.0.1:0").is_err(), "opened an IPv4 socket");
+            assert!(std::net::TcpListener::bind("[::1]:0").is_err(), "opened an IPv6 socket");
+            assert!(std::fs::read(&outside).is_err(), "read {}", outside.display());
+            return;
+        }
+
+        assert!(std::fs::read(&outside).is_ok());
+
+        let settings = PluginSandboxSettings {
+            allow_fs_access: false,
+            allow_network_access: false,
+            // The test harness reserves more address space than plugins get
+            max_memory_bytes: None,
+            allowed_executables: vec![],
+            allowed_env_vars: vec![],
+        };
+        let plugin_dir = tempfile::tempdir().unwrap();
+        let test_name = concat!(module_path!(), "::test_sandboxed_child_has_no_network_or_outside_paths");
+
+        let mut command = PluginSandbox::new(settings, plugin_dir.path())
+            .command(&std::env::current_exe().unwrap())
+            .unwrap();
+        command.env(CHILD_ENV, "1")
+            .args(["--exact", test_name.split_once("::").unwrap().1, "--test-threads=1"]);
+
+        let output = match command.output() {
+            Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                eprintln!("Skipping: Landlock is not available in this kernel");
+                return;
+            },
+            output => output.unwrap(),
+        };
+
+        assert!(
+            output.status.success(),
+            "sandboxed child failed:\n{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("1 passed"));
+    }
+}
@@ -2,17 +2,29 @@ use crate::domain::{
     Plugin, PluginMetadata, PluginStatus, PluginInfo,
     EventBus, Event, Hook, Profile,
 };
+use crate::application::plugin_sandbox::PluginSandbox;
+use crate::application::policy::Policy;
+use crate::application::progress::{NoProgress, Progress, ProgressStep};
 use crate::errors::{ShellBeError, Result, ErrorContext};
-use crate::utils::{FileLock, ensure_directory, system_requirements::SystemRequirements, plugin_security::PluginSecurityValidator};
+use crate::utils::{FileLock, closest_matches, http_client, ensure_directory, system_requirements::SystemRequirements, plugin_security::PluginSecurityValidator};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use libloading::{Library, Symbol};
-use reqwest::blocking::Client;
 use std::fs;
-use std::io::{self, Write};
+use std::io;
 use std::collections::HashSet;
 use chrono::Utc;
-use tokio::sync::RwLock;
+use futures::future::join_all;
+use std::time::Duration;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{OnceCell, RwLock};
+
+/// How long a plugin's hook may take unless `plugins.hook_timeout_secs` says otherwise
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where plugins are downloaded from unless `plugins.registry` says otherwise
+pub const DEFAULT_PLUGIN_REGISTRY: &str = "https://github.com";
 
 /// Repository for managing plugin metadata
 #[async_trait::async_trait]
@@ -60,20 +72,63 @@ impl Default for PluginSandboxSettings {
                 "PATH".to_string(),
                 "USER".to_string(),
                 "SHELL".to_string(),
+                // Windows counterparts of HOME and USER
+                "USERPROFILE".to_string(),
+                "USERNAME".to_string(),
             ],
         }
     }
 }
 
+/// Publishers plugins may be installed from
+///
+/// Sources are a host followed by an optional path, e.g. `github.com/acme` for a GitHub
+/// organization or `registry.example.com/plugins` for a registry; a URL scheme is ignored.
+/// A source covers everything below it.
+#[derive(Debug, Clone, Default)]
+pub struct PluginSources {
+    /// Only these sources are allowed; empty allows any
+    pub allowed: Vec<String>,
+    /// These sources are refused, even when allowed
+    pub blocked: Vec<String>,
+}
+
+impl PluginSources {
+    /// Check a plugin source URL against the lists
+    pub fn check(&self, url: &str) -> Result<()> {
+        let source = normalize_source(url);
+
+        if let Some(blocked) = self.blocked.iter().find(|rule| source_matches(rule, &source)) {
+            return Err(ShellBeError::Security(format!(
+                "Plugin source {} is blocked by plugins.blocked_sources ({})", source, blocked
+            )));
+        }
+
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|rule| source_matches(rule, &source)) {
+            return Err(ShellBeError::Security(format!(
+                "Plugin source {} is not in plugins.allowed_sources ({})", source, self.allowed.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+}
+
 /// Service for managing plugins
 pub struct PluginService {
     repository: Arc<dyn PluginRepository>,
     event_bus: Arc<EventBus>,
     plugins_dir: PathBuf,
     loaded_plugins: Arc<RwLock<Vec<(String, Arc<dyn Plugin>, Arc<Library>)>>>,
+    enabled_loaded: OnceCell<()>,
     sandbox_settings: PluginSandboxSettings,
     security_validator: PluginSecurityValidator,
     system_requirements: SystemRequirements,
+    progress: Arc<dyn Progress>,
+    policy: Arc<Policy>,
+    sources: PluginSources,
+    hook_timeout: Duration,
+    registry: String,
 }
 
 impl PluginService {
@@ -88,13 +143,22 @@ impl PluginService {
             event_bus,
             plugins_dir: plugins_dir.into(),
             loaded_plugins: Arc::new(RwLock::new(Vec::new())),
+            enabled_loaded: OnceCell::new(),
             sandbox_settings: PluginSandboxSettings::default(),
             security_validator: PluginSecurityValidator::default(),
             system_requirements: SystemRequirements::default(),
+            progress: Arc::new(NoProgress),
+            policy: Arc::new(Policy::default()),
+            sources: PluginSources::default(),
+            hook_timeout: DEFAULT_HOOK_TIMEOUT,
+            registry: DEFAULT_PLUGIN_REGISTRY.to_string(),
         }
     }
 
-    /// Initialize the plugin system and load enabled plugins
+    /// Initialize the plugin system
+    ///
+    /// Only metadata is read here; plugin libraries are validated and loaded when a hook
+    /// first fires or one of their commands runs, so commands that don't need them stay fast.
     pub async fn initialize(&self) -> Result<()> {
         // Check system requirements
         self.system_requirements.all_requirements_met()
@@ -104,22 +168,6 @@ impl PluginService {
         ensure_directory(&self.plugins_dir).await
             .with_context(|| format!("Failed to create plugins directory: {}", self.plugins_dir.display()))?;
 
-        // Load enabled plugins
-        let plugins = self.repository.list().await?;
-
-        for metadata in plugins {
-            if metadata.status == PluginStatus::Enabled {
-                match self.load_plugin_internal(&metadata.info.name, &metadata.path).await {
-                    Ok(_) => {
-                        tracing::info!("Loaded plugin: {}", metadata.info.name);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to load plugin {}: {}", metadata.info.name, e);
-                    }
-                }
-            }
-        }
-
         Ok(())
     }
 
@@ -128,6 +176,12 @@ impl PluginService {
         self.repository.list().await
     }
 
+    /// Names of installed plugins that are close to `name`, for "did you mean" hints
+    pub async fn similar_names(&self, name: &str) -> Result<Vec<String>> {
+        let plugins = self.repository.list().await?;
+        Ok(closest_matches(name, plugins.iter().map(|p| p.info.name.as_str())))
+    }
+
     /// Get metadata for a specific plugin
     pub async fn get_plugin(&self, name: &str) -> Result<PluginMetadata> {
         match self.repository.get(name).await? {
@@ -141,12 +195,32 @@ impl PluginService {
         self.sandbox_settings = settings;
     }
 
+    /// Sandbox for running a plugin's executables as separate processes
+    pub fn sandbox(&self, plugin_dir: &Path) -> PluginSandbox {
+        PluginSandbox::new(self.sandbox_settings.clone(), plugin_dir)
+    }
+
     /// Install a plugin from a GitHub URL
     pub async fn install_from_github(&self, github_url: &str) -> Result<PluginMetadata> {
+        let metadata = self.install(github_url).await?;
+
+        self.event_bus.publish(Event::PluginInstalled {
+            name: metadata.info.name.clone(),
+            version: metadata.info.version.clone(),
+        });
+
+        Ok(metadata)
+    }
+
+    /// Download, check and register a plugin; shared by install and update
+    async fn install(&self, github_url: &str) -> Result<PluginMetadata> {
         // Parse GitHub URL
-        let (owner, repo) = parse_github_url(github_url)
+        let (owner, repo) = parse_plugin_url(github_url, &self.registry)
             .with_context(|| format!("Failed to parse GitHub URL: {}", github_url))?;
 
+        // Refuse publishers outside the configured sources before downloading anything
+        self.sources.check(&format!("{}/{}/{}", self.registry, owner, repo))?;
+
         // Create plugin directory path
         let plugin_dir = self.plugins_dir.join(&repo);
 
@@ -155,7 +229,7 @@ impl PluginService {
         let mut lock = FileLock::new(&lock_path).await;
 
         if !lock.acquire(10000).await? {
-            return Err(ShellBeError::Security(format!(
+            return Err(ShellBeError::FileLock(format!(
                 "Failed to acquire lock for plugin installation: {}", repo
             )));
         }
@@ -166,24 +240,32 @@ impl PluginService {
         let zip_path = temp_dir.path().join(format!("{}.zip", repo));
 
         // Download the zip file
-        let download_url = format!("https://github.com/{}/{}/archive/main.zip", owner, repo);
+        let download_url = format!("{}/{}/{}/archive/main.zip", self.registry, owner, repo);
 
         tracing::info!("Downloading plugin from {}", download_url);
 
-        let client = Client::new();
-        let mut response = client.get(&download_url).send()
-            .map_err(|e| ShellBeError::Update(format!("Failed to download plugin: {}", e)))?;
+        let client = http_client();
+        let mut response = client.get(&download_url).send().await
+            .map_err(|e| ShellBeError::http("Failed to download plugin", &e))?;
 
         if !response.status().is_success() {
-            return Err(ShellBeError::Update(format!("HTTP error: {}", response.status())));
+            return Err(ShellBeError::http_status("Failed to download plugin", response.status()));
         }
 
         // Save the zip file
-        let mut file = fs::File::create(&zip_path)
+        let mut file = tokio::fs::File::create(&zip_path).await
             .map_err(|e| ShellBeError::Io(format!("Failed to create zip file: {}", e)))?;
-        
-        response.copy_to(&mut file)
+
+        self.progress.start(ProgressStep::Download, response.content_length());
+        while let Some(chunk) = response.chunk().await
+            .map_err(|e| ShellBeError::http("Failed to download plugin", &e))? {
+            file.write_all(&chunk).await
+                .map_err(|e| ShellBeError::Io(format!("Failed to save zip content: {}", e)))?;
+            self.progress.advance(chunk.len() as u64);
+        }
+        file.flush().await
             .map_err(|e| ShellBeError::Io(format!("Failed to save zip content: {}", e)))?;
+        self.progress.finish();
 
         // Extract the zip file
         let extract_dir = temp_dir.path().join("extract");
@@ -198,6 +280,7 @@ impl PluginService {
         let mut archive = zip::ZipArchive::new(file)
             .map_err(|e| ShellBeError::Plugin(format!("Failed to read zip archive: {}", e)))?;
 
+        self.progress.start(ProgressStep::Extract, Some(archive.len() as u64));
         for i in 0..archive.len() {
             let mut file = archive.by_index(i)
                 .map_err(|e| ShellBeError::Plugin(format!("Failed to extract zip entry: {}", e)))?;
@@ -218,7 +301,10 @@ impl PluginService {
                 io::copy(&mut file, &mut outfile)
                     .map_err(|e| ShellBeError::Io(format!("Failed to write file: {}", e)))?;
             }
+
+            self.progress.advance(1);
         }
+        self.progress.finish();
 
         // Find the plugin directory
         let plugin_root = extract_dir.join(format!("{}-main", repo));
@@ -264,11 +350,22 @@ impl PluginService {
             return Err(ShellBeError::Plugin("Plugin API version mismatch".to_string()));
         }
 
+        // Only plugins the organization policy allows may be installed
+        self.policy.check_plugin(&plugin_name)?;
+
         // Check if plugin already exists
         if let Some(_) = self.repository.get(&plugin_name).await? {
             return Err(ShellBeError::AlreadyExists(format!("Plugin already exists: {}", plugin_name)));
         }
 
+        // Scan the source before anything is copied into the plugins directory
+        let security_findings = if PluginSecurityValidator::has_source(&plugin_root) {
+            self.security_validator.scan_source(&plugin_root)
+                .with_context(|| format!("Plugin source scan failed for {}", plugin_name))?
+        } else {
+            Vec::new()
+        };
+
         // Create plugin directory
         fs::create_dir_all(&plugin_dir)
             .map_err(|e| ShellBeError::Io(format!("Failed to create plugin directory: {}", e)))?;
@@ -285,6 +382,9 @@ impl PluginService {
         self.security_validator.validate(&lib_path)
             .with_context(|| format!("Plugin security validation failed for {}", lib_path.display()))?;
 
+        // Remember the library so changes made outside 'plugin update' are noticed
+        let library_hash = library_hash(&lib_path)?;
+
         // Create metadata
         let metadata = PluginMetadata {
             info: PluginInfo {
@@ -298,6 +398,9 @@ impl PluginService {
             path: plugin_dir,
             installed_at: Utc::now(),
             updated_at: None,
+            security_findings,
+            validated_hash: self.security_validator.is_enabled().then(|| library_hash.clone()),
+            library_hash: Some(library_hash),
         };
 
         // Save metadata
@@ -326,7 +429,14 @@ impl PluginService {
         }
 
         // Load the plugin
-        self.load_plugin_internal(name, &metadata.path).await?;
+        self.load_plugin_internal(&metadata).await?;
+
+        // Shared libraries run inside this process, so the sandbox settings can't apply
+        tracing::warn!(
+            "Plugin {} is a shared library and runs without the plugin sandbox (no network or path restrictions); \
+             only its programs in bin/ are sandboxed",
+            name
+        );
 
         // Update status
         self.repository.update_status(name, PluginStatus::Enabled).await?;
@@ -358,12 +468,15 @@ impl PluginService {
             None => return Err(ShellBeError::NotFound(format!("Plugin not found: {}", name))),
         };
 
-        // Check if already disabled
-        if metadata.status == PluginStatus::Disabled {
+        // Check if already disabled; quarantined plugins aren't loaded either
+        if metadata.status != PluginStatus::Enabled {
             return Ok(());
         }
 
-        // Run plugin hooks
+        // Run plugin hooks; a plugin that was never used in this run is loaded for them
+        if let Err(e) = self.load_plugin_internal(&metadata).await {
+            tracing::warn!("Failed to load plugin {} to disable it: {}", name, e);
+        }
         if let Ok(plugin) = self.get_loaded_plugin(name).await {
             if let Err(e) = plugin.on_disable().await {
                 tracing::warn!("Error in plugin.on_disable: {}", e);
@@ -413,6 +526,8 @@ impl PluginService {
 
         tracing::info!("Plugin '{}' removed", name);
 
+        self.event_bus.publish(Event::PluginRemoved(name.to_string()));
+
         Ok(())
     }
 
@@ -463,7 +578,7 @@ impl PluginService {
         }
 
         // Install the plugin again
-        let result = self.install_from_github(&source_url).await;
+        let result = self.install(&source_url).await;
 
         // Release the lock
         lock.release().await?;
@@ -503,14 +618,36 @@ impl PluginService {
         tracing::info!("Plugin '{}' updated successfully", name);
 
         // Return the updated metadata
-        match self.repository.get(name).await? {
-            Some(updated_metadata) => Ok(updated_metadata),
-            None => Err(ShellBeError::NotFound(format!("Plugin not found after update: {}", name))),
-        }
+        let updated_metadata = match self.repository.get(name).await? {
+            Some(updated_metadata) => updated_metadata,
+            None => return Err(ShellBeError::NotFound(format!("Plugin not found after update: {}", name))),
+        };
+
+        self.event_bus.publish(Event::PluginUpdated {
+            name: name.to_string(),
+            version: updated_metadata.info.version.clone(),
+        });
+
+        Ok(updated_metadata)
     }
 
     /// Execute a plugin command
+    ///
+    /// Commands shipped as programs in the plugin's `bin` directory run as separate,
+    /// sandboxed processes; other commands are handled by the plugin library.
     pub async fn execute_command(&self, plugin_name: &str, command: &str, args: &[String]) -> Result<()> {
+        // Load just this plugin if nothing needed it yet
+        if let Some(metadata) = self.repository.get(plugin_name).await? {
+            if metadata.status == PluginStatus::Enabled {
+                if let Some(program) = plugin_program(&metadata.path, command) {
+                    self.policy.check_plugin(plugin_name)?;
+                    return self.run_program(&metadata.path, &program, args).await;
+                }
+
+                self.load_plugin_internal(&metadata).await?;
+            }
+        }
+
         // Get the plugin
         let plugin = self.get_loaded_plugin(plugin_name).await?;
 
@@ -527,29 +664,66 @@ impl PluginService {
             .map_err(|e| ShellBeError::Plugin(format!("Command execution failed: {}", e)))
     }
 
-    /// Get all loaded plugins
+    /// Run a plugin program in the sandbox and wait for it to finish
+    async fn run_program(&self, plugin_dir: &Path, program: &Path, args: &[String]) -> Result<()> {
+        let mut command = self.sandbox(plugin_dir).command(program)?;
+        command.args(args);
+
+        let status = tokio::process::Command::from(command).status().await
+            .map_err(|e| ShellBeError::Plugin(format!("Failed to run {}: {}", program.display(), e)))?;
+
+        if !status.success() {
+            return Err(ShellBeError::Plugin(format!("Command execution failed: {} exited with {}", program.display(), status)));
+        }
+
+        Ok(())
+    }
+
+    /// Get all enabled plugins, loading them the first time they're needed
     pub async fn get_loaded_plugins(&self) -> Vec<Arc<dyn Plugin>> {
+        self.load_enabled_plugins().await;
+
         let plugins = self.loaded_plugins.read().await;
         plugins.iter().map(|(_, plugin, _)| plugin.clone()).collect()
     }
 
-    /// Execute a hook on all enabled plugins
+    /// Execute a hook on all enabled plugins concurrently
+    ///
+    /// Each plugin gets the hook timeout; one that fails or takes longer is logged and
+    /// doesn't hold up the others.
     pub async fn execute_hook(&self, hook: Hook, profile: Option<&Profile>) -> Result<()> {
         let plugins = self.get_loaded_plugins().await;
-
-        for plugin in plugins {
-            if let Err(e) = plugin.execute_hook(hook, profile).await {
-                tracing::warn!("Error in plugin hook: {}", e);
-            }
-        }
-
+        run_hook(plugins, hook, profile, self.hook_timeout).await;
         Ok(())
     }
 
     // Private methods
 
-    /// Load a plugin from a directory
-    async fn load_plugin_internal(&self, name: &str, plugin_dir: &Path) -> Result<()> {
+    /// Load every enabled plugin, once per run; plugins that fail to load are logged and skipped
+    async fn load_enabled_plugins(&self) {
+        self.enabled_loaded.get_or_init(|| async {
+            let plugins = match self.repository.list().await {
+                Ok(plugins) => plugins,
+                Err(e) => {
+                    tracing::error!("Failed to list plugins: {}", e);
+                    return;
+                }
+            };
+
+            for metadata in plugins.iter().filter(|p| p.status == PluginStatus::Enabled) {
+                match self.load_plugin_internal(metadata).await {
+                    Ok(_) => tracing::info!("Loaded plugin: {}", metadata.info.name),
+                    Err(e) => tracing::error!("Failed to load plugin {}: {}", metadata.info.name, e),
+                }
+            }
+        }).await;
+    }
+
+    /// Load a plugin from its installation directory
+    async fn load_plugin_internal(&self, metadata: &PluginMetadata) -> Result<()> {
+        let name = metadata.info.name.as_str();
+        let plugin_dir = metadata.path.as_path();
+
         // Check if plugin is already loaded
         {
             let plugins = self.loaded_plugins.read().await;
@@ -558,13 +732,45 @@ impl PluginService {
             }
         }
 
+        // Plugins installed before the policy disallowed them stay unloaded
+        self.policy.check_plugin(name)?;
+
         // Find the library file
         let lib_path = find_plugin_library(plugin_dir)
             .with_context(|| format!("Failed to find plugin library in {}", plugin_dir.display()))?;
 
-        // Validate plugin security before loading
-        self.security_validator.validate(&lib_path)
-            .with_context(|| format!("Plugin security validation failed for {}", lib_path.display()))?;
+        // Refuse libraries that changed since they were installed
+        let hash = library_hash(&lib_path)?;
+        match &metadata.library_hash {
+            Some(expected) if *expected != hash => {
+                self.repository.update_status(name, PluginStatus::Quarantined).await?;
+                return Err(ShellBeError::Security(format!(
+                    "Plugin library {} changed since it was installed, so plugin '{}' is quarantined. \
+                     Reinstall it with 'shellbe plugin update {}' or remove it",
+                    lib_path.display(), name, name
+                )));
+            },
+            Some(_) => {},
+            None => {
+                // Installed before hashes were recorded: trust the library as it is now
+                self.repository.save(PluginMetadata { library_hash: Some(hash.clone()), ..metadata.clone() }).await?;
+            },
+        }
+
+        // Validate plugin security before loading; validation runs `strings` and `nm`,
+        // so a library that already passed isn't checked again
+        if metadata.validated_hash.as_deref() != Some(hash.as_str()) {
+            self.security_validator.validate(&lib_path)
+                .with_context(|| format!("Plugin security validation failed for {}", lib_path.display()))?;
+
+            if self.security_validator.is_enabled() {
+                self.repository.save(PluginMetadata {
+                    library_hash: Some(hash.clone()),
+                    validated_hash: Some(hash),
+                    ..metadata.clone()
+                }).await?;
+            }
+        }
 
         // Load the library
         let lib = unsafe { 
@@ -612,18 +818,19 @@ impl PluginService {
             .ok_or_else(|| ShellBeError::NotFound(format!("Plugin not loaded: {}", name)))
     }
 
-    /// Unload a plugin by name
+    /// Unload a plugin by name; plugins are loaded lazily, so it may not be loaded at all
     async fn unload_plugin(&self, name: &str) -> Result<()> {
         let mut plugins = self.loaded_plugins.write().await;
-        let idx = plugins.iter().position(|(n, _, _)| n == name)
-            .ok_or_else(|| ShellBeError::NotFound(format!("Plugin not loaded: {}", name)))?;
-
-        // Remove the plugin
-        plugins.remove(idx);
+        plugins.retain(|(n, _, _)| n != name);
 
         Ok(())
     }
     
+    /// Report download and extraction progress to the given receiver
+    pub fn set_progress(&mut self, progress: Arc<dyn Progress>) {
+        self.progress = progress;
+    }
+
     /// Set security validator options
     pub fn set_security_validator(&mut self, validator: PluginSecurityValidator) {
         self.security_validator = validator;
@@ -633,16 +840,42 @@ impl PluginService {
     pub fn set_system_requirements(&mut self, requirements: SystemRequirements) {
         self.system_requirements = requirements;
     }
+
+    /// Only install and load the plugins the organization policy allows
+    pub fn set_policy(&mut self, policy: Arc<Policy>) {
+        self.policy = policy;
+    }
+
+    /// Restrict which sources plugins may be installed from
+    pub fn set_plugin_sources(&mut self, sources: PluginSources) {
+        self.sources = sources;
+    }
+
+    /// How long each plugin's hook may take
+    pub fn set_hook_timeout(&mut self, timeout: Duration) {
+        self.hook_timeout = timeout;
+    }
+
+    /// Download plugins from a GitHub-compatible host or mirror, e.g. `https://git.example.com`
+    pub fn set_registry(&mut self, registry: &str) {
+        self.registry = registry.trim_end_matches('/').to_string();
+    }
 }
 
 // Helper functions
 
-/// Parse a GitHub URL into owner and repo
-fn parse_github_url(url: &str) -> Result<(String, String)> {
-    // Extract owner and repo from different GitHub URL formats
-    let re = regex::Regex::new(r"github\.com[/:]([^/]+)/([^/]+)")
+/// Owner and repository from `owner/repo`, a GitHub URL or a URL on the registry's host
+fn parse_plugin_url(url: &str, registry: &str) -> Result<(String, String)> {
+    let hosts = [normalize_source(registry), "github.com".to_string()];
+    let pattern = format!(r"(?i)^(?:(?:{})[/:])?([\w-]+)/([\w.-]+?)(?:\.git)?(?:/.*)?$",
+                          hosts.iter().map(|host| regex::escape(host)).collect::<Vec<_>>().join("|"));
+    let re = regex::Regex::new(&pattern)
         .map_err(|_| ShellBeError::Update("Invalid GitHub URL format".to_string()))?;
 
+    let url = url.trim();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.strip_prefix("git@").unwrap_or(url);
+
     if let Some(captures) = re.captures(url) {
         let owner = captures.get(1).unwrap().as_str().to_string();
         let mut repo = captures.get(2).unwrap().as_str().to_string();
@@ -658,8 +891,22 @@ fn parse_github_url(url: &str) -> Result<(String, String)> {
     }
 }
 
+/// Source without scheme, trailing slash or `.git`, lowercased, e.g. `github.com/acme/plugin`
+fn normalize_source(source: &str) -> String {
+    let source = source.trim();
+    let source = source.split_once("://").map_or(source, |(_, rest)| rest);
+    let source = source.trim_end_matches('/');
+    source.strip_suffix(".git").unwrap_or(source).to_lowercase()
+}
+
+/// Check if a source is the rule itself or below it
+fn source_matches(rule: &str, source: &str) -> bool {
+    let rule = normalize_source(rule);
+    source == rule || source.starts_with(&format!("{}/", rule))
+}
+
 /// Find a plugin library file in a directory
-fn find_plugin_library(plugin_dir: &Path) -> Result<PathBuf> {
+pub(crate) fn find_plugin_library(plugin_dir: &Path) -> Result<PathBuf> {
     let lib_extensions = if cfg!(target_os = "windows") {
         vec!["dll"]
     } else if cfg!(target_os = "macos") {
@@ -688,6 +935,83 @@ fn find_plugin_library(plugin_dir: &Path) -> Result<PathBuf> {
     Err(ShellBeError::Plugin(format!("No plugin library found in {}", plugin_dir.display())))
 }
 
+/// Program for a command in the plugin's `bin` directory, if the plugin ships one
+fn plugin_program(plugin_dir: &Path, command: &str) -> Option<PathBuf> {
+    // Only plain names, so a command can't point outside the plugin
+    if command.is_empty() || command.contains(['/', '\\']) || command.starts_with('.') {
+        return None;
+    }
+
+    let program = plugin_dir.join("bin").join(command);
+    program.is_file().then_some(program)
+}
+
+/// SHA-256 of a plugin library, hex encoded
+pub(crate) fn library_hash(lib_path: &Path) -> Result<String> {
+    let mut file = fs::File::open(lib_path)
+        .map_err(|e| ShellBeError::Io(format!("Failed to read plugin library {}: {}", lib_path.display(), e)))?;
+
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|e| ShellBeError::Io(format!("Failed to read plugin library {}: {}", lib_path.display(), e)))?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check that a plugin library passes security validation and exposes `create_plugin`,
+/// without instantiating the plugin
+pub(crate) fn verify_plugin_library(plugin_dir: &Path, validator: &PluginSecurityValidator) -> Result<PathBuf> {
+    let lib_path = find_plugin_library(plugin_dir)?;
+
+    validator.validate(&lib_path)
+        .with_context(|| format!("Plugin security validation failed for {}", lib_path.display()))?;
+
+    let lib = unsafe {
+        Library::new(&lib_path)
+            .map_err(|e| ShellBeError::Plugin(format!("Failed to load plugin library: {}", e)))?
+    };
+
+    unsafe {
+        lib.get::<unsafe fn()>(b"create_plugin")
+            .map_err(|_| ShellBeError::Plugin("Symbol 'create_plugin' not found".to_string()))?;
+    }
+
+    Ok(lib_path)
+}
+
+/// Run `hook` on each plugin, returning the names of those that didn't finish in `timeout`
+///
+/// A loaded library's hook is synchronous code, so each runs on a blocking thread where it
+/// can't stall the executor and the timeout can fire. A hook that never returns keeps its
+/// thread, which the CLI doesn't wait for when it exits.
+async fn run_hook(plugins: Vec<Arc<dyn Plugin>>, hook: Hook, profile: Option<&Profile>, timeout: Duration) -> Vec<String> {
+    let runtime = tokio::runtime::Handle::current();
+    let runs = plugins.into_iter().map(|plugin| {
+        let name = plugin.info().name;
+        let profile = profile.cloned();
+        let runtime = runtime.clone();
+        let run = tokio::task::spawn_blocking(move || runtime.block_on(plugin.execute_hook(hook, profile.as_ref())));
+        async move { (name, tokio::time::timeout(timeout, run).await) }
+    });
+
+    let mut overran = Vec::new();
+    for (name, result) in join_all(runs).await {
+        match result {
+            Ok(Ok(Ok(()))) => {},
+            Ok(Ok(Err(e))) => tracing::warn!("Plugin '{}' error in hook {:?}: {}", name, hook, e),
+            Ok(Err(e)) => tracing::warn!("Plugin '{}' failed in hook {:?}: {}", name, hook, e),
+            Err(_) => {
+                tracing::warn!(
+                    "Plugin '{}' didn't finish hook {:?} within {}s and was skipped",
+                    name, hook, timeout.as_secs()
+                );
+                overran.push(name);
+            },
+        }
+    }
+    overran
+}
+
 /// Copy a directory recursively
 fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
     fs::create_dir_all(&dst)?;
@@ -701,4 +1025,58 @@ fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()>
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::plugin::{PluginCommand, PluginResult};
+    use std::time::Instant;
+
+    /// Blocks its thread in every hook, like a library doing synchronous I/O
+    struct BlockingPlugin {
+        name: &'static str,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for BlockingPlugin {
+        fn info(&self) -> PluginInfo {
+            PluginInfo {
+                name: self.name.to_string(),
+                version: "1.0.0".to_string(),
+                description: String::new(),
+                author: String::new(),
+                source_url: None,
+            }
+        }
+
+        fn commands(&self) -> Vec<PluginCommand> {
+            Vec::new()
+        }
+
+        async fn execute_hook(&self, _hook: Hook, _profile: Option<&Profile>) -> PluginResult {
+            std::thread::sleep(self.delay);
+            Ok(())
+        }
+
+        async fn execute_command(&self, _command: &str, _args: &[String]) -> PluginResult {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_hook_times_out() {
+        let plugins: Vec<Arc<dyn Plugin>> = vec![
+            Arc::new(BlockingPlugin { name: "slow", delay: Duration::from_secs(3) }),
+            Arc::new(BlockingPlugin { name: "fast", delay: Duration::ZERO }),
+        ];
+
+        // The test runtime has a single thread, which the slow hook would otherwise block
+        let started = Instant::now();
+        let overran = run_hook(plugins, Hook::PreConnect, None, Duration::from_millis(100)).await;
+
+        assert_eq!(overran, vec!["slow"]);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,106 @@
+use crate::domain::{glob_match, DomainError, Profile};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable pointing at a different policy file, e.g. for testing a policy
+pub const POLICY_FILE_ENV: &str = "SHELLBE_POLICY_FILE";
+
+/// Where administrators put the organization policy
+#[cfg(not(windows))]
+pub const DEFAULT_POLICY_FILE: &str = "/etc/shellbe/policy.toml";
+
+/// Where administrators put the organization policy
+#[cfg(windows)]
+pub const DEFAULT_POLICY_FILE: &str = r"C:\ProgramData\shellbe\policy.toml";
+
+/// Constraints an administrator enforces on every user, read from `policy.toml`
+///
+/// Unlike settings, users can't override the policy; a missing file enforces nothing.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// SSH options every profile must use, e.g. `PasswordAuthentication = "no"`
+    pub required_options: HashMap<String, String>,
+    /// Host globs no profile may connect to, e.g. `*.prod.internal`
+    pub banned_hosts: Vec<String>,
+    /// Always refuse unknown or changed host keys, as if `--strict` were given
+    pub strict_host_keys: bool,
+    /// Plugins that may be installed and loaded; unset allows any
+    pub allowed_plugins: Option<Vec<String>>,
+    /// File the policy was read from, for error messages
+    #[serde(skip)]
+    source: Option<PathBuf>,
+}
+
+impl Policy {
+    /// The policy file in effect: `SHELLBE_POLICY_FILE` or the system-wide default
+    pub fn default_path() -> PathBuf {
+        std::env::var_os(POLICY_FILE_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_POLICY_FILE))
+    }
+
+    /// Read the policy, enforcing nothing when the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self, DomainError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path).map_err(DomainError::IoError)?;
+        let mut policy: Policy = toml::from_str(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid policy in {}: {}", path.display(), e)))?;
+        policy.source = Some(path.to_path_buf());
+
+        Ok(policy)
+    }
+
+    /// Check a profile against the policy and add required options it doesn't set
+    pub fn apply(&self, profile: &mut Profile) -> Result<(), DomainError> {
+        let hostname = profile.hostname.to_lowercase();
+        if let Some(pattern) = self.banned_hosts.iter().find(|pattern| glob_match(&pattern.to_lowercase(), &hostname)) {
+            return Err(self.violation(format!(
+                "connections to {} are not allowed (banned host '{}')",
+                profile.hostname, pattern,
+            )));
+        }
+
+        for (option, required) in &self.required_options {
+            // SSH option names and yes/no values are case-insensitive
+            let current = profile.options.iter().find(|(name, _)| name.eq_ignore_ascii_case(option));
+
+            match current {
+                Some((name, value)) if !value.eq_ignore_ascii_case(required) => {
+                    return Err(self.violation(format!(
+                        "profile '{}' sets {}={}, but {}={} is required",
+                        profile.name, name, value, option, required,
+                    )));
+                },
+                Some(_) => {},
+                None => {
+                    profile.options.insert(option.clone(), required.clone());
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a plugin may be installed or loaded
+    pub fn check_plugin(&self, name: &str) -> Result<(), DomainError> {
+        match &self.allowed_plugins {
+            Some(allowed) if !allowed.iter().any(|allowed| allowed == name) => {
+                Err(self.violation(format!("plugin '{}' is not on the list of allowed plugins", name)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn violation(&self, message: String) -> DomainError {
+        match &self.source {
+            Some(source) => DomainError::PolicyViolation(format!("{} (set by {})", message, source.display())),
+            None => DomainError::PolicyViolation(message),
+        }
+    }
+}
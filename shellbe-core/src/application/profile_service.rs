@@ -0,0 +1,388 @@
+use crate::domain::{
+    Profile, ProfileExporter, ProfileFilter, ProfileRepository, Event, EventBus,
+    DomainError, is_secret_reference,
+};
+use crate::application::policy::Policy;
+use crate::utils::closest_matches;
+use std::sync::Arc;
+
+/// ProfileService manages SSH profiles
+pub struct ProfileService {
+    repository: Arc<dyn ProfileRepository>,
+    event_bus: Arc<EventBus>,
+    policy: Arc<Policy>,
+}
+
+impl ProfileService {
+    /// Create a new ProfileService with the provided repository and event bus
+    pub fn new(repository: Arc<dyn ProfileRepository>, event_bus: Arc<EventBus>) -> Self {
+        Self {
+            repository,
+            event_bus,
+            policy: Arc::new(Policy::default()),
+        }
+    }
+
+    /// Enforce the organization policy on profiles that are added or changed
+    pub fn set_policy(&mut self, policy: Arc<Policy>) {
+        self.policy = policy;
+    }
+
+    /// Add the options the policy requires, as saving the profile would
+    pub fn apply_policy(&self, profile: &mut Profile) -> Result<(), DomainError> {
+        self.policy.apply(profile)
+    }
+
+    /// Add a new profile
+    pub async fn add_profile(&self, mut profile: Profile) -> Result<(), DomainError> {
+        // Check if profile already exists
+        if self.repository.exists(&profile.name).await? {
+            return Err(DomainError::ProfileAlreadyExists(profile.name));
+        }
+
+        check_secret_references(&profile)?;
+        self.policy.apply(&mut profile)?;
+
+        // Add the profile
+        self.repository.add(profile.clone()).await?;
+
+        // Publish event
+        self.event_bus.publish(Event::ProfileCreated(profile));
+
+        Ok(())
+    }
+
+    /// Get a profile by name
+    pub async fn get_profile(&self, name: &str) -> Result<Profile, DomainError> {
+        match self.repository.get(name).await? {
+            Some(profile) => Ok(profile),
+            None => Err(DomainError::ProfileNotFound(name.to_string())),
+        }
+    }
+
+    /// Update an existing profile
+    pub async fn update_profile(&self, profile: Profile) -> Result<(), DomainError> {
+        // Check if profile exists
+        if !self.repository.exists(&profile.name).await? {
+            return Err(DomainError::ProfileNotFound(profile.name.clone()));
+        }
+
+        // Update the profile with current timestamp
+        check_secret_references(&profile)?;
+        let mut updated_profile = profile.clone();
+        self.policy.apply(&mut updated_profile)?;
+        updated_profile.mark_as_updated();
+
+        // Update the profile
+        self.repository.update(updated_profile.clone()).await?;
+
+        // Publish event
+        self.event_bus.publish(Event::ProfileUpdated(updated_profile));
+
+        Ok(())
+    }
+
+    /// Add or replace a profile taken from `source`: `ssh_config`, `bundle` or a URL
+    pub async fn import_profile(&self, profile: Profile, source: &str) -> Result<(), DomainError> {
+        let name = profile.name.clone();
+
+        if self.repository.exists(&name).await? {
+            self.update_profile(profile).await?;
+        } else {
+            self.add_profile(profile).await?;
+        }
+
+        self.event_bus.publish(Event::ProfileImported {
+            name,
+            source: source.to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// Render profiles in another tool's format
+    pub fn export_profiles(&self, profiles: &[Profile], exporter: &dyn ProfileExporter) -> Result<String, DomainError> {
+        let contents = exporter.export(profiles)?;
+
+        self.event_bus.publish(Event::ProfilesExported {
+            format: exporter.name().to_string(),
+            count: profiles.len(),
+        });
+
+        Ok(contents)
+    }
+
+    /// Remove a profile by name
+    pub async fn remove_profile(&self, name: &str) -> Result<(), DomainError> {
+        // Check if profile exists
+        if !self.repository.exists(name).await? {
+            return Err(DomainError::ProfileNotFound(name.to_string()));
+        }
+
+        // Remove the profile
+        self.repository.remove(name).await?;
+
+        // Publish event
+        self.event_bus.publish(Event::ProfileRemoved(name.to_string()));
+
+        Ok(())
+    }
+
+    /// List all profiles
+    pub async fn list_profiles(&self) -> Result<Vec<Profile>, DomainError> {
+        self.repository.list().await
+    }
+
+    /// Names of profiles that are close to `name`, for "did you mean" hints
+    pub async fn similar_names(&self, name: &str) -> Result<Vec<String>, DomainError> {
+        let profiles = self.repository.list().await?;
+        Ok(closest_matches(name, profiles.iter().map(|p| p.name.as_str())))
+    }
+
+    /// Another profile connecting to the same host, port and user as `profile`, under a different name
+    ///
+    /// Hostnames are compared case-insensitively; Docker and Kubernetes profiles never match.
+    pub async fn find_duplicate(&self, profile: &Profile) -> Result<Option<Profile>, DomainError> {
+        if !profile.kind.is_ssh() {
+            return Ok(None);
+        }
+
+        let profiles = self.repository.list().await?;
+        Ok(profiles.into_iter().find(|existing| {
+            existing.name != profile.name
+                && existing.kind.is_ssh()
+                && existing.hostname.eq_ignore_ascii_case(&profile.hostname)
+                && existing.port == profile.port
+                && existing.username == profile.username
+        }))
+    }
+
+    /// List profiles matching a filter
+    pub async fn find_profiles(&self, filter: &ProfileFilter) -> Result<Vec<Profile>, DomainError> {
+        let profiles = self.repository.list().await?;
+
+        if filter.is_empty() {
+            return Ok(profiles);
+        }
+
+        Ok(profiles.into_iter().filter(|p| filter.matches(p)).collect())
+    }
+}
+
+/// Refuse password and passphrase fields that hold a secret instead of a reference to one
+fn check_secret_references(profile: &Profile) -> Result<(), DomainError> {
+    let fields = [("password", &profile.password), ("identity_passphrase", &profile.identity_passphrase)];
+
+    for (field, value) in fields {
+        if value.as_deref().is_some_and(|value| !is_secret_reference(value)) {
+            return Err(DomainError::ConfigError(format!(
+                "The {} of profile '{}' must be a secret manager reference (op://... or bw://...); ShellBe doesn't store secrets",
+                field, profile.name,
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::events::tests::TestEventListener;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    struct MockProfileRepository {
+        profiles: Mutex<HashMap<String, Profile>>,
+    }
+
+    impl MockProfileRepository {
+        fn new() -> Self {
+            Self {
+                profiles: Mutex::new(HashMap::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ProfileRepository for MockProfileRepository {
+        async fn add(&self, profile: Profile) -> Result<(), DomainError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            profiles.insert(profile.name.clone(), profile);
+            Ok(())
+        }
+
+        async fn get(&self, name: &str) -> Result<Option<Profile>, DomainError> {
+            let profiles = self.profiles.lock().unwrap();
+            Ok(profiles.get(name).cloned())
+        }
+
+        async fn update(&self, profile: Profile) -> Result<(), DomainError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            profiles.insert(profile.name.clone(), profile);
+            Ok(())
+        }
+
+        async fn remove(&self, name: &str) -> Result<(), DomainError> {
+            let mut profiles = self.profiles.lock().unwrap();
+            profiles.remove(name);
+            Ok(())
+        }
+
+        async fn list(&self) -> Result<Vec<Profile>, DomainError> {
+            let profiles = self.profiles.lock().unwrap();
+            Ok(profiles.values().cloned().collect())
+        }
+
+        async fn exists(&self, name: &str) -> Result<bool, DomainError> {
+            let profiles = self.profiles.lock().unwrap();
+            Ok(profiles.contains_key(name))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_profile() {
+        // Set up dependencies
+        let repository = Arc::new(MockProfileRepository::new());
+        let event_listener = Arc::new(TestEventListener::new());
+        let event_bus = Arc::new(EventBus::new());
+        event_bus.register(event_listener.clone());
+        let service = ProfileService::new(repository.clone(), event_bus.clone());
+
+        // Create a test profile
+        let profile = Profile::new("test", "example.com", "user");
+
+        // Add the profile
+        service.add_profile(profile.clone()).await.unwrap();
+
+        // Verify the profile was added
+        let stored_profile = repository.get(&profile.name).await.unwrap().unwrap();
+        assert_eq!(stored_profile.name, profile.name);
+        assert_eq!(stored_profile.hostname, profile.hostname);
+
+        // Verify the event was published
+        event_bus.flush().await;
+        let events = event_listener.events();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Event::ProfileCreated(p) => {
+                assert_eq!(p.name, profile.name);
+            }
+            _ => panic!("Expected ProfileCreated event"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_profiles() {
+        let repository = Arc::new(MockProfileRepository::new());
+        let service = ProfileService::new(repository, Arc::new(EventBus::new()));
+
+        let mut web = Profile::new("web", "web-1.Example.com", "deploy");
+        web.port = 2222;
+        web.tags = vec!["prod".to_string()];
+        service.add_profile(web).await.unwrap();
+        service.add_profile(Profile::new("db", "db.example.com", "root")).await.unwrap();
+        service.add_profile(Profile::new("lab", "lab.internal", "deploy")).await.unwrap();
+
+        let names = |profiles: Vec<Profile>| {
+            let mut names: Vec<String> = profiles.into_iter().map(|p| p.name).collect();
+            names.sort();
+            names
+        };
+
+        let filter = ProfileFilter { host: Some("*.example.com".to_string()), ..Default::default() };
+        assert_eq!(names(service.find_profiles(&filter).await.unwrap()), vec!["db", "web"]);
+
+        let filter = ProfileFilter { user: Some("deploy".to_string()), ..Default::default() };
+        assert_eq!(names(service.find_profiles(&filter).await.unwrap()), vec!["lab", "web"]);
+
+        let filter = ProfileFilter {
+            host: Some("web-?.example.com".to_string()),
+            port: Some(2222),
+            tag: Some("prod".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(service.find_profiles(&filter).await.unwrap()), vec!["web"]);
+
+        let filter = ProfileFilter { port: Some(2222), user: Some("root".to_string()), ..Default::default() };
+        assert!(service.find_profiles(&filter).await.unwrap().is_empty());
+
+        assert_eq!(service.find_profiles(&ProfileFilter::default()).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate() {
+        let repository = Arc::new(MockProfileRepository::new());
+        let service = ProfileService::new(repository, Arc::new(EventBus::new()));
+        service.add_profile(Profile::new("web", "Web.Example.com", "deploy")).await.unwrap();
+
+        let duplicate = service.find_duplicate(&Profile::new("web-copy", "web.example.com", "deploy")).await.unwrap();
+        assert_eq!(duplicate.map(|p| p.name), Some("web".to_string()));
+
+        // The profile itself, another user or another port isn't a duplicate
+        assert!(service.find_duplicate(&Profile::new("web", "web.example.com", "deploy")).await.unwrap().is_none());
+        assert!(service.find_duplicate(&Profile::new("web-root", "web.example.com", "root")).await.unwrap().is_none());
+        let mut other_port = Profile::new("web-2222", "web.example.com", "deploy");
+        other_port.port = 2222;
+        assert!(service.find_duplicate(&other_port).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_secret_references() {
+        let repository = Arc::new(MockProfileRepository::new());
+        let service = ProfileService::new(repository, Arc::new(EventBus::new()));
+
+        let mut profile = Profile::new("web", "example.com", "deploy");
+        profile.password = Some("hunter2".to_string());
+        assert!(matches!(service.add_profile(profile.clone()).await, Err(DomainError::ConfigError(_))));
+
+        profile.password = Some("bw://web-1/password".to_string());
+        profile.identity_passphrase = Some("op://Private/web key/passphrase".to_string());
+        service.add_profile(profile.clone()).await.unwrap();
+
+        profile.identity_passphrase = Some("op://".to_string());
+        assert!(service.update_profile(profile).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_similar_names() {
+        let repository = Arc::new(MockProfileRepository::new());
+        let service = ProfileService::new(repository, Arc::new(EventBus::new()));
+
+        for name in ["web-server", "web-servers", "db-server", "lab"] {
+            service.add_profile(Profile::new(name, "example.com", "user")).await.unwrap();
+        }
+
+        assert_eq!(service.similar_names("web-srever").await.unwrap(), vec!["web-server", "web-servers"]);
+        assert_eq!(service.similar_names("LAB").await.unwrap(), vec!["lab"]);
+        assert!(service.similar_names("staging").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_policy() {
+        let repository = Arc::new(MockProfileRepository::new());
+        let mut service = ProfileService::new(repository.clone(), Arc::new(EventBus::new()));
+
+        let policy: Policy = toml::from_str(
+            "banned_hosts = [\"*.prod.internal\"]\nrequired_options = { PasswordAuthentication = \"no\" }\n",
+        ).unwrap();
+        service.set_policy(Arc::new(policy));
+
+        // Banned hosts are refused
+        let result = service.add_profile(Profile::new("db", "DB.prod.internal", "root")).await;
+        assert!(matches!(result, Err(DomainError::PolicyViolation(_))));
+
+        // Conflicting options are refused
+        let mut web = Profile::new("web", "web.example.com", "deploy");
+        web.options.insert("passwordauthentication".to_string(), "yes".to_string());
+        let result = service.add_profile(web.clone()).await;
+        assert!(matches!(result, Err(DomainError::PolicyViolation(_))));
+
+        // Missing required options are added
+        web.options.clear();
+        service.add_profile(web).await.unwrap();
+        let stored = repository.get("web").await.unwrap().unwrap();
+        assert_eq!(stored.options.get("PasswordAuthentication").map(String::as_str), Some("no"));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+/// A long-running step that reports progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStep {
+    /// Downloading a plugin archive or a release; measured in bytes
+    Download,
+    /// Extracting a plugin archive; measured in files
+    Extract,
+}
+
+/// Receives progress from services so the interface can show it, e.g. as a progress bar
+pub trait Progress: Send + Sync {
+    /// Begin a step; `total` is the amount of work when known
+    fn start(&self, step: ProgressStep, total: Option<u64>);
+
+    /// Record finished work for the current step
+    fn advance(&self, amount: u64);
+
+    /// The current step is done
+    fn finish(&self);
+}
+
+/// Progress that isn't shown anywhere
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _step: ProgressStep, _total: Option<u64>) {}
+
+    fn advance(&self, _amount: u64) {}
+
+    fn finish(&self) {}
+}
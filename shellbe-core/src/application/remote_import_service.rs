@@ -0,0 +1,177 @@
+use crate::application::BundleService;
+use crate::domain::{DomainError, Profile};
+use crate::utils::{http_client, write_private};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+/// File in the data directory remembering which URL each imported profile came from
+pub const REMOTE_IMPORTS_FILE: &str = "remote_imports.json";
+
+/// Appended to the URL of a profile set to find its detached signature
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Largest profile set or signature downloaded
+const MAX_DOWNLOAD_BYTES: usize = 16 * 1024 * 1024;
+
+/// Accepted shapes of a shared profile set
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProfileSet {
+    /// `shellbe list --output json`
+    List(Vec<Profile>),
+    /// A bundle manifest or `{ "profiles": [...] }`
+    Manifest { profiles: Vec<Profile> },
+    /// `profiles.json` from a data directory
+    Map(HashMap<String, Profile>),
+}
+
+/// Profiles downloaded from a URL
+#[derive(Debug, Clone)]
+pub struct RemoteProfileSet {
+    /// Where they came from
+    pub url: String,
+    /// The profiles, without personal usage data
+    pub profiles: Vec<Profile>,
+    /// Trusted key that signed them, when the signature was verified
+    pub signer: Option<PathBuf>,
+}
+
+/// What importing a profile would do to the local profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportChange {
+    /// No local profile has this name
+    New,
+    /// Replaces the local profile: an update from the same URL, or `--replace`
+    Updated,
+    /// Same as the local profile
+    Unchanged,
+    /// A local profile of this name didn't come from this URL; kept unless replacing
+    Conflict,
+}
+
+/// Fetches shared profile sets over HTTP and remembers where imported profiles came from,
+/// so importing the same URL again picks up the team's changes
+pub struct RemoteImportService {
+    client: reqwest::Client,
+    sources_path: PathBuf,
+}
+
+impl RemoteImportService {
+    /// Track imported profiles in the given data directory
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client: http_client(),
+            sources_path: data_dir.into().join(REMOTE_IMPORTS_FILE),
+        }
+    }
+
+    /// Download a profile set; with `verify`, its signature at `<url>.sig` must check out
+    pub async fn fetch(&self, url: &str, verify: Option<&BundleService>) -> Result<RemoteProfileSet, DomainError> {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(DomainError::ConfigError(format!("'{}' is not an http(s) URL", url)));
+        }
+
+        let content = self.download(url).await?;
+
+        let signer = match verify {
+            Some(bundles) => {
+                let signature_url = format!("{}{}", url, SIGNATURE_SUFFIX);
+                let signature = self.download(&signature_url).await
+                    .map_err(|e| DomainError::ConfigError(format!("Failed to fetch the signature from {}: {}", signature_url, e)))?;
+                Some(bundles.verify(&content, &signature)?)
+            },
+            None => None,
+        };
+
+        let set: ProfileSet = serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("{} doesn't hold a list of profiles: {}", url, e)))?;
+
+        let mut profiles = match set {
+            ProfileSet::List(profiles) | ProfileSet::Manifest { profiles } => profiles,
+            ProfileSet::Map(profiles) => profiles.into_values().collect(),
+        };
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        for profile in &mut profiles {
+            profile.last_used = None;
+        }
+
+        Ok(RemoteProfileSet {
+            url: url.to_string(),
+            profiles,
+            signer,
+        })
+    }
+
+    /// Decide what importing each profile would do, given the local profiles
+    pub fn plan(&self, set: &RemoteProfileSet, local: &[Profile], replace: bool) -> Result<Vec<(Profile, ImportChange)>, DomainError> {
+        let sources = self.load()?;
+
+        Ok(set.profiles.iter()
+            .map(|profile| {
+                let change = match local.iter().find(|local| local.name == profile.name) {
+                    None => ImportChange::New,
+                    Some(local) if same_settings(local, profile) => ImportChange::Unchanged,
+                    Some(_) if replace || sources.get(&profile.name) == Some(&set.url) => ImportChange::Updated,
+                    Some(_) => ImportChange::Conflict,
+                };
+                (profile.clone(), change)
+            })
+            .collect())
+    }
+
+    /// Remember that these profiles came from `url`
+    pub fn record(&self, url: &str, names: &[String]) -> Result<(), DomainError> {
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let mut sources = self.load()?;
+        for name in names {
+            sources.insert(name.clone(), url.to_string());
+        }
+
+        let content = serde_json::to_vec_pretty(&sources)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize {}: {}", REMOTE_IMPORTS_FILE, e)))?;
+        write_private(&self.sources_path, &content).map_err(DomainError::IoError)
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, DomainError> {
+        if !self.sources_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let content = fs::read(&self.sources_path).map_err(DomainError::IoError)?;
+        serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to parse {}: {}", REMOTE_IMPORTS_FILE, e)))
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, DomainError> {
+        let response = self.client.get(url).send().await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| DomainError::ConfigError(format!("Failed to fetch {}: {}", url, e)))?;
+
+        if response.content_length().is_some_and(|length| length > MAX_DOWNLOAD_BYTES as u64) {
+            return Err(DomainError::ConfigError(format!("{} is too large to be a profile set", url)));
+        }
+
+        let bytes = response.bytes().await
+            .map_err(|e| DomainError::ConfigError(format!("Failed to fetch {}: {}", url, e)))?;
+        if bytes.len() > MAX_DOWNLOAD_BYTES {
+            return Err(DomainError::ConfigError(format!("{} is too large to be a profile set", url)));
+        }
+
+        Ok(bytes.to_vec())
+    }
+}
+
+/// Whether two profiles connect the same way, ignoring timestamps
+fn same_settings(a: &Profile, b: &Profile) -> bool {
+    a.hostname == b.hostname
+        && a.username == b.username
+        && a.port == b.port
+        && a.identity_file == b.identity_file
+        && a.options == b.options
+        && a.tags == b.tags
+}
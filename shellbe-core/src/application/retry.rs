@@ -0,0 +1,175 @@
+use crate::domain::DomainError;
+use crate::errors::ShellBeError;
+use std::future::Future;
+use std::time::Duration;
+
+/// Attempts, the first one included, for operations that fail transiently
+pub const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry; doubled for each one after it
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Errors that tell a transient failure, worth trying again, from a permanent one
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+impl Retryable for DomainError {
+    fn is_retryable(&self) -> bool {
+        DomainError::is_retryable(self)
+    }
+}
+
+impl Retryable for ShellBeError {
+    fn is_retryable(&self) -> bool {
+        ShellBeError::is_retryable(self)
+    }
+}
+
+/// Runs an operation again when it fails transiently, such as a connection timing out
+///
+/// Permanent failures like refused authentication or a missing profile are returned
+/// right away, so retries don't hammer them.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    attempts: u32,
+    backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETRY_ATTEMPTS, DEFAULT_RETRY_BACKOFF)
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making at most `attempts` attempts; 0 is treated as 1
+    pub fn new(attempts: u32, backoff: Duration) -> Self {
+        Self { attempts: attempts.max(1), backoff }
+    }
+
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    /// Run `operation` until it succeeds, fails permanently or runs out of attempts
+    pub async fn run<T, E, F, Fut>(&self, what: &str, operation: F) -> Result<T, E>
+    where
+        E: Retryable + std::fmt::Display,
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut delay = self.backoff;
+        let mut attempt = 1;
+
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.attempts && e.is_retryable() => {
+                    tracing::debug!("{} failed (attempt {} of {}), retrying in {:?}: {}", what, attempt, self.attempts, delay, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    async fn attempts_for(error: fn() -> DomainError) -> (u32, Result<(), DomainError>) {
+        let attempts = AtomicU32::new(0);
+        let result = RetryPolicy::new(3, Duration::ZERO).run("Test", || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(error()) }
+        }).await;
+        (attempts.load(Ordering::SeqCst), result)
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let (attempts, result) = attempts_for(|| DomainError::Unavailable("timed out".to_string())).await;
+        assert_eq!(attempts, 3);
+        assert!(matches!(result, Err(DomainError::Unavailable(_))));
+
+        let (attempts, _) = attempts_for(|| std::io::Error::from(std::io::ErrorKind::TimedOut).into()).await;
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failures_are_not_retried() {
+        let (attempts, _) = attempts_for(|| DomainError::ProfileNotFound("web".to_string())).await;
+        assert_eq!(attempts, 1);
+
+        let (attempts, _) = attempts_for(|| DomainError::SshError("Permission denied (publickey)".to_string())).await;
+        assert_eq!(attempts, 1);
+    }
+
+    fn retryable(error: impl Into<ShellBeError>) -> bool {
+        error.into().is_retryable()
+    }
+
+    #[test]
+    fn test_transient_io_errors_are_retryable() {
+        use std::io::{Error, ErrorKind};
+
+        for kind in [ErrorKind::TimedOut, ErrorKind::ConnectionReset, ErrorKind::Interrupted, ErrorKind::WouldBlock] {
+            assert!(DomainError::from(Error::from(kind)).is_retryable(), "{:?}", kind);
+            assert!(retryable(Error::from(kind)), "{:?}", kind);
+            assert!(retryable(DomainError::from(Error::from(kind))), "{:?}", kind);
+        }
+
+        for kind in [ErrorKind::NotFound, ErrorKind::PermissionDenied, ErrorKind::InvalidData] {
+            assert!(!DomainError::from(Error::from(kind)).is_retryable(), "{:?}", kind);
+            assert!(!retryable(Error::from(kind)), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn test_unavailable_and_lock_contention_are_retryable() {
+        assert!(retryable(DomainError::Unavailable("server is starting".to_string())));
+        assert!(retryable(ShellBeError::Unavailable("server is starting".to_string())));
+        assert!(retryable(ShellBeError::FileLock("busy".to_string())));
+    }
+
+    #[test]
+    fn test_http_status_classes() {
+        use reqwest::StatusCode;
+
+        assert!(ShellBeError::http_status("Download", StatusCode::SERVICE_UNAVAILABLE).is_retryable());
+        assert!(ShellBeError::http_status("Download", StatusCode::TOO_MANY_REQUESTS).is_retryable());
+        assert!(!ShellBeError::http_status("Download", StatusCode::NOT_FOUND).is_retryable());
+        assert!(!ShellBeError::http_status("Download", StatusCode::UNAUTHORIZED).is_retryable());
+    }
+
+    #[test]
+    fn test_message_wording_doesnt_decide() {
+        // Only the kind of error counts, so rewording or translating messages can't change retries
+        assert!(!retryable(ShellBeError::Io("operation timed out, try again".to_string())));
+        assert!(!retryable(ShellBeError::Connection("connection reset by peer".to_string())));
+        assert!(!retryable(ShellBeError::Update("temporarily unavailable".to_string())));
+        assert!(retryable(ShellBeError::Unavailable("Zeitüberschreitung".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_stops_retrying_after_success() {
+        let attempts = AtomicU32::new(0);
+        let result = RetryPolicy::new(5, Duration::ZERO).run("Test", || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                match attempt {
+                    1 => Err(ShellBeError::FileLock("busy".to_string())),
+                    _ => Ok(attempt),
+                }
+            }
+        }).await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
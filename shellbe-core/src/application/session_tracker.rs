@@ -0,0 +1,205 @@
+use crate::domain::{DomainError, Session};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Directory in the cache directory holding a state file per open session
+pub const SESSIONS_DIR: &str = "sessions";
+
+/// Sessions opened by `connect`, kept in memory and as `<id>.json` files so other shellbe
+/// processes can list and end them
+///
+/// Each session has a file of its own, so concurrent connections never write the same file.
+/// Files left behind by a crashed shellbe are dropped once their process is gone.
+pub struct SessionTracker {
+    dir: PathBuf,
+    active: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionTracker {
+    /// Sessions kept in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Directory the session files are in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Record a session whose process has just started
+    pub fn start(&self, profile: &str, target: &str, pid: u32) -> Session {
+        let session = Session {
+            id: uuid::Uuid::new_v4().simple().to_string()[..8].to_string(),
+            profile: profile.to_string(),
+            target: target.to_string(),
+            pid,
+            started_at: chrono::Utc::now(),
+        };
+
+        // Listing is best effort, so a session that can't be written still connects
+        if let Err(e) = self.write(&session) {
+            tracing::warn!("Failed to record session {}: {}", session.id, e);
+        }
+        self.active.lock().unwrap().insert(session.id.clone(), session.clone());
+        session
+    }
+
+    /// Forget a session that has ended
+    pub fn end(&self, id: &str) {
+        self.active.lock().unwrap().remove(id);
+        match fs::remove_file(self.path(id)) {
+            Ok(()) => {},
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => tracing::warn!("Failed to remove session {}: {}", id, e),
+        }
+    }
+
+    /// Sessions open in this process
+    pub fn active(&self) -> Vec<Session> {
+        let mut sessions: Vec<_> = self.active.lock().unwrap().values().cloned().collect();
+        sessions.sort_by_key(|session| session.started_at);
+        sessions
+    }
+
+    /// Sessions open in any shellbe process, oldest first
+    pub fn list(&self) -> io::Result<Vec<Session>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut sessions = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+
+            let session = match fs::read(&path).map(|contents| serde_json::from_slice::<Session>(&contents)) {
+                Ok(Ok(session)) => session,
+                Ok(Err(e)) => {
+                    tracing::debug!("Ignoring unreadable session file {}: {}", path.display(), e);
+                    continue;
+                },
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            if is_running(session.pid) {
+                sessions.push(session);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+
+        sessions.sort_by_key(|session| session.started_at);
+        Ok(sessions)
+    }
+
+    /// End a session by terminating its process; `None` when no session has that ID
+    pub fn kill(&self, id: &str) -> Result<Option<Session>, DomainError> {
+        let Some(session) = self.list()?.into_iter().find(|session| session.id == id) else {
+            return Ok(None);
+        };
+
+        terminate(session.pid)
+            .map_err(|e| DomainError::SshError(format!("Failed to end session {} (PID {}): {}", id, session.pid, e)))?;
+        self.end(id);
+        Ok(Some(session))
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn write(&self, session: &Session) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let contents = serde_json::to_vec_pretty(session).map_err(io::Error::other)?;
+        fs::write(self.path(&session.id), contents)
+    }
+}
+
+/// Signal 0 checks that the process exists without touching it; EPERM means it does but
+/// belongs to someone else
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    unsafe { libc::kill(pid, 0) == 0 } || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// There is no cheap check on Windows, so sessions are listed until they end or are killed
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+/// Ask the process to exit, the way closing its terminal would
+#[cfg(unix)]
+fn terminate(pid: u32) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(pid).map_err(io::Error::other)?;
+    if unsafe { libc::kill(pid, libc::SIGTERM) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(pid: u32) -> io::Result<()> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("taskkill exited with {}", status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_are_listed_until_they_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = SessionTracker::new(dir.path().join(SESSIONS_DIR));
+        assert!(tracker.list().unwrap().is_empty());
+
+        // This test's own process is certainly running
+        let session = tracker.start("web", "web.example.com", std::process::id());
+        assert_eq!(tracker.active(), vec![session.clone()]);
+
+        // Another tracker over the same directory, as another shellbe process would have
+        let other = SessionTracker::new(tracker.dir());
+        assert_eq!(other.list().unwrap(), vec![session.clone()]);
+
+        tracker.end(&session.id);
+        assert!(tracker.active().is_empty());
+        assert!(other.list().unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sessions_of_exited_processes_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = SessionTracker::new(dir.path());
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        tracker.start("web", "web.example.com", pid);
+        assert!(tracker.list().unwrap().is_empty());
+        assert!(fs::read_dir(dir.path()).unwrap().next().is_none());
+    }
+}
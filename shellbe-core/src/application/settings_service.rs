@@ -0,0 +1,545 @@
+use crate::application::telemetry_service::DEFAULT_TELEMETRY_ENDPOINT;
+use crate::domain::DomainError;
+use crate::utils::OpenSshVersion;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the settings file in the config directory
+pub const SETTINGS_FILE: &str = "settings.toml";
+
+/// User settings read from `settings.toml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Defaults for new profiles and keys
+    pub defaults: DefaultSettings,
+    /// Prompts and output
+    pub ui: UiSettings,
+    /// Connection history
+    pub history: HistorySettings,
+    /// Update checks
+    pub updates: UpdateSettings,
+    /// Proxy and root certificates for network calls
+    pub network: NetworkSettings,
+    /// Encrypted vault
+    pub vault: VaultSettings,
+    /// SSH connections
+    pub ssh: SshSettings,
+    /// Profile bundles
+    pub bundles: BundleSettings,
+    /// Plugin installation
+    pub plugins: PluginSettings,
+    /// Encrypted sync between machines
+    pub sync: SyncSettings,
+    /// Running daemon the CLI talks to instead of the files
+    pub daemon: DaemonSettings,
+    /// Event journal
+    pub events: EventSettings,
+    /// HashiCorp Vault SSH secrets engine, for profiles with a `vault_role`
+    pub vault_ssh: VaultSshSettings,
+    /// Anonymous usage counters, off unless turned on
+    pub telemetry: TelemetrySettings,
+    /// User command aliases, e.g. `ls = "list"`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+}
+
+/// Defaults for new profiles and keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefaultSettings {
+    /// Port used when `add` isn't given one
+    pub port: u16,
+    /// Key type used by `generate-key` (ed25519 or rsa)
+    pub key_type: String,
+}
+
+impl Default for DefaultSettings {
+    fn default() -> Self {
+        Self {
+            port: 22,
+            key_type: "ed25519".to_string(),
+        }
+    }
+}
+
+/// Prompts and output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiSettings {
+    /// Ask before removing or overwriting; `false` behaves like `--yes`
+    pub confirm: bool,
+    /// Output format used when `--output` isn't given (table or json)
+    pub output: String,
+    /// When to use colors (auto, always or never)
+    pub color: String,
+    /// Built-in color theme
+    pub theme: String,
+    /// Per-role style overrides, e.g. `success = "magenta.bold"`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub colors: HashMap<String, String>,
+    /// Language of messages, e.g. `de`; `auto` follows `LC_ALL`, `LC_MESSAGES` and `LANG`
+    pub locale: String,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            confirm: true,
+            output: "table".to_string(),
+            color: "auto".to_string(),
+            theme: "default".to_string(),
+            colors: HashMap::new(),
+            locale: "auto".to_string(),
+        }
+    }
+}
+
+/// Connection history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistorySettings {
+    /// Drop entries older than this many days; unset keeps history forever
+    pub retention_days: Option<u32>,
+}
+
+/// How often to look for a new release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateCheckFrequency {
+    Never,
+    Daily,
+    #[default]
+    Weekly,
+}
+
+/// Which releases updates come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// Releases only
+    #[default]
+    Stable,
+    /// Releases and beta or release candidate builds, e.g. `2.1.0-beta.1`
+    Beta,
+    /// Everything, including nightly builds such as `2.1.0-nightly.20240501`
+    Nightly,
+}
+
+impl UpdateChannel {
+    /// Whether builds of this version are published on the channel
+    pub fn allows(&self, version: &semver::Version) -> bool {
+        let pre = version.pre.as_str();
+        match self {
+            UpdateChannel::Stable => pre.is_empty(),
+            UpdateChannel::Beta => pre.is_empty() || pre.starts_with("beta") || pre.starts_with("rc"),
+            UpdateChannel::Nightly => true,
+        }
+    }
+}
+
+impl std::fmt::Display for UpdateChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+            UpdateChannel::Nightly => "nightly",
+        })
+    }
+}
+
+/// Update checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpdateSettings {
+    /// How often to check for updates
+    pub check: UpdateCheckFrequency,
+    /// Releases to update to: stable, beta or nightly
+    pub channel: UpdateChannel,
+    /// Repository releases are published in, as `owner/name`
+    pub repository: String,
+    /// GitHub-compatible API serving the repository's releases, e.g. a GitHub Enterprise
+    /// `https://github.example.com/api/v3` or a mirror
+    pub api_url: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        Self {
+            check: UpdateCheckFrequency::default(),
+            channel: UpdateChannel::default(),
+            repository: "arash/shellbe".to_string(),
+            api_url: "https://api.github.com".to_string(),
+        }
+    }
+}
+
+/// Proxy and root certificates for network calls
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSettings {
+    /// Proxy for every request, e.g. `http://proxy.example.com:3128`; unset uses `HTTPS_PROXY`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// PEM files with extra root certificates, e.g. the CA of a proxy that intercepts TLS
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ca_certificates: Vec<PathBuf>,
+}
+
+/// Encrypted vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VaultSettings {
+    /// Where the key of an unlocked vault is kept (auto, keychain or file)
+    pub key_store: String,
+    /// Lock an unlocked vault after this many minutes without use; unset keeps it unlocked
+    pub idle_lock_minutes: Option<u32>,
+}
+
+impl Default for VaultSettings {
+    fn default() -> Self {
+        Self {
+            key_store: "auto".to_string(),
+            idle_lock_minutes: None,
+        }
+    }
+}
+
+/// SSH connections
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SshSettings {
+    /// Refuse hosts with unknown or changed keys, like `--strict`
+    pub strict_host_keys: bool,
+    /// Oldest OpenSSH client `doctor` accepts, e.g. `8.9` or `9.3p2`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_client_version: Option<String>,
+    /// Share one connection per host between sessions (ControlMaster), with sockets that
+    /// `shellbe sockets` manages
+    pub multiplex: bool,
+    /// Seconds `test` remembers what hostnames resolved to; unset looks them up every time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_cache_secs: Option<u64>,
+    /// Run ssh with `-q` for every profile, hiding server banners and ssh's warnings
+    pub quiet: bool,
+}
+
+/// Profile bundles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BundleSettings {
+    /// Public keys (PEM) whose signed bundles 'bundle import' accepts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trusted_keys: Vec<PathBuf>,
+}
+
+/// Plugin installation and hooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginSettings {
+    /// Sources plugins may be installed from, e.g. `github.com/acme`; empty allows any
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_sources: Vec<String>,
+    /// Sources plugins are never installed from; takes precedence over `allowed_sources`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_sources: Vec<String>,
+    /// Seconds a plugin's hook may take before it's abandoned, so one slow plugin can't hold up a connection
+    pub hook_timeout_secs: u64,
+    /// Where `plugin install owner/name` downloads plugins from, as `<registry>/owner/name/archive/main.zip`
+    pub registry: String,
+}
+
+impl Default for PluginSettings {
+    fn default() -> Self {
+        Self {
+            allowed_sources: Vec::new(),
+            blocked_sources: Vec::new(),
+            hook_timeout_secs: 5,
+            registry: "https://github.com".to_string(),
+        }
+    }
+}
+
+/// Encrypted sync between machines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SyncSettings {
+    /// Where the snapshot is kept (none, s3, webdav or http)
+    pub backend: String,
+    /// Snapshot location: `s3://bucket/key` for S3, the file's http(s) URL otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// S3 region; defaults to `AWS_REGION`, then us-east-1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// S3-compatible endpoint such as MinIO; defaults to AWS
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// User name for WebDAV and HTTP basic authentication
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            backend: "none".to_string(),
+            url: None,
+            region: None,
+            endpoint: None,
+            username: None,
+        }
+    }
+}
+
+/// Running daemon the CLI talks to instead of the files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DaemonSettings {
+    /// gRPC address of `shellbe serve --grpc`, e.g. `http://127.0.0.1:7879`; unset uses the files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+/// Event journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventSettings {
+    /// Append every event to `events.jsonl` in the data directory, for `shellbe events log`
+    pub journal: bool,
+    /// Size in bytes at which the journal file is rotated
+    pub journal_max_bytes: u64,
+    /// Journal files kept, the current one included
+    pub journal_files: usize,
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self {
+            journal: false,
+            journal_max_bytes: 1024 * 1024,
+            journal_files: 3,
+        }
+    }
+}
+
+/// How Vault's SSH secrets engine authenticates connections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultSshMode {
+    /// Sign the profile's public key and connect with the certificate
+    #[default]
+    Ca,
+    /// Get a one-time password and answer the password prompt with it
+    Otp,
+}
+
+impl std::fmt::Display for VaultSshMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            VaultSshMode::Ca => "ca",
+            VaultSshMode::Otp => "otp",
+        })
+    }
+}
+
+/// HashiCorp Vault SSH secrets engine, for profiles with a `vault_role`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VaultSshSettings {
+    /// Vault server, e.g. `https://vault.example.com:8200`; defaults to `VAULT_ADDR`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Path the SSH secrets engine is mounted at
+    pub mount: String,
+    /// Signed certificates (ca) or one-time passwords (otp)
+    pub mode: VaultSshMode,
+}
+
+impl Default for VaultSshSettings {
+    fn default() -> Self {
+        Self {
+            address: None,
+            mount: "ssh".to_string(),
+            mode: VaultSshMode::Ca,
+        }
+    }
+}
+
+/// Anonymous usage counters, off unless turned on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetrySettings {
+    /// Count commands and error categories and send the counts once a day
+    pub enabled: bool,
+    /// Where counts are sent, as a JSON POST
+    pub endpoint: String,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: DEFAULT_TELEMETRY_ENDPOINT.to_string(),
+        }
+    }
+}
+
+/// Service for loading and saving user settings
+pub struct SettingsService {
+    path: PathBuf,
+    settings: Settings,
+}
+
+impl SettingsService {
+    /// Load settings from the given file, using defaults when it doesn't exist
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, DomainError> {
+        let path = path.into();
+
+        let settings = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(DomainError::IoError)?;
+
+            let settings: Settings = toml::from_str(&content)
+                .map_err(|e| DomainError::ConfigError(format!("Invalid settings in {}: {}", path.display(), e)))?;
+
+            validate(&settings)
+                .map_err(|e| DomainError::ConfigError(format!("Invalid settings in {}: {}", path.display(), e)))?;
+
+            settings
+        } else {
+            Settings::default()
+        };
+
+        Ok(Self { path, settings })
+    }
+
+    /// Current settings
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Path of the settings file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replace the settings and write them to disk
+    pub fn save(&mut self, settings: Settings) -> Result<(), DomainError> {
+        validate(&settings).map_err(DomainError::ConfigError)?;
+
+        let content = toml::to_string_pretty(&settings)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize settings: {}", e)))?;
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(DomainError::IoError)?;
+        }
+
+        std::fs::write(&self.path, content).map_err(DomainError::IoError)?;
+        self.settings = settings;
+
+        Ok(())
+    }
+}
+
+/// Check values serde can't validate on its own
+fn validate(settings: &Settings) -> Result<(), String> {
+    if settings.defaults.port == 0 {
+        return Err("defaults.port must be between 1 and 65535".to_string());
+    }
+
+    if !matches!(settings.defaults.key_type.as_str(), "ed25519" | "rsa") {
+        return Err(format!("defaults.key_type must be 'ed25519' or 'rsa', not '{}'", settings.defaults.key_type));
+    }
+
+    if !matches!(settings.ui.output.as_str(), "table" | "json") {
+        return Err(format!("ui.output must be 'table' or 'json', not '{}'", settings.ui.output));
+    }
+
+    if !matches!(settings.ui.color.as_str(), "auto" | "always" | "never") {
+        return Err(format!("ui.color must be 'auto', 'always' or 'never', not '{}'", settings.ui.color));
+    }
+
+    let locale = &settings.ui.locale;
+    if locale.is_empty() || !locale.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '@')) {
+        return Err(format!("ui.locale must be 'auto' or a language code like 'de', not '{}'", locale));
+    }
+
+    if !matches!(settings.vault.key_store.as_str(), "auto" | "keychain" | "file") {
+        return Err(format!("vault.key_store must be 'auto', 'keychain' or 'file', not '{}'", settings.vault.key_store));
+    }
+
+    if settings.vault.idle_lock_minutes == Some(0) {
+        return Err("vault.idle_lock_minutes must be at least 1".to_string());
+    }
+
+    if let Some(version) = &settings.ssh.min_client_version {
+        version.parse::<OpenSshVersion>().map_err(|e| format!("ssh.min_client_version: {}", e))?;
+    }
+
+    for source in settings.plugins.allowed_sources.iter().chain(&settings.plugins.blocked_sources) {
+        if source.trim().trim_end_matches('/').is_empty() {
+            return Err("plugins: plugin sources must not be empty".to_string());
+        }
+    }
+
+    if settings.plugins.hook_timeout_secs == 0 {
+        return Err("plugins.hook_timeout_secs must be at least 1".to_string());
+    }
+
+    let sync = &settings.sync;
+    if !matches!(sync.backend.as_str(), "none" | "s3" | "webdav" | "http") {
+        return Err(format!("sync.backend must be 'none', 's3', 'webdav' or 'http', not '{}'", sync.backend));
+    }
+
+    match (sync.backend.as_str(), sync.url.as_deref()) {
+        ("none", _) => {},
+        (backend, None) => return Err(format!("sync.url is required for the {} backend", backend)),
+        ("s3", Some(url)) if !url.starts_with("s3://") => {
+            return Err(format!("sync.url must be an s3://bucket/key URL, not '{}'", url));
+        },
+        ("webdav" | "http", Some(url)) if !url.starts_with("https://") && !url.starts_with("http://") => {
+            return Err(format!("sync.url must be an http(s) URL, not '{}'", url));
+        },
+        _ => {},
+    }
+
+    let endpoint = &settings.telemetry.endpoint;
+    if !endpoint.starts_with("https://") && !endpoint.starts_with("http://") {
+        return Err(format!("telemetry.endpoint must be an http(s) URL, not '{}'", endpoint));
+    }
+
+    if let Some(address) = &settings.daemon.address {
+        if !address.starts_with("http://") && !address.starts_with("https://") {
+            return Err(format!("daemon.address must be an http(s) URL, not '{}'", address));
+        }
+    }
+
+    if settings.events.journal_max_bytes < 4096 {
+        return Err("events.journal_max_bytes must be at least 4096".to_string());
+    }
+    if settings.events.journal_files == 0 {
+        return Err("events.journal_files must be at least 1".to_string());
+    }
+
+    if let Some(address) = &settings.vault_ssh.address {
+        if !address.starts_with("http://") && !address.starts_with("https://") {
+            return Err(format!("vault_ssh.address must be an http(s) URL, not '{}'", address));
+        }
+    }
+    if settings.vault_ssh.mount.trim_matches('/').is_empty() {
+        return Err("vault_ssh.mount must not be empty".to_string());
+    }
+
+    for (name, expansion) in &settings.aliases {
+        if name.is_empty() || name.starts_with('-') || name.contains(char::is_whitespace) {
+            return Err(format!("aliases: '{}' is not a valid command alias", name));
+        }
+        if expansion.trim().is_empty() {
+            return Err(format!("aliases.{} must not be empty", name));
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::domain::{
-    Profile, SshConfigRepository, DomainError,
+    Profile, Alias, SshConfigRepository, DomainError, Event, EventBus,
 };
 use std::sync::Arc;
 use chrono::Utc;
@@ -7,6 +7,7 @@ use chrono::Utc;
 /// Service for managing SSH config integration
 pub struct SshConfigService {
     repository: Arc<dyn SshConfigRepository>,
+    event_bus: Arc<EventBus>,
 }
 
 impl SshConfigService {
@@ -14,9 +15,22 @@ impl SshConfigService {
     pub fn new(repository: Arc<dyn SshConfigRepository>) -> Self {
         Self {
             repository,
+            event_bus: Arc::new(EventBus::new()),
         }
     }
 
+    /// Publish exports on this event bus
+    pub fn set_event_bus(&mut self, event_bus: Arc<EventBus>) {
+        self.event_bus = event_bus;
+    }
+
+    fn exported(&self, count: usize) {
+        self.event_bus.publish(Event::ProfilesExported {
+            format: "ssh_config".to_string(),
+            count,
+        });
+    }
+
     /// Import profiles from SSH config file
     pub async fn import_profiles(&self) -> Result<Vec<Profile>, DomainError> {
         self.repository.import().await
@@ -24,7 +38,28 @@ impl SshConfigService {
 
     /// Export profiles to SSH config file
     pub async fn export_profiles(&self, profiles: &[Profile], replace: bool) -> Result<(), DomainError> {
-        self.repository.export(profiles, replace).await
+        let profiles = ssh_profiles(profiles);
+        self.repository.export(&profiles, replace).await?;
+        self.exported(profiles.len());
+        Ok(())
+    }
+
+    /// Import profiles and their aliases from SSH config file
+    pub async fn import_profiles_with_aliases(&self) -> Result<(Vec<Profile>, Vec<Alias>), DomainError> {
+        self.repository.import_with_aliases().await
+    }
+
+    /// Export profiles and their aliases to SSH config file
+    pub async fn export_profiles_with_aliases(
+        &self,
+        profiles: &[Profile],
+        aliases: &[Alias],
+        replace: bool,
+    ) -> Result<(), DomainError> {
+        let profiles = ssh_profiles(profiles);
+        self.repository.export_with_aliases(&profiles, aliases, replace).await?;
+        self.exported(profiles.len());
+        Ok(())
     }
 
     /// Add a single profile to SSH config
@@ -92,4 +127,9 @@ impl SshConfigService {
 
         output
     }
+}
+
+/// The profiles a `Host` block can describe; Docker containers aren't reachable by ssh alone
+fn ssh_profiles(profiles: &[Profile]) -> Vec<Profile> {
+    profiles.iter().filter(|profile| profile.kind.is_ssh()).cloned().collect()
 }
\ No newline at end of file
@@ -0,0 +1,197 @@
+use crate::application::VaultSshMode;
+use crate::domain::{ConnectionArgs, DomainError, Profile, SshCredentialIssuer};
+use crate::utils::write_private;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Certificates closer than this to expiring are renewed before connecting
+const CERTIFICATE_RENEW_MARGIN_SECONDS: i64 = 60;
+
+/// Latest expiry worth telling apart, 9999-12-31T23:59:59Z
+const LAST_SECOND_OF_9999: u64 = 253_402_300_799;
+
+/// Public keys tried, in order, for profiles without an identity file
+const DEFAULT_PUBLIC_KEYS: [&str; 3] = ["id_ed25519.pub", "id_ecdsa.pub", "id_rsa.pub"];
+
+/// Gets short-lived SSH credentials for profiles with a `vault_role` before connecting
+pub struct SshCredentialService {
+    issuer: Option<Arc<dyn SshCredentialIssuer>>,
+    mode: VaultSshMode,
+    certificate_dir: PathBuf,
+}
+
+impl SshCredentialService {
+    /// Credentials from `issuer`, with signed certificates kept in `certificate_dir`
+    ///
+    /// Without an issuer, connecting to a profile with a role fails with a hint.
+    pub fn new(issuer: Option<Arc<dyn SshCredentialIssuer>>, mode: VaultSshMode, certificate_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            issuer,
+            mode,
+            certificate_dir: certificate_dir.into(),
+        }
+    }
+
+    /// Add the profile's certificate or one-time password to the connection arguments
+    pub async fn prepare(&self, profile: &Profile, args: &mut ConnectionArgs) -> Result<(), DomainError> {
+        let Some(role) = &profile.vault_role else {
+            return Ok(());
+        };
+
+        let issuer = self.issuer.as_ref().ok_or_else(|| DomainError::ConfigError(format!(
+            "Profile '{}' uses Vault role '{}', but no Vault address is set; set VAULT_ADDR or vault_ssh.address",
+            profile.name, role,
+        )))?;
+
+        match self.mode {
+            VaultSshMode::Ca => {
+                let certificate = self.certificate(issuer.as_ref(), profile, role).await?;
+                args.ssh_args.push("-o".to_string());
+                args.ssh_args.push(format!("CertificateFile={}", certificate.display()));
+            },
+            VaultSshMode::Otp => {
+                let ip = resolve_ip(&profile.hostname, profile.port).await?;
+                args.one_time_password = Some(issuer.one_time_password(role, &ip, &profile.username).await?);
+            },
+        }
+
+        Ok(())
+    }
+
+    /// The profile's signed certificate, requesting a new one when it's missing, expiring or stale
+    async fn certificate(&self, issuer: &dyn SshCredentialIssuer, profile: &Profile, role: &str) -> Result<PathBuf, DomainError> {
+        let public_key_path = public_key_path(profile)?;
+        let certificate_path = self.certificate_dir.join(format!("{}-cert.pub", file_name(&profile.name)));
+
+        if is_current(&certificate_path, &public_key_path) {
+            tracing::debug!("Reusing certificate {}", certificate_path.display());
+            return Ok(certificate_path);
+        }
+
+        let public_key = std::fs::read_to_string(&public_key_path)?;
+        let certificate = issuer.sign_key(role, public_key.trim(), &profile.username).await?;
+
+        std::fs::create_dir_all(&self.certificate_dir)?;
+        write_private(&certificate_path, format!("{}\n", certificate).as_bytes())?;
+        tracing::info!("{} signed {} for {}", issuer.name(), public_key_path.display(), profile.name);
+
+        Ok(certificate_path)
+    }
+}
+
+/// The public key to sign: the identity file's `.pub`, or the first default key
+fn public_key_path(profile: &Profile) -> Result<PathBuf, DomainError> {
+    if let Some(identity) = &profile.identity_file {
+        let mut path = identity.clone().into_os_string();
+        path.push(".pub");
+        return Ok(PathBuf::from(path));
+    }
+
+    let ssh_dir = crate::utils::home_dir().unwrap_or_default().join(".ssh");
+    DEFAULT_PUBLIC_KEYS.iter()
+        .map(|name| ssh_dir.join(name))
+        .find(|path| path.exists())
+        .ok_or_else(|| DomainError::ConfigError(format!(
+            "Profile '{}' has no identity file and there is no key in {} to sign; run `shellbe generate-key`",
+            profile.name, ssh_dir.display(),
+        )))
+}
+
+/// Whether a stored certificate is still valid for a while and newer than its public key
+fn is_current(certificate_path: &Path, public_key_path: &Path) -> bool {
+    let Ok(certificate) = std::fs::read_to_string(certificate_path) else {
+        return false;
+    };
+
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    if let (Some(signed), Some(key_changed)) = (modified(certificate_path), modified(public_key_path)) {
+        if key_changed > signed {
+            return false;
+        }
+    }
+
+    certificate_valid_before(&certificate)
+        .is_some_and(|valid_before| valid_before > Utc::now() + Duration::seconds(CERTIFICATE_RENEW_MARGIN_SECONDS))
+}
+
+/// Expiry of an OpenSSH certificate line such as `ssh-ed25519-cert-v01@openssh.com AAAA...`
+///
+/// See PROTOCOL.certkeys in OpenSSH: the key type and nonce come first, then the public key
+/// fields of the type, serial, type, key ID, principals, valid after and valid before.
+pub fn certificate_valid_before(certificate: &str) -> Option<DateTime<Utc>> {
+    let blob = certificate.split_whitespace().nth(1)?;
+    let blob = base64::engine::general_purpose::STANDARD.decode(blob).ok()?;
+    let mut reader = CertificateReader { data: &blob };
+
+    let key_type = String::from_utf8(reader.string()?.to_vec()).ok()?;
+    reader.string()?; // nonce
+
+    let key_fields = match key_type.as_str() {
+        "ssh-ed25519-cert-v01@openssh.com" => 1,
+        "ssh-rsa-cert-v01@openssh.com" => 2,
+        "sk-ssh-ed25519-cert-v01@openssh.com" => 2,
+        "ssh-dss-cert-v01@openssh.com" => 4,
+        t if t.starts_with("ecdsa-sha2-") => 2,
+        t if t.starts_with("sk-ecdsa-sha2-") => 3,
+        _ => return None,
+    };
+    for _ in 0..key_fields {
+        reader.string()?;
+    }
+
+    reader.u64()?; // serial
+    reader.u32()?; // certificate type
+    reader.string()?; // key ID
+    reader.string()?; // principals
+    reader.u64()?; // valid after
+    let valid_before = reader.u64()?;
+
+    // "Forever" is u64::MAX; anything past year 9999 is as good
+    DateTime::from_timestamp(valid_before.min(LAST_SECOND_OF_9999) as i64, 0)
+}
+
+/// Reads the big-endian fields of an SSH wire-format blob
+struct CertificateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CertificateReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8).map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+}
+
+/// IP address to request a one-time password for; Vault's OTP roles are scoped by IP
+async fn resolve_ip(hostname: &str, port: u16) -> Result<String, DomainError> {
+    tokio::net::lookup_host((hostname, port)).await?
+        .next()
+        .map(|address| address.ip().to_string())
+        .ok_or_else(|| DomainError::SshError(format!("Could not resolve {}", hostname)))
+}
+
+/// A profile name made safe to use in a file name
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect()
+}
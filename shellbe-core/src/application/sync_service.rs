@@ -0,0 +1,282 @@
+use crate::domain::{DomainError, HistoryEntry, HistoryFormat, SyncBackend};
+use crate::infrastructure::repositories::encrypted_storage::{EncryptedFile, EncryptedStorage, KdfParams, VaultKey};
+use crate::infrastructure::repositories::file_history_repository::{decode_history, encode_history};
+use crate::infrastructure::repositories::write_behind::EncodeError;
+use crate::utils::FileLock;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Environment variable with the sync passphrase, for scripts that can't type it
+pub const SYNC_PASSPHRASE_ENV: &str = "SHELLBE_SYNC_PASSPHRASE";
+
+/// Data files shared between machines
+pub const SYNC_DATA_FILES: &[&str] = &["profiles.json", "aliases.json", "history.json"];
+
+/// Snapshot format written by this version
+const SYNC_FORMAT_VERSION: u32 = 1;
+
+/// What is uploaded; only this header can be read without the passphrase
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncEnvelope {
+    /// Format version
+    shellbe_sync: u32,
+    /// When the snapshot was pushed
+    pushed_at: DateTime<Utc>,
+    /// Who pushed it
+    pushed_by: String,
+    /// Parameters turning the passphrase into the key
+    kdf: KdfParams,
+    /// The encrypted [`SyncSnapshot`]
+    snapshot: EncryptedFile,
+}
+
+/// Contents of a snapshot: each data file as JSON
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncSnapshot {
+    files: BTreeMap<String, serde_json::Value>,
+}
+
+/// The snapshot on the sync backend, as far as it can be seen without the passphrase
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    /// Kind of backend, e.g. "S3"
+    pub backend: String,
+    /// Where the snapshot is kept
+    pub location: String,
+    /// When the snapshot was last pushed; unset when nothing was pushed yet
+    pub pushed_at: Option<DateTime<Utc>>,
+    /// Who pushed it
+    pub pushed_by: Option<String>,
+}
+
+/// Shares profiles, aliases and history between machines through a [`SyncBackend`]
+///
+/// Snapshots are encrypted here with a key derived from the sync passphrase, so the
+/// backend only ever stores ciphertext. The passphrase is separate from the vault's,
+/// since it is shared with every machine (or teammate) that syncs.
+pub struct SyncService {
+    backend: Arc<dyn SyncBackend>,
+    data_dir: PathBuf,
+    storage: EncryptedStorage,
+}
+
+impl SyncService {
+    /// Sync the data files in `data_dir`, read and written through the vault storage
+    pub fn new(backend: Arc<dyn SyncBackend>, data_dir: impl Into<PathBuf>, storage: EncryptedStorage) -> Self {
+        Self {
+            backend,
+            data_dir: data_dir.into(),
+            storage,
+        }
+    }
+
+    /// The backend snapshots are kept on
+    pub fn backend(&self) -> &dyn SyncBackend {
+        self.backend.as_ref()
+    }
+
+    /// Upload the local data files, replacing the snapshot; returns the files sent
+    ///
+    /// Refuses when the existing snapshot was encrypted with a different passphrase,
+    /// so one machine with a typo can't lock out the others.
+    pub async fn push(&self, passphrase: &str) -> Result<Vec<String>, DomainError> {
+        let (kdf, key) = match self.download().await? {
+            Some(envelope) => {
+                let key = VaultKey::derive(passphrase.as_bytes(), &envelope.kdf)?;
+                self.open(&envelope, &key)?;
+                (envelope.kdf, key)
+            },
+            None => {
+                let kdf = KdfParams::generate();
+                let key = VaultKey::derive(passphrase.as_bytes(), &kdf)?;
+                (kdf, key)
+            },
+        };
+
+        let mut snapshot = SyncSnapshot::default();
+        for file in SYNC_DATA_FILES {
+            let path = self.data_dir.join(file);
+            if !path.exists() {
+                continue;
+            }
+
+            let content = self.storage.read(&path)?;
+            let (value, _) = parse(file, &content)?;
+            snapshot.files.insert(file.to_string(), value);
+        }
+
+        let plaintext = Zeroizing::new(serde_json::to_vec(&snapshot)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize sync snapshot: {}", e)))?);
+
+        let envelope = SyncEnvelope {
+            shellbe_sync: SYNC_FORMAT_VERSION,
+            pushed_at: Utc::now(),
+            pushed_by: whoami::username(),
+            kdf,
+            snapshot: EncryptedFile::seal(&key, &plaintext)?,
+        };
+        let data = serde_json::to_vec_pretty(&envelope)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize sync snapshot: {}", e)))?;
+
+        self.backend.upload(&data).await?;
+        Ok(snapshot.files.into_keys().collect())
+    }
+
+    /// Merge the snapshot into the local data files; returns the files changed
+    ///
+    /// Entries from the snapshot replace local ones with the same name and history is
+    /// combined; nothing is deleted locally.
+    pub async fn pull(&self, passphrase: &str) -> Result<Vec<String>, DomainError> {
+        let envelope = self.download().await?
+            .ok_or_else(|| DomainError::ConfigError(format!(
+                "Nothing was pushed to {} yet. Run `shellbe sync push` on the machine with your profiles",
+                self.backend.location(),
+            )))?;
+
+        let key = VaultKey::derive(passphrase.as_bytes(), &envelope.kdf)?;
+        let snapshot = self.open(&envelope, &key)?;
+
+        let mut changed = Vec::new();
+        for (file, remote) in snapshot.files {
+            // Only known data files; a crafted snapshot can't write elsewhere
+            if !SYNC_DATA_FILES.contains(&file.as_str()) {
+                continue;
+            }
+
+            let path = self.data_dir.join(&file);
+            // History stays in the format it's stored in here, whatever the other machine uses
+            let (local, format) = if path.exists() {
+                let content = self.storage.read(&path)?;
+                let (value, format) = parse(&file, &content)?;
+                (Some(value), format)
+            } else {
+                (None, HistoryFormat::Json)
+            };
+
+            let merged = match &local {
+                Some(local) => merge(local.clone(), remote),
+                None => remote,
+            };
+            if local.as_ref() == Some(&merged) {
+                continue;
+            }
+
+            self.write(&path, &merged, format).await?;
+            changed.push(file);
+        }
+
+        Ok(changed)
+    }
+
+    /// What the backend holds, without decrypting it
+    pub async fn status(&self) -> Result<SyncStatus, DomainError> {
+        let envelope = self.download().await?;
+
+        Ok(SyncStatus {
+            backend: self.backend.name().to_string(),
+            location: self.backend.location().to_string(),
+            pushed_at: envelope.as_ref().map(|envelope| envelope.pushed_at),
+            pushed_by: envelope.map(|envelope| envelope.pushed_by),
+        })
+    }
+
+    async fn download(&self) -> Result<Option<SyncEnvelope>, DomainError> {
+        let Some(data) = self.backend.download().await? else {
+            return Ok(None);
+        };
+
+        let envelope: SyncEnvelope = serde_json::from_slice(&data)
+            .map_err(|e| DomainError::ConfigError(format!(
+                "{} doesn't hold a ShellBe sync snapshot: {}",
+                self.backend.location(), e,
+            )))?;
+
+        if envelope.shellbe_sync > SYNC_FORMAT_VERSION {
+            return Err(DomainError::ConfigError(format!(
+                "The sync snapshot uses format version {}; update ShellBe to sync with it",
+                envelope.shellbe_sync,
+            )));
+        }
+
+        Ok(Some(envelope))
+    }
+
+    fn open(&self, envelope: &SyncEnvelope, key: &VaultKey) -> Result<SyncSnapshot, DomainError> {
+        let plaintext = envelope.snapshot.open(key)
+            .map_err(|_| DomainError::ConfigError(format!(
+                "Wrong sync passphrase for the snapshot on {}, or the snapshot was modified",
+                self.backend.location(),
+            )))?;
+
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid sync snapshot: {}", e)))
+    }
+
+    async fn write(&self, path: &std::path::Path, value: &serde_json::Value, format: HistoryFormat) -> Result<(), DomainError> {
+        let mut lock = FileLock::new(path).await;
+        if !lock.acquire(5000).await.map_err(DomainError::IoError)? {
+            return Err(DomainError::ConfigError(format!("Failed to acquire lock for writing {}", path.display())));
+        }
+
+        // History keeps its record layout, so it can still be read from the end
+        let content: Result<Vec<u8>, EncodeError> = match path.ends_with("history.json") {
+            true => serde_json::from_value::<Vec<HistoryEntry>>(value.clone())
+                .map_err(Into::into)
+                .and_then(|entries| encode_history(&entries, format)),
+            false => serde_json::to_vec_pretty(value).map_err(Into::into),
+        };
+        let content = Zeroizing::new(content
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize {}: {}", path.display(), e)))?);
+
+        let temp_path = path.with_extension("temp");
+        self.storage.write(&temp_path, &content)?;
+        fs::rename(&temp_path, path).map_err(DomainError::IoError)?;
+
+        lock.release().await.map_err(DomainError::IoError)
+    }
+}
+
+/// Combine a local data file with the snapshot's copy
+///
+/// Parse a data file as JSON; history may also be stored in the binary format
+fn parse(file: &str, content: &[u8]) -> Result<(serde_json::Value, HistoryFormat), DomainError> {
+    if file == "history.json" {
+        let (entries, format) = decode_history(content)?;
+        let value = serde_json::to_value(entries)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to parse {}: {}", file, e)))?;
+        return Ok((value, format));
+    }
+
+    let value = serde_json::from_slice(content)
+        .map_err(|e| DomainError::ConfigError(format!("Failed to parse {}: {}", file, e)))?;
+    Ok((value, HistoryFormat::Json))
+}
+
+/// Maps (profiles, aliases) take the snapshot's entry for each name; lists (history)
+/// keep every entry once, in chronological order.
+fn merge(local: serde_json::Value, remote: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match (local, remote) {
+        (Value::Object(mut local), Value::Object(remote)) => {
+            local.extend(remote);
+            Value::Object(local)
+        },
+        (Value::Array(mut local), Value::Array(remote)) => {
+            for entry in remote {
+                if !local.contains(&entry) {
+                    local.push(entry);
+                }
+            }
+            // UTC RFC 3339 timestamps sort chronologically as text
+            local.sort_by(|a, b| a["timestamp"].as_str().cmp(&b["timestamp"].as_str()));
+            Value::Array(local)
+        },
+        (_, remote) => remote,
+    }
+}
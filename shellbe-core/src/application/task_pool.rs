@@ -0,0 +1,144 @@
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Notify, Semaphore};
+
+/// Hosts or plugins worked on at once by bulk commands unless `--jobs` says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 16;
+
+/// Runs a task for many items with at most `limit` running at once
+///
+/// Bulk commands like `test --all` use it so hundreds of hosts don't open hundreds of
+/// sockets at the same time. Cancelling stops tasks that are running and skips the rest.
+pub struct TaskPool {
+    limit: usize,
+    cancellation: Arc<Cancellation>,
+}
+
+/// Cancels the [`TaskPool`] it came from; can be moved to another task, e.g. a Ctrl-C handler
+#[derive(Clone)]
+pub struct TaskPoolCanceller {
+    cancellation: Arc<Cancellation>,
+}
+
+/// What a [`TaskPool::run`] did
+pub struct PoolOutcome<T, R> {
+    /// Items whose task finished, with its result, in the order the items were given
+    pub finished: Vec<(T, R)>,
+    /// Items that were skipped or stopped because the pool was cancelled
+    pub cancelled: Vec<T>,
+}
+
+#[derive(Default)]
+struct Cancellation {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl Cancellation {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    async fn cancelled(&self) {
+        loop {
+            // Registered before the check, so a cancel in between still wakes it
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl TaskPool {
+    /// Pool running at most `limit` tasks at once; a limit of 0 is treated as 1
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            cancellation: Arc::new(Cancellation::default()),
+        }
+    }
+
+    /// Handle for cancelling this pool
+    pub fn canceller(&self) -> TaskPoolCanceller {
+        TaskPoolCanceller { cancellation: self.cancellation.clone() }
+    }
+
+    /// Run `task` for every item, calling `on_done` as each one finishes
+    pub async fn run<T, R, F, Fut>(&self, items: Vec<T>, task: F, mut on_done: impl FnMut(&T, &R)) -> PoolOutcome<T, R>
+    where
+        T: Clone,
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        let semaphore = Semaphore::new(self.limit);
+        let cancellation = &self.cancellation;
+
+        let mut running: FuturesUnordered<_> = items.into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let semaphore = &semaphore;
+                let task = &task;
+                async move {
+                    let permit = tokio::select! {
+                        permit = semaphore.acquire() => permit.ok(),
+                        _ = cancellation.cancelled() => None,
+                    };
+                    if permit.is_none() || cancellation.is_cancelled() {
+                        return (index, item, None);
+                    }
+
+                    let result = tokio::select! {
+                        result = task(item.clone()) => Some(result),
+                        _ = cancellation.cancelled() => None,
+                    };
+                    (index, item, result)
+                }
+            })
+            .collect();
+
+        let mut finished = Vec::new();
+        let mut cancelled = Vec::new();
+        while let Some((index, item, result)) = running.next().await {
+            match result {
+                Some(result) => {
+                    on_done(&item, &result);
+                    finished.push((index, item, result));
+                },
+                None => cancelled.push((index, item)),
+            }
+        }
+
+        finished.sort_by_key(|(index, _, _)| *index);
+        cancelled.sort_by_key(|(index, _)| *index);
+
+        PoolOutcome {
+            finished: finished.into_iter().map(|(_, item, result)| (item, result)).collect(),
+            cancelled: cancelled.into_iter().map(|(_, item)| item).collect(),
+        }
+    }
+}
+
+impl TaskPoolCanceller {
+    /// Stop running tasks and skip the ones not started yet
+    pub fn cancel(&self) {
+        self.cancellation.cancel();
+    }
+
+    /// Cancel when Ctrl-C is pressed; the returned handle stops listening when aborted
+    pub fn cancel_on_ctrl_c(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                self.cancel();
+            }
+        })
+    }
+}
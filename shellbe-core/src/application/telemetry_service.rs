@@ -0,0 +1,247 @@
+use crate::application::settings_service::TelemetrySettings;
+use crate::application::update_service::CURRENT_VERSION;
+use crate::domain::DomainError;
+use crate::utils::http_client;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File in the data directory holding the counters not sent yet
+pub const TELEMETRY_FILE: &str = "telemetry.json";
+
+/// Where counters are sent unless `[telemetry] endpoint` says otherwise
+pub const DEFAULT_TELEMETRY_ENDPOINT: &str = "https://telemetry.shellbe.dev/v1/usage";
+
+/// Set to anything but `0` to turn telemetry off whatever the settings say
+pub const DO_NOT_TRACK_ENV: &str = "DO_NOT_TRACK";
+
+/// Counters are sent at most this often
+pub const TELEMETRY_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long a command finishing before its telemetry is sent waits for it
+pub const TELEMETRY_SEND_GRACE: Duration = Duration::from_secs(1);
+
+/// Everything telemetry reports: counts, the version and the platform
+///
+/// There is no user, machine or installation ID, and no profile names, hosts or
+/// arguments; commands are counted by their top-level name only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    /// ShellBe version
+    pub version: String,
+    /// Operating system, e.g. `linux`
+    pub os: String,
+    /// CPU architecture, e.g. `x86_64`
+    pub arch: String,
+    /// Start of the period the counts cover
+    pub since: DateTime<Utc>,
+    /// Runs of each command, e.g. `connect`
+    pub commands: BTreeMap<String, u64>,
+    /// Failed runs by error category, e.g. `connection`
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl TelemetryReport {
+    fn new() -> Self {
+        Self {
+            version: CURRENT_VERSION.to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            since: Utc::now(),
+            commands: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+
+    /// Whether there is nothing to send
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.errors.is_empty()
+    }
+
+    /// Take the counts of a report that was sent out of this one
+    fn subtract(&mut self, sent: &TelemetryReport) {
+        for (counts, sent) in [(&mut self.commands, &sent.commands), (&mut self.errors, &sent.errors)] {
+            for (name, count) in sent {
+                if let Some(left) = counts.get_mut(name) {
+                    *left = left.saturating_sub(*count);
+                }
+            }
+            counts.retain(|_, count| *count > 0);
+        }
+        self.since = Utc::now();
+    }
+}
+
+/// Counts command runs and failures, for users who opted in, and sends the counts
+/// to the telemetry endpoint once a day
+///
+/// Counters are written after each command; concurrent runs may lose a count,
+/// which is fine for aggregate numbers.
+pub struct TelemetryService {
+    path: PathBuf,
+    enabled: bool,
+    endpoint: String,
+}
+
+impl TelemetryService {
+    /// Service keeping its counters in `data_dir`
+    pub fn new(data_dir: &Path, settings: &TelemetrySettings) -> Self {
+        Self {
+            path: data_dir.join(TELEMETRY_FILE),
+            enabled: settings.enabled && !do_not_track(),
+            endpoint: settings.endpoint.clone(),
+        }
+    }
+
+    /// Whether counters are kept and sent
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether `DO_NOT_TRACK` overrides the settings
+    pub fn do_not_track(&self) -> bool {
+        do_not_track()
+    }
+
+    /// Where counters are sent
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Counts waiting to be sent
+    pub fn pending(&self) -> TelemetryReport {
+        fs::read(&self.path).ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_else(TelemetryReport::new)
+    }
+
+    /// Count a run of `command`, and its failure in `error_category` if it failed
+    pub fn record(&self, command: &str, error_category: Option<&str>) -> Result<(), DomainError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut report = self.pending();
+        *report.commands.entry(command.to_string()).or_default() += 1;
+        if let Some(category) = error_category {
+            *report.errors.entry(category.to_string()).or_default() += 1;
+        }
+        self.save(&report)
+    }
+
+    /// Drop the counts not sent yet
+    pub fn clear(&self) -> Result<(), DomainError> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(DomainError::IoError(e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Send the counts when they cover more than [`TELEMETRY_INTERVAL`], and start over
+    ///
+    /// Counts stay on disk until the endpoint accepts them, so the task can be aborted at
+    /// any time. Failures are only logged, so telemetry never gets in the way of a command.
+    pub fn spawn_send(&self) -> Option<tokio::task::JoinHandle<()>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let report = self.pending();
+        let age = Utc::now().signed_duration_since(report.since).to_std().unwrap_or_default();
+        if report.is_empty() || age < TELEMETRY_INTERVAL {
+            return None;
+        }
+
+        let service = Self { path: self.path.clone(), enabled: self.enabled, endpoint: self.endpoint.clone() };
+
+        Some(tokio::spawn(async move {
+            let sent = http_client().post(&service.endpoint)
+                .json(&report)
+                .send().await
+                .and_then(|response| response.error_for_status());
+
+            if let Err(e) = sent {
+                tracing::debug!("Failed to send telemetry to {}: {}", service.endpoint, e);
+                return;
+            }
+
+            // Counts recorded while sending stay for the next report
+            let mut pending = service.pending();
+            pending.subtract(&report);
+            if let Err(e) = service.save(&pending) {
+                tracing::debug!("Failed to reset telemetry counters in {}: {}", service.path.display(), e);
+            }
+        }))
+    }
+
+    fn save(&self, report: &TelemetryReport) -> Result<(), DomainError> {
+        let content = serde_json::to_vec_pretty(report)
+            .map_err(|e| DomainError::IoError(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(DomainError::IoError)?;
+        }
+        fs::write(&self.path, content).map_err(DomainError::IoError)
+    }
+}
+
+/// The `DO_NOT_TRACK` convention: set and not `0` means no telemetry
+fn do_not_track() -> bool {
+    std::env::var(DO_NOT_TRACK_ENV).map_or(false, |value| !value.is_empty() && value != "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(dir: &Path, enabled: bool) -> TelemetryService {
+        let settings = TelemetrySettings { enabled, ..TelemetrySettings::default() };
+        TelemetryService { enabled, ..TelemetryService::new(dir, &settings) }
+    }
+
+    #[test]
+    fn test_record_counts_commands_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let telemetry = service(dir.path(), true);
+
+        telemetry.record("connect", None).unwrap();
+        telemetry.record("connect", Some("connection")).unwrap();
+        telemetry.record("list", None).unwrap();
+
+        let pending = telemetry.pending();
+        assert_eq!(pending.commands.get("connect"), Some(&2));
+        assert_eq!(pending.commands.get("list"), Some(&1));
+        assert_eq!(pending.errors.get("connection"), Some(&1));
+        assert_eq!(pending.version, CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_disabled_records_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let telemetry = service(dir.path(), false);
+
+        telemetry.record("connect", Some("connection")).unwrap();
+
+        assert!(!dir.path().join(TELEMETRY_FILE).exists());
+        assert!(telemetry.spawn_send().is_none());
+    }
+
+    #[test]
+    fn test_subtract_keeps_counts_recorded_while_sending() {
+        let dir = tempfile::tempdir().unwrap();
+        let telemetry = service(dir.path(), true);
+        telemetry.record("connect", Some("connection")).unwrap();
+        let sent = telemetry.pending();
+
+        telemetry.record("connect", None).unwrap();
+        telemetry.record("list", None).unwrap();
+
+        let mut pending = telemetry.pending();
+        pending.subtract(&sent);
+        assert_eq!(pending.commands.get("connect"), Some(&1));
+        assert_eq!(pending.commands.get("list"), Some(&1));
+        assert!(pending.errors.is_empty());
+    }
+}
\ No newline at end of file
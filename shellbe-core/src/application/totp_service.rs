@@ -0,0 +1,185 @@
+use crate::domain::{DomainError, Sensitive};
+use crate::infrastructure::EncryptedStorage;
+use crate::utils::{register_secret, FileLock};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// File in the data directory holding TOTP secrets; only ever written encrypted
+pub const TOTP_FILE: &str = "totp.json";
+
+/// Seconds each code is valid for (RFC 6238 default)
+const TOTP_STEP_SECONDS: i64 = 30;
+
+/// Number of digits in a code
+const TOTP_DIGITS: u32 = 6;
+
+/// RFC 4648 base32 alphabet used by authenticator apps
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// A one-time code and how long it stays valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotpCode {
+    /// The code, zero-padded
+    pub code: String,
+    /// Seconds until the next code
+    pub valid_for: i64,
+}
+
+/// Stores per-profile TOTP secrets in the vault and generates their codes
+pub struct TotpService {
+    path: PathBuf,
+    storage: EncryptedStorage,
+}
+
+impl TotpService {
+    /// Secrets in the given data directory, read and written through the vault storage
+    pub fn new(data_dir: &Path, storage: EncryptedStorage) -> Self {
+        Self {
+            path: data_dir.join(TOTP_FILE),
+            storage,
+        }
+    }
+
+    /// Check if a profile has a TOTP secret
+    pub fn has_secret(&self, profile: &str) -> Result<bool, DomainError> {
+        Ok(self.load()?.contains_key(profile))
+    }
+
+    /// Store a profile's secret, given as base32 or an `otpauth://` URI
+    ///
+    /// Secrets are only stored encrypted, so the vault has to be set up and unlocked.
+    pub async fn set_secret(&self, profile: &str, secret: &str) -> Result<(), DomainError> {
+        if !self.storage.is_encrypted() {
+            return Err(DomainError::ConfigError(
+                "TOTP secrets are only stored in the encrypted vault. Run `shellbe vault init` first".to_string(),
+            ));
+        }
+
+        let secret = normalize_secret(secret)?;
+
+        let mut secrets = self.load()?;
+        secrets.insert(profile.to_string(), Sensitive::new(secret.to_string()));
+        self.save(&secrets).await
+    }
+
+    /// Forget a profile's secret; returns whether it had one
+    pub async fn remove_secret(&self, profile: &str) -> Result<bool, DomainError> {
+        let mut secrets = self.load()?;
+        if secrets.remove(profile).is_none() {
+            return Ok(false);
+        }
+
+        self.save(&secrets).await?;
+        Ok(true)
+    }
+
+    /// Current code for a profile, if it has a secret
+    pub fn code(&self, profile: &str, now: DateTime<Utc>) -> Result<Option<TotpCode>, DomainError> {
+        let secrets = self.load()?;
+        let Some(secret) = secrets.get(profile) else {
+            return Ok(None);
+        };
+
+        register_secret(secret.expose());
+        Ok(Some(generate_code(&decode_base32(secret.expose())?, now.timestamp())))
+    }
+
+    fn load(&self) -> Result<HashMap<String, Sensitive<String>>, DomainError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = self.storage.read(&self.path)?;
+        serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to parse {}: {}", TOTP_FILE, e)))
+    }
+
+    async fn save(&self, secrets: &HashMap<String, Sensitive<String>>) -> Result<(), DomainError> {
+        let mut lock = FileLock::new(&self.path).await;
+        if !lock.acquire(5000).await.map_err(DomainError::IoError)? {
+            return Err(DomainError::ConfigError(format!("Failed to acquire lock for writing {}", TOTP_FILE)));
+        }
+
+        let content = Zeroizing::new(serde_json::to_vec_pretty(secrets)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize TOTP secrets: {}", e)))?);
+
+        let temp_path = self.path.with_extension("temp");
+        self.storage.write(&temp_path, &content)?;
+        fs::rename(&temp_path, &self.path).map_err(DomainError::IoError)?;
+
+        lock.release().await.map_err(DomainError::IoError)
+    }
+}
+
+/// Base32 secret from user input: plain base32 (spaces and case ignored) or an `otpauth://` URI
+fn normalize_secret(input: &str) -> Result<Zeroizing<String>, DomainError> {
+    let input = input.trim();
+
+    let secret = if input.starts_with("otpauth://") {
+        input.split_once('?')
+            .map(|(_, query)| query)
+            .unwrap_or_default()
+            .split('&')
+            .find_map(|param| param.strip_prefix("secret="))
+            .ok_or_else(|| DomainError::ConfigError("The otpauth URI has no secret".to_string()))?
+    } else {
+        input
+    };
+
+    let secret: String = secret.chars()
+        .filter(|c| !c.is_whitespace() && *c != '=' && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    if decode_base32(&secret)?.is_empty() {
+        return Err(DomainError::ConfigError("The TOTP secret is empty".to_string()));
+    }
+
+    Ok(Zeroizing::new(secret))
+}
+
+/// Decode unpadded RFC 4648 base32
+fn decode_base32(input: &str) -> Result<Zeroizing<Vec<u8>>, DomainError> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(input.len() * 5 / 8));
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+
+    for c in input.bytes() {
+        let value = BASE32_ALPHABET.iter()
+            .position(|&a| a == c.to_ascii_uppercase())
+            .ok_or_else(|| DomainError::ConfigError("The TOTP secret is not valid base32".to_string()))?;
+
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// RFC 6238 code with HMAC-SHA1, 30 second steps and 6 digits
+fn generate_code(secret: &[u8], unix_time: i64) -> TotpCode {
+    let counter = unix_time.div_euclid(TOTP_STEP_SECONDS) as u64;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+
+    TotpCode {
+        code: format!("{:0width$}", binary % 10u32.pow(TOTP_DIGITS), width = TOTP_DIGITS as usize),
+        valid_for: TOTP_STEP_SECONDS - unix_time.rem_euclid(TOTP_STEP_SECONDS),
+    }
+}
@@ -0,0 +1,601 @@
+use crate::application::progress::{NoProgress, Progress, ProgressStep};
+use crate::application::settings_service::{UpdateChannel, UpdateCheckFrequency, UpdateSettings};
+use crate::domain::DomainError;
+use crate::utils::http_client;
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Version of shellbe-core; it is versioned separately from the CLI, so update checks
+/// compare releases against the version the embedding binary passes in
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name release binaries start with
+const BINARY_NAME: &str = "shellbe";
+
+/// Most recent releases looked at for the newest one on a channel
+const RELEASES_PER_PAGE: usize = 30;
+
+/// Release asset listing the SHA-256 of every other asset, in `sha256sum` format
+const CHECKSUMS_ASSET: &str = "SHA256SUMS";
+
+/// File in the cache directory remembering the last background update check
+pub const UPDATE_CHECK_FILE: &str = "update-check.json";
+
+/// How long a command finishing before its background update check waits for it
+pub const UPDATE_CHECK_GRACE: Duration = Duration::from_secs(1);
+
+/// Target triple this binary was built for, e.g. `x86_64-unknown-linux-gnu`
+const TARGET: &str = env!("SHELLBE_TARGET");
+
+/// Error type for self-update operations
+#[derive(thiserror::Error, Debug)]
+pub enum UpdateError {
+    #[error("HTTP error: {0}")]
+    HttpError(#[from] reqwest::Error),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Domain error: {0}")]
+    DomainError(#[from] DomainError),
+
+    #[error("Update error: {0}")]
+    Other(String),
+}
+
+/// Result type for self-update operations
+pub type Result<T> = std::result::Result<T, UpdateError>;
+
+/// GitHub release information
+#[derive(Debug, serde::Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    /// Release notes, in Markdown
+    #[serde(default)]
+    body: Option<String>,
+    assets: Vec<GithubAsset>,
+}
+
+/// GitHub release asset
+#[derive(Debug, serde::Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl GithubRelease {
+    fn asset(&self, name: &str) -> Option<&GithubAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Release asset with the binary for this platform, e.g. `shellbe-x86_64-unknown-linux-gnu`
+fn asset_name() -> String {
+    format!("{}-{}{}", BINARY_NAME, TARGET, env::consts::EXE_SUFFIX)
+}
+
+/// Checksum of `name` in a `sha256sum` listing; binary-mode entries are marked with `*`
+fn checksum_for(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (checksum, file) = line.split_once(char::is_whitespace)?;
+        (file.trim_start().trim_start_matches('*') == name).then(|| checksum.to_string())
+    })
+}
+
+/// Version to move to from `current`, given every `published` version
+///
+/// That is the newest release on `channel` when it is newer, ignoring build metadata. A
+/// published pre-release that isn't on `channel`, as after switching from beta back to
+/// stable, moves to the channel's newest release even when it is older; local builds
+/// aren't published and are only ever updated to newer versions.
+fn update_version(current: &str, channel: UpdateChannel, published: &[Version]) -> Option<Version> {
+    let latest = published.iter().filter(|version| channel.allows(version)).max()?;
+    let Ok(current) = Version::parse(current) else {
+        return Some(latest.clone());
+    };
+
+    let left_channel = !channel.allows(&current) && published.contains(&current);
+    match latest.cmp_precedence(&current) {
+        Ordering::Greater => Some(latest.clone()),
+        Ordering::Less if left_channel => Some(latest.clone()),
+        _ => None,
+    }
+}
+
+/// Replace the executable at `exe` with `binary` if its SHA-256 is `expected`
+fn install_verified(exe: &Path, name: &str, binary: &[u8], expected: &str) -> Result<()> {
+    let actual = format!("{:x}", Sha256::digest(binary));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(UpdateError::Other(format!(
+            "Checksum mismatch for {}: expected {}, got {}; the executable was not replaced", name, expected, actual
+        )));
+    }
+
+    replace_executable(exe, binary)
+}
+
+/// Put `binary` in place of the executable at `exe`
+///
+/// The new file is written next to it and renamed over it, so an interrupted update leaves
+/// the old executable. Windows can't replace a running executable, so it is moved aside first.
+fn replace_executable(exe: &Path, binary: &[u8]) -> Result<()> {
+    let dir = exe.parent()
+        .ok_or_else(|| UpdateError::Other(format!("Executable path {} has no directory", exe.display())))?;
+
+    let mut staged = tempfile::NamedTempFile::new_in(dir)?;
+    staged.write_all(binary)?;
+    staged.as_file().sync_all()?;
+    fs::set_permissions(staged.path(), fs::metadata(exe)?.permissions())?;
+
+    #[cfg(windows)]
+    let old = {
+        let old = exe.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)?;
+        old
+    };
+
+    if let Err(e) = staged.persist(exe) {
+        // Put the old executable back rather than leave none
+        #[cfg(windows)]
+        let _ = fs::rename(&old, exe);
+        return Err(UpdateError::IoError(e.error));
+    }
+
+    Ok(())
+}
+
+/// Where releases are looked up, from the `[updates]` settings
+#[derive(Debug, Clone)]
+pub struct UpdateSource {
+    /// GitHub-compatible API, e.g. `https://api.github.com`
+    pub api_url: String,
+    /// Repository as `owner/name`
+    pub repository: String,
+}
+
+impl Default for UpdateSource {
+    fn default() -> Self {
+        Self::from(&UpdateSettings::default())
+    }
+}
+
+impl From<&UpdateSettings> for UpdateSource {
+    fn from(settings: &UpdateSettings) -> Self {
+        Self {
+            api_url: settings.api_url.clone(),
+            repository: settings.repository.clone(),
+        }
+    }
+}
+
+impl UpdateSource {
+    /// URL listing the most recent releases
+    fn releases_url(&self) -> String {
+        format!("{}/repos/{}/releases?per_page={}",
+                self.api_url.trim_end_matches('/'), self.repository.trim_matches('/'), RELEASES_PER_PAGE)
+    }
+}
+
+/// Notes of one release, in Markdown
+#[derive(Debug, Clone)]
+pub struct ReleaseNotes {
+    pub version: String,
+    pub notes: String,
+}
+
+/// A release to update to
+#[derive(Debug, Clone)]
+pub struct AvailableUpdate {
+    pub version: String,
+    /// Notes of every release between the running version and `version`, newest first;
+    /// releases without notes are left out
+    pub notes: Vec<ReleaseNotes>,
+}
+
+/// Service for handling application self-updates
+pub struct UpdateService {
+    client: Client,
+    current_version: String,
+    progress: Arc<dyn Progress>,
+    source: UpdateSource,
+}
+
+impl UpdateService {
+    /// Create an update service for the binary at `current_version`
+    pub fn new(current_version: impl Into<String>) -> Self {
+        Self {
+            client: http_client(),
+            current_version: current_version.into(),
+            progress: Arc::new(NoProgress),
+            source: UpdateSource::default(),
+        }
+    }
+
+    /// Report update progress to the given receiver
+    pub fn set_progress(&mut self, progress: Arc<dyn Progress>) {
+        self.progress = progress;
+    }
+
+    /// Look for releases in the given repository instead of the official one
+    pub fn set_source(&mut self, source: UpdateSource) {
+        self.source = source;
+    }
+
+    /// Published releases on every channel, newest first; tags that aren't versions are skipped
+    async fn releases(&self) -> Result<Vec<(Version, GithubRelease)>> {
+        let url = self.source.releases_url();
+
+        let response = self.client
+            .get(&url)
+            .header("User-Agent", format!("ShellBe/{}", self.current_version))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(UpdateError::Other(format!("Failed to check for updates: {}", response.status())));
+        }
+
+        let releases: Vec<GithubRelease> = response.json().await?;
+
+        let mut releases: Vec<(Version, GithubRelease)> = releases.into_iter()
+            .filter(|release| !release.draft)
+            .filter_map(|release| Some((Version::parse(release.tag_name.trim_start_matches('v')).ok()?, release)))
+            .collect();
+        releases.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Ok(releases)
+    }
+
+    /// The release to install from `channel`, if any, with the other published releases
+    async fn update_target(&self, channel: UpdateChannel) -> Result<Option<(Version, Vec<(Version, GithubRelease)>)>> {
+        let releases = self.releases().await?;
+        let published: Vec<Version> = releases.iter().map(|(version, _)| version.clone()).collect();
+
+        Ok(update_version(&self.current_version, channel, &published)
+            .map(|target| (target, releases)))
+    }
+
+    /// Check if an update is available on `channel`
+    pub async fn check_for_update(&self, channel: UpdateChannel) -> Result<Option<String>> {
+        Ok(self.available_update(channel).await?.map(|update| update.version))
+    }
+
+    /// The update available on `channel`, with the notes of the releases it brings in
+    ///
+    /// When the update goes back to an older version only the target's notes are given.
+    pub async fn available_update(&self, channel: UpdateChannel) -> Result<Option<AvailableUpdate>> {
+        let Some((target, releases)) = self.update_target(channel).await? else {
+            return Ok(None);
+        };
+        let current = Version::parse(&self.current_version).ok();
+
+        let notes = releases.iter()
+            .filter(|(version, _)| channel.allows(version))
+            .filter(|(version, _)| *version == target || (*version < target && current.as_ref()
+                .map_or(false, |current| version.cmp_precedence(current) == Ordering::Greater)))
+            .filter_map(|(version, release)| {
+                let notes = release.body.as_deref()?.trim();
+                (!notes.is_empty()).then(|| ReleaseNotes { version: version.to_string(), notes: notes.to_string() })
+            })
+            .collect();
+
+        Ok(Some(AvailableUpdate { version: target.to_string(), notes }))
+    }
+
+    /// Whether installing `version` goes back to an older version
+    pub fn is_downgrade(&self, version: &str) -> bool {
+        match (Version::parse(version), Version::parse(&self.current_version)) {
+            (Ok(version), Ok(current)) => version.cmp_precedence(&current) == Ordering::Less,
+            _ => false,
+        }
+    }
+
+    /// Update the application to the latest version on `channel`
+    ///
+    /// Downloads the release's binary for this platform, checks it against the release's
+    /// `SHA256SUMS` and swaps it in for the running executable in one rename.
+    pub async fn update(&self, channel: UpdateChannel) -> Result<()> {
+        let (latest_version, release) = self.update_target(channel).await?
+            .and_then(|(target, releases)| releases.into_iter().find(|(version, _)| *version == target))
+            .ok_or_else(|| UpdateError::Other("No update available".to_string()))?;
+
+        println!("Updating from {} to {}...", self.current_version, latest_version);
+
+        let name = asset_name();
+        let asset = release.asset(&name).ok_or_else(|| UpdateError::Other(format!(
+            "Release {} has no build for this platform ({})", release.tag_name, name
+        )))?;
+        let checksums = release.asset(CHECKSUMS_ASSET).ok_or_else(|| UpdateError::Other(format!(
+            "Release {} has no {} to verify the download with", release.tag_name, CHECKSUMS_ASSET
+        )))?;
+
+        let checksums = String::from_utf8_lossy(&self.download(checksums, false).await?).into_owned();
+        let expected = checksum_for(&checksums, &name).ok_or_else(|| UpdateError::Other(format!(
+            "{} of release {} has no checksum for {}", CHECKSUMS_ASSET, release.tag_name, name
+        )))?;
+
+        let binary = self.download(asset, true).await?;
+        install_verified(&Self::executable_path()?, &name, &binary, &expected)?;
+
+        println!("Update completed successfully!");
+
+        Ok(())
+    }
+
+    /// Download a release asset, showing progress for the large ones
+    async fn download(&self, asset: &GithubAsset, show_progress: bool) -> Result<Vec<u8>> {
+        let mut response = self.client
+            .get(&asset.browser_download_url)
+            .header("User-Agent", format!("ShellBe/{}", self.current_version))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(UpdateError::Other(format!("Failed to download {}: {}", asset.name, response.status())));
+        }
+
+        let progress: &dyn Progress = if show_progress { self.progress.as_ref() } else { &NoProgress };
+        progress.start(ProgressStep::Download, response.content_length());
+
+        let mut data = Vec::with_capacity(response.content_length().unwrap_or(0) as usize);
+        let downloaded = async {
+            while let Some(chunk) = response.chunk().await? {
+                data.extend_from_slice(&chunk);
+                progress.advance(chunk.len() as u64);
+            }
+            Ok::<_, UpdateError>(())
+        }.await;
+
+        progress.finish();
+        downloaded?;
+
+        Ok(data)
+    }
+
+    /// Get the path to the current executable
+    pub fn executable_path() -> Result<PathBuf> {
+        let exe = env::current_exe()
+            .map_err(|e| UpdateError::Other(format!("Failed to get executable path: {}", e)))?;
+
+        Ok(exe)
+    }
+
+    /// Create a backup of the current executable
+    pub fn backup_executable(&self) -> Result<PathBuf> {
+        let exe_path = Self::executable_path()?;
+        let backup_path = exe_path.with_extension(format!("backup.{}", chrono::Utc::now().format("%Y%m%d%H%M%S")));
+
+        fs::copy(&exe_path, &backup_path)?;
+
+        Ok(backup_path)
+    }
+}
+
+/// Result of the last background update check
+#[derive(Debug, Serialize, Deserialize)]
+struct UpdateCheck {
+    checked_at: DateTime<Utc>,
+    channel: UpdateChannel,
+    /// Version to update to, if there was one
+    available: Option<String>,
+}
+
+/// Looks for updates on its own task while other commands run, at most once per
+/// `[updates] check` interval, so they can mention a new version without waiting for GitHub
+pub struct UpdateNotifier {
+    path: PathBuf,
+    current_version: String,
+    interval: Option<Duration>,
+    channel: UpdateChannel,
+    source: UpdateSource,
+}
+
+impl UpdateNotifier {
+    /// Notifier for the binary at `current_version`, remembering its checks in `cache_dir`
+    pub fn new(cache_dir: &Path, current_version: impl Into<String>, frequency: UpdateCheckFrequency, channel: UpdateChannel) -> Self {
+        let interval = match frequency {
+            UpdateCheckFrequency::Never => None,
+            UpdateCheckFrequency::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            UpdateCheckFrequency::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        };
+
+        Self {
+            path: cache_dir.join(UPDATE_CHECK_FILE),
+            current_version: current_version.into(),
+            interval,
+            channel,
+            source: UpdateSource::default(),
+        }
+    }
+
+    /// Look for releases in the given repository instead of the official one
+    pub fn set_source(&mut self, source: UpdateSource) {
+        self.source = source;
+    }
+
+    /// The last check, unless it was for another channel
+    fn last_check(&self) -> Option<UpdateCheck> {
+        let content = fs::read(&self.path).ok()?;
+        let check: UpdateCheck = serde_json::from_slice(&content).ok()?;
+        (check.channel == self.channel).then_some(check)
+    }
+
+    /// Start a check when the last one is older than the interval; the task can be aborted
+    /// at any time and the next command tries again
+    pub fn spawn_check(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.interval?;
+        let last_check = self.last_check();
+        if let Some(last_check) = &last_check {
+            let age = Utc::now().signed_duration_since(last_check.checked_at).to_std().unwrap_or_default();
+            if age < interval {
+                return None;
+            }
+        }
+
+        let path = self.path.clone();
+        let channel = self.channel;
+        let mut service = UpdateService::new(self.current_version.clone());
+        service.set_source(self.source.clone());
+        let previous = last_check.and_then(|check| check.available);
+
+        Some(tokio::spawn(async move {
+            // Offline counts as checked too, so commands don't retry until the next interval
+            let available = match service.check_for_update(channel).await {
+                Ok(available) => available,
+                Err(e) => {
+                    tracing::debug!("Background update check failed: {}", e);
+                    previous
+                },
+            };
+
+            let check = UpdateCheck { checked_at: Utc::now(), channel, available };
+            let saved = path.parent().map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, serde_json::to_vec(&check).unwrap_or_default()));
+            if let Err(e) = saved {
+                tracing::debug!("Failed to save the update check in {}: {}", path.display(), e);
+            }
+        }))
+    }
+
+    /// Version found by the last check, unless it is the one running now
+    pub fn available(&self) -> Option<String> {
+        self.interval?;
+        self.last_check()?.available.filter(|version| *version != self.current_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions.iter().map(|version| Version::parse(version).unwrap()).collect()
+    }
+
+    fn target(current: &str, channel: UpdateChannel, published: &[&str]) -> Option<String> {
+        update_version(current, channel, &versions(published)).map(|version| version.to_string())
+    }
+
+    #[test]
+    fn test_update_version_newer_release() {
+        let published = ["1.2.0", "1.1.0", "1.0.0"];
+
+        assert_eq!(target("1.1.0", UpdateChannel::Stable, &published), Some("1.2.0".to_string()));
+        assert_eq!(target("1.2.0", UpdateChannel::Stable, &published), None);
+        // Compared as versions, not strings
+        assert_eq!(target("1.10.0", UpdateChannel::Stable, &["1.9.0"]), None);
+        assert_eq!(target("1.9.0", UpdateChannel::Stable, &["1.10.0"]), Some("1.10.0".to_string()));
+    }
+
+    #[test]
+    fn test_update_version_local_builds() {
+        // Newer than anything published: never offered a downgrade
+        assert_eq!(target("1.3.0", UpdateChannel::Stable, &["1.2.0"]), None);
+        assert_eq!(target("1.3.0-dev", UpdateChannel::Stable, &["1.2.0"]), None);
+        // Build metadata doesn't make a version newer or older
+        assert_eq!(target("1.2.0+local.5", UpdateChannel::Stable, &["1.2.0"]), None);
+        assert_eq!(target("1.1.0+local.5", UpdateChannel::Stable, &["1.2.0"]), Some("1.2.0".to_string()));
+    }
+
+    #[test]
+    fn test_update_version_pre_releases() {
+        let published = ["1.3.0-rc.1", "1.3.0-beta.2", "1.3.0-beta.1", "1.3.0-nightly.20240101", "1.2.0"];
+
+        // Stable ignores pre-releases
+        assert_eq!(target("1.2.0", UpdateChannel::Stable, &published), None);
+        // rc sorts after beta, and both are on the beta channel
+        assert_eq!(target("1.3.0-beta.1", UpdateChannel::Beta, &published), Some("1.3.0-rc.1".to_string()));
+        assert_eq!(target("1.2.0", UpdateChannel::Beta, &published), Some("1.3.0-rc.1".to_string()));
+        // A release is newer than its pre-releases
+        assert_eq!(target("1.3.0-rc.1", UpdateChannel::Beta, &["1.3.0", "1.3.0-rc.1"]), Some("1.3.0".to_string()));
+        // Nightly takes any pre-release
+        assert_eq!(target("1.2.0", UpdateChannel::Nightly, &published), Some("1.3.0-rc.1".to_string()));
+    }
+
+    #[test]
+    fn test_asset_name() {
+        let name = asset_name();
+        assert!(name.starts_with("shellbe-"));
+        assert!(name.contains(TARGET));
+        assert!(name.ends_with(env::consts::EXE_SUFFIX));
+    }
+
+    #[test]
+    fn test_checksum_for() {
+        let checksums = "\
+            1111  shellbe-x86_64-unknown-linux-gnu\n\
+            2222 *shellbe-x86_64-pc-windows-msvc.exe\n\
+            3333  shellbe-x86_64-unknown-linux-gnu.tar.gz\n";
+
+        assert_eq!(checksum_for(checksums, "shellbe-x86_64-unknown-linux-gnu"), Some("1111".to_string()));
+        // Binary-mode entries
+        assert_eq!(checksum_for(checksums, "shellbe-x86_64-pc-windows-msvc.exe"), Some("2222".to_string()));
+        // Names match exactly: no prefixes and no case folding
+        assert_eq!(checksum_for(checksums, "shellbe-x86_64"), None);
+        assert_eq!(checksum_for(checksums, "SHELLBE-x86_64-unknown-linux-gnu"), None);
+        assert_eq!(checksum_for(checksums, "shellbe-aarch64-apple-darwin"), None);
+        assert_eq!(checksum_for("", "shellbe-x86_64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn test_install_verified_replaces_matching_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("shellbe");
+        fs::write(&exe, b"old build").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        // Checksums may be listed in upper case
+        let expected = format!("{:X}", Sha256::digest(b"new build"));
+        install_verified(&exe, "shellbe", b"new build", &expected).unwrap();
+
+        assert_eq!(fs::read(&exe).unwrap(), b"new build");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&exe).unwrap().permissions().mode() & 0o777, 0o755);
+        }
+    }
+
+    #[test]
+    fn test_install_verified_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("shellbe");
+        fs::write(&exe, b"old build").unwrap();
+
+        let expected = format!("{:x}", Sha256::digest(b"what was published"));
+        let error = install_verified(&exe, "shellbe", b"tampered build", &expected).unwrap_err();
+
+        assert!(error.to_string().contains("Checksum mismatch"));
+        assert_eq!(fs::read(&exe).unwrap(), b"old build");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_update_version_leaving_channel() {
+        let published = ["1.3.0-beta.1", "1.2.0"];
+
+        // A published beta goes back to the newest stable release after switching channels
+        assert_eq!(target("1.3.0-beta.1", UpdateChannel::Stable, &published), Some("1.2.0".to_string()));
+        // An unpublished pre-release stays
+        assert_eq!(target("1.3.0-beta.9", UpdateChannel::Stable, &published), None);
+    }
+}
\ No newline at end of file
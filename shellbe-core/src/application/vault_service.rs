@@ -0,0 +1,420 @@
+use crate::domain::{DomainError, SecretBackend};
+use crate::infrastructure::repositories::encrypted_storage::{
+    EncryptedFile, EncryptedStorage, KdfParams, VaultKey, VAULT_FORMAT_VERSION,
+};
+use crate::application::vault_session::VaultSession;
+use crate::infrastructure::FileSecretBackend;
+use crate::utils::{FileLock, ShellBePaths, ensure_directory, write_private};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Vault header in the data directory; its presence means the vault is set up
+pub const VAULT_FILE: &str = "vault.json";
+
+/// Account the key of an unlocked vault is stored under in the secret backend
+pub const VAULT_KEY_ACCOUNT: &str = "vault-key";
+
+/// Environment variable with the vault passphrase, for scripts that can't type it
+pub const VAULT_PASSPHRASE_ENV: &str = "SHELLBE_VAULT_PASSPHRASE";
+
+/// Data files the vault encrypts
+pub const VAULT_DATA_FILES: &[&str] = &["profiles.json", "aliases.json", "history.json", "totp.json"];
+
+/// Known plaintext sealed into the header to check a passphrase before touching any data
+const CHECK_PLAINTEXT: &[u8] = b"shellbe-vault";
+
+/// What the vault key is derived from
+pub enum VaultSecret {
+    /// A passphrase typed by the user
+    Passphrase(Zeroizing<String>),
+    /// The contents of a keyfile
+    Keyfile(PathBuf),
+}
+
+impl VaultSecret {
+    fn bytes(&self) -> Result<Zeroizing<Vec<u8>>, DomainError> {
+        match self {
+            VaultSecret::Passphrase(passphrase) => Ok(Zeroizing::new(passphrase.as_bytes().to_vec())),
+            VaultSecret::Keyfile(path) => {
+                let contents = Zeroizing::new(fs::read(path).map_err(|e| DomainError::ConfigError(
+                    format!("Failed to read keyfile {}: {}", path.display(), e)
+                ))?);
+
+                if contents.is_empty() {
+                    return Err(DomainError::ConfigError(format!("Keyfile {} is empty", path.display())));
+                }
+
+                Ok(contents)
+            },
+        }
+    }
+}
+
+/// Stored in `vault.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultHeader {
+    version: u32,
+    kdf: KdfParams,
+    /// Keyfile the key is derived from; unset for a passphrase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keyfile: Option<PathBuf>,
+    check: EncryptedFile,
+}
+
+/// State of the vault, as shown by 'vault status'
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    /// Whether the vault is set up
+    pub initialized: bool,
+    /// Whether data can be read without asking for the passphrase
+    pub unlocked: bool,
+    /// Keyfile the key is derived from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyfile: Option<PathBuf>,
+    /// Where the key of an unlocked vault is kept
+    pub key_store: String,
+    /// Minutes without use after which an unlocked vault locks itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_lock_minutes: Option<i64>,
+    /// When the unlocked vault locks itself unless it is used before
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locks_at: Option<DateTime<Utc>>,
+    /// Data files currently stored encrypted
+    pub encrypted_files: Vec<String>,
+}
+
+/// Service for the encrypted profile vault
+///
+/// When set up, profiles, aliases and history are stored encrypted with XChaCha20-Poly1305
+/// under a key derived from a passphrase or keyfile with Argon2id. Unlocking keeps the key
+/// in a secret backend (the OS keychain, or a file in the cache directory) until `vault lock`.
+pub struct VaultService {
+    data_dir: PathBuf,
+    secrets: Arc<dyn SecretBackend>,
+    session: VaultSession,
+}
+
+impl VaultService {
+    /// Create a vault service for the given directories, keeping unlocked keys in the cache directory
+    pub fn new(paths: &ShellBePaths) -> Self {
+        Self {
+            data_dir: paths.data_dir.clone(),
+            secrets: Arc::new(FileSecretBackend::new(&paths.cache_dir)),
+            session: VaultSession::new(&paths.cache_dir, None),
+        }
+    }
+
+    /// Lock an unlocked vault again once it hasn't been used for this long
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.session.set_idle_timeout(idle_timeout);
+    }
+
+    /// Keep the key of an unlocked vault in another secret backend, e.g. the OS keychain
+    pub fn set_secret_backend(&mut self, secrets: Arc<dyn SecretBackend>) {
+        self.secrets = secrets;
+    }
+
+    /// Name of the keychain service for a data directory, so separate directories don't share keys
+    pub fn keychain_service(paths: &ShellBePaths) -> String {
+        format!("shellbe:{}", paths.data_dir.display())
+    }
+
+    /// Whether the vault is set up
+    pub fn is_initialized(&self) -> bool {
+        self.data_dir.join(VAULT_FILE).exists()
+    }
+
+    fn header(&self) -> Result<VaultHeader, DomainError> {
+        let path = self.data_dir.join(VAULT_FILE);
+        let content = fs::read_to_string(&path).map_err(DomainError::IoError)?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid vault header {}: {}", path.display(), e)))
+    }
+
+    fn save_header(&self, header: &VaultHeader) -> Result<(), DomainError> {
+        let content = serde_json::to_string_pretty(header)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize vault header: {}", e)))?;
+
+        write_private(&self.data_dir.join(VAULT_FILE), content.as_bytes()).map_err(DomainError::IoError)
+    }
+
+    /// Keyfile the vault key is derived from, if it uses one
+    pub fn keyfile(&self) -> Result<Option<PathBuf>, DomainError> {
+        Ok(self.header()?.keyfile)
+    }
+
+    /// Current state of the vault
+    pub fn status(&self) -> Result<VaultStatus, DomainError> {
+        let encrypted_files = VAULT_DATA_FILES.iter()
+            .filter(|file| fs::read(self.data_dir.join(file))
+                .map(|contents| EncryptedFile::parse(&contents).is_some())
+                .unwrap_or(false))
+            .map(|file| file.to_string())
+            .collect();
+
+        if !self.is_initialized() {
+            return Ok(VaultStatus {
+                initialized: false,
+                unlocked: true,
+                keyfile: None,
+                key_store: self.secrets.name().to_string(),
+                idle_lock_minutes: None,
+                locks_at: None,
+                encrypted_files,
+            });
+        }
+
+        let keyfile = self.keyfile()?;
+        let session_unlocked = keyfile.is_none() && self.session_key()?.is_some();
+
+        Ok(VaultStatus {
+            initialized: true,
+            unlocked: keyfile.is_some() || session_unlocked,
+            keyfile,
+            key_store: self.secrets.name().to_string(),
+            idle_lock_minutes: self.session.idle_timeout().map(|timeout| timeout.num_minutes()),
+            locks_at: self.session.locks_at().filter(|_| session_unlocked),
+            encrypted_files,
+        })
+    }
+
+    /// Derive the key for a secret and check it against the header
+    pub fn open(&self, secret: &VaultSecret) -> Result<VaultKey, DomainError> {
+        let header = self.header()?;
+        let key = VaultKey::derive(&secret.bytes()?, &header.kdf)?;
+
+        header.check.open(&key)
+            .map_err(|_| DomainError::ConfigError("Wrong vault passphrase or keyfile".to_string()))?;
+
+        Ok(key)
+    }
+
+    /// Key kept by `unlock`, if the vault is unlocked and hasn't been idle too long
+    pub fn session_key(&self) -> Result<Option<VaultKey>, DomainError> {
+        if self.session.is_idle(Utc::now()) {
+            return Ok(None);
+        }
+
+        let Some(encoded) = self.secrets.get(VAULT_KEY_ACCOUNT)?.map(Zeroizing::new) else {
+            return Ok(None);
+        };
+
+        Ok(VaultKey::from_base64(&encoded).ok())
+    }
+
+    /// Lock an unlocked vault that has been idle for longer than the idle timeout
+    ///
+    /// Returns whether it was locked now.
+    pub fn lock_if_idle(&self) -> Result<bool, DomainError> {
+        if !self.session.is_idle(Utc::now()) || self.secrets.get(VAULT_KEY_ACCOUNT)?.is_none() {
+            return Ok(false);
+        }
+
+        self.lock()
+    }
+
+    /// Idle period after which an unlocked vault locks itself
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.session.idle_timeout()
+    }
+
+    /// Name of the secret backend unlocked keys are kept in
+    pub fn key_store(&self) -> &str {
+        self.secrets.name()
+    }
+
+    /// Key to open the data files with without asking: from the keyfile or an unlocked session
+    ///
+    /// `None` when the vault isn't set up, or it is locked and needs the passphrase.
+    pub fn available_key(&self) -> Result<Option<VaultKey>, DomainError> {
+        if !self.is_initialized() {
+            return Ok(None);
+        }
+
+        match self.keyfile()? {
+            Some(keyfile) => self.open(&VaultSecret::Keyfile(keyfile)).map(Some),
+            None => {
+                let key = self.session_key()?;
+                if key.is_some() {
+                    self.session.touch()?;
+                }
+                Ok(key)
+            },
+        }
+    }
+
+    /// Set up the vault and encrypt the data files; returns the files that were encrypted
+    ///
+    /// A passphrase vault is left unlocked.
+    pub async fn init(&self, secret: &VaultSecret) -> Result<Vec<String>, DomainError> {
+        if self.is_initialized() {
+            return Err(DomainError::ConfigError("The vault is already set up".to_string()));
+        }
+
+        ensure_directory(&self.data_dir).await.map_err(DomainError::IoError)?;
+
+        // Header first: if encrypting stops half way, the rest is still readable as plain JSON
+        let (header, key) = new_header(secret)?;
+        self.save_header(&header)?;
+        let encrypted = self.stage_files(&EncryptedStorage::plain(), &EncryptedStorage::with_key(key.clone())).await?.commit()?;
+
+        if header.keyfile.is_none() {
+            self.save_session(&key)?;
+        }
+
+        Ok(encrypted)
+    }
+
+    /// Check the secret and remember the key until `lock`
+    pub fn unlock(&self, secret: &VaultSecret) -> Result<(), DomainError> {
+        let key = self.open(secret)?;
+        self.save_session(&key)
+    }
+
+    /// Forget the kept key; returns whether the vault was unlocked
+    pub fn lock(&self) -> Result<bool, DomainError> {
+        self.session.clear()?;
+        self.secrets.delete(VAULT_KEY_ACCOUNT)
+    }
+
+    /// Re-encrypt the data files under a new passphrase or keyfile
+    pub async fn change_secret(&self, current: &VaultSecret, new: &VaultSecret) -> Result<(), DomainError> {
+        let old_key = self.open(current)?;
+        let was_unlocked = self.session_key()?.is_some();
+
+        // Nothing is replaced until every file is re-encrypted and the new header is saved,
+        // so a failure on the way leaves the vault readable with the current secret
+        let (header, new_key) = new_header(new)?;
+        let staged = self.stage_files(&EncryptedStorage::with_key(old_key), &EncryptedStorage::with_key(new_key.clone())).await?;
+        self.save_header(&header)?;
+        staged.commit()?;
+
+        // A cached session would hold the old key
+        self.lock()?;
+        if was_unlocked && header.keyfile.is_none() {
+            self.save_session(&new_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read every data file with one storage and write it with another next to the original
+    ///
+    /// The files stay locked until the staged copies are committed or dropped.
+    async fn stage_files(&self, from: &EncryptedStorage, to: &EncryptedStorage) -> Result<StagedFiles, DomainError> {
+        let mut staged = StagedFiles::default();
+
+        for file in VAULT_DATA_FILES {
+            let path = self.data_dir.join(file);
+            if !path.exists() {
+                continue;
+            }
+
+            let mut lock = FileLock::new(&path).await;
+            if !lock.acquire(5000).await.map_err(DomainError::IoError)? {
+                return Err(DomainError::ConfigError(format!("Failed to acquire lock for writing {}", file)));
+            }
+
+            let content = from.read(&path)?;
+            let temp_path = path.with_extension("temp");
+            staged.files.push((file.to_string(), temp_path.clone(), path, lock));
+            to.write(&temp_path, &content)?;
+        }
+
+        Ok(staged)
+    }
+
+    fn save_session(&self, key: &VaultKey) -> Result<(), DomainError> {
+        self.secrets.set(VAULT_KEY_ACCOUNT, &key.to_base64())?;
+        self.session.touch()
+    }
+}
+
+/// Data files written next to the originals, not yet moved into place
+#[derive(Default)]
+struct StagedFiles {
+    files: Vec<(String, PathBuf, PathBuf, FileLock)>,
+}
+
+impl StagedFiles {
+    /// Replace the originals with the staged files; returns the files that were replaced
+    fn commit(mut self) -> Result<Vec<String>, DomainError> {
+        let mut replaced = Vec::new();
+
+        for (file, temp_path, path, _lock) in std::mem::take(&mut self.files) {
+            fs::rename(&temp_path, &path).map_err(DomainError::IoError)?;
+            replaced.push(file);
+        }
+
+        Ok(replaced)
+    }
+}
+
+impl Drop for StagedFiles {
+    fn drop(&mut self) {
+        for (_, temp_path, _, _) in &self.files {
+            let _ = fs::remove_file(temp_path);
+        }
+    }
+}
+
+/// A fresh header (new salt) for a secret, with the key it derives
+fn new_header(secret: &VaultSecret) -> Result<(VaultHeader, VaultKey), DomainError> {
+    let kdf = KdfParams::generate();
+    let key = VaultKey::derive(&secret.bytes()?, &kdf)?;
+
+    let keyfile = match secret {
+        VaultSecret::Keyfile(path) => Some(path.canonicalize().unwrap_or_else(|_| path.clone())),
+        VaultSecret::Passphrase(_) => None,
+    };
+
+    let header = VaultHeader {
+        version: VAULT_FORMAT_VERSION,
+        kdf,
+        keyfile,
+        check: EncryptedFile::seal(&key, CHECK_PLAINTEXT)?,
+    };
+
+    Ok((header, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::DirectoryLayout;
+
+    fn passphrase(value: &str) -> VaultSecret {
+        VaultSecret::Passphrase(Zeroizing::new(value.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_failed_change_secret_keeps_current_secret() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ShellBePaths {
+            config_dir: dir.path().to_path_buf(),
+            data_dir: dir.path().to_path_buf(),
+            cache_dir: dir.path().join("cache"),
+            layout: DirectoryLayout::Legacy,
+            overridden: true,
+        };
+        fs::write(dir.path().join("profiles.json"), b"[\"web-1\"]").unwrap();
+        fs::write(dir.path().join("aliases.json"), b"[\"web\"]").unwrap();
+
+        let vault = VaultService::new(&paths);
+        vault.init(&passphrase("old secret")).await.unwrap();
+
+        // Another process holds aliases.json, after profiles.json was already re-encrypted
+        fs::write(dir.path().join("aliases.lock"), b"").unwrap();
+        assert!(vault.change_secret(&passphrase("old secret"), &passphrase("new secret")).await.is_err());
+
+        let storage = EncryptedStorage::with_key(vault.open(&passphrase("old secret")).unwrap());
+        assert_eq!(&storage.read(&dir.path().join("profiles.json")).unwrap()[..], b"[\"web-1\"]");
+        assert_eq!(&storage.read(&dir.path().join("aliases.json")).unwrap()[..], b"[\"web\"]");
+        assert!(!dir.path().join("profiles.temp").exists());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::domain::DomainError;
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// File in the cache directory recording when the unlocked vault was last used
+pub const VAULT_ACTIVITY_FILE: &str = "vault-activity";
+
+/// Tracks when an unlocked vault was last used, so it can lock itself after an idle period
+///
+/// Every command that opens the vault counts as activity; long-running modes should call
+/// [`VaultSession::touch`] as the user works and check [`VaultSession::is_idle`] periodically.
+#[derive(Debug, Clone)]
+pub struct VaultSession {
+    path: PathBuf,
+    idle_timeout: Option<Duration>,
+}
+
+impl VaultSession {
+    /// Track activity in the given cache directory; without a timeout the vault never locks itself
+    pub fn new(cache_dir: &Path, idle_timeout: Option<Duration>) -> Self {
+        Self {
+            path: cache_dir.join(VAULT_ACTIVITY_FILE),
+            idle_timeout,
+        }
+    }
+
+    /// Lock after this idle period; `None` never locks
+    pub fn set_idle_timeout(&mut self, idle_timeout: Option<Duration>) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    /// Idle period after which the vault locks
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// When the vault was last used, if recorded
+    pub fn last_activity(&self) -> Option<DateTime<Utc>> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        DateTime::parse_from_rfc3339(content.trim()).ok().map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Record activity now
+    pub fn touch(&self) -> Result<(), DomainError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(DomainError::IoError)?;
+        }
+
+        fs::write(&self.path, Utc::now().to_rfc3339()).map_err(DomainError::IoError)
+    }
+
+    /// Whether the idle period has passed since the last activity
+    ///
+    /// A session without recorded activity counts as idle, so a stray key is never trusted.
+    pub fn is_idle(&self, now: DateTime<Utc>) -> bool {
+        match (self.idle_timeout, self.last_activity()) {
+            (None, _) => false,
+            (Some(timeout), Some(last)) => now - last > timeout,
+            (Some(_), None) => true,
+        }
+    }
+
+    /// When the vault will lock if it isn't used again
+    pub fn locks_at(&self) -> Option<DateTime<Utc>> {
+        Some(self.last_activity()? + self.idle_timeout?)
+    }
+
+    /// Forget the recorded activity
+    pub fn clear(&self) -> Result<(), DomainError> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(DomainError::IoError(e)),
+        }
+    }
+}
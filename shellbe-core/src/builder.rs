@@ -0,0 +1,309 @@
+use crate::application::{
+    AliasService, ConnectionService, PluginService, PluginSources, Policy, ProfileService, Progress, SessionTracker,
+    Settings, SettingsService, SshConfigService, SshCredentialService, SESSIONS_DIR, SETTINGS_FILE,
+};
+use crate::domain::{AliasRepository, EventBus, HistoryRepository, ProfileRepository, SshCredentialIssuer};
+use crate::errors::{Result, ShellBeError};
+use crate::infrastructure::{
+    vault_ssh_issuer, ControlSockets, EncryptedStorage, FileAliasRepository, FileHistoryRepository,
+    FilePluginRepository, FileProfileRepository, FileSshConfigRepository, FileStorageConfig, PluginRepository,
+    Resolver, ThrushSshService, CONTROL_SOCKETS_DIR, DNS_CACHE_FILE,
+};
+use crate::utils::{home_dir, PluginSecurityValidator, ShellBePaths, SystemRequirements};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builds [`ShellBe`]'s services from a data directory and settings, wired the way the
+/// `shellbe` CLI wires its own
+///
+/// ```no_run
+/// # async fn example() -> shellbe_core::Result<()> {
+/// let shellbe = shellbe_core::ShellBeBuilder::new()
+///     .data_dir("/var/lib/my-tool/shellbe")
+///     .build().await?;
+///
+/// for profile in shellbe.profiles().list_profiles().await? {
+///     println!("{}", profile.name);
+/// }
+///
+/// shellbe.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ShellBeBuilder {
+    paths: ShellBePaths,
+    settings: Option<Settings>,
+    storage: EncryptedStorage,
+    ssh_config_path: Option<PathBuf>,
+    policy: Option<Policy>,
+    event_bus: Option<Arc<EventBus>>,
+    profile_repository: Option<Arc<dyn ProfileRepository>>,
+    strict_host_keys: bool,
+    progress: Option<Arc<dyn Progress>>,
+}
+
+impl ShellBeBuilder {
+    /// Builder using the directories the CLI uses, honouring `SHELLBE_CONFIG_DIR` and `SHELLBE_LAYOUT`
+    pub fn new() -> Self {
+        Self {
+            paths: ShellBePaths::resolve(),
+            settings: None,
+            storage: EncryptedStorage::plain(),
+            ssh_config_path: None,
+            policy: None,
+            event_bus: None,
+            profile_repository: None,
+            strict_host_keys: false,
+            progress: None,
+        }
+    }
+
+    /// Keep all state (settings, data and caches) in one directory, apart from the CLI's
+    pub fn data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.paths = ShellBePaths::in_dir(dir);
+        self
+    }
+
+    /// Use these directories
+    pub fn paths(mut self, paths: ShellBePaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Use these settings instead of reading `settings.toml` from the config directory
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Read and write the data files with this storage, e.g. one holding the unlocked vault key
+    pub fn storage(mut self, storage: EncryptedStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// OpenSSH config file imports and exports go to; defaults to `~/.ssh/config`
+    pub fn ssh_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ssh_config_path = Some(path.into());
+        self
+    }
+
+    /// Organization policy to enforce instead of the one at its default path
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Publish events on this bus, e.g. one with listeners that should see plugins load
+    pub fn event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Keep profiles in this repository instead of the data directory's `profiles.json`
+    pub fn profile_repository(mut self, repository: Arc<dyn ProfileRepository>) -> Self {
+        self.profile_repository = Some(repository);
+        self
+    }
+
+    /// Refuse unknown host keys even when settings and policy don't require it
+    pub fn strict_host_keys(mut self, strict: bool) -> Self {
+        self.strict_host_keys = strict;
+        self
+    }
+
+    /// Report plugin download and extraction progress here
+    pub fn progress(mut self, progress: Arc<dyn Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Open the repositories, load the plugins and build the services
+    pub async fn build(self) -> Result<ShellBe> {
+        let paths = self.paths;
+        let settings = match self.settings {
+            Some(settings) => settings,
+            None => SettingsService::load(paths.config_dir.join(SETTINGS_FILE))?.settings().clone(),
+        };
+        let policy = Arc::new(match self.policy {
+            Some(policy) => policy,
+            None => Policy::load(&Policy::default_path())?,
+        });
+
+        let data_dir = paths.data_dir.clone();
+        let plugins_dir = data_dir.join("plugins");
+        std::fs::create_dir_all(&plugins_dir)
+            .map_err(|e| ShellBeError::Io(format!("Failed to create directory {}: {}", plugins_dir.display(), e)))?;
+
+        let event_bus = self.event_bus.unwrap_or_else(|| Arc::new(EventBus::new()));
+
+        // Initialize repositories
+        let profile_repository: Arc<dyn ProfileRepository> = match self.profile_repository {
+            Some(repository) => repository,
+            None => Arc::new(FileProfileRepository::new(FileStorageConfig {
+                config_dir: data_dir.clone(),
+                profiles_file: "profiles.json".to_string(),
+                storage: self.storage.clone(),
+            }).await?),
+        };
+        let alias_repository = Arc::new(FileAliasRepository::new(data_dir.clone(), "aliases.json".to_string(), self.storage.clone()).await?);
+        let history_repository = Arc::new(FileHistoryRepository::new(data_dir.clone(), "history.json".to_string(), self.storage).await?);
+        let plugin_repository = Arc::new(FilePluginRepository::new(data_dir.clone(), "plugins.json".to_string()).await?);
+
+        // Initialize SSH service
+        let mut ssh_service = ThrushSshService::new();
+        ssh_service.set_strict_host_keys(self.strict_host_keys || settings.ssh.strict_host_keys || policy.strict_host_keys);
+        // Windows' OpenSSH can't multiplex, so the setting only applies elsewhere
+        if settings.ssh.multiplex && cfg!(unix) {
+            let sockets = ControlSockets::new(paths.cache_dir.join(CONTROL_SOCKETS_DIR));
+            sockets.ensure_dir()
+                .map_err(|e| ShellBeError::Io(format!("Failed to create socket directory {}: {}", sockets.dir().display(), e)))?;
+            ssh_service.set_control_sockets(sockets);
+        }
+        ssh_service.set_quiet(settings.ssh.quiet);
+        if let Some(secs) = settings.ssh.dns_cache_secs {
+            ssh_service.set_resolver(Resolver::cached(paths.cache_dir.join(DNS_CACHE_FILE), Duration::from_secs(secs)));
+        }
+
+        // Initialize plugin system; a successful requirements check is cached
+        let mut system_requirements = SystemRequirements::default();
+        system_requirements.set_cache_dir(&paths.cache_dir);
+        let mut plugin_service = PluginService::new(plugin_repository.clone(), event_bus.clone(), plugins_dir);
+        plugin_service.set_security_validator(PluginSecurityValidator::default());
+        plugin_service.set_system_requirements(system_requirements);
+        if let Some(progress) = self.progress {
+            plugin_service.set_progress(progress);
+        }
+        plugin_service.set_policy(policy.clone());
+        plugin_service.set_plugin_sources(PluginSources {
+            allowed: settings.plugins.allowed_sources.clone(),
+            blocked: settings.plugins.blocked_sources.clone(),
+        });
+        plugin_service.set_hook_timeout(Duration::from_secs(settings.plugins.hook_timeout_secs));
+        plugin_service.set_registry(&settings.plugins.registry);
+        let plugin_service = Arc::new(plugin_service);
+        plugin_service.initialize().await
+            .map_err(|e| ShellBeError::Plugin(format!("Failed to initialize plugin system: {}", e)))?;
+
+        // Initialize services
+        let mut profile_service = ProfileService::new(profile_repository.clone(), event_bus.clone());
+        profile_service.set_policy(policy.clone());
+        let mut alias_service = AliasService::new(alias_repository.clone(), profile_repository.clone());
+        alias_service.set_event_bus(event_bus.clone());
+        let alias_service = Arc::new(alias_service);
+        let mut connection_service = ConnectionService::new(
+            profile_repository.clone(),
+            alias_service.clone(),
+            history_repository.clone(),
+            Arc::new(ssh_service),
+            event_bus.clone(),
+            plugin_service.clone(),
+        );
+        connection_service.set_policy(policy);
+        let issuer = vault_ssh_issuer(&settings.vault_ssh).map(|issuer| Arc::new(issuer) as Arc<dyn SshCredentialIssuer>);
+        connection_service.set_credentials(Arc::new(SshCredentialService::new(
+            issuer, settings.vault_ssh.mode, paths.cache_dir.join("certs"),
+        )));
+        connection_service.set_sessions(Arc::new(SessionTracker::new(paths.cache_dir.join(SESSIONS_DIR))));
+        let ssh_config_path = self.ssh_config_path
+            .unwrap_or_else(|| home_dir().unwrap_or_default().join(".ssh").join("config"));
+        let mut ssh_config_service = SshConfigService::new(Arc::new(FileSshConfigRepository::new(ssh_config_path)));
+        ssh_config_service.set_event_bus(event_bus.clone());
+
+        Ok(ShellBe {
+            paths,
+            settings,
+            event_bus,
+            profile_repository,
+            alias_repository,
+            history_repository,
+            plugin_repository,
+            profiles: Arc::new(profile_service),
+            aliases: alias_service,
+            connections: Arc::new(connection_service),
+            plugins: plugin_service,
+            ssh_config: Arc::new(ssh_config_service),
+        })
+    }
+}
+
+impl Default for ShellBeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ShellBe's services, for tools and GUIs embedding profile and connection management
+///
+/// Repositories write behind, so call [`ShellBe::flush`] before exiting.
+pub struct ShellBe {
+    paths: ShellBePaths,
+    settings: Settings,
+    event_bus: Arc<EventBus>,
+    profile_repository: Arc<dyn ProfileRepository>,
+    alias_repository: Arc<FileAliasRepository>,
+    history_repository: Arc<FileHistoryRepository>,
+    plugin_repository: Arc<FilePluginRepository>,
+    profiles: Arc<ProfileService>,
+    aliases: Arc<AliasService>,
+    connections: Arc<ConnectionService>,
+    plugins: Arc<PluginService>,
+    ssh_config: Arc<SshConfigService>,
+}
+
+impl ShellBe {
+    /// Start building
+    pub fn builder() -> ShellBeBuilder {
+        ShellBeBuilder::new()
+    }
+
+    /// Adding, editing, listing and exporting profiles
+    pub fn profiles(&self) -> &Arc<ProfileService> {
+        &self.profiles
+    }
+
+    /// Profile aliases
+    pub fn aliases(&self) -> &Arc<AliasService> {
+        &self.aliases
+    }
+
+    /// Connecting, testing connections and history
+    pub fn connections(&self) -> &Arc<ConnectionService> {
+        &self.connections
+    }
+
+    /// Installed plugins and their hooks
+    pub fn plugins(&self) -> &Arc<PluginService> {
+        &self.plugins
+    }
+
+    /// Importing from and exporting to the OpenSSH config file
+    pub fn ssh_config(&self) -> &Arc<SshConfigService> {
+        &self.ssh_config
+    }
+
+    /// Domain events the services publish; register an [`EventListener`](crate::domain::EventListener) to follow them
+    pub fn events(&self) -> &Arc<EventBus> {
+        &self.event_bus
+    }
+
+    /// Directories the services use
+    pub fn paths(&self) -> &ShellBePaths {
+        &self.paths
+    }
+
+    /// Settings the services were built with
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Write pending changes of every repository to disk
+    pub async fn flush(&self) -> Result<()> {
+        self.profile_repository.flush().await?;
+        self.alias_repository.flush().await?;
+        self.history_repository.flush().await?;
+        self.plugin_repository.flush().await?;
+        Ok(())
+    }
+}
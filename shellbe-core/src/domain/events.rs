@@ -0,0 +1,725 @@
+use crate::domain::models::{Alias, Profile, HistoryEntry};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{FutureExt, Stream};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, Semaphore};
+use uuid::Uuid;
+
+/// Version of the JSON event schema, raised only for changes that break consumers
+///
+/// New event types and new fields inside `data` are not breaking; consumers should
+/// ignore what they don't know.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Events waiting for each listener before `publish` starts dropping them
+pub const LISTENER_QUEUE: usize = 256;
+
+/// How long a command waits at exit for listeners to handle its events
+pub const EVENT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a listener may take for one event before it is skipped, unless registered otherwise
+pub const LISTENER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Listeners taking longer than this for an event are logged
+const SLOW_LISTENER: Duration = Duration::from_secs(1);
+
+/// Domain events represent significant occurrences in the system
+///
+/// Serializes as `{"type": "profile_created", "data": ...}`; the type names are stable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    /// A profile was created
+    ProfileCreated(Profile),
+    /// A profile was updated
+    ProfileUpdated(Profile),
+    /// A profile was removed
+    ProfileRemoved(String),
+    /// A connection was established
+    ConnectionStarted(Profile),
+    /// A connection has ended
+    ConnectionEnded(HistoryEntry),
+    /// A plugin was enabled
+    PluginEnabled(String),
+    /// A plugin was disabled
+    PluginDisabled(String),
+    /// An SSH key pair was generated
+    KeyGenerated { name: String, public_key: PathBuf },
+    /// A key was installed on a profile's host
+    KeyCopied { profile: String, key: PathBuf },
+    /// A profile was added or replaced from an SSH config, a bundle or a URL
+    ProfileImported { name: String, source: String },
+    /// Profiles were exported, e.g. to the SSH config or a PuTTY file
+    ProfilesExported { format: String, count: usize },
+    /// A plugin was installed
+    PluginInstalled { name: String, version: String },
+    /// A plugin was updated
+    PluginUpdated { name: String, version: String },
+    /// A plugin was removed
+    PluginRemoved(String),
+    /// An alias was created
+    AliasCreated(Alias),
+    /// An alias was removed
+    AliasRemoved(String),
+}
+
+impl Event {
+    /// Stable name of the event type, as in the JSON `type` field
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::ProfileCreated(_) => "profile_created",
+            Event::ProfileUpdated(_) => "profile_updated",
+            Event::ProfileRemoved(_) => "profile_removed",
+            Event::ConnectionStarted(_) => "connection_started",
+            Event::ConnectionEnded(_) => "connection_ended",
+            Event::PluginEnabled(_) => "plugin_enabled",
+            Event::PluginDisabled(_) => "plugin_disabled",
+            Event::KeyGenerated { .. } => "key_generated",
+            Event::KeyCopied { .. } => "key_copied",
+            Event::ProfileImported { .. } => "profile_imported",
+            Event::ProfilesExported { .. } => "profiles_exported",
+            Event::PluginInstalled { .. } => "plugin_installed",
+            Event::PluginUpdated { .. } => "plugin_updated",
+            Event::PluginRemoved(_) => "plugin_removed",
+            Event::AliasCreated(_) => "alias_created",
+            Event::AliasRemoved(_) => "alias_removed",
+        }
+    }
+
+    /// Name of the profile, plugin, alias or key the event is about; the format for exports
+    pub fn subject(&self) -> &str {
+        match self {
+            Event::ProfileCreated(profile) | Event::ProfileUpdated(profile) | Event::ConnectionStarted(profile) => &profile.name,
+            Event::ConnectionEnded(entry) => &entry.profile_name,
+            Event::ProfileRemoved(name) | Event::PluginEnabled(name) | Event::PluginDisabled(name) => name,
+            Event::KeyGenerated { name, .. } | Event::ProfileImported { name, .. } => name,
+            Event::KeyCopied { profile, .. } => profile,
+            Event::ProfilesExported { format, .. } => format,
+            Event::PluginInstalled { name, .. } | Event::PluginUpdated { name, .. } => name,
+            Event::PluginRemoved(name) | Event::AliasRemoved(name) => name,
+            Event::AliasCreated(alias) => &alias.name,
+        }
+    }
+}
+
+/// An event as handed to integrations, in the versioned JSON schema
+///
+/// ```json
+/// {"schema_version": 1, "id": "…", "timestamp": "2024-05-01T12:00:00Z",
+///  "type": "profile_removed", "data": "web-1"}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// [`EVENT_SCHEMA_VERSION`] the event was written with
+    pub schema_version: u32,
+    /// Unique ID, for consumers that need to drop duplicates
+    pub id: Uuid,
+    /// When the event was published
+    pub timestamp: DateTime<Utc>,
+    /// The event, flattened into `type` and `data`
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl EventEnvelope {
+    /// Wrap an event published now
+    pub fn new(event: Event) -> Self {
+        Self {
+            schema_version: EVENT_SCHEMA_VERSION,
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            event,
+        }
+    }
+}
+
+/// Which events a listener or subscription receives; the default matches every event
+///
+/// ```ignore
+/// let filter = EventFilter::all().kind("profile_updated").subject("web-1");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    kinds: Vec<String>,
+    subjects: Vec<String>,
+}
+
+impl EventFilter {
+    /// Filter matching every event
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Also match events of this type, as returned by [`Event::kind`]
+    pub fn kind(mut self, kind: impl Into<String>) -> Self {
+        self.kinds.push(kind.into());
+        self
+    }
+
+    /// Also match events about this profile or plugin, as returned by [`Event::subject`]
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subjects.push(subject.into());
+        self
+    }
+
+    /// Whether the event has one of the types and one of the subjects, if any are given
+    pub fn matches(&self, event: &Event) -> bool {
+        (self.kinds.is_empty() || self.kinds.iter().any(|kind| kind == event.kind()))
+            && (self.subjects.is_empty() || self.subjects.iter().any(|subject| subject == event.subject()))
+    }
+}
+
+/// How a listener is registered: which events, in what order, and for how long
+#[derive(Debug, Clone)]
+pub struct ListenerOptions {
+    filter: EventFilter,
+    priority: i32,
+    timeout: Duration,
+}
+
+impl Default for ListenerOptions {
+    fn default() -> Self {
+        Self {
+            filter: EventFilter::all(),
+            priority: 0,
+            timeout: LISTENER_TIMEOUT,
+        }
+    }
+}
+
+impl ListenerOptions {
+    /// Only receive the events matching `filter`
+    pub fn filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Listeners with a higher priority handle each event first: those with a lower one wait
+    /// until they finished or timed out on it. The default is 0
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Skip an event when the listener takes longer than this for it
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Event listener trait for components that need to react to events
+///
+/// Each listener gets events in order on its own task, so a slow one (a webhook, a
+/// plugin) only holds up itself and listeners registered with a lower priority. A listener that panics or times out on an event is
+/// logged and carries on with the next one.
+#[async_trait]
+pub trait EventListener: Send + Sync {
+    async fn on_event(&self, event: &Event);
+
+    /// An event from the journal, published again by `events replay` so new integrations
+    /// can catch up; listeners that record events should ignore it
+    async fn on_replay(&self, envelope: &EventEnvelope) {
+        self.on_event(&envelope.event).await;
+    }
+}
+
+/// Event bus for publishing events to registered listeners
+///
+/// Every listener has a queue of [`LISTENER_QUEUE`] events drained by its own task.
+/// `publish` never waits: when a listener's queue is full the event is dropped for that
+/// listener. `publish_wait` waits for room instead, and `flush` until listeners caught up.
+/// Listeners can be registered and removed at any time, also through a shared `Arc<EventBus>`.
+#[derive(Default)]
+pub struct EventBus {
+    listeners: Arc<RwLock<Vec<ListenerQueue>>>,
+    next_id: AtomicU64,
+}
+
+/// A registered listener; `unsubscribe` stops its events
+///
+/// Dropping the handle keeps the listener registered.
+pub struct Subscription {
+    id: u64,
+    listeners: Weak<RwLock<Vec<ListenerQueue>>>,
+}
+
+impl Subscription {
+    /// Stop delivering events to the listener; events already queued are still handled
+    pub fn unsubscribe(self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            listeners.write().unwrap_or_else(|e| e.into_inner()).retain(|listener| listener.id != self.id);
+        }
+    }
+}
+
+/// Stream of the events matching an [`EventFilter`], from [`EventBus::subscribe`]
+///
+/// Dropping the stream unsubscribes it. Like a listener, it holds up `flush` until the
+/// events published before have been read.
+pub struct EventSubscription {
+    receiver: mpsc::Receiver<Delivery>,
+    subscription: Option<Subscription>,
+    /// Event received but waiting for listeners with a higher priority
+    waiting: Option<Pin<Box<dyn Future<Output = Event> + Send>>>,
+}
+
+impl Stream for EventSubscription {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        let this = self.get_mut();
+
+        loop {
+            if let Some(waiting) = this.waiting.as_mut() {
+                let event = ready!(waiting.as_mut().poll(cx));
+                this.waiting = None;
+                return Poll::Ready(Some(event));
+            }
+
+            match ready!(this.receiver.poll_recv(cx)) {
+                Some(Delivery::Event(event, turn)) => {
+                    this.waiting = Some(Box::pin(async move {
+                        turn.wait().await;
+                        event
+                    }));
+                },
+                Some(Delivery::Replay(envelope, turn)) => {
+                    this.waiting = Some(Box::pin(async move {
+                        turn.wait().await;
+                        envelope.event
+                    }));
+                },
+                Some(Delivery::Flush(done)) => {
+                    let _ = done.send(());
+                },
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            subscription.unsubscribe();
+        }
+    }
+}
+
+/// Sending end of a listener's queue
+#[derive(Clone)]
+struct ListenerQueue {
+    id: u64,
+    /// Type name of the listener, for log messages
+    name: &'static str,
+    /// Events not matching it are never queued
+    filter: EventFilter,
+    priority: i32,
+    sender: mpsc::Sender<Delivery>,
+}
+
+enum Delivery {
+    Event(Event, Turn),
+    Replay(EventEnvelope, Turn),
+    /// Answered once everything queued before it was handled
+    Flush(oneshot::Sender<()>),
+}
+
+/// A listener's place in handling one event: it waits for the listeners with the next higher
+/// priority, and once dropped (handled, skipped or never queued) lets the next lower ones go
+struct Turn {
+    /// Listeners to wait for, and how many there are
+    after: Option<(Arc<Semaphore>, u32)>,
+    /// Signalled by every listener of this priority
+    done: Option<Arc<Semaphore>>,
+}
+
+impl Turn {
+    async fn wait(&self) {
+        if let Some((handled, count)) = &self.after {
+            // Give the permits back right away: the other listeners of this priority wait too
+            let _ = handled.acquire_many(*count).await;
+        }
+    }
+}
+
+impl Drop for Turn {
+    fn drop(&mut self) {
+        if let Some(done) = &self.done {
+            done.add_permits(1);
+        }
+    }
+}
+
+/// The listeners that want `event`, highest priority first, each with its turn for it
+fn turns<'a>(listeners: &'a [ListenerQueue], event: &Event) -> Vec<(&'a ListenerQueue, Turn)> {
+    let matching: Vec<&ListenerQueue> = listeners.iter().filter(|listener| listener.filter.matches(event)).collect();
+    let mut turns = Vec::with_capacity(matching.len());
+    let mut after = None;
+    let mut start = 0;
+
+    while start < matching.len() {
+        let priority = matching[start].priority;
+        let end = matching[start..].iter()
+            .position(|listener| listener.priority != priority)
+            .map_or(matching.len(), |len| start + len);
+
+        // Nobody waits for the lowest priority
+        let done = (end < matching.len()).then(|| Arc::new(Semaphore::new(0)));
+        for listener in &matching[start..end] {
+            turns.push((*listener, Turn { after: after.clone(), done: done.clone() }));
+        }
+
+        after = done.map(|done| (done, (end - start) as u32));
+        start = end;
+    }
+
+    turns
+}
+
+impl EventBus {
+    /// Create a new empty event bus
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new event listener; must be called within a Tokio runtime
+    pub fn register<L: EventListener + ?Sized + 'static>(&self, listener: Arc<L>) -> Subscription {
+        self.register_filtered(listener, EventFilter::all())
+    }
+
+    /// Register a listener that only receives the events matching `filter`
+    pub fn register_filtered<L: EventListener + ?Sized + 'static>(&self, listener: Arc<L>, filter: EventFilter) -> Subscription {
+        self.register_with(listener, ListenerOptions::default().filter(filter))
+    }
+
+    /// Register a listener with a filter, priority or timeout
+    pub fn register_with<L: EventListener + ?Sized + 'static>(&self, listener: Arc<L>, options: ListenerOptions) -> Subscription {
+        let (sender, mut receiver) = mpsc::channel(LISTENER_QUEUE);
+        let name = std::any::type_name::<L>();
+        let timeout = options.timeout;
+
+        tokio::spawn(async move {
+            while let Some(delivery) = receiver.recv().await {
+                match delivery {
+                    Delivery::Event(event, turn) => {
+                        turn.wait().await;
+                        isolate(name, event.kind(), timeout, listener.on_event(&event)).await;
+                    },
+                    Delivery::Replay(envelope, turn) => {
+                        turn.wait().await;
+                        isolate(name, envelope.event.kind(), timeout, listener.on_replay(&envelope)).await;
+                    },
+                    Delivery::Flush(done) => {
+                        let _ = done.send(());
+                    },
+                }
+            }
+        });
+
+        self.add_queue(name, options, sender)
+    }
+
+    /// Stream of the events matching `filter`, for code that would rather await events
+    /// than implement [`EventListener`]
+    pub fn subscribe(&self, filter: EventFilter) -> EventSubscription {
+        let (sender, receiver) = mpsc::channel(LISTENER_QUEUE);
+        let subscription = self.add_queue("event subscription", ListenerOptions::default().filter(filter), sender);
+
+        EventSubscription {
+            receiver,
+            subscription: Some(subscription),
+            waiting: None,
+        }
+    }
+
+    /// Add a queue behind those with the same or a higher priority
+    fn add_queue(&self, name: &'static str, options: ListenerOptions, sender: mpsc::Sender<Delivery>) -> Subscription {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        let position = listeners.iter().position(|listener| listener.priority < options.priority).unwrap_or(listeners.len());
+        listeners.insert(position, ListenerQueue {
+            id,
+            name,
+            filter: options.filter,
+            priority: options.priority,
+            sender,
+        });
+
+        Subscription {
+            id,
+            listeners: Arc::downgrade(&self.listeners),
+        }
+    }
+
+    /// Number of registered listeners
+    pub fn listener_count(&self) -> usize {
+        self.listeners.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// The registered listeners' queues, copied so no lock is held while waiting on them
+    fn queues(&self) -> Vec<ListenerQueue> {
+        self.listeners.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Publish an event to all registered listeners without waiting for them
+    pub fn publish(&self, event: Event) {
+        let listeners = self.listeners.read().unwrap_or_else(|e| e.into_inner());
+        for (listener, turn) in turns(&listeners, &event) {
+            if let Err(mpsc::error::TrySendError::Full(_)) = listener.sender.try_send(Delivery::Event(event.clone(), turn)) {
+                tracing::warn!("Event listener {} is falling behind; dropped a {} event", listener.name, event.kind());
+            }
+        }
+    }
+
+    /// Publish an event, waiting while a listener's queue is full
+    pub async fn publish_wait(&self, event: Event) {
+        let queues = self.queues();
+        for (listener, turn) in turns(&queues, &event) {
+            let _ = listener.sender.send(Delivery::Event(event.clone(), turn)).await;
+        }
+    }
+
+    /// Publish a journaled event again, keeping its ID and time; waits like `publish_wait`
+    pub async fn replay(&self, envelope: EventEnvelope) {
+        let queues = self.queues();
+        for (listener, turn) in turns(&queues, &envelope.event) {
+            let _ = listener.sender.send(Delivery::Replay(envelope.clone(), turn)).await;
+        }
+    }
+
+    /// Wait until every listener handled the events published so far
+    pub async fn flush(&self) {
+        let mut pending = Vec::new();
+        for listener in self.queues() {
+            let (done, handled) = oneshot::channel();
+            if listener.sender.send(Delivery::Flush(done)).await.is_ok() {
+                pending.push(handled);
+            }
+        }
+
+        for handled in pending {
+            let _ = handled.await;
+        }
+    }
+}
+
+/// Run one listener call so that a panic or hang only costs that event
+///
+/// Catching the panic needs unwinding, which is why the release profile doesn't abort on panic.
+async fn isolate(name: &str, kind: &str, timeout: Duration, handle: impl Future<Output = ()>) {
+    let started = Instant::now();
+
+    match tokio::time::timeout(timeout, AssertUnwindSafe(handle).catch_unwind()).await {
+        Ok(Ok(())) => {
+            let elapsed = started.elapsed();
+            if elapsed >= SLOW_LISTENER {
+                tracing::warn!("Event listener {} took {:?} to handle a {} event", name, elapsed, kind);
+            }
+        },
+        Ok(Err(_)) => tracing::error!("Event listener {} panicked handling a {} event", name, kind),
+        Err(_) => tracing::warn!("Event listener {} took longer than {:?} for a {} event; skipped it", name, timeout, kind),
+    }
+}
+
+// Simple implementation of an event handler that logs events
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    pub struct TestEventListener {
+        pub events: Mutex<Vec<Event>>,
+    }
+
+    impl TestEventListener {
+        pub fn new() -> Self {
+            Self {
+                events: Mutex::new(Vec::new()),
+            }
+        }
+
+        pub fn events(&self) -> Vec<Event> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl EventListener for TestEventListener {
+        async fn on_event(&self, event: &Event) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_and_unsubscribe() {
+        let bus = Arc::new(EventBus::new());
+        let listener = Arc::new(TestEventListener::new());
+
+        // Registering through a shared bus, after it was handed out
+        let subscription = bus.register(listener.clone());
+        bus.publish(Event::PluginEnabled("a".to_string()));
+        bus.flush().await;
+        assert_eq!(listener.events().len(), 1);
+
+        subscription.unsubscribe();
+        assert_eq!(bus.listener_count(), 0);
+        bus.publish(Event::PluginEnabled("b".to_string()));
+        bus.flush().await;
+        assert_eq!(listener.events().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filtered_subscriptions() {
+        use futures::StreamExt;
+
+        let bus = EventBus::new();
+        let listener = Arc::new(TestEventListener::new());
+        bus.register_filtered(listener.clone(), EventFilter::all().kind("plugin_enabled"));
+        let mut web = bus.subscribe(EventFilter::all().subject("web-1"));
+
+        bus.publish(Event::PluginEnabled("a".to_string()));
+        bus.publish(Event::PluginDisabled("a".to_string()));
+        bus.publish(Event::ProfileRemoved("db-1".to_string()));
+        bus.publish(Event::ProfileRemoved("web-1".to_string()));
+
+        let event = web.next().await.unwrap();
+        assert_eq!((event.kind(), event.subject()), ("profile_removed", "web-1"));
+
+        // Dropping the stream unsubscribes it, so `flush` doesn't wait for it to be read
+        drop(web);
+        assert_eq!(bus.listener_count(), 1);
+
+        bus.flush().await;
+        let events = listener.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind(), "plugin_enabled");
+    }
+
+    /// Panics on plugin events, hangs on profile removals
+    struct FaultyListener;
+
+    #[async_trait]
+    impl EventListener for FaultyListener {
+        async fn on_event(&self, event: &Event) {
+            match event {
+                Event::PluginEnabled(_) => panic!("listener failure"),
+                Event::ProfileRemoved(_) => std::future::pending::<()>().await,
+                _ => {},
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_listener_isolation() {
+        let bus = EventBus::new();
+        let faulty = ListenerOptions::default().priority(10).timeout(Duration::from_millis(50));
+        bus.register_with(Arc::new(FaultyListener), faulty);
+        let listener = Arc::new(TestEventListener::new());
+        bus.register(listener.clone());
+
+        bus.publish(Event::PluginEnabled("a".to_string()));
+        bus.publish(Event::ProfileRemoved("web-1".to_string()));
+        bus.publish(Event::PluginDisabled("a".to_string()));
+
+        // The faulty listener survived both and caught up, so flush returns
+        bus.flush().await;
+        assert_eq!(listener.events().len(), 3);
+        assert_eq!(bus.listener_count(), 2);
+    }
+
+    #[test]
+    fn test_release_profile_unwinds() {
+        // With panic = "abort", test_listener_isolation would pass but a shipped binary would exit
+        let manifest = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../Cargo.toml")).unwrap();
+        let manifest: toml::Table = manifest.parse().unwrap();
+        let panic = manifest.get("profile")
+            .and_then(|profile| profile.get("release"))
+            .and_then(|release| release.get("panic"))
+            .and_then(|panic| panic.as_str());
+        assert_ne!(panic, Some("abort"));
+    }
+
+    /// Records its name once it handled an event, after a delay
+    struct OrderListener {
+        name: &'static str,
+        delay: Duration,
+        handled: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl EventListener for OrderListener {
+        async fn on_event(&self, _event: &Event) {
+            tokio::time::sleep(self.delay).await;
+            self.handled.lock().unwrap().push(self.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_listener_priority() {
+        use futures::StreamExt;
+
+        let bus = EventBus::new();
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let listener = |name, delay| Arc::new(OrderListener { name, delay, handled: handled.clone() });
+
+        // Registered lowest first, and the slowest handles first
+        bus.register_with(listener("low", Duration::ZERO), ListenerOptions::default().priority(-5));
+        bus.register(listener("default", Duration::from_millis(20)));
+        bus.register_with(listener("high", Duration::from_millis(50)), ListenerOptions::default().priority(10));
+        let mut stream = bus.subscribe(EventFilter::all());
+
+        bus.publish(Event::PluginEnabled("a".to_string()));
+        stream.next().await.unwrap();
+        handled.lock().unwrap().push("stream");
+        bus.flush().await;
+
+        assert_eq!(*handled.lock().unwrap(), vec!["high", "stream", "default", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let bus = EventBus::new();
+        let listener = Arc::new(TestEventListener::new());
+        bus.register(listener.clone());
+
+        let envelope = EventEnvelope::new(Event::ProfileRemoved("web-1".to_string()));
+        bus.replay(envelope.clone()).await;
+        bus.flush().await;
+
+        let events = listener.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].subject(), "web-1");
+    }
+
+    #[test]
+    fn test_event_schema() {
+        let envelope = EventEnvelope::new(Event::ProfileRemoved("web-1".to_string()));
+        let json = serde_json::to_value(&envelope).unwrap();
+
+        assert_eq!(json["schema_version"], EVENT_SCHEMA_VERSION);
+        assert_eq!(json["type"], "profile_removed");
+        assert_eq!(json["data"], "web-1");
+        assert!(json["timestamp"].is_string());
+
+        let parsed: EventEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.event.kind(), "profile_removed");
+        assert_eq!(parsed.event.subject(), "web-1");
+        assert_eq!(parsed.id, envelope.id);
+
+        let event = Event::KeyCopied { profile: "web-1".to_string(), key: "/home/me/.ssh/id_ed25519.pub".into() };
+        let json = serde_json::to_value(EventEnvelope::new(event)).unwrap();
+        assert_eq!(json["type"], "key_copied");
+        assert_eq!(json["data"]["profile"], "web-1");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+pub mod models;
+pub mod events;
+pub mod plugin;
+pub mod services;
+pub mod sensitive;
+
+// Re-export common types
+pub use models::{
+    Profile, ProfileKind, KubernetesPod, ProfileFilter, AdHocTarget, SshUri, UriScheme, DiscoveredHost, Alias, AliasResolution, AliasStatus, ConnectionArgs, Session, HistoryEntry, HistoryFormat, ConnectionStats,
+    HistoryGroup, HistoryGrouping, template_params, match_template, glob_match, is_secret_reference, docker_exec_command, SECRET_REFERENCE_SCHEMES,
+};
+pub use events::{Event, EventBus, EventEnvelope, EventFilter, EventListener, EventSubscription, ListenerOptions, Subscription, EVENT_SCHEMA_VERSION, EVENT_FLUSH_TIMEOUT, LISTENER_QUEUE, LISTENER_TIMEOUT};
+pub use sensitive::{Sensitive, with_redaction, REDACTED};
+pub use plugin::{Plugin, PluginInfo, PluginCommand, Hook, PluginStatus, PluginMetadata, SecurityFinding};
+pub use services::{
+    ProfileRepository, AliasRepository, HistoryRepository,
+    SshConfigRepository, ProfileExporter, SshService, SecretBackend, SecretResolver, SshCredentialIssuer, HostDiscovery, SyncBackend, Error as DomainError
+};
\ No newline at end of file
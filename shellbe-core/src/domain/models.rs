@@ -0,0 +1,896 @@
+use crate::domain::sensitive::Sensitive;
+use crate::utils::{shell_join, shell_quote};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// SSH profile configuration containing connection details.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Profile {
+    /// Unique name/identifier for the profile
+    pub name: String,
+    /// Hostname or IP address; the container for Docker profiles, the pod or selector for Kubernetes ones
+    pub hostname: String,
+    /// Username for SSH login
+    pub username: String,
+    /// SSH port, defaults to 22
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Path to identity file (private key)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// Secret manager reference for the identity file passphrase, e.g. `op://Private/web/passphrase`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_passphrase: Option<String>,
+    /// Secret manager reference for the login password, e.g. `bw://web-1/password`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Role of HashiCorp Vault's SSH secrets engine to get a certificate or OTP from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_role: Option<String>,
+    /// Address to connect to instead of what `hostname` resolves to; host keys are still
+    /// checked against `hostname`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_to: Option<IpAddr>,
+    /// Run ssh with `-q`, hiding the server's banner and ssh's own warnings
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quiet: bool,
+    /// Message printed before connecting, e.g. `PRODUCTION — change ticket required`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    /// SSH host or Docker container
+    #[serde(default, skip_serializing_if = "ProfileKind::is_ssh")]
+    pub kind: ProfileKind,
+    /// Additional SSH options
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub options: HashMap<String, String>,
+    /// Free-form labels for grouping profiles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Date the profile was created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Date the profile was last modified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Date the profile was last accessed/used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_port() -> u16 {
+    22
+}
+
+/// What a profile connects to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProfileKind {
+    /// A host reached over SSH
+    #[default]
+    Ssh,
+    /// A container entered with `docker exec`, on this machine or on the host of another profile
+    Docker {
+        /// SSH profile of the host running the Docker daemon; unset uses the local daemon
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host_profile: Option<String>,
+        /// Container name or ID
+        container: String,
+    },
+    /// A pod entered with `kubectl exec`
+    Kubernetes(KubernetesPod),
+}
+
+/// Pod of a Kubernetes profile, named or picked by a label selector
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KubernetesPod {
+    /// kubeconfig context; unset uses the current context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Namespace; unset uses the context's namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Pod name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    /// Label selector, e.g. `app=web`, picking the first running pod when no pod is named
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    /// Container in the pod; unset uses the pod's default container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+}
+
+impl KubernetesPod {
+    /// `kubectl` with the context and namespace flags
+    fn kubectl(&self) -> Vec<String> {
+        let mut command_line = vec!["kubectl".to_string()];
+        if let Some(context) = &self.context {
+            command_line.extend(["--context".to_string(), context.clone()]);
+        }
+        if let Some(namespace) = &self.namespace {
+            command_line.extend(["--namespace".to_string(), namespace.clone()]);
+        }
+        command_line
+    }
+
+    /// `kubectl exec` command line running `command` in the pod, or a shell
+    ///
+    /// kubectl exec only takes a pod name, so with a selector the command line is a small
+    /// `sh -c` script looking the pod up first.
+    pub fn exec_command(&self, command: Option<&str>) -> Vec<String> {
+        let mut exec = self.kubectl();
+        exec.extend(["exec".to_string(), "-it".to_string()]);
+        exec.push(match (&self.pod, &self.selector) {
+            (Some(pod), _) => pod.clone(),
+            _ => "$POD".to_string(),
+        });
+        if let Some(container) = &self.container {
+            exec.extend(["--container".to_string(), container.clone()]);
+        }
+        let script = command.unwrap_or("command -v bash >/dev/null && exec bash || exec sh");
+        exec.extend(["--", "sh", "-c", script].iter().map(|arg| arg.to_string()));
+
+        let Some(selector) = self.selector.as_ref().filter(|_| self.pod.is_none()) else {
+            return exec;
+        };
+
+        let mut lookup = self.kubectl();
+        lookup.extend(["get", "pods", "--selector", selector, "--field-selector=status.phase=Running", "--output", "name"]
+            .iter().map(|arg| arg.to_string()));
+        let exec = exec.iter()
+            .map(|arg| if arg == "$POD" { "\"$POD\"".to_string() } else { shell_quote(arg) })
+            .collect::<Vec<_>>()
+            .join(" ");
+        let script = format!(
+            "POD=$({} | head -n 1) && [ -n \"$POD\" ] || {{ echo {} >&2; exit 1; }}; exec {}",
+            shell_join(&lookup), shell_quote(&format!("No running pod matches {}", selector)), exec,
+        );
+        vec!["sh".to_string(), "-c".to_string(), script]
+    }
+
+    /// `kubectl get` command line printing the Ready condition of the pod, or of every pod
+    /// the selector matches, as `True`/`False` words
+    pub fn ready_command(&self) -> Vec<String> {
+        let mut command_line = self.kubectl();
+        match (&self.pod, &self.selector) {
+            (Some(pod), _) => command_line.extend([
+                "get".to_string(), "pod".to_string(), pod.clone(), "--output".to_string(),
+                r#"jsonpath={.status.conditions[?(@.type=="Ready")].status}"#.to_string(),
+            ]),
+            (None, selector) => command_line.extend([
+                "get".to_string(), "pods".to_string(), "--selector".to_string(), selector.clone().unwrap_or_default(),
+                "--output".to_string(),
+                r#"jsonpath={.items[*].status.conditions[?(@.type=="Ready")].status}"#.to_string(),
+            ]),
+        }
+        command_line
+    }
+
+    /// The pod for display: `pod`, `namespace/pod` or the selector, with `@context`
+    pub fn target(&self) -> String {
+        let pod = match (&self.pod, &self.selector) {
+            (Some(pod), _) => pod.clone(),
+            (None, selector) => format!("-l {}", selector.as_deref().unwrap_or_default()),
+        };
+        let mut target = match &self.namespace {
+            Some(namespace) => format!("{}/{}", namespace, pod),
+            None => pod,
+        };
+        if let Some(context) = &self.context {
+            target = format!("{}@{}", target, context);
+        }
+        target
+    }
+}
+
+impl ProfileKind {
+    /// Check if this is a plain SSH profile
+    pub fn is_ssh(&self) -> bool {
+        matches!(self, ProfileKind::Ssh)
+    }
+}
+
+/// `docker exec` command line entering a container as `user` (the image's own user when
+/// empty), running `command` in it or a shell
+///
+/// Without a command, bash is preferred and sh is the fallback, since slim images lack bash.
+pub fn docker_exec_command(container: &str, user: &str, command: Option<&str>) -> Vec<String> {
+    let mut command_line: Vec<String> = vec!["docker".into(), "exec".into(), "-it".into()];
+    if !user.is_empty() {
+        command_line.push("-u".into());
+        command_line.push(user.to_string());
+    }
+
+    let script = command.unwrap_or("command -v bash >/dev/null && exec bash || exec sh");
+    command_line.extend([container, "sh", "-c", script].iter().map(|arg| arg.to_string()));
+    command_line
+}
+
+/// Schemes a secret reference can use: `op://` for 1Password and `bw://` for Bitwarden
+pub const SECRET_REFERENCE_SCHEMES: [&str; 2] = ["op", "bw"];
+
+/// Check whether a value names a secret in a secret manager rather than being one
+pub fn is_secret_reference(value: &str) -> bool {
+    SECRET_REFERENCE_SCHEMES.iter().any(|scheme| {
+        value.strip_prefix(scheme)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .is_some_and(|path| !path.is_empty())
+    })
+}
+
+impl Profile {
+    /// Create a new SSH profile with default values
+    pub fn new(name: impl Into<String>, hostname: impl Into<String>, username: impl Into<String>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            name: name.into(),
+            hostname: hostname.into(),
+            username: username.into(),
+            port: default_port(),
+            identity_file: None,
+            identity_passphrase: None,
+            password: None,
+            vault_role: None,
+            resolve_to: None,
+            quiet: false,
+            banner: None,
+            kind: ProfileKind::Ssh,
+            options: HashMap::new(),
+            tags: Vec::new(),
+            created_at: Some(now),
+            updated_at: Some(now),
+            last_used: None,
+        }
+    }
+
+    /// Update the last used timestamp
+    pub fn mark_as_used(&mut self) {
+        self.last_used = Some(chrono::Utc::now());
+    }
+
+    /// Update the last modified timestamp
+    pub fn mark_as_updated(&mut self) {
+        self.updated_at = Some(chrono::Utc::now());
+    }
+
+    /// Get SSH connection string in the format username@hostname
+    pub fn connection_string(&self) -> String {
+        format!("{}@{}", self.username, self.hostname)
+    }
+
+    /// Where the profile connects to, for display: the hostname, or the container and its host
+    pub fn target(&self) -> String {
+        match &self.kind {
+            ProfileKind::Ssh => self.hostname.clone(),
+            ProfileKind::Docker { host_profile: Some(host), container } => format!("docker:{}@{}", container, host),
+            ProfileKind::Docker { host_profile: None, container } => format!("docker:{}", container),
+            ProfileKind::Kubernetes(pod) => format!("k8s:{}", pod.target()),
+        }
+    }
+
+    /// Build SSH command string with all options
+    pub fn ssh_command(&self) -> String {
+        let mut cmd = String::from("ssh");
+
+        // Add port if not default
+        if self.port != 22 {
+            cmd.push_str(&format!(" -p {}", self.port));
+        }
+
+        // Add identity file if specified
+        if let Some(identity) = &self.identity_file {
+            cmd.push_str(&format!(" -i {}", identity.display()));
+        }
+
+        // Add any additional options
+        for (key, value) in &self.options {
+            cmd.push_str(&format!(" -{} {}", key, value));
+        }
+
+        // Add the connection string
+        cmd.push_str(&format!(" {}", self.connection_string()));
+
+        cmd
+    }
+}
+
+/// Criteria for narrowing down a profile list; unset fields match everything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileFilter {
+    /// Hostname glob, e.g. `*.example.com` (case-insensitive)
+    pub host: Option<String>,
+    /// Username glob
+    pub user: Option<String>,
+    /// Exact port
+    pub port: Option<u16>,
+    /// Tag the profile must have
+    pub tag: Option<String>,
+}
+
+impl ProfileFilter {
+    /// Check if no criteria are set
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Check if a profile meets every criterion
+    pub fn matches(&self, profile: &Profile) -> bool {
+        self.host.as_ref().map_or(true, |p| glob_match(&p.to_lowercase(), &profile.hostname.to_lowercase()))
+            && self.user.as_ref().map_or(true, |p| glob_match(p, &profile.username))
+            && self.port.map_or(true, |p| p == profile.port)
+            && self.tag.as_ref().map_or(true, |t| profile.tags.iter().any(|tag| tag == t))
+    }
+}
+
+/// Match text against a glob pattern supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A one-off `user@host[:port]` connection target that isn't saved as a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdHocTarget {
+    pub username: String,
+    pub hostname: String,
+    pub port: Option<u16>,
+}
+
+impl AdHocTarget {
+    /// Parse `user@host`, `user@host:port` or `user@[ipv6]:port`
+    pub fn parse(target: &str) -> Option<Self> {
+        let (username, rest) = target.split_once('@')?;
+        if username.is_empty() || rest.is_empty() {
+            return None;
+        }
+
+        let (hostname, port) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')?;
+            match after.strip_prefix(':') {
+                Some(port) => (host, Some(port.parse().ok()?)),
+                None if after.is_empty() => (host, None),
+                None => return None,
+            }
+        } else {
+            match rest.split_once(':') {
+                // More than one colon is a bare IPv6 address without a port
+                Some((host, port)) if !port.contains(':') => (host, Some(port.parse().ok()?)),
+                _ => (rest, None),
+            }
+        };
+
+        if hostname.is_empty() || hostname.contains(char::is_whitespace) {
+            return None;
+        }
+
+        Some(Self {
+            username: username.to_string(),
+            hostname: hostname.to_string(),
+            port,
+        })
+    }
+
+    /// Build a profile from the target, using `default_port` when none was given
+    pub fn to_profile(&self, name: impl Into<String>, default_port: u16) -> Profile {
+        let mut profile = Profile::new(name, &self.hostname, &self.username);
+        profile.port = self.port.unwrap_or(default_port);
+        profile
+    }
+
+    /// Suggested profile name: the first label of the hostname
+    pub fn suggested_name(&self) -> String {
+        self.hostname.split('.').next().unwrap_or(&self.hostname).to_string()
+    }
+}
+
+/// Scheme of an [`SshUri`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UriScheme {
+    Ssh,
+    Sftp,
+}
+
+impl UriScheme {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UriScheme::Ssh => "ssh",
+            UriScheme::Sftp => "sftp",
+        }
+    }
+}
+
+/// An `ssh://[user@]host[:port]` or `sftp://[user@]host[:port][/path]` URI, as in RFC 3986
+/// and the IETF draft on SSH URIs
+///
+/// Connection parameters after a `;` in the user part, such as a host key fingerprint, are
+/// accepted but not used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshUri {
+    pub scheme: UriScheme,
+    pub username: Option<String>,
+    pub hostname: String,
+    pub port: Option<u16>,
+    /// Directory or file an sftp session starts in; `/~/` paths are relative to the home directory
+    pub path: Option<String>,
+}
+
+impl SshUri {
+    /// Parse a URI; `None` when it isn't an ssh or sftp URI
+    pub fn parse(uri: &str) -> Option<Self> {
+        let (scheme, rest) = uri.split_once("://")?;
+        let scheme = match scheme.to_ascii_lowercase().as_str() {
+            "ssh" => UriScheme::Ssh,
+            "sftp" => UriScheme::Sftp,
+            _ => return None,
+        };
+
+        // Queries and fragments mean nothing here
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], Some(&rest[index..])),
+            None => (rest, None),
+        };
+
+        let (username, host_port) = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => {
+                let user = userinfo.split(';').next().unwrap_or_default();
+                (Some(percent_decode(user)?).filter(|user| !user.is_empty()), host_port)
+            },
+            None => (None, authority),
+        };
+
+        let (hostname, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')?;
+            match after.strip_prefix(':') {
+                Some("") => (host, None),
+                Some(port) => (host, Some(port.parse().ok()?)),
+                None if after.is_empty() => (host, None),
+                None => return None,
+            }
+        } else {
+            match host_port.split_once(':') {
+                Some((host, "")) => (host, None),
+                Some((host, port)) => (host, Some(port.parse().ok()?)),
+                None => (host_port, None),
+            }
+        };
+
+        let hostname = percent_decode(hostname)?;
+        if hostname.is_empty() || hostname.contains(char::is_whitespace) || port == Some(0) {
+            return None;
+        }
+
+        // A path only means something to sftp; "/" alone is the same as none
+        let path = match (scheme, path) {
+            (UriScheme::Sftp, Some(path)) if path != "/" => Some(percent_decode(path)?),
+            _ => None,
+        };
+
+        Some(Self { scheme, username, hostname, port, path })
+    }
+
+    /// URI connecting to a profile's host, user and port
+    pub fn for_profile(profile: &Profile, scheme: UriScheme) -> Self {
+        Self {
+            scheme,
+            username: Some(profile.username.clone()).filter(|user| !user.is_empty()),
+            hostname: profile.hostname.clone(),
+            port: Some(profile.port).filter(|port| *port != 22),
+            path: None,
+        }
+    }
+
+    /// Connection target of the URI, with `default_user` when it names none
+    pub fn target(&self, default_user: &str) -> AdHocTarget {
+        AdHocTarget {
+            username: self.username.clone().unwrap_or_else(|| default_user.to_string()),
+            hostname: self.hostname.clone(),
+            port: self.port,
+        }
+    }
+
+    /// Path for sftp's `host:path` destination: `/~/dir` becomes `dir`, relative to home
+    pub fn sftp_path(&self) -> Option<String> {
+        let path = self.path.as_ref()?;
+        match path.strip_prefix("/~") {
+            Some(relative) => Some(relative.trim_start_matches('/').to_string()).filter(|path| !path.is_empty()),
+            None => Some(path.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for SshUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}://", self.scheme.as_str())?;
+        if let Some(username) = &self.username {
+            write!(f, "{}@", percent_encode(username))?;
+        }
+        if self.hostname.contains(':') {
+            write!(f, "[{}]", self.hostname)?;
+        } else {
+            write!(f, "{}", percent_encode(&self.hostname))?;
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+        if let Some(path) = &self.path {
+            let path = path.split('/').map(percent_encode).collect::<Vec<_>>().join("/");
+            write!(f, "{}{}", if path.starts_with('/') { "" } else { "/" }, path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode `%XX` escapes; `None` for malformed escapes or invalid UTF-8
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Escape everything but RFC 3986's unreserved characters
+fn percent_encode(value: &str) -> String {
+    value.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+        _ => format!("%{:02X}", byte),
+    }).collect()
+}
+
+/// An alias points to a profile by name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Alias {
+    /// Alias name
+    pub name: String,
+    /// Target profile name
+    pub target: String,
+    /// Extra SSH arguments applied when connecting through this alias
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Remote command run when connecting through this alias
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Alias {
+    pub fn new(name: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            target: target.into(),
+            args: Vec::new(),
+            command: None,
+        }
+    }
+
+    /// Attach extra connection arguments to the alias
+    pub fn with_connection_args(mut self, args: ConnectionArgs) -> Self {
+        self.args = args.ssh_args;
+        self.command = args.command;
+        self
+    }
+
+    /// Connection arguments carried by this alias
+    pub fn connection_args(&self) -> ConnectionArgs {
+        ConnectionArgs {
+            ssh_args: self.args.clone(),
+            command: self.command.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Check if this is a template alias such as `web{N}`
+    pub fn is_template(&self) -> bool {
+        !template_params(&self.name).is_empty()
+    }
+
+    /// Expand a template alias for a concrete name, e.g. `web3` with `web{N} -> web-{N}.prod`
+    /// gives `web-3.prod`. Returns None if this is not a template or the name doesn't match.
+    pub fn expand_template(&self, name: &str) -> Option<String> {
+        let params = template_params(&self.name);
+        if params.is_empty() {
+            return None;
+        }
+
+        // Build an anchored regex from the pattern, one capture group per parameter
+        let mut pattern = String::from("^");
+        let mut rest = self.name.as_str();
+        for param in &params {
+            let placeholder = format!("{{{}}}", param);
+            let idx = rest.find(&placeholder)?;
+            pattern.push_str(&regex::escape(&rest[..idx]));
+            pattern.push_str("(.+?)");
+            rest = &rest[idx + placeholder.len()..];
+        }
+        pattern.push_str(&regex::escape(rest));
+        pattern.push('$');
+
+        let captures = regex::Regex::new(&pattern).ok()?.captures(name)?;
+
+        let mut target = self.target.clone();
+        for (i, param) in params.iter().enumerate() {
+            target = target.replace(&format!("{{{}}}", param), &captures[i + 1]);
+        }
+
+        Some(target)
+    }
+}
+
+/// The template alias matching `name`, returned with its target expanded
+///
+/// When several templates match, the most specific one wins: the one with the most literal
+/// characters, so `web-{N}.eu` beats `web-{N}` for `web-3.eu`. Remaining ties go to the one
+/// with fewer parameters, then to the pattern that sorts first, never to storage order.
+pub fn match_template(aliases: impl IntoIterator<Item = Alias>, name: &str) -> Option<Alias> {
+    aliases.into_iter()
+        .filter_map(|template| {
+            let target = template.expand_template(name)?;
+            Some(Alias { target, ..template })
+        })
+        .min_by_key(|alias| {
+            let params = template_params(&alias.name);
+            let placeholders: usize = params.iter().map(|param| param.len() + 2).sum();
+            (std::cmp::Reverse(alias.name.len() - placeholders), params.len(), alias.name.clone())
+        })
+}
+
+/// Parameter names used in a template such as `web{N}`, in order of appearance
+pub fn template_params(template: &str) -> Vec<String> {
+    let re = regex::Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid template regex");
+    re.captures_iter(template)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+/// Outcome of following an alias chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AliasStatus {
+    /// The chain ends at an existing profile
+    Resolved,
+    /// The chain ends at a name that is neither an alias nor a profile
+    Dangling,
+    /// The chain loops back on itself
+    Circular,
+    /// The alias is a template and only resolves for concrete names
+    Template,
+}
+
+/// Diagnostics for an alias: every name visited on the way to a profile
+#[derive(Debug, Clone)]
+pub struct AliasResolution {
+    /// Names in resolution order, starting with the alias itself
+    pub chain: Vec<String>,
+    /// How the chain ended
+    pub status: AliasStatus,
+}
+
+impl AliasResolution {
+    /// Last name in the chain
+    pub fn last(&self) -> &str {
+        self.chain.last().map(String::as_str).unwrap_or_default()
+    }
+}
+
+/// Extra arguments applied to a single connection, e.g. from an alias
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectionArgs {
+    /// Extra SSH arguments placed before the destination
+    pub ssh_args: Vec<String>,
+    /// Remote command executed instead of a login shell
+    pub command: Option<String>,
+    /// One-time password to answer ssh's password prompt with, e.g. from Vault
+    pub one_time_password: Option<Sensitive<String>>,
+    /// Start an sftp session instead of a shell, in this remote directory (home when empty)
+    pub sftp: Option<String>,
+}
+
+impl ConnectionArgs {
+    /// Check if there is nothing to apply
+    pub fn is_empty(&self) -> bool {
+        self.ssh_args.is_empty() && self.command.is_none() && self.sftp.is_none()
+    }
+}
+
+/// A host found by network, directory or cloud discovery, not yet saved as a profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredHost {
+    /// Stable identifier at the source, e.g. `aws:i-0abc...`, to find the host again on refresh
+    pub id: String,
+    /// Short name to propose for the profile, e.g. the LDAP `cn` or mDNS instance name
+    pub name: String,
+    /// Hostname or IP address to connect to
+    pub hostname: String,
+    /// SSH port the host advertises
+    pub port: u16,
+    /// Login user the source knows of, e.g. an Azure VM's admin user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Private key the source says the host accepts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// Tags describing where the host lives, e.g. `account:123456789012` and `region:eu-west-1`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Where the host was found, e.g. "mDNS"
+    pub source: String,
+}
+
+/// A connection shellbe started that is still open
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Session {
+    /// Short ID naming the session in `shellbe sessions kill`
+    pub id: String,
+    /// Profile name, or the connection string of a target without a profile
+    pub profile: String,
+    /// Where the session connects to, as `Profile::target` shows it
+    pub target: String,
+    /// Process ID of the ssh (or docker, kubectl, sftp) process
+    pub pid: u32,
+    /// When the session started
+    pub started_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Connection history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Timestamp of the connection
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Profile name used
+    pub profile_name: String,
+    /// Host connected to
+    pub hostname: String,
+    /// Exit code of the connection
+    pub exit_code: Option<i32>,
+    /// Duration of the connection
+    pub duration: Option<std::time::Duration>,
+}
+
+impl HistoryEntry {
+    pub fn new(profile_name: impl Into<String>, hostname: impl Into<String>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now(),
+            profile_name: profile_name.into(),
+            hostname: hostname.into(),
+            exit_code: None,
+            duration: None,
+        }
+    }
+
+    pub fn with_result(mut self, exit_code: i32, duration: std::time::Duration) -> Self {
+        self.exit_code = Some(exit_code);
+        self.duration = Some(duration);
+        self
+    }
+}
+
+/// How the connection history is stored on disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryFormat {
+    /// Readable JSON, one entry per line
+    #[default]
+    Json,
+    /// Compact MessagePack, faster to load for very large histories
+    Binary,
+}
+
+/// Connection statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    /// Profile name
+    pub profile_name: String,
+    /// Number of connections
+    pub connection_count: usize,
+    /// Total connection time
+    pub total_duration: std::time::Duration,
+    /// Average connection time
+    pub average_duration: std::time::Duration,
+    /// Last connection timestamp
+    pub last_connection: chrono::DateTime<chrono::Utc>,
+}
+
+/// How history entries are aggregated when viewing grouped history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryGrouping {
+    /// One group per profile
+    Profile,
+    /// One group per calendar day
+    Day,
+}
+
+/// Aggregated connection history for a profile or a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryGroup {
+    /// Group key (profile name or date)
+    pub key: String,
+    /// Number of connections in the group
+    pub connection_count: usize,
+    /// Total connection time
+    pub total_duration: std::time::Duration,
+    /// Most recent connection timestamp
+    pub last_connection: chrono::DateTime<chrono::Utc>,
+}
+
+impl HistoryGroup {
+    pub fn new(key: impl Into<String>, timestamp: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            key: key.into(),
+            connection_count: 0,
+            total_duration: std::time::Duration::ZERO,
+            last_connection: timestamp,
+        }
+    }
+
+    /// Fold a history entry into the group
+    pub fn add(&mut self, entry: &HistoryEntry) {
+        self.connection_count += 1;
+
+        if let Some(duration) = entry.duration {
+            self.total_duration += duration;
+        }
+
+        if entry.timestamp > self.last_connection {
+            self.last_connection = entry.timestamp;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_template_prefers_most_specific() {
+        let templates = || vec![
+            Alias::new("web-{N}", "web-{N}.prod"),
+            Alias::new("web-{N}.eu", "eu-web-{N}"),
+            Alias::new("{host}", "{host}.internal"),
+        ];
+
+        // Every order of the stored aliases picks the same template
+        for rotation in 0..3 {
+            let mut aliases = templates();
+            aliases.rotate_left(rotation);
+
+            assert_eq!(match_template(aliases.clone(), "web-3.eu").unwrap().target, "eu-web-3");
+            assert_eq!(match_template(aliases.clone(), "web-3").unwrap().target, "web-3.prod");
+            assert_eq!(match_template(aliases, "db").unwrap().target, "db.internal");
+        }
+
+        assert!(match_template(vec![Alias::new("web", "web-1")], "web").is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::domain::models::Profile;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::path::Path;
 
@@ -96,6 +97,8 @@ pub enum PluginStatus {
     Enabled,
     /// Plugin is installed but disabled
     Disabled,
+    /// Plugin library changed after it was installed; it isn't loaded until reinstalled
+    Quarantined,
 }
 
 /// Metadata for an installed plugin
@@ -111,4 +114,31 @@ pub struct PluginMetadata {
     pub installed_at: chrono::DateTime<chrono::Utc>,
     /// Last update date
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Findings of the source scan done at install time, kept for later review
+    pub security_findings: Vec<SecurityFinding>,
+    /// SHA-256 of the plugin library when it was installed, checked before every load
+    pub library_hash: Option<String>,
+    /// SHA-256 of the library that last passed security validation, so it isn't validated again
+    pub validated_hash: Option<String>,
+}
+
+/// Something the plugin security scan flagged for review
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityFinding {
+    /// File the finding is in, relative to the plugin source root
+    pub file: String,
+    /// Line number, if the finding is on a specific line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// What was found
+    pub message: String,
+}
+
+impl std::fmt::Display for SecurityFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            None => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
 }
\ No newline at end of file
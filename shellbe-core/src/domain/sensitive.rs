@@ -0,0 +1,81 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Placeholder printed instead of a sensitive value
+pub const REDACTED: &str = "********";
+
+thread_local! {
+    static REDACTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with every [`Sensitive`] value serializing as [`REDACTED`]
+///
+/// Used for output meant for people or other programs; storage serializes normally.
+pub fn with_redaction<R>(f: impl FnOnce() -> R) -> R {
+    let previous = REDACTING.with(|redacting| redacting.replace(true));
+    let result = f();
+    REDACTING.with(|redacting| redacting.set(previous));
+    result
+}
+
+/// A field holding a password, passphrase or other secret
+///
+/// Debug and Display always print [`REDACTED`]. Serialization writes the value, so it
+/// can be stored, except inside [`with_redaction`], which all command output uses.
+/// The value is wiped from memory when dropped.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Sensitive<T: Zeroize>(T);
+
+impl<T: Zeroize> Sensitive<T> {
+    /// Wrap a secret value
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// The secret itself; only pass it to code that needs the real value
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize> Drop for Sensitive<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Zeroize + Serialize> Serialize for Sensitive<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if REDACTING.with(|redacting| redacting.get()) {
+            serializer.serialize_str(REDACTED)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Sensitive<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
@@ -0,0 +1,254 @@
+use crate::domain::models::{Profile, Alias, ConnectionArgs, DiscoveredHost, HistoryEntry, HistoryFormat};
+use crate::domain::sensitive::Sensitive;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// ProfileRepository defines the interface for profile storage
+#[async_trait]
+pub trait ProfileRepository: Send + Sync {
+    /// Add a new profile
+    async fn add(&self, profile: Profile) -> Result<(), Error>;
+
+    /// Get a profile by name
+    async fn get(&self, name: &str) -> Result<Option<Profile>, Error>;
+
+    /// Update an existing profile
+    async fn update(&self, profile: Profile) -> Result<(), Error>;
+
+    /// Remove a profile by name
+    async fn remove(&self, name: &str) -> Result<(), Error>;
+
+    /// List all profiles
+    async fn list(&self) -> Result<Vec<Profile>, Error>;
+
+    /// Check if a profile exists
+    async fn exists(&self, name: &str) -> Result<bool, Error>;
+
+    /// Write changes that are still pending to storage
+    async fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// AliasRepository defines the interface for alias storage
+#[async_trait]
+pub trait AliasRepository: Send + Sync {
+    /// Add a new alias
+    async fn add(&self, alias: Alias) -> Result<(), Error>;
+
+    /// Get an alias by name
+    async fn get(&self, alias_name: &str) -> Result<Option<Alias>, Error>;
+
+    /// Get the target profile name for an alias
+    async fn get_target(&self, alias_name: &str) -> Result<Option<String>, Error>;
+
+    /// Remove an alias
+    async fn remove(&self, alias_name: &str) -> Result<(), Error>;
+
+    /// List all aliases
+    async fn list(&self) -> Result<Vec<Alias>, Error>;
+
+    /// List aliases pointing to a specific profile
+    async fn list_for_profile(&self, profile_name: &str) -> Result<Vec<Alias>, Error>;
+
+    /// Write changes that are still pending to storage
+    async fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// HistoryRepository defines the interface for connection history storage
+#[async_trait]
+pub trait HistoryRepository: Send + Sync {
+    /// Add a history entry
+    async fn add(&self, entry: HistoryEntry) -> Result<(), Error>;
+
+    /// Get recent history entries
+    async fn get_recent(&self, limit: usize) -> Result<Vec<HistoryEntry>, Error>;
+
+    /// Get all history entries in chronological order
+    async fn list(&self) -> Result<Vec<HistoryEntry>, Error>;
+
+    /// Get history for a specific profile
+    async fn get_for_profile(&self, profile_name: &str) -> Result<Vec<HistoryEntry>, Error>;
+
+    /// Get connection statistics
+    async fn get_stats(&self) -> Result<HashMap<String, usize>, Error>;
+
+    /// Remove entries older than the cutoff, returning how many were removed
+    async fn prune_before(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<usize, Error>;
+
+    /// Rewrite the stored history in another format, which later writes keep using
+    async fn convert(&self, _format: HistoryFormat) -> Result<(), Error> {
+        Err(Error::ConfigError("This history storage can't be converted".to_string()))
+    }
+
+    /// Write changes that are still pending to storage
+    async fn flush(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// SshConfigRepository defines the interface for SSH config file operations
+#[async_trait]
+pub trait SshConfigRepository: Send + Sync {
+    /// Import profiles from SSH config
+    async fn import(&self) -> Result<Vec<Profile>, Error>;
+
+    /// Import profiles along with aliases declared as extra Host names
+    async fn import_with_aliases(&self) -> Result<(Vec<Profile>, Vec<Alias>), Error>;
+
+    /// Export profiles to SSH config
+    async fn export(&self, profiles: &[Profile], replace: bool) -> Result<(), Error>;
+
+    /// Export profiles to SSH config, writing aliases as extra Host names
+    async fn export_with_aliases(&self, profiles: &[Profile], aliases: &[Alias], replace: bool) -> Result<(), Error>;
+
+    /// Add a single profile to SSH config
+    async fn add_profile(&self, profile: &Profile) -> Result<(), Error>;
+
+    /// Remove a profile from SSH config
+    async fn remove_profile(&self, profile_name: &str) -> Result<(), Error>;
+}
+
+/// ProfileExporter writes profiles in another SSH client's import format
+pub trait ProfileExporter: Send + Sync {
+    /// Human-readable name of the format, e.g. "PuTTY"
+    fn name(&self) -> &str;
+
+    /// Render the profiles as the contents of one file
+    fn export(&self, profiles: &[Profile]) -> Result<String, Error>;
+}
+
+/// SshService defines the interface for SSH operations
+#[async_trait]
+pub trait SshService: Send + Sync {
+    /// Connect to a profile, applying any extra connection arguments; `started` gets the
+    /// process ID once the ssh process runs
+    async fn connect(&self, profile: &Profile, args: &ConnectionArgs, started: &(dyn Fn(u32) + Send + Sync)) -> Result<i32, Error>;
+
+    /// Build the ssh command line `connect` would run, program name first
+    fn command_line(&self, profile: &Profile, args: &ConnectionArgs) -> Vec<String>;
+
+    /// Test connection to a profile; the address that answered, or `None` if none did
+    async fn test_connection(&self, profile: &Profile) -> Result<Option<SocketAddr>, Error>;
+
+    /// Copy SSH key to a remote server
+    async fn copy_key(&self, profile: &Profile, key_path: &Path) -> Result<(), Error>;
+
+    /// Generate a new SSH key pair of the given type (ed25519 or rsa)
+    async fn generate_key(&self, key_name: &str, key_type: &str, comment: Option<&str>) -> Result<(PathBuf, PathBuf), Error>;
+}
+
+/// SecretBackend stores small secrets, such as the vault key, outside the data files
+pub trait SecretBackend: Send + Sync {
+    /// Human-readable name of the store, e.g. "macOS Keychain"
+    fn name(&self) -> &str;
+
+    /// Read a secret; `None` when it isn't stored
+    fn get(&self, account: &str) -> Result<Option<String>, Error>;
+
+    /// Store or replace a secret
+    fn set(&self, account: &str, secret: &str) -> Result<(), Error>;
+
+    /// Remove a secret, returning whether it was stored
+    fn delete(&self, account: &str) -> Result<bool, Error>;
+}
+
+/// SecretResolver reads a secret from an external secret manager by reference
+///
+/// Resolved values are only handed to ssh when it asks for them and never stored.
+pub trait SecretResolver: Send + Sync {
+    /// Human-readable name of the secret manager, e.g. "1Password"
+    fn name(&self) -> &str;
+
+    /// Read the secret a reference such as `op://vault/item/field` points to
+    fn resolve(&self, reference: &str) -> Result<Sensitive<String>, Error>;
+}
+
+/// SshCredentialIssuer hands out short-lived SSH credentials, e.g. HashiCorp Vault's SSH engine
+#[async_trait]
+pub trait SshCredentialIssuer: Send + Sync {
+    /// Human-readable name of the issuer, e.g. "Vault"
+    fn name(&self) -> &str;
+
+    /// Sign an OpenSSH public key for `principal`, returning the certificate line
+    async fn sign_key(&self, role: &str, public_key: &str, principal: &str) -> Result<String, Error>;
+
+    /// Get a one-time password for logging in to `ip` as `username`
+    async fn one_time_password(&self, role: &str, ip: &str, username: &str) -> Result<Sensitive<String>, Error>;
+}
+
+/// HostDiscovery finds SSH hosts that could become profiles
+#[async_trait]
+pub trait HostDiscovery: Send + Sync {
+    /// Human-readable name of the source, e.g. "LDAP"
+    fn name(&self) -> &str;
+
+    /// Look for hosts; an empty list when none were found
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, Error>;
+}
+
+/// SyncBackend keeps the sync snapshot on a remote store shared between machines
+///
+/// Backends only ever see data that was encrypted on this machine.
+#[async_trait]
+pub trait SyncBackend: Send + Sync {
+    /// Human-readable name of the store, e.g. "WebDAV"
+    fn name(&self) -> &str;
+
+    /// Where the snapshot is kept, for messages
+    fn location(&self) -> &str;
+
+    /// Download the snapshot; `None` when nothing was pushed yet
+    async fn download(&self) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Upload the snapshot, replacing the previous one
+    async fn upload(&self, data: &[u8]) -> Result<(), Error>;
+}
+
+/// Unified error type for domain services
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(String),
+
+    #[error("Profile already exists: {0}")]
+    ProfileAlreadyExists(String),
+
+    #[error("Alias not found: {0}")]
+    AliasNotFound(String),
+
+    #[error("Alias already exists: {0}")]
+    AliasAlreadyExists(String),
+
+    #[error("SSH error: {0}")]
+    SshError(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Config error: {0}")]
+    ConfigError(String),
+
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+
+    #[error("Temporarily unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl Error {
+    /// Whether the failure is transient, like a timeout or a server that isn't reachable
+    /// yet, so trying again may succeed; authentication, missing profiles and invalid
+    /// settings fail the same way every time
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Unavailable(_) => true,
+            Error::IoError(e) => crate::errors::is_transient_io(e.kind()),
+            _ => false,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,472 @@
+use thiserror::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Unified error type for ShellBe
+#[derive(Error, Debug, Clone)]
+pub enum ShellBeError {
+    #[error("Profile error: {0}")]
+    Profile(String),
+
+    #[error("Connection error: {0}")]
+    Connection(String),
+
+    #[error("SSH error: {0}")]
+    Ssh(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Plugin error: {0}")]
+    Plugin(String),
+
+    #[error("Security error: {0}")]
+    Security(String),
+
+    #[error("IO error: {0}")]
+    Io(String),
+
+    #[error("File lock error: {0}")]
+    FileLock(String),
+
+    #[error("Update error: {0}")]
+    Update(String),
+
+    #[error("System requirement error: {0}")]
+    SystemRequirement(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    /// A transient failure, like a timeout or a server that isn't reachable yet
+    #[error("Temporarily unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+/// Process exit codes, one per category of error, so scripts can branch on failures
+pub mod exit_code {
+    /// Any failure without a code of its own
+    pub const FAILURE: u8 = 1;
+    /// A profile, alias, plugin or file doesn't exist
+    pub const NOT_FOUND: u8 = 2;
+    /// Connecting to a host or running SSH failed, or a service was temporarily unavailable
+    pub const CONNECTION: u8 = 3;
+    /// A plugin failed to install, load or run
+    pub const PLUGIN: u8 = 4;
+    /// Refused by a security check or the organization policy
+    pub const SECURITY: u8 = 5;
+    /// A profile, alias or plugin with that name already exists
+    pub const ALREADY_EXISTS: u8 = 6;
+    /// Settings or data files are invalid
+    pub const CONFIG: u8 = 7;
+    /// Checking for or installing an update failed
+    pub const UPDATE: u8 = 8;
+    /// A required tool, such as OpenSSH, is missing
+    pub const SYSTEM_REQUIREMENT: u8 = 9;
+    /// The command line couldn't be parsed
+    pub const USAGE: u8 = 64;
+}
+
+impl ShellBeError {
+    /// Stable identifier of the error's kind, e.g. `not_found`
+    pub fn code(&self) -> &'static str {
+        match self {
+            ShellBeError::Profile(_) => "profile",
+            ShellBeError::Connection(_) => "connection",
+            ShellBeError::Ssh(_) => "ssh",
+            ShellBeError::Config(_) => "config",
+            ShellBeError::Plugin(_) => "plugin",
+            ShellBeError::Security(_) => "security",
+            ShellBeError::Io(_) => "io",
+            ShellBeError::FileLock(_) => "file_lock",
+            ShellBeError::Update(_) => "update",
+            ShellBeError::SystemRequirement(_) => "system_requirement",
+            ShellBeError::NotFound(_) => "not_found",
+            ShellBeError::AlreadyExists(_) => "already_exists",
+            ShellBeError::Unavailable(_) => "unavailable",
+            ShellBeError::Other(_) => "error",
+        }
+    }
+
+    /// Process exit code for the error's category, see [`exit_code`]
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ShellBeError::NotFound(_) => exit_code::NOT_FOUND,
+            ShellBeError::Connection(_) | ShellBeError::Ssh(_) | ShellBeError::Unavailable(_) => exit_code::CONNECTION,
+            ShellBeError::Plugin(_) => exit_code::PLUGIN,
+            ShellBeError::Security(_) => exit_code::SECURITY,
+            ShellBeError::AlreadyExists(_) => exit_code::ALREADY_EXISTS,
+            ShellBeError::Config(_) => exit_code::CONFIG,
+            ShellBeError::Update(_) => exit_code::UPDATE,
+            ShellBeError::SystemRequirement(_) => exit_code::SYSTEM_REQUIREMENT,
+            ShellBeError::Profile(_) | ShellBeError::Io(_) | ShellBeError::FileLock(_) | ShellBeError::Other(_) => exit_code::FAILURE,
+        }
+    }
+
+    /// Whether the failure is transient, like lock contention or a network timeout, so
+    /// trying again may succeed
+    ///
+    /// Decided by the variant alone: errors are [`ShellBeError::Unavailable`] when they are
+    /// created from a timeout, a transient I/O error or a server asking to try again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ShellBeError::FileLock(_) | ShellBeError::Unavailable(_))
+    }
+
+    /// Error for a failed HTTP request, [`ShellBeError::Unavailable`] when it timed out or
+    /// couldn't connect
+    pub fn http(context: &str, error: &reqwest::Error) -> Self {
+        let message = format!("{}: {}", context, error);
+        match error.is_timeout() || error.is_connect() {
+            true => ShellBeError::Unavailable(message),
+            false => ShellBeError::Update(message),
+        }
+    }
+
+    /// Error for an unsuccessful HTTP response; server errors and rate limiting are transient
+    pub fn http_status(context: &str, status: reqwest::StatusCode) -> Self {
+        let message = format!("{}: HTTP error: {}", context, status);
+        match status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            true => ShellBeError::Unavailable(message),
+            false => ShellBeError::Update(message),
+        }
+    }
+
+    /// Category scripts branch on, named after its exit code, e.g. `connection` for SSH errors
+    pub fn category(&self) -> &'static str {
+        match self.exit_code() {
+            exit_code::NOT_FOUND => "not_found",
+            exit_code::CONNECTION => "connection",
+            exit_code::PLUGIN => "plugin",
+            exit_code::SECURITY => "security",
+            exit_code::ALREADY_EXISTS => "already_exists",
+            exit_code::CONFIG => "config",
+            exit_code::UPDATE => "update",
+            exit_code::SYSTEM_REQUIREMENT => "system_requirement",
+            _ => "failure",
+        }
+    }
+
+    /// The message, without the category's prefix
+    pub fn message(&self) -> &str {
+        match self {
+            ShellBeError::Profile(msg)
+            | ShellBeError::Connection(msg)
+            | ShellBeError::Ssh(msg)
+            | ShellBeError::Config(msg)
+            | ShellBeError::Plugin(msg)
+            | ShellBeError::Security(msg)
+            | ShellBeError::Io(msg)
+            | ShellBeError::FileLock(msg)
+            | ShellBeError::Update(msg)
+            | ShellBeError::SystemRequirement(msg)
+            | ShellBeError::NotFound(msg)
+            | ShellBeError::AlreadyExists(msg)
+            | ShellBeError::Unavailable(msg)
+            | ShellBeError::Other(msg) => msg,
+        }
+    }
+
+    /// Constructor of this error's variant
+    fn variant(&self) -> fn(String) -> ShellBeError {
+        match self {
+            ShellBeError::Profile(_) => ShellBeError::Profile,
+            ShellBeError::Connection(_) => ShellBeError::Connection,
+            ShellBeError::Ssh(_) => ShellBeError::Ssh,
+            ShellBeError::Config(_) => ShellBeError::Config,
+            ShellBeError::Plugin(_) => ShellBeError::Plugin,
+            ShellBeError::Security(_) => ShellBeError::Security,
+            ShellBeError::Io(_) => ShellBeError::Io,
+            ShellBeError::FileLock(_) => ShellBeError::FileLock,
+            ShellBeError::Update(_) => ShellBeError::Update,
+            ShellBeError::SystemRequirement(_) => ShellBeError::SystemRequirement,
+            ShellBeError::NotFound(_) => ShellBeError::NotFound,
+            ShellBeError::AlreadyExists(_) => ShellBeError::AlreadyExists,
+            ShellBeError::Unavailable(_) => ShellBeError::Unavailable,
+            ShellBeError::Other(_) => ShellBeError::Other,
+        }
+    }
+}
+
+/// I/O errors that are likely to go away when the operation is tried again
+pub(crate) fn is_transient_io(kind: io::ErrorKind) -> bool {
+    matches!(kind,
+        io::ErrorKind::TimedOut
+        | io::ErrorKind::Interrupted
+        | io::ErrorKind::WouldBlock
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::UnexpectedEof)
+}
+
+/// Variant for a known error type, keeping its category
+fn variant_of(cause: &(dyn std::error::Error + 'static)) -> Option<fn(String) -> ShellBeError> {
+    use crate::domain::Error as DomainError;
+
+    if let Some(error) = cause.downcast_ref::<ShellBeError>() {
+        return Some(error.variant());
+    }
+    if let Some(error) = cause.downcast_ref::<DomainError>() {
+        return Some(match error {
+            DomainError::ProfileNotFound(_) | DomainError::AliasNotFound(_) => ShellBeError::NotFound,
+            DomainError::ProfileAlreadyExists(_) | DomainError::AliasAlreadyExists(_) => ShellBeError::AlreadyExists,
+            DomainError::SshError(_) => ShellBeError::Ssh,
+            DomainError::IoError(e) if is_transient_io(e.kind()) => ShellBeError::Unavailable,
+            DomainError::IoError(_) => ShellBeError::Io,
+            DomainError::ConfigError(_) => ShellBeError::Config,
+            DomainError::PolicyViolation(_) => ShellBeError::Security,
+            DomainError::Unavailable(_) => ShellBeError::Unavailable,
+        });
+    }
+    if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+        return Some(match error.is_timeout() || error.is_connect() {
+            true => ShellBeError::Unavailable,
+            false => ShellBeError::Update,
+        });
+    }
+    if cause.is::<crate::application::UpdateError>() {
+        return Some(ShellBeError::Update);
+    }
+    if let Some(error) = cause.downcast_ref::<io::Error>() {
+        return Some(match is_transient_io(error.kind()) {
+            true => ShellBeError::Unavailable,
+            false => ShellBeError::Io,
+        });
+    }
+    None
+}
+
+// Implement From for common error types
+impl From<io::Error> for ShellBeError {
+    fn from(error: io::Error) -> Self {
+        match is_transient_io(error.kind()) {
+            true => ShellBeError::Unavailable(error.to_string()),
+            false => ShellBeError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for ShellBeError {
+    fn from(error: serde_json::Error) -> Self {
+        ShellBeError::Config(format!("JSON error: {}", error))
+    }
+}
+
+impl From<reqwest::Error> for ShellBeError {
+    fn from(error: reqwest::Error) -> Self {
+        ShellBeError::http("Network error", &error)
+    }
+}
+
+/// Hint for fixing an error, such as names close to one that wasn't found
+///
+/// Command handlers attach it as the outermost context of their error; it is reported as
+/// the suggestion instead of being part of the message.
+#[derive(Debug, Clone)]
+pub struct Suggestion(pub String);
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Why a run failed: the error, the causes behind it and a hint for fixing it
+#[derive(Debug)]
+pub struct ErrorReport {
+    pub error: ShellBeError,
+    /// Causes of the error, outermost first
+    pub causes: Vec<String>,
+    pub suggestion: Option<String>,
+}
+
+impl From<ShellBeError> for ErrorReport {
+    fn from(error: ShellBeError) -> Self {
+        Self { error, causes: Vec::new(), suggestion: None }
+    }
+}
+
+/// Errors from command handlers, in the category of the first known error in the chain
+///
+/// Messages are redacted, since they may quote anything the command saw.
+impl From<anyhow::Error> for ErrorReport {
+    fn from(error: anyhow::Error) -> Self {
+        let suggestion = error.downcast_ref::<Suggestion>().map(|suggestion| suggestion.0.clone());
+        let variant = error.chain().find_map(variant_of).unwrap_or(ShellBeError::Other);
+
+        let mut messages = error.chain()
+            .skip(usize::from(suggestion.is_some()))
+            .map(|cause| crate::utils::redact_text(&cause.to_string()));
+        let message = messages.next().unwrap_or_default();
+
+        Self { error: variant(message), causes: messages.collect(), suggestion }
+    }
+}
+
+impl ErrorReport {
+    /// The report for `--output json`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.error.code(),
+            "category": self.error.category(),
+            "exit_code": self.error.exit_code(),
+            "message": self.error.message(),
+            "context": self.causes,
+            "suggestion": self.suggestion,
+        })
+    }
+}
+
+impl fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        for cause in &self.causes {
+            write!(f, ": {}", cause)?;
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<libloading::Error> for ShellBeError {
+    fn from(error: libloading::Error) -> Self {
+        ShellBeError::Plugin(format!("Library loading error: {}", error))
+    }
+}
+
+// Result type alias for ShellBe
+pub type Result<T> = std::result::Result<T, ShellBeError>;
+
+// Helper functions for error context
+pub trait ErrorContext<T> {
+    fn with_context<C>(self, context: C) -> Result<T>
+    where
+        C: FnOnce() -> String;
+}
+
+impl<T, E: Into<ShellBeError>> ErrorContext<T> for std::result::Result<T, E> {
+    fn with_context<C>(self, context: C) -> Result<T>
+    where
+        C: FnOnce() -> String,
+    {
+        self.map_err(|err| {
+            let shell_be_err = err.into();
+            match shell_be_err {
+                ShellBeError::Io(msg) => ShellBeError::Io(format!("{}: {}", context(), msg)),
+                ShellBeError::Config(msg) => ShellBeError::Config(format!("{}: {}", context(), msg)),
+                ShellBeError::Plugin(msg) => ShellBeError::Plugin(format!("{}: {}", context(), msg)),
+                ShellBeError::Ssh(msg) => ShellBeError::Ssh(format!("{}: {}", context(), msg)),
+                ShellBeError::Connection(msg) => ShellBeError::Connection(format!("{}: {}", context(), msg)),
+                ShellBeError::Profile(msg) => ShellBeError::Profile(format!("{}: {}", context(), msg)),
+                ShellBeError::Security(msg) => ShellBeError::Security(format!("{}: {}", context(), msg)),
+                ShellBeError::FileLock(msg) => ShellBeError::FileLock(format!("{}: {}", context(), msg)),
+                ShellBeError::Update(msg) => ShellBeError::Update(format!("{}: {}", context(), msg)),
+                ShellBeError::SystemRequirement(msg) => ShellBeError::SystemRequirement(format!("{}: {}", context(), msg)),
+                ShellBeError::NotFound(msg) => ShellBeError::NotFound(format!("{}: {}", context(), msg)),
+                ShellBeError::AlreadyExists(msg) => ShellBeError::AlreadyExists(format!("{}: {}", context(), msg)),
+                ShellBeError::Unavailable(msg) => ShellBeError::Unavailable(format!("{}: {}", context(), msg)),
+                ShellBeError::Other(msg) => ShellBeError::Other(format!("{}: {}", context(), msg)),
+            }
+        })
+    }
+}
+
+// Conversion from domain errors
+impl From<crate::domain::Error> for ShellBeError {
+    fn from(error: crate::domain::Error) -> Self {
+        match error {
+            crate::domain::Error::ProfileNotFound(name) => ShellBeError::NotFound(format!("Profile not found: {}", name)),
+            crate::domain::Error::ProfileAlreadyExists(name) => ShellBeError::AlreadyExists(format!("Profile already exists: {}", name)),
+            crate::domain::Error::AliasNotFound(name) => ShellBeError::NotFound(format!("Alias not found: {}", name)),
+            crate::domain::Error::AliasAlreadyExists(name) => ShellBeError::AlreadyExists(format!("Alias already exists: {}", name)),
+            crate::domain::Error::SshError(msg) => ShellBeError::Ssh(msg),
+            crate::domain::Error::IoError(err) => ShellBeError::from(err),
+            crate::domain::Error::ConfigError(msg) => ShellBeError::Config(msg),
+            crate::domain::Error::PolicyViolation(msg) => ShellBeError::Security(format!("Policy violation: {}", msg)),
+            crate::domain::Error::Unavailable(msg) => ShellBeError::Unavailable(msg),
+        }
+    }
+}
+
+// Conversions from other error types in the codebase
+impl From<crate::application::PluginError> for ShellBeError {
+    fn from(error: crate::application::PluginError) -> Self {
+        match error {
+            crate::application::PluginError::NotFound(name) => ShellBeError::NotFound(format!("Plugin not found: {}", name)),
+            crate::application::PluginError::AlreadyExists(name) => ShellBeError::AlreadyExists(format!("Plugin already exists: {}", name)),
+            crate::application::PluginError::LoadFailed(msg) => ShellBeError::Plugin(format!("Failed to load plugin: {}", msg)),
+            crate::application::PluginError::ApiVersionMismatch => ShellBeError::Plugin("Plugin API version mismatch".to_string()),
+            crate::application::PluginError::SecurityValidationFailed(msg) => ShellBeError::Security(msg),
+            crate::application::PluginError::DownloadFailed(msg) => ShellBeError::Update(format!("Download failed: {}", msg)),
+            crate::application::PluginError::InstallationFailed(msg) => ShellBeError::Plugin(format!("Installation failed: {}", msg)),
+            crate::application::PluginError::IoError(err) => ShellBeError::Io(err.to_string()),
+            crate::application::PluginError::HttpError(err) => ShellBeError::Update(format!("HTTP error: {}", err)),
+            crate::application::PluginError::LibraryError(msg) => ShellBeError::Plugin(format!("Library error: {}", msg)),
+            crate::application::PluginError::DomainError(err) => err.into(),
+            crate::application::PluginError::LockError(msg) => ShellBeError::FileLock(msg),
+        }
+    }
+}
+
+impl From<crate::application::UpdateError> for ShellBeError {
+    fn from(error: crate::application::UpdateError) -> Self {
+        match error {
+            crate::application::UpdateError::HttpError(err) => ShellBeError::Update(format!("HTTP error: {}", err)),
+            crate::application::UpdateError::IoError(err) => ShellBeError::Io(err.to_string()),
+            crate::application::UpdateError::JsonError(err) => ShellBeError::Config(format!("JSON error: {}", err)),
+            crate::application::UpdateError::DomainError(err) => err.into(),
+            crate::application::UpdateError::Other(msg) => ShellBeError::Update(msg),
+        }
+    }
+}
+
+// Function to check if file exists and is accessible
+pub fn check_file_exists_and_accessible(path: &PathBuf) -> Result<()> {
+    if !path.exists() {
+        return Err(ShellBeError::NotFound(format!("File not found: {}", path.display())));
+    }
+
+    // Try to open the file to check if it's accessible
+    let file = std::fs::File::open(path)
+        .map_err(|e| ShellBeError::Io(format!("Cannot access file {}: {}", path.display(), e)))?;
+
+    // Check if it's a file
+    let metadata = file.metadata()
+        .map_err(|e| ShellBeError::Io(format!("Cannot get metadata for {}: {}", path.display(), e)))?;
+
+    if !metadata.is_file() {
+        return Err(ShellBeError::Io(format!("{} is not a file", path.display())));
+    }
+
+    Ok(())
+}
+
+// Function to check if directory exists and is accessible
+pub fn check_directory_exists_and_accessible(path: &PathBuf) -> Result<()> {
+    if !path.exists() {
+        return Err(ShellBeError::NotFound(format!("Directory not found: {}", path.display())));
+    }
+
+    // Try to read the directory to check if it's accessible
+    std::fs::read_dir(path)
+        .map_err(|e| ShellBeError::Io(format!("Cannot access directory {}: {}", path.display(), e)))?;
+
+    // Check if it's a directory
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| ShellBeError::Io(format!("Cannot get metadata for {}: {}", path.display(), e)))?;
+
+    if !metadata.is_dir() {
+        return Err(ShellBeError::Io(format!("{} is not a directory", path.display())));
+    }
+
+    Ok(())
+}
\ No newline at end of file
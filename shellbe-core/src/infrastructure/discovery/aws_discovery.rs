@@ -0,0 +1,87 @@
+use super::{existing_key, run_cli_json};
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery};
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Finds running EC2 instances with the AWS CLI (`aws ec2 describe-instances`)
+///
+/// Credentials, the default region and SSO sessions come from the CLI's own configuration.
+#[derive(Default)]
+pub struct AwsDiscovery {
+    region: Option<String>,
+    profile: Option<String>,
+    private: bool,
+}
+
+impl AwsDiscovery {
+    /// Instances in `region` (default: the CLI's), using the named CLI profile if given
+    pub fn new(region: Option<String>, profile: Option<String>, private: bool) -> Self {
+        Self { region, profile, private }
+    }
+
+    fn host(&self, account: &str, instance: &Value) -> Option<DiscoveredHost> {
+        let id = instance["InstanceId"].as_str()?;
+
+        let address = if self.private {
+            instance["PrivateIpAddress"].as_str()
+        } else {
+            instance["PublicIpAddress"].as_str().or(instance["PrivateIpAddress"].as_str())
+        }?;
+
+        let name = instance["Tags"].as_array()
+            .and_then(|tags| tags.iter().find(|tag| tag["Key"] == "Name"))
+            .and_then(|tag| tag["Value"].as_str())
+            .unwrap_or(id);
+
+        // "eu-west-1a" is in eu-west-1
+        let zone = instance["Placement"]["AvailabilityZone"].as_str().unwrap_or_default();
+        let region = zone.trim_end_matches(|c: char| c.is_ascii_lowercase());
+
+        let mut tags = vec!["aws".to_string(), format!("account:{}", account)];
+        if !region.is_empty() {
+            tags.push(format!("region:{}", region));
+        }
+
+        Some(DiscoveredHost {
+            id: format!("aws:{}", id),
+            name: name.to_string(),
+            hostname: address.to_string(),
+            port: 22,
+            username: None,
+            identity_file: instance["KeyName"].as_str().and_then(|key| existing_key(&[&format!("{}.pem", key), key])),
+            tags,
+            source: "AWS".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl HostDiscovery for AwsDiscovery {
+    fn name(&self) -> &str {
+        "AWS"
+    }
+
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, DomainError> {
+        let mut args = vec![
+            "ec2", "describe-instances",
+            "--filters", "Name=instance-state-name,Values=running",
+            "--output", "json",
+        ];
+        if let Some(region) = &self.region {
+            args.extend(["--region", region.as_str()]);
+        }
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile", profile.as_str()]);
+        }
+
+        let output = run_cli_json(self.name(), "aws", &args).await?;
+
+        Ok(output["Reservations"].as_array().into_iter().flatten()
+            .flat_map(|reservation| {
+                let account = reservation["OwnerId"].as_str().unwrap_or("unknown");
+                reservation["Instances"].as_array().into_iter().flatten()
+                    .filter_map(move |instance| self.host(account, instance))
+            })
+            .collect())
+    }
+}
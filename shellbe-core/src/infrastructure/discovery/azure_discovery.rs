@@ -0,0 +1,79 @@
+use super::run_cli_json;
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery};
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Finds running virtual machines with the Azure CLI (`az vm list --show-details`)
+#[derive(Default)]
+pub struct AzureDiscovery {
+    subscription: Option<String>,
+    resource_group: Option<String>,
+    private: bool,
+}
+
+impl AzureDiscovery {
+    /// VMs in `subscription` (default: the CLI's), optionally only in one resource group
+    pub fn new(subscription: Option<String>, resource_group: Option<String>, private: bool) -> Self {
+        Self { subscription, resource_group, private }
+    }
+
+    fn host(&self, vm: &Value) -> Option<DiscoveredHost> {
+        if vm["powerState"].as_str() != Some("VM running") {
+            return None;
+        }
+
+        let name = vm["name"].as_str()?;
+        let first_ip = |field: &str| vm[field].as_str()
+            .and_then(|ips| ips.split(',').map(str::trim).find(|ip| !ip.is_empty()));
+
+        let address = if self.private {
+            first_ip("privateIps")
+        } else {
+            first_ip("publicIps").or_else(|| first_ip("privateIps"))
+        }?;
+
+        // id: /subscriptions/<subscription>/resourceGroups/<group>/providers/...
+        let subscription = vm["id"].as_str()
+            .and_then(|id| id.split('/').skip_while(|part| *part != "subscriptions").nth(1))
+            .unwrap_or("unknown");
+
+        let mut tags = vec!["azure".to_string(), format!("account:{}", subscription)];
+        if let Some(location) = vm["location"].as_str() {
+            tags.push(format!("region:{}", location));
+        }
+
+        Some(DiscoveredHost {
+            id: format!("azure:{}", vm["vmId"].as_str().unwrap_or(name)),
+            name: name.to_string(),
+            hostname: address.to_string(),
+            port: 22,
+            username: vm["osProfile"]["adminUsername"].as_str().map(str::to_string),
+            identity_file: None,
+            tags,
+            source: "Azure".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl HostDiscovery for AzureDiscovery {
+    fn name(&self) -> &str {
+        "Azure"
+    }
+
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, DomainError> {
+        let mut args = vec!["vm", "list", "--show-details", "--output", "json"];
+        if let Some(subscription) = &self.subscription {
+            args.extend(["--subscription", subscription.as_str()]);
+        }
+        if let Some(group) = &self.resource_group {
+            args.extend(["--resource-group", group.as_str()]);
+        }
+
+        let output = run_cli_json(self.name(), "az", &args).await?;
+
+        Ok(output.as_array().into_iter().flatten()
+            .filter_map(|vm| self.host(vm))
+            .collect())
+    }
+}
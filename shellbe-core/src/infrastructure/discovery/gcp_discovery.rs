@@ -0,0 +1,77 @@
+use super::{existing_key, run_cli_json};
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery};
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Finds running Compute Engine instances with the Google Cloud CLI (`gcloud compute instances list`)
+#[derive(Default)]
+pub struct GcpDiscovery {
+    project: Option<String>,
+    private: bool,
+}
+
+impl GcpDiscovery {
+    /// Instances in `project` (default: the CLI's)
+    pub fn new(project: Option<String>, private: bool) -> Self {
+        Self { project, private }
+    }
+
+    fn host(&self, instance: &Value) -> Option<DiscoveredHost> {
+        let name = instance["name"].as_str()?;
+        let interface = &instance["networkInterfaces"][0];
+
+        let address = if self.private {
+            interface["networkIP"].as_str()
+        } else {
+            interface["accessConfigs"][0]["natIP"].as_str().or(interface["networkIP"].as_str())
+        }?;
+
+        // selfLink: .../projects/<project>/zones/<zone>/instances/<name>
+        let self_link = instance["selfLink"].as_str().unwrap_or_default();
+        let segment_after = |segment: &str| self_link.split('/')
+            .skip_while(|part| *part != segment)
+            .nth(1)
+            .map(str::to_string);
+        let project = segment_after("projects").unwrap_or_else(|| "unknown".to_string());
+        let zone = segment_after("zones").unwrap_or_default();
+
+        let mut tags = vec!["gcp".to_string(), format!("account:{}", project)];
+        // "europe-west1-b" is in europe-west1
+        if let Some((region, _)) = zone.rsplit_once('-') {
+            tags.push(format!("region:{}", region));
+        }
+
+        Some(DiscoveredHost {
+            id: format!("gcp:{}", instance["id"].as_str().unwrap_or(self_link)),
+            name: name.to_string(),
+            hostname: address.to_string(),
+            port: 22,
+            username: None,
+            identity_file: existing_key(&["google_compute_engine"]),
+            tags,
+            source: "GCP".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl HostDiscovery for GcpDiscovery {
+    fn name(&self) -> &str {
+        "Google Cloud"
+    }
+
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, DomainError> {
+        let mut args = vec!["compute", "instances", "list", "--filter=status=RUNNING", "--format=json"];
+        let project;
+        if let Some(name) = &self.project {
+            project = format!("--project={}", name);
+            args.push(&project);
+        }
+
+        let output = run_cli_json(self.name(), "gcloud", &args).await?;
+
+        Ok(output.as_array().into_iter().flatten()
+            .filter_map(|instance| self.host(instance))
+            .collect())
+    }
+}
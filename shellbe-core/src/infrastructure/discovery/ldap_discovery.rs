@@ -0,0 +1,102 @@
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery};
+use async_trait::async_trait;
+use ldap3::adapters::{Adapter, EntriesOnly, PagedResults};
+use ldap3::{LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Entries requested per page, below Active Directory's default size limit of 1000
+const LDAP_PAGE_SIZE: i32 = 500;
+
+/// Seconds to wait for the directory server to accept the connection
+const LDAP_CONNECT_TIMEOUT_SECONDS: u64 = 10;
+
+/// Finds hosts in an LDAP directory, such as Active Directory computer objects
+pub struct LdapDiscovery {
+    url: String,
+    base: String,
+    filter: String,
+    attribute: String,
+    bind: Option<(String, Zeroizing<String>)>,
+}
+
+impl LdapDiscovery {
+    /// Search below `base` for entries matching `filter`, taking the hostname from `attribute`
+    pub fn new(url: impl Into<String>, base: impl Into<String>, filter: impl Into<String>, attribute: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            base: base.into(),
+            filter: filter.into(),
+            attribute: attribute.into(),
+            bind: None,
+        }
+    }
+
+    /// Bind as `dn` before searching; anonymous otherwise
+    pub fn with_bind(mut self, dn: impl Into<String>, password: Zeroizing<String>) -> Self {
+        self.bind = Some((dn.into(), password));
+        self
+    }
+
+    fn error(&self, error: LdapError) -> DomainError {
+        DomainError::ConfigError(format!("LDAP search on {} failed: {}", self.url, error))
+    }
+
+    /// The host an entry describes; entries without a hostname or `cn` are skipped
+    fn host(&self, entry: SearchEntry) -> Option<DiscoveredHost> {
+        let first = |attribute: &str| entry.attrs.get(attribute).and_then(|values| values.first()).cloned();
+
+        let hostname = first(&self.attribute).or_else(|| first("cn"))?;
+        let name = first("cn")
+            .unwrap_or_else(|| hostname.split('.').next().unwrap_or(&hostname).to_string())
+            .to_lowercase();
+
+        Some(DiscoveredHost {
+            id: format!("ldap:{}", entry.dn.to_lowercase()),
+            name,
+            hostname: hostname.to_lowercase(),
+            port: 22,
+            username: None,
+            identity_file: None,
+            tags: Vec::new(),
+            source: "LDAP".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl HostDiscovery for LdapDiscovery {
+    fn name(&self) -> &str {
+        "LDAP"
+    }
+
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, DomainError> {
+        let settings = LdapConnSettings::new().set_conn_timeout(Duration::from_secs(LDAP_CONNECT_TIMEOUT_SECONDS));
+        let (connection, mut ldap) = LdapConnAsync::with_settings(settings, &self.url).await
+            .map_err(|e| self.error(e))?;
+        ldap3::drive!(connection);
+
+        if let Some((dn, password)) = &self.bind {
+            ldap.simple_bind(dn, password).await
+                .and_then(|result| result.success())
+                .map_err(|e| self.error(e))?;
+        }
+
+        // Page through large directories instead of hitting the server's size limit
+        let adapters: Vec<Box<dyn Adapter<_, _>>> = vec![
+            Box::new(EntriesOnly::new()),
+            Box::new(PagedResults::new(LDAP_PAGE_SIZE)),
+        ];
+        let mut search = ldap.streaming_search_with(adapters, &self.base, Scope::Subtree, &self.filter, vec![self.attribute.as_str(), "cn"]).await
+            .map_err(|e| self.error(e))?;
+
+        let mut hosts = Vec::new();
+        while let Some(entry) = search.next().await.map_err(|e| self.error(e))? {
+            hosts.extend(self.host(SearchEntry::construct(entry)));
+        }
+        search.finish().await.success().map_err(|e| self.error(e))?;
+
+        let _ = ldap.unbind().await;
+        Ok(hosts)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::domain::{DiscoveredHost, DomainError, HostDiscovery};
+use async_trait::async_trait;
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use std::time::Duration;
+
+/// DNS-SD service type SSH servers advertise, e.g. with Avahi or macOS Remote Login
+const SSH_SERVICE_TYPE: &str = "_ssh._tcp.local.";
+
+/// Finds SSH servers on the local network that advertise `_ssh._tcp` over mDNS
+pub struct MdnsDiscovery {
+    timeout: Duration,
+}
+
+impl MdnsDiscovery {
+    /// Listen for answers for `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+#[async_trait]
+impl HostDiscovery for MdnsDiscovery {
+    fn name(&self) -> &str {
+        "mDNS"
+    }
+
+    async fn discover(&self) -> Result<Vec<DiscoveredHost>, DomainError> {
+        let error = |e: mdns_sd::Error| DomainError::ConfigError(format!("mDNS discovery failed: {}", e));
+
+        let daemon = ServiceDaemon::new().map_err(error)?;
+        let receiver = daemon.browse(SSH_SERVICE_TYPE).map_err(error)?;
+
+        let mut hosts: Vec<DiscoveredHost> = Vec::new();
+        let deadline = tokio::time::Instant::now() + self.timeout;
+
+        while let Ok(Ok(event)) = tokio::time::timeout_at(deadline, receiver.recv_async()).await {
+            let ServiceEvent::ServiceResolved(info) = event else {
+                continue;
+            };
+
+            // "web-1._ssh._tcp.local." is advertised by the host "web-1.local."
+            let name = info.get_fullname()
+                .strip_suffix(SSH_SERVICE_TYPE)
+                .unwrap_or(info.get_fullname())
+                .trim_end_matches('.')
+                .to_string();
+            let hostname = info.get_hostname().trim_end_matches('.').to_lowercase();
+
+            if !hosts.iter().any(|host| host.hostname == hostname && host.port == info.get_port()) {
+                hosts.push(DiscoveredHost {
+                    id: format!("mdns:{}", info.get_fullname()),
+                    name,
+                    hostname,
+                    port: info.get_port(),
+                    username: None,
+                    identity_file: None,
+                    tags: Vec::new(),
+                    source: "mDNS".to_string(),
+                });
+            }
+        }
+
+        let _ = daemon.shutdown();
+        Ok(hosts)
+    }
+}
@@ -0,0 +1,47 @@
+pub mod ldap_discovery;
+pub mod mdns_discovery;
+pub mod aws_discovery;
+pub mod gcp_discovery;
+pub mod azure_discovery;
+
+pub use ldap_discovery::LdapDiscovery;
+pub use mdns_discovery::MdnsDiscovery;
+pub use aws_discovery::AwsDiscovery;
+pub use gcp_discovery::GcpDiscovery;
+pub use azure_discovery::AzureDiscovery;
+
+use crate::domain::DomainError;
+use std::path::PathBuf;
+use tokio::process::Command;
+
+/// Environment variable with the password for `discover ldap --bind-dn`
+pub const LDAP_PASSWORD_ENV: &str = "SHELLBE_LDAP_PASSWORD";
+
+/// Run a cloud provider's CLI and parse the JSON it prints
+///
+/// The CLIs handle credentials, SSO and MFA themselves, so nothing is configured in ShellBe.
+async fn run_cli_json(name: &str, program: &str, args: &[&str]) -> Result<serde_json::Value, DomainError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DomainError::ConfigError(format!("The {} CLI `{}` is not installed", name, program)),
+            _ => DomainError::ConfigError(format!("Failed to run `{}`: {}", program, e)),
+        })?;
+
+    if !output.status.success() {
+        return Err(DomainError::ConfigError(format!(
+            "`{} {}` failed: {}", program, args.join(" "), String::from_utf8_lossy(&output.stderr).trim(),
+        )));
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| DomainError::ConfigError(format!("`{}` printed output that isn't JSON: {}", program, e)))
+}
+
+/// The first of these files that exists in ~/.ssh
+fn existing_key(names: &[&str]) -> Option<PathBuf> {
+    let ssh_dir = crate::utils::home_dir()?.join(".ssh");
+    names.iter().map(|name| ssh_dir.join(name)).find(|path| path.is_file())
+}
@@ -0,0 +1,137 @@
+use crate::domain::{with_redaction, DomainError, Event, EventBus, EventEnvelope, EventFilter, EventListener};
+use crate::utils::append_private;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Journal file in the data directory; rotated files are `events.1.jsonl`, `events.2.jsonl`, ...
+pub const EVENT_JOURNAL_FILE: &str = "events.jsonl";
+
+/// Event listener appending every event to a journal file, one JSON envelope per line
+///
+/// Once the file grows past `max_bytes` it is rotated, keeping `max_files` files in
+/// total. Secrets in profiles are redacted, like in command output.
+pub struct EventJournal {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    /// Only one append or rotation at a time
+    writing: Mutex<()>,
+}
+
+impl EventJournal {
+    /// Journal in `data_dir`, rotated at `max_bytes` and keeping `max_files` files
+    pub fn new(data_dir: &Path, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: data_dir.join(EVENT_JOURNAL_FILE),
+            max_bytes,
+            max_files: max_files.max(1),
+            writing: Mutex::new(()),
+        }
+    }
+
+    /// Path of the current journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Journaled events, oldest first, across the rotated files
+    ///
+    /// Lines that can't be parsed, such as one cut short by a crash, are skipped.
+    pub fn read(&self) -> Result<Vec<EventEnvelope>, DomainError> {
+        let mut events = Vec::new();
+
+        for index in (0..self.max_files).rev() {
+            let path = self.rotated_path(index);
+            let file = match fs::File::open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(DomainError::IoError(e)),
+            };
+
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(DomainError::IoError)?;
+                match serde_json::from_str(&line) {
+                    Ok(envelope) => events.push(envelope),
+                    Err(e) => tracing::debug!("Skipping unreadable line in {}: {}", path.display(), e),
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Publish the journaled events since `since` that match `filter` again on `bus`,
+    /// oldest first; returns how many were replayed
+    pub async fn replay(&self, bus: &EventBus, since: DateTime<Utc>, filter: &EventFilter) -> Result<usize, DomainError> {
+        let events: Vec<EventEnvelope> = self.read()?.into_iter()
+            .filter(|envelope| envelope.timestamp >= since && filter.matches(&envelope.event))
+            .collect();
+        let count = events.len();
+
+        for envelope in events {
+            bus.replay(envelope).await;
+        }
+
+        Ok(count)
+    }
+
+    /// `events.jsonl` for 0, `events.<n>.jsonl` for older files
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.path.clone(),
+            _ => self.path.with_extension(format!("{}.jsonl", index)),
+        }
+    }
+
+    fn append(&self, envelope: &EventEnvelope) -> Result<(), DomainError> {
+        let mut line = with_redaction(|| serde_json::to_vec(envelope))
+            .map_err(|e| DomainError::ConfigError(format!("Failed to serialize event: {}", e)))?;
+        line.push(b'\n');
+
+        let _writing = self.writing.lock().unwrap_or_else(|e| e.into_inner());
+
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        append_private(&self.path)
+            .and_then(|mut file| file.write_all(&line))
+            .map_err(DomainError::IoError)
+    }
+
+    /// Shift every file one up, dropping the oldest
+    fn rotate(&self) -> Result<(), DomainError> {
+        for index in (0..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if !from.exists() {
+                continue;
+            }
+
+            if index + 1 == self.max_files {
+                fs::remove_file(&from).map_err(DomainError::IoError)?;
+            } else {
+                fs::rename(&from, self.rotated_path(index + 1)).map_err(DomainError::IoError)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventListener for EventJournal {
+    async fn on_event(&self, event: &Event) {
+        let envelope = EventEnvelope::new(event.clone());
+        if let Err(e) = self.append(&envelope) {
+            tracing::warn!("Failed to journal {} event: {}", event.kind(), e);
+        }
+    }
+
+    /// Replayed events are already in the journal
+    async fn on_replay(&self, _envelope: &EventEnvelope) {}
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod termius_exporter;
+pub mod putty_exporter;
+
+pub use termius_exporter::TermiusCsvExporter;
+pub use putty_exporter::PuttySessionExporter;
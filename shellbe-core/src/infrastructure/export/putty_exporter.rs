@@ -0,0 +1,63 @@
+use crate::domain::{Profile, ProfileExporter, DomainError};
+
+/// Registry key PuTTY keeps its saved sessions under
+const PUTTY_SESSIONS_KEY: &str = r"HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions";
+
+/// Exports profiles as PuTTY saved sessions in a `.reg` file
+///
+/// Importing the file with `regedit` adds one session per profile. PuTTY only reads
+/// `.ppk` keys, so identity files need converting with `puttygen` first.
+#[derive(Default)]
+pub struct PuttySessionExporter;
+
+impl PuttySessionExporter {
+    /// Create a new PuTTY exporter
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Escape a session name the way PuTTY does for its registry keys
+    fn session_key(name: &str) -> String {
+        let mut key = String::new();
+
+        for (i, c) in name.chars().enumerate() {
+            let escape = matches!(c, ' ' | '\\' | '*' | '?' | '%') || c < ' ' || (i == 0 && c == '.');
+            if escape {
+                key.push_str(&format!("%{:02X}", c as u32));
+            } else {
+                key.push(c);
+            }
+        }
+
+        key
+    }
+
+    /// Quote a registry string value
+    fn string_value(value: &str) -> String {
+        format!("\"{}\"", value.replace('\\', r"\\").replace('"', "\\\""))
+    }
+}
+
+impl ProfileExporter for PuttySessionExporter {
+    fn name(&self) -> &str {
+        "PuTTY"
+    }
+
+    fn export(&self, profiles: &[Profile]) -> Result<String, DomainError> {
+        let mut reg = String::from("Windows Registry Editor Version 5.00\r\n");
+
+        for profile in profiles {
+            reg.push_str(&format!("\r\n[{}\\{}]\r\n", PUTTY_SESSIONS_KEY, Self::session_key(&profile.name)));
+            reg.push_str(&format!("\"HostName\"={}\r\n", Self::string_value(&profile.hostname)));
+            reg.push_str(&format!("\"UserName\"={}\r\n", Self::string_value(&profile.username)));
+            reg.push_str(&format!("\"PortNumber\"=dword:{:08x}\r\n", profile.port));
+            reg.push_str("\"Protocol\"=\"ssh\"\r\n");
+
+            if let Some(identity_file) = &profile.identity_file {
+                reg.push_str(&format!("\"PublicKeyFile\"={}\r\n", Self::string_value(&identity_file.to_string_lossy())));
+            }
+        }
+
+        Ok(reg)
+    }
+}
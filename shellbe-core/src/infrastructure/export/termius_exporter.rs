@@ -0,0 +1,48 @@
+use crate::domain::{Profile, ProfileExporter, DomainError};
+
+/// Columns of Termius' CSV host import
+const TERMIUS_COLUMNS: [&str; 7] = ["Groups", "Label", "Tags", "Hostname/IP", "Protocol", "Port", "Username"];
+
+/// Exports profiles as a CSV file for Termius' host import
+///
+/// Termius keeps keys in its own keychain, so identity files and options are left out.
+#[derive(Default)]
+pub struct TermiusCsvExporter;
+
+impl TermiusCsvExporter {
+    /// Create a new Termius exporter
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Quote a CSV field when it contains a separator, quote or line break
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl ProfileExporter for TermiusCsvExporter {
+    fn name(&self) -> &str {
+        "Termius"
+    }
+
+    fn export(&self, profiles: &[Profile]) -> Result<String, DomainError> {
+        let mut csv = TERMIUS_COLUMNS.join(",");
+        csv.push_str("\r\n");
+
+        for profile in profiles {
+            let port = profile.port.to_string();
+            let tags = profile.tags.join(",");
+            let row = ["", &profile.name, &tags, &profile.hostname, "ssh", &port, &profile.username];
+
+            csv.push_str(&row.iter().map(|value| Self::field(value)).collect::<Vec<_>>().join(","));
+            csv.push_str("\r\n");
+        }
+
+        Ok(csv)
+    }
+}
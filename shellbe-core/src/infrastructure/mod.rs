@@ -0,0 +1,26 @@
+pub mod discovery;
+pub mod event_journal;
+pub mod export;
+pub mod repositories;
+pub mod secrets;
+pub mod ssh;
+pub mod sync;
+
+pub use repositories::{
+    FileProfileRepository,
+    FileStorageConfig,
+    FileAliasRepository,
+    FileHistoryRepository,
+    FilePluginRepository,
+    PluginRepository,
+    FileSshConfigRepository,
+    EncryptedStorage,
+    VaultKey,
+};
+
+pub use event_journal::{EventJournal, EVENT_JOURNAL_FILE};
+pub use discovery::{LdapDiscovery, MdnsDiscovery, AwsDiscovery, GcpDiscovery, AzureDiscovery, LDAP_PASSWORD_ENV};
+pub use export::{TermiusCsvExporter, PuttySessionExporter};
+pub use secrets::{FileSecretBackend, KeychainSecretBackend, OnePasswordResolver, BitwardenResolver, secret_backend, secret_resolver};
+pub use ssh::{ThrushSshService, ControlSocket, ControlSockets, CONTROL_SOCKETS_DIR, Resolver, DNS_CACHE_FILE, VaultSshIssuer, askpass_env, answer_prompt, is_askpass, vault_ssh_issuer};
+pub use sync::{sync_backend, SYNC_PASSWORD_ENV, SYNC_TOKEN_ENV};
\ No newline at end of file
@@ -0,0 +1,205 @@
+use crate::domain::DomainError;
+use crate::utils::write_private;
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// Format version written into encrypted files and the vault header
+pub const VAULT_FORMAT_VERSION: u32 = 1;
+
+/// Length of the derived key in bytes
+const KEY_LEN: usize = 32;
+
+/// Length of the random Argon2 salt in bytes
+const SALT_LEN: usize = 16;
+
+/// 256-bit key the data files are encrypted with; wiped from memory when dropped
+#[derive(Clone)]
+pub struct VaultKey(Zeroizing<[u8; KEY_LEN]>);
+
+impl VaultKey {
+    /// Derive a key from a passphrase or keyfile contents
+    pub fn derive(secret: &[u8], kdf: &KdfParams) -> Result<Self, DomainError> {
+        let salt = BASE64.decode(&kdf.salt)
+            .map_err(|e| DomainError::ConfigError(format!("Invalid vault salt: {}", e)))?;
+
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(KEY_LEN))
+            .map_err(|e| DomainError::ConfigError(format!("Invalid vault key parameters: {}", e)))?;
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(secret, &salt, &mut key[..])
+            .map_err(|e| DomainError::ConfigError(format!("Failed to derive vault key: {}", e)))?;
+
+        Ok(Self(key))
+    }
+
+    /// Key from its base64 form, as stored by an unlocked session
+    pub fn from_base64(encoded: &str) -> Result<Self, DomainError> {
+        let bytes = Zeroizing::new(BASE64.decode(encoded.trim())
+            .map_err(|e| DomainError::ConfigError(format!("Invalid vault key: {}", e)))?);
+
+        let key: [u8; KEY_LEN] = bytes.as_slice().try_into()
+            .map_err(|_| DomainError::ConfigError("Invalid vault key length".to_string()))?;
+
+        Ok(Self(Zeroizing::new(key)))
+    }
+
+    /// Base64 form of the key
+    pub fn to_base64(&self) -> Zeroizing<String> {
+        Zeroizing::new(BASE64.encode(&self.0[..]))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.0[..]))
+    }
+}
+
+impl fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VaultKey(..)")
+    }
+}
+
+/// Argon2id parameters used to turn the passphrase into a key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Random salt, base64
+    pub salt: String,
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Recommended Argon2id parameters with a fresh random salt
+    pub fn generate() -> Self {
+        let salt: [u8; SALT_LEN] = rand_bytes();
+
+        Self {
+            salt: BASE64.encode(salt),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+fn rand_bytes<const N: usize>() -> [u8; N] {
+    use chacha20poly1305::aead::rand_core::RngCore;
+
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// On-disk form of an encrypted file: XChaCha20-Poly1305 ciphertext with its nonce
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptedFile {
+    /// Format version
+    pub shellbe_vault: u32,
+    /// Random 192-bit nonce, base64
+    pub nonce: String,
+    /// Ciphertext and authentication tag, base64
+    pub data: String,
+}
+
+impl EncryptedFile {
+    /// Encrypt data with a fresh random nonce
+    pub fn seal(key: &VaultKey, plaintext: &[u8]) -> Result<Self, DomainError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = key.cipher().encrypt(&nonce, plaintext)
+            .map_err(|_| DomainError::ConfigError("Failed to encrypt data".to_string()))?;
+
+        Ok(Self {
+            shellbe_vault: VAULT_FORMAT_VERSION,
+            nonce: BASE64.encode(nonce),
+            data: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the data; fails with a wrong key or modified contents
+    pub fn open(&self, key: &VaultKey) -> Result<Zeroizing<Vec<u8>>, DomainError> {
+        let invalid = || DomainError::ConfigError("Encrypted data is corrupt".to_string());
+
+        let nonce = BASE64.decode(&self.nonce).map_err(|_| invalid())?;
+        if nonce.len() != 24 {
+            return Err(invalid());
+        }
+        let ciphertext = BASE64.decode(&self.data).map_err(|_| invalid())?;
+
+        key.cipher().decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map(Zeroizing::new)
+            .map_err(|_| DomainError::ConfigError("Wrong vault passphrase or keyfile, or the data was modified".to_string()))
+    }
+
+    /// Parse file contents as an encrypted file; `None` for plain JSON
+    pub fn parse(contents: &[u8]) -> Option<Self> {
+        serde_json::from_slice(contents).ok()
+    }
+}
+
+/// Reads and writes data files, encrypting them when a vault key is set
+///
+/// Without a key, files are plain JSON and encrypted files can't be read. With a key,
+/// writes are always encrypted, while plain files are still read so data written before
+/// the vault was set up keeps loading until it is saved again.
+#[derive(Debug, Clone, Default)]
+pub struct EncryptedStorage {
+    key: Option<VaultKey>,
+}
+
+impl EncryptedStorage {
+    /// Storage for plain JSON files
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// Storage encrypting with the given key
+    pub fn with_key(key: VaultKey) -> Self {
+        Self { key: Some(key) }
+    }
+
+    /// Whether files are written encrypted
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Read a file, decrypting it if needed
+    pub fn read(&self, path: &Path) -> Result<Zeroizing<Vec<u8>>, DomainError> {
+        let contents = Zeroizing::new(fs::read(path).map_err(DomainError::IoError)?);
+
+        match (EncryptedFile::parse(&contents), &self.key) {
+            (Some(encrypted), Some(key)) => encrypted.open(key)
+                .map_err(|e| DomainError::ConfigError(format!("{}: {}", path.display(), e))),
+            (Some(_), None) => Err(DomainError::ConfigError(format!(
+                "{} is encrypted and the vault is locked. Run `shellbe vault unlock` first",
+                path.display()
+            ))),
+            (None, _) => Ok(contents),
+        }
+    }
+
+    /// Write an owner-only file, encrypting it when a key is set
+    pub fn write(&self, path: &Path, contents: &[u8]) -> Result<(), DomainError> {
+        match &self.key {
+            Some(key) => {
+                let encrypted = EncryptedFile::seal(key, contents)?;
+                let json = serde_json::to_vec_pretty(&encrypted)
+                    .map_err(|e| DomainError::ConfigError(format!("Failed to serialize encrypted data: {}", e)))?;
+                write_private(path, &json).map_err(DomainError::IoError)
+            },
+            None => write_private(path, contents).map_err(DomainError::IoError),
+        }
+    }
+}
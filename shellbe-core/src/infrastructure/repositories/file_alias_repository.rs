@@ -0,0 +1,170 @@
+use crate::domain::{AliasRepository, Alias, DomainError};
+use crate::utils::{ensure_directory, ensure_file};
+use super::encrypted_storage::EncryptedStorage;
+use super::write_behind::WriteBehind;
+use async_trait::async_trait;
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Serialized alias entry; a plain target string is kept for aliases without arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum AliasEntry {
+    /// Legacy format: alias name maps directly to its target
+    Target(String),
+    /// Alias with extra connection arguments
+    Full {
+        target: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        command: Option<String>,
+    },
+}
+
+impl AliasEntry {
+    fn target(&self) -> &str {
+        match self {
+            AliasEntry::Target(target) => target,
+            AliasEntry::Full { target, .. } => target,
+        }
+    }
+
+    fn to_alias(&self, name: &str) -> Alias {
+        match self {
+            AliasEntry::Target(target) => Alias::new(name, target),
+            AliasEntry::Full { target, args, command } => Alias {
+                name: name.to_string(),
+                target: target.clone(),
+                args: args.clone(),
+                command: command.clone(),
+            },
+        }
+    }
+}
+
+impl From<Alias> for AliasEntry {
+    fn from(alias: Alias) -> Self {
+        if alias.args.is_empty() && alias.command.is_none() {
+            AliasEntry::Target(alias.target)
+        } else {
+            AliasEntry::Full {
+                target: alias.target,
+                args: alias.args,
+                command: alias.command,
+            }
+        }
+    }
+}
+
+/// File-based implementation of the alias repository
+pub struct FileAliasRepository {
+    aliases: Arc<RwLock<HashMap<String, AliasEntry>>>,
+    store: WriteBehind<RwLock<HashMap<String, AliasEntry>>>,
+}
+
+impl FileAliasRepository {
+    /// Create a new file-based alias repository
+    pub async fn new(config_dir: PathBuf, aliases_file: String, storage: EncryptedStorage) -> Result<Self, DomainError> {
+        // Create config directory if it doesn't exist
+        ensure_directory(&config_dir).await
+            .map_err(|e| DomainError::IoError(e))?;
+
+        let aliases_path = config_dir.join(&aliases_file);
+        let aliases: HashMap<String, AliasEntry> = if aliases_path.exists() {
+            let content = storage.read(&aliases_path)?;
+
+            serde_json::from_slice(&content)
+                .map_err(|e| DomainError::ConfigError(format!("Failed to parse aliases: {}", e)))?
+        } else {
+            // Create empty aliases file
+            ensure_file(&aliases_path, Some("{}")).await
+                .map_err(|e| DomainError::IoError(e))?;
+            HashMap::new()
+        };
+
+        let aliases = Arc::new(RwLock::new(aliases));
+        Ok(Self {
+            store: WriteBehind::new(aliases_path, "aliases", storage, aliases.clone()),
+            aliases,
+        })
+    }
+
+    /// Schedule writing the aliases to disk; bursts of changes are written once
+    async fn save_aliases(&self) -> Result<(), DomainError> {
+        self.store.mark_dirty();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AliasRepository for FileAliasRepository {
+    /// Add a new alias
+    async fn add(&self, alias: Alias) -> Result<(), DomainError> {
+        let mut aliases = self.aliases.write().await;
+
+        if aliases.contains_key(&alias.name) {
+            return Err(DomainError::AliasAlreadyExists(alias.name));
+        }
+
+        aliases.insert(alias.name.clone(), alias.into());
+        drop(aliases);
+
+        self.save_aliases().await
+    }
+
+    /// Get an alias by name
+    async fn get(&self, alias_name: &str) -> Result<Option<Alias>, DomainError> {
+        let aliases = self.aliases.read().await;
+        Ok(aliases.get(alias_name).map(|entry| entry.to_alias(alias_name)))
+    }
+
+    /// Get the target profile name for an alias
+    async fn get_target(&self, alias_name: &str) -> Result<Option<String>, DomainError> {
+        let aliases = self.aliases.read().await;
+        Ok(aliases.get(alias_name).map(|entry| entry.target().to_string()))
+    }
+
+    /// Remove an alias
+    async fn remove(&self, alias_name: &str) -> Result<(), DomainError> {
+        let mut aliases = self.aliases.write().await;
+
+        if !aliases.contains_key(alias_name) {
+            return Err(DomainError::AliasNotFound(alias_name.to_string()));
+        }
+
+        aliases.remove(alias_name);
+        drop(aliases);
+
+        self.save_aliases().await
+    }
+
+    /// List all aliases
+    async fn list(&self) -> Result<Vec<Alias>, DomainError> {
+        let aliases = self.aliases.read().await;
+        let result = aliases.iter()
+            .map(|(name, entry)| entry.to_alias(name))
+            .collect();
+
+        Ok(result)
+    }
+
+    /// List aliases pointing to a specific profile
+    async fn list_for_profile(&self, profile_name: &str) -> Result<Vec<Alias>, DomainError> {
+        let aliases = self.aliases.read().await;
+        let result = aliases.iter()
+            .filter(|(_, entry)| entry.target() == profile_name)
+            .map(|(name, entry)| entry.to_alias(name))
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Write pending changes now
+    async fn flush(&self) -> Result<(), DomainError> {
+        self.store.flush().await
+    }
+}
\ No newline at end of file
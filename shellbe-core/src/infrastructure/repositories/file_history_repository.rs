@@ -0,0 +1,620 @@
+use crate::domain::{HistoryRepository, HistoryEntry, HistoryFormat, DomainError};
+use crate::utils::{ensure_directory, ensure_file};
+use super::encrypted_storage::EncryptedStorage;
+use super::write_behind::{EncodeError, Snapshot, WriteBehind};
+use async_trait::async_trait;
+use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// How long new entries are buffered before the history file is written
+///
+/// Longer than other files: connections are recorded often and nothing reads the
+/// file meanwhile. Whatever is buffered is written when ShellBe exits.
+pub const HISTORY_WRITE_DELAY: Duration = Duration::from_secs(2);
+
+/// First bytes of a history file stored in the binary format: MessagePack records, each
+/// followed by its length as a little-endian `u32`, so the file can be read from the end
+pub const BINARY_HISTORY_MAGIC: &[u8] = b"SBHIST2\n";
+
+/// First bytes of the older binary format, a single MessagePack array; still read
+pub const BINARY_HISTORY_MAGIC_V1: &[u8] = b"SBHIST1\n";
+
+/// Whether file contents are history in either binary format
+pub fn is_binary_history(content: &[u8]) -> bool {
+    content.starts_with(BINARY_HISTORY_MAGIC) || content.starts_with(BINARY_HISTORY_MAGIC_V1)
+}
+
+/// How much of a history file is read at a time when reading it from the end
+const TAIL_CHUNK: u64 = 8 * 1024;
+
+fn parse_error(e: &dyn std::fmt::Display) -> DomainError {
+    DomainError::ConfigError(format!("Failed to parse history: {}", e))
+}
+
+/// Call `f` with each entry of a history file in either format, without collecting them
+///
+/// Returns the format the file is stored in.
+pub fn for_each_history_entry(content: &[u8], mut f: impl FnMut(HistoryEntry)) -> Result<HistoryFormat, DomainError> {
+    if let Some(mut records) = content.strip_prefix(BINARY_HISTORY_MAGIC) {
+        while !records.is_empty() {
+            let before = records.len();
+            let entry: HistoryEntry = rmp_serde::from_read(&mut records).map_err(|e| parse_error(&e))?;
+            let len = (before - records.len()) as u32;
+
+            let suffix = records.get(..4).ok_or_else(|| parse_error(&"truncated record"))?;
+            if suffix != len.to_le_bytes() {
+                return Err(parse_error(&"record length doesn't match"));
+            }
+            records = &records[4..];
+            f(entry);
+        }
+        return Ok(HistoryFormat::Binary);
+    }
+
+    match content.strip_prefix(BINARY_HISTORY_MAGIC_V1) {
+        Some(binary) => {
+            let mut deserializer = rmp_serde::Deserializer::new(binary);
+            EachEntry(f).deserialize(&mut deserializer).map_err(|e| parse_error(&e))?;
+            Ok(HistoryFormat::Binary)
+        },
+        None => {
+            let mut deserializer = serde_json::Deserializer::from_slice(content);
+            EachEntry(f).deserialize(&mut deserializer).map_err(|e| parse_error(&e))?;
+            deserializer.end().map_err(|e| parse_error(&e))?;
+            Ok(HistoryFormat::Json)
+        },
+    }
+}
+
+/// Parse a history file in either format, returning the format it was stored in
+pub fn decode_history(content: &[u8]) -> Result<(Vec<HistoryEntry>, HistoryFormat), DomainError> {
+    let mut entries = Vec::new();
+    let format = for_each_history_entry(content, |entry| entries.push(entry))?;
+    Ok((entries, format))
+}
+
+/// Serialize history entries in the given format
+///
+/// JSON is an array with one entry per line, which stays valid JSON while letting
+/// [`RecordFile`] read it line by line from the end.
+pub fn encode_history(entries: &[HistoryEntry], format: HistoryFormat) -> Result<Vec<u8>, EncodeError> {
+    match format {
+        HistoryFormat::Json => {
+            let mut content = b"[\n".to_vec();
+            for (i, entry) in entries.iter().enumerate() {
+                if i > 0 {
+                    content.extend(b",\n");
+                }
+                serde_json::to_writer(&mut content, entry)?;
+            }
+            if !entries.is_empty() {
+                content.push(b'\n');
+            }
+            content.extend(b"]\n");
+            Ok(content)
+        },
+        HistoryFormat::Binary => {
+            let mut content = BINARY_HISTORY_MAGIC.to_vec();
+            for entry in entries {
+                let record = rmp_serde::to_vec_named(entry)?;
+                content.extend(&record);
+                content.extend((record.len() as u32).to_le_bytes());
+            }
+            Ok(content)
+        },
+    }
+}
+
+/// A plain history file stored as records, read from either end without loading the rest
+///
+/// Pretty-printed JSON and the first binary format aren't records, and encrypted files are
+/// sealed as a whole; those are read whole until the history is next written.
+struct RecordFile {
+    file: File,
+    format: HistoryFormat,
+    /// Offset of the first record, after the magic or the opening bracket
+    start: u64,
+    /// Offset the records not read from the end yet end at
+    end: u64,
+}
+
+impl RecordFile {
+    fn open(path: &Path) -> Result<Option<Self>, DomainError> {
+        let mut file = File::open(path).map_err(DomainError::IoError)?;
+        let end = file.metadata().map_err(DomainError::IoError)?.len();
+
+        let mut head = Vec::new();
+        (&mut file).take(BINARY_HISTORY_MAGIC.len() as u64).read_to_end(&mut head).map_err(DomainError::IoError)?;
+
+        let (format, start) = if head.starts_with(BINARY_HISTORY_MAGIC) {
+            (HistoryFormat::Binary, BINARY_HISTORY_MAGIC.len() as u64)
+        } else if head.starts_with(b"[\n{") || head.starts_with(b"[\n]") {
+            (HistoryFormat::Json, 2)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self { file, format, start, end }))
+    }
+
+    /// The oldest entry
+    fn first(&mut self) -> Result<Option<HistoryEntry>, DomainError> {
+        if self.start >= self.end {
+            return Ok(None);
+        }
+        self.file.seek(SeekFrom::Start(self.start)).map_err(DomainError::IoError)?;
+        let mut reader = BufReader::new(&mut self.file);
+
+        match self.format {
+            HistoryFormat::Binary => rmp_serde::from_read(reader).map(Some).map_err(|e| parse_error(&e)),
+            HistoryFormat::Json => {
+                let mut line = Vec::new();
+                reader.read_until(b'\n', &mut line).map_err(DomainError::IoError)?;
+                parse_json_line(&line)
+            },
+        }
+    }
+
+    /// Visit entries newest first until `f` returns `false`
+    fn rev(&mut self, mut f: impl FnMut(HistoryEntry) -> bool) -> Result<(), DomainError> {
+        match self.format {
+            HistoryFormat::Binary => {
+                while self.end > self.start {
+                    let suffix: [u8; 4] = self.read_back(4)?.try_into()
+                        .map_err(|_| parse_error(&"truncated record"))?;
+                    let len = u64::from(u32::from_le_bytes(suffix));
+                    if len > self.end - self.start {
+                        return Err(parse_error(&"record length doesn't match"));
+                    }
+
+                    let entry = rmp_serde::from_slice(&self.read_back(len)?).map_err(|e| parse_error(&e))?;
+                    if !f(entry) {
+                        break;
+                    }
+                }
+            },
+            HistoryFormat::Json => {
+                // Bytes of lines not visited yet; the last one is the closing bracket
+                let mut unread = Vec::new();
+                loop {
+                    let line = match unread.iter().rposition(|&b| b == b'\n') {
+                        Some(newline) => {
+                            let line = unread.split_off(newline + 1);
+                            unread.truncate(newline);
+                            line
+                        },
+                        None if self.end > self.start => {
+                            let mut chunk = self.read_back(TAIL_CHUNK)?;
+                            chunk.append(&mut unread);
+                            unread = chunk;
+                            continue;
+                        },
+                        None => std::mem::take(&mut unread),
+                    };
+
+                    let done = unread.is_empty() && self.end == self.start;
+                    if let Some(entry) = parse_json_line(&line)? {
+                        if !f(entry) {
+                            break;
+                        }
+                    }
+                    if done {
+                        break;
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Read up to `len` bytes ending where the last read from the end started
+    fn read_back(&mut self, len: u64) -> Result<Vec<u8>, DomainError> {
+        let len = len.min(self.end - self.start);
+        self.end -= len;
+        self.file.seek(SeekFrom::Start(self.end)).map_err(DomainError::IoError)?;
+
+        let mut bytes = vec![0; len as usize];
+        self.file.read_exact(&mut bytes).map_err(DomainError::IoError)?;
+        Ok(bytes)
+    }
+}
+
+/// Entry on one line of the JSON array; `None` for the closing bracket or an empty line
+fn parse_json_line(line: &[u8]) -> Result<Option<HistoryEntry>, DomainError> {
+    let line = line.trim_ascii();
+    let line = line.strip_suffix(b",").unwrap_or(line);
+    if line.is_empty() || line == b"]" {
+        return Ok(None);
+    }
+    serde_json::from_slice(line).map(Some).map_err(|e| parse_error(&e))
+}
+
+/// Deserializes the history array one entry at a time
+struct EachEntry<F>(F);
+
+impl<'de, F: FnMut(HistoryEntry)> DeserializeSeed<'de> for EachEntry<F> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(HistoryEntry)> Visitor<'de> for EachEntry<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a list of history entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(entry) = seq.next_element::<HistoryEntry>()? {
+            (self.0)(entry);
+        }
+        Ok(())
+    }
+}
+
+/// File-based implementation of the history repository
+pub struct FileHistoryRepository {
+    history: Arc<HistoryLog>,
+    store: WriteBehind<HistoryLog>,
+}
+
+/// Recorded entries, plus new ones not merged in yet
+///
+/// Nothing is read at startup: recent entries are read from the end of the file, queries
+/// for one profile or stats stream through it, and the whole history is only loaded once
+/// it has to be rewritten or listed. Adding only pushes to `pending`, so recording a connection never waits
+/// for the history to be read, pruned or serialized.
+struct HistoryLog {
+    path: PathBuf,
+    storage: EncryptedStorage,
+    /// Stored entries once loaded, followed by ones merged in from `pending`
+    entries: RwLock<Vec<HistoryEntry>>,
+    loaded: AtomicBool,
+    pending: Mutex<Vec<HistoryEntry>>,
+    /// Format the file is written in: the one it was read in unless converted; `None` until known
+    format: Mutex<Option<HistoryFormat>>,
+}
+
+impl HistoryLog {
+    fn push(&self, entry: HistoryEntry) {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).push(entry);
+    }
+
+    fn take_pending(&self) -> Vec<HistoryEntry> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn format(&self) -> HistoryFormat {
+        self.format.lock().unwrap_or_else(|e| e.into_inner()).unwrap_or_default()
+    }
+
+    fn set_format(&self, format: HistoryFormat) {
+        *self.format.lock().unwrap_or_else(|e| e.into_inner()) = Some(format);
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.loaded.load(Ordering::SeqCst)
+    }
+
+    /// Visit every stored entry and then the ones not merged in yet, oldest first
+    async fn scan(&self, mut f: impl FnMut(&HistoryEntry)) -> Result<(), DomainError> {
+        let entries = self.entries.read().await;
+        if !self.is_loaded() {
+            let content = self.storage.read(&self.path)?;
+            for_each_history_entry(&content, |entry| f(&entry))?;
+        }
+        entries.iter().for_each(&mut f);
+        drop(entries);
+
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).iter().for_each(f);
+        Ok(())
+    }
+
+    /// Visit entries newest first until `f` returns `false`: the ones not merged in yet, then
+    /// stored ones, reading the file from the end
+    async fn scan_rev(&self, mut f: impl FnMut(&HistoryEntry) -> bool) -> Result<(), DomainError> {
+        let entries = self.entries.read().await;
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if !pending.iter().rev().all(&mut f) || !entries.iter().rev().all(&mut f) || self.is_loaded() {
+            return Ok(());
+        }
+
+        match RecordFile::open(&self.path)? {
+            Some(mut file) => file.rev(|entry| f(&entry)),
+            None => {
+                let content = self.storage.read(&self.path)?;
+                let (stored, _) = decode_history(&content)?;
+                for entry in stored.iter().rev() {
+                    if !f(entry) {
+                        break;
+                    }
+                }
+                Ok(())
+            },
+        }
+    }
+
+    /// Whether any entry is older than `cutoff`; entries are stored oldest first, so only
+    /// the first one is read
+    async fn has_entries_before(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<bool, DomainError> {
+        let entries = self.entries.read().await;
+        let oldest = match self.is_loaded() {
+            true => entries.first().cloned(),
+            false => match RecordFile::open(&self.path)? {
+                Some(mut file) => file.first()?,
+                None => decode_history(&self.storage.read(&self.path)?)?.0.into_iter().next(),
+            },
+        };
+        Ok(oldest.is_some_and(|entry| entry.timestamp < cutoff))
+    }
+
+    /// Read the stored entries in front of the ones added since startup
+    ///
+    /// Called with the entries locked for writing, so the file is loaded once.
+    fn load_into(&self, entries: &mut Vec<HistoryEntry>) -> Result<(), DomainError> {
+        if self.is_loaded() {
+            return Ok(());
+        }
+
+        let content = self.storage.read(&self.path)?;
+        let (stored, format) = decode_history(&content)?;
+        entries.splice(0..0, stored);
+        self.format.lock().unwrap_or_else(|e| e.into_inner()).get_or_insert(format);
+        self.loaded.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// The whole history for changing it, including the entries added since the last read
+    async fn write(&self) -> Result<RwLockWriteGuard<'_, Vec<HistoryEntry>>, DomainError> {
+        let mut entries = self.entries.write().await;
+        self.load_into(&mut entries)?;
+        entries.extend(self.take_pending());
+        Ok(entries)
+    }
+
+    /// The whole history, including the entries added since the last read
+    async fn read(&self) -> Result<RwLockReadGuard<'_, Vec<HistoryEntry>>, DomainError> {
+        Ok(self.write().await?.downgrade())
+    }
+}
+
+#[async_trait]
+impl Snapshot for HistoryLog {
+    async fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        encode_history(&self.read().await?, self.format())
+    }
+
+    fn try_encode(&self) -> Option<Result<Vec<u8>, EncodeError>> {
+        let mut entries = self.entries.try_write().ok()?;
+        if let Err(e) = self.load_into(&mut entries) {
+            return Some(Err(e.into()));
+        }
+        entries.extend(self.take_pending());
+        Some(encode_history(&entries, self.format()))
+    }
+}
+
+impl FileHistoryRepository {
+    /// Create a new file-based history repository
+    pub async fn new(config_dir: PathBuf, history_file: String, storage: EncryptedStorage) -> Result<Self, DomainError> {
+        // Create config directory if it doesn't exist
+        ensure_directory(&config_dir).await
+            .map_err(|e| DomainError::IoError(e))?;
+
+        // Create an empty history file; an existing one is read when first needed
+        let history_path = config_dir.join(&history_file);
+        if !history_path.exists() {
+            ensure_file(&history_path, Some("[]")).await
+                .map_err(|e| DomainError::IoError(e))?;
+        }
+
+        let history = Arc::new(HistoryLog {
+            path: history_path.clone(),
+            storage: storage.clone(),
+            entries: RwLock::default(),
+            loaded: AtomicBool::new(false),
+            pending: Mutex::default(),
+            format: Mutex::default(),
+        });
+        Ok(Self {
+            store: WriteBehind::new(history_path, "history", storage, history.clone())
+                .with_delay(HISTORY_WRITE_DELAY),
+            history,
+        })
+    }
+
+    /// Schedule writing the history to disk; bursts of changes are written once
+    async fn save_history(&self) -> Result<(), DomainError> {
+        self.store.mark_dirty();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HistoryRepository for FileHistoryRepository {
+    /// Add a history entry; it's buffered and written with the next batch
+    async fn add(&self, entry: HistoryEntry) -> Result<(), DomainError> {
+        self.history.push(entry);
+        self.save_history().await
+    }
+
+    /// Get recent history entries, reading only the last `limit` from the end of the file
+    async fn get_recent(&self, limit: usize) -> Result<Vec<HistoryEntry>, DomainError> {
+        let mut recent = Vec::with_capacity(limit.min(1024));
+        if limit > 0 {
+            self.history.scan_rev(|entry| {
+                recent.push(entry.clone());
+                recent.len() < limit
+            }).await?;
+        }
+
+        recent.reverse();
+        Ok(recent)
+    }
+
+    /// Get all history entries in chronological order
+    async fn list(&self) -> Result<Vec<HistoryEntry>, DomainError> {
+        let history = self.history.read().await?;
+        Ok(history.clone())
+    }
+
+    /// Get history for a specific profile
+    async fn get_for_profile(&self, profile_name: &str) -> Result<Vec<HistoryEntry>, DomainError> {
+        let mut result = Vec::new();
+
+        self.history.scan(|entry| {
+            if entry.profile_name == profile_name {
+                result.push(entry.clone());
+            }
+        }).await?;
+
+        Ok(result)
+    }
+
+    /// Get connection statistics
+    async fn get_stats(&self) -> Result<HashMap<String, usize>, DomainError> {
+        let mut stats = HashMap::new();
+
+        self.history.scan(|entry| {
+            *stats.entry(entry.profile_name.clone()).or_insert(0) += 1;
+        }).await?;
+
+        Ok(stats)
+    }
+
+    /// Remove entries older than the cutoff
+    async fn prune_before(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<usize, DomainError> {
+        // Retention is applied on every start; only load the history when something expired
+        if !self.history.has_entries_before(cutoff).await? {
+            return Ok(0);
+        }
+
+        let mut history = self.history.write().await?;
+        let before = history.len();
+        history.retain(|entry| entry.timestamp >= cutoff);
+        let removed = before - history.len();
+        drop(history);
+
+        if removed > 0 {
+            self.save_history().await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Rewrite the history file in another format right away
+    async fn convert(&self, format: HistoryFormat) -> Result<(), DomainError> {
+        self.history.set_format(format);
+        self.store.mark_dirty();
+        self.store.flush().await
+    }
+
+    /// Write pending changes now
+    async fn flush(&self) -> Result<(), DomainError> {
+        self.store.flush().await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(profile: &str, days_ago: i64) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(profile, "example.com");
+        entry.timestamp = chrono::Utc::now() - chrono::Duration::days(days_ago);
+        entry
+    }
+
+    fn entries() -> Vec<HistoryEntry> {
+        vec![entry("old", 30), entry("web", 2), entry("db", 1)]
+    }
+
+    async fn open(dir: &Path) -> FileHistoryRepository {
+        FileHistoryRepository::new(dir.to_path_buf(), "history.json".to_string(), EncryptedStorage::plain()).await.unwrap()
+    }
+
+    fn names(entries: Vec<HistoryEntry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.profile_name).collect()
+    }
+
+    #[tokio::test]
+    async fn test_recent_entries_are_read_from_the_end() {
+        for format in [HistoryFormat::Json, HistoryFormat::Binary] {
+            let dir = tempfile::tempdir().unwrap();
+            let mut content = encode_history(&entries(), format).unwrap();
+
+            // Break the oldest entry; only reading the whole file notices
+            match format {
+                HistoryFormat::Json => content = [&content[..2], &b"{broken},\n"[..], &content[2..]].concat(),
+                HistoryFormat::Binary => content[BINARY_HISTORY_MAGIC.len()] = 0xc1,
+            }
+            std::fs::write(dir.path().join("history.json"), content).unwrap();
+
+            let history = open(dir.path()).await;
+            assert_eq!(names(history.get_recent(2).await.unwrap()), vec!["web", "db"]);
+            assert!(history.list().await.is_err());
+
+            // New entries come first, without waiting for a write
+            history.add(entry("app", 0)).await.unwrap();
+            assert_eq!(names(history.get_recent(2).await.unwrap()), vec!["db", "app"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_older_layouts_are_still_read() {
+        let mut binary = BINARY_HISTORY_MAGIC_V1.to_vec();
+        binary.extend(rmp_serde::to_vec_named(&entries()).unwrap());
+        let pretty = serde_json::to_vec_pretty(&entries()).unwrap();
+
+        for content in [binary, pretty] {
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::write(dir.path().join("history.json"), content).unwrap();
+
+            let history = open(dir.path()).await;
+            assert_eq!(names(history.get_recent(2).await.unwrap()), vec!["web", "db"]);
+            assert_eq!(names(history.list().await.unwrap()), vec!["old", "web", "db"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_only_reads_the_oldest_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut content = encode_history(&entries(), HistoryFormat::Json).unwrap();
+        content.truncate(content.len() - b"]\n".len());
+        content.extend(b",\n{broken}\n]\n");
+        std::fs::write(dir.path().join("history.json"), content).unwrap();
+
+        // Nothing is older than the oldest entry, so the rest of the file isn't read
+        let history = open(dir.path()).await;
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(60);
+        assert_eq!(history.prune_before(cutoff).await.unwrap(), 0);
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(10);
+        assert!(history.prune_before(cutoff).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pruned_history_keeps_record_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("history.json"), encode_history(&entries(), HistoryFormat::Json).unwrap()).unwrap();
+
+        let history = open(dir.path()).await;
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(10);
+        assert_eq!(history.prune_before(cutoff).await.unwrap(), 1);
+        history.flush().await.unwrap();
+
+        let content = std::fs::read_to_string(dir.path().join("history.json")).unwrap();
+        assert_eq!(content.lines().count(), 4);
+        let reopened = open(dir.path()).await;
+        assert_eq!(names(reopened.get_recent(10).await.unwrap()), vec!["web", "db"]);
+    }
+}
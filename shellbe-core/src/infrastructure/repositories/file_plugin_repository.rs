@@ -1,6 +1,8 @@
-use crate::domain::{PluginMetadata, PluginStatus, PluginInfo};
+use crate::domain::{PluginMetadata, PluginStatus, PluginInfo, SecurityFinding};
 use crate::application::PluginError;
-use crate::utils::{FileLock, ensure_directory, ensure_file};
+use crate::utils::{ensure_directory, ensure_file};
+use super::encrypted_storage::EncryptedStorage;
+use super::write_behind::WriteBehind;
 use async_trait::async_trait;
 use std::path::PathBuf;
 use std::fs;
@@ -25,6 +27,11 @@ pub trait PluginRepository: Send + Sync {
 
     /// Update plugin status
     async fn update_status(&self, name: &str, status: PluginStatus) -> Result<(), PluginError>;
+
+    /// Write changes that are still pending to storage
+    async fn flush(&self) -> Result<(), PluginError> {
+        Ok(())
+    }
 }
 
 /// Serializable plugin metadata for storage
@@ -48,6 +55,15 @@ struct SerializablePluginMetadata {
     pub installed_at: chrono::DateTime<chrono::Utc>,
     /// Last update date
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Findings of the install-time source scan
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security_findings: Vec<SecurityFinding>,
+    /// SHA-256 of the plugin library recorded at install time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_hash: Option<String>,
+    /// SHA-256 of the plugin library that last passed security validation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validated_hash: Option<String>,
 }
 
 impl From<PluginMetadata> for SerializablePluginMetadata {
@@ -62,6 +78,9 @@ impl From<PluginMetadata> for SerializablePluginMetadata {
             path: metadata.path.to_string_lossy().to_string(),
             installed_at: metadata.installed_at,
             updated_at: metadata.updated_at,
+            security_findings: metadata.security_findings,
+            library_hash: metadata.library_hash,
+            validated_hash: metadata.validated_hash,
         }
     }
 }
@@ -80,15 +99,17 @@ impl From<SerializablePluginMetadata> for PluginMetadata {
             path: PathBuf::from(serializable.path),
             installed_at: serializable.installed_at,
             updated_at: serializable.updated_at,
+            security_findings: serializable.security_findings,
+            library_hash: serializable.library_hash,
+            validated_hash: serializable.validated_hash,
         }
     }
 }
 
 /// File-based implementation of the plugin repository
 pub struct FilePluginRepository {
-    config_dir: PathBuf,
-    plugins_file: String,
     plugins: Arc<RwLock<Vec<SerializablePluginMetadata>>>,
+    store: WriteBehind<RwLock<Vec<SerializablePluginMetadata>>>,
 }
 
 impl FilePluginRepository {
@@ -112,45 +133,16 @@ impl FilePluginRepository {
             Vec::new()
         };
 
+        let plugins = Arc::new(RwLock::new(plugins));
         Ok(Self {
-            config_dir,
-            plugins_file,
-            plugins: Arc::new(RwLock::new(plugins)),
+            store: WriteBehind::new(plugins_path, "plugins", EncryptedStorage::plain(), plugins.clone()),
+            plugins,
         })
     }
 
-    /// Save plugins to disk with proper file locking
+    /// Schedule writing the plugins to disk; bursts of changes are written once
     async fn save_plugins(&self) -> Result<(), PluginError> {
-        let plugins_path = self.config_dir.join(&self.plugins_file);
-
-        // Acquire a lock for writing
-        let mut lock = FileLock::new(&plugins_path).await;
-        if !lock.acquire(5000).await.map_err(|e| PluginError::IoError(e))? {
-            return Err(PluginError::InstallationFailed("Failed to acquire lock for writing plugins".to_string()));
-        }
-
-        // Get a snapshot of the plugins
-        let plugins = {
-            let plugins = self.plugins.read().await;
-            plugins.clone()
-        };
-
-        // Write to a temporary file first
-        let temp_path = plugins_path.with_extension("temp");
-        let file = fs::File::create(&temp_path)
-            .map_err(|e| PluginError::IoError(e))?;
-
-        serde_json::to_writer_pretty(file, &plugins)
-            .map_err(|e| PluginError::InstallationFailed(format!("Failed to save plugins: {}", e)))?;
-
-        // Rename the temporary file to the actual file
-        // This provides atomic file replacement
-        fs::rename(&temp_path, &plugins_path)
-            .map_err(|e| PluginError::IoError(e))?;
-
-        // Release the lock
-        lock.release().await.map_err(|e| PluginError::IoError(e))?;
-
+        self.store.mark_dirty();
         Ok(())
     }
 }
@@ -226,4 +218,9 @@ impl PluginRepository for FilePluginRepository {
 
         self.save_plugins().await
     }
+    /// Write pending changes now
+    async fn flush(&self) -> Result<(), PluginError> {
+        self.store.flush().await
+            .map_err(|e| PluginError::InstallationFailed(e.to_string()))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,264 @@
+use crate::domain::{ProfileRepository, Profile, DomainError};
+use crate::utils::{ensure_directory, ensure_file};
+use super::encrypted_storage::EncryptedStorage;
+use super::write_behind::{merge_maps, EncodeError, Snapshot, WriteBehind};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::collections::HashMap;
+use std::sync::Arc;
+use arc_swap::ArcSwap;
+use std::sync::Mutex;
+
+/// Struct for configuring the file storage
+#[derive(Debug, Clone)]
+pub struct FileStorageConfig {
+    /// Directory where configuration files are stored
+    pub config_dir: PathBuf,
+    /// Profile configuration file name
+    pub profiles_file: String,
+    /// Reads and writes the file, encrypted when the vault is set up
+    pub storage: EncryptedStorage,
+}
+
+impl Default for FileStorageConfig {
+    fn default() -> Self {
+        Self {
+            config_dir: crate::utils::data_dir_path(),
+            profiles_file: "profiles.json".to_string(),
+            storage: EncryptedStorage::plain(),
+        }
+    }
+}
+
+/// Profiles by name; values are shared so a new version of the map copies pointers, not profiles
+type ProfileMap = HashMap<String, Arc<Profile>>;
+
+/// The current profiles, shared with the write-behind store
+struct Profiles {
+    current: ArcSwap<ProfileMap>,
+    /// Serializes changes, including merges of what another process saved
+    writer: Mutex<()>,
+}
+
+impl Profiles {
+    /// Apply a change to a copy of the current profiles and publish it
+    fn change<F>(&self, change: F) -> Result<(), DomainError>
+    where
+        F: FnOnce(&mut ProfileMap) -> Result<(), DomainError>,
+    {
+        let _writer = self.writer.lock().unwrap();
+
+        let mut profiles = ProfileMap::clone(&self.current.load());
+        change(&mut profiles)?;
+        self.current.store(Arc::new(profiles));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Snapshot for Profiles {
+    async fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        self.current.encode().await
+    }
+
+    fn try_encode(&self) -> Option<Result<Vec<u8>, EncodeError>> {
+        self.current.try_encode()
+    }
+
+    /// Keep the profiles this process added, changed or removed, and take every other one as
+    /// the other process saved it
+    fn merge(&self, base: &[u8], theirs: &[u8]) -> Result<(), EncodeError> {
+        let base: ProfileMap = serde_json::from_slice(base)?;
+        let theirs: ProfileMap = serde_json::from_slice(theirs)?;
+        self.change(|ours| {
+            *ours = merge_maps(&base, ours, &theirs);
+            Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// File-based implementation of the profile repository
+///
+/// Readers load the current snapshot without locking. Changes are serialized by a writer
+/// mutex, publish a new snapshot and leave saving the file to the write-behind task, which
+/// merges in profiles another shellbe process saved meanwhile rather than overwriting them.
+pub struct FileProfileRepository {
+    profiles: Arc<Profiles>,
+    store: WriteBehind<Profiles>,
+}
+
+impl FileProfileRepository {
+    /// Create a new file-based profile repository
+    pub async fn new(config: FileStorageConfig) -> Result<Self, DomainError> {
+        // Create config directory if it doesn't exist
+        ensure_directory(&config.config_dir).await
+            .map_err(|e| DomainError::IoError(e))?;
+
+        let profiles_path = config.config_dir.join(&config.profiles_file);
+        let content = if profiles_path.exists() {
+            config.storage.read(&profiles_path)?
+        } else {
+            // Create an empty profiles file
+            ensure_file(&profiles_path, Some("{}")).await
+                .map_err(|e| DomainError::IoError(e))?;
+            b"{}".to_vec()
+        };
+        let profiles: ProfileMap = serde_json::from_slice(&content)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to parse profiles: {}", e)))?;
+
+        let profiles = Arc::new(Profiles {
+            current: ArcSwap::from_pointee(profiles),
+            writer: Mutex::new(()),
+        });
+        Ok(Self {
+            store: WriteBehind::new(profiles_path, "profiles", config.storage, profiles.clone()).merging(content),
+            profiles,
+        })
+    }
+
+    /// Apply a change to a copy of the current profiles and publish it
+    async fn change<F>(&self, change: F) -> Result<(), DomainError>
+    where
+        F: FnOnce(&mut ProfileMap) -> Result<(), DomainError>,
+    {
+        self.profiles.change(change)?;
+        self.save_profiles().await
+    }
+
+    /// Schedule writing the profiles to disk; bursts of changes are written once
+    async fn save_profiles(&self) -> Result<(), DomainError> {
+        self.store.mark_dirty();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProfileRepository for FileProfileRepository {
+    /// Add a new profile
+    async fn add(&self, profile: Profile) -> Result<(), DomainError> {
+        self.change(|profiles| {
+            if profiles.contains_key(&profile.name) {
+                return Err(DomainError::ProfileAlreadyExists(profile.name));
+            }
+
+            profiles.insert(profile.name.clone(), Arc::new(profile));
+            Ok(())
+        }).await
+    }
+
+    /// Get a profile by name
+    async fn get(&self, name: &str) -> Result<Option<Profile>, DomainError> {
+        Ok(self.profiles.current.load().get(name).map(|profile| Profile::clone(profile)))
+    }
+
+    /// Update an existing profile
+    async fn update(&self, profile: Profile) -> Result<(), DomainError> {
+        self.change(|profiles| {
+            if !profiles.contains_key(&profile.name) {
+                return Err(DomainError::ProfileNotFound(profile.name));
+            }
+
+            profiles.insert(profile.name.clone(), Arc::new(profile));
+            Ok(())
+        }).await
+    }
+
+    /// Remove a profile by name
+    async fn remove(&self, name: &str) -> Result<(), DomainError> {
+        self.change(|profiles| {
+            profiles.remove(name)
+                .map(|_| ())
+                .ok_or_else(|| DomainError::ProfileNotFound(name.to_string()))
+        }).await
+    }
+
+    /// List all profiles
+    async fn list(&self) -> Result<Vec<Profile>, DomainError> {
+        Ok(self.profiles.current.load().values().map(|profile| Profile::clone(profile)).collect())
+    }
+
+    /// Check if a profile exists
+    async fn exists(&self, name: &str) -> Result<bool, DomainError> {
+        Ok(self.profiles.current.load().contains_key(name))
+    }
+
+    /// Write pending changes now
+    async fn flush(&self) -> Result<(), DomainError> {
+        self.store.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    async fn open(dir: &Path) -> FileProfileRepository {
+        FileProfileRepository::new(FileStorageConfig {
+            config_dir: dir.to_path_buf(),
+            profiles_file: "profiles.json".to_string(),
+            storage: EncryptedStorage::plain(),
+        }).await.unwrap()
+    }
+
+    fn names(profiles: Vec<Profile>) -> Vec<String> {
+        let mut names: Vec<_> = profiles.into_iter().map(|profile| profile.name).collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_instances_merge_instead_of_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = open(dir.path()).await;
+        seed.add(Profile::new("shared", "shared.example.com", "admin")).await.unwrap();
+        seed.add(Profile::new("old", "old.example.com", "admin")).await.unwrap();
+        seed.flush().await.unwrap();
+
+        // Two terminals, both loaded before either saves
+        let first = open(dir.path()).await;
+        let second = open(dir.path()).await;
+
+        first.add(Profile::new("web-1", "web-1.example.com", "deploy")).await.unwrap();
+        first.remove("old").await.unwrap();
+        first.flush().await.unwrap();
+
+        second.add(Profile::new("db", "db.example.com", "postgres")).await.unwrap();
+        let mut shared = second.get("shared").await.unwrap().unwrap();
+        shared.port = 2222;
+        second.update(shared).await.unwrap();
+        second.flush().await.unwrap();
+
+        // The second save kept the first's new profile and removal, and picked them up itself
+        assert_eq!(names(second.list().await.unwrap()), vec!["db", "shared", "web-1"]);
+
+        let reopened = open(dir.path()).await;
+        assert_eq!(names(reopened.list().await.unwrap()), vec!["db", "shared", "web-1"]);
+        assert_eq!(reopened.get("shared").await.unwrap().unwrap().port, 2222);
+    }
+
+    #[tokio::test]
+    async fn test_same_length_rewrite_is_merged() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed = open(dir.path()).await;
+        seed.add(Profile::new("shared", "shared.example.com", "admin")).await.unwrap();
+        seed.flush().await.unwrap();
+
+        let repo = open(dir.path()).await;
+
+        // Another process changes a host without changing the size or the modification time
+        let path = dir.path().join("profiles.json");
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let content = std::fs::read_to_string(&path).unwrap().replace("shared.example.com", "shared.example.org");
+        std::fs::write(&path, content).unwrap();
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+        repo.add(Profile::new("db", "db.example.com", "postgres")).await.unwrap();
+        repo.flush().await.unwrap();
+
+        let reopened = open(dir.path()).await;
+        assert_eq!(reopened.get("shared").await.unwrap().unwrap().hostname, "shared.example.org");
+        assert!(reopened.exists("db").await.unwrap());
+    }
+}
@@ -1,10 +1,13 @@
+pub mod encrypted_storage;
+pub mod write_behind;
 pub mod file_profile_repository;
 pub mod file_alias_repository;
 pub mod file_history_repository;
 pub mod file_plugin_repository;
 pub mod ssh_config_repository;
 
-pub use file_profile_repository::FileProfileRepository;
+pub use encrypted_storage::{EncryptedStorage, VaultKey};
+pub use file_profile_repository::{FileProfileRepository, FileStorageConfig};
 pub use file_alias_repository::FileAliasRepository;
 pub use file_history_repository::FileHistoryRepository;
 pub use file_plugin_repository::{FilePluginRepository, PluginRepository};
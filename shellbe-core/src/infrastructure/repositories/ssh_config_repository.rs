@@ -1,5 +1,5 @@
-use crate::domain::{Profile, SshConfigRepository, DomainError};
-use crate::utils::{backup_file, ensure_directory, ensure_file};
+use crate::domain::{Profile, Alias, SshConfigRepository, DomainError};
+use crate::utils::{backup_file, ensure_directory, ensure_file, expand_path, expand_value};
 use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 use std::fs::{self, File};
@@ -47,9 +47,12 @@ impl FileSshConfigRepository {
     }
 
     /// Parse SSH config file and extract profiles
-    fn parse_config(&self) -> Result<Vec<Profile>, DomainError> {
+    ///
+    /// With `with_aliases`, multi-name `Host` lines become a profile named after the
+    /// first name plus aliases for the remaining names instead of being skipped.
+    fn parse_config(&self, with_aliases: bool) -> Result<(Vec<Profile>, Vec<Alias>), DomainError> {
         if !self.ssh_config_path.exists() {
-            return Ok(Vec::new());
+            return Ok((Vec::new(), Vec::new()));
         }
 
         let file = File::open(&self.ssh_config_path)
@@ -57,7 +60,9 @@ impl FileSshConfigRepository {
 
         let reader = BufReader::new(file);
         let mut profiles = Vec::new();
+        let mut aliases = Vec::new();
         let mut current_host: Option<String> = None;
+        let mut current_aliases: Vec<String> = Vec::new();
         let mut hostname: Option<String> = None;
         let mut username: Option<String> = None;
         let mut port: u16 = 22;
@@ -113,19 +118,24 @@ impl FileSshConfigRepository {
                         profile.port = port;
 
                         if let Some(identity) = identity_file.take() {
-                            profile.identity_file = Some(PathBuf::from(shellexpand::tilde(&identity).into_owned()));
+                            profile.identity_file = Some(expand_path(&identity));
                         }
 
                         for (key, value) in options.drain(..) {
                             profile.options.insert(key, value);
                         }
 
+                        for alias in current_aliases.drain(..) {
+                            aliases.push(Alias::new(alias, &profile.name));
+                        }
+
                         profiles.push(profile);
                     }
                 }
 
                 // Reset for new host
                 current_host = None;
+                current_aliases.clear();
                 hostname = None;
                 username = None;
                 port = 22;
@@ -138,9 +148,14 @@ impl FileSshConfigRepository {
                 // If multiple hosts, split by whitespace and take first
                 let host_parts: Vec<&str> = host_value.split_whitespace().collect();
 
-                // Skip wildcards/patterns and multiple hosts
-                if host_parts.len() == 1 && !host_parts[0].contains('*') && !host_parts[0].contains('?') && !host_parts[0].contains('%') {
+                let is_pattern = |p: &&str| p.contains('*') || p.contains('?') || p.contains('%') || p.starts_with('!');
+
+                // Skip wildcards/patterns, and multiple hosts unless they are imported as aliases
+                if host_parts.len() == 1 && !is_pattern(&host_parts[0]) {
+                    current_host = Some(host_parts[0].to_string());
+                } else if with_aliases && host_parts.len() > 1 && !host_parts.iter().any(is_pattern) {
                     current_host = Some(host_parts[0].to_string());
+                    current_aliases = host_parts[1..].iter().map(|p| p.to_string()).collect();
                 }
             } else if let Some(_) = current_host.as_ref() {
                 // Parse host properties - handle more complex whitespace formats
@@ -174,24 +189,43 @@ impl FileSshConfigRepository {
                 profile.port = port;
 
                 if let Some(identity) = identity_file {
-                    profile.identity_file = Some(PathBuf::from(shellexpand::tilde(&identity).into_owned()));
+                    profile.identity_file = Some(expand_path(&identity));
                 }
 
                 for (key, value) in options {
                     profile.options.insert(key, value);
                 }
 
+                for alias in current_aliases {
+                    aliases.push(Alias::new(alias, &profile.name));
+                }
+
                 profiles.push(profile);
             }
         }
 
-        Ok(profiles)
+        Ok((profiles, aliases))
     }
 
     /// Format a profile for SSH config output
     fn format_profile(&self, profile: &Profile) -> String {
-        let mut output = format!("Host {}\n", profile.name);
-        output.push_str(&format!("    HostName {}\n", profile.hostname));
+        self.format_profile_with_aliases(profile, &[])
+    }
+
+    /// Format a profile for SSH config output, listing aliases as extra Host names
+    fn format_profile_with_aliases(&self, profile: &Profile, aliases: &[&str]) -> String {
+        let mut host_names = vec![profile.name.as_str()];
+        host_names.extend_from_slice(aliases);
+
+        let mut output = format!("Host {}\n", host_names.join(" "));
+        // With resolve_to, ssh connects to the address but keeps checking the hostname's key
+        match profile.resolve_to {
+            Some(address) => {
+                output.push_str(&format!("    HostName {}\n", address));
+                output.push_str(&format!("    HostKeyAlias {}\n", profile.hostname));
+            },
+            None => output.push_str(&format!("    HostName {}\n", profile.hostname)),
+        }
         output.push_str(&format!("    User {}\n", profile.username));
 
         if profile.port != 22 {
@@ -199,7 +233,7 @@ impl FileSshConfigRepository {
         }
 
         if let Some(identity) = &profile.identity_file {
-            output.push_str(&format!("    IdentityFile {}\n", identity.display()));
+            output.push_str(&format!("    IdentityFile {}\n", expand_path(identity).display()));
         }
 
         for (key, value) in &profile.options {
@@ -207,7 +241,7 @@ impl FileSshConfigRepository {
             let key = key.chars().next().map(|c| c.to_uppercase().collect::<String>())
                 .unwrap_or_default() + &key[1..];
 
-            output.push_str(&format!("    {} {}\n", key, value));
+            output.push_str(&format!("    {} {}\n", key, expand_value(value)));
         }
 
         // Add a comment with shellbe metadata
@@ -253,13 +287,31 @@ impl SshConfigRepository for FileSshConfigRepository {
     /// Import profiles from SSH config
     async fn import(&self) -> Result<Vec<Profile>, DomainError> {
         self.ensure_config_file().await?;
-        self.parse_config()
+        self.parse_config(false).map(|(profiles, _)| profiles)
+    }
+
+    /// Import profiles and the aliases declared as extra Host names
+    async fn import_with_aliases(&self) -> Result<(Vec<Profile>, Vec<Alias>), DomainError> {
+        self.ensure_config_file().await?;
+        self.parse_config(true)
     }
 
     /// Export profiles to SSH config
     async fn export(&self, profiles: &[Profile], replace: bool) -> Result<(), DomainError> {
+        self.export_with_aliases(profiles, &[], replace).await
+    }
+
+    /// Export profiles to SSH config, writing aliases as extra Host names
+    async fn export_with_aliases(&self, profiles: &[Profile], aliases: &[Alias], replace: bool) -> Result<(), DomainError> {
         self.ensure_config_file().await?;
 
+        let aliases_for = |profile: &Profile| -> Vec<&str> {
+            aliases.iter()
+                .filter(|a| a.target == profile.name)
+                .map(|a| a.name.as_str())
+                .collect()
+        };
+
         // Create a backup
         let backup_path = self.backup_config().await?;
 
@@ -275,7 +327,7 @@ impl SshConfigRepository for FileSshConfigRepository {
             writeln!(file).map_err(|e| DomainError::IoError(e))?;
 
             for profile in profiles {
-                write!(file, "{}", self.format_profile(profile))
+                write!(file, "{}", self.format_profile_with_aliases(profile, &aliases_for(profile)))
                     .map_err(|e| DomainError::IoError(e))?;
             }
         } else {
@@ -305,7 +357,7 @@ impl SshConfigRepository for FileSshConfigRepository {
 
             // Write profiles
             for profile in profiles {
-                write!(file, "{}", self.format_profile(profile))
+                write!(file, "{}", self.format_profile_with_aliases(profile, &aliases_for(profile)))
                     .map_err(|e| DomainError::IoError(e))?;
             }
         }
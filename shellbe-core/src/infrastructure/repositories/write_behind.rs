@@ -0,0 +1,299 @@
+use crate::domain::DomainError;
+use crate::utils::FileLock;
+use super::encrypted_storage::EncryptedStorage;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, RwLock};
+
+/// How long a change waits for more changes before the file is written
+pub const WRITE_DELAY: Duration = Duration::from_millis(250);
+
+/// Error serializing a [`Snapshot`]
+pub type EncodeError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Shared in-memory data a [`WriteBehind`] can save
+#[async_trait]
+pub trait Snapshot: Send + Sync + 'static {
+    /// Serialize the current state
+    async fn encode(&self) -> Result<Vec<u8>, EncodeError>;
+
+    /// Serialize without waiting, from synchronous code; `None` while the data is being changed
+    fn try_encode(&self) -> Option<Result<Vec<u8>, EncodeError>>;
+
+    /// Take in the changes another process saved, given the file as this process last read or
+    /// wrote it (`base`) and as it is now (`theirs`); only called for stores made with
+    /// [`WriteBehind::merging`]
+    fn merge(&self, _base: &[u8], _theirs: &[u8]) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+/// Three-way merge of keyed data: keys this process changed keep its version, every other key
+/// takes the other process's, including removals
+pub fn merge_maps<K, V>(base: &HashMap<K, V>, ours: &HashMap<K, V>, theirs: &HashMap<K, V>) -> HashMap<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: PartialEq + Clone,
+{
+    let keys: HashSet<&K> = base.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let value = match ours.get(key) == base.get(key) {
+                true => theirs.get(key),
+                false => ours.get(key),
+            };
+            value.map(|value| (key.clone(), value.clone()))
+        })
+        .collect()
+}
+
+/// Size, modification time and content hash of a file, to notice another process replacing it
+///
+/// The hash catches rewrites of the same length within the modification time's resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    digest: [u8; 32],
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let content = fs::read(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            digest: Sha256::digest(&content).into(),
+        })
+    }
+}
+
+/// The file as this process last read or wrote it
+struct Synced {
+    stamp: Option<FileStamp>,
+    content: Vec<u8>,
+}
+
+#[async_trait]
+impl<T: Serialize + Send + Sync + 'static> Snapshot for RwLock<T> {
+    async fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        Ok(serde_json::to_vec_pretty(&*self.read().await)?)
+    }
+
+    fn try_encode(&self) -> Option<Result<Vec<u8>, EncodeError>> {
+        let data = self.try_read().ok()?;
+        Some(serde_json::to_vec_pretty(&*data).map_err(Into::into))
+    }
+}
+
+/// Readers never wait: they see the last stored version while a new one is written
+#[async_trait]
+impl<T: Serialize + Send + Sync + 'static> Snapshot for ArcSwap<T> {
+    async fn encode(&self) -> Result<Vec<u8>, EncodeError> {
+        Ok(serde_json::to_vec_pretty(&**self.load())?)
+    }
+
+    fn try_encode(&self) -> Option<Result<Vec<u8>, EncodeError>> {
+        Some(serde_json::to_vec_pretty(&**self.load()).map_err(Into::into))
+    }
+}
+
+/// Write-behind persistence for a repository's in-memory data
+///
+/// Changes only mark the data dirty and schedule a write after [`WRITE_DELAY`], so a burst
+/// of changes (importing 200 profiles) serializes and replaces the file once. `flush` writes
+/// right away; whatever is still pending when the store is dropped is written then.
+pub struct WriteBehind<S: Snapshot> {
+    inner: Arc<Inner<S>>,
+}
+
+struct Inner<S: Snapshot> {
+    path: PathBuf,
+    /// What the file holds, for error messages
+    what: &'static str,
+    storage: EncryptedStorage,
+    data: Arc<S>,
+    /// How long a change waits for more changes
+    delay: Duration,
+    dirty: AtomicBool,
+    scheduled: AtomicBool,
+    /// Only one write of the file at a time
+    writing: Mutex<()>,
+    /// Set for stores merging other processes' changes instead of overwriting them
+    synced: Option<std::sync::Mutex<Synced>>,
+}
+
+impl<S: Snapshot> WriteBehind<S> {
+    /// Persist `data` to `path` through `storage`
+    pub fn new(path: PathBuf, what: &'static str, storage: EncryptedStorage, data: Arc<S>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path,
+                what,
+                storage,
+                data,
+                delay: WRITE_DELAY,
+                dirty: AtomicBool::new(false),
+                scheduled: AtomicBool::new(false),
+                writing: Mutex::new(()),
+                synced: None,
+            }),
+        }
+    }
+
+    /// Before writing, merge in what other processes saved since `loaded`, the content the data
+    /// was read from, instead of overwriting it
+    pub fn merging(mut self, loaded: Vec<u8>) -> Self {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            let stamp = FileStamp::of(&inner.path);
+            inner.synced = Some(std::sync::Mutex::new(Synced { stamp, content: loaded }));
+        }
+        self
+    }
+
+    /// Wait `delay` instead of [`WRITE_DELAY`] before writing
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.delay = delay;
+        }
+        self
+    }
+
+    /// Note that the data changed; it's written once no more changes arrive for a moment
+    pub fn mark_dirty(&self) {
+        self.inner.dirty.store(true, Ordering::SeqCst);
+
+        if self.inner.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        // Outside a runtime there's nothing to run the delayed write; write now instead
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            self.inner.scheduled.store(false, Ordering::SeqCst);
+            if let Err(e) = self.inner.write_blocking() {
+                tracing::error!("Failed to save {}: {}", self.inner.what, e);
+            }
+            return;
+        };
+
+        let inner = self.inner.clone();
+        runtime.spawn(async move {
+            tokio::time::sleep(inner.delay).await;
+            inner.scheduled.store(false, Ordering::SeqCst);
+            if let Err(e) = inner.flush().await {
+                tracing::error!("Failed to save {}: {}", inner.what, e);
+            }
+        });
+    }
+
+    /// Write pending changes now
+    pub async fn flush(&self) -> Result<(), DomainError> {
+        self.inner.flush().await
+    }
+}
+
+impl<S: Snapshot> Inner<S> {
+    async fn flush(&self) -> Result<(), DomainError> {
+        let _writing = self.writing.lock().await;
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let result = self.write().await;
+        if result.is_err() {
+            // Try again on the next change or flush
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+        result
+    }
+
+    /// Save a snapshot of the data with proper file locking
+    async fn write(&self) -> Result<(), DomainError> {
+        // Acquire a lock for writing
+        let mut lock = FileLock::new(&self.path).await;
+        if !lock.acquire(5000).await.map_err(DomainError::IoError)? {
+            return Err(DomainError::ConfigError(format!("Failed to acquire lock for writing {}", self.what)));
+        }
+
+        self.merge_outside_changes()?;
+        let content = self.data.encode().await
+            .map_err(|e| DomainError::ConfigError(format!("Failed to save {}: {}", self.what, e)))?;
+        self.replace(&content)?;
+
+        // Release the lock
+        lock.release().await.map_err(DomainError::IoError)?;
+
+        Ok(())
+    }
+
+    /// Save from a synchronous context: the end of the process, or no runtime at all
+    fn write_blocking(&self) -> Result<(), DomainError> {
+        let mut lock = FileLock::for_path(&self.path);
+        if !lock.acquire_blocking(5000).map_err(DomainError::IoError)? {
+            return Err(DomainError::ConfigError(format!("Failed to acquire lock for writing {}", self.what)));
+        }
+
+        self.merge_outside_changes()?;
+        let Some(content) = self.data.try_encode() else {
+            return Err(DomainError::ConfigError(format!("{} are still being changed", self.what)));
+        };
+        let content = content
+            .map_err(|e| DomainError::ConfigError(format!("Failed to save {}: {}", self.what, e)))?;
+        self.dirty.store(false, Ordering::SeqCst);
+        self.replace(&content)
+    }
+
+    /// Merge the file into the data if another process replaced it since it was last read
+    /// or written; the file lock keeps it from changing again until this write is done
+    fn merge_outside_changes(&self) -> Result<(), DomainError> {
+        let Some(synced) = &self.synced else {
+            return Ok(());
+        };
+        let mut synced = synced.lock().unwrap();
+
+        let stamp = FileStamp::of(&self.path);
+        if stamp.is_none() || stamp == synced.stamp {
+            return Ok(());
+        }
+
+        tracing::debug!("{} changed on disk since they were loaded; merging", self.what);
+        let theirs = self.storage.read(&self.path)?;
+        self.data.merge(&synced.content, &theirs)
+            .map_err(|e| DomainError::ConfigError(format!("Failed to merge {} saved by another process: {}", self.what, e)))?;
+        *synced = Synced { stamp, content: theirs };
+        Ok(())
+    }
+
+    /// Write to a temporary file, then rename it over the file for atomic replacement
+    fn replace(&self, content: &[u8]) -> Result<(), DomainError> {
+        let temp_path = self.path.with_extension("temp");
+        self.storage.write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path).map_err(DomainError::IoError)?;
+
+        if let Some(synced) = &self.synced {
+            *synced.lock().unwrap() = Synced { stamp: FileStamp::of(&self.path), content: content.to_vec() };
+        }
+        Ok(())
+    }
+}
+
+impl<S: Snapshot> Drop for Inner<S> {
+    fn drop(&mut self) {
+        // The last store and any delayed write are gone, e.g. the runtime shut down early
+        if self.dirty.load(Ordering::SeqCst) {
+            if let Err(e) = self.write_blocking() {
+                tracing::error!("Failed to save {}: {}", self.what, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::run_secret_cli;
+use crate::domain::{DomainError, SecretResolver, Sensitive};
+
+/// Fields `bw get` reads directly; anything else is looked up in the item's custom fields
+const BITWARDEN_FIELDS: [&str; 4] = ["password", "username", "notes", "totp"];
+
+/// Reads `bw://item/field` references with the Bitwarden CLI
+///
+/// The item is a name or ID and the field defaults to `password`. The vault has to be
+/// unlocked, with `BW_SESSION` set, before connecting.
+#[derive(Default)]
+pub struct BitwardenResolver;
+
+impl BitwardenResolver {
+    /// Create a new Bitwarden resolver
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Split a reference into item and field
+    fn parse(reference: &str) -> Result<(&str, &str), DomainError> {
+        let path = reference.strip_prefix("bw://").unwrap_or(reference);
+
+        let (item, field) = match path.rsplit_once('/') {
+            Some((item, field)) => (item, field),
+            None => (path, "password"),
+        };
+
+        if item.is_empty() || field.is_empty() {
+            return Err(DomainError::ConfigError(format!("Invalid Bitwarden reference '{}', expected bw://item/field", reference)));
+        }
+
+        Ok((item, field))
+    }
+}
+
+impl SecretResolver for BitwardenResolver {
+    fn name(&self) -> &str {
+        "Bitwarden"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<Sensitive<String>, DomainError> {
+        let (item, field) = Self::parse(reference)?;
+
+        if BITWARDEN_FIELDS.contains(&field) {
+            return run_secret_cli(self.name(), "bw", &["get", field, item]);
+        }
+
+        // Custom fields only come with the whole item
+        let json = run_secret_cli(self.name(), "bw", &["get", "item", item])?;
+        let item_json: serde_json::Value = serde_json::from_str(json.expose())
+            .map_err(|e| DomainError::ConfigError(format!("Bitwarden returned an unreadable item: {}", e)))?;
+
+        item_json["fields"].as_array()
+            .and_then(|fields| fields.iter().find(|f| f["name"] == field))
+            .and_then(|f| f["value"].as_str())
+            .map(|value| Sensitive::new(value.to_string()))
+            .ok_or_else(|| DomainError::ConfigError(format!("Bitwarden item '{}' has no field '{}'", item, field)))
+    }
+}
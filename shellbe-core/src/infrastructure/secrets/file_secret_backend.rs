@@ -0,0 +1,49 @@
+use crate::domain::{DomainError, SecretBackend};
+use std::fs;
+use crate::utils::write_private;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Keeps each secret in its own owner-only file, for systems without a keychain
+pub struct FileSecretBackend {
+    dir: PathBuf,
+}
+
+impl FileSecretBackend {
+    /// Store secrets as files in the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, account: &str) -> PathBuf {
+        self.dir.join(account)
+    }
+}
+
+impl SecretBackend for FileSecretBackend {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>, DomainError> {
+        match fs::read_to_string(self.path(account)) {
+            Ok(secret) => Ok(Some(secret)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(DomainError::IoError(e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), DomainError> {
+        fs::create_dir_all(&self.dir).map_err(DomainError::IoError)?;
+
+        write_private(&self.path(account), secret.as_bytes()).map_err(DomainError::IoError)
+    }
+
+    fn delete(&self, account: &str) -> Result<bool, DomainError> {
+        match fs::remove_file(self.path(account)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(DomainError::IoError(e)),
+        }
+    }
+}
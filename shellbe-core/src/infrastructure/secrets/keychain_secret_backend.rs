@@ -0,0 +1,69 @@
+use crate::domain::{DomainError, SecretBackend};
+use keyring::Entry;
+
+/// Name of the platform's secret store
+#[cfg(target_os = "macos")]
+const KEYCHAIN_NAME: &str = "macOS Keychain";
+#[cfg(target_os = "windows")]
+const KEYCHAIN_NAME: &str = "Windows Credential Manager";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const KEYCHAIN_NAME: &str = "Secret Service";
+
+/// Keeps secrets in the OS keychain: the macOS Keychain, the Windows Credential Manager,
+/// or the Secret Service (GNOME Keyring, KWallet) on Linux and BSD
+pub struct KeychainSecretBackend {
+    service: String,
+}
+
+impl KeychainSecretBackend {
+    /// Store secrets under the given service name
+    pub fn new(service: impl Into<String>) -> Self {
+        Self { service: service.into() }
+    }
+
+    /// Whether this system is expected to have a keychain
+    ///
+    /// macOS and Windows always do; elsewhere the Secret Service needs a D-Bus session,
+    /// which headless machines and SSH logins usually lack.
+    pub fn is_available() -> bool {
+        if cfg!(any(target_os = "macos", target_os = "windows")) {
+            return true;
+        }
+
+        std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some_and(|address| !address.is_empty())
+    }
+
+    fn entry(&self, account: &str) -> Result<Entry, DomainError> {
+        Entry::new(&self.service, account).map_err(|e| self.error(e))
+    }
+
+    fn error(&self, error: keyring::Error) -> DomainError {
+        DomainError::ConfigError(format!("{}: {}", KEYCHAIN_NAME, error))
+    }
+}
+
+impl SecretBackend for KeychainSecretBackend {
+    fn name(&self) -> &str {
+        KEYCHAIN_NAME
+    }
+
+    fn get(&self, account: &str) -> Result<Option<String>, DomainError> {
+        match self.entry(account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(self.error(e)),
+        }
+    }
+
+    fn set(&self, account: &str, secret: &str) -> Result<(), DomainError> {
+        self.entry(account)?.set_password(secret).map_err(|e| self.error(e))
+    }
+
+    fn delete(&self, account: &str) -> Result<bool, DomainError> {
+        match self.entry(account)?.delete_password() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(self.error(e)),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+pub mod file_secret_backend;
+pub mod keychain_secret_backend;
+pub mod one_password_resolver;
+pub mod bitwarden_resolver;
+
+pub use file_secret_backend::FileSecretBackend;
+pub use keychain_secret_backend::KeychainSecretBackend;
+pub use one_password_resolver::OnePasswordResolver;
+pub use bitwarden_resolver::BitwardenResolver;
+
+use crate::domain::{DomainError, SecretBackend, SecretResolver, Sensitive};
+use crate::utils::register_secret;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+/// Pick where secrets are kept for a `vault.key_store` setting
+///
+/// `auto` uses the OS keychain when one is available and files in `dir` otherwise.
+/// `service` names the keychain entries, so separate data directories don't share them.
+pub fn secret_backend(key_store: &str, dir: &Path, service: &str) -> Arc<dyn SecretBackend> {
+    let use_keychain = match key_store {
+        "keychain" => true,
+        "file" => false,
+        _ => KeychainSecretBackend::is_available(),
+    };
+
+    if use_keychain {
+        Arc::new(KeychainSecretBackend::new(service))
+    } else {
+        Arc::new(FileSecretBackend::new(dir))
+    }
+}
+
+/// Pick the resolver for a secret reference by its scheme
+pub fn secret_resolver(reference: &str) -> Result<Box<dyn SecretResolver>, DomainError> {
+    match reference.split_once("://").map(|(scheme, _)| scheme) {
+        Some("op") => Ok(Box::new(OnePasswordResolver::new())),
+        Some("bw") => Ok(Box::new(BitwardenResolver::new())),
+        _ => Err(DomainError::ConfigError(format!(
+            "Unsupported secret reference '{}'; use op://vault/item/field or bw://item/field", reference,
+        ))),
+    }
+}
+
+/// Run a secret manager CLI and return what it printed
+///
+/// Stdin stays attached so the CLI can ask to unlock; the secret is registered for redaction.
+fn run_secret_cli(name: &str, program: &str, args: &[&str]) -> Result<Sensitive<String>, DomainError> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DomainError::ConfigError(format!("The {} CLI `{}` is not installed", name, program)),
+            _ => DomainError::ConfigError(format!("Failed to run `{}`: {}", program, e)),
+        })?;
+
+    if !output.status.success() {
+        return Err(DomainError::ConfigError(format!(
+            "{} couldn't read the secret: {}", name, String::from_utf8_lossy(&output.stderr).trim(),
+        )));
+    }
+
+    let secret = String::from_utf8(output.stdout)
+        .map_err(|_| DomainError::ConfigError(format!("{} returned a secret that isn't UTF-8", name)))?;
+    let secret = secret.strip_suffix('\n').map(str::to_string).unwrap_or(secret);
+
+    register_secret(&secret);
+    Ok(Sensitive::new(secret))
+}
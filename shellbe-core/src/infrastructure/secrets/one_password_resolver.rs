@@ -0,0 +1,25 @@
+use super::run_secret_cli;
+use crate::domain::{DomainError, SecretResolver, Sensitive};
+
+/// Reads `op://vault/item/field` references with the 1Password CLI (`op read`)
+///
+/// `op` handles sign-in itself, through the desktop app integration or `OP_SESSION_*`.
+#[derive(Default)]
+pub struct OnePasswordResolver;
+
+impl OnePasswordResolver {
+    /// Create a new 1Password resolver
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl SecretResolver for OnePasswordResolver {
+    fn name(&self) -> &str {
+        "1Password"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<Sensitive<String>, DomainError> {
+        run_secret_cli(self.name(), "op", &["read", "--no-newline", reference])
+    }
+}
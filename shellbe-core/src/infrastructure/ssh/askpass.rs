@@ -0,0 +1,87 @@
+use crate::infrastructure::secrets::secret_resolver;
+use crate::domain::{ConnectionArgs, DomainError, Profile, Sensitive};
+use std::io::{BufRead, BufReader, Write};
+
+/// Set when ssh runs shellbe as its askpass program
+pub const ASKPASS_ENV: &str = "SHELLBE_ASKPASS";
+
+/// Secret reference for password prompts
+pub const ASKPASS_PASSWORD_ENV: &str = "SHELLBE_ASKPASS_PASSWORD";
+
+/// Secret reference for key passphrase prompts
+pub const ASKPASS_PASSPHRASE_ENV: &str = "SHELLBE_ASKPASS_PASSPHRASE";
+
+/// One-time password for password prompts; it is useless once used
+pub const ASKPASS_OTP_ENV: &str = "SHELLBE_ASKPASS_OTP";
+
+/// Environment that makes ssh ask shellbe for a profile's password and key passphrase
+///
+/// Only the references are passed; ssh calls back into shellbe for each prompt, which
+/// resolves the secret right then. Empty when there is nothing to answer with.
+pub fn askpass_env(profile: &Profile, args: &ConnectionArgs) -> Result<Vec<(&'static str, String)>, DomainError> {
+    if profile.password.is_none() && profile.identity_passphrase.is_none() && args.one_time_password.is_none() {
+        return Ok(Vec::new());
+    }
+
+    let program = std::env::current_exe()
+        .map_err(|e| DomainError::SshError(format!("Failed to locate the shellbe executable for SSH_ASKPASS: {}", e)))?;
+
+    let mut env = vec![
+        ("SSH_ASKPASS", program.to_string_lossy().into_owned()),
+        ("SSH_ASKPASS_REQUIRE", "force".to_string()),
+        (ASKPASS_ENV, "1".to_string()),
+    ];
+    if let Some(reference) = &profile.password {
+        env.push((ASKPASS_PASSWORD_ENV, reference.clone()));
+    }
+    if let Some(reference) = &profile.identity_passphrase {
+        env.push((ASKPASS_PASSPHRASE_ENV, reference.clone()));
+    }
+    if let Some(otp) = &args.one_time_password {
+        env.push((ASKPASS_OTP_ENV, otp.expose().clone()));
+    }
+
+    Ok(env)
+}
+
+/// Whether this process was started by ssh to answer a prompt
+pub fn is_askpass() -> bool {
+    std::env::var_os(ASKPASS_ENV).is_some_and(|value| value == "1")
+}
+
+/// Answer an ssh prompt: from the matching secret reference, or by asking on the terminal
+///
+/// With `SSH_ASKPASS_REQUIRE=force` every prompt comes here, including host key
+/// confirmations and keyboard-interactive questions, so those go to the user as usual.
+pub fn answer_prompt(prompt: &str) -> Result<Sensitive<String>, DomainError> {
+    let lower = prompt.to_lowercase();
+    let reference_env = if lower.contains("passphrase") {
+        Some(ASKPASS_PASSPHRASE_ENV)
+    } else if lower.contains("password") {
+        Some(ASKPASS_PASSWORD_ENV)
+    } else {
+        None
+    };
+
+    if reference_env == Some(ASKPASS_PASSWORD_ENV) {
+        if let Ok(otp) = std::env::var(ASKPASS_OTP_ENV) {
+            return Ok(Sensitive::new(otp));
+        }
+    }
+
+    if let Some(reference) = reference_env.and_then(|env| std::env::var(env).ok()) {
+        return secret_resolver(&reference)?.resolve(&reference);
+    }
+
+    // Confirmations are answered in the clear, anything else may be a secret
+    if std::env::var("SSH_ASKPASS_PROMPT").is_ok_and(|kind| kind == "confirm") {
+        let mut tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        tty.write_all(prompt.as_bytes())?;
+
+        let mut answer = String::new();
+        BufReader::new(tty).read_line(&mut answer)?;
+        return Ok(Sensitive::new(answer.trim_end().to_string()));
+    }
+
+    Ok(Sensitive::new(rpassword::prompt_password(prompt)?))
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory in the cache directory holding the ControlMaster sockets of multiplexed connections
+pub const CONTROL_SOCKETS_DIR: &str = "sockets";
+
+/// How long a master connection stays open after its last session ends
+pub const CONTROL_PERSIST: &str = "10m";
+
+/// A ControlMaster socket left by a multiplexed connection
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlSocket {
+    /// Socket file, named by ssh's `%C` connection hash
+    pub path: PathBuf,
+    /// Whether a master connection still listens on it
+    pub alive: bool,
+    /// When the master connection was started
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+impl ControlSocket {
+    /// File name of the socket
+    pub fn name(&self) -> String {
+        self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+}
+
+/// The directory ShellBe's multiplexed connections keep their ControlMaster sockets in
+///
+/// Only sockets in this directory are ever listed or removed, never ones from the user's
+/// own `ControlPath`.
+#[derive(Debug, Clone)]
+pub struct ControlSockets {
+    dir: PathBuf,
+}
+
+impl ControlSockets {
+    /// Sockets kept in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Directory the sockets are in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Create the directory, readable by the user only since its sockets open their connections
+    pub fn ensure_dir(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.dir, fs::Permissions::from_mode(0o700))?;
+        }
+
+        Ok(())
+    }
+
+    /// ssh options sharing one master connection per destination through this directory
+    pub fn ssh_options(&self) -> Vec<String> {
+        vec![
+            "ControlMaster=auto".to_string(),
+            format!("ControlPath={}", self.dir.join("%C").display()),
+            format!("ControlPersist={}", CONTROL_PERSIST),
+        ]
+    }
+
+    /// Every socket in the directory, live ones first
+    pub fn list(&self) -> io::Result<Vec<ControlSocket>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut sockets = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !is_socket(&metadata) {
+                continue;
+            }
+
+            let path = entry.path();
+            sockets.push(ControlSocket {
+                alive: is_alive(&path),
+                created_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+                path,
+            });
+        }
+
+        sockets.sort_by(|a, b| b.alive.cmp(&a.alive).then_with(|| a.path.cmp(&b.path)));
+        Ok(sockets)
+    }
+
+    /// Remove sockets whose master connection is gone; returns the removed ones
+    ///
+    /// A stale socket makes ssh fail with "Control socket connect: Connection refused"
+    /// instead of starting a new master.
+    pub fn clean(&self) -> io::Result<Vec<ControlSocket>> {
+        let mut removed = Vec::new();
+        for socket in self.list()?.into_iter().filter(|socket| !socket.alive) {
+            match fs::remove_file(&socket.path) {
+                Ok(()) => removed.push(socket),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(unix)]
+fn is_socket(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    metadata.file_type().is_socket()
+}
+
+/// Windows' OpenSSH doesn't multiplex, so there are never sockets to look at
+#[cfg(not(unix))]
+fn is_socket(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// A master connection accepts connections on its socket; a dead one leaves nothing listening
+#[cfg(unix)]
+fn is_alive(path: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+#[cfg(not(unix))]
+fn is_alive(_path: &Path) -> bool {
+    false
+}
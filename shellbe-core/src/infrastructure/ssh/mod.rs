@@ -0,0 +1,11 @@
+pub mod askpass;
+pub mod control_sockets;
+pub mod resolver;
+pub mod thrush_ssh_service;
+pub mod vault_ssh_issuer;
+
+pub use askpass::{askpass_env, answer_prompt, is_askpass, ASKPASS_ENV};
+pub use control_sockets::{ControlSocket, ControlSockets, CONTROL_SOCKETS_DIR};
+pub use resolver::{Resolver, DNS_CACHE_FILE};
+pub use thrush_ssh_service::ThrushSshService;
+pub use vault_ssh_issuer::{vault_ssh_issuer, VaultSshIssuer, VAULT_ADDR_ENV, VAULT_TOKEN_ENV};
\ No newline at end of file
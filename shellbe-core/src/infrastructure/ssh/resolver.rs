@@ -0,0 +1,141 @@
+use crate::domain::DomainError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// File in the cache directory holding resolved addresses, when `[ssh] dns_cache_secs` is set
+pub const DNS_CACHE_FILE: &str = "dns.json";
+
+/// Addresses a hostname resolved to, and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedAddresses {
+    addresses: Vec<IpAddr>,
+    resolved_at: DateTime<Utc>,
+}
+
+/// Resolves hostnames for connection tests, optionally remembering the answers on disk
+///
+/// ssh resolves hostnames itself when connecting; this only covers what ShellBe connects
+/// to on its own.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    cache: Option<DnsCache>,
+}
+
+#[derive(Debug)]
+struct DnsCache {
+    path: PathBuf,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedAddresses>>,
+}
+
+impl Resolver {
+    /// Resolver keeping answers in `path` for `ttl`
+    pub fn cached(path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        let path = path.into();
+        let entries = fs::read(&path).ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+
+        Self { cache: Some(DnsCache { path, ttl, entries: Mutex::new(entries) }) }
+    }
+
+    /// Every address `host` resolves to, IPv6 literals with or without their brackets
+    ///
+    /// Literal addresses are returned as they are, without a lookup.
+    pub async fn resolve(&self, host: &str, port: u16) -> Result<Vec<SocketAddr>, DomainError> {
+        let host = host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host);
+        if let Ok(address) = host.parse::<IpAddr>() {
+            return Ok(vec![SocketAddr::new(address, port)]);
+        }
+
+        if let Some(addresses) = self.cache.as_ref().and_then(|cache| cache.get(host)) {
+            return Ok(addresses.into_iter().map(|address| SocketAddr::new(address, port)).collect());
+        }
+
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await
+            .map_err(|e| DomainError::SshError(format!("Could not resolve {}: {}", host, e)))?
+            .collect();
+
+        if addrs.is_empty() {
+            return Err(DomainError::SshError(format!("{} has no addresses", host)));
+        }
+
+        if let Some(cache) = &self.cache {
+            cache.insert(host, addrs.iter().map(SocketAddr::ip).collect());
+        }
+
+        Ok(addrs)
+    }
+}
+
+impl DnsCache {
+    fn get(&self, host: &str) -> Option<Vec<IpAddr>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(&host.to_ascii_lowercase())
+            .filter(|entry| self.is_fresh(entry))
+            .map(|entry| entry.addresses.clone())
+    }
+
+    /// Remember an answer; failing to write the file only costs a lookup next time
+    fn insert(&self, host: &str, addresses: Vec<IpAddr>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, entry| self.is_fresh(entry));
+        entries.insert(host.to_ascii_lowercase(), CachedAddresses { addresses, resolved_at: Utc::now() });
+
+        let written = serde_json::to_vec_pretty(&*entries)
+            .map_err(io::Error::from)
+            .and_then(|content| {
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&self.path, content)
+            });
+
+        if let Err(e) = written {
+            tracing::debug!("Failed to write DNS cache {}: {}", self.path.display(), e);
+        }
+    }
+
+    fn is_fresh(&self, entry: &CachedAddresses) -> bool {
+        Utc::now().signed_duration_since(entry.resolved_at).to_std()
+            .map_or(false, |age| age < self.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cached_answers_skip_the_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DNS_CACHE_FILE);
+
+        // .invalid never resolves, so only the cache can answer
+        let resolver = Resolver::cached(&path, Duration::from_secs(60));
+        resolver.cache.as_ref().unwrap().insert("db.invalid", vec!["10.0.0.5".parse().unwrap()]);
+
+        let reloaded = Resolver::cached(&path, Duration::from_secs(60));
+        let addrs = reloaded.resolve("DB.invalid", 2222).await.unwrap();
+        assert_eq!(addrs, vec!["10.0.0.5:2222".parse::<SocketAddr>().unwrap()]);
+
+        let expired = Resolver::cached(&path, Duration::ZERO);
+        assert!(expired.resolve("db.invalid", 2222).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_literal_addresses_need_no_lookup() {
+        let resolver = Resolver::default();
+
+        assert_eq!(resolver.resolve("[::1]", 22).await.unwrap(), vec!["[::1]:22".parse::<SocketAddr>().unwrap()]);
+        assert_eq!(resolver.resolve("::1", 22).await.unwrap(), vec!["[::1]:22".parse::<SocketAddr>().unwrap()]);
+        assert_eq!(resolver.resolve("192.0.2.1", 22).await.unwrap(), vec!["192.0.2.1:22".parse::<SocketAddr>().unwrap()]);
+    }
+}
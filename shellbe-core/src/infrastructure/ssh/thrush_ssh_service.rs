@@ -0,0 +1,572 @@
+use crate::domain::{Profile, ProfileKind, KubernetesPod, ConnectionArgs, SshService, docker_exec_command};
+use super::askpass::askpass_env;
+use super::control_sockets::ControlSockets;
+use super::resolver::Resolver;
+use crate::utils::{command_program, expand_path, expand_value, find_command};
+use crate::errors::{ShellBeError, Result, ErrorContext};
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::io::{Write, Read};
+
+use tokio::time::timeout;
+use thrussh::client::{self, Config};
+use thrussh::ChannelId;
+use thrussh_keys::key::{self, KeyPair, PublicKey};
+use thrussh_keys::agent;
+use std::sync::Arc;
+use futures::future::BoxFuture;
+
+/// Tokio-based implementation of the SSH service
+pub struct ThrushSshService {
+    client_config: Config,
+    strict_host_keys: bool,
+    control_sockets: Option<ControlSockets>,
+    resolver: Resolver,
+    quiet: bool,
+}
+
+impl ThrushSshService {
+    /// Create a new SSH service
+    pub fn new() -> Self {
+        let mut client_config = Config::default();
+        client_config.connection_timeout = Some(Duration::from_secs(10));
+        client_config.authenticate_timeout = Some(Duration::from_secs(10));
+
+        Self {
+            client_config,
+            strict_host_keys: false,
+            control_sockets: None,
+            resolver: Resolver::default(),
+            quiet: false,
+        }
+    }
+
+    /// Refuse hosts whose key is missing from `known_hosts` or has changed
+    pub fn set_strict_host_keys(&mut self, strict: bool) {
+        self.strict_host_keys = strict;
+    }
+
+    /// Share one master connection per destination through sockets in this directory
+    pub fn set_control_sockets(&mut self, sockets: ControlSockets) {
+        self.control_sockets = Some(sockets);
+    }
+
+    /// Run ssh with `-q` for every profile, not only those asking for it
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// Resolve hostnames for connection tests with this resolver, e.g. one with a cache
+    pub fn set_resolver(&mut self, resolver: Resolver) {
+        self.resolver = resolver;
+    }
+
+    // Install a public key the way ssh-copy-id does, for clients without it
+    fn append_authorized_key(&self, profile: &Profile, key_path: &Path) -> Result<(), DomainError> {
+        // Like ssh-copy-id, accept either half of the key pair
+        let public_key_path = if key_path.extension().map_or(false, |ext| ext == "pub") {
+            key_path.to_path_buf()
+        } else {
+            PathBuf::from(format!("{}.pub", key_path.display()))
+        };
+        let public_key = fs::read_to_string(&public_key_path)
+            .map_err(|e| DomainError::SshError(format!("Failed to read {}: {}", public_key_path.display(), e)))?;
+
+        let mut cmd = Command::new(command_program("ssh"));
+        if self.strict_host_keys {
+            cmd.arg("-o").arg("StrictHostKeyChecking=yes");
+        }
+        for option in resolve_to_options(profile) {
+            cmd.arg("-o").arg(option);
+        }
+        if profile.port != 22 {
+            cmd.arg("-p").arg(profile.port.to_string());
+        }
+        cmd.arg(format!("{}@{}", profile.username, profile.hostname));
+
+        // Skip keys already authorized, so running copy-key twice is harmless
+        cmd.arg("umask 077; mkdir -p ~/.ssh && key=$(cat) && \
+            { grep -qxF \"$key\" ~/.ssh/authorized_keys 2>/dev/null || echo \"$key\" >> ~/.ssh/authorized_keys; }");
+
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        let mut child = cmd.spawn()
+            .map_err(|e| DomainError::SshError(format!("Failed to execute SSH: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(public_key.trim().as_bytes())
+                .map_err(|e| DomainError::SshError(format!("Failed to send key: {}", e)))?;
+        }
+        let status = child.wait()
+            .map_err(|e| DomainError::SshError(format!("Failed to wait for SSH: {}", e)))?;
+
+        if !status.success() {
+            return Err(DomainError::SshError(format!("Copying the key over SSH failed: {}", status)));
+        }
+
+        Ok(())
+    }
+
+    // Helper function to load SSH keys
+    async fn load_key(&self, path: &Path) -> Result<KeyPair, DomainError> {
+        let key_data = tokio::fs::read(path).await
+            .map_err(|e| DomainError::IoError(e))?;
+
+        match key::parse_secret_key(&key_data, None) {
+            Ok(key_pair) => Ok(key_pair),
+            Err(_) => {
+                // Try with empty passphrase
+                key::parse_secret_key(&key_data, Some(b""))
+                    .map_err(|e| DomainError::SshError(format!("Failed to load key: {}", e)))
+            }
+        }
+    }
+
+    // Create a pure-Rust SSH key pair
+    async fn create_key_pair(&self, key_path: &Path, key_type: &str, comment: Option<&str>) -> Result<(), DomainError> {
+        match key_type {
+            "ed25519" => {
+                let key_pair = KeyPair::generate_ed25519()
+                    .map_err(|e| DomainError::SshError(format!("Failed to generate key: {}", e)))?;
+
+                // Save private key
+                let private_key = key_pair.serialize_openssh()
+                    .map_err(|e| DomainError::SshError(format!("Failed to serialize private key: {}", e)))?;
+
+                tokio::fs::write(key_path, private_key).await
+                    .map_err(|e| DomainError::IoError(e))?;
+
+                // Set permissions on Unix
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let metadata = tokio::fs::metadata(key_path).await.map_err(|e| DomainError::IoError(e))?;
+                    let mut permissions = metadata.permissions();
+                    permissions.set_mode(0o600);
+                    tokio::fs::set_permissions(key_path, permissions).await.map_err(|e| DomainError::IoError(e))?;
+                }
+
+                // Generate public key
+                let pubkey_path = format!("{}.pub", key_path.display());
+                let pubkey = key_pair.clone_public_key().serialize_openssh()
+                    .map_err(|e| DomainError::SshError(format!("Failed to serialize public key: {}", e)))?;
+
+                let pubkey_content = if let Some(c) = comment {
+                    format!("{} {}", pubkey, c)
+                } else {
+                    pubkey
+                };
+
+                tokio::fs::write(pubkey_path, pubkey_content).await
+                    .map_err(|e| DomainError::IoError(e))?;
+
+                Ok(())
+            },
+            "rsa" => {
+                let key_pair = KeyPair::generate_rsa(3072)
+                    .map_err(|e| DomainError::SshError(format!("Failed to generate key: {}", e)))?;
+
+                // Save private key
+                let private_key = key_pair.serialize_openssh()
+                    .map_err(|e| DomainError::SshError(format!("Failed to serialize private key: {}", e)))?;
+
+                tokio::fs::write(key_path, private_key).await
+                    .map_err(|e| DomainError::IoError(e))?;
+
+                // Set permissions on Unix
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    let metadata = tokio::fs::metadata(key_path).await.map_err(|e| DomainError::IoError(e))?;
+                    let mut permissions = metadata.permissions();
+                    permissions.set_mode(0o600);
+                    tokio::fs::set_permissions(key_path, permissions).await.map_err(|e| DomainError::IoError(e))?;
+                }
+
+                // Generate public key
+                let pubkey_path = format!("{}.pub", key_path.display());
+                let pubkey = key_pair.clone_public_key().serialize_openssh()
+                    .map_err(|e| DomainError::SshError(format!("Failed to serialize public key: {}", e)))?;
+
+                let pubkey_content = if let Some(c) = comment {
+                    format!("{} {}", pubkey, c)
+                } else {
+                    pubkey
+                };
+
+                tokio::fs::write(pubkey_path, pubkey_content).await
+                    .map_err(|e| DomainError::IoError(e))?;
+
+                Ok(())
+            },
+            _ => Err(DomainError::SshError(format!("Unsupported key type: {}", key_type))),
+        }
+    }
+}
+
+/// Check that the pod, or one of the pods the selector matches, is Ready
+async fn pod_ready(pod: &KubernetesPod) -> Result<(), DomainError> {
+    let command_line = pod.ready_command();
+    let output = tokio::process::Command::new(&command_line[0])
+        .args(&command_line[1..])
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => DomainError::ConfigError("kubectl is not installed".to_string()),
+            _ => DomainError::ConfigError(format!("Failed to run kubectl: {}", e)),
+        })?;
+
+    if !output.status.success() {
+        return Err(DomainError::ConfigError(format!(
+            "kubectl failed: {}", String::from_utf8_lossy(&output.stderr).trim(),
+        )));
+    }
+
+    // A pod that is starting or failing its readiness probe is worth retrying
+    if !String::from_utf8_lossy(&output.stdout).split_whitespace().any(|status| status == "True") {
+        return Err(DomainError::Unavailable(format!("No ready pod for {}", pod.target())));
+    }
+    Ok(())
+}
+
+/// ssh options connecting to a profile's `resolve_to` address, while known_hosts is still
+/// looked up under its hostname
+fn resolve_to_options(profile: &Profile) -> Vec<String> {
+    match profile.resolve_to {
+        Some(address) => vec![
+            format!("HostName={}", address),
+            format!("HostKeyAlias={}", profile.hostname),
+        ],
+        None => Vec::new(),
+    }
+}
+
+// SSH client handler
+struct ClientHandler {
+    success: bool,
+    finish_on_session: bool,
+    host: String,
+    port: u16,
+    strict_host_keys: bool,
+}
+
+impl ClientHandler {
+    fn new(finish_on_session: bool, host: &str, port: u16, strict_host_keys: bool) -> Self {
+        Self {
+            success: false,
+            finish_on_session,
+            host: host.to_string(),
+            port,
+            strict_host_keys,
+        }
+    }
+}
+
+impl client::Handler for ClientHandler {
+    type Error = thrussh::Error;
+    type FutureUnit = BoxFuture<'static, Result<(Self, client::Session), Self::Error>>;
+    type FutureBool = BoxFuture<'static, Result<(Self, bool), Self::Error>>;
+
+    fn finished_bool(self, b: bool) -> Self::FutureBool {
+        Box::pin(async move { Ok((self, b)) })
+    }
+
+    fn finished(self, session: client::Session) -> Self::FutureUnit {
+        if self.finish_on_session {
+            Box::pin(async move { Ok((self, session)) })
+        } else {
+            // Continue the session
+            Box::pin(async move { Ok((self, session)) })
+        }
+    }
+
+    fn check_server_key(self, server_public_key: &PublicKey) -> Self::FutureBool {
+        // Outside strict mode unknown keys are accepted, like StrictHostKeyChecking=accept-new
+        let accepted = if self.strict_host_keys {
+            match thrussh_keys::check_known_hosts(&self.host, self.port, server_public_key) {
+                Ok(known) => known,
+                Err(e) => {
+                    tracing::warn!("Host key for {} doesn't match known_hosts: {}", self.host, e);
+                    false
+                }
+            }
+        } else {
+            true
+        };
+
+        Box::pin(async move { Ok((self, accepted)) })
+    }
+
+    fn channel_open_confirmation(
+        mut self,
+        _channel: ChannelId,
+        _max_packet_size: u32,
+        _window_size: u32,
+        session: client::Session,
+    ) -> Self::FutureUnit {
+        self.success = true;
+        Box::pin(async move { Ok((self, session)) })
+    }
+}
+
+#[async_trait]
+impl SshService for ThrushSshService {
+    /// Connect to a profile
+    async fn connect(&self, profile: &Profile, args: &ConnectionArgs, started: &(dyn Fn(u32) + Send + Sync)) -> Result<i32, DomainError> {
+        // For interactive sessions, we still need to use system SSH
+        // thrussh doesn't handle terminal properly for fully interactive sessions
+        let command_line = self.command_line(profile, args);
+        let mut cmd = Command::new(command_program(&command_line[0]));
+        cmd.args(&command_line[1..]);
+
+        // Let ssh ask shellbe for secrets kept in a secret manager
+        cmd.envs(askpass_env(profile, args)?);
+
+        // Set stdin/stdout/stderr for interactive use
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        // Run the command
+        let mut child = cmd.spawn()
+            .map_err(|e| DomainError::SshError(format!("Failed to execute SSH: {}", e)))?;
+        started(child.id());
+        let status = child.wait()
+            .map_err(|e| DomainError::SshError(format!("Failed to wait for SSH: {}", e)))?;
+
+        Ok(status.code().unwrap_or(1))
+    }
+
+    /// Build the ssh command line for a profile
+    fn command_line(&self, profile: &Profile, args: &ConnectionArgs) -> Vec<String> {
+        // Containers on a remote host arrive here as their host profile, so this is the local daemon
+        if let ProfileKind::Docker { container, .. } = &profile.kind {
+            return docker_exec_command(container, &profile.username, args.command.as_deref());
+        }
+        if let ProfileKind::Kubernetes(pod) = &profile.kind {
+            return pod.exec_command(args.command.as_deref());
+        }
+
+        // sftp takes the same options as ssh, except for the port's flag
+        let sftp = args.sftp.is_some();
+        let mut command_line = vec![if sftp { "sftp" } else { "ssh" }.to_string()];
+
+        // Put strict checking first; ssh uses the first value given for an option
+        if self.strict_host_keys {
+            command_line.push("-o".to_string());
+            command_line.push("StrictHostKeyChecking=yes".to_string());
+        }
+
+        // Also ahead of the profile's options, so a HostName among them can't undo resolve_to
+        for option in resolve_to_options(profile) {
+            command_line.push("-o".to_string());
+            command_line.push(option);
+        }
+
+        // -q is LogLevel=QUIET: no pre-login banner, no warnings
+        if self.quiet || profile.quiet {
+            command_line.push("-q".to_string());
+        }
+
+        // Add port if not default
+        if profile.port != 22 {
+            command_line.push(if sftp { "-P" } else { "-p" }.to_string());
+            command_line.push(profile.port.to_string());
+        }
+
+        // Add identity file if specified; ~ and $VARS are expanded here since ssh
+        // wouldn't expand a path given on its command line
+        if let Some(identity) = &profile.identity_file {
+            command_line.push("-i".to_string());
+            command_line.push(expand_path(identity).display().to_string());
+        }
+
+        // Add any additional options
+        for (key, value) in &profile.options {
+            command_line.push(format!("-{}", key));
+            command_line.push(expand_value(value));
+        }
+
+        // After the profile's options, so a profile's own ControlPath wins
+        if let Some(sockets) = &self.control_sockets {
+            for option in sockets.ssh_options() {
+                command_line.push("-o".to_string());
+                command_line.push(option);
+            }
+        }
+
+        // Add extra arguments, e.g. from an alias
+        command_line.extend(args.ssh_args.iter().cloned());
+
+        // sftp's destination is user@host:path, with brackets around IPv6 addresses
+        if let Some(path) = &args.sftp {
+            let host = if profile.hostname.contains(':') {
+                format!("[{}]", profile.hostname)
+            } else {
+                profile.hostname.clone()
+            };
+            command_line.push(match path.is_empty() {
+                true => format!("{}@{}", profile.username, host),
+                false => format!("{}@{}:{}", profile.username, host, path),
+            });
+            return command_line;
+        }
+
+        // Force a TTY so interactive remote commands (tmux, screen) work
+        if args.command.is_some() {
+            command_line.push("-t".to_string());
+        }
+
+        // Add the connection string
+        command_line.push(format!("{}@{}", profile.username, profile.hostname));
+
+        // Add the remote command, if any
+        if let Some(command) = &args.command {
+            command_line.push(command.clone());
+        }
+
+        command_line
+    }
+
+    /// Test connection to a profile using thrussh, trying each address the host resolves to
+    async fn test_connection(&self, profile: &Profile) -> Result<Option<SocketAddr>, DomainError> {
+        match &profile.kind {
+            ProfileKind::Ssh => {},
+            ProfileKind::Docker { .. } => return Err(DomainError::ConfigError(format!(
+                "'{}' is a container on the local Docker daemon; there is no SSH connection to test", profile.name
+            ))),
+            ProfileKind::Kubernetes(pod) => return pod_ready(pod).await.map(|_| None),
+        }
+
+        let addrs = match profile.resolve_to {
+            Some(address) => vec![SocketAddr::new(address, profile.port)],
+            None => self.resolver.resolve(&profile.hostname, profile.port).await?,
+        };
+
+        let mut timed_out = 0;
+        for addr in &addrs {
+            // Try to connect with timeout
+            let handler = ClientHandler::new(true, &profile.hostname, profile.port, self.strict_host_keys);
+            match timeout(Duration::from_secs(10), thrussh::client::connect(self.client_config.clone(), *addr, handler)).await {
+                Ok(Ok((_, session))) => {
+                    // Successfully connected to SSH server
+                    // In a real implementation, we would also attempt to authenticate
+                    return Ok(Some(*addr));
+                },
+                Ok(Err(thrussh::Error::UnknownKey)) => {
+                    return Err(DomainError::SshError(format!(
+                        "Host key for {} is not in known_hosts or has changed; strict host key checking refused it",
+                        profile.hostname
+                    )));
+                },
+                Ok(Err(e)) => {
+                    // Connection error; the next address may still work
+                    tracing::debug!("SSH connection error on {}: {}", addr, e);
+                },
+                Err(_) => {
+                    tracing::debug!("Timed out connecting to {}", addr);
+                    timed_out += 1;
+                }
+            }
+        }
+
+        // Timeouts are worth retrying, unlike refused connections
+        if timed_out == addrs.len() {
+            return Err(DomainError::Unavailable(format!("Timed out connecting to {}:{}", profile.hostname, profile.port)));
+        }
+
+        Ok(None)
+    }
+
+    /// Copy SSH key to a remote server
+    async fn copy_key(&self, profile: &Profile, key_path: &Path) -> Result<(), DomainError> {
+        if !profile.kind.is_ssh() {
+            return Err(DomainError::ConfigError(format!(
+                "'{}' is not an SSH profile; keys can only be copied to SSH hosts", profile.name
+            )));
+        }
+
+        // Windows OpenSSH has no ssh-copy-id, so append the key over plain ssh there
+        let Some(ssh_copy_id) = find_command("ssh-copy-id") else {
+            return self.append_authorized_key(profile, key_path);
+        };
+
+        let mut cmd = Command::new(ssh_copy_id);
+
+        if self.strict_host_keys {
+            cmd.arg("-o").arg("StrictHostKeyChecking=yes");
+        }
+        for option in resolve_to_options(profile) {
+            cmd.arg("-o").arg(option);
+        }
+
+        // Add port if not default
+        if profile.port != 22 {
+            cmd.arg("-p").arg(profile.port.to_string());
+        }
+
+        // Add identity file
+        cmd.arg("-i").arg(key_path);
+
+        // Add the connection string
+        cmd.arg(format!("{}@{}", profile.username, profile.hostname));
+
+        // Set stdin/stdout/stderr
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        // Run the command
+        let status = cmd.spawn()
+            .map_err(|e| DomainError::SshError(format!("Failed to execute ssh-copy-id: {}", e)))?
+            .wait()
+            .map_err(|e| DomainError::SshError(format!("Failed to wait for ssh-copy-id: {}", e)))?;
+
+        if !status.success() {
+            return Err(DomainError::SshError(format!("ssh-copy-id returned error: {}", status)));
+        }
+
+        Ok(())
+    }
+
+    /// Generate a new SSH key pair
+    async fn generate_key(&self, key_name: &str, key_type: &str, comment: Option<&str>) -> Result<(PathBuf, PathBuf), DomainError> {
+        // Determine paths
+        let ssh_dir = crate::utils::home_dir()
+            .ok_or_else(|| DomainError::ConfigError("Could not determine home directory".to_string()))?
+            .join(".ssh");
+
+        if !ssh_dir.exists() {
+            tokio::fs::create_dir_all(&ssh_dir).await
+                .map_err(|e| DomainError::IoError(e))?;
+
+            // Set permissions on Unix
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let metadata = tokio::fs::metadata(&ssh_dir).await.map_err(|e| DomainError::IoError(e))?;
+                let mut permissions = metadata.permissions();
+                permissions.set_mode(0o700);
+                tokio::fs::set_permissions(&ssh_dir, permissions).await.map_err(|e| DomainError::IoError(e))?;
+            }
+        }
+
+        let key_path = ssh_dir.join(key_name);
+        let pubkey_path = ssh_dir.join(format!("{}.pub", key_name));
+
+        // Check if file already exists
+        if key_path.exists() {
+            return Err(DomainError::ConfigError(format!("Key file already exists: {}", key_path.display())));
+        }
+
+        // Create the key pair
+        self.create_key_pair(&key_path, key_type, comment).await?;
+
+        Ok((key_path, pubkey_path))
+    }
+}
\ No newline at end of file
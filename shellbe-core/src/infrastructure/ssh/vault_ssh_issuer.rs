@@ -0,0 +1,121 @@
+use crate::application::VaultSshSettings;
+use crate::domain::{DomainError, Sensitive, SshCredentialIssuer};
+use crate::utils::http_client;
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+
+/// Environment variable with the Vault server address, as used by the vault CLI
+pub const VAULT_ADDR_ENV: &str = "VAULT_ADDR";
+
+/// Environment variable with the Vault token, as used by the vault CLI
+pub const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+
+/// Environment variable with the Vault Enterprise namespace, if any
+pub const VAULT_NAMESPACE_ENV: &str = "VAULT_NAMESPACE";
+
+/// Signs keys and issues one-time passwords with HashiCorp Vault's SSH secrets engine
+///
+/// The token is read for every request, from `VAULT_TOKEN` or the `~/.vault-token` file
+/// that `vault login` writes, so logging in again takes effect right away.
+pub struct VaultSshIssuer {
+    client: Client,
+    address: String,
+    mount: String,
+}
+
+impl VaultSshIssuer {
+    /// Issuer for the engine mounted at `mount` on the Vault server at `address`
+    pub fn new(address: impl Into<String>, mount: impl Into<String>) -> Self {
+        Self {
+            client: http_client(),
+            address: address.into().trim_end_matches('/').to_string(),
+            mount: mount.into().trim_matches('/').to_string(),
+        }
+    }
+
+    fn token(&self) -> Result<Zeroizing<String>, DomainError> {
+        if let Some(token) = std::env::var(VAULT_TOKEN_ENV).ok().filter(|token| !token.is_empty()) {
+            return Ok(Zeroizing::new(token));
+        }
+
+        crate::utils::home_dir()
+            .and_then(|home| std::fs::read_to_string(home.join(".vault-token")).ok())
+            .map(|token| Zeroizing::new(token.trim().to_string()))
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| DomainError::ConfigError(format!("No Vault token; run `vault login` or set {}", VAULT_TOKEN_ENV)))
+    }
+
+    /// POST to an endpoint of the SSH engine and return the response's `data`
+    async fn post(&self, endpoint: &str, body: Value) -> Result<Value, DomainError> {
+        let url = format!("{}/v1/{}/{}", self.address, self.mount, endpoint);
+
+        let mut request = self.client.post(&url)
+            .header("X-Vault-Token", self.token()?.as_str())
+            .json(&body);
+        if let Ok(namespace) = std::env::var(VAULT_NAMESPACE_ENV) {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+
+        let response = request.send().await
+            .map_err(|e| DomainError::ConfigError(format!("Vault request to {} failed: {}", url, e)))?;
+
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            let errors = body["errors"].as_array()
+                .map(|errors| errors.iter().filter_map(Value::as_str).collect::<Vec<_>>().join("; "))
+                .unwrap_or_default();
+            let hint = match status {
+                StatusCode::FORBIDDEN => "; check the token and its policies",
+                _ => "",
+            };
+            return Err(DomainError::ConfigError(format!("Vault answered {} for {}: {}{}", status, url, errors, hint)));
+        }
+
+        Ok(body["data"].clone())
+    }
+}
+
+#[async_trait]
+impl SshCredentialIssuer for VaultSshIssuer {
+    fn name(&self) -> &str {
+        "Vault"
+    }
+
+    async fn sign_key(&self, role: &str, public_key: &str, principal: &str) -> Result<String, DomainError> {
+        let data = self.post(&format!("sign/{}", role), json!({
+            "public_key": public_key,
+            "valid_principals": principal,
+            "cert_type": "user",
+        })).await?;
+
+        data["signed_key"].as_str()
+            .map(|key| key.trim().to_string())
+            .ok_or_else(|| DomainError::ConfigError(format!("Vault role '{}' returned no signed key", role)))
+    }
+
+    async fn one_time_password(&self, role: &str, ip: &str, username: &str) -> Result<Sensitive<String>, DomainError> {
+        let data = self.post(&format!("creds/{}", role), json!({
+            "ip": ip,
+            "username": username,
+        })).await?;
+
+        let key = data["key"].as_str()
+            .ok_or_else(|| DomainError::ConfigError(format!("Vault role '{}' returned no one-time password", role)))?;
+
+        crate::utils::register_secret(key);
+        Ok(Sensitive::new(key.to_string()))
+    }
+}
+
+/// Build the Vault issuer from the `[vault_ssh]` settings; `None` without a Vault address
+pub fn vault_ssh_issuer(settings: &VaultSshSettings) -> Option<VaultSshIssuer> {
+    let address = std::env::var(VAULT_ADDR_ENV).ok()
+        .filter(|address| !address.is_empty())
+        .or_else(|| settings.address.clone())?;
+
+    Some(VaultSshIssuer::new(address, &settings.mount))
+}
@@ -0,0 +1,103 @@
+use crate::domain::{DomainError, SyncBackend};
+use crate::utils::http_client;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use zeroize::Zeroizing;
+
+/// How requests to the sync server authenticate
+#[derive(Default)]
+pub enum HttpAuth {
+    /// No authentication, e.g. a pre-signed URL
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`
+    Bearer(Zeroizing<String>),
+    /// HTTP basic authentication
+    Basic {
+        username: String,
+        password: Zeroizing<String>,
+    },
+}
+
+/// Keeps the snapshot at a URL any server accepting GET and PUT can serve
+pub struct HttpSyncBackend {
+    client: Client,
+    url: String,
+    auth: HttpAuth,
+}
+
+impl HttpSyncBackend {
+    /// Snapshot at the given http(s) URL
+    pub fn new(url: impl Into<String>, auth: HttpAuth) -> Self {
+        Self {
+            client: http_client(),
+            url: url.into(),
+            auth,
+        }
+    }
+
+    pub(super) fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+
+        match &self.auth {
+            HttpAuth::None => request,
+            HttpAuth::Bearer(token) => request.bearer_auth(token.as_str()),
+            HttpAuth::Basic { username, password } => request.basic_auth(username, Some(password.as_str())),
+        }
+    }
+
+    pub(super) fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[async_trait]
+impl SyncBackend for HttpSyncBackend {
+    fn name(&self) -> &str {
+        "HTTP"
+    }
+
+    fn location(&self) -> &str {
+        &self.url
+    }
+
+    async fn download(&self) -> Result<Option<Vec<u8>>, DomainError> {
+        let response = self.request(reqwest::Method::GET, &self.url).send().await
+            .map_err(|e| request_error(&self.url, e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = check_status(&self.url, response)?;
+        let bytes = response.bytes().await.map_err(|e| request_error(&self.url, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn upload(&self, data: &[u8]) -> Result<(), DomainError> {
+        let response = self.request(reqwest::Method::PUT, &self.url)
+            .body(data.to_vec())
+            .send().await
+            .map_err(|e| request_error(&self.url, e))?;
+
+        check_status(&self.url, response).map(|_| ())
+    }
+}
+
+pub(super) fn request_error(url: &str, error: reqwest::Error) -> DomainError {
+    DomainError::ConfigError(format!("Sync request to {} failed: {}", url, error))
+}
+
+/// Turn error responses into errors, with a hint for authentication failures
+pub(super) fn check_status(url: &str, response: reqwest::Response) -> Result<reqwest::Response, DomainError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let hint = match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => "; check the sync credentials",
+        _ => "",
+    };
+    Err(DomainError::ConfigError(format!("Sync server answered {} for {}{}", status, url, hint)))
+}
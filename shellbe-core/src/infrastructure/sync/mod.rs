@@ -0,0 +1,74 @@
+pub mod http_sync_backend;
+pub mod webdav_sync_backend;
+pub mod s3_sync_backend;
+
+pub use http_sync_backend::{HttpAuth, HttpSyncBackend};
+pub use webdav_sync_backend::WebDavSyncBackend;
+pub use s3_sync_backend::{S3Credentials, S3SyncBackend};
+
+use crate::application::SyncSettings;
+use crate::domain::{DomainError, SyncBackend};
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Environment variable with the password for WebDAV and HTTP basic authentication
+pub const SYNC_PASSWORD_ENV: &str = "SHELLBE_SYNC_PASSWORD";
+
+/// Environment variable with a bearer token for the HTTP backend
+pub const SYNC_TOKEN_ENV: &str = "SHELLBE_SYNC_TOKEN";
+
+/// Build the backend chosen by the `[sync]` settings; `None` when sync isn't set up
+///
+/// Credentials come from the environment so they never end up in the settings file.
+pub fn sync_backend(settings: &SyncSettings) -> Result<Option<Arc<dyn SyncBackend>>, DomainError> {
+    let url = match (settings.backend.as_str(), &settings.url) {
+        ("none", _) => return Ok(None),
+        (_, Some(url)) => url.clone(),
+        (backend, None) => {
+            return Err(DomainError::ConfigError(format!("sync.url is required for the {} backend", backend)));
+        },
+    };
+
+    let backend: Arc<dyn SyncBackend> = match settings.backend.as_str() {
+        "s3" => {
+            let region = settings.region.clone()
+                .or_else(|| env("AWS_REGION"))
+                .unwrap_or_else(|| "us-east-1".to_string());
+
+            let credentials = S3Credentials {
+                access_key_id: env("AWS_ACCESS_KEY_ID").ok_or_else(|| missing_env("AWS_ACCESS_KEY_ID"))?,
+                secret_access_key: env("AWS_SECRET_ACCESS_KEY")
+                    .map(Zeroizing::new)
+                    .ok_or_else(|| missing_env("AWS_SECRET_ACCESS_KEY"))?,
+                session_token: env("AWS_SESSION_TOKEN").map(Zeroizing::new),
+            };
+
+            Arc::new(S3SyncBackend::new(&url, &region, settings.endpoint.as_deref(), credentials)?)
+        },
+        "webdav" => Arc::new(WebDavSyncBackend::new(url, http_auth(settings)?)),
+        "http" => Arc::new(HttpSyncBackend::new(url, http_auth(settings)?)),
+        backend => return Err(DomainError::ConfigError(format!("Unknown sync backend '{}'", backend))),
+    };
+
+    Ok(Some(backend))
+}
+
+fn http_auth(settings: &SyncSettings) -> Result<HttpAuth, DomainError> {
+    if let Some(username) = &settings.username {
+        let password = env(SYNC_PASSWORD_ENV).ok_or_else(|| missing_env(SYNC_PASSWORD_ENV))?;
+        return Ok(HttpAuth::Basic {
+            username: username.clone(),
+            password: Zeroizing::new(password),
+        });
+    }
+
+    Ok(env(SYNC_TOKEN_ENV).map_or(HttpAuth::None, |token| HttpAuth::Bearer(Zeroizing::new(token))))
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn missing_env(name: &str) -> DomainError {
+    DomainError::ConfigError(format!("Set {} to use the configured sync backend", name))
+}
@@ -0,0 +1,165 @@
+use super::http_sync_backend::{check_status, request_error};
+use crate::domain::{DomainError, SyncBackend};
+use crate::utils::http_client;
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, StatusCode};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Credentials for signing S3 requests, as in the AWS CLI environment variables
+pub struct S3Credentials {
+    /// `AWS_ACCESS_KEY_ID`
+    pub access_key_id: String,
+    /// `AWS_SECRET_ACCESS_KEY`
+    pub secret_access_key: Zeroizing<String>,
+    /// `AWS_SESSION_TOKEN`, for temporary credentials
+    pub session_token: Option<Zeroizing<String>>,
+}
+
+/// Keeps the snapshot as an object in an S3 bucket, or any S3-compatible store such as MinIO
+///
+/// Requests are signed with AWS Signature Version 4 and use path-style URLs, which
+/// every S3-compatible store understands.
+pub struct S3SyncBackend {
+    client: Client,
+    location: String,
+    endpoint: String,
+    bucket: String,
+    key: String,
+    region: String,
+    credentials: S3Credentials,
+}
+
+impl S3SyncBackend {
+    /// Object at an `s3://bucket/key` URL; `endpoint` defaults to AWS in the given region
+    pub fn new(url: &str, region: &str, endpoint: Option<&str>, credentials: S3Credentials) -> Result<Self, DomainError> {
+        let (bucket, key) = url.strip_prefix("s3://")
+            .and_then(|path| path.split_once('/'))
+            .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+            .ok_or_else(|| DomainError::ConfigError(format!("'{}' is not an s3://bucket/key URL", url)))?;
+
+        let endpoint = endpoint
+            .map(|endpoint| endpoint.trim_end_matches('/').to_string())
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+
+        Ok(Self {
+            client: http_client(),
+            location: url.to_string(),
+            endpoint,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            region: region.to_string(),
+            credentials,
+        })
+    }
+
+    /// Send a signed request for the object
+    async fn send(&self, method: Method, body: &[u8]) -> Result<reqwest::Response, DomainError> {
+        let path = format!("/{}/{}", uri_encode(&self.bucket), uri_encode(&self.key));
+        let url = format!("{}{}", self.endpoint, path);
+
+        let host = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|parsed| parsed.host_str().map(|host| match parsed.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            }))
+            .ok_or_else(|| DomainError::ConfigError(format!("Invalid S3 endpoint '{}'", self.endpoint)))?;
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(body));
+
+        // Headers to sign, sorted by name
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.to_string()));
+        }
+
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            method, path, canonical_headers, signed_headers, payload_hash,
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date, scope, hex(&Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let secret = Zeroizing::new(format!("AWS4{}", self.credentials.secret_access_key.as_str()));
+        let mut signing_key = hmac_sha256(secret.as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac_sha256(&signing_key, part.as_bytes());
+        }
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        let mut request = self.client.request(method, &url)
+            .header("Authorization", format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.credentials.access_key_id, scope, signed_headers, signature,
+            ));
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+
+        request.body(body.to_vec()).send().await.map_err(|e| request_error(&self.location, e))
+    }
+}
+
+#[async_trait]
+impl SyncBackend for S3SyncBackend {
+    fn name(&self) -> &str {
+        "S3"
+    }
+
+    fn location(&self) -> &str {
+        &self.location
+    }
+
+    async fn download(&self) -> Result<Option<Vec<u8>>, DomainError> {
+        let response = self.send(Method::GET, &[]).await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let response = check_status(&self.location, response)?;
+        let bytes = response.bytes().await.map_err(|e| request_error(&self.location, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn upload(&self, data: &[u8]) -> Result<(), DomainError> {
+        let response = self.send(Method::PUT, data).await?;
+        check_status(&self.location, response).map(|_| ())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Percent-encode a path as SigV4 expects: everything but unreserved characters and `/`
+fn uri_encode(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+use super::http_sync_backend::{check_status, request_error, HttpAuth, HttpSyncBackend};
+use crate::domain::{DomainError, SyncBackend};
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+
+/// Keeps the snapshot on a WebDAV server such as Nextcloud, creating its folder when needed
+pub struct WebDavSyncBackend {
+    http: HttpSyncBackend,
+}
+
+impl WebDavSyncBackend {
+    /// Snapshot at the given file URL, e.g. `https://cloud.example.com/remote.php/dav/files/me/shellbe/sync.json`
+    pub fn new(url: impl Into<String>, auth: HttpAuth) -> Self {
+        Self { http: HttpSyncBackend::new(url, auth) }
+    }
+
+    async fn put(&self, data: &[u8]) -> Result<reqwest::Response, DomainError> {
+        let url = self.http.url();
+        self.http.request(Method::PUT, url)
+            .body(data.to_vec())
+            .send().await
+            .map_err(|e| request_error(url, e))
+    }
+
+    /// Create the folder the snapshot goes into
+    async fn create_parent(&self) -> Result<(), DomainError> {
+        let url = self.http.url();
+        let Some((parent, _)) = url.trim_end_matches('/').rsplit_once('/') else {
+            return Ok(());
+        };
+        let parent = format!("{}/", parent);
+
+        let method = Method::from_bytes(b"MKCOL").expect("MKCOL is a valid method");
+        let response = self.http.request(method, &parent).send().await
+            .map_err(|e| request_error(&parent, e))?;
+
+        // 405 means the folder already exists
+        if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(());
+        }
+        check_status(&parent, response).map(|_| ())
+    }
+}
+
+#[async_trait]
+impl SyncBackend for WebDavSyncBackend {
+    fn name(&self) -> &str {
+        "WebDAV"
+    }
+
+    fn location(&self) -> &str {
+        self.http.url()
+    }
+
+    async fn download(&self) -> Result<Option<Vec<u8>>, DomainError> {
+        self.http.download().await
+    }
+
+    async fn upload(&self, data: &[u8]) -> Result<(), DomainError> {
+        let mut response = self.put(data).await?;
+
+        // WebDAV servers answer 409 when the folder doesn't exist yet
+        if response.status() == StatusCode::CONFLICT {
+            self.create_parent().await?;
+            response = self.put(data).await?;
+        }
+
+        check_status(self.http.url(), response).map(|_| ())
+    }
+}
@@ -0,0 +1,44 @@
+//! The core of ShellBe: SSH profiles, aliases, connections, history and plugins, without the CLI
+//!
+//! [`ShellBeBuilder`] builds the services the way the `shellbe` CLI does. The re-exports below
+//! and the builder are the stable API and follow semver; the modules are public for the CLI
+//! and may change between minor releases.
+
+pub mod domain;
+pub mod application;
+pub mod infrastructure;
+pub mod utils;
+pub mod errors;
+pub mod builder;
+
+pub use builder::{ShellBe, ShellBeBuilder};
+
+/// Re-export common types
+pub use domain::{
+    Profile, Alias, HistoryEntry, ConnectionStats,
+    Event, EventBus, EventListener,
+    Plugin, PluginInfo, PluginCommand, Hook, PluginStatus, PluginMetadata,
+};
+
+pub use application::{
+    ProfileService, ConnectionService, AliasService,
+    PluginService, SshConfigService, UpdateService,
+    Settings, Policy,
+};
+
+pub use infrastructure::{
+    FileProfileRepository, FileAliasRepository, FileHistoryRepository,
+    FilePluginRepository, FileSshConfigRepository, ThrushSshService,
+    EncryptedStorage,
+};
+
+// Re-export error and result types
+pub use errors::{ShellBeError, Result, ErrorContext, ErrorReport, Suggestion};
+
+// Re-export useful utility functions
+pub use utils::{
+    ensure_directory, ensure_file, backup_file,
+    shellbe_config_dir, ssh_config_dir,
+    FileLock, PluginSecurityValidator, SystemRequirements,
+    ShellBePaths,
+};
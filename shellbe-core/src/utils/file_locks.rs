@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::time::Duration;
+use tokio::time::sleep;
+
+pub struct FileLock {
+    lock_file: PathBuf,
+    _file_handle: Option<File>,
+}
+
+impl FileLock {
+    pub async fn new(path: &Path) -> Self {
+        Self::for_path(path)
+    }
+
+    /// Lock for `path`, for synchronous code that uses [`FileLock::acquire_blocking`]
+    pub fn for_path(path: &Path) -> Self {
+        let lock_file = path.with_extension("lock");
+        Self {
+            lock_file,
+            _file_handle: None,
+        }
+    }
+
+    pub async fn acquire(&mut self, timeout_ms: u64) -> io::Result<bool> {
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_millis(timeout_ms);
+
+        loop {
+            if self.try_acquire()? {
+                return Ok(true);
+            }
+
+            // If we've timed out, return false
+            if start.elapsed() > timeout {
+                return Ok(false);
+            }
+
+            // Wait a bit before trying again
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Like [`FileLock::acquire`], blocking the thread while it waits
+    pub fn acquire_blocking(&mut self, timeout_ms: u64) -> io::Result<bool> {
+        let start = std::time::Instant::now();
+        let timeout = Duration::from_millis(timeout_ms);
+
+        loop {
+            if self.try_acquire()? {
+                return Ok(true);
+            }
+
+            if start.elapsed() > timeout {
+                return Ok(false);
+            }
+
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Take the lock if nobody holds it, clearing stale locks first
+    fn try_acquire(&mut self) -> io::Result<bool> {
+        loop {
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&self.lock_file)
+            {
+                Ok(file) => {
+                    self._file_handle = Some(file);
+                    return Ok(true);
+                },
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    // Check if the lock file is stale (older than 30 seconds)
+                    if let Ok(metadata) = self.lock_file.metadata() {
+                        if let Ok(modified) = metadata.modified() {
+                            if std::time::SystemTime::now().duration_since(modified).unwrap_or_default() > Duration::from_secs(30) {
+                                // Stale lock, try to remove it
+                                if std::fs::remove_file(&self.lock_file).is_ok() {
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+
+                    return Ok(false);
+                },
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Give up the lock; the lock file is only removed if this instance holds it
+    pub async fn release(&mut self) -> io::Result<()> {
+        if self._file_handle.take().is_some() && self.lock_file.exists() {
+            tokio::fs::remove_file(&self.lock_file).await?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        // A lock that timed out waiting must not remove the holder's lock file
+        if self._file_handle.take().is_some() && self.lock_file.exists() {
+            let _ = std::fs::remove_file(&self.lock_file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_losing_lock_keeps_holders_lock_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+
+        let mut holder = FileLock::for_path(&path);
+        assert!(holder.acquire_blocking(0).unwrap());
+
+        let mut loser = FileLock::for_path(&path);
+        assert!(!loser.acquire_blocking(0).unwrap());
+        drop(loser);
+
+        // Still held, so a third writer has to wait
+        let mut third = FileLock::for_path(&path);
+        assert!(!third.acquire_blocking(0).unwrap());
+
+        drop(holder);
+        assert!(third.acquire_blocking(0).unwrap());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,221 @@
+use std::path::{Path, PathBuf};
+use std::io;
+use tokio::fs;
+
+use super::layout::ShellBePaths;
+use crate::domain::Profile;
+
+/// Ensure a directory exists with proper permissions
+pub async fn ensure_directory(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        fs::create_dir_all(path).await?;
+
+        // Set proper permissions on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = fs::metadata(path).await?;
+            let mut permissions = metadata.permissions();
+            permissions.set_mode(0o700);
+            fs::set_permissions(path, permissions).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ensure a file exists with proper permissions
+pub async fn ensure_file(path: &Path, default_content: Option<&str>) -> io::Result<()> {
+    if !path.exists() {
+        // Make sure parent directory exists
+        if let Some(parent) = path.parent() {
+            ensure_directory(parent).await?;
+        }
+
+        // Create file with default content
+        if let Some(content) = default_content {
+            fs::write(path, content).await?;
+        } else {
+            fs::write(path, "").await?;
+        }
+
+        // Set proper permissions on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let metadata = fs::metadata(path).await?;
+            let mut permissions = metadata.permissions();
+            permissions.set_mode(0o600);
+            fs::set_permissions(path, permissions).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create or truncate a file only the owner can read and write
+pub fn create_private(path: &Path) -> io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)
+}
+
+/// Open a file for appending, creating it so only the owner can read and write
+pub fn append_private(path: &Path) -> io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.append(true).create(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options.open(path)
+}
+
+/// Write a file only the owner can read and write
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    create_private(path)?.write_all(contents)
+}
+
+/// Create a backup of a file with timestamp
+pub async fn backup_file(path: &Path) -> io::Result<PathBuf> {
+    if !path.exists() {
+        return Ok(path.to_owned());
+    }
+
+    let backup_path = path.with_extension(
+        format!("backup.{}", chrono::Utc::now().format("%Y%m%d%H%M%S"))
+    );
+
+    fs::copy(path, &backup_path).await?;
+
+    Ok(backup_path)
+}
+
+/// Environment variable overriding the shellbe config directory
+pub const CONFIG_DIR_ENV: &str = "SHELLBE_CONFIG_DIR";
+
+/// Path of the shellbe config directory (settings)
+pub fn config_dir_path() -> PathBuf {
+    ShellBePaths::resolve().config_dir
+}
+
+/// Path of the shellbe data directory (profiles, aliases, history, plugins)
+pub fn data_dir_path() -> PathBuf {
+    ShellBePaths::resolve().data_dir
+}
+
+/// Path of the shellbe cache directory
+pub fn cache_dir_path() -> PathBuf {
+    ShellBePaths::resolve().cache_dir
+}
+
+/// Get the shellbe config directory, creating it if it doesn't exist
+pub async fn shellbe_config_dir() -> io::Result<PathBuf> {
+    let dir = config_dir_path();
+
+    ensure_directory(&dir).await?;
+
+    Ok(dir)
+}
+
+/// Get the shellbe data directory, creating it if it doesn't exist
+pub async fn shellbe_data_dir() -> io::Result<PathBuf> {
+    let dir = data_dir_path();
+
+    ensure_directory(&dir).await?;
+
+    Ok(dir)
+}
+
+/// Get the shellbe cache directory, creating it if it doesn't exist
+pub async fn shellbe_cache_dir() -> io::Result<PathBuf> {
+    let dir = cache_dir_path();
+
+    ensure_directory(&dir).await?;
+
+    Ok(dir)
+}
+
+/// Get the SSH config directory, creating it if it doesn't exist
+pub async fn ssh_config_dir() -> io::Result<PathBuf> {
+    let dir = super::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ssh");
+
+    ensure_directory(&dir).await?;
+
+    Ok(dir)
+}
+
+/// Expand a leading `~` to the home directory and `$VAR` or `${VAR}` to environment variables
+///
+/// Variables that aren't set are kept as written, so a typo shows up in the path instead of
+/// silently turning into an empty string.
+pub fn expand_path(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    match path.to_str() {
+        Some(value) => PathBuf::from(expand_value(value)),
+        // Paths that aren't UTF-8 can't hold anything to expand that we could read
+        None => path.to_path_buf(),
+    }
+}
+
+/// Apply [`expand_path`] to a profile's identity file and [`expand_value`] to its SSH option values
+///
+/// Profiles are stored as written, so they keep working when synced to another machine;
+/// they are expanded where they are used.
+pub fn expand_profile(profile: &mut Profile) {
+    if let Some(identity) = &profile.identity_file {
+        profile.identity_file = Some(expand_path(identity));
+    }
+    for value in profile.options.values_mut() {
+        *value = expand_value(value);
+    }
+}
+
+/// [`expand_path`] for strings such as SSH option values
+pub fn expand_value(value: &str) -> String {
+    let value = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with(std::path::MAIN_SEPARATOR) => {
+            match super::home_dir() {
+                Some(home) => format!("{}{}", home.display(), rest),
+                None => value.to_string(),
+            }
+        },
+        _ => value.to_string(),
+    };
+
+    shellexpand::env_with_context_no_errors(&value, |name| std::env::var(name).ok()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_value_tilde_and_variables() {
+        std::env::set_var("SHELLBE_TEST_KEYS", "/opt/keys");
+        let home = crate::utils::home_dir().unwrap();
+
+        assert_eq!(expand_value("~/.ssh/id_ed25519"), format!("{}/.ssh/id_ed25519", home.display()));
+        assert_eq!(expand_value("$SHELLBE_TEST_KEYS/web"), "/opt/keys/web");
+        assert_eq!(expand_value("${SHELLBE_TEST_KEYS}/web"), "/opt/keys/web");
+        // Only a leading ~ means home, and ~user isn't supported
+        assert_eq!(expand_value("keys/~backup"), "keys/~backup");
+        assert_eq!(expand_value("~deploy/key"), "~deploy/key");
+        // Unset variables are left alone
+        assert_eq!(expand_value("$SHELLBE_TEST_UNSET/key"), "$SHELLBE_TEST_UNSET/key");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,83 @@
+use crate::domain::DomainError;
+use super::fs::expand_path;
+use once_cell::sync::OnceCell;
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use std::fs;
+use std::path::PathBuf;
+
+/// Client every network call goes through, once [`configure_http`] has run
+static CLIENT: OnceCell<Client> = OnceCell::new();
+
+/// How outgoing HTTP requests reach the network
+#[derive(Debug, Clone, Default)]
+pub struct HttpOptions {
+    /// Proxy for every request, e.g. `http://proxy.example.com:3128`; unset uses
+    /// `HTTPS_PROXY`, `HTTP_PROXY` and `ALL_PROXY`
+    pub proxy: Option<String>,
+    /// PEM files with root certificates trusted on top of the system's, e.g. the CA of a
+    /// proxy that intercepts TLS
+    pub ca_certificates: Vec<PathBuf>,
+}
+
+impl HttpOptions {
+    /// Build a client with these options
+    pub fn client(&self) -> Result<Client, DomainError> {
+        let mut builder = Client::builder();
+
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| DomainError::ConfigError(format!("Invalid proxy '{}': {}", proxy, e)))?
+                .no_proxy(NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+
+        for path in &self.ca_certificates {
+            let path = expand_path(path);
+            let pem = fs::read_to_string(&path)
+                .map_err(|e| DomainError::ConfigError(format!("Failed to read CA certificates {}: {}", path.display(), e)))?;
+
+            let certificates = pem_certificates(&pem);
+            if certificates.is_empty() {
+                return Err(DomainError::ConfigError(format!("No certificates in {}", path.display())));
+            }
+            for certificate in certificates {
+                let certificate = Certificate::from_pem(certificate.as_bytes())
+                    .map_err(|e| DomainError::ConfigError(format!("Invalid CA certificate in {}: {}", path.display(), e)))?;
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        builder.build()
+            .map_err(|e| DomainError::ConfigError(format!("Failed to set up HTTP client: {}", e)))
+    }
+}
+
+/// Use `options` for every network call made from now on; the first call wins
+pub fn configure_http(options: &HttpOptions) -> Result<(), DomainError> {
+    let client = options.client()?;
+    let _ = CLIENT.set(client);
+    Ok(())
+}
+
+/// Client for network calls, with the configured proxy and root certificates
+///
+/// Clients share their connection pool, so this is cheap to call.
+pub fn http_client() -> Client {
+    CLIENT.get_or_init(Client::new).clone()
+}
+
+/// Each `CERTIFICATE` block in a PEM bundle
+fn pem_certificates(pem: &str) -> Vec<&str> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certificates = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(BEGIN) {
+        let Some(end) = rest[start..].find(END) else { break };
+        let end = start + end + END.len();
+        certificates.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    certificates
+}
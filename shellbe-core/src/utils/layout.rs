@@ -0,0 +1,170 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::fs::CONFIG_DIR_ENV;
+
+/// Environment variable selecting the directory layout (`xdg` or `legacy`)
+pub const LAYOUT_ENV: &str = "SHELLBE_LAYOUT";
+
+/// Files that belong in the config directory rather than the data directory
+const CONFIG_FILES: &[&str] = &["settings.toml"];
+
+/// How ShellBe lays out its files on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectoryLayout {
+    /// XDG Base Directory layout (`~/.config`, `~/.local/share`, `~/.cache`)
+    Xdg,
+    /// Everything in `~/.shellbe`, as in earlier releases
+    Legacy,
+}
+
+impl DirectoryLayout {
+    /// Read the layout from `SHELLBE_LAYOUT`, defaulting to XDG
+    pub fn from_env() -> Self {
+        match std::env::var(LAYOUT_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("legacy") => DirectoryLayout::Legacy,
+            _ => DirectoryLayout::Xdg,
+        }
+    }
+}
+
+/// Directories ShellBe stores its files in
+#[derive(Debug, Clone)]
+pub struct ShellBePaths {
+    /// User-editable configuration (settings)
+    pub config_dir: PathBuf,
+    /// Profiles, aliases, history and plugins
+    pub data_dir: PathBuf,
+    /// Disposable caches
+    pub cache_dir: PathBuf,
+    /// Layout the directories were resolved with
+    pub layout: DirectoryLayout,
+    /// Whether the directories come from an explicit `SHELLBE_CONFIG_DIR` override
+    pub overridden: bool,
+}
+
+impl ShellBePaths {
+    /// Resolve directories from the environment
+    pub fn resolve() -> Self {
+        // An explicit directory keeps all state together in one place
+        if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV).filter(|d| !d.is_empty()) {
+            let dir = PathBuf::from(dir);
+            return Self::single(dir, DirectoryLayout::Legacy, true);
+        }
+
+        match DirectoryLayout::from_env() {
+            DirectoryLayout::Legacy => Self::single(legacy_dir(), DirectoryLayout::Legacy, false),
+            DirectoryLayout::Xdg => Self {
+                config_dir: xdg_dir("XDG_CONFIG_HOME", ".config").join("shellbe"),
+                data_dir: xdg_dir("XDG_DATA_HOME", ".local/share").join("shellbe"),
+                cache_dir: xdg_dir("XDG_CACHE_HOME", ".cache").join("shellbe"),
+                layout: DirectoryLayout::Xdg,
+                overridden: false,
+            },
+        }
+    }
+
+    /// All directories in `dir`, as with `SHELLBE_CONFIG_DIR`
+    pub fn in_dir(dir: impl Into<PathBuf>) -> Self {
+        Self::single(dir.into(), DirectoryLayout::Legacy, true)
+    }
+
+    /// All directories in one place
+    fn single(dir: PathBuf, layout: DirectoryLayout, overridden: bool) -> Self {
+        Self {
+            config_dir: dir.clone(),
+            data_dir: dir.clone(),
+            cache_dir: dir.join("cache"),
+            layout,
+            overridden,
+        }
+    }
+
+    /// Move files from `~/.shellbe` into the XDG directories; returns the moved paths
+    ///
+    /// Files that already exist at the destination are left in place untouched.
+    pub fn migrate_legacy(&self) -> io::Result<Vec<PathBuf>> {
+        let legacy = legacy_dir();
+
+        if self.layout != DirectoryLayout::Xdg || self.overridden || !legacy.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        fs::create_dir_all(&self.config_dir)?;
+        fs::create_dir_all(&self.data_dir)?;
+
+        let mut moved = Vec::new();
+
+        for entry in fs::read_dir(&legacy)? {
+            let entry = entry?;
+            let name = entry.file_name();
+
+            let target_dir = if CONFIG_FILES.iter().any(|f| name == *f) {
+                &self.config_dir
+            } else {
+                &self.data_dir
+            };
+            let target = target_dir.join(&name);
+
+            if target.exists() {
+                continue;
+            }
+
+            move_path(&entry.path(), &target)?;
+            moved.push(target);
+        }
+
+        // Only remove the legacy directory once it's empty
+        let _ = fs::remove_dir(&legacy);
+
+        Ok(moved)
+    }
+}
+
+/// The pre-XDG `~/.shellbe` directory
+pub fn legacy_dir() -> PathBuf {
+    home_or_current_dir().join(".shellbe")
+}
+
+/// The user's home directory; on Windows `USERPROFILE` wins, as `HOME` does elsewhere
+pub fn home_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        if let Some(profile) = std::env::var_os("USERPROFILE").filter(|p| !p.is_empty()) {
+            return Some(PathBuf::from(profile));
+        }
+    }
+    crate::utils::home_dir()
+}
+
+fn home_or_current_dir() -> PathBuf {
+    home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// An XDG base directory from the environment, falling back to a path under home
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(var) {
+        // The spec says relative paths must be ignored
+        Some(dir) if Path::new(&dir).is_absolute() => PathBuf::from(dir),
+        _ => home_or_current_dir().join(fallback),
+    }
+}
+
+/// Move a file or directory, copying when a rename isn't possible (e.g. across filesystems)
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            move_path(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::remove_dir(from)
+    } else {
+        fs::copy(from, to)?;
+        fs::remove_file(from)
+    }
+}
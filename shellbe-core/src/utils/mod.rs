@@ -0,0 +1,21 @@
+pub mod fs;
+pub mod file_locks;
+pub mod http;
+pub mod layout;
+pub mod permissions;
+pub mod plugin_security;
+pub mod redact;
+pub mod shell_alias;
+pub mod suggest;
+pub mod system_requirements;
+
+pub use fs::*;
+pub use file_locks::FileLock;
+pub use http::{configure_http, http_client, HttpOptions};
+pub use layout::{DirectoryLayout, ShellBePaths, home_dir};
+pub use permissions::PermissionIssue;
+pub use plugin_security::PluginSecurityValidator;
+pub use redact::{redact_json, redact_text, register_secret, RedactingWriter};
+pub use shell_alias::{shell_join, shell_quote, ShellAliasEntry, ShellAliasManifest};
+pub use suggest::closest_matches;
+pub use system_requirements::{SystemRequirements, OpenSshVersion, command_program, find_command, SshAdvisory, SshClientIssue, SSH_ADVISORIES};
\ No newline at end of file
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Mode for directories holding private data
+pub const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Mode for files holding private data and identity files
+pub const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// A private file or directory that other users can access, like OpenSSH refuses for keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionIssue {
+    /// The file or directory
+    pub path: PathBuf,
+    /// Current permission bits
+    pub mode: u32,
+    /// Permission bits it should have
+    pub expected_mode: u32,
+    /// Whether the current user owns it; only then can it be fixed without root
+    pub owned_by_user: bool,
+}
+
+impl PermissionIssue {
+    /// Whether `fix` can correct the issue
+    pub fn is_fixable(&self) -> bool {
+        self.owned_by_user
+    }
+
+    /// Command that corrects the issue by hand
+    pub fn fix_command(&self) -> String {
+        if self.owned_by_user {
+            format!("chmod {:o} {}", self.expected_mode, self.path.display())
+        } else {
+            format!("sudo chown $USER {} && chmod {:o} {}", self.path.display(), self.expected_mode, self.path.display())
+        }
+    }
+
+    /// Set the expected permission bits
+    pub fn fix(&self) -> io::Result<()> {
+        if !self.owned_by_user {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is owned by another user", self.path.display()),
+            ));
+        }
+
+        set_mode(&self.path, self.expected_mode)
+    }
+}
+
+impl fmt::Display for PermissionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.owned_by_user {
+            write!(f, "{} is owned by another user", self.path.display())
+        } else {
+            write!(f, "{} is {:o} and accessible to other users", self.path.display(), self.mode)
+        }
+    }
+}
+
+/// Check that an existing path is owned by the current user and closed to others
+///
+/// Returns `None` when it is fine, doesn't exist, or the platform has no permission bits.
+#[cfg(unix)]
+pub fn check_private(path: &Path, expected_mode: u32) -> io::Result<Option<PermissionIssue>> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mode = metadata.permissions().mode() & 0o777;
+    let owned_by_user = metadata.uid() == unsafe { libc::geteuid() };
+
+    if owned_by_user && mode & 0o077 == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(PermissionIssue {
+        path: path.to_path_buf(),
+        mode,
+        expected_mode,
+        owned_by_user,
+    }))
+}
+
+/// Permission bits aren't meaningful on this platform
+#[cfg(not(unix))]
+pub fn check_private(_path: &Path, _expected_mode: u32) -> io::Result<Option<PermissionIssue>> {
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
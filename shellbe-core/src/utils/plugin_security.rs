@@ -1,3 +1,4 @@
+use crate::domain::SecurityFinding;
 use crate::errors::{Result, ShellBeError};
 use std::path::{Path, PathBuf};
 use std::fs;
@@ -5,6 +6,12 @@ use std::process::Command;
 use std::collections::HashSet;
 use regex::Regex;
 
+/// Pattern whose presence in a plugin fails validation instead of only being reported
+const UNSAFE_PATTERN: &str = r"unsafe\s+\{";
+
+/// Dependency tables checked in a plugin's `Cargo.toml`
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "build-dependencies"];
+
 /// Plugin security validator to ensure plugins are safe to load
 pub struct PluginSecurityValidator {
     max_file_size: u64,
@@ -41,7 +48,7 @@ impl Default for PluginSecurityValidator {
             Regex::new(r"TcpStream::connect").unwrap(),
             Regex::new(r"reqwest::").unwrap(),
             // Unsafe blocks
-            Regex::new(r"unsafe\s+\{").unwrap(),
+            Regex::new(UNSAFE_PATTERN).unwrap(),
             // Dynamic code evaluation (if possible in Rust)
             Regex::new(r"eval\(").unwrap(),
             // Shell script execution
@@ -158,6 +165,11 @@ impl PluginSecurityValidator {
         Ok(suspicious_imports)
     }
 
+    /// Whether validation is on; when off, `validate` passes everything
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
     /// Validate a plugin library
     pub fn validate(&self, path: &Path) -> Result<()> {
         if !self.enabled {
@@ -194,6 +206,109 @@ impl PluginSecurityValidator {
         Ok(())
     }
 
+    /// Check if a plugin source tree can be scanned (it has a `Cargo.toml`)
+    pub fn has_source(root: &Path) -> bool {
+        root.join("Cargo.toml").is_file()
+    }
+
+    /// Scan a plugin's source tree before it is installed
+    ///
+    /// Rust files are checked for suspicious patterns, and `Cargo.toml` for dependencies
+    /// outside the allowed imports, git or path dependencies and build scripts. Findings are
+    /// returned for review; `unsafe` blocks fail the scan like they fail `validate`.
+    pub fn scan_source(&self, root: &Path) -> Result<Vec<SecurityFinding>> {
+        if !self.enabled {
+            tracing::warn!("Plugin security validation is disabled. This is not recommended.");
+            return Ok(Vec::new());
+        }
+
+        let mut findings = Vec::new();
+        let mut blocking = Vec::new();
+
+        for path in source_files(root)? {
+            let relative = path.strip_prefix(root).unwrap_or(&path).display().to_string();
+
+            if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+                findings.extend(self.check_manifest(&path, &relative)?);
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                if path.file_name().is_some_and(|name| name == "build.rs") {
+                    findings.push(finding(&relative, None, "Build script runs code when the plugin is built"));
+                }
+
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| ShellBeError::Security(format!("Failed to read {}: {}", path.display(), e)))?;
+
+                for (index, line) in content.lines().enumerate() {
+                    for pattern in self.suspicious_patterns.iter().filter(|pattern| pattern.is_match(line)) {
+                        let found = finding(&relative, Some(index + 1), format!("Suspicious pattern found: {}", line.trim()));
+                        if pattern.as_str() == UNSAFE_PATTERN {
+                            blocking.push(found.clone());
+                        }
+                        findings.push(found);
+                    }
+                }
+            }
+        }
+
+        for finding in &findings {
+            tracing::warn!("Security finding in plugin source {}: {}", root.display(), finding);
+        }
+
+        if let Some(first) = blocking.first() {
+            return Err(ShellBeError::Security(format!("Plugin source contains potentially unsafe code: {}", first)));
+        }
+
+        Ok(findings)
+    }
+
+    /// Check the dependencies and build settings in a `Cargo.toml`
+    fn check_manifest(&self, path: &Path, relative: &str) -> Result<Vec<SecurityFinding>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| ShellBeError::Security(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        let manifest: toml::Table = match toml::from_str(&content) {
+            Ok(manifest) => manifest,
+            Err(e) => return Ok(vec![finding(relative, None, format!("Cargo.toml could not be parsed: {}", e.message()))]),
+        };
+
+        let mut findings = Vec::new();
+
+        if let Some(build) = manifest.get("package").and_then(|package| package.get("build")) {
+            if build.as_bool() != Some(false) {
+                findings.push(finding(relative, line_of(&content, "build"), "Build script runs code when the plugin is built"));
+            }
+        }
+
+        // Top-level tables and their `[target.'cfg(..)'.dependencies]` variants
+        let mut tables: Vec<&toml::Table> = DEPENDENCY_TABLES.iter()
+            .filter_map(|name| manifest.get(*name).and_then(|table| table.as_table()))
+            .collect();
+        if let Some(targets) = manifest.get("target").and_then(|targets| targets.as_table()) {
+            for target in targets.values().filter_map(|target| target.as_table()) {
+                tables.extend(DEPENDENCY_TABLES.iter().filter_map(|name| target.get(*name).and_then(|table| table.as_table())));
+            }
+        }
+
+        for (name, spec) in tables.into_iter().flatten() {
+            let line = line_of(&content, name);
+            let crate_name = spec.get("package").and_then(|package| package.as_str()).unwrap_or(name.as_str());
+
+            if !self.allowed_imports.contains(&crate_name.replace('-', "_")) {
+                findings.push(finding(relative, line, format!("Dependency `{}` is not on the list of allowed crates", crate_name)));
+            }
+
+            if let Some(git) = spec.get("git").and_then(|git| git.as_str()) {
+                findings.push(finding(relative, line, format!("Dependency `{}` is fetched from git: {}", crate_name, git)));
+            }
+
+            if spec.get("path").is_some() {
+                findings.push(finding(relative, line, format!("Dependency `{}` comes from a local path", crate_name)));
+            }
+        }
+
+        Ok(findings)
+    }
+
     /// Set validation enabled/disabled
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
@@ -208,4 +323,51 @@ impl PluginSecurityValidator {
     pub fn set_max_file_size(&mut self, max_size: u64) {
         self.max_file_size = max_size;
     }
-}
\ No newline at end of file
+}
+
+fn finding(file: &str, line: Option<usize>, message: impl Into<String>) -> SecurityFinding {
+    SecurityFinding {
+        file: file.to_string(),
+        line,
+        message: message.into(),
+    }
+}
+
+/// Line number of the first `key = ...` or `key.x = ...` entry in a TOML document
+fn line_of(content: &str, key: &str) -> Option<usize> {
+    content.lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(['=', '.']))
+        })
+        .map(|index| index + 1)
+}
+
+/// `Cargo.toml` and Rust files under a source root, skipping build output and hidden directories
+fn source_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| ShellBeError::Security(format!("Failed to read {}: {}", dir.display(), e)))?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if path.is_dir() {
+                if name != "target" && !name.starts_with('.') {
+                    dirs.push(path);
+                }
+            } else if name == "Cargo.toml" || name.ends_with(".rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,124 @@
+use crate::domain::REDACTED;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+/// `password=...`, `token: ...` and similar assignments in free text
+static ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)\b(password|passwd|passphrase|secret|token|api[_-]?key)(\s*[=:]\s*)("[^"]*"|'[^']*'|[^\s&]+)"#).unwrap()
+});
+
+/// `sshpass -p <password>`
+static SSHPASS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)(sshpass\s+-p\s*)(\S+)").unwrap());
+
+/// Private key blocks
+static PRIVATE_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----").unwrap()
+});
+
+/// JSON keys whose values are always secret
+static SENSITIVE_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(password|passwd|passphrase|secret|token|api[_-]?key|totp[_-]?secret)$").unwrap()
+});
+
+/// Secrets seen by this process, e.g. the vault passphrase, masked wherever they appear
+static KNOWN_SECRETS: Lazy<Mutex<Vec<Zeroizing<String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Shortest registered secret that is masked; shorter ones would mask ordinary words
+const MIN_SECRET_LEN: usize = 4;
+
+/// Mask this value wherever it appears in logs, errors and output from now on
+pub fn register_secret(secret: &str) {
+    let secret = secret.trim();
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+
+    if let Ok(mut secrets) = KNOWN_SECRETS.lock() {
+        if !secrets.iter().any(|known| known.as_str() == secret) {
+            secrets.push(Zeroizing::new(secret.to_string()));
+        }
+    }
+}
+
+/// Mask passwords, passphrases, tokens, private keys and registered secrets in text
+pub fn redact_text(text: &str) -> String {
+    let mut text = PRIVATE_KEY.replace_all(text, REDACTED).into_owned();
+    text = SSHPASS.replace_all(&text, format!("${{1}}{}", REDACTED)).into_owned();
+    text = ASSIGNMENT.replace_all(&text, format!("${{1}}${{2}}{}", REDACTED)).into_owned();
+
+    if let Ok(secrets) = KNOWN_SECRETS.lock() {
+        for secret in secrets.iter() {
+            if text.contains(secret.as_str()) {
+                text = text.replace(secret.as_str(), REDACTED);
+            }
+        }
+    }
+
+    text
+}
+
+/// Mask secret-named fields and secrets inside strings of a JSON value
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SENSITIVE_KEY.is_match(key) && !value.is_null() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        },
+        serde_json::Value::Array(values) => values.iter_mut().for_each(redact_json),
+        serde_json::Value::String(text) => {
+            let redacted = redact_text(text);
+            if redacted != *text {
+                *text = redacted;
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Log writer that masks secrets before anything reaches the terminal
+///
+/// Collects one log event and writes it redacted when flushed or dropped.
+pub struct RedactingWriter {
+    buffer: Vec<u8>,
+}
+
+impl RedactingWriter {
+    /// Writer for standard output, for use with `fmt::layer().with_writer`
+    pub fn stdout() -> Self {
+        Self { buffer: Vec::new() }
+    }
+}
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let text = redact_text(&String::from_utf8_lossy(&self.buffer));
+        self.buffer.clear();
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()
+    }
+}
+
+impl Drop for RedactingWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
@@ -0,0 +1,196 @@
+use super::fs::write_private;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the manifest file tracking shell aliases written by ShellBe
+pub const SHELL_ALIAS_MANIFEST: &str = "shell_aliases.json";
+
+/// Comment line written above every shell alias added by ShellBe
+const ALIAS_COMMENT_PREFIX: &str = "# ShellBe alias added on";
+
+/// A shell alias that ShellBe wrote to an rc file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShellAliasEntry {
+    /// Alias name as used in the shell
+    pub name: String,
+    /// Profile or alias the shell alias connects to
+    pub target: String,
+    /// Shell rc file containing the alias line
+    pub rc_file: PathBuf,
+    /// When the alias line was written
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Manifest of shell aliases created by ShellBe, used to clean them up later
+pub struct ShellAliasManifest {
+    path: PathBuf,
+    entries: Vec<ShellAliasEntry>,
+}
+
+impl ShellAliasManifest {
+    /// Load the manifest from disk, starting empty if it doesn't exist yet
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let entries = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self { path, entries })
+    }
+
+    /// Write the manifest back to disk
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        write_private(&self.path, content.as_bytes())
+    }
+
+    /// All tracked shell aliases
+    pub fn entries(&self) -> &[ShellAliasEntry] {
+        &self.entries
+    }
+
+    /// Get a tracked shell alias by name
+    pub fn get(&self, name: &str) -> Option<&ShellAliasEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Track a shell alias, replacing any previous entry with the same name
+    pub fn record(&mut self, entry: ShellAliasEntry) {
+        self.entries.retain(|e| e.name != entry.name);
+        self.entries.push(entry);
+    }
+
+    /// Stop tracking a shell alias
+    pub fn forget(&mut self, name: &str) -> Option<ShellAliasEntry> {
+        let idx = self.entries.iter().position(|e| e.name == name)?;
+        Some(self.entries.remove(idx))
+    }
+}
+
+/// Whether an rc file is a PowerShell profile rather than a POSIX shell rc file
+pub fn is_powershell_profile(rc_file: &Path) -> bool {
+    rc_file.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("ps1"))
+}
+
+/// The current user's PowerShell profile
+///
+/// PowerShell 7 keeps it under `Documents\PowerShell`; Windows PowerShell 5.1, used
+/// when PowerShell 7 was never set up, under `Documents\WindowsPowerShell`.
+pub fn powershell_profile() -> Option<PathBuf> {
+    let documents = dirs::document_dir().or_else(|| super::home_dir().map(|h| h.join("Documents")))?;
+    let modern = documents.join("PowerShell");
+    let dir = if modern.exists() || !documents.join("WindowsPowerShell").exists() {
+        modern
+    } else {
+        documents.join("WindowsPowerShell")
+    };
+    Some(dir.join("Microsoft.PowerShell_profile.ps1"))
+}
+
+/// Build the rc file line for a shell alias
+///
+/// PowerShell aliases can't carry arguments, so PowerShell profiles get a function instead.
+pub fn shell_alias_line(rc_file: &Path, name: &str, target: &str) -> String {
+    if is_powershell_profile(rc_file) {
+        format!("function {} {{ shellbe connect {} @args }}", name, target)
+    } else {
+        format!("alias {}='shellbe connect {}'", name, target)
+    }
+}
+
+/// Start of the line [`shell_alias_line`] writes for `name`
+fn shell_alias_prefix(rc_file: &Path, name: &str) -> String {
+    if is_powershell_profile(rc_file) {
+        format!("function {} {{ shellbe connect ", name)
+    } else {
+        format!("alias {}='shellbe connect ", name)
+    }
+}
+
+/// Append a shell alias to an rc file; returns false if the line was already present
+pub fn add_shell_alias_line(rc_file: &Path, name: &str, target: &str) -> io::Result<bool> {
+    add_rc_line(rc_file, ALIAS_COMMENT_PREFIX, &shell_alias_line(rc_file, name, target))
+}
+
+/// Append a line to an rc file below a dated comment; returns false if the line was already present
+pub fn add_rc_line(rc_file: &Path, comment_prefix: &str, line: &str) -> io::Result<bool> {
+    use std::io::Write;
+
+    if rc_file.exists() && fs::read_to_string(rc_file)?.lines().any(|l| l == line) {
+        return Ok(false);
+    }
+
+    // A PowerShell profile's directory usually doesn't exist until something writes to it
+    if let Some(dir) = rc_file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(rc_file)?;
+
+    writeln!(file, "\n{} {}", comment_prefix, chrono::Local::now().format("%Y-%m-%d %H:%M:%S"))?;
+    writeln!(file, "{}", line)?;
+
+    Ok(true)
+}
+
+/// Remove a ShellBe shell alias (and its comment line) from an rc file; returns false if not found
+pub fn remove_shell_alias_line(rc_file: &Path, name: &str) -> io::Result<bool> {
+    if !rc_file.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(rc_file)?;
+    let prefix = shell_alias_prefix(rc_file, name);
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut output: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut removed = false;
+
+    for line in lines {
+        if line.starts_with(&prefix) {
+            removed = true;
+
+            // Drop the comment (and blank separator) written together with the alias
+            if output.last().map_or(false, |l| l.starts_with(ALIAS_COMMENT_PREFIX)) {
+                output.pop();
+                if output.last().map_or(false, |l| l.trim().is_empty()) {
+                    output.pop();
+                }
+            }
+            continue;
+        }
+
+        output.push(line);
+    }
+
+    if removed {
+        fs::write(rc_file, output.join("\n") + "\n")?;
+    }
+
+    Ok(removed)
+}
+
+
+/// Join a command line into one POSIX shell command, quoting arguments where needed
+pub fn shell_join(args: &[String]) -> String {
+    args.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")
+}
+
+/// Quote an argument for a POSIX shell command line
+pub fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:@=,+%".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
@@ -0,0 +1,693 @@
+use crate::errors::{ShellBeError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsStr;
+use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// File in the cache directory remembering the last successful requirements check
+pub const REQUIREMENTS_CACHE_FILE: &str = "requirements.json";
+
+/// How long a successful requirements check is trusted before commands are looked up again
+pub const REQUIREMENTS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A successful check, valid while the commands and PATH stay the same
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct RequirementsCache {
+    checked_at: DateTime<Utc>,
+    commands: Vec<String>,
+    path: String,
+}
+
+/// Version of the OpenSSH client, e.g. `9.6p1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpenSshVersion {
+    pub major: u32,
+    pub minor: u32,
+    /// Portable release, the `N` in `pN`; 0 when absent
+    pub patch: u32,
+}
+
+impl OpenSshVersion {
+    /// Version from its parts
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// Version from `ssh -V` output, e.g. `OpenSSH_9.6p1 Ubuntu-3ubuntu13, OpenSSL 3.0.13`
+    pub fn from_banner(banner: &str) -> Option<Self> {
+        let re = regex::Regex::new(r"OpenSSH_(?:for_Windows_)?(\d+\.\d+(?:p\d+)?)").ok()?;
+        re.captures(banner)?.get(1)?.as_str().parse().ok()
+    }
+}
+
+impl FromStr for OpenSshVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not an OpenSSH version like '8.9' or '9.3p2'", s);
+
+        let (version, patch) = match s.trim().split_once('p') {
+            Some((version, patch)) => (version, patch.parse().map_err(|_| invalid())?),
+            None => (s.trim(), 0),
+        };
+        let (major, minor) = version.split_once('.').ok_or_else(invalid)?;
+
+        Ok(Self::new(
+            major.parse().map_err(|_| invalid())?,
+            minor.parse().map_err(|_| invalid())?,
+            patch,
+        ))
+    }
+}
+
+impl fmt::Display for OpenSshVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch > 0 {
+            write!(f, "p{}", self.patch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A published vulnerability in a range of OpenSSH client versions
+#[derive(Debug, Clone, Copy)]
+pub struct SshAdvisory {
+    /// CVE identifier
+    pub id: &'static str,
+    /// What an attacker can do
+    pub summary: &'static str,
+    /// First affected version
+    pub introduced: OpenSshVersion,
+    /// First version with the fix
+    pub fixed: OpenSshVersion,
+}
+
+impl SshAdvisory {
+    /// Check if a version is affected
+    pub fn affects(&self, version: &OpenSshVersion) -> bool {
+        self.introduced <= *version && *version < self.fixed
+    }
+}
+
+/// Client-side OpenSSH vulnerabilities that matter when ShellBe runs `ssh` for you
+pub const SSH_ADVISORIES: &[SshAdvisory] = &[
+    SshAdvisory {
+        id: "CVE-2016-0777",
+        summary: "a malicious server can read client memory, including private keys, through roaming",
+        introduced: OpenSshVersion::new(5, 4, 0),
+        fixed: OpenSshVersion::new(7, 1, 2),
+    },
+    SshAdvisory {
+        id: "CVE-2023-38408",
+        summary: "a forwarded ssh-agent can be made to run code through PKCS#11 providers",
+        introduced: OpenSshVersion::new(0, 0, 0),
+        fixed: OpenSshVersion::new(9, 3, 2),
+    },
+    SshAdvisory {
+        id: "CVE-2023-48795",
+        summary: "a network attacker can drop messages at the start of a session (Terrapin)",
+        introduced: OpenSshVersion::new(0, 0, 0),
+        fixed: OpenSshVersion::new(9, 6, 1),
+    },
+    SshAdvisory {
+        id: "CVE-2023-51385",
+        summary: "shell metacharacters in user or host names can run commands through ProxyCommand",
+        introduced: OpenSshVersion::new(0, 0, 0),
+        fixed: OpenSshVersion::new(9, 6, 1),
+    },
+    SshAdvisory {
+        id: "CVE-2025-26465",
+        summary: "a machine-in-the-middle can impersonate servers when VerifyHostKeyDNS is enabled",
+        introduced: OpenSshVersion::new(6, 8, 1),
+        fixed: OpenSshVersion::new(9, 9, 2),
+    },
+];
+
+/// Why an installed OpenSSH client should be upgraded
+#[derive(Debug, Clone)]
+pub struct SshClientIssue {
+    /// What is wrong with the version
+    pub message: String,
+    /// Lowest version without the issue
+    pub upgrade_to: OpenSshVersion,
+}
+
+/// System requirements checker
+pub struct SystemRequirements {
+    required_commands: Vec<String>,
+    optional_commands: Vec<String>,
+    required_libraries: Vec<String>,
+    required_directories: Vec<String>,
+    min_disk_space_mb: u64,
+    min_ssh_version: Option<OpenSshVersion>,
+    cache_path: Option<PathBuf>,
+}
+
+impl Default for SystemRequirements {
+    fn default() -> Self {
+        // SSH tools are required
+        let required_commands = vec!["ssh".to_string(), "ssh-keygen".to_string()];
+
+        // Only plugin development uses git, so its absence is reported by 'doctor'
+        // but doesn't stop other commands; 'copy-key' falls back to plain ssh
+        // without ssh-copy-id, which Windows OpenSSH doesn't ship
+        let optional_commands = vec!["git".to_string()];
+
+        Self {
+            required_commands,
+            optional_commands,
+            required_libraries: Vec::new(),
+            required_directories: Vec::new(),
+            min_disk_space_mb: 10, // Minimal requirement
+            min_ssh_version: None,
+            cache_path: None,
+        }
+    }
+}
+
+impl SystemRequirements {
+    /// Create a new system requirements checker with custom requirements
+    pub fn new(
+        required_commands: Vec<String>,
+        required_libraries: Vec<String>,
+        required_directories: Vec<String>,
+        min_disk_space_mb: u64,
+    ) -> Self {
+        Self {
+            required_commands,
+            optional_commands: Vec::new(),
+            required_libraries,
+            required_directories,
+            min_disk_space_mb,
+            min_ssh_version: None,
+            cache_path: None,
+        }
+    }
+
+    /// Remember successful checks in this directory for [`REQUIREMENTS_CACHE_TTL`]
+    pub fn set_cache_dir(&mut self, cache_dir: &Path) {
+        self.cache_path = Some(cache_dir.join(REQUIREMENTS_CACHE_FILE));
+    }
+
+    /// Check if a command is available in PATH
+    fn check_command(&self, command: &str) -> Result<()> {
+        find_command(command)
+            .map(|_| ())
+            .ok_or_else(|| ShellBeError::SystemRequirement(format!(
+                "Required command '{}' not found in PATH", command
+            )))
+    }
+
+    /// Check if a library is available
+    #[cfg(unix)]
+    fn check_library(&self, library: &str) -> Result<()> {
+        // On Unix, we can use ldconfig to check for libraries
+        let status = Command::new("ldconfig")
+            .arg("-p")
+            .output();
+
+        match status {
+            Ok(output) if output.status.success() => {
+                let output_str = String::from_utf8_lossy(&output.stdout);
+                if output_str.contains(library) {
+                    Ok(())
+                } else {
+                    Err(ShellBeError::SystemRequirement(format!(
+                        "Required library '{}' not found", library
+                    )))
+                }
+            },
+            _ => Err(ShellBeError::SystemRequirement(format!(
+                "Could not check for library '{}', ldconfig failed", library
+            ))),
+        }
+    }
+
+    /// Check if a library is available
+    #[cfg(windows)]
+    fn check_library(&self, library: &str) -> Result<()> {
+        // On Windows, checking for libraries is more complex
+        // This is a simplified approach
+        let lib_paths = vec![
+            std::env::var("PATH").unwrap_or_default(),
+            std::env::var("SYSTEMROOT").unwrap_or_default() + "\\System32",
+        ];
+
+        for path in lib_paths {
+            for entry in std::path::Path::new(&path).read_dir().ok().into_iter().flatten() {
+                if let Ok(entry) = entry {
+                    if let Some(file_name) = entry.file_name().to_str() {
+                        if file_name.to_lowercase() == format!("{}.dll", library).to_lowercase() {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+
+        Err(ShellBeError::SystemRequirement(format!(
+            "Required library '{}' not found", library
+        )))
+    }
+
+    /// Check if a directory exists and is writable
+    fn check_directory(&self, directory: &str) -> Result<()> {
+        let path = std::path::Path::new(directory);
+
+        if !path.exists() {
+            return Err(ShellBeError::SystemRequirement(format!(
+                "Required directory '{}' does not exist", directory
+            )));
+        }
+
+        if !path.is_dir() {
+            return Err(ShellBeError::SystemRequirement(format!(
+                "'{}' is not a directory", directory
+            )));
+        }
+
+        // Test if directory is writable by creating a temporary file
+        let test_file = path.join(".shellbe_write_test");
+        match std::fs::File::create(&test_file) {
+            Ok(_) => {
+                // Clean up test file
+                let _ = std::fs::remove_file(&test_file);
+                Ok(())
+            },
+            Err(_) => Err(ShellBeError::SystemRequirement(format!(
+                "Directory '{}' is not writable", directory
+            ))),
+        }
+    }
+
+    /// Check available disk space
+    fn check_disk_space(&self, path: &str) -> Result<u64> {
+        let path = std::path::Path::new(path);
+
+        #[cfg(unix)]
+        {
+            use std::mem::MaybeUninit;
+
+            let path_cstr = std::ffi::CString::new(path.to_string_lossy().as_bytes())
+                .map_err(|_| ShellBeError::SystemRequirement(
+                    "Path contains invalid characters".to_string()
+                ))?;
+
+            unsafe {
+                let mut stat: MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
+                if libc::statvfs(path_cstr.as_ptr(), stat.as_mut_ptr()) == 0 {
+                    let stat = stat.assume_init();
+                    let available_kb = (stat.f_bavail as u64) * (stat.f_frsize as u64) / 1024;
+                    let available_mb = available_kb / 1024;
+                    return Ok(available_mb);
+                }
+            }
+
+            Err(ShellBeError::SystemRequirement(
+                "Could not determine available disk space".to_string()
+            ))
+        }
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStrExt;
+            use std::ffi::OsStr;
+            use std::ptr;
+            use winapi::um::fileapi::{GetDiskFreeSpaceExW, PULARGE_INTEGER};
+
+            let path: Vec<u16> = OsStr::new(path).encode_wide().chain(Some(0)).collect();
+
+            let mut free_bytes: winapi::shared::minwindef::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+            let ret = unsafe {
+                GetDiskFreeSpaceExW(
+                    path.as_ptr(),
+                    &mut free_bytes as PULARGE_INTEGER,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+
+            if ret != 0 {
+                let available_bytes = unsafe { *free_bytes.QuadPart() };
+                let available_mb = available_bytes / 1024 / 1024;
+                return Ok(available_mb);
+            }
+
+            Err(ShellBeError::SystemRequirement(
+                "Could not determine available disk space".to_string()
+            ))
+        }
+    }
+
+    /// Check all system requirements, including optional commands; never cached
+    pub fn check_all(&self) -> HashMap<String, Result<()>> {
+        let mut results = HashMap::new();
+
+        // Check commands
+        for command in self.required_commands.iter().chain(&self.optional_commands) {
+            results.insert(
+                format!("command:{}", command),
+                self.check_command(command),
+            );
+        }
+
+        // Check libraries
+        for library in &self.required_libraries {
+            results.insert(
+                format!("library:{}", library),
+                self.check_library(library),
+            );
+        }
+
+        // Check directories
+        for directory in &self.required_directories {
+            results.insert(
+                format!("directory:{}", directory),
+                self.check_directory(directory),
+            );
+        }
+
+        // Check disk space for home directory
+        if let Some(home_dir) = super::home_dir() {
+            match self.check_disk_space(home_dir.to_str().unwrap_or("")) {
+                Ok(available_mb) => {
+                    if available_mb < self.min_disk_space_mb {
+                        results.insert(
+                            "disk_space".to_string(),
+                            Err(ShellBeError::SystemRequirement(format!(
+                                "Not enough disk space: {} MB available, {} MB required",
+                                available_mb, self.min_disk_space_mb
+                            ))),
+                        );
+                    } else {
+                        results.insert("disk_space".to_string(), Ok(()));
+                    }
+                },
+                Err(e) => {
+                    results.insert("disk_space".to_string(), Err(e));
+                },
+            }
+        }
+
+        results
+    }
+
+    /// Check if all hard requirements are met
+    ///
+    /// With a cache directory set, a successful result is reused until it expires or PATH
+    /// changes; failures are always checked again so installing a missing tool takes effect.
+    pub fn all_requirements_met(&self) -> Result<()> {
+        let expected = self.cache_entry();
+        if self.cached_check().is_some_and(|cached| cached.commands == expected.commands && cached.path == expected.path) {
+            return Ok(());
+        }
+
+        let mut results = self.check_all();
+        for command in &self.optional_commands {
+            results.remove(&format!("command:{}", command));
+        }
+
+        let mut failed_checks = Vec::new();
+
+        for (requirement, result) in results {
+            if let Err(e) = result {
+                failed_checks.push(format!("{}: {}", requirement, e));
+            }
+        }
+
+        if failed_checks.is_empty() {
+            self.save_check(&expected);
+            Ok(())
+        } else {
+            Err(ShellBeError::SystemRequirement(format!(
+                "System requirements not met:\n{}",
+                failed_checks.join("\n")
+            )))
+        }
+    }
+
+    /// What a successful check right now would record
+    fn cache_entry(&self) -> RequirementsCache {
+        RequirementsCache {
+            checked_at: Utc::now(),
+            commands: self.required_commands.clone(),
+            path: std::env::var("PATH").unwrap_or_default(),
+        }
+    }
+
+    /// The last successful check, if it hasn't expired
+    fn cached_check(&self) -> Option<RequirementsCache> {
+        let content = std::fs::read(self.cache_path.as_ref()?).ok()?;
+        let cached: RequirementsCache = serde_json::from_slice(&content).ok()?;
+
+        let age = Utc::now().signed_duration_since(cached.checked_at).to_std().ok()?;
+        (age < REQUIREMENTS_CACHE_TTL).then_some(cached)
+    }
+
+    fn save_check(&self, entry: &RequirementsCache) {
+        let Some(path) = &self.cache_path else {
+            return;
+        };
+
+        let saved = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, serde_json::to_vec(entry).unwrap_or_default()));
+        if let Err(e) = saved {
+            tracing::debug!("Failed to cache the requirements check in {}: {}", path.display(), e);
+        }
+    }
+
+    /// Add a required command
+    pub fn add_required_command(&mut self, command: &str) {
+        self.required_commands.push(command.to_string());
+    }
+
+    /// Add a command only some features need; 'doctor' reports it when missing
+    pub fn add_optional_command(&mut self, command: &str) {
+        self.optional_commands.push(command.to_string());
+    }
+
+    /// Check if a command is only needed by some features
+    pub fn is_optional_command(&self, command: &str) -> bool {
+        self.optional_commands.iter().any(|optional| optional == command)
+    }
+
+    /// Add a required library
+    pub fn add_required_library(&mut self, library: &str) {
+        self.required_libraries.push(library.to_string());
+    }
+
+    /// Add a required directory
+    pub fn add_required_directory(&mut self, directory: &str) {
+        self.required_directories.push(directory.to_string());
+    }
+
+    /// Set minimum disk space requirement
+    pub fn set_min_disk_space_mb(&mut self, min_disk_space_mb: u64) {
+        self.min_disk_space_mb = min_disk_space_mb;
+    }
+
+    /// Set the oldest acceptable OpenSSH client
+    pub fn set_min_ssh_version(&mut self, version: Option<OpenSshVersion>) {
+        self.min_ssh_version = version;
+    }
+
+    /// Version of the installed OpenSSH client, from `ssh -V`
+    pub fn ssh_client_version(&self) -> Result<OpenSshVersion> {
+        let output = Command::new(command_program("ssh"))
+            .arg("-V")
+            .output()
+            .map_err(|e| ShellBeError::SystemRequirement(format!("Failed to run 'ssh -V': {}", e)))?;
+
+        // ssh prints its version to stderr
+        let banner = String::from_utf8_lossy(&output.stderr);
+        OpenSshVersion::from_banner(&banner)
+            .or_else(|| OpenSshVersion::from_banner(&String::from_utf8_lossy(&output.stdout)))
+            .ok_or_else(|| ShellBeError::SystemRequirement(format!(
+                "'ssh' is not OpenSSH: {}", banner.trim()
+            )))
+    }
+
+    /// Reasons to upgrade an OpenSSH client: the configured minimum and known advisories
+    pub fn ssh_client_issues(&self, version: &OpenSshVersion) -> Vec<SshClientIssue> {
+        let mut issues = Vec::new();
+
+        if let Some(min) = self.min_ssh_version.filter(|min| version < min) {
+            issues.push(SshClientIssue {
+                message: format!("OpenSSH {} is older than the required {}", version, min),
+                upgrade_to: min,
+            });
+        }
+
+        for advisory in SSH_ADVISORIES.iter().filter(|advisory| advisory.affects(version)) {
+            issues.push(SshClientIssue {
+                message: format!("OpenSSH {} is affected by {}: {}", version, advisory.id, advisory.summary),
+                upgrade_to: advisory.fixed,
+            });
+        }
+
+        issues
+    }
+}
+
+/// Where Windows installs its OpenSSH client, which isn't always on PATH
+fn windows_openssh_dir() -> Option<PathBuf> {
+    env::var_os("SystemRoot").map(|root| PathBuf::from(root).join("System32").join("OpenSSH"))
+}
+
+/// Extensions Windows tries for a bare command name, from `PATHEXT`
+fn path_extensions() -> Vec<String> {
+    if !cfg!(windows) {
+        return Vec::new();
+    }
+
+    env::var("PATHEXT")
+        .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Find `command` in the directories of `path`, as is or with one of `extensions`
+///
+/// This is what `which` and `where` do, without needing either to be installed.
+pub fn find_in_path(command: &str, path: &OsStr, extensions: &[String]) -> Option<PathBuf> {
+    env::split_paths(path).find_map(|dir| {
+        std::iter::once("")
+            .chain(extensions.iter().map(String::as_str))
+            .map(|ext| dir.join(format!("{}{}", command, ext)))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+/// Find a command on PATH, or on Windows in the OpenSSH directory too
+pub fn find_command(command: &str) -> Option<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default();
+    if cfg!(windows) {
+        dirs.extend(windows_openssh_dir());
+    }
+
+    let path = env::join_paths(dirs).ok()?;
+    find_in_path(command, &path, &path_extensions())
+}
+
+/// Program to run for `command`: its full path when found, so ssh works on Windows
+/// even when OpenSSH isn't on PATH
+pub fn command_program(command: &str) -> PathBuf {
+    find_command(command).unwrap_or_else(|| PathBuf::from(command))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().map_or(false, |m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn touch(path: &Path) {
+        fs::write(path, b"").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_find_in_path_tries_windows_extensions() {
+        let openssh = tempfile::tempdir().unwrap();
+        touch(&openssh.path().join("ssh.EXE"));
+        let path = env::join_paths([openssh.path()]).unwrap();
+
+        let extensions = vec![".COM".to_string(), ".EXE".to_string()];
+        assert_eq!(find_in_path("ssh", &path, &extensions), Some(openssh.path().join("ssh.EXE")));
+        assert_eq!(find_in_path("ssh", &path, &[]), None);
+    }
+
+    #[test]
+    fn test_find_in_path_uses_first_match() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        touch(&first.path().join("ssh-keygen"));
+        touch(&second.path().join("ssh-keygen"));
+        let path = env::join_paths([first.path(), second.path()]).unwrap();
+
+        assert_eq!(find_in_path("ssh-keygen", &path, &[]), Some(first.path().join("ssh-keygen")));
+        assert_eq!(find_in_path("ssh-copy-id", &path, &[]), None);
+    }
+
+    const MISSING_COMMAND: &str = "shellbe-test-missing-command";
+
+    /// Requirements that only pass when answered from the cache
+    fn unmet_requirements(cache_dir: &Path) -> SystemRequirements {
+        let mut requirements = SystemRequirements::new(vec![MISSING_COMMAND.to_string()], Vec::new(), Vec::new(), 0);
+        requirements.set_cache_dir(cache_dir);
+        requirements
+    }
+
+    fn write_cache(cache_dir: &Path, checked_at: DateTime<Utc>, path: &str) {
+        let entry = RequirementsCache {
+            checked_at,
+            commands: vec![MISSING_COMMAND.to_string()],
+            path: path.to_string(),
+        };
+        fs::write(cache_dir.join(REQUIREMENTS_CACHE_FILE), serde_json::to_vec(&entry).unwrap()).unwrap();
+    }
+
+    fn current_path() -> String {
+        env::var("PATH").unwrap_or_default()
+    }
+
+    #[test]
+    fn test_cached_check_is_reused() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        write_cache(cache_dir.path(), Utc::now(), &current_path());
+
+        assert!(unmet_requirements(cache_dir.path()).all_requirements_met().is_ok());
+    }
+
+    #[test]
+    fn test_expired_check_runs_again() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let expired = Utc::now() - chrono::Duration::from_std(REQUIREMENTS_CACHE_TTL).unwrap() - chrono::Duration::minutes(1);
+        write_cache(cache_dir.path(), expired, &current_path());
+
+        assert!(unmet_requirements(cache_dir.path()).all_requirements_met().is_err());
+    }
+
+    #[test]
+    fn test_path_change_invalidates_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        write_cache(cache_dir.path(), Utc::now(), "/shellbe-test/old-path");
+
+        assert!(unmet_requirements(cache_dir.path()).all_requirements_met().is_err());
+    }
+
+    #[test]
+    fn test_failed_check_is_not_cached() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let requirements = unmet_requirements(cache_dir.path());
+
+        assert!(requirements.all_requirements_met().is_err());
+        assert!(!cache_dir.path().join(REQUIREMENTS_CACHE_FILE).exists());
+        assert!(requirements.all_requirements_met().is_err());
+    }
+}
\ No newline at end of file
@@ -1,14 +0,0 @@
-pub mod profile_service;
-pub mod connection_service;
-pub mod alias_service;
-pub mod plugin_service;
-pub mod ssh_config_service;
-pub mod update_service;
-
-// Re-export application services
-pub use profile_service::ProfileService;
-pub use connection_service::ConnectionService;
-pub use alias_service::AliasService;
-pub use plugin_service::{PluginService, PluginError};
-pub use ssh_config_service::SshConfigService;
-pub use update_service::{UpdateService, UpdateError};
\ No newline at end of file
@@ -1,13 +0,0 @@
-pub mod repositories;
-pub mod ssh;
-
-pub use repositories::{
-    FileProfileRepository,
-    FileAliasRepository,
-    FileHistoryRepository,
-    FilePluginRepository,
-    PluginRepository,
-    FileSshConfigRepository,
-};
-
-pub use ssh::ThrushSshService;
\ No newline at end of file
@@ -1,3 +0,0 @@
-pub mod thrush_ssh_service;
-
-pub use thrush_ssh_service::ThrushSshService;
\ No newline at end of file
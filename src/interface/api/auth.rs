@@ -0,0 +1,79 @@
+use crate::domain::DomainError;
+use crate::utils::{register_secret, write_private};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Environment variable with a fixed API token, e.g. for containers
+pub const API_TOKEN_ENV: &str = "SHELLBE_API_TOKEN";
+
+/// File in the cache directory holding the API token, readable only by the owner
+pub const API_TOKEN_FILE: &str = "api-token";
+
+/// Shortest token accepted from the environment
+const MIN_TOKEN_LEN: usize = 16;
+
+/// Bearer token every API request has to present
+pub struct ApiToken {
+    token: Zeroizing<String>,
+    /// File the token was read from or written to; unset when it came from the environment
+    path: Option<PathBuf>,
+}
+
+impl ApiToken {
+    /// The token from `SHELLBE_API_TOKEN`, else the one in the cache directory, else a new one
+    ///
+    /// New tokens are saved so GUIs and editors can read them from the same file across restarts.
+    pub fn load_or_create(cache_dir: &Path) -> Result<Self, DomainError> {
+        if let Ok(token) = std::env::var(API_TOKEN_ENV) {
+            if token.len() < MIN_TOKEN_LEN {
+                return Err(DomainError::ConfigError(format!(
+                    "{} must be at least {} characters long",
+                    API_TOKEN_ENV, MIN_TOKEN_LEN,
+                )));
+            }
+
+            register_secret(&token);
+            return Ok(Self {
+                token: Zeroizing::new(token),
+                path: None,
+            });
+        }
+
+        let path = cache_dir.join(API_TOKEN_FILE);
+        let token = match fs::read_to_string(&path) {
+            Ok(token) if token.trim().len() >= MIN_TOKEN_LEN => Zeroizing::new(token.trim().to_string()),
+            _ => {
+                let token = Zeroizing::new(format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()));
+                fs::create_dir_all(cache_dir).map_err(DomainError::IoError)?;
+                write_private(&path, token.as_bytes()).map_err(DomainError::IoError)?;
+                token
+            },
+        };
+
+        register_secret(&token);
+        Ok(Self {
+            token,
+            path: Some(path),
+        })
+    }
+
+    /// Where clients can read the token, if it is kept in a file
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The token itself, for clients of a daemon on this machine
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+
+    /// Check a presented token without leaking how much of it matched through timing
+    pub fn matches(&self, presented: &str) -> bool {
+        let expected = self.token.as_bytes();
+        let presented = presented.as_bytes();
+
+        expected.len() == presented.len()
+            && expected.iter().zip(presented).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
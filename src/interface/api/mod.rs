@@ -0,0 +1,5 @@
+pub mod auth;
+pub mod server;
+
+pub use auth::{ApiToken, API_TOKEN_ENV, API_TOKEN_FILE};
+pub use server::{router, serve, ApiState};
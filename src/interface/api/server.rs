@@ -0,0 +1,169 @@
+use super::auth::ApiToken;
+use crate::application::{ConnectionService, ProfileService};
+use crate::domain::{DomainError, HistoryEntry, Profile};
+use crate::utils::redact_text;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Entries returned by `GET /v1/history` without a `limit`
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Services the API exposes
+#[derive(Clone)]
+pub struct ApiState {
+    profiles: Arc<ProfileService>,
+    connections: Arc<ConnectionService>,
+    token: Arc<ApiToken>,
+}
+
+impl ApiState {
+    /// Serve these services to clients presenting the token
+    pub fn new(profiles: Arc<ProfileService>, connections: Arc<ConnectionService>, token: Arc<ApiToken>) -> Self {
+        Self {
+            profiles,
+            connections,
+            token,
+        }
+    }
+}
+
+/// Routes of the JSON API; everything but `/v1/health` needs `Authorization: Bearer <token>`
+///
+/// Interactive sessions are left out on purpose: the API never opens a terminal.
+pub fn router(state: ApiState) -> Router {
+    let authenticated = Router::new()
+        .route("/v1/profiles", get(list_profiles).post(add_profile))
+        .route("/v1/profiles/:name", get(get_profile))
+        .route("/v1/profiles/:name/test", post(test_profile))
+        .route("/v1/history", get(history))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authenticate));
+
+    Router::new()
+        .route("/v1/health", get(health))
+        .merge(authenticated)
+        .with_state(state)
+}
+
+/// Serve the API until Ctrl-C
+pub async fn serve(listener: tokio::net::TcpListener, state: ApiState) -> std::io::Result<()> {
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+/// Error response: `{ "error": "..." }` with a matching status code
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl From<DomainError> for ApiError {
+    fn from(error: DomainError) -> Self {
+        let status = match &error {
+            DomainError::ProfileNotFound(_) | DomainError::AliasNotFound(_) => StatusCode::NOT_FOUND,
+            DomainError::ProfileAlreadyExists(_) | DomainError::AliasAlreadyExists(_) => StatusCode::CONFLICT,
+            DomainError::PolicyViolation(_) => StatusCode::FORBIDDEN,
+            DomainError::ConfigError(_) => StatusCode::BAD_REQUEST,
+            DomainError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            DomainError::SshError(_) | DomainError::IoError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Self {
+            status,
+            message: error.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": redact_text(&self.message) });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+async fn authenticate(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let presented = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match presented {
+        Some(token) if state.token.matches(token) => next.run(request).await,
+        _ => ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing or wrong bearer token".to_string(),
+        }.into_response(),
+    }
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok", "version": crate::VERSION }))
+}
+
+async fn list_profiles(State(state): State<ApiState>) -> Result<Json<Vec<Profile>>, ApiError> {
+    Ok(Json(state.profiles.list_profiles().await?))
+}
+
+async fn get_profile(State(state): State<ApiState>, Path(name): Path<String>) -> Result<Json<Profile>, ApiError> {
+    Ok(Json(state.profiles.get_profile(&name).await?))
+}
+
+async fn add_profile(State(state): State<ApiState>, Json(mut profile): Json<Profile>) -> Result<(StatusCode, Json<Profile>), ApiError> {
+    if profile.name.trim().is_empty() || profile.hostname.trim().is_empty() {
+        return Err(DomainError::ConfigError("Profiles need a name and a hostname".to_string()).into());
+    }
+
+    let now = chrono::Utc::now();
+    profile.created_at.get_or_insert(now);
+    profile.updated_at.get_or_insert(now);
+    profile.last_used = None;
+
+    let name = profile.name.clone();
+    state.profiles.add_profile(profile).await?;
+
+    // Return the profile as saved, with the options the policy added
+    Ok((StatusCode::CREATED, Json(state.profiles.get_profile(&name).await?)))
+}
+
+async fn test_profile(State(state): State<ApiState>, Path(name): Path<String>) -> Result<Json<serde_json::Value>, ApiError> {
+    let address = state.connections.test_connection(&name).await?;
+    Ok(Json(serde_json::json!({
+        "name": name,
+        "success": address.is_some(),
+        "address": address.map(|address| address.to_string()),
+    })))
+}
+
+/// Query of `GET /v1/history`
+#[derive(Deserialize)]
+struct HistoryQuery {
+    /// Most recent entries to return
+    limit: Option<usize>,
+    /// Only entries of this profile
+    profile: Option<String>,
+}
+
+async fn history(State(state): State<ApiState>, Query(query): Query<HistoryQuery>) -> Result<Json<Vec<HistoryEntry>>, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+
+    let entries = match &query.profile {
+        Some(profile) => {
+            let mut entries = state.connections.get_profile_history(profile).await?;
+            let start = entries.len().saturating_sub(limit);
+            entries.drain(..start);
+            entries
+        },
+        None => state.connections.get_recent_history(limit).await?,
+    };
+
+    Ok(Json(entries))
+}
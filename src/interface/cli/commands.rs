@@ -1,28 +1,205 @@
-use clap::{Parser, Subcommand, Args};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use clap::{CommandFactory, Parser, Subcommand, Args, ValueEnum};
+use clap_complete::Shell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 
+use crate::application::DEFAULT_CONCURRENCY;
+use super::completions::CompletionKind;
+
 /// ShellBe - A comprehensive SSH management tool with plugin support
 #[derive(Parser)]
 #[command(name = "shellbe")]
 #[command(author = "Arash")]
 #[command(version = "2.0.0")]
 #[command(about = "SSH management tool with plugin support", long_about = None)]
+#[command(infer_subcommands = true)]
 pub struct Cli {
+    /// Output format for listing commands (default from settings)
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Directory for ShellBe configuration and data (default: ~/.shellbe)
+    #[arg(long, global = true, env = "SHELLBE_CONFIG_DIR", value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Never prompt: answer yes to confirmations and use defaults elsewhere (also: SHELLBE_ASSUME_YES=1, CI=true)
+    #[arg(long, short = 'y', global = true, visible_alias = "non-interactive")]
+    pub yes: bool,
+
+    /// Only print results and errors
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Show exact timestamps instead of relative times like "2 hours ago"
+    #[arg(long, global = true)]
+    pub absolute: bool,
+
+    /// Refuse hosts whose key is unknown or has changed (StrictHostKeyChecking=yes)
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Proxy for network calls like updates and plugin downloads (default: network.proxy in settings, then HTTPS_PROXY)
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Print extra details (repeat for more tracing output)
+    #[arg(long, short, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
 
+/// Global flags that take a separate value, e.g. `--output json`
+const GLOBAL_VALUE_FLAGS: &[&str] = &["--output", "--config-dir", "--proxy"];
+
+impl Cli {
+    /// Find `--config-dir` in raw arguments, before they are parsed
+    pub fn config_dir_arg(args: &[OsString]) -> Option<PathBuf> {
+        let mut iter = args.iter().skip(1);
+
+        while let Some(arg) = iter.next() {
+            let arg = arg.to_string_lossy();
+            if arg == "--" {
+                break;
+            }
+            if arg == "--config-dir" {
+                return iter.next().map(PathBuf::from);
+            }
+            if let Some(dir) = arg.strip_prefix("--config-dir=") {
+                return Some(PathBuf::from(dir));
+            }
+        }
+
+        None
+    }
+
+    /// Name of the command in parsed arguments, e.g. `connect`, without its arguments
+    pub fn command_name(args: &[OsString]) -> Option<String> {
+        let matches = Cli::command().try_get_matches_from(args).ok()?;
+        matches.subcommand_name().map(str::to_string)
+    }
+
+    /// Replace a user-defined command alias with its expansion
+    ///
+    /// Only the command word is expanded, only once, and built-in commands always win.
+    pub fn expand_aliases(args: Vec<OsString>, aliases: &HashMap<String, String>) -> Vec<OsString> {
+        if aliases.is_empty() {
+            return args;
+        }
+
+        // Find the command word, skipping global flags and their values
+        let mut idx = 1;
+        while idx < args.len() {
+            let arg = args[idx].to_string_lossy();
+            if !arg.starts_with('-') {
+                break;
+            }
+            if arg == "--" {
+                return args;
+            }
+            idx += if GLOBAL_VALUE_FLAGS.contains(&arg.as_ref()) { 2 } else { 1 };
+        }
+
+        let Some(word) = args.get(idx).and_then(|a| a.to_str()) else {
+            return args;
+        };
+
+        let command = Cli::command();
+        if word == "help" || command.find_subcommand(word).is_some() {
+            return args;
+        }
+
+        let Some(expansion) = aliases.get(word) else {
+            return args;
+        };
+
+        let mut expanded = args[..idx].to_vec();
+        expanded.extend(expansion.split_whitespace().map(OsString::from));
+        expanded.extend(args[idx + 1..].iter().cloned());
+        expanded
+    }
+}
+
+/// Output format for listing commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// Machine-readable JSON
+    Json,
+}
+
 /// Supported commands
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Set up ShellBe: settings, SSH config import, a default key and shell completions
+    Init,
+
     /// Add a new SSH connection profile
     Add(AddArgs),
 
     /// List all configured SSH profiles
-    List,
+    List {
+        /// Columns to show, comma separated (name, host, user, port, identity, tags, last-used, created)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+
+        /// Only profiles whose hostname matches this glob, e.g. '*.example.com'
+        #[arg(long, value_name = "GLOB")]
+        host: Option<String>,
+
+        /// Only profiles whose username matches this glob
+        #[arg(long, value_name = "GLOB")]
+        user: Option<String>,
+
+        /// Only profiles using this port
+        #[arg(long)]
+        port: Option<u16>,
 
-    /// Connect to a saved profile
+        /// Only profiles with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// Connect to a saved profile (or a number from 'recent')
     Connect {
+        /// Profile name, alias, user@host or ssh:// URI (pick interactively if omitted)
+        name: Option<String>,
+    },
+
+    /// Open an ssh:// or sftp:// URI, e.g. from a browser or the OS URI handler
+    Open {
+        /// URI such as ssh://user@host:2222 or sftp://user@host/var/log
+        uri: String,
+    },
+
+    /// Print an ssh:// URI for a profile, to share or open elsewhere
+    Uri {
+        /// Profile name or alias
+        name: String,
+
+        /// Print an sftp:// URI instead
+        #[arg(long)]
+        sftp: bool,
+    },
+
+    /// Show every detail of a profile
+    Show {
+        /// Profile name or alias
+        name: String,
+    },
+
+    /// Print what a connection would use, once aliases and the organization policy are applied
+    Env {
         /// Profile name or alias
         name: String,
     },
@@ -31,34 +208,48 @@ pub enum Commands {
     #[command(name = "copy-id")]
     CopyId {
         /// Profile name or alias
-        name: String,
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
 
         /// Path to the identity file (public key)
         #[arg(long, short)]
         identity: Option<PathBuf>,
+
+        /// Copy the key to all profiles
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+
+        /// How many profiles to copy to at once with --all (copying may ask for passwords)
+        #[arg(long, short, default_value_t = 1, requires = "all")]
+        jobs: usize,
     },
 
     /// Generate a new SSH key pair
     #[command(name = "generate-key")]
     GenerateKey {
-        /// Key name (default: id_rsa)
-        #[arg(default_value = "id_rsa")]
-        name: String,
+        /// Key name (default: id_<type>)
+        name: Option<String>,
 
         /// Key comment (e.g., email)
         #[arg(long, short)]
         comment: Option<String>,
 
-        /// Key type (ed25519 or rsa)
-        #[arg(long, short, default_value = "ed25519")]
-        type_: String,
+        /// Key type (ed25519 or rsa; default from settings)
+        #[arg(long = "type", short, value_parser = ["ed25519", "rsa"])]
+        type_: Option<String>,
     },
 
     /// Create an alias for a connection
     Alias(AliasArgs),
 
     /// List all connection aliases
-    Aliases,
+    Aliases(AliasesArgs),
+
+    /// Remove a connection alias
+    Unalias {
+        /// Alias name
+        name: String,
+    },
 
     /// Remove a profile
     Remove {
@@ -70,12 +261,46 @@ pub enum Commands {
     Edit {
         /// Profile name
         name: String,
+
+        /// Edit the profile as TOML in $VISUAL/$EDITOR instead of answering prompts
+        #[arg(long, short)]
+        editor: bool,
     },
 
     /// Test connection to a profile
     Test {
+        /// Profile name or alias
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+
+        /// Test all profiles
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+
+        /// How many profiles to test at once with --all
+        #[arg(long, short, default_value_t = DEFAULT_CONCURRENCY, requires = "all")]
+        jobs: usize,
+    },
+
+    /// Print the current TOTP code of a profile, or store its secret in the vault
+    Totp {
         /// Profile name or alias
         name: String,
+
+        /// Store the profile's TOTP secret (base32 or otpauth:// URI), read from a prompt or stdin
+        #[arg(long)]
+        set: bool,
+
+        /// Forget the profile's TOTP secret
+        #[arg(long, conflicts_with = "set")]
+        remove: bool,
+    },
+
+    /// Show the most recently used profiles, numbered for 'connect <#>'
+    Recent {
+        /// Number of profiles to show
+        #[arg(default_value = "10")]
+        limit: usize,
     },
 
     /// Show connection history
@@ -83,22 +308,63 @@ pub enum Commands {
         /// Number of entries to show
         #[arg(default_value = "10")]
         limit: usize,
+
+        /// Aggregate entries by profile or day instead of listing them
+        #[arg(long, value_enum)]
+        group_by: Option<HistoryGroupBy>,
+
+        /// Rewrite the history file as readable JSON or compact binary, then exit
+        #[arg(long, value_name = "FORMAT", value_parser = ["json", "binary"], conflicts_with = "group_by")]
+        convert: Option<String>,
     },
 
-    /// Export profiles to SSH config
+    /// Export profiles to SSH config, or to another SSH client's import format
     Export {
         /// Replace existing SSH config
         #[arg(long, short)]
         replace: bool,
+
+        /// Also export aliases as additional Host names
+        #[arg(long)]
+        with_aliases: bool,
+
+        /// Format to export to
+        #[arg(long, value_enum, default_value_t = ExportFormat::SshConfig)]
+        format: ExportFormat,
+
+        /// File to write Termius or PuTTY exports to (default: stdout)
+        #[arg(long, short, value_name = "FILE")]
+        file: Option<PathBuf>,
     },
 
-    /// Import profiles from SSH config
+    /// Import profiles from SSH config, or a shared profile set from a URL
     Import {
         /// Replace existing profiles
         #[arg(long, short)]
         replace: bool,
+
+        /// Also import additional Host names as aliases
+        #[arg(long, conflicts_with = "url")]
+        with_aliases: bool,
+
+        /// Fetch profiles from a JSON file at this URL; run again to pick up changes
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Require a signature at <URL>.sig from a key in bundles.trusted_keys
+        #[arg(long, requires = "url")]
+        verify: bool,
     },
 
+    /// Find SSH hosts in a directory or on the local network and propose profiles for them
+    Discover(DiscoverArgs),
+
+    /// Share profiles with a team as signed bundles
+    Bundle(BundleArgs),
+
+    /// Share profiles, aliases and history between machines through an encrypted snapshot
+    Sync(SyncArgs),
+
     /// Plugin management commands
     Plugin(PluginArgs),
 
@@ -107,6 +373,10 @@ pub enum Commands {
         /// Check for updates without installing
         #[arg(long, short)]
         check: bool,
+
+        /// Releases to update to, instead of [updates] channel; stable also leaves a beta or nightly build
+        #[arg(long, value_parser = ["stable", "beta", "nightly"])]
+        channel: Option<String>,
     },
 
     /// Uninstall ShellBe
@@ -114,10 +384,70 @@ pub enum Commands {
         /// Keep configuration files
         #[arg(long, short)]
         keep_config: bool,
+    },
 
-        /// Confirm uninstallation without prompt
-        #[arg(long, short)]
-        yes: bool,
+    /// Diagnose the ShellBe installation and suggest fixes
+    Doctor {
+        /// Restrict permissions of private files, directories and identity files before checking
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Encrypt profiles, aliases and history with a passphrase or keyfile
+    Vault(VaultArgs),
+
+    /// Serve profiles, connection tests and history over an authenticated HTTP JSON API
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: SocketAddr,
+
+        /// Also serve the gRPC API, which other shellbe runs can use instead of the files
+        #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = "127.0.0.1:7879")]
+        grpc: Option<SocketAddr>,
+    },
+
+    /// Follow the events of a running daemon
+    Events(EventsArgs),
+
+    /// Show or change anonymous usage telemetry, which is off unless enabled
+    Telemetry(TelemetryArgs),
+
+    /// List or clean up the ControlMaster sockets of multiplexed connections ([ssh] multiplex)
+    Sockets(SocketsArgs),
+
+    /// List the connections shellbe has open, or end one
+    Sessions(SessionsArgs),
+
+    /// Generate shell completion scripts
+    Completions {
+        /// Shell to generate completions for (detected from $SHELL with --install)
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<Shell>,
+
+        /// Write the script to the shell's completion directory and load it from the rc file
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Generate man pages and a markdown reference for packaging
+    #[command(name = "generate-docs", hide = true)]
+    GenerateDocs {
+        /// Directory to write the documentation to
+        #[arg(long, default_value = "docs")]
+        out_dir: PathBuf,
+
+        /// Which documentation to generate
+        #[arg(long, value_enum, default_value_t = DocsFormat::All)]
+        format: DocsFormat,
+    },
+
+    /// Print names for dynamic shell completion
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Kind of names to print
+        #[arg(value_enum)]
+        kind: CompletionKind,
     },
 }
 
@@ -132,39 +462,508 @@ pub struct AddArgs {
     #[arg(long, short)]
     pub host: Option<String>,
 
-    /// Username
+    /// Username; for --docker, the user to run as in the container
     #[arg(long, short)]
     pub user: Option<String>,
 
-    /// SSH port
-    #[arg(long, short, default_value = "22")]
-    pub port: u16,
+    /// Enter this Docker container with `docker exec` instead of connecting over SSH
+    #[arg(long, value_name = "CONTAINER", conflicts_with_all = ["host", "port", "identity"])]
+    pub docker: Option<String>,
+
+    /// SSH profile of the host running the container's daemon; the local daemon when not given
+    #[arg(long, value_name = "PROFILE", requires = "docker")]
+    pub docker_host: Option<String>,
+
+    /// Enter this Kubernetes pod with `kubectl exec` instead of connecting over SSH
+    #[arg(long, value_name = "POD", group = "kubernetes", conflicts_with_all = ["host", "port", "identity", "docker"])]
+    pub pod: Option<String>,
+
+    /// Enter the first running pod matching this label selector, e.g. app=web
+    #[arg(long, value_name = "SELECTOR", group = "kubernetes", conflicts_with_all = ["host", "port", "identity", "docker"])]
+    pub selector: Option<String>,
+
+    /// kubeconfig context of the pod; the current context when not given
+    #[arg(long, value_name = "CONTEXT", requires = "kubernetes")]
+    pub kube_context: Option<String>,
+
+    /// Namespace of the pod; the context's namespace when not given
+    #[arg(long, value_name = "NAMESPACE", requires = "kubernetes")]
+    pub namespace: Option<String>,
+
+    /// Container in the pod; the pod's default container when not given
+    #[arg(long, value_name = "CONTAINER", requires = "kubernetes")]
+    pub container: Option<String>,
+
+    /// SSH port (default from settings)
+    #[arg(long, short)]
+    pub port: Option<u16>,
 
     /// Path to identity file
     #[arg(long, short)]
     pub identity: Option<PathBuf>,
 
+    /// Secret manager reference for the identity file passphrase (op://... or bw://...)
+    #[arg(long, value_name = "REF")]
+    pub identity_passphrase: Option<String>,
+
+    /// Secret manager reference for the login password (op://... or bw://...)
+    #[arg(long, value_name = "REF")]
+    pub password: Option<String>,
+
+    /// Role of Vault's SSH secrets engine to get a certificate or one-time password from
+    #[arg(long, value_name = "ROLE")]
+    pub vault_role: Option<String>,
+
+    /// Connect to this IP address instead of what the host resolves to, still checking the host's key
+    #[arg(long, value_name = "IP")]
+    pub resolve_to: Option<IpAddr>,
+
+    /// Run ssh with -q, hiding the server's banner and ssh's warnings
+    #[arg(long)]
+    pub quiet_ssh: bool,
+
+    /// Message to print before connecting, e.g. "PRODUCTION — change ticket required"
+    #[arg(long, value_name = "TEXT")]
+    pub banner: Option<String>,
+
     /// Additional SSH options (key=value pairs)
     #[arg(long, short)]
     pub options: Vec<String>,
 
-    /// Non-interactive mode
-    #[arg(long, short)]
-    pub non_interactive: bool,
+    /// Tags for grouping profiles (repeatable)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+}
+
+/// Formats for the 'export' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// ~/.ssh/config Host entries
+    SshConfig,
+    /// CSV for Termius' host import
+    Termius,
+    /// PuTTY saved sessions as a .reg file
+    Putty,
+}
+
+/// Documentation formats for the 'generate-docs' command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DocsFormat {
+    /// Man pages (section 1)
+    Man,
+    /// A single markdown reference
+    Markdown,
+    /// Both
+    All,
+}
+
+/// Grouping modes for the 'history' command
+#[derive(Clone, Copy, ValueEnum)]
+pub enum HistoryGroupBy {
+    /// One row per profile
+    Profile,
+    /// One row per day
+    Day,
 }
 
 /// Arguments for the 'alias' command
 #[derive(Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct AliasArgs {
+    #[command(subcommand)]
+    pub command: Option<AliasCommands>,
+
     /// Alias name
-    pub name: String,
+    #[arg(required = true)]
+    pub name: Option<String>,
 
     /// Target profile name
-    pub profile: String,
+    #[arg(required = true)]
+    pub profile: Option<String>,
 
     /// Create shell alias in rc file
     #[arg(long, short)]
     pub shell_alias: bool,
+
+    /// Remote command to run when connecting through the alias
+    #[arg(long)]
+    pub exec: Option<String>,
+
+    /// Extra SSH arguments applied when connecting through the alias (after --)
+    #[arg(last = true)]
+    pub ssh_args: Vec<String>,
+}
+
+/// Alias subcommands
+#[derive(Subcommand)]
+pub enum AliasCommands {
+    /// Rename an existing alias
+    Rename {
+        /// Current alias name
+        old: String,
+
+        /// New alias name
+        new: String,
+    },
+}
+
+/// Arguments for the 'vault' command
+#[derive(Args)]
+pub struct VaultArgs {
+    #[command(subcommand)]
+    pub command: VaultCommands,
+}
+
+/// Vault subcommands
+#[derive(Subcommand)]
+pub enum VaultCommands {
+    /// Set up the vault and encrypt the existing data files
+    Init {
+        /// Derive the key from this file instead of a passphrase
+        #[arg(long, value_name = "PATH")]
+        keyfile: Option<PathBuf>,
+    },
+
+    /// Enter the passphrase once and keep the vault open until 'vault lock'
+    Unlock,
+
+    /// Forget the cached key; the passphrase is needed again
+    Lock,
+
+    /// Re-encrypt the data files under a new passphrase or keyfile
+    #[command(name = "change-passphrase")]
+    ChangePassphrase {
+        /// Switch to this keyfile instead of a new passphrase
+        #[arg(long, value_name = "PATH")]
+        keyfile: Option<PathBuf>,
+    },
+
+    /// Show whether the vault is set up and unlocked
+    Status,
+}
+
+/// Arguments for the 'bundle' command
+#[derive(Args)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommands,
+}
+
+/// Bundle subcommands
+#[derive(Subcommand)]
+pub enum BundleCommands {
+    /// Write profiles to a bundle signed with an ed25519 private key
+    Create {
+        /// Profiles to include (default: all)
+        names: Vec<String>,
+
+        /// Only include profiles with this tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Private key to sign with (PEM, e.g. from 'openssl genpkey -algorithm ed25519')
+        #[arg(long, value_name = "KEY")]
+        sign: PathBuf,
+
+        /// File to write the bundle to
+        #[arg(long, short, default_value = "profiles.sbp")]
+        out: PathBuf,
+    },
+
+    /// Verify a bundle against the trusted keys and add its profiles
+    Import {
+        /// Bundle file
+        path: PathBuf,
+
+        /// Replace existing profiles with the same name
+        #[arg(long, short)]
+        replace: bool,
+    },
+
+    /// Write a detached signature to <FILE>.sig, e.g. for 'import --url --verify'
+    Sign {
+        /// File to sign
+        path: PathBuf,
+
+        /// Private key to sign with (PEM)
+        #[arg(long, value_name = "KEY")]
+        sign: PathBuf,
+    },
+}
+
+/// Arguments for the 'discover' command
+#[derive(Args)]
+pub struct DiscoverArgs {
+    #[command(subcommand)]
+    pub command: DiscoverCommands,
+
+    /// Username for the proposed profiles (default: current user)
+    #[arg(long, short, global = true)]
+    pub user: Option<String>,
+
+    /// Tag added to the proposed profiles (repeatable)
+    #[arg(long = "tag", value_name = "TAG", global = true)]
+    pub tags: Vec<String>,
+
+    /// Only update the addresses of profiles discovered before; don't propose new ones
+    #[arg(long, global = true)]
+    pub refresh: bool,
+}
+
+/// Discover subcommands
+#[derive(Subcommand)]
+pub enum DiscoverCommands {
+    /// Search an LDAP directory, such as Active Directory, for computers
+    Ldap {
+        /// Directory server, e.g. ldaps://dc1.example.com
+        #[arg(long)]
+        url: String,
+
+        /// Search base, e.g. OU=Servers,DC=example,DC=com
+        #[arg(long)]
+        base: String,
+
+        /// LDAP filter selecting the hosts
+        #[arg(long, default_value = "(objectClass=computer)")]
+        filter: String,
+
+        /// Attribute holding the hostname; `cn` is used when it's missing
+        #[arg(long, default_value = "dNSHostName")]
+        attribute: String,
+
+        /// DN to bind as; the password is read from SHELLBE_LDAP_PASSWORD or asked for
+        #[arg(long, value_name = "DN")]
+        bind_dn: Option<String>,
+    },
+
+    /// Listen for SSH servers advertising _ssh._tcp over mDNS/DNS-SD
+    Mdns {
+        /// Seconds to listen for answers
+        #[arg(long, default_value_t = 3)]
+        timeout: u64,
+    },
+
+    /// List running EC2 instances with the AWS CLI
+    Aws {
+        /// Region to search (default: the CLI's)
+        #[arg(long)]
+        region: Option<String>,
+
+        /// Named AWS CLI profile to use
+        #[arg(long = "aws-profile", value_name = "PROFILE")]
+        aws_profile: Option<String>,
+
+        /// Use private IP addresses instead of public ones
+        #[arg(long)]
+        private: bool,
+    },
+
+    /// List running Compute Engine instances with the gcloud CLI
+    Gcp {
+        /// Project to search (default: the CLI's)
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Use internal IP addresses instead of external ones
+        #[arg(long)]
+        private: bool,
+    },
+
+    /// List running virtual machines with the Azure CLI
+    Azure {
+        /// Subscription to search (default: the CLI's)
+        #[arg(long)]
+        subscription: Option<String>,
+
+        /// Only VMs in this resource group
+        #[arg(long, short = 'g')]
+        resource_group: Option<String>,
+
+        /// Use private IP addresses instead of public ones
+        #[arg(long)]
+        private: bool,
+    },
+}
+
+/// Arguments for the 'sync' command
+#[derive(Args)]
+pub struct SyncArgs {
+    #[command(subcommand)]
+    pub command: SyncCommands,
+}
+
+/// Sync subcommands
+#[derive(Subcommand)]
+pub enum SyncCommands {
+    /// Encrypt the local data and upload it, replacing the snapshot
+    Push,
+
+    /// Download the snapshot and merge it into the local data
+    Pull,
+
+    /// Show where the snapshot is kept and when it was last pushed
+    Status,
+}
+
+/// Arguments for the 'events' command
+#[derive(Args)]
+pub struct EventsArgs {
+    #[command(subcommand)]
+    pub command: EventsCommands,
+}
+
+/// Arguments for the 'telemetry' command
+#[derive(Args)]
+pub struct TelemetryArgs {
+    #[command(subcommand)]
+    pub command: TelemetryCommands,
+}
+
+/// Telemetry subcommands
+#[derive(Subcommand)]
+pub enum TelemetryCommands {
+    /// Show whether telemetry is on, where it goes and the counts not sent yet
+    Status,
+
+    /// Send anonymous counts of commands and error categories once a day
+    Enable,
+
+    /// Stop counting and drop the counts not sent yet
+    Disable,
+}
+
+/// Arguments for the 'sockets' command
+#[derive(Args)]
+pub struct SocketsArgs {
+    #[command(subcommand)]
+    pub command: SocketsCommands,
+}
+
+/// Sockets subcommands
+#[derive(Subcommand)]
+pub enum SocketsCommands {
+    /// Show every socket and whether its master connection is still alive
+    List,
+
+    /// Remove sockets whose master connection is gone
+    Clean,
+}
+
+/// Arguments for the 'sessions' command
+#[derive(Args)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    pub command: Option<SessionsCommands>,
+}
+
+/// Sessions subcommands
+#[derive(Subcommand)]
+pub enum SessionsCommands {
+    /// Show every open session with its profile, start time and PID (the default)
+    List,
+
+    /// End a session by terminating its ssh process
+    Kill {
+        /// Session ID from 'sessions list'
+        id: String,
+    },
+}
+
+/// Events subcommands
+#[derive(Subcommand)]
+pub enum EventsCommands {
+    /// Print events as the daemon publishes them, until Ctrl-C
+    Tail {
+        /// Line format: readable text, or one JSON event per line
+        #[arg(long, value_enum, default_value_t = EventFormat::Text)]
+        format: EventFormat,
+    },
+
+    /// Show events recorded in the journal ([events] journal = true), most recent last
+    Log {
+        /// Number of events to show
+        #[arg(long, short = 'n', default_value = "20")]
+        limit: usize,
+
+        /// Only events about this profile or plugin
+        #[arg(long)]
+        subject: Option<String>,
+
+        /// Only events of this type, e.g. profile_updated
+        #[arg(long = "type", value_name = "TYPE")]
+        kind: Option<String>,
+
+        /// Line format: readable text, or one JSON event per line
+        #[arg(long, value_enum, default_value_t = EventFormat::Text)]
+        format: EventFormat,
+    },
+
+    /// Have the daemon publish journaled events again, so new integrations can catch up
+    Replay {
+        /// Oldest event to replay: a time (2024-05-01T12:00:00Z), a date (2024-05-01) or an age (30m, 12h, 7d)
+        #[arg(long, value_parser = parse_since)]
+        since: DateTime<Utc>,
+
+        /// Only events about this profile or plugin
+        #[arg(long)]
+        subject: Option<String>,
+
+        /// Only events of this type, e.g. profile_updated
+        #[arg(long = "type", value_name = "TYPE")]
+        kind: Option<String>,
+    },
+}
+
+/// Parse a `--since` time: RFC 3339, a date (midnight UTC), or an age like `12h`
+fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Some(midnight) = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().and_then(|date| date.and_hms_opt(0, 0, 0)) {
+        return Ok(Utc.from_utc_datetime(&midnight));
+    }
+
+    let invalid = || format!("expected a time, a date or an age like 30m, 12h, 7d, not '{}'", value);
+    let split = value.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let count: i64 = value[..split].parse().map_err(|_| invalid())?;
+    let age = match &value[split..] {
+        "s" => chrono::Duration::seconds(count),
+        "m" => chrono::Duration::minutes(count),
+        "h" => chrono::Duration::hours(count),
+        "d" => chrono::Duration::days(count),
+        "w" => chrono::Duration::weeks(count),
+        _ => return Err(invalid()),
+    };
+
+    Ok(Utc::now() - age)
+}
+
+/// Line formats for 'events tail' and 'events log'
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EventFormat {
+    /// Time, type and subject
+    Text,
+    /// The versioned JSON envelope, see schemas/event.v1.json
+    Json,
+}
+
+/// Arguments for the 'aliases' command
+#[derive(Args)]
+pub struct AliasesArgs {
+    #[command(subcommand)]
+    pub command: Option<AliasesCommands>,
+}
+
+/// Aliases subcommands
+#[derive(Subcommand)]
+pub enum AliasesCommands {
+    /// Reconcile shell rc aliases created by ShellBe with the current aliases
+    #[command(name = "sync-shell")]
+    SyncShell {
+        /// Remove every shell alias created by ShellBe instead of syncing
+        #[arg(long)]
+        remove_shell: bool,
+    },
 }
 
 /// Arguments for the 'plugin' command
@@ -192,7 +991,16 @@ pub enum PluginCommands {
     /// Update an installed plugin
     Update {
         /// Plugin name
-        name: String,
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
+
+        /// Update all installed plugins
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+
+        /// How many plugins to update at once with --all
+        #[arg(long, short, default_value_t = DEFAULT_CONCURRENCY, requires = "all")]
+        jobs: usize,
     },
 
     /// Remove an installed plugin
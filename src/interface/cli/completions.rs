@@ -0,0 +1,216 @@
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::Shell;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::commands::Cli;
+
+/// Commands whose first argument is a profile name or alias
+const TARGET_COMMANDS: &[&str] = &["connect", "show", "copy-id", "test", "totp"];
+
+/// Commands whose first argument is a profile name
+const PROFILE_COMMANDS: &[&str] = &["edit", "remove"];
+
+/// Commands whose first argument is an alias name
+const ALIAS_COMMANDS: &[&str] = &["unalias"];
+
+/// Plugin subcommands whose first argument is a plugin name
+const PLUGIN_COMMANDS: &[&str] = &["update", "remove", "enable", "disable", "run"];
+
+/// What kind of names the hidden `__complete` command prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionKind {
+    /// Profile names and aliases
+    Targets,
+    /// Profile names only
+    Profiles,
+    /// Alias names only
+    Aliases,
+    /// Installed plugin names
+    Plugins,
+}
+
+/// Write the completion script for a shell, including dynamic name completion where supported
+pub fn generate_completions(shell: Shell, out: &mut dyn Write) -> std::io::Result<()> {
+    let mut cmd = Cli::command();
+    let bin_name = cmd.get_name().to_string();
+
+    clap_complete::generate(shell, &mut cmd, &bin_name, out);
+
+    match shell {
+        Shell::Bash => write!(out, "{}", bash_dynamic(&bin_name)),
+        Shell::Zsh => write!(out, "{}", zsh_dynamic(&bin_name)),
+        Shell::Fish => write!(out, "{}", fish_dynamic(&bin_name)),
+        // Other shells only get static completions
+        _ => Ok(()),
+    }
+}
+
+/// Comment line written above the line that loads completions from an rc file
+pub const COMPLETION_COMMENT_PREFIX: &str = "# ShellBe completions added on";
+
+/// Where a shell picks up completion scripts for the current user
+pub fn completion_install_path(shell: Shell) -> Option<PathBuf> {
+    let home = crate::utils::home_dir()?;
+
+    match shell {
+        Shell::Bash => Some(dirs::data_dir().unwrap_or_else(|| home.join(".local/share"))
+            .join("bash-completion/completions/shellbe")),
+        Shell::Zsh => Some(home.join(".zsh/completions/_shellbe")),
+        Shell::Fish => Some(dirs::config_dir().unwrap_or_else(|| home.join(".config"))
+            .join("fish/completions/shellbe.fish")),
+        _ => None,
+    }
+}
+
+/// The rc file that has to load the completion script, for shells that don't find it on their own
+///
+/// Fish loads everything in its completions directory, so it needs no rc line.
+pub fn completion_rc_file(shell: Shell) -> Option<PathBuf> {
+    let home = crate::utils::home_dir()?;
+
+    match shell {
+        Shell::Bash => Some(home.join(".bashrc")),
+        Shell::Zsh => Some(std::env::var_os("ZDOTDIR").map(PathBuf::from).unwrap_or(home).join(".zshrc")),
+        _ => None,
+    }
+}
+
+/// The rc file line that loads an installed completion script
+pub fn completion_source_line(shell: Shell, script: &Path) -> String {
+    let script = script.display();
+
+    match shell {
+        // The script registers itself with compdef, which only exists after compinit
+        Shell::Zsh => format!(
+            "(( $+functions[compdef] )) || {{ autoload -Uz compinit && compinit }}; source '{}'",
+            script
+        ),
+        _ => format!("[ -f '{}' ] && . '{}'", script, script),
+    }
+}
+
+/// Write the completion script for a shell to its install location; returns the path written
+pub fn install_completions(shell: Shell) -> std::io::Result<PathBuf> {
+    let path = completion_install_path(shell).ok_or_else(|| std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("No known completion directory for {}", shell),
+    ))?;
+
+    write_completions(shell, &path)?;
+    Ok(path)
+}
+
+fn write_completions(shell: Shell, path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::fs::File::create(path)?;
+    generate_completions(shell, &mut file)
+}
+
+/// Shell `case` pattern matching any of the given commands
+fn case_pattern(commands: &[&str]) -> String {
+    commands.join("|")
+}
+
+fn bash_dynamic(bin: &str) -> String {
+    format!(r#"
+# Dynamic completion of profile, alias and plugin names
+_{bin}_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local kind=""
+
+    if [[ $COMP_CWORD -eq 2 ]]; then
+        case "${{COMP_WORDS[1]}}" in
+            {targets}) kind="targets" ;;
+            {profiles}) kind="profiles" ;;
+            {aliases}) kind="aliases" ;;
+        esac
+    elif [[ $COMP_CWORD -eq 3 && "${{COMP_WORDS[1]}}" == "plugin" ]]; then
+        case "${{COMP_WORDS[2]}}" in
+            {plugins}) kind="plugins" ;;
+        esac
+    fi
+
+    if [[ -n "$kind" && "$cur" != -* ]]; then
+        COMPREPLY=( $(compgen -W "$({bin} __complete "$kind" 2>/dev/null)" -- "$cur") )
+        return 0
+    fi
+
+    _{bin} "$@"
+}}
+
+complete -F _{bin}_dynamic -o bashdefault -o default {bin}
+"#,
+        bin = bin,
+        targets = case_pattern(TARGET_COMMANDS),
+        profiles = case_pattern(PROFILE_COMMANDS),
+        aliases = case_pattern(ALIAS_COMMANDS),
+        plugins = case_pattern(PLUGIN_COMMANDS),
+    )
+}
+
+fn zsh_dynamic(bin: &str) -> String {
+    format!(r#"
+# Dynamic completion of profile, alias and plugin names
+_{bin}_dynamic() {{
+    local kind=""
+
+    if (( CURRENT == 3 )); then
+        case "${{words[2]}}" in
+            {targets}) kind="targets" ;;
+            {profiles}) kind="profiles" ;;
+            {aliases}) kind="aliases" ;;
+        esac
+    elif (( CURRENT == 4 )) && [[ "${{words[2]}}" == "plugin" ]]; then
+        case "${{words[3]}}" in
+            {plugins}) kind="plugins" ;;
+        esac
+    fi
+
+    if [[ -n "$kind" && "${{words[CURRENT]}}" != -* ]]; then
+        local -a names
+        names=(${{(f)"$({bin} __complete "$kind" 2>/dev/null)"}})
+        compadd -a names
+        return
+    fi
+
+    _{bin} "$@"
+}}
+
+compdef _{bin}_dynamic {bin}
+"#,
+        bin = bin,
+        targets = case_pattern(TARGET_COMMANDS),
+        profiles = case_pattern(PROFILE_COMMANDS),
+        aliases = case_pattern(ALIAS_COMMANDS),
+        plugins = case_pattern(PLUGIN_COMMANDS),
+    )
+}
+
+fn fish_dynamic(bin: &str) -> String {
+    let mut out = String::from("\n# Dynamic completion of profile, alias and plugin names\n");
+
+    for (commands, kind) in [
+        (TARGET_COMMANDS, "targets"),
+        (PROFILE_COMMANDS, "profiles"),
+        (ALIAS_COMMANDS, "aliases"),
+    ] {
+        out.push_str(&format!(
+            "complete -c {bin} -n '__fish_seen_subcommand_from {cmds}; and test (count (commandline -opc)) -eq 2' -f -a '({bin} __complete {kind} 2>/dev/null)'\n",
+            bin = bin,
+            cmds = commands.join(" "),
+            kind = kind,
+        ));
+    }
+
+    out.push_str(&format!(
+        "complete -c {bin} -n '__fish_seen_subcommand_from plugin; and __fish_seen_subcommand_from {cmds}; and test (count (commandline -opc)) -eq 3' -f -a '({bin} __complete plugins 2>/dev/null)'\n",
+        bin = bin,
+        cmds = PLUGIN_COMMANDS.join(" "),
+    ));
+
+    out
+}
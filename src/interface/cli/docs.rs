@@ -0,0 +1,167 @@
+use clap::{Arg, Command, CommandFactory};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::commands::Cli;
+use crate::domain::{PluginCommand, PluginInfo};
+
+/// Commands provided by an installed plugin, documented alongside the built-in ones
+pub struct PluginDocs {
+    pub info: PluginInfo,
+    pub commands: Vec<PluginCommand>,
+}
+
+/// The fully built command tree, so subcommands carry their `shellbe-<name>` display names
+fn cli_command() -> Command {
+    let mut cmd = Cli::command();
+    cmd.build();
+    cmd
+}
+
+/// Subcommands worth documenting (not hidden, not the generated `help`)
+fn visible_subcommands(cmd: &Command) -> impl Iterator<Item = &Command> {
+    cmd.get_subcommands().filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+}
+
+/// Write a man page for every command and plugin into `out_dir`; returns the written files
+pub fn generate_man_pages(out_dir: &Path, plugins: &[PluginDocs]) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(out_dir)?;
+
+    let mut written = Vec::new();
+    write_man_pages(out_dir, cli_command(), &mut written)?;
+
+    for plugin in plugins {
+        write_man_pages(out_dir, plugin_command(plugin), &mut written)?;
+    }
+
+    Ok(written)
+}
+
+fn write_man_pages(out_dir: &Path, cmd: Command, written: &mut Vec<PathBuf>) -> io::Result<()> {
+    let name = cmd.get_display_name().unwrap_or_else(|| cmd.get_name()).to_string();
+    let path = out_dir.join(format!("{}.1", name));
+
+    let mut file = fs::File::create(&path)?;
+    clap_mangen::Man::new(cmd.clone()).render(&mut file)?;
+    written.push(path);
+
+    for sub in visible_subcommands(&cmd) {
+        write_man_pages(out_dir, sub.clone(), written)?;
+    }
+
+    Ok(())
+}
+
+/// Describe a plugin as a command whose subcommands are the plugin's commands
+fn plugin_command(plugin: &PluginDocs) -> Command {
+    let name = format!("shellbe-plugin-{}", plugin.info.name);
+
+    let mut cmd = Command::new(name.clone())
+        .display_name(name.clone())
+        .version(plugin.info.version.clone())
+        .author(plugin.info.author.clone())
+        .about(plugin.info.description.clone())
+        .override_usage(format!("shellbe plugin run {} <COMMAND> [ARGS]...", plugin.info.name));
+
+    for command in &plugin.commands {
+        cmd = cmd.subcommand(Command::new(command.name.clone())
+            .display_name(format!("{}-{}", name, command.name))
+            .about(command.description.clone())
+            .override_usage(command.usage.clone()));
+    }
+
+    cmd
+}
+
+/// Render a markdown reference for every command and plugin
+pub fn generate_markdown(plugins: &[PluginDocs]) -> String {
+    let mut out = String::new();
+    let mut cmd = cli_command();
+
+    let _ = writeln!(out, "# {} command reference\n", cmd.get_name());
+    if let Some(about) = cmd.get_about() {
+        let _ = writeln!(out, "{}\n", about);
+    }
+
+    write_markdown_command(&mut out, &mut cmd, "shellbe", 1);
+
+    if !plugins.is_empty() {
+        let _ = writeln!(out, "## Plugin commands\n");
+
+        for plugin in plugins {
+            let _ = writeln!(out, "### {} {}\n", plugin.info.name, plugin.info.version);
+            let _ = writeln!(out, "{}\n", plugin.info.description);
+
+            for command in &plugin.commands {
+                let _ = writeln!(out, "#### shellbe plugin run {} {}\n", plugin.info.name, command.name);
+                let _ = writeln!(out, "{}\n", command.description);
+                let _ = writeln!(out, "```\n{}\n```\n", command.usage);
+            }
+        }
+    }
+
+    out
+}
+
+/// Write one command and its subcommands; the root (level 1) gets its heading from the caller
+fn write_markdown_command(out: &mut String, cmd: &mut Command, path: &str, level: usize) {
+    if level > 1 {
+        let _ = writeln!(out, "{} {}\n", "#".repeat(level.min(6)), path);
+        if let Some(about) = cmd.get_long_about().or_else(|| cmd.get_about()) {
+            let _ = writeln!(out, "{}\n", about);
+        }
+    }
+
+    let _ = writeln!(out, "```\n{}\n```\n", cmd.render_usage().to_string().trim());
+
+    let args: Vec<&Arg> = cmd.get_arguments()
+        .filter(|arg| !arg.is_hide_set() && !matches!(arg.get_id().as_str(), "help" | "version"))
+        // Global flags are listed once, on the root command
+        .filter(|arg| level == 1 || !arg.is_global_set())
+        .collect();
+
+    if !args.is_empty() {
+        let _ = writeln!(out, "| Argument | Description |\n|---|---|");
+        for arg in args {
+            let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+            let _ = writeln!(out, "| `{}` | {} |", arg_signature(arg), help.replace('|', "\\|"));
+        }
+        let _ = writeln!(out);
+    }
+
+    let subcommands: Vec<String> = visible_subcommands(cmd).map(|sub| sub.get_name().to_string()).collect();
+    for name in subcommands {
+        if let Some(sub) = cmd.find_subcommand_mut(&name) {
+            write_markdown_command(out, sub, &format!("{} {}", path, name), level + 1);
+        }
+    }
+}
+
+/// How an argument is written on the command line, e.g. `-p, --port <PORT>`
+fn arg_signature(arg: &Arg) -> String {
+    let value = arg.get_value_names()
+        .map(|names| names.iter().map(|n| format!("<{}>", n)).collect::<Vec<_>>().join(" "))
+        .unwrap_or_else(|| format!("<{}>", arg.get_id().as_str().to_uppercase()));
+
+    if arg.is_positional() {
+        return value;
+    }
+
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("--{}", long));
+    }
+
+    let mut signature = flags.join(", ");
+    if arg.get_action().takes_values() {
+        signature.push(' ');
+        signature.push_str(&value);
+    }
+
+    signature
+}
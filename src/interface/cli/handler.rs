@@ -1,14 +1,50 @@
 use crate::application::{
     ProfileService, ConnectionService, AliasService,
-    PluginService, SshConfigService, PluginError, UpdateService
+    PluginService, SshConfigService, PluginError, UpdateService,
+    DoctorService, CheckStatus, Settings, SettingsService, SETTINGS_FILE, BundleService,
+    VaultService, VaultSecret, VAULT_PASSPHRASE_ENV, TotpService, SyncService, SYNC_PASSPHRASE_ENV,
+    RemoteImportService, ImportChange, SIGNATURE_SUFFIX, DiscoveryService, DiscoveredProfile, TaskPool, RetryPolicy, UpdateChannel, UpdateSource,
+    TelemetryService, DO_NOT_TRACK_ENV, OptionSource,
 };
-use crate::domain::{Profile, Alias, DomainError};
-use crate::interface::cli::commands::{Commands, AddArgs, AliasArgs, PluginCommands};
-use std::io::{self, Write};
-use std::path::PathBuf;
+use crate::domain::{Profile, ProfileKind, KubernetesPod, ProfileExporter, HostDiscovery, ProfileFilter, AdHocTarget, SshUri, UriScheme, Alias, AliasStatus, ConnectionArgs, DomainError, EventBus, EventEnvelope, HistoryFormat, HistoryGrouping, template_params, is_secret_reference};
+use crate::interface::cli::completions::{
+    CompletionKind, COMPLETION_COMMENT_PREFIX,
+    completion_rc_file, completion_source_line, generate_completions, install_completions,
+};
+use crate::interface::cli::commands::{
+    Commands, AddArgs, AliasArgs, AliasCommands, AliasesArgs, AliasesCommands,
+    PluginCommands, HistoryGroupBy, OutputFormat, DocsFormat, VaultArgs, VaultCommands,
+    BundleArgs, BundleCommands, DiscoverArgs, DiscoverCommands, ExportFormat, SyncArgs, SyncCommands, EventsArgs, EventsCommands, EventFormat,
+    TelemetryArgs, TelemetryCommands, SocketsArgs, SocketsCommands, SessionsArgs, SessionsCommands,
+};
+use crate::interface::api::{self, ApiState, ApiToken, API_TOKEN_ENV};
+use crate::interface::grpc::{self, DaemonClient, EventStream, GrpcService, DAEMON_ENV};
+use crate::interface::cli::docs::{generate_man_pages, generate_markdown, PluginDocs};
+use crate::interface::cli::i18n::tr;
+use crate::interface::cli::markdown;
+use crate::interface::cli::progress::{self, ProgressBars};
+use crate::interface::cli::prompt::Prompter;
+use crate::infrastructure::{
+    EncryptedStorage, EventJournal, ControlSockets, CONTROL_SOCKETS_DIR, PuttySessionExporter, TermiusCsvExporter, LdapDiscovery, MdnsDiscovery, AwsDiscovery, GcpDiscovery, AzureDiscovery, LDAP_PASSWORD_ENV,
+    secret_backend, sync_backend,
+};
+use crate::interface::cli::output::{detail, status, format_optional_time, format_time, to_json, TimeFormat};
+use crate::interface::cli::table::{Column, Table};
+use crate::interface::cli::theme;
+use crate::errors::{ShellBeError, Suggestion};
+use crate::utils::{expand_path, redact_text, register_secret, shell_join, ShellBePaths};
+use crate::utils::suggest::levenshtein;
+use crate::utils::shell_alias::{
+    ShellAliasEntry, ShellAliasManifest, SHELL_ALIAS_MANIFEST,
+    add_rc_line, add_shell_alias_line, remove_shell_alias_line,
+    is_powershell_profile, powershell_profile,
+};
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use dialoguer::{Input, Select, Confirm};
-use console::{style, Term};
+use dialoguer::Input;
+use zeroize::Zeroizing;
 
 pub struct CommandHandler {
     profile_service: Arc<ProfileService>,
@@ -16,7 +52,14 @@ pub struct CommandHandler {
     alias_service: Arc<AliasService>,
     plugin_service: Arc<PluginService>,
     ssh_config_service: Arc<SshConfigService>,
+    totp_service: Option<Arc<TotpService>>,
+    event_stream: Option<Arc<EventStream>>,
+    event_bus: Option<Arc<EventBus>>,
     update_service: UpdateService,
+    output: OutputFormat,
+    prompt: Prompter,
+    time_format: TimeFormat,
+    settings: Settings,
 }
 
 impl CommandHandler {
@@ -28,812 +71,3079 @@ impl CommandHandler {
         plugin_service: Arc<PluginService>,
         ssh_config_service: Arc<SshConfigService>,
     ) -> Self {
+        let mut update_service = UpdateService::new(crate::VERSION);
+        update_service.set_progress(Arc::new(ProgressBars::default()));
+
         Self {
             profile_service,
             connection_service,
             alias_service,
             plugin_service,
             ssh_config_service,
-            update_service: UpdateService::new(),
+            totp_service: None,
+            event_stream: None,
+            event_bus: None,
+            update_service,
+            output: OutputFormat::Table,
+            prompt: Prompter::default(),
+            time_format: TimeFormat::default(),
+            settings: Settings::default(),
         }
     }
 
+    /// Use the given user settings for defaults
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.prompt.set_confirm(settings.ui.confirm);
+        self.update_service.set_source(UpdateSource::from(&settings.updates));
+        self.settings = settings;
+    }
+
+    /// Keep TOTP secrets with the given service, enabling 'totp' and codes on connect
+    pub fn set_totp_service(&mut self, totp_service: Arc<TotpService>) {
+        self.totp_service = Some(totp_service);
+    }
+
+    /// Events published on the services' event bus, streamed to gRPC clients by 'serve --grpc'
+    pub fn set_event_stream(&mut self, event_stream: Arc<EventStream>) {
+        self.event_stream = Some(event_stream);
+    }
+
+    /// The services' event bus, which 'serve --grpc' replays journaled events on
+    pub fn set_event_bus(&mut self, event_bus: Arc<EventBus>) {
+        self.event_bus = Some(event_bus);
+    }
+
+    /// Answer yes to every confirmation instead of prompting
+    pub fn set_assume_yes(&mut self, assume_yes: bool) {
+        self.prompt.set_assume_yes(assume_yes);
+    }
+
+    /// Show timestamps relative to now or as exact dates
+    pub fn set_time_format(&mut self, time_format: TimeFormat) {
+        self.time_format = time_format;
+    }
+
+    /// Set the output format used by listing commands
+    pub fn set_output_format(&mut self, output: OutputFormat) {
+        self.output = output;
+    }
+
+    /// Check if machine-readable output was requested
+    fn json_output(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+
+    /// Print a value as pretty JSON
+    fn print_json<T: serde::Serialize>(&self, value: &T) -> anyhow::Result<()> {
+        println!("{}", to_json(value)?);
+        Ok(())
+    }
+
     /// Handle a CLI command
     pub async fn handle_command(&self, command: Commands) -> anyhow::Result<()> {
         match command {
+            Commands::Init => self.handle_init().await?,
             Commands::Add(args) => self.handle_add(args).await?,
-            Commands::List => self.handle_list().await?,
+            Commands::List { columns, host, user, port, tag } => {
+                self.handle_list(columns, ProfileFilter { host, user, port, tag }).await?
+            },
             Commands::Connect { name } => self.handle_connect(name).await?,
-            Commands::CopyId { name, identity } => self.handle_copy_id(name, identity).await?,
-            Commands::GenerateKey { name, comment } => self.handle_generate_key(name, comment).await?,
+            Commands::Open { uri } => self.handle_open(uri).await?,
+            Commands::Uri { name, sftp } => self.handle_uri(name, sftp).await?,
+            Commands::Show { name } => self.handle_show(name).await?,
+            Commands::Env { name } => self.handle_env(name).await?,
+            Commands::CopyId { name, identity, all, jobs } => self.handle_copy_id(name, identity, all, jobs).await?,
+            Commands::GenerateKey { name, comment, type_ } => self.handle_generate_key(name, comment, type_).await?,
             Commands::Alias(args) => self.handle_alias(args).await?,
-            Commands::Aliases => self.handle_aliases().await?,
+            Commands::Aliases(args) => self.handle_aliases(args).await?,
+            Commands::Unalias { name } => self.handle_unalias(name).await?,
             Commands::Remove { name } => self.handle_remove(name).await?,
-            Commands::Edit { name } => self.handle_edit(name).await?,
-            Commands::Test { name } => self.handle_test(name).await?,
-            Commands::History { limit } => self.handle_history(limit).await?,
-            Commands::Export { replace } => self.handle_export(replace).await?,
-            Commands::Import { replace } => self.handle_import(replace).await?,
+            Commands::Edit { name, editor } => self.handle_edit(name, editor).await?,
+            Commands::Test { name, all, jobs } => self.handle_test(name, all, jobs).await?,
+            Commands::Totp { name, set, remove } => self.handle_totp(name, set, remove).await?,
+            Commands::Recent { limit } => self.handle_recent(limit).await?,
+            Commands::History { limit, group_by, convert } => self.handle_history(limit, group_by, convert).await?,
+            Commands::Export { format: ExportFormat::SshConfig, replace, with_aliases, .. } => self.handle_export(replace, with_aliases).await?,
+            Commands::Export { format, replace, with_aliases, file } => self.handle_export_format(format, file, replace || with_aliases).await?,
+            Commands::Import { replace, url: Some(url), verify, .. } => self.handle_import_url(&url, replace, verify).await?,
+            Commands::Import { replace, with_aliases, .. } => self.handle_import(replace, with_aliases).await?,
+            Commands::Discover(args) => self.handle_discover(args).await?,
+            Commands::Bundle(args) => self.handle_bundle(args).await?,
             Commands::Plugin(args) => self.handle_plugin(args).await?,
-            Commands::Update { check } => self.handle_update(check).await?,Commands::Uninstall { keep_config, yes } => self.handle_uninstall(keep_config, yes).await?,
+            Commands::Update { check, channel } => self.handle_update(check, channel).await?,Commands::Uninstall { keep_config } => self.handle_uninstall(keep_config, self.prompt.assume_yes()).await?,
+            Commands::Doctor { .. } => unreachable!("doctor runs before the command handler is built"),
+            Commands::Vault(args) => {
+                let vault = vault_service(&ShellBePaths::resolve(), &self.settings);
+                Self::handle_vault(&vault, &args, &self.prompt, self.output).await?;
+            },
+            Commands::Sync(args) => {
+                let paths = ShellBePaths::resolve();
+                let storage = Self::vault_storage(&vault_service(&paths, &self.settings), &self.prompt)?;
+                let sync = sync_service(&paths, &self.settings, storage)?;
+                Self::handle_sync(&sync, &args, &self.prompt, self.output).await?;
+            },
+            Commands::Serve { listen, grpc } => self.handle_serve(listen, grpc).await?,
+            Commands::Events(args) => self.handle_events(args).await?,
+            Commands::Telemetry(args) => self.handle_telemetry(args)?,
+            Commands::Sockets(args) => self.handle_sockets(args)?,
+            Commands::Sessions(args) => self.handle_sessions(args)?,
+            Commands::Completions { shell, install } => self.handle_completions(shell, install)?,
+            Commands::GenerateDocs { out_dir, format } => self.handle_generate_docs(out_dir, format).await?,
+            Commands::Complete { kind } => self.handle_complete(kind).await?,
         }
 
         Ok(())
     }
-    /// Handle the 'update' command
-    async fn handle_update(&self, check_only: bool) -> anyhow::Result<()> {
-        println!("{} Checking for updates...", style("→").cyan().bold());
+    /// Handle the 'doctor' command; returns whether the installation is healthy
+    ///
+    /// Takes no services so it can run before startup, when the setup may be broken.
+    pub async fn handle_doctor(doctor: &DoctorService, output: OutputFormat, fix: bool) -> anyhow::Result<bool> {
+        if fix {
+            for (issue, result) in doctor.fix_permissions() {
+                match result {
+                    Ok(()) => status!("{} {}", theme::success("✓"), tr!("doctor.fixed-permissions", issue.path.display(), format!("{:o}", issue.expected_mode))),
+                    Err(e) => println!("{} {}", theme::error("✗"), tr!("doctor.failed-to-fix-permissions", issue.path.display(), e)),
+                }
+            }
+        }
 
-        match self.update_service.check_for_update() {
-            Ok(Some(version)) => {
-                println!("{} A new version {} is available (current: {})",
-                         style("✓").green().bold(),
-                         style(&version).green(),
-                         style(crate::application::update_service::CURRENT_VERSION).yellow());
+        let report = doctor.run().await;
 
-                if !check_only {
-                    // Ask for confirmation
-                    let confirm = Confirm::new()
-                        .with_prompt("Do you want to update now?")
-                        .default(true)
-                        .interact()?;
+        if output == OutputFormat::Json {
+            println!("{}", to_json(&report)?);
+            return Ok(report.is_healthy());
+        }
 
-                    if confirm {
-                        // Backup the executable
-                        match self.update_service.backup_executable() {
-                            Ok(path) => {
-                                println!("{} Created backup at {}",
-                                         style("✓").green().bold(),
-                                         path.display());
-                            },
-                            Err(e) => {
-                                println!("{} Failed to create backup: {}",
-                                         style("!").yellow().bold(), e);
+        let mut category = "";
+        for check in &report.checks {
+            if check.category != category {
+                category = &check.category;
+                println!("\n{}", theme::heading(category));
+                println!("{}", theme::note("-------------------------------------"));
+            }
 
-                                // Ask to continue without backup
-                                let continue_anyway = Confirm::new()
-                                    .with_prompt("Continue without backup?")
-                                    .default(false)
-                                    .interact()?;
+            let marker = match check.status {
+                CheckStatus::Ok => theme::success("✓"),
+                CheckStatus::Warning => theme::warning("!"),
+                CheckStatus::Error => theme::error("✗"),
+            };
 
-                                if !continue_anyway {
-                                    println!("{} Update cancelled", style("!").yellow().bold());
-                                    return Ok(());
-                                }
-                            }
-                        }
+            println!("{} {}: {}", marker, check.name, check.message);
 
-                        // Perform the update
-                        match self.update_service.update() {
-                            Ok(_) => {
-                                println!("{} Successfully updated to {}!",
-                                         style("✓").green().bold(),
-                                         style(&version).green());
-                            },
-                            Err(e) => {
-                                println!("{} Update failed: {}",
-                                         style("✗").red().bold(), e);
-                            }
-                        }
-                    } else {
-                        println!("{} Update cancelled", style("!").yellow().bold());
-                    }
-                }
-            },
-            Ok(None) => {
-                println!("{} You are already using the latest version ({})",
-                         style("✓").green().bold(),
-                         style(crate::application::update_service::CURRENT_VERSION).green());
-            },
-            Err(e) => {
-                println!("{} Failed to check for updates: {}",
-                         style("✗").red().bold(), e);
+            if let Some(fix) = &check.fix {
+                println!("    {} {}", theme::accent(tr!("doctor.fix")), fix);
             }
         }
 
-        Ok(())
+        println!();
+        if report.is_healthy() {
+            status!("{} {}",
+                    theme::success("✓"),
+                    tr!("doctor.no-problems-found-warnings", report.count(CheckStatus::Warning)));
+        } else {
+            println!("{} {}",
+                     theme::error("✗"),
+                     tr!("doctor.problems-and-warnings-found",
+                         report.count(CheckStatus::Error),
+                         report.count(CheckStatus::Warning)));
+        }
+
+        Ok(report.is_healthy())
     }
-    async fn unload_plugin(&self, name: &str) -> Result<()> {
-        let mut plugins = self.loaded_plugins.write().await;
-        let idx = plugins.iter().position(|(n, _, _)| n == name)
-            .ok_or_else(|| PluginError::NotFound(name.to_string()))?;
 
-        // Remove the plugin
-        plugins.remove(idx);
+    /// Warn about private files that other users can read, like OpenSSH does for keys
+    pub fn warn_permission_issues(doctor: &DoctorService, identity_files: &[PathBuf]) {
+        let issues = doctor.permission_issues(identity_files);
+        if issues.is_empty() {
+            return;
+        }
 
-        Ok(())
+        for issue in &issues {
+            eprintln!("{} {}", theme::warning(tr!("permissions.warning")), issue);
+        }
+        eprintln!("  {}", tr!("permissions.run-doctor-fix", theme::accent("shellbe doctor --fix")));
     }
-}
 
-// Helper functions
+    /// Handle the 'vault' command
+    ///
+    /// Like 'doctor', this runs before the data files are opened, so it works while the vault is locked.
+    pub async fn handle_vault(vault: &VaultService, args: &VaultArgs, prompt: &Prompter, output: OutputFormat) -> anyhow::Result<()> {
+        if !matches!(args.command, VaultCommands::Init { .. } | VaultCommands::Status) && !vault.is_initialized() {
+            anyhow::bail!(tr!("vault.not-set-up", theme::accent("shellbe vault init")));
+        }
 
-/// Parse a GitHub URL into owner and repo
-    /// Handle the 'add' command
-    async fn handle_add(&self, args: AddArgs) -> anyhow::Result<()> {
-        println!("{}", style("Adding a new SSH profile...").cyan().bold());
+        match &args.command {
+            VaultCommands::Init { keyfile } => {
+                let secret = match keyfile {
+                    Some(path) => VaultSecret::Keyfile(path.clone()),
+                    None => VaultSecret::Passphrase(new_vault_passphrase(prompt)?),
+                };
 
-        // Collect profile information
-        let name = if let Some(name) = args.name {
-            name
-        } else if args.non_interactive {
-            return Err(anyhow::anyhow!("Profile name is required in non-interactive mode"));
-        } else {
-            Input::<String>::new()
-                .with_prompt("Enter profile name")
-                .interact()?
-        };
+                let encrypted = vault.init(&secret).await?;
+                status!("{} {}", theme::success("✓"), tr!("vault.vault-set-up", encrypted.len()));
 
-        let hostname = if let Some(host) = args.host {
-            host
-        } else if args.non_interactive {
-            return Err(anyhow::anyhow!("Hostname is required in non-interactive mode"));
-        } else {
-            Input::<String>::new()
-                .with_prompt("Enter hostname or IP address")
-                .interact()?
-        };
+                if keyfile.is_some() {
+                    status!("  {}", tr!("vault.keep-the-keyfile-safe"));
+                } else {
+                    status!("  {}", tr!("vault.unlocked-until", theme::accent("shellbe vault lock")));
+                }
+            },
+            VaultCommands::Unlock => {
+                if vault.keyfile()?.is_some() {
+                    status!("{} {}", theme::warning("!"), tr!("vault.keyfile-vaults-open-automatically"));
+                    return Ok(());
+                }
 
-        let username = if let Some(user) = args.user {
-            user
-        } else if args.non_interactive {
-            return Err(anyhow::anyhow!("Username is required in non-interactive mode"));
-        } else {
-            Input::<String>::new()
-                .with_prompt("Enter username")
-                .interact()?
-        };
+                let passphrase = vault_passphrase(prompt, tr!("vault.passphrase"))?;
+                vault.unlock(&VaultSecret::Passphrase(passphrase))?;
+                status!("{} {}", theme::success("✓"), tr!("vault.vault-unlocked", vault.key_store()));
+            },
+            VaultCommands::Lock => {
+                if vault.lock()? {
+                    status!("{} {}", theme::success("✓"), tr!("vault.vault-locked"));
+                } else {
+                    status!("{} {}", theme::warning("!"), tr!("vault.vault-was-not-unlocked"));
+                }
+            },
+            VaultCommands::ChangePassphrase { keyfile } => {
+                let current = match vault.keyfile()? {
+                    Some(path) => VaultSecret::Keyfile(path),
+                    None => VaultSecret::Passphrase(vault_passphrase(prompt, tr!("vault.current-passphrase"))?),
+                };
+
+                let new = match keyfile {
+                    Some(path) => VaultSecret::Keyfile(path.clone()),
+                    None => VaultSecret::Passphrase(new_vault_passphrase(prompt)?),
+                };
+
+                vault.change_secret(&current, &new).await?;
+                status!("{} {}", theme::success("✓"), tr!("vault.data-re-encrypted"));
+            },
+            VaultCommands::Status => {
+                let status = vault.status()?;
 
-        let port = if args.non_interactive {
-            args.port
-        } else {
-            Input::<u16>::new()
-                .with_prompt("Enter port")
-                .default(args.port)
-                .interact()?
-        };
+                if output == OutputFormat::Json {
+                    println!("{}", to_json(&status)?);
+                    return Ok(());
+                }
 
-        let identity_file = if let Some(identity) = args.identity {
-            Some(identity)
-        } else if !args.non_interactive {
-            let use_identity = Confirm::new()
-                .with_prompt("Use identity file?")
-                .default(false)
-                .interact()?;
+                if !status.initialized {
+                    println!("{} {}", theme::warning("!"), tr!("vault.not-set-up", theme::accent("shellbe vault init")));
+                    return Ok(());
+                }
 
-            if use_identity {
-                Some(Input::<PathBuf>::new()
-                    .with_prompt("Enter identity file path")
-                    .interact()?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+                if status.unlocked {
+                    println!("{} {}", theme::success("✓"), tr!("vault.status-unlocked"));
+                } else {
+                    println!("{} {}", theme::warning("!"), tr!("vault.status-locked", theme::accent("shellbe vault unlock")));
+                }
 
-        // Create a new profile
-        let mut profile = Profile::new(name, hostname, username);
-        profile.port = port;
+                if let Some(keyfile) = &status.keyfile {
+                    println!("  {}", tr!("vault.status-keyfile", keyfile.display()));
+                } else {
+                    println!("  {}", tr!("vault.status-key-store", status.key_store));
+                }
 
-        if let Some(identity) = identity_file {
-            profile.identity_file = Some(identity);
+                if let Some(locks_at) = status.locks_at {
+                    println!("  {}", tr!("vault.status-locks-at", format_time(locks_at, TimeFormat::Absolute)));
+                }
+
+                let files = if status.encrypted_files.is_empty() {
+                    "-".to_string()
+                } else {
+                    status.encrypted_files.join(", ")
+                };
+                println!("  {}", tr!("vault.status-encrypted-files", files));
+            },
         }
 
-        // Parse options
-        for option in args.options {
-            if let Some(idx) = option.find('=') {
-                let key = option[..idx].to_string();
-                let value = option[idx+1..].to_string();
-                profile.options.insert(key, value);
-            } else {
-                profile.options.insert(option, "".to_string());
-            }
+        Ok(())
+    }
+
+    /// Storage for the data files: encrypted when the vault is set up, asking for the passphrase if it's locked
+    pub fn vault_storage(vault: &VaultService, prompt: &Prompter) -> anyhow::Result<EncryptedStorage> {
+        if !vault.is_initialized() {
+            return Ok(EncryptedStorage::plain());
         }
 
-        // Add the profile
-        match self.profile_service.add_profile(profile.clone()).await {
-            Ok(_) => {
-                println!("{} Profile '{}' added successfully!", style("✓").green().bold(), profile.name);
+        if vault.lock_if_idle()? {
+            let minutes = vault.idle_timeout().map_or(0, |timeout| timeout.num_minutes());
+            status!("{} {}", theme::warning("!"), tr!("vault.locked-after-idle", minutes));
+        }
 
-                // Ask if user wants to add to SSH config
-                if !args.non_interactive {
-                    let add_to_ssh_config = Confirm::new()
-                        .with_prompt("Add this profile to SSH config?")
-                        .default(false)
-                        .interact()?;
+        if let Some(key) = vault.available_key()? {
+            return Ok(EncryptedStorage::with_key(key));
+        }
 
-                    if add_to_ssh_config {
-                        match self.ssh_config_service.add_profile_to_ssh_config(&profile).await {
-                            Ok(_) => println!("{} Profile added to SSH config", style("✓").green().bold()),
-                            Err(e) => println!("{} Failed to add profile to SSH config: {}", style("✗").red().bold(), e),
-                        }
-                    }
+        let passphrase = vault_passphrase(prompt, tr!("vault.passphrase"))
+            .map_err(|_| anyhow::anyhow!(tr!("vault.vault-is-locked",
+                                             theme::accent("shellbe vault unlock"),
+                                             VAULT_PASSPHRASE_ENV)))?;
 
-                    let copy_key = Confirm::new()
-                        .with_prompt("Copy SSH key to this server?")
-                        .default(false)
-                        .interact()?;
+        Ok(EncryptedStorage::with_key(vault.open(&VaultSecret::Passphrase(passphrase))?))
+    }
 
-                    if copy_key {
-                        let key_path = if let Some(identity) = profile.identity_file {
-                            // Use the specified identity file
-                            identity
-                        } else {
-                            // Use default identity file
-                            dirs::home_dir()
-                                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-                                .join(".ssh")
-                                .join("id_rsa.pub")
-                        };
+    /// Handle the 'sync' command
+    ///
+    /// Runs before the repositories open, so a pull is picked up by the rest of the run.
+    pub async fn handle_sync(sync: &SyncService, args: &SyncArgs, prompt: &Prompter, output: OutputFormat) -> anyhow::Result<()> {
+        let location = sync.backend().location().to_string();
 
-                        match self.connection_service.copy_ssh_key(&profile.name, &key_path).await {
-                            Ok(_) => println!("{} SSH key copied successfully", style("✓").green().bold()),
-                            Err(e) => println!("{} Failed to copy SSH key: {}", style("✗").red().bold(), e),
-                        }
-                    }
+        match &args.command {
+            SyncCommands::Push => {
+                // The first push chooses the passphrase every other machine will need
+                let first_push = sync.status().await?.pushed_at.is_none();
+                let passphrase = sync_passphrase(prompt, first_push)?;
+
+                let files = sync.push(&passphrase).await?;
+                status!("{} {}", theme::success("✓"), tr!("sync.pushed", files.len(), theme::highlight(&location)));
+            },
+            SyncCommands::Pull => {
+                let passphrase = sync_passphrase(prompt, false)?;
+
+                let changed = sync.pull(&passphrase).await?;
+                if changed.is_empty() {
+                    status!("{} {}", theme::success("✓"), tr!("sync.up-to-date"));
+                } else {
+                    status!("{} {}", theme::success("✓"), tr!("sync.pulled", changed.join(", "), theme::highlight(&location)));
                 }
             },
-            Err(e) => {
-                println!("{} Failed to add profile: {}", style("✗").red().bold(), e);
+            SyncCommands::Status => {
+                let status = sync.status().await?;
+
+                if output == OutputFormat::Json {
+                    println!("{}", to_json(&status)?);
+                    return Ok(());
+                }
+
+                match (status.pushed_at, &status.pushed_by) {
+                    (Some(pushed_at), Some(pushed_by)) => {
+                        println!("{} {}", theme::success("✓"), tr!("sync.status-snapshot", theme::highlight(&status.location), status.backend));
+                        println!("  {}", tr!("sync.status-pushed", format_time(pushed_at, TimeFormat::Absolute), pushed_by));
+                    },
+                    _ => {
+                        println!("{} {}", theme::warning("!"), tr!("sync.status-nothing-pushed", theme::highlight(&status.location), status.backend));
+                    },
+                }
             },
         }
 
         Ok(())
     }
 
-    /// Handle the 'list' command
-    async fn handle_list(&self) -> anyhow::Result<()> {
-        println!("{}", style("Available SSH profiles:").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
-        println!("{:<15} {:<20} {:<15} {:<5}",
-                 style("NAME").cyan().bold(),
-                 style("HOST").cyan().bold(),
-                 style("USER").cyan().bold(),
-                 style("PORT").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
+    /// Handle the 'serve' command: run the JSON API, and the gRPC API if asked, until Ctrl-C
+    async fn handle_serve(&self, listen: SocketAddr, grpc: Option<SocketAddr>) -> anyhow::Result<()> {
+        let token = ApiToken::load_or_create(&ShellBePaths::resolve().cache_dir)?;
+        let token_path = token.path().map(Path::to_path_buf);
 
-        let profiles = self.profile_service.list_profiles().await?;
+        let listener = tokio::net::TcpListener::bind(listen).await
+            .map_err(|e| anyhow::anyhow!(tr!("serve.failed-to-listen", listen, e)))?;
+        let address = listener.local_addr()?;
 
-        if profiles.is_empty() {
-            println!("{} No profiles found. Use 'add' command to create one.", style("!").yellow().bold());
-            return Ok(());
+        if !address.ip().is_loopback() {
+            println!("{} {}", theme::warning("!"), tr!("serve.reachable-from-network", address));
         }
 
-        for profile in profiles {
-            println!("{:<15} {:<20} {:<15} {:<5}",
-                     style(&profile.name).green(),
-                     profile.hostname,
-                     profile.username,
-                     profile.port);
-        }
+        status!("{} {}", theme::success("✓"), tr!("serve.listening", theme::highlight(format!("http://{}/v1", address))));
 
-        Ok(())
-    }
+        let grpc_listener = match grpc {
+            Some(grpc) => {
+                let listener = tokio::net::TcpListener::bind(grpc).await
+                    .map_err(|e| anyhow::anyhow!(tr!("serve.failed-to-listen", grpc, e)))?;
+                let address = listener.local_addr()?;
 
-    /// Handle the 'connect' command
-    async fn handle_connect(&self, name: String) -> anyhow::Result<()> {
-        // Resolve alias first
-        let profile_name = match self.alias_service.resolve_alias(&name).await {
-            Ok(resolved) => {
-                if resolved != name {
-                    println!("{} Connecting via alias '{}' -> '{}'", style("→").cyan().bold(), name, resolved);
+                if !address.ip().is_loopback() {
+                    println!("{} {}", theme::warning("!"), tr!("serve.reachable-from-network", address));
                 }
-                resolved
+                status!("{} {}", theme::success("✓"), tr!("serve.listening-grpc", theme::highlight(format!("http://{}", address))));
+                status!("  {}", tr!("serve.use-daemon", theme::accent(format!("{}=http://{}", DAEMON_ENV, address))));
+                Some(listener)
             },
-            Err(_) => name.clone(),
+            None => None,
         };
 
-        // Get the profile for display
-        match self.profile_service.get_profile(&profile_name).await {
-            Ok(profile) => {
-                println!("{} Connecting to {} ({}@{})...",
-                         style("→").green().bold(),
-                         style(&profile.name).green(),
-                         profile.username,
-                         profile.hostname);
-
-                // Connect to the profile
-                match self.connection_service.connect(&name).await {
-                    Ok(exit_code) => {
-                        if exit_code == 0 {
-                            println!("{} Connection closed successfully", style("✓").green().bold());
-                        } else {
-                            println!("{} Connection closed with exit code {}", style("!").yellow().bold(), exit_code);
-                        }
-                    },
-                    Err(e) => {
-                        println!("{} Connection failed: {}", style("✗").red().bold(), e);
-                    },
+        match &token_path {
+            Some(path) => status!("  {}", tr!("serve.token-in-file", theme::accent(path.display()))),
+            None => status!("  {}", tr!("serve.token-from-environment", API_TOKEN_ENV)),
+        }
+        status!("  {}", tr!("serve.stop-with-ctrl-c"));
+
+        let token = Arc::new(token);
+        let state = ApiState::new(self.profile_service.clone(), self.connection_service.clone(), token.clone());
+
+        match grpc_listener {
+            Some(grpc_listener) => {
+                let events = self.event_stream.clone().unwrap_or_default();
+                let mut service = GrpcService::new(self.profile_service.clone(), self.connection_service.clone(), events);
+                if let (true, Some(event_bus)) = (self.settings.events.journal, &self.event_bus) {
+                    let settings = &self.settings.events;
+                    let journal = EventJournal::new(&ShellBePaths::resolve().data_dir, settings.journal_max_bytes, settings.journal_files);
+                    service.set_replay(journal, event_bus.clone());
                 }
+
+                tokio::try_join!(
+                    async { api::serve(listener, state).await.map_err(anyhow::Error::from) },
+                    async { grpc::serve(grpc_listener, service, token).await.map_err(anyhow::Error::from) },
+                )?;
             },
-            Err(e) => {
-                println!("{} Profile not found: {}", style("✗").red().bold(), e);
-            },
+            None => api::serve(listener, state).await?,
         }
 
         Ok(())
     }
 
-    /// Handle the 'copy-id' command
-    async fn handle_copy_id(&self, name: String, identity: Option<PathBuf>) -> anyhow::Result<()> {
-        // Get the key path
-        let key_path = if let Some(identity) = identity {
-            identity
-        } else {
-            // Use default identity file
-            dirs::home_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-                .join(".ssh")
-                .join("id_rsa.pub")
-        };
-
-        // Check if key exists
-        if !key_path.exists() {
-            println!("{} Key file not found: {}", style("✗").red().bold(), key_path.display());
-
-            // Ask if user wants to generate a key
-            let generate_key = Confirm::new()
-                .with_prompt("Generate a new SSH key?")
-                .default(true)
-                .interact()?;
+    /// One line after a command about a newer version found by the background check
+    pub fn notify_update(&self, version: &str) {
+        eprintln!("{} {}", theme::note("→"), tr!("update.notice",
+            theme::highlight(version),
+            theme::note(crate::VERSION),
+            theme::accent("shellbe update")));
+    }
 
-            if generate_key {
-                let key_name = key_path.file_stem()
-                    .ok_or_else(|| anyhow::anyhow!("Invalid key file name"))?
-                    .to_string_lossy();
+    /// Handle the 'telemetry' command
+    fn handle_telemetry(&self, args: TelemetryArgs) -> anyhow::Result<()> {
+        let paths = ShellBePaths::resolve();
 
-                self.handle_generate_key(key_name.to_string(), None).await?;
-            } else {
-                return Ok(());
-            }
-        }
+        match args.command {
+            TelemetryCommands::Status => {
+                let telemetry = TelemetryService::new(&paths.data_dir, &self.settings.telemetry);
+                let pending = telemetry.pending();
+
+                if self.json_output() {
+                    return self.print_json(&serde_json::json!({
+                        "enabled": telemetry.enabled(),
+                        "do_not_track": telemetry.do_not_track(),
+                        "endpoint": telemetry.endpoint(),
+                        "pending": pending,
+                    }));
+                }
 
-        println!("{} Copying SSH key {} to {}...",
-                 style("→").cyan().bold(),
-                 key_path.display(),
-                 style(&name).green());
+                if telemetry.enabled() {
+                    println!("{} {}", theme::success("✓"), tr!("telemetry.status-enabled", theme::highlight(telemetry.endpoint())));
+                } else if self.settings.telemetry.enabled && telemetry.do_not_track() {
+                    println!("{} {}", theme::warning("!"), tr!("telemetry.status-do-not-track", DO_NOT_TRACK_ENV));
+                } else {
+                    println!("{} {}", theme::muted("-"), tr!("telemetry.status-disabled", theme::accent("shellbe telemetry enable")));
+                }
 
-        match self.connection_service.copy_ssh_key(&name, &key_path).await {
-            Ok(_) => {
-                println!("{} SSH key copied successfully", style("✓").green().bold());
+                if !pending.is_empty() {
+                    println!();
+                    println!("{}", theme::heading(tr!("telemetry.pending")));
+                    println!("{}", serde_json::to_string_pretty(&pending)?);
+                }
             },
-            Err(e) => {
-                println!("{} Failed to copy SSH key: {}", style("✗").red().bold(), e);
+            TelemetryCommands::Enable | TelemetryCommands::Disable => {
+                let enable = matches!(args.command, TelemetryCommands::Enable);
+                let telemetry = TelemetryService::new(&paths.data_dir, &self.settings.telemetry);
+
+                let mut settings_service = SettingsService::load(paths.config_dir.join(SETTINGS_FILE))?;
+                let mut settings = settings_service.settings().clone();
+                settings.telemetry.enabled = enable;
+                settings_service.save(settings)?;
+
+                if enable {
+                    status!("{} {}", theme::success("✓"), tr!("telemetry.enabled", theme::highlight(&self.settings.telemetry.endpoint)));
+                    status!("  {}", tr!("telemetry.what-is-sent"));
+                    if telemetry.do_not_track() {
+                        status!("{} {}", theme::warning("!"), tr!("telemetry.status-do-not-track", DO_NOT_TRACK_ENV));
+                    }
+                } else {
+                    telemetry.clear()?;
+                    status!("{} {}", theme::success("✓"), tr!("telemetry.disabled"));
+                }
             },
         }
 
         Ok(())
     }
 
-    /// Handle the 'generate-key' command
-    async fn handle_generate_key(&self, name: String, comment: Option<String>) -> anyhow::Result<()> {
-        println!("{} Generating a new SSH key pair...", style("→").cyan().bold());
+    /// Handle the 'sockets' command
+    fn handle_sockets(&self, args: SocketsArgs) -> anyhow::Result<()> {
+        let sockets = ControlSockets::new(ShellBePaths::resolve().cache_dir.join(CONTROL_SOCKETS_DIR));
 
-        // Get or create SSH directory
-        let ssh_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            .join(".ssh");
+        match args.command {
+            SocketsCommands::List => {
+                let list = sockets.list()?;
 
-        if !ssh_dir.exists() {
-            std::fs::create_dir_all(&ssh_dir)?;
+                if self.json_output() {
+                    return self.print_json(&list);
+                }
 
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let metadata = std::fs::metadata(&ssh_dir)?;
-                let mut permissions = metadata.permissions();
-                permissions.set_mode(0o700);
-                std::fs::set_permissions(&ssh_dir, permissions)?;
-            }
-        }
+                if list.is_empty() {
+                    status!("{} {}", theme::warning("!"), tr!("sockets.none", sockets.dir().display()));
+                    if !self.settings.ssh.multiplex {
+                        status!("  {}", tr!("sockets.multiplex-off", theme::accent("multiplex = true")));
+                    }
+                    return Ok(());
+                }
+
+                let mut table = Table::new(vec![
+                    Column::new("socket", tr!("column.socket")),
+                    Column::new("status", tr!("column.status")),
+                    Column::new("created", tr!("column.created")),
+                ]);
+                for socket in &list {
+                    let status = if socket.alive {
+                        theme::highlight(tr!("sockets.alive"))
+                    } else {
+                        theme::error(tr!("sockets.stale"))
+                    };
+                    table.add_row(vec![
+                        socket.name(),
+                        status.to_string(),
+                        format_optional_time(socket.created_at, self.time_format),
+                    ]);
+                }
 
-        let ssh_service = crate::infrastructure::ThrushSshService::new();
+                println!("{}", theme::heading(tr!("sockets.heading", sockets.dir().display())));
+                table.print();
 
-        match ssh_service.generate_key(&name, comment.as_deref()).await {
-            Ok((private_key, public_key)) => {
-                println!("{} SSH key pair generated successfully:", style("✓").green().bold());
-                println!("  Private key: {}", style(private_key.display()).cyan());
-                println!("  Public key: {}", style(public_key.display()).cyan());
+                let stale = list.iter().filter(|socket| !socket.alive).count();
+                if stale > 0 {
+                    println!();
+                    status!("{} {}", theme::warning("!"), tr!("sockets.stale-found", stale, theme::accent("shellbe sockets clean")));
+                }
             },
-            Err(e) => {
-                println!("{} Failed to generate SSH key: {}", style("✗").red().bold(), e);
+            SocketsCommands::Clean => {
+                let removed = sockets.clean()?;
+
+                if self.json_output() {
+                    return self.print_json(&removed);
+                }
+
+                if removed.is_empty() {
+                    status!("{} {}", theme::success("✓"), tr!("sockets.nothing-to-clean"));
+                } else {
+                    for socket in &removed {
+                        detail!("  {}", socket.name());
+                    }
+                    status!("{} {}", theme::success("✓"), tr!("sockets.cleaned", removed.len()));
+                }
             },
         }
 
         Ok(())
     }
 
-    /// Handle the 'alias' command
-    async fn handle_alias(&self, args: AliasArgs) -> anyhow::Result<()> {
-        // Create alias
-        match self.alias_service.create_alias(&args.name, &args.profile).await {
-            Ok(_) => {
-                println!("{} Alias '{}' created for profile '{}'",
-                         style("✓").green().bold(),
-                         style(&args.name).green(),
-                         style(&args.profile).green());
+    /// Handle the 'sessions' command
+    fn handle_sessions(&self, args: SessionsArgs) -> anyhow::Result<()> {
+        match args.command.unwrap_or(SessionsCommands::List) {
+            SessionsCommands::List => {
+                let sessions = self.connection_service.list_sessions()?;
 
-                // Create shell alias if requested
-                if args.shell_alias {
-                    self.create_shell_alias(&args.name, &args.profile)?;
+                if self.json_output() {
+                    return self.print_json(&sessions);
+                }
+
+                if sessions.is_empty() {
+                    status!("{} {}", theme::warning("!"), tr!("sessions.none"));
+                    return Ok(());
+                }
+
+                let mut table = Table::new(vec![
+                    Column::new("id", tr!("column.id")),
+                    Column::new("profile", tr!("column.profile")),
+                    Column::new("host", tr!("column.host")),
+                    Column::new("started", tr!("column.started")),
+                    Column::new("pid", tr!("column.pid")),
+                ]);
+                for session in &sessions {
+                    table.add_row(vec![
+                        theme::accent(&session.id).to_string(),
+                        theme::highlight(&session.profile).to_string(),
+                        session.target.clone(),
+                        format_optional_time(Some(session.started_at), self.time_format),
+                        session.pid.to_string(),
+                    ]);
                 }
+
+                println!("{}", theme::heading(tr!("sessions.heading")));
+                table.print();
             },
-            Err(e) => {
-                println!("{} Failed to create alias: {}", style("✗").red().bold(), e);
+            SessionsCommands::Kill { id } => {
+                let Some(session) = self.connection_service.kill_session(&id)? else {
+                    return self.report_not_found(tr!("sessions.not-found", id), &[]);
+                };
+
+                if self.json_output() {
+                    return self.print_json(&session);
+                }
+
+                status!("{} {}", theme::success("✓"), tr!("sessions.killed", session.id, theme::highlight(&session.profile), session.pid));
             },
         }
 
         Ok(())
     }
 
-    /// Helper method to create a shell alias
-    fn create_shell_alias(&self, alias_name: &str, profile_name: &str) -> anyhow::Result<()> {
-        // Detect user's shell and corresponding rc file
-        let shell_rc_file = if let Ok(shell) = std::env::var("SHELL") {
-            if shell.contains("zsh") {
-                dirs::home_dir().map(|h| h.join(".zshrc"))
-            } else if shell.contains("bash") {
-                dirs::home_dir().map(|h| h.join(".bashrc"))
-            } else {
-                dirs::home_dir().map(|h| h.join(".profile"))
-            }
-        } else {
-            dirs::home_dir().map(|h| h.join(".bashrc"))
-        };
+    /// Handle the 'events' command
+    async fn handle_events(&self, args: EventsArgs) -> anyhow::Result<()> {
+        match args.command {
+            EventsCommands::Tail { format } => self.handle_events_tail(format).await,
+            EventsCommands::Log { limit, subject, kind, format } => self.handle_events_log(limit, subject, kind, format),
+            EventsCommands::Replay { since, subject, kind } => self.handle_events_replay(since, subject, kind).await,
+        }
+    }
+
+    /// Handle the 'events replay' command: have the daemon publish its journaled events again
+    async fn handle_events_replay(&self, since: chrono::DateTime<chrono::Utc>, subject: Option<String>, kind: Option<String>) -> anyhow::Result<()> {
+        // Integrations follow the daemon's stream, so that is where the events go
+        let address = daemon_address(&self.settings)
+            .ok_or_else(|| anyhow::anyhow!(tr!("events.no-daemon", theme::accent("shellbe serve --grpc"), DAEMON_ENV)))?;
+        let token = ApiToken::load_or_create(&ShellBePaths::resolve().cache_dir)?;
+        let daemon = DaemonClient::connect(&address, &token).await?;
+
+        let since_text = format_time(since, TimeFormat::Absolute);
+        match daemon.replay_events(since, kind, subject).await? {
+            0 => status!("{} {}", theme::warning("!"), tr!("events.nothing-to-replay", since_text)),
+            replayed => status!("{} {}", theme::success("✓"), tr!("events.replayed", replayed, since_text)),
+        }
+
+        Ok(())
+    }
 
-        let shell_rc_file = shell_rc_file.ok_or_else(|| anyhow::anyhow!("Could not determine shell configuration file"))?;
+    /// Handle the 'events log' command: print events from the journal
+    fn handle_events_log(&self, limit: usize, subject: Option<String>, kind: Option<String>, format: EventFormat) -> anyhow::Result<()> {
+        let events = &self.settings.events;
+        let journal = EventJournal::new(&ShellBePaths::resolve().data_dir, events.journal_max_bytes, events.journal_files);
 
-        // Check if alias already exists
-        let mut content = String::new();
-        if shell_rc_file.exists() {
-            content = std::fs::read_to_string(&shell_rc_file)?;
+        if !events.journal && !journal.path().exists() {
+            status!("{} {}", theme::warning("!"), tr!("events.journal-disabled", theme::accent("[events] journal = true")));
+            return Ok(());
         }
 
-        let alias_line = format!("alias {}='shellbe connect {}'", alias_name, profile_name);
+        let mut entries: Vec<EventEnvelope> = journal.read()?.into_iter()
+            .filter(|entry| subject.as_deref().map_or(true, |subject| entry.event.subject() == subject))
+            .filter(|entry| kind.as_deref().map_or(true, |kind| entry.event.kind() == kind))
+            .collect();
+        entries.drain(..entries.len().saturating_sub(limit));
 
-        if content.contains(&alias_line) {
-            println!("{} Shell alias '{}' already exists in {}",
-                     style("!").yellow().bold(),
-                     alias_name,
-                     shell_rc_file.display());
+        if entries.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("events.no-journaled-events"));
             return Ok(());
         }
 
-        // Add alias to shell config
-        let mut file = std::fs::OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(&shell_rc_file)?;
+        for entry in &entries {
+            match format {
+                EventFormat::Json => println!("{}", redact_text(&serde_json::to_string(entry)?)),
+                EventFormat::Text => println!("{}  {}  {}",
+                    theme::note(format_time(entry.timestamp, self.time_format)),
+                    theme::accent(entry.event.kind()),
+                    theme::highlight(entry.event.subject())),
+            }
+        }
+
+        Ok(())
+    }
 
-        writeln!(file, "\n# ShellBe alias added on {}", chrono::Local::now().format("%Y-%m-%d %H:%M:%S"))?;
-        writeln!(file, "{}", alias_line)?;
+    /// Handle the 'events tail' command: follow the daemon's event stream until Ctrl-C or the daemon stops
+    async fn handle_events_tail(&self, format: EventFormat) -> anyhow::Result<()> {
+        // Events are only published inside a process, so there is nothing to follow without a daemon
+        let address = daemon_address(&self.settings)
+            .ok_or_else(|| anyhow::anyhow!(tr!("events.no-daemon", theme::accent("shellbe serve --grpc"), DAEMON_ENV)))?;
+        let token = ApiToken::load_or_create(&ShellBePaths::resolve().cache_dir)?;
+        let daemon = DaemonClient::connect(&address, &token).await?;
+        let mut events = daemon.events().await?;
+
+        status!("{} {}", theme::heading("→"), tr!("events.following", theme::highlight(&address)));
+
+        loop {
+            let event = tokio::select! {
+                event = events.message() => event?,
+                _ = tokio::signal::ctrl_c() => break,
+            };
+            let Some(event) = event else {
+                status!("{} {}", theme::warning("!"), tr!("events.daemon-stopped"));
+                break;
+            };
 
-        println!("{} Shell alias '{}' added to {}",
-                 style("✓").green().bold(),
-                 alias_name,
-                 shell_rc_file.display());
-        println!("{} To use this alias, restart your shell or run: source {}",
-                 style("!").yellow().bold(),
-                 shell_rc_file.display());
+            match format {
+                EventFormat::Json => println!("{}", redact_text(&event.envelope_json)),
+                EventFormat::Text => {
+                    let time = chrono::DateTime::parse_from_rfc3339(&event.timestamp)
+                        .map(|time| format_time(time.with_timezone(&chrono::Utc), TimeFormat::Absolute))
+                        .unwrap_or(event.timestamp);
+                    println!("{}  {}  {}", theme::note(time), theme::accent(&event.kind), theme::highlight(&event.subject));
+                },
+            }
+            std::io::stdout().flush()?;
+        }
 
         Ok(())
     }
 
-    /// Handle the 'aliases' command
-    async fn handle_aliases(&self) -> anyhow::Result<()> {
-        println!("{}", style("Available connection aliases:").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
-        println!("{:<15} {:<15}",
-                 style("ALIAS").cyan().bold(),
-                 style("PROFILE").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
+    /// Handle the 'completions' command
+    fn handle_completions(&self, shell: Option<clap_complete::Shell>, install: bool) -> anyhow::Result<()> {
+        if install {
+            let shell = shell.or_else(clap_complete::Shell::from_env)
+                .ok_or_else(|| anyhow::anyhow!(tr!("completions.couldnt-detect-your-shell")))?;
+            return self.install_shell_completions(shell);
+        }
 
-        let aliases = self.alias_service.list_aliases().await?;
+        let shell = shell.ok_or_else(|| anyhow::anyhow!(tr!("completions.shell-is-required")))?;
+        let mut stdout = std::io::stdout();
+        generate_completions(shell, &mut stdout)?;
+        Ok(())
+    }
 
-        if aliases.is_empty() {
-            println!("{} No aliases found. Use 'alias' command to create one.", style("!").yellow().bold());
+    /// Write the completion script for a shell and load it from the shell's rc file
+    fn install_shell_completions(&self, shell: clap_complete::Shell) -> anyhow::Result<()> {
+        let path = install_completions(shell)?;
+        status!("{} {}", theme::success("✓"), tr!("completions.installed-completions-to", path.display()));
+
+        // Same mechanism as shell aliases: a dated comment and one line appended to the rc file
+        let Some(rc_file) = completion_rc_file(shell) else {
             return Ok(());
+        };
+
+        let line = completion_source_line(shell, &path);
+        if add_rc_line(&rc_file, COMPLETION_COMMENT_PREFIX, &line)? {
+            status!("{} {}", theme::success("✓"), tr!("completions.completions-loaded-from", rc_file.display()));
+            status!("{} {}",
+                    theme::warning("!"),
+                    tr!("completions.to-use-them-restart", rc_file.display()));
+        } else {
+            detail!("{} {}", theme::muted("→"), tr!("completions.already-loaded-from", rc_file.display()));
         }
 
-        for alias in aliases {
-            println!("{:<15} {:<15}",
-                     style(&alias.name).green(),
-                     alias.target);
+        Ok(())
+    }
+
+    /// Handle the hidden 'generate-docs' command
+    async fn handle_generate_docs(&self, out_dir: PathBuf, format: DocsFormat) -> anyhow::Result<()> {
+        // Document the commands of every loaded plugin too
+        let plugins: Vec<PluginDocs> = self.plugin_service.get_loaded_plugins().await
+            .iter()
+            .map(|plugin| PluginDocs { info: plugin.info(), commands: plugin.commands() })
+            .collect();
+
+        if matches!(format, DocsFormat::Man | DocsFormat::All) {
+            let pages = generate_man_pages(&out_dir.join("man"), &plugins)?;
+            status!("{} {}",
+                    theme::success("✓"),
+                    tr!("generate-docs.wrote-man-pages-to", pages.len(), out_dir.join("man").display()));
+        }
+
+        if matches!(format, DocsFormat::Markdown | DocsFormat::All) {
+            std::fs::create_dir_all(&out_dir)?;
+            let path = out_dir.join("commands.md");
+            std::fs::write(&path, generate_markdown(&plugins))?;
+            status!("{} {}", theme::success("✓"), tr!("generate-docs.wrote", path.display()));
         }
 
         Ok(())
     }
 
-    /// Handle the 'remove' command
-    async fn handle_remove(&self, name: String) -> anyhow::Result<()> {
-        // Ask for confirmation
-        let confirm = Confirm::new()
-            .with_prompt(format!("Are you sure you want to remove profile '{}'?", name))
-            .default(false)
-            .interact()?;
+    /// Handle the hidden '__complete' command, printing one name per line
+    async fn handle_complete(&self, kind: CompletionKind) -> anyhow::Result<()> {
+        let mut names: Vec<String> = Vec::new();
 
-        if !confirm {
-            println!("{} Operation cancelled", style("!").yellow().bold());
-            return Ok(());
+        if matches!(kind, CompletionKind::Targets | CompletionKind::Profiles) {
+            names.extend(self.profile_service.list_profiles().await?.into_iter().map(|p| p.name));
         }
 
-        // Remove profile
-        match self.profile_service.remove_profile(&name).await {
-            Ok(_) => {
-                println!("{} Profile '{}' removed successfully", style("✓").green().bold(), name);
+        if matches!(kind, CompletionKind::Targets | CompletionKind::Aliases) {
+            names.extend(self.alias_service.list_aliases().await?
+                .into_iter()
+                .filter(|a| !a.is_template())
+                .map(|a| a.name));
+        }
 
-                // Ask if user wants to remove from SSH config
-                let remove_from_ssh_config = Confirm::new()
-                    .with_prompt("Remove this profile from SSH config?")
-                    .default(false)
-                    .interact()?;
+        if kind == CompletionKind::Plugins {
+            names.extend(self.plugin_service.list_plugins().await?.into_iter().map(|p| p.info.name));
+        }
 
-                if remove_from_ssh_config {
-                    match self.ssh_config_service.remove_profile_from_ssh_config(&name).await {
-                        Ok(_) => println!("{} Profile removed from SSH config", style("✓").green().bold()),
-                        Err(e) => println!("{} Failed to remove profile from SSH config: {}", style("✗").red().bold(), e),
-                    }
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            println!("{}", name);
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'update' command
+    async fn handle_update(&self, check_only: bool, channel: Option<String>) -> anyhow::Result<()> {
+        let channel = match channel.as_deref() {
+            Some("stable") => UpdateChannel::Stable,
+            Some("beta") => UpdateChannel::Beta,
+            Some("nightly") => UpdateChannel::Nightly,
+            _ => self.settings.updates.channel,
+        };
+
+        status!("{} {}", theme::heading("→"), tr!("update.checking-for-updates"));
+
+        match self.update_service.available_update(channel).await {
+            Ok(Some(update)) => {
+                let version = update.version;
+                let current = theme::note(crate::VERSION);
+                if self.update_service.is_downgrade(&version) {
+                    status!("{} {}", theme::warning("!"), tr!("update.latest-on-channel", theme::highlight(&version), channel, current));
+                } else {
+                    status!("{} {}", theme::success("✓"), tr!("update.a-new-version-is-available", theme::highlight(&version), current));
                 }
 
-                // List and remove aliases pointing to this profile
-                match self.alias_service.get_aliases_for_profile(&name).await {
-                    Ok(aliases) => {
-                        if !aliases.is_empty() {
-                            println!("{} Found aliases pointing to this profile:", style("!").yellow().bold());
+                for release in &update.notes {
+                    status!("\n{}\n{}", theme::heading(tr!("update.release-notes", release.version)), markdown::render(&release.notes).trim_end());
+                }
 
-                            for alias in &aliases {
-                                println!("  - {}", style(&alias.name).yellow());
-                            }
+                if !check_only {
+                    // Ask for confirmation
+                    let confirm = self.prompt.confirm(tr!("update.do-you-want-to-update"), true)?;
+
+                    if confirm {
+                        // Backup the executable
+                        match self.update_service.backup_executable() {
+                            Ok(path) => {
+                                status!("{} {}", theme::success("✓"), tr!("update.created-backup-at", path.display()));
+                            },
+                            Err(e) => {
+                                status!("{} {}", theme::warning("!"), tr!("update.failed-to-create-backup", e));
 
-                            let remove_aliases = Confirm::new()
-                                .with_prompt("Remove these aliases?")
-                                .default(true)
-                                .interact()?;
+                                // Ask to continue without backup
+                                let continue_anyway = self.prompt.confirm(tr!("update.continue-without-backup"), false)?;
 
-                            if remove_aliases {
-                                for alias in aliases {
-                                    match self.alias_service.remove_alias(&alias.name).await {
-                                        Ok(_) => println!("{} Removed alias '{}'", style("✓").green().bold(), alias.name),
-                                        Err(e) => println!("{} Failed to remove alias '{}': {}", style("✗").red().bold(), alias.name, e),
-                                    }
+                                if !continue_anyway {
+                                    status!("{} {}", theme::warning("!"), tr!("update.update-cancelled"));
+                                    return Ok(());
                                 }
                             }
                         }
-                    },
-                    Err(e) => {
-                        println!("{} Error checking for aliases: {}", style("!").yellow().bold(), e);
-                    },
+
+                        // Perform the update
+                        match self.update_service.update(channel).await {
+                            Ok(_) => {
+                                status!("{} {}",
+                                        theme::success("✓"),
+                                        tr!("update.successfully-updated-to", theme::highlight(&version)));
+                            },
+                            Err(e) => {
+                                println!("{} {}", theme::error("✗"), tr!("update.update-failed", e));
+                            }
+                        }
+                    } else {
+                        status!("{} {}", theme::warning("!"), tr!("update.update-cancelled"));
+                    }
                 }
             },
+            Ok(None) => {
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("update.you-are-already-using-the",
+                            theme::highlight(crate::VERSION)));
+            },
             Err(e) => {
-                println!("{} Failed to remove profile: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("update.failed-to-check-for-updates", e));
+            }
+        }
+
+        Ok(())
+    }
+    async fn unload_plugin(&self, name: &str) -> Result<()> {
+        let mut plugins = self.loaded_plugins.write().await;
+        let idx = plugins.iter().position(|(n, _, _)| n == name)
+            .ok_or_else(|| PluginError::NotFound(name.to_string()))?;
+
+        // Remove the plugin
+        plugins.remove(idx);
+
+        Ok(())
+    }
+}
+
+// Helper functions
+
+/// Parse a GitHub URL into owner and repo
+    /// Handle the 'init' command
+    async fn handle_init(&self) -> anyhow::Result<()> {
+        println!("{}", theme::heading(tr!("init.setting-up-shellbe")));
+
+        // 1. Config directory and settings file
+        let paths = ShellBePaths::resolve();
+        let settings_path = paths.config_dir.join(SETTINGS_FILE);
+
+        if settings_path.exists() {
+            status!("{} {}", theme::success("✓"), tr!("init.settings-already-exist-at", settings_path.display()));
+        } else {
+            let mut settings_service = SettingsService::load(&settings_path)?;
+            settings_service.save(self.settings.clone())?;
+            status!("{} {}", theme::success("✓"), tr!("init.created", settings_path.display()));
+        }
+
+        // 2. Import hosts from the SSH config
+        let ssh_config_path = default_ssh_config_path();
+
+        if !ssh_config_path.exists() {
+            status!("{} {}", theme::warning("!"), tr!("init.no-ssh-config-at-skipping", ssh_config_path.display()));
+        } else if self.prompt.confirm(tr!("init.import-hosts-from", ssh_config_path.display()), true)? {
+            self.handle_import(false, true).await?;
+        }
+
+        // 3. Make sure there's a key to log in with
+        let ssh_dir = ssh_config_path.parent().map(PathBuf::from).unwrap_or_default();
+        let existing_key = ["id_ed25519", "id_ecdsa", "id_rsa"].into_iter()
+            .map(|name| ssh_dir.join(name))
+            .find(|path| path.exists());
+
+        match existing_key {
+            Some(key) => status!("{} {}", theme::success("✓"), tr!("init.found-ssh-key", key.display())),
+            None => {
+                let prompt = tr!("init.no-ssh-key-found-generate", self.settings.defaults.key_type);
+                if self.prompt.confirm(prompt, true)? {
+                    self.handle_generate_key(None, None, None).await?;
+                }
+            },
+        }
+
+        // 4. Shell completions for the current shell
+        match clap_complete::Shell::from_env() {
+            Some(shell) => {
+                if self.prompt.confirm(tr!("init.install-completions", shell), true)? {
+                    if let Err(e) = self.install_shell_completions(shell) {
+                        println!("{} {}", theme::error("✗"), tr!("init.failed-to-install-completions", e));
+                    }
+                }
             },
+            None => status!("{} {}", theme::warning("!"), tr!("init.couldnt-detect-your-shell-run")),
         }
 
+        println!();
+        status!("{} {}",
+                theme::success("✓"),
+                tr!("init.shellbe-is-ready-try-or", theme::accent("shellbe list"), theme::accent("shellbe add")));
+
         Ok(())
     }
 
-    /// Handle the 'edit' command
-    async fn handle_edit(&self, name: String) -> anyhow::Result<()> {
-        // Get the profile
-        let profile = match self.profile_service.get_profile(&name).await {
-            Ok(p) => p,
-            Err(e) => {
-                println!("{} Failed to get profile: {}", style("✗").red().bold(), e);
-                return Ok(());
+    /// Handle the 'add' command
+    async fn handle_add(&self, args: AddArgs) -> anyhow::Result<()> {
+        println!("{}", theme::heading(tr!("add.adding-a-new-ssh-profile")));
+
+        let non_interactive = !self.prompt.is_interactive();
+
+        // Collect profile information
+        let name = if let Some(name) = args.name {
+            name
+        } else if non_interactive {
+            return Err(anyhow::anyhow!(tr!("common.profile-name-is-required-in")));
+        } else {
+            Input::<String>::new()
+                .with_prompt(tr!("add.enter-profile-name"))
+                .interact()?
+        };
+
+        // Containers and pods need no host, port or key of their own
+        if let Some(container) = args.docker {
+            let mut profile = Profile::new(name, container.clone(), args.user.unwrap_or_default());
+            profile.kind = ProfileKind::Docker { host_profile: args.docker_host, container };
+            profile.tags = args.tags;
+            profile.banner = args.banner;
+
+            self.profile_service.add_profile(profile.clone()).await?;
+            status!("{} {}", theme::success("✓"), tr!("add.profile-added-successfully", profile.name));
+            return Ok(());
+        }
+        if args.pod.is_some() || args.selector.is_some() {
+            let pod = KubernetesPod {
+                context: args.kube_context,
+                namespace: args.namespace,
+                pod: args.pod,
+                selector: args.selector,
+                container: args.container,
+            };
+            let hostname = pod.pod.clone().or_else(|| pod.selector.clone()).unwrap_or_default();
+            let mut profile = Profile::new(name, hostname, args.user.unwrap_or_default());
+            profile.kind = ProfileKind::Kubernetes(pod);
+            profile.tags = args.tags;
+            profile.banner = args.banner;
+
+            self.profile_service.add_profile(profile.clone()).await?;
+            status!("{} {}", theme::success("✓"), tr!("add.profile-added-successfully", profile.name));
+            return Ok(());
+        }
+
+        let hostname = if let Some(host) = args.host {
+            host
+        } else if non_interactive {
+            return Err(anyhow::anyhow!(tr!("add.hostname-is-required-in-non")));
+        } else {
+            Input::<String>::new()
+                .with_prompt(tr!("add.enter-hostname-or-ip-address"))
+                .interact()?
+        };
+
+        let username = if let Some(user) = args.user {
+            user
+        } else if non_interactive {
+            return Err(anyhow::anyhow!(tr!("add.username-is-required-in-non")));
+        } else {
+            Input::<String>::new()
+                .with_prompt(tr!("add.enter-username"))
+                .interact()?
+        };
+
+        let default_port = args.port.unwrap_or(self.settings.defaults.port);
+        let port = if non_interactive {
+            default_port
+        } else {
+            Input::<u16>::new()
+                .with_prompt(tr!("add.enter-port"))
+                .default(default_port)
+                .interact()?
+        };
+
+        let identity_file = if let Some(identity) = args.identity {
+            Some(identity)
+        } else if !non_interactive {
+            let use_identity = self.prompt.confirm(tr!("add.use-identity-file"), false)?;
+
+            if use_identity {
+                Some(Input::<PathBuf>::new()
+                    .with_prompt(tr!("add.enter-identity-file-path"))
+                    .interact()?)
+            } else {
+                None
             }
+        } else {
+            None
         };
 
-        println!("{} Editing profile '{}'", style("→").cyan().bold(), style(&profile.name).green());
-        println!("{} (Press Enter to keep current value)", style("Tip").yellow().italic());
+        // Create a new profile
+        let mut profile = Profile::new(name, hostname, username);
+        profile.port = port;
+
+        if let Some(identity) = identity_file {
+            profile.identity_file = Some(identity);
+        }
+
+        profile.tags = args.tags;
+        profile.identity_passphrase = args.identity_passphrase;
+        profile.password = args.password;
+        profile.vault_role = args.vault_role;
+        profile.resolve_to = args.resolve_to;
+        profile.quiet = args.quiet_ssh;
+        profile.banner = args.banner;
+
+        // Parse options
+        for option in args.options {
+            if let Some(idx) = option.find('=') {
+                let key = option[..idx].to_string();
+                let value = option[idx+1..].to_string();
+                profile.options.insert(key, value);
+            } else {
+                profile.options.insert(option, "".to_string());
+            }
+        }
+
+        if self.alias_instead_of_duplicate(&profile, !non_interactive).await? {
+            return Ok(());
+        }
+
+        // Add the profile
+        match self.profile_service.add_profile(profile.clone()).await {
+            Ok(_) => {
+                status!("{} {}", theme::success("✓"), tr!("add.profile-added-successfully", profile.name));
+
+                // Ask if user wants to add to SSH config
+                if !non_interactive {
+                    let add_to_ssh_config = self.prompt.confirm(tr!("add.add-this-profile-to-ssh"), false)?;
+
+                    if add_to_ssh_config {
+                        match self.ssh_config_service.add_profile_to_ssh_config(&profile).await {
+                            Ok(_) => status!("{} {}", theme::success("✓"), tr!("add.profile-added-to-ssh-config")),
+                            Err(e) => println!("{} {}", theme::error("✗"), tr!("add.failed-to-add-profile-to", e)),
+                        }
+                    }
+
+                    let copy_key = self.prompt.confirm(tr!("add.copy-ssh-key-to-this"), false)?;
+
+                    if copy_key {
+                        let key_path = if let Some(identity) = profile.identity_file {
+                            // Use the specified identity file
+                            identity
+                        } else {
+                            // Use default identity file
+                            crate::utils::home_dir()
+                                .ok_or_else(|| anyhow::anyhow!(tr!("common.could-not-determine-home-directory")))?
+                                .join(".ssh")
+                                .join("id_rsa.pub")
+                        };
+
+                        match self.connection_service.copy_ssh_key(&profile.name, &key_path).await {
+                            Ok(_) => status!("{} {}", theme::success("✓"), tr!("common.ssh-key-copied-successfully")),
+                            Err(e) => println!("{} {}", theme::error("✗"), tr!("common.failed-to-copy-ssh-key", e)),
+                        }
+                    }
+                }
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("add.failed-to-add-profile", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'list' command
+    async fn handle_list(&self, columns: Vec<String>, filter: ProfileFilter) -> anyhow::Result<()> {
+        if self.json_output() {
+            let profiles = self.profile_service.find_profiles(&filter).await?;
+            return self.print_json(&profiles);
+        }
+
+        let mut table = Table::new(vec![
+            Column::new("name", tr!("column.name")),
+            Column::new("host", tr!("column.host")),
+            Column::new("user", tr!("column.user")),
+            Column::new("port", tr!("column.port")),
+            Column::new("identity", tr!("column.identity")),
+            Column::new("tags", tr!("column.tags")),
+            Column::new("last-used", tr!("column.last-used")),
+            Column::new("created", tr!("column.created")),
+        ]);
+
+        let columns = if columns.is_empty() {
+            vec!["name".to_string(), "host".to_string(), "user".to_string(), "port".to_string()]
+        } else {
+            columns
+        };
+        table.select_columns(&columns).map_err(|e| anyhow::anyhow!(e))?;
+
+        let profiles = self.profile_service.find_profiles(&filter).await?;
+
+        if profiles.is_empty() {
+            if filter.is_empty() {
+                status!("{} {}", theme::warning("!"), tr!("list.no-profiles-found-use-add"));
+            } else {
+                status!("{} {}", theme::warning("!"), tr!("list.no-profiles-match-the-given"));
+            }
+            return Ok(());
+        }
+
+        for profile in profiles {
+            table.add_row(vec![
+                theme::highlight(&profile.name).to_string(),
+                profile.target(),
+                profile.username.clone(),
+                if profile.kind.is_ssh() { profile.port.to_string() } else { "-".to_string() },
+                profile.identity_file.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string()),
+                profile.tags.join(","),
+                format_optional_time(profile.last_used, self.time_format),
+                format_optional_time(profile.created_at, self.time_format),
+            ]);
+        }
+
+        println!("{}", theme::heading(tr!("list.available-ssh-profiles")));
+        table.print();
+
+        Ok(())
+    }
+
+    /// Handle the 'connect' command
+    async fn handle_connect(&self, name: Option<String>) -> anyhow::Result<()> {
+        let name = match name {
+            Some(name) => name,
+            None => match self.pick_connection_target().await? {
+                Some(name) => name,
+                None => return Ok(()),
+            },
+        };
+
+        let name = self.resolve_recent_index(name).await?;
+
+        // Resolve alias first
+        let profile_name = match self.alias_service.resolve_alias(&name).await {
+            Ok(resolved) => {
+                if resolved != name {
+                    status!("{} {}", theme::heading("→"), tr!("connect.connecting-via-alias", name, resolved));
+                }
+                resolved
+            },
+            Err(_) => name.clone(),
+        };
+
+        // Get the profile for display
+        match self.profile_service.get_profile(&profile_name).await {
+            Ok(profile) => {
+                status!("{} {}",
+                        theme::success("→"),
+                        tr!("connect.connecting-to",
+                            theme::highlight(&profile.name),
+                            profile.username,
+                            profile.target()));
+
+                if let Ok(command_line) = self.connection_service.ssh_command(&name).await {
+                    detail!("{} {}", theme::muted("$"), shell_join(&command_line));
+                }
+
+                // A warning about the host, so not silenced by --quiet
+                if let Some(banner) = &profile.banner {
+                    println!("{} {}", theme::warning("!"), theme::warning(banner));
+                }
+
+                // Show the code ahead of the server's verification prompt
+                if let Some(totp) = &self.totp_service {
+                    match totp.code(&profile.name, chrono::Utc::now()) {
+                        Ok(Some(code)) => status!("{} {}",
+                                                  theme::accent("→"),
+                                                  tr!("totp.verification-code", theme::highlight(&code.code), code.valid_for)),
+                        Ok(None) => {},
+                        Err(e) => tracing::warn!("Failed to read TOTP secret: {}", e),
+                    }
+                }
+
+                // Connect to the profile
+                self.report_connection(self.connection_service.connect(&name).await);
+            },
+            Err(e) => {
+                // ssh:// URIs and user@host connect without a profile
+                if let Some(uri) = SshUri::parse(&name) {
+                    return self.open_uri(uri).await;
+                }
+                if let Some(target) = AdHocTarget::parse(&name) {
+                    return self.connect_ad_hoc(target, ConnectionArgs::default()).await;
+                }
+
+                self.report_not_found(tr!("common.profile-not-found", e), &self.profile_suggestions(&name, true).await)?;
+                self.offer_profile_for(&name).await?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'totp' command
+    async fn handle_totp(&self, name: String, set: bool, remove: bool) -> anyhow::Result<()> {
+        let Some(totp) = &self.totp_service else {
+            anyhow::bail!(tr!("totp.not-available"));
+        };
+
+        let profile_name = self.alias_service.resolve_alias(&name).await.unwrap_or_else(|_| name.clone());
+        let profile = match self.profile_service.get_profile(&profile_name).await {
+            Ok(profile) => profile,
+            Err(e) => {
+                return self.report_not_found(tr!("common.profile-not-found", e), &self.profile_suggestions(&name, true).await);
+            },
+        };
+
+        if set {
+            let secret = match self.prompt.password(tr!("totp.enter-secret"))? {
+                Some(secret) => secret,
+                None => {
+                    let mut line = Zeroizing::new(String::new());
+                    std::io::stdin().read_line(&mut line)?;
+                    line
+                },
+            };
+
+            register_secret(&secret);
+            totp.set_secret(&profile.name, &secret).await?;
+            status!("{} {}", theme::success("✓"), tr!("totp.secret-stored", theme::highlight(&profile.name)));
+            return Ok(());
+        }
+
+        if remove {
+            if totp.remove_secret(&profile.name).await? {
+                status!("{} {}", theme::success("✓"), tr!("totp.secret-removed", theme::highlight(&profile.name)));
+            } else {
+                status!("{} {}", theme::warning("!"), tr!("totp.no-secret", theme::highlight(&profile.name)));
+            }
+            return Ok(());
+        }
+
+        let Some(code) = totp.code(&profile.name, chrono::Utc::now())? else {
+            println!("{} {}", theme::error("✗"), tr!("totp.no-secret", theme::highlight(&profile.name)));
+            println!("{}", tr!("totp.use-to-store-one", theme::accent(format!("shellbe totp {} --set", profile.name))));
+            return Ok(());
+        };
+
+        if self.json_output() {
+            return self.print_json(&serde_json::json!({
+                "profile": profile.name,
+                "code": code.code,
+                "valid_for": code.valid_for,
+            }));
+        }
+
+        println!("{}", code.code);
+        status!("{}", theme::note(tr!("totp.valid-for", code.valid_for)));
+
+        Ok(())
+    }
+
+    /// Turn a number from 'recent' into the profile it lists; other names pass through
+    ///
+    /// A profile or alias that is literally named with a number takes precedence.
+    async fn resolve_recent_index(&self, name: String) -> anyhow::Result<String> {
+        let Ok(index) = name.parse::<usize>() else {
+            return Ok(name);
+        };
+
+        if self.profile_service.get_profile(&name).await.is_ok()
+            || self.alias_service.resolve_alias(&name).await.is_ok()
+        {
+            return Ok(name);
+        }
+
+        let recent = self.connection_service.get_recent_profiles(index).await?;
+        match index.checked_sub(1).and_then(|i| recent.get(i)) {
+            Some(entry) => {
+                status!("{} {}",
+                        theme::heading("→"),
+                        tr!("resolve-recent-index.recent", index, theme::highlight(&entry.profile_name)));
+                Ok(entry.profile_name.clone())
+            },
+            None => anyhow::bail!(tr!("resolve-recent-index.no-recent-profile-run-shellbe", index)),
+        }
+    }
+
+    /// Print how a connection ended; returns whether it was established
+    fn report_connection(&self, result: Result<i32, DomainError>) -> bool {
+        match result {
+            Ok(0) => {
+                status!("{} {}", theme::success("✓"), tr!("report-connection.connection-closed-successfully"));
+                true
+            },
+            Ok(exit_code) => {
+                status!("{} {}",
+                        theme::warning("!"),
+                        tr!("report-connection.connection-closed-with-exit-code", exit_code));
+                true
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("report-connection.connection-failed", e));
+                false
+            },
+        }
+    }
+
+    /// Handle the 'open' command
+    async fn handle_open(&self, uri: String) -> anyhow::Result<()> {
+        let Some(parsed) = SshUri::parse(&uri) else {
+            anyhow::bail!(tr!("open.not-an-ssh-uri", uri));
+        };
+        self.open_uri(parsed).await
+    }
+
+    /// Connect to the host of an ssh:// URI, or start sftp for an sftp:// one
+    async fn open_uri(&self, uri: SshUri) -> anyhow::Result<()> {
+        let args = ConnectionArgs {
+            sftp: match uri.scheme {
+                UriScheme::Ssh => None,
+                UriScheme::Sftp => Some(uri.sftp_path().unwrap_or_default()),
+            },
+            ..Default::default()
+        };
+        self.connect_ad_hoc(uri.target(&whoami::username()), args).await
+    }
+
+    /// Handle the 'uri' command
+    async fn handle_uri(&self, name: String, sftp: bool) -> anyhow::Result<()> {
+        let profile_name = self.alias_service.resolve_alias(&name).await.unwrap_or_else(|_| name.clone());
+        let profile = match self.profile_service.get_profile(&profile_name).await {
+            Ok(profile) => profile,
+            Err(e) => {
+                return self.report_not_found(tr!("common.profile-not-found", e), &self.profile_suggestions(&name, true).await);
+            },
+        };
+        if !profile.kind.is_ssh() {
+            anyhow::bail!(tr!("uri.not-an-ssh-profile", profile.name));
+        }
+
+        let uri = SshUri::for_profile(&profile, if sftp { UriScheme::Sftp } else { UriScheme::Ssh });
+        if self.json_output() {
+            return self.print_json(&serde_json::json!({
+                "profile": profile.name,
+                "uri": uri.to_string(),
+            }));
+        }
+
+        println!("{}", uri);
+        Ok(())
+    }
+
+    /// Connect to `user@host[:port]` without a profile, then offer to save it
+    async fn connect_ad_hoc(&self, target: AdHocTarget, args: ConnectionArgs) -> anyhow::Result<()> {
+        let mut profile = target.to_profile(target.suggested_name(), self.settings.defaults.port);
+
+        status!("{} {}",
+                theme::success("→"),
+                tr!("connect-ad-hoc.connecting-to-no-saved-profile", theme::highlight(profile.connection_string())));
+
+        if !self.report_connection(self.connection_service.connect_ad_hoc(&profile, &args).await) {
+            return Ok(());
+        }
+
+        if !self.prompt.is_interactive() || !self.prompt.confirm(tr!("connect-ad-hoc.save-as-a-profile", profile.connection_string()), false)? {
+            return Ok(());
+        }
+
+        profile.name = Input::<String>::new()
+            .with_prompt(tr!("connect-ad-hoc.profile-name"))
+            .default(target.suggested_name())
+            .interact()?;
+
+        let name = profile.name.clone();
+        match self.profile_service.add_profile(profile).await {
+            Ok(_) => status!("{} {}",
+                             theme::success("✓"),
+                             tr!("connect-ad-hoc.profile-saved-next-time-use",
+                                 theme::highlight(&name),
+                                 theme::accent(format!("shellbe connect {}", name)))),
+            Err(e) => println!("{} {}", theme::error("✗"), tr!("connect-ad-hoc.failed-to-save-profile", e)),
+        }
+
+        Ok(())
+    }
+
+    /// Warn when another profile already connects to the same host, port and user, and offer
+    /// to make the new name an alias of it; returns whether the alias was created instead
+    async fn alias_instead_of_duplicate(&self, profile: &Profile, interactive: bool) -> anyhow::Result<bool> {
+        let Some(existing) = self.profile_service.find_duplicate(profile).await? else {
+            return Ok(false);
+        };
+
+        status!("{} {}",
+                theme::warning("!"),
+                tr!("duplicate.same-host-as",
+                    theme::highlight(&profile.name),
+                    theme::highlight(&existing.name),
+                    format!("{}:{}", existing.connection_string(), existing.port)));
+
+        if !interactive || !self.prompt.confirm(tr!("duplicate.create-alias-instead", profile.name, existing.name), true)? {
+            return Ok(false);
+        }
+
+        self.alias_service.create_alias(&profile.name, &existing.name).await?;
+        status!("{} {}", theme::success("✓"), tr!("duplicate.alias-created", theme::highlight(&profile.name), theme::highlight(&existing.name)));
+        Ok(true)
+    }
+
+    /// Offer to create a profile for a name that doesn't exist yet, then connect to it
+    async fn offer_profile_for(&self, name: &str) -> anyhow::Result<()> {
+        if !self.prompt.is_interactive() || !self.prompt.confirm(tr!("offer-profile-for.create-a-profile-named", name), false)? {
+            return Ok(());
+        }
+
+        let hostname = Input::<String>::new()
+            .with_prompt(tr!("offer-profile-for.hostname-or-ip-address"))
+            .default(name.to_string())
+            .interact()?;
+
+        let username = Input::<String>::new()
+            .with_prompt(tr!("common.username"))
+            .default(whoami::username())
+            .interact()?;
+
+        let port = Input::<u16>::new()
+            .with_prompt(tr!("common.port"))
+            .default(self.settings.defaults.port)
+            .interact()?;
+
+        let mut profile = Profile::new(name, hostname, username);
+        profile.port = port;
+
+        if let Err(e) = self.profile_service.add_profile(profile).await {
+            println!("{} {}", theme::error("✗"), tr!("offer-profile-for.failed-to-create-profile", e));
+            return Ok(());
+        }
+
+        status!("{} {}", theme::success("✓"), tr!("offer-profile-for.profile-created", theme::highlight(name)));
+
+        if self.prompt.confirm(tr!("offer-profile-for.connect-now"), true)? {
+            self.report_connection(self.connection_service.connect(name).await);
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'show' command
+    async fn handle_show(&self, name: String) -> anyhow::Result<()> {
+        // Resolve alias first
+        let profile_name = self.alias_service.resolve_alias(&name).await.unwrap_or_else(|_| name.clone());
+
+        let profile = match self.profile_service.get_profile(&profile_name).await {
+            Ok(profile) => profile,
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("common.profile-not-found", e));
+                return Ok(());
+            },
+        };
+
+        // Aliases that end up at this profile, directly or through other aliases
+        let mut aliases = Vec::new();
+        for alias in self.alias_service.list_aliases().await? {
+            let resolution = self.alias_service.diagnose_alias(&alias.name).await?;
+            if resolution.status == AliasStatus::Resolved && resolution.last() == profile.name {
+                aliases.push((alias, resolution.chain));
+            }
+        }
+
+        // Most recent connections first
+        let mut history = self.connection_service.get_profile_history(&profile.name).await?;
+        history.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        history.truncate(5);
+
+        let ssh_command = self.connection_service.ssh_command(&name).await?;
+
+        if self.json_output() {
+            return self.print_json(&serde_json::json!({
+                "profile": profile,
+                "aliases": aliases.iter().map(|(alias, chain)| serde_json::json!({
+                    "name": alias.name,
+                    "chain": chain,
+                    "args": alias.args,
+                    "command": alias.command,
+                })).collect::<Vec<_>>(),
+                "recent_history": history,
+                "ssh_command": ssh_command,
+            }));
+        }
+
+        if profile_name != name {
+            status!("{} {}", theme::heading("→"), tr!("show.is-an-alias-for", name, profile_name));
+        }
+
+        println!("{}", theme::heading(tr!("show.profile", profile.name)));
+        println!("{}", theme::note("-------------------------------------"));
+        println!("{:<15} {}", theme::accent(tr!("show.host")), profile.target());
+        if let Some(address) = profile.resolve_to {
+            println!("{:<15} {}", theme::accent(tr!("show.resolves-to")), address);
+        }
+        println!("{:<15} {}", theme::accent(tr!("show.user")), profile.username);
+        println!("{:<15} {}", theme::accent(tr!("common.port")), profile.port);
+        println!("{:<15} {}", theme::accent(tr!("show.identity")),
+                 profile.identity_file.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string()));
+        println!("{:<15} {}", theme::accent(tr!("show.created")), format_optional_time(profile.created_at, self.time_format));
+        println!("{:<15} {}", theme::accent(tr!("show.updated")), format_optional_time(profile.updated_at, self.time_format));
+        println!("{:<15} {}", theme::accent(tr!("show.last-used")), format_optional_time(profile.last_used, self.time_format));
+        if !profile.tags.is_empty() {
+            println!("{:<15} {}", theme::accent(tr!("show.tags")), profile.tags.join(", "));
+        }
+        if let Some(banner) = &profile.banner {
+            println!("{:<15} {}", theme::accent(tr!("show.banner")), banner);
+        }
+
+        // Port forwards are stored as regular SSH options
+        let mut options: Vec<_> = profile.options.iter().collect();
+        options.sort();
+        let (forwards, options): (Vec<_>, Vec<_>) = options.into_iter()
+            .partition(|(key, _)| key.to_ascii_lowercase().ends_with("forward"));
+
+        if !forwards.is_empty() {
+            println!("\n{}", theme::heading(tr!("show.forwards")));
+            for (key, value) in forwards {
+                println!("  {} {}", key, value);
+            }
+        }
+
+        if !options.is_empty() {
+            println!("\n{}", theme::heading(tr!("show.options")));
+            for (key, value) in options {
+                println!("  {} = {}", key, value);
+            }
+        }
+
+        println!("\n{}", theme::heading(tr!("show.aliases")));
+        if aliases.is_empty() {
+            println!("  -");
+        }
+        for (alias, chain) in &aliases {
+            println!("  {} {}", chain.join(" → "), format_connection_args(&alias.connection_args()));
+        }
+
+        println!("\n{}", theme::heading(tr!("show.recent-connections")));
+        if history.is_empty() {
+            println!("  -");
+        }
+        for entry in &history {
+            let duration = entry.duration.map(format_duration).unwrap_or_else(|| "-".to_string());
+            let exit = entry.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string());
+            println!("  {:<19}  {:<10} {}",
+                     format_time(entry.timestamp, self.time_format),
+                     duration,
+                     tr!("show.exit", exit));
+        }
+
+        println!("\n{}", theme::heading(tr!("show.ssh-command")));
+        println!("  {}", shell_join(&ssh_command));
+
+        Ok(())
+    }
+
+    /// Handle the 'env' command: print everything a connection would use
+    async fn handle_env(&self, name: String) -> anyhow::Result<()> {
+        let resolved = match self.connection_service.resolve(&name).await {
+            Ok(resolved) => resolved,
+            Err(e @ DomainError::ProfileNotFound(_)) => {
+                return self.report_not_found(tr!("common.profile-not-found", e), &self.profile_suggestions(&name, true).await);
+            },
+            Err(e) => return Err(e.into()),
+        };
+        let profile = &resolved.profile;
+
+        let mut options: Vec<_> = profile.options.iter().collect();
+        options.sort();
+        let source = |key: &String| resolved.option_sources.get(key).copied().unwrap_or(OptionSource::Profile);
+
+        // Jump hosts are stored as a regular SSH option
+        let jump_host = profile.options.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("ProxyJump"))
+            .map(|(_, value)| value.clone());
+        let vault_mode = profile.vault_role.as_ref().map(|_| self.settings.vault_ssh.mode);
+
+        if self.json_output() {
+            return self.print_json(&serde_json::json!({
+                "name": name,
+                "aliases": resolved.aliases,
+                "profile": profile.name,
+                "hostname": profile.hostname,
+                "kind": profile.kind,
+                "resolve_to": profile.resolve_to,
+                "banner": profile.banner,
+                "username": profile.username,
+                "port": profile.port,
+                "identity_file": profile.identity_file,
+                "jump_host": jump_host,
+                "options": options.iter().map(|(key, value)| serde_json::json!({
+                    "name": key,
+                    "value": value,
+                    "source": source(key).as_str(),
+                })).collect::<Vec<_>>(),
+                "ssh_args": resolved.args.ssh_args,
+                "command": resolved.args.command,
+                "vault_role": profile.vault_role,
+                "vault_mode": vault_mode,
+                "plugins": resolved.plugins,
+                "ssh_command": resolved.command_line,
+            }));
+        }
+
+        println!("{}", theme::heading(tr!("env.resolved", name)));
+        println!("{}", theme::note("-------------------------------------"));
+        if !resolved.aliases.is_empty() {
+            let mut chain = resolved.aliases.clone();
+            chain.push(profile.name.clone());
+            println!("{:<15} {}", theme::accent(tr!("env.via")), chain.join(" → "));
+        }
+        println!("{:<15} {}", theme::accent(tr!("env.profile")), profile.name);
+        println!("{:<15} {}", theme::accent(tr!("show.host")), profile.target());
+        if let Some(address) = profile.resolve_to {
+            println!("{:<15} {}", theme::accent(tr!("show.resolves-to")), address);
+        }
+        println!("{:<15} {}", theme::accent(tr!("show.user")), profile.username);
+        println!("{:<15} {}", theme::accent(tr!("common.port")), profile.port);
+        println!("{:<15} {}", theme::accent(tr!("show.identity")),
+                 profile.identity_file.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string()));
+        println!("{:<15} {}", theme::accent(tr!("env.jump-host")), jump_host.as_deref().unwrap_or("-"));
+        if let (Some(role), Some(mode)) = (&profile.vault_role, vault_mode) {
+            println!("{:<15} {}", theme::accent(tr!("env.vault-role")), tr!("env.vault-role-mode", role, mode));
+        }
+
+        if !options.is_empty() {
+            println!("\n{}", theme::heading(tr!("show.options")));
+            for (key, value) in &options {
+                match source(key) {
+                    OptionSource::Policy => println!("  {} = {} {}", key, value, theme::muted(tr!("env.from-policy"))),
+                    OptionSource::Profile => println!("  {} = {}", key, value),
+                }
+            }
+        }
+
+        if !resolved.args.is_empty() {
+            println!("\n{}", theme::heading(tr!("env.alias-arguments")));
+            println!("  {}", format_connection_args(&resolved.args));
+        }
+
+        if !resolved.plugins.is_empty() {
+            println!("\n{}", theme::heading(tr!("env.plugins")));
+            println!("  {}", resolved.plugins.join(", "));
+        }
+
+        println!("\n{}", theme::heading(tr!("show.ssh-command")));
+        println!("  {}", shell_join(&resolved.command_line));
+
+        Ok(())
+    }
+
+    /// Let the user fuzzy-search profiles and aliases, most recently used first
+    async fn pick_connection_target(&self) -> anyhow::Result<Option<String>> {
+        if !self.prompt.is_interactive() {
+            anyhow::bail!(tr!("common.profile-name-is-required-in"));
+        }
+
+        let profiles = self.profile_service.list_profiles().await?;
+        let aliases = self.alias_service.list_aliases().await?;
+
+        // (name, label, last used)
+        let mut choices: Vec<(String, String, Option<chrono::DateTime<chrono::Utc>>)> = Vec::new();
+
+        for profile in &profiles {
+            choices.push((
+                profile.name.clone(),
+                format!("{} ({}@{})", profile.name, profile.username, profile.hostname),
+                profile.last_used,
+            ));
+        }
+
+        for alias in aliases.iter().filter(|a| !a.is_template()) {
+            // Aliases inherit the recency of the profile they resolve to
+            let last_used = match self.alias_service.resolve_alias(&alias.name).await {
+                Ok(profile_name) => profiles.iter()
+                    .find(|p| p.name == profile_name)
+                    .and_then(|p| p.last_used),
+                Err(_) => continue,
+            };
+
+            choices.push((
+                alias.name.clone(),
+                format!("{} → {}", alias.name, alias.target),
+                last_used,
+            ));
+        }
+
+        if choices.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-profiles-found-use-add"));
+            return Ok(None);
+        }
+
+        // Most recently used first, never-used entries last
+        choices.sort_by(|a, b| b.2.cmp(&a.2));
+
+        let labels: Vec<&str> = choices.iter().map(|c| c.1.as_str()).collect();
+        let selection = self.prompt.fuzzy_select(tr!("pick-connection-target.connect-to"), &labels)?;
+
+        Ok(selection.map(|i| choices[i].0.clone()))
+    }
+
+    /// Handle the 'copy-id' command
+    async fn handle_copy_id(&self, name: Option<String>, identity: Option<PathBuf>, all: bool, jobs: usize) -> anyhow::Result<()> {
+        let Some(key_path) = self.resolve_copy_id_key(identity).await? else {
+            return Ok(());
+        };
+
+        let name = match name {
+            Some(name) if !all => name,
+            _ => return self.handle_copy_id_all(&key_path, jobs).await,
+        };
+
+        status!("{} {}",
+                theme::heading("→"),
+                tr!("copy-id.copying-ssh-key-to", key_path.display(), theme::highlight(&name)));
+
+        match self.connection_service.copy_ssh_key(&name, &key_path).await {
+            Ok(_) => {
+                status!("{} {}", theme::success("✓"), tr!("common.ssh-key-copied-successfully"));
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("common.failed-to-copy-ssh-key", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'copy-id --all' command
+    async fn handle_copy_id_all(&self, key_path: &Path, jobs: usize) -> anyhow::Result<()> {
+        let profiles = self.profile_service.list_profiles().await?;
+
+        if profiles.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-profiles-found-use-add"));
+            return Ok(());
+        }
+
+        let bar = progress::items_bar(profiles.len() as u64);
+        let mut failed = 0;
+
+        // Copying a key may ask for a password, so keep the bar out of its way; with
+        // several copies at once, prompts can't be told apart anyway
+        let paused = (jobs <= 1).then(|| progress::pause(&bar));
+
+        let names: Vec<String> = profiles.iter().map(|p| p.name.clone()).collect();
+        let pool = TaskPool::new(jobs);
+        let ctrl_c = pool.canceller().cancel_on_ctrl_c();
+
+        let outcome = pool.run(names, |name| {
+            let bar = &bar;
+            async move {
+                bar.set_message(tr!("copy-id.copying-to", theme::highlight(&name)));
+                self.connection_service.copy_ssh_key(&name, key_path).await
+            }
+        }, |name, result| {
+            bar.suspend(|| match result {
+                Ok(_) => status!("{} {}", theme::success("✓"), theme::highlight(name)),
+                Err(e) => println!("{} {}: {}", theme::error("✗"), theme::highlight(name), e),
+            });
+
+            if result.is_err() {
+                failed += 1;
+            }
+            bar.inc(1);
+        }).await;
+
+        ctrl_c.abort();
+        drop(paused);
+        bar.finish_and_clear();
+
+        if !outcome.cancelled.is_empty() {
+            println!("{} {}", theme::warning("!"), tr!("common.bulk-cancelled", outcome.cancelled.len()));
+            failed += outcome.cancelled.len();
+        }
+
+        if failed == 0 {
+            status!("{} {}", theme::success("✓"), tr!("copy-id.copied-to-all-profiles", key_path.display(), profiles.len()));
+        } else {
+            println!("{} {}", theme::error("✗"), tr!("copy-id.copies-failed", failed, profiles.len()));
+        }
+
+        Ok(())
+    }
+
+    /// The public key to copy, offering to generate it when it doesn't exist yet
+    ///
+    /// Returns `None` when the user declines to generate a missing key.
+    async fn resolve_copy_id_key(&self, identity: Option<PathBuf>) -> anyhow::Result<Option<PathBuf>> {
+        // Get the key path
+        let key_path = if let Some(identity) = identity {
+            identity
+        } else {
+            // Use default identity file
+            crate::utils::home_dir()
+                .ok_or_else(|| anyhow::anyhow!(tr!("common.could-not-determine-home-directory")))?
+                .join(".ssh")
+                .join("id_rsa.pub")
+        };
+
+        // Check if key exists
+        if !key_path.exists() {
+            println!("{} {}", theme::error("✗"), tr!("copy-id.key-file-not-found", key_path.display()));
+
+            // Ask if user wants to generate a key
+            let generate_key = self.prompt.confirm(tr!("copy-id.generate-a-new-ssh-key"), true)?;
+
+            if generate_key {
+                let key_name = key_path.file_stem()
+                    .ok_or_else(|| anyhow::anyhow!(tr!("copy-id.invalid-key-file-name")))?
+                    .to_string_lossy()
+                    .to_string();
+
+                // Keep the type the file name suggests, e.g. id_rsa
+                let key_type = ["rsa", "ed25519"].into_iter()
+                    .find(|t| key_name.contains(t))
+                    .map(str::to_string);
+
+                self.handle_generate_key(Some(key_name), None, key_type).await?;
+            } else {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(key_path))
+    }
+
+    /// Handle the 'generate-key' command
+    async fn handle_generate_key(&self, name: Option<String>, comment: Option<String>, key_type: Option<String>) -> anyhow::Result<()> {
+        let key_type = key_type.unwrap_or_else(|| self.settings.defaults.key_type.clone());
+        let name = name.unwrap_or_else(|| format!("id_{}", key_type));
+
+        status!("{} {}", theme::heading("→"), tr!("generate-key.generating-a-new-ssh-key", key_type));
+
+        // Get or create SSH directory
+        let ssh_dir = crate::utils::home_dir()
+            .ok_or_else(|| anyhow::anyhow!(tr!("common.could-not-determine-home-directory")))?
+            .join(".ssh");
+
+        if !ssh_dir.exists() {
+            std::fs::create_dir_all(&ssh_dir)?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let metadata = std::fs::metadata(&ssh_dir)?;
+                let mut permissions = metadata.permissions();
+                permissions.set_mode(0o700);
+                std::fs::set_permissions(&ssh_dir, permissions)?;
+            }
+        }
+
+        match self.connection_service.generate_key(&name, &key_type, comment.as_deref()).await {
+            Ok((private_key, public_key)) => {
+                status!("{} {}", theme::success("✓"), tr!("generate-key.ssh-key-pair-generated-successfully"));
+                println!("  {}", tr!("generate-key.private-key", theme::accent(private_key.display())));
+                println!("  {}", tr!("generate-key.public-key", theme::accent(public_key.display())));
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("generate-key.failed-to-generate-ssh-key", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'alias' command
+    async fn handle_alias(&self, args: AliasArgs) -> anyhow::Result<()> {
+        if let Some(AliasCommands::Rename { old, new }) = args.command {
+            return self.handle_alias_rename(old, new).await;
+        }
+
+        let name = args.name.ok_or_else(|| anyhow::anyhow!(tr!("alias.alias-name-is-required")))?;
+        let profile = args.profile.ok_or_else(|| anyhow::anyhow!(tr!("alias.target-profile-is-required")))?;
+
+        let connection_args = ConnectionArgs {
+            ssh_args: args.ssh_args,
+            command: args.exec,
+            ..Default::default()
+        };
+
+        // Create alias
+        match self.alias_service.create_alias_with_args(&name, &profile, connection_args.clone()).await {
+            Ok(_) => {
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("alias.alias-created-for-profile", theme::highlight(&name), theme::highlight(&profile)));
+
+                if !connection_args.is_empty() {
+                    status!("{} {}",
+                            theme::accent("→"),
+                            tr!("alias.applies", format_connection_args(&connection_args)));
+                }
+
+                // Create shell alias if requested
+                if args.shell_alias && !template_params(&name).is_empty() {
+                    status!("{} {}", theme::warning("!"), tr!("alias.shell-aliases-cant-be-created"));
+                } else if args.shell_alias {
+                    self.create_shell_alias(&name).await?;
+                }
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("alias.failed-to-create-alias", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'alias rename' command
+    async fn handle_alias_rename(&self, old: String, new: String) -> anyhow::Result<()> {
+        match self.alias_service.rename_alias(&old, &new).await {
+            Ok(_) => {
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("alias-rename.alias-renamed-to", theme::note(&old), theme::highlight(&new)));
+
+                // Keep a shell alias created with --shell-alias in sync
+                self.rename_shell_alias(&old, &new).await?;
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("alias-rename.failed-to-rename-alias", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'unalias' command
+    async fn handle_unalias(&self, name: String) -> anyhow::Result<()> {
+        match self.alias_service.remove_alias(&name).await {
+            Ok(_) => {
+                status!("{} {}", theme::success("✓"), tr!("unalias.alias-removed", name));
+                self.remove_shell_alias(&name).await?;
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("unalias.failed-to-remove-alias", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Helper method to detect the user's shell rc file
+    ///
+    /// On Windows `SHELL` is only set inside POSIX shells like Git Bash, so without it
+    /// aliases go to the PowerShell profile.
+    fn shell_rc_file(&self) -> anyhow::Result<PathBuf> {
+        let shell_rc_file = if let Ok(shell) = std::env::var("SHELL") {
+            if shell.contains("zsh") {
+                crate::utils::home_dir().map(|h| h.join(".zshrc"))
+            } else if shell.contains("bash") {
+                crate::utils::home_dir().map(|h| h.join(".bashrc"))
+            } else {
+                crate::utils::home_dir().map(|h| h.join(".profile"))
+            }
+        } else if cfg!(windows) {
+            powershell_profile()
+        } else {
+            crate::utils::home_dir().map(|h| h.join(".bashrc"))
+        };
+
+        shell_rc_file.ok_or_else(|| anyhow::anyhow!(tr!("shell-rc-file.could-not-determine-shell-configuration")))
+    }
+
+    /// Helper method to load the manifest of shell aliases created by ShellBe
+    async fn shell_alias_manifest(&self) -> anyhow::Result<ShellAliasManifest> {
+        let data_dir = crate::utils::shellbe_data_dir().await?;
+        Ok(ShellAliasManifest::load(data_dir.join(SHELL_ALIAS_MANIFEST))?)
+    }
+
+    /// Helper method to rename a shell alias previously added to the rc file
+    async fn rename_shell_alias(&self, old_name: &str, new_name: &str) -> anyhow::Result<()> {
+        let mut manifest = self.shell_alias_manifest().await?;
+        let mut changed = false;
+
+        // Rename the shell alias for the alias itself
+        if let Some(mut entry) = manifest.forget(old_name) {
+            remove_shell_alias_line(&entry.rc_file, old_name)?;
+            add_shell_alias_line(&entry.rc_file, new_name, new_name)?;
+            entry.name = new_name.to_string();
+            entry.target = new_name.to_string();
+            manifest.record(entry);
+            changed = true;
+        }
+
+        // Repoint shell aliases that connect through the old name
+        let dependents: Vec<ShellAliasEntry> = manifest.entries().iter()
+            .filter(|e| e.target == old_name)
+            .cloned()
+            .collect();
+
+        for mut entry in dependents {
+            remove_shell_alias_line(&entry.rc_file, &entry.name)?;
+            add_shell_alias_line(&entry.rc_file, &entry.name, new_name)?;
+            entry.target = new_name.to_string();
+            manifest.record(entry);
+            changed = true;
+        }
+
+        if changed {
+            manifest.save()?;
+            status!("{} {}", theme::success("✓"), tr!("rename-shell-alias.shell-aliases-updated"));
+        }
+
+        Ok(())
+    }
+
+    /// Helper method to remove a shell alias created by ShellBe
+    async fn remove_shell_alias(&self, alias_name: &str) -> anyhow::Result<()> {
+        let mut manifest = self.shell_alias_manifest().await?;
+
+        if let Some(entry) = manifest.forget(alias_name) {
+            if remove_shell_alias_line(&entry.rc_file, &entry.name)? {
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("remove-shell-alias.shell-alias-removed-from", entry.name, entry.rc_file.display()));
+            }
+            manifest.save()?;
+        }
+
+        Ok(())
+    }
+
+    /// Helper method to create a shell alias
+    async fn create_shell_alias(&self, alias_name: &str) -> anyhow::Result<()> {
+        // Detect user's shell and corresponding rc file
+        let shell_rc_file = self.shell_rc_file()?;
+
+        // Connect through the alias, not its profile, so the alias's arguments apply
+        let added = add_shell_alias_line(&shell_rc_file, alias_name, alias_name)?;
+
+        // Track the alias so it can be cleaned up later
+        let mut manifest = self.shell_alias_manifest().await?;
+        manifest.record(ShellAliasEntry {
+            name: alias_name.to_string(),
+            target: alias_name.to_string(),
+            rc_file: shell_rc_file.clone(),
+            created_at: chrono::Utc::now(),
+        });
+        manifest.save()?;
+
+        if !added {
+            status!("{} {}",
+                    theme::warning("!"),
+                    tr!("create-shell-alias.shell-alias-already-exists-in", alias_name, shell_rc_file.display()));
+            return Ok(());
+        }
+
+        status!("{} {}",
+                theme::success("✓"),
+                tr!("create-shell-alias.shell-alias-added-to", alias_name, shell_rc_file.display()));
+        if is_powershell_profile(&shell_rc_file) {
+            status!("{} {}",
+                    theme::warning("!"),
+                    tr!("create-shell-alias.to-use-this-alias-restart-powershell", shell_rc_file.display()));
+        } else {
+            status!("{} {}",
+                    theme::warning("!"),
+                    tr!("create-shell-alias.to-use-this-alias-restart", shell_rc_file.display()));
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'aliases sync-shell' command
+    async fn handle_aliases_sync_shell(&self, remove_shell: bool) -> anyhow::Result<()> {
+        let mut manifest = self.shell_alias_manifest().await?;
+        let entries: Vec<ShellAliasEntry> = manifest.entries().to_vec();
+
+        if entries.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("aliases-sync-shell.no-shell-aliases-created-by"));
+            return Ok(());
+        }
+
+        let aliases = if remove_shell {
+            Vec::new()
+        } else {
+            self.alias_service.list_aliases().await?
+        };
+
+        let mut added = 0;
+        let mut removed = 0;
+
+        for mut entry in entries {
+            match aliases.iter().find(|a| a.name == entry.name).cloned() {
+                Some(_) => {
+                    // Keep the line in place and connecting through the alias; lines from
+                    // older versions point at the profile and would skip the alias's arguments
+                    if entry.target != entry.name {
+                        remove_shell_alias_line(&entry.rc_file, &entry.name)?;
+                        entry.target = entry.name.clone();
+                    }
+
+                    if add_shell_alias_line(&entry.rc_file, &entry.name, &entry.target)? {
+                        status!("{} {}",
+                                theme::success("✓"),
+                                tr!("aliases-sync-shell.restored-shell-alias-in", entry.name, entry.rc_file.display()));
+                        added += 1;
+                    }
+
+                    manifest.record(entry);
+                },
+                None => {
+                    // The alias is gone (or everything is being removed)
+                    if remove_shell_alias_line(&entry.rc_file, &entry.name)? {
+                        status!("{} {}",
+                                theme::success("✓"),
+                                tr!("aliases-sync-shell.removed-shell-alias-from",
+                                    entry.name,
+                                    entry.rc_file.display()));
+                        removed += 1;
+                    }
+
+                    manifest.forget(&entry.name);
+                },
+            }
+        }
+
+        manifest.save()?;
+
+        status!("{} {}",
+                theme::success("✓"),
+                tr!("aliases-sync-shell.shell-aliases-synced-added-removed", added, removed));
+
+        Ok(())
+    }
+
+    /// Handle the 'aliases' command
+    async fn handle_aliases(&self, args: AliasesArgs) -> anyhow::Result<()> {
+        if let Some(AliasesCommands::SyncShell { remove_shell }) = args.command {
+            return self.handle_aliases_sync_shell(remove_shell).await;
+        }
+
+        let aliases = self.alias_service.list_aliases().await?;
+
+        if aliases.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("aliases.no-aliases-found-use-alias"));
+            return Ok(());
+        }
+
+        let mut table = Table::new(vec![
+            Column::new("alias", tr!("column.alias")),
+            Column::new("resolution", tr!("column.resolution")),
+            Column::new("status", tr!("column.status")),
+            Column::new("args", tr!("column.args")),
+        ]);
+        let mut broken = 0;
+
+        for alias in aliases {
+            let resolution = self.alias_service.diagnose_alias(&alias.name).await?;
+
+            let status = match resolution.status {
+                AliasStatus::Resolved => theme::highlight(tr!("aliases.ok")),
+                AliasStatus::Template => theme::accent(tr!("aliases.template")),
+                AliasStatus::Dangling => {
+                    broken += 1;
+                    theme::error(tr!("aliases.dangling"))
+                },
+                AliasStatus::Circular => {
+                    broken += 1;
+                    theme::error(tr!("aliases.circular"))
+                },
+            };
+
+            table.add_row(vec![
+                theme::highlight(&alias.name).to_string(),
+                resolution.chain[1..].join(" → "),
+                status.to_string(),
+                format_connection_args(&alias.connection_args()),
+            ]);
+        }
+
+        println!("{}", theme::heading(tr!("aliases.available-connection-aliases")));
+        table.print();
+
+        if broken > 0 {
+            println!();
+            status!("{} {}", theme::warning("!"), tr!("aliases.alias-es-no-longer-reach", broken));
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'remove' command
+    async fn handle_remove(&self, name: String) -> anyhow::Result<()> {
+        // Ask for confirmation
+        let confirm = self.prompt.confirm(tr!("remove.are-you-sure-you-want", name), false)?;
+
+        if !confirm {
+            status!("{} {}", theme::warning("!"), tr!("remove.operation-cancelled"));
+            return Ok(());
+        }
+
+        // Remove profile
+        match self.profile_service.remove_profile(&name).await {
+            Ok(_) => {
+                status!("{} {}", theme::success("✓"), tr!("remove.profile-removed-successfully", name));
+
+                if let Some(totp) = &self.totp_service {
+                    if let Err(e) = totp.remove_secret(&name).await {
+                        tracing::warn!("Failed to remove TOTP secret: {}", e);
+                    }
+                }
+
+                // Ask if user wants to remove from SSH config
+                let remove_from_ssh_config = self.prompt.confirm(tr!("remove.remove-this-profile-from-ssh"), false)?;
+
+                if remove_from_ssh_config {
+                    match self.ssh_config_service.remove_profile_from_ssh_config(&name).await {
+                        Ok(_) => status!("{} {}", theme::success("✓"), tr!("remove.profile-removed-from-ssh-config")),
+                        Err(e) => println!("{} {}", theme::error("✗"), tr!("common.failed-to-remove-profile-from", e)),
+                    }
+                }
+
+                // List and remove aliases pointing to this profile
+                match self.alias_service.get_aliases_for_profile(&name).await {
+                    Ok(aliases) => {
+                        if !aliases.is_empty() {
+                            status!("{} {}", theme::warning("!"), tr!("remove.found-aliases-pointing-to-this"));
+
+                            for alias in &aliases {
+                                println!("  - {}", theme::note(&alias.name));
+                            }
+
+                            let remove_aliases = self.prompt.confirm(tr!("remove.remove-these-aliases"), true)?;
+
+                            if remove_aliases {
+                                for alias in aliases {
+                                    match self.alias_service.remove_alias(&alias.name).await {
+                                        Ok(_) => {
+                                            status!("{} {}",
+                                                    theme::success("✓"),
+                                                    tr!("remove.removed-alias", alias.name));
+                                            self.remove_shell_alias(&alias.name).await?;
+                                        },
+                                        Err(e) => println!("{} {}",
+                                                           theme::error("✗"),
+                                                           tr!("remove.failed-to-remove-alias", alias.name, e)),
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        status!("{} {}", theme::warning("!"), tr!("remove.error-checking-for-aliases", e));
+                    },
+                }
+            },
+            Err(e) => {
+                if matches!(e, DomainError::ProfileNotFound(_)) {
+                    return self.report_not_found(tr!("remove.failed-to-remove-profile", e), &self.profile_suggestions(&name, false).await);
+                }
+                println!("{} {}", theme::error("✗"), tr!("remove.failed-to-remove-profile", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'edit' command
+    async fn handle_edit(&self, name: String, editor: bool) -> anyhow::Result<()> {
+        if !self.prompt.is_interactive() {
+            anyhow::bail!(tr!("edit.edit-needs-an-interactive-terminal"));
+        }
+
+        // Get the profile
+        let profile = match self.profile_service.get_profile(&name).await {
+            Ok(p) => p,
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("edit.failed-to-get-profile", e));
+                return Ok(());
+            }
+        };
+
+        status!("{} {}", theme::heading("→"), tr!("edit.editing-profile", theme::highlight(&profile.name)));
+
+        let updated_profile = if editor {
+            match self.edit_profile_in_editor(&profile)? {
+                Some(updated) => updated,
+                None => {
+                    status!("{} {}", theme::warning("!"), tr!("edit.edit-cancelled"));
+                    return Ok(());
+                },
+            }
+        } else {
+            self.prompt_profile_changes(&profile)?
+        };
+
+        if updated_profile == profile {
+            status!("{} {}", theme::warning("!"), tr!("edit.no-changes-made"));
+            return Ok(());
+        }
+
+        // Update the profile
+        match self.profile_service.update_profile(updated_profile.clone()).await {
+            Ok(_) => {
+                status!("{} {}", theme::success("✓"), tr!("edit.profile-updated-successfully", name));
+
+                // Ask if user wants to update SSH config
+                let update_ssh_config = self.prompt.confirm(tr!("edit.update-this-profile-in-ssh"), false)?;
+
+                if update_ssh_config {
+                    match self.ssh_config_service.remove_profile_from_ssh_config(&name).await {
+                        Ok(_) => {
+                            match self.ssh_config_service.add_profile_to_ssh_config(&updated_profile).await {
+                                Ok(_) => status!("{} {}",
+                                                 theme::success("✓"),
+                                                 tr!("edit.profile-updated-in-ssh-config")),
+                                Err(e) => println!("{} {}",
+                                                   theme::error("✗"),
+                                                   tr!("edit.failed-to-update-profile-in", e)),
+                            }
+                        },
+                        Err(e) => println!("{} {}", theme::error("✗"), tr!("common.failed-to-remove-profile-from", e)),
+                    }
+                }
+            },
+            Err(e) => {
+                println!("{} {}", theme::error("✗"), tr!("edit.failed-to-update-profile", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Ask for new values field by field
+    fn prompt_profile_changes(&self, profile: &Profile) -> anyhow::Result<Profile> {
+        println!("{}",
+                 tr!("prompt-profile-changes.press-enter-to-keep-current",
+                     theme::note(tr!("prompt-profile-changes.tip"))));
+
+        // Edit each field
+        let hostname = Input::<String>::new()
+            .with_prompt(tr!("prompt-profile-changes.hostname"))
+            .with_initial_text(&profile.hostname)
+            .allow_empty(true)
+            .interact()?;
+
+        let username = Input::<String>::new()
+            .with_prompt(tr!("common.username"))
+            .with_initial_text(&profile.username)
+            .allow_empty(true)
+            .interact()?;
+
+        let port = Input::<u16>::new()
+            .with_prompt(tr!("common.port"))
+            .with_initial_text(&profile.port.to_string())
+            .allow_empty(true)
+            .interact()?;
+
+        let identity_file = Input::<String>::new()
+            .with_prompt(tr!("prompt-profile-changes.identity-file"))
+            .with_initial_text(profile.identity_file.as_ref().map_or("", |p| p.to_str().unwrap_or("")))
+            .allow_empty(true)
+            .interact()?;
+
+        // Create updated profile
+        let mut updated_profile = profile.clone();
+
+        if !hostname.is_empty() {
+            updated_profile.hostname = hostname;
+        }
+
+        if !username.is_empty() {
+            updated_profile.username = username;
+        }
+
+        updated_profile.port = port;
+
+        if !identity_file.is_empty() {
+            updated_profile.identity_file = Some(PathBuf::from(identity_file));
+        } else {
+            updated_profile.identity_file = None;
+        }
+
+        // Update options
+        let update_options = self.prompt.confirm(tr!("prompt-profile-changes.update-ssh-options"), false)?;
+
+        if update_options {
+            // Show current options
+            if !updated_profile.options.is_empty() {
+                status!("{} {}", theme::accent("→"), tr!("prompt-profile-changes.current-options"));
+                for (key, value) in &updated_profile.options {
+                    println!("  {} = {}", key, value);
+                }
+            }
+
+            // Clear or add options
+            let clear_options = self.prompt.confirm(tr!("prompt-profile-changes.clear-all-options"), false)?;
+
+            if clear_options {
+                updated_profile.options.clear();
+            }
+
+            let add_options = self.prompt.confirm(tr!("prompt-profile-changes.add-new-options"), true)?;
+
+            if add_options {
+                loop {
+                    let key = Input::<String>::new()
+                        .with_prompt(tr!("prompt-profile-changes.option-key-empty-to-finish"))
+                        .allow_empty(true)
+                        .interact()?;
+
+                    if key.is_empty() {
+                        break;
+                    }
+
+                    let value = Input::<String>::new()
+                        .with_prompt(tr!("prompt-profile-changes.option-value"))
+                        .allow_empty(true)
+                        .interact()?;
+
+                    updated_profile.options.insert(key, value);
+                }
+            }
+        }
+
+        Ok(updated_profile)
+    }
+
+    /// Open the profile as TOML in $VISUAL/$EDITOR until it parses; `None` if the user gives up
+    fn edit_profile_in_editor(&self, profile: &Profile) -> anyhow::Result<Option<Profile>> {
+        let mut file = tempfile::Builder::new()
+            .prefix(&format!("shellbe-{}-", profile.name))
+            .suffix(".toml")
+            .tempfile()?;
+
+        let document = toml::to_string_pretty(&ProfileDocument::from(profile))?;
+        write!(file, "# Editing profile '{}'. Save and close the editor to apply.\n\n{}", profile.name, document)?;
+        file.flush()?;
+
+        loop {
+            run_editor(file.path())?;
+
+            let content = std::fs::read_to_string(file.path())?;
+            let result = toml::from_str::<ProfileDocument>(&content)
+                .map_err(|e| e.to_string())
+                .and_then(|document| document.apply(profile));
+
+            match result {
+                Ok(updated) => return Ok(Some(updated)),
+                Err(e) => {
+                    println!("{} {}", theme::error("✗"), tr!("edit-profile-in-editor.invalid-profile", e));
+                    if !self.prompt.confirm(tr!("edit-profile-in-editor.edit-again"), true)? {
+                        return Ok(None);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Handle the 'test' command
+    async fn handle_test(&self, name: Option<String>, all: bool, jobs: usize) -> anyhow::Result<()> {
+        let name = match name {
+            Some(name) if !all => name,
+            _ => return self.handle_test_all(jobs).await,
+        };
+
+        if self.json_output() {
+            let result = self.connection_service.test_connection(&name).await;
+            return self.print_json(&test_result_json(&name, result));
+        }
+
+        status!("{} {}", theme::heading("→"), tr!("test.testing-connection-to", theme::highlight(&name)));
+
+        match self.connection_service.test_connection(&name).await {
+            Ok(Some(address)) => {
+                status!("{} {}", theme::success("✓"), tr!("test.connection-successful"));
+                status!("  {}", tr!("test.connected-to-address", theme::muted(address)));
+            },
+            Ok(None) => {
+                println!("{} {}", theme::error("✗"), tr!("test.connection-failed"));
+                status!("{} {}", theme::warning("!"), tr!("test.troubleshooting-tips"));
+                println!("  {}", tr!("test.check-if-the-server-is"));
+                println!("  {}", tr!("test.verify-your-username-and-host"));
+                println!("  {}", tr!("test.make-sure-your-ssh-key"));
+                println!("  {}", tr!("test.check-if-the-port-is"));
+            },
+            Err(e) => {
+                if matches!(e, DomainError::ProfileNotFound(_)) {
+                    return self.report_not_found(tr!("test.error-testing-connection", e), &self.profile_suggestions(&name, true).await);
+                }
+                println!("{} {}", theme::error("✗"), tr!("test.error-testing-connection", e));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'test --all' command
+    async fn handle_test_all(&self, jobs: usize) -> anyhow::Result<()> {
+        let profiles = self.profile_service.list_profiles().await?;
+        let mut failed = 0;
+
+        let bar = progress::items_bar(profiles.len() as u64);
+
+        let names: Vec<String> = profiles.iter().map(|p| p.name.clone()).collect();
+        let pool = TaskPool::new(jobs);
+        let ctrl_c = pool.canceller().cancel_on_ctrl_c();
+
+        let outcome = pool.run(names, |name| {
+            let bar = &bar;
+            async move {
+                bar.set_message(tr!("test-all.testing", theme::highlight(&name)));
+                self.connection_service.test_connection(&name).await
+            }
+        }, |name, result| {
+            if !self.json_output() {
+                bar.suspend(|| match result {
+                    Ok(Some(address)) => status!("{} {} {}", theme::success("✓"), theme::highlight(name), theme::muted(format!("({})", address))),
+                    Ok(None) => println!("{} {}", theme::error("✗"), theme::highlight(name)),
+                    Err(e) => println!("{} {}: {}", theme::error("✗"), theme::highlight(name), e),
+                });
+            }
+
+            if !matches!(result, Ok(Some(_))) {
+                failed += 1;
+            }
+            bar.inc(1);
+        }).await;
+
+        ctrl_c.abort();
+        bar.finish_and_clear();
+
+        if self.json_output() {
+            // In the order profiles were listed, whatever order the tests finished in
+            let results: Vec<serde_json::Value> = outcome.finished.into_iter()
+                .map(|(name, result)| test_result_json(&name, result))
+                .collect();
+            return self.print_json(&results);
+        }
+
+        if !outcome.cancelled.is_empty() {
+            println!("{} {}", theme::warning("!"), tr!("common.bulk-cancelled", outcome.cancelled.len()));
+            failed += outcome.cancelled.len();
+        }
+
+        if profiles.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-profiles-found-use-add"));
+        } else if failed == 0 {
+            status!("{} {}", theme::success("✓"), tr!("test-all.all-connections-successful", profiles.len()));
+        } else {
+            println!("{} {}", theme::error("✗"), tr!("test-all.of-connections-failed", failed, profiles.len()));
+        }
+
+        Ok(())
+    }
+
+    /// Handle the 'recent' command
+    async fn handle_recent(&self, limit: usize) -> anyhow::Result<()> {
+        let recent = self.connection_service.get_recent_profiles(limit).await?;
+
+        if self.json_output() {
+            let recent: Vec<serde_json::Value> = recent.iter()
+                .enumerate()
+                .map(|(i, entry)| serde_json::json!({
+                    "index": i + 1,
+                    "profile": entry.profile_name,
+                    "hostname": entry.hostname,
+                    "last_connected": entry.timestamp,
+                }))
+                .collect();
+            return self.print_json(&recent);
+        }
+
+        if recent.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-connection-history-found"));
+            return Ok(());
+        }
+
+        let mut table = Table::new(vec![
+            Column::new("index", "#"),
+            Column::new("profile", tr!("column.profile")),
+            Column::new("host", tr!("column.host")),
+            Column::new("last", tr!("column.last-connected")),
+        ]);
+
+        for (i, entry) in recent.iter().enumerate() {
+            table.add_row(vec![
+                (i + 1).to_string(),
+                theme::highlight(&entry.profile_name).to_string(),
+                entry.hostname.clone(),
+                format_time(entry.timestamp, self.time_format),
+            ]);
+        }
+
+        println!("{}", theme::heading(tr!("recent.recent-profiles")));
+        table.print();
+        status!("\n{} {}", theme::accent("→"), tr!("recent.connect-with", theme::accent("shellbe connect <#>")));
+
+        Ok(())
+    }
+
+    /// Handle the 'history --convert' command
+    async fn handle_history_convert(&self, name: &str) -> anyhow::Result<()> {
+        let format = match name {
+            "binary" => HistoryFormat::Binary,
+            _ => HistoryFormat::Json,
+        };
+        let path = ShellBePaths::resolve().data_dir.join("history.json");
+        let size = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+
+        let before = size(&path);
+        self.connection_service.convert_history(format).await?;
+        let after = size(&path);
+
+        status!("{} {}", theme::success("✓"), tr!("history.converted", name, after, before));
+        Ok(())
+    }
+
+    /// Handle the 'history' command
+    async fn handle_history(&self, limit: usize, group_by: Option<HistoryGroupBy>, convert: Option<String>) -> anyhow::Result<()> {
+        if let Some(format) = convert {
+            return self.handle_history_convert(&format).await;
+        }
+
+        if let Some(group_by) = group_by {
+            return self.handle_history_grouped(limit, group_by).await;
+        }
+
+        if self.json_output() {
+            let history = self.connection_service.get_recent_history(limit).await?;
+            return self.print_json(&history);
+        }
+
+        let history = self.connection_service.get_recent_history(limit).await?;
+
+        if history.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-connection-history-found"));
+            return Ok(());
+        }
+
+        let mut table = Table::new(vec![
+            Column::new("time", tr!("column.when")),
+            Column::new("profile", tr!("column.profile")),
+            Column::new("host", tr!("column.host")),
+        ]);
+
+        for entry in history {
+            table.add_row(vec![
+                format_time(entry.timestamp, self.time_format),
+                theme::highlight(&entry.profile_name).to_string(),
+                entry.hostname,
+            ]);
+        }
+
+        println!("{}", theme::heading(tr!("common.connection-history")));
+        table.print();
+
+        // Show stats
+        let stats = self.connection_service.get_connection_stats().await?;
+
+        let mut stats_table = Table::new(vec![
+            Column::new("profile", tr!("column.profile")),
+            Column::new("connections", tr!("column.connections")),
+        ]);
+
+        for (profile, count) in stats {
+            stats_table.add_row(vec![theme::highlight(profile).to_string(), count.to_string()]);
+        }
+
+        println!("\n{}", theme::heading(tr!("history.connection-statistics")));
+        stats_table.print();
+
+        Ok(())
+    }
+
+    /// Handle the 'history --group-by' command
+    async fn handle_history_grouped(&self, limit: usize, group_by: HistoryGroupBy) -> anyhow::Result<()> {
+        let (grouping, heading) = match group_by {
+            HistoryGroupBy::Profile => (HistoryGrouping::Profile, tr!("column.profile")),
+            HistoryGroupBy::Day => (HistoryGrouping::Day, tr!("column.day")),
+        };
+
+        if self.json_output() {
+            let groups: Vec<_> = self.connection_service.get_grouped_history(grouping).await?
+                .into_iter()
+                .take(limit)
+                .collect();
+            return self.print_json(&groups);
+        }
+
+        let groups = self.connection_service.get_grouped_history(grouping).await?;
+
+        if groups.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("common.no-connection-history-found"));
+            return Ok(());
+        }
+
+        let mut table = Table::new(vec![
+            Column::new("key", heading),
+            Column::new("connections", tr!("column.connections")),
+            Column::new("total-time", tr!("column.total-time")),
+            Column::new("last", tr!("column.last")),
+        ]);
+
+        for group in groups.into_iter().take(limit) {
+            table.add_row(vec![
+                theme::highlight(&group.key).to_string(),
+                group.connection_count.to_string(),
+                format_duration(group.total_duration),
+                format_time(group.last_connection, self.time_format),
+            ]);
+        }
+
+        println!("{}", theme::heading(tr!("common.connection-history")));
+        table.print();
+
+        Ok(())
+    }
+
+    /// Handle the 'export' command
+    /// Handle the 'bundle' command
+    /// Bundle service trusting the keys from the settings
+    fn bundle_service(&self) -> BundleService {
+        let trusted_keys = self.settings.bundles.trusted_keys.iter()
+            .map(expand_path)
+            .collect();
+        BundleService::new(trusted_keys)
+    }
+
+    async fn handle_bundle(&self, args: BundleArgs) -> anyhow::Result<()> {
+        let bundles = self.bundle_service();
+
+        match args.command {
+            BundleCommands::Create { names, tag, sign, out } => {
+                let mut profiles = Vec::new();
+                if names.is_empty() {
+                    profiles = self.profile_service.list_profiles().await?;
+                } else {
+                    for name in &names {
+                        profiles.push(self.profile_service.get_profile(name).await?);
+                    }
+                }
+
+                if let Some(tag) = &tag {
+                    profiles.retain(|profile| profile.tags.contains(tag));
+                }
+
+                if profiles.is_empty() {
+                    status!("{} {}", theme::warning("!"), tr!("bundle.no-profiles-to-bundle"));
+                    return Ok(());
+                }
+
+                bundles.create(&profiles, &sign, &out)?;
+                status!("{} {}", theme::success("✓"), tr!("bundle.created", profiles.len(), theme::highlight(out.display())));
+            },
+            BundleCommands::Import { path, replace } => {
+                let bundle = bundles.open(&path)?;
+                let manifest = bundle.manifest;
+
+                status!("{} {}", theme::success("✓"), tr!("bundle.signature-verified", bundle.signer.display()));
+                status!("{} {}", theme::accent("→"), tr!("bundle.contains-profiles", manifest.profiles.len(), manifest.created_by, format_time(manifest.created_at, self.time_format)));
+
+                for profile in &manifest.profiles {
+                    println!("  - {}: {}@{}",
+                             theme::highlight(&profile.name),
+                             profile.username,
+                             profile.hostname);
+                }
+
+                if !self.prompt.confirm(tr!("import.import-profiles", manifest.profiles.len()), true)? {
+                    status!("{} {}", theme::warning("!"), tr!("import.import-cancelled"));
+                    return Ok(());
+                }
+
+                let mut imported = 0;
+                let mut skipped = 0;
+
+                for profile in manifest.profiles {
+                    let exists = self.profile_service.get_profile(&profile.name).await.is_ok();
+
+                    if exists && !replace {
+                        status!("{} {}", theme::note("→"), tr!("import.skipping-existing-profile", profile.name));
+                        skipped += 1;
+                        continue;
+                    }
+
+                    if exists {
+                        status!("{} {}", theme::accent("→"), tr!("import.updating-existing-profile", profile.name));
+                    } else if self.alias_instead_of_duplicate(&profile, self.prompt.is_interactive()).await? {
+                        skipped += 1;
+                        continue;
+                    } else {
+                        status!("{} {}", theme::accent("→"), tr!("import.adding-new-profile", profile.name));
+                    }
+
+                    match self.profile_service.import_profile(profile, "bundle").await {
+                        Ok(_) => imported += 1,
+                        Err(e) => {
+                            println!("{} {}", theme::error("✗"), tr!("import.failed-to-import-profile", e));
+                            skipped += 1;
+                        },
+                    }
+                }
 
-        // Edit each field
-        let hostname = Input::<String>::new()
-            .with_prompt("Hostname")
-            .with_initial_text(&profile.hostname)
-            .allow_empty(true)
-            .interact()?;
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("import.successfully-imported-profiles-skipped", imported, skipped));
+            },
+            BundleCommands::Sign { path, sign } => {
+                let content = std::fs::read(&path)?;
+                let signature = bundles.sign(&content, &sign)?;
 
-        let username = Input::<String>::new()
-            .with_prompt("Username")
-            .with_initial_text(&profile.username)
-            .allow_empty(true)
-            .interact()?;
+                let mut out = path.into_os_string();
+                out.push(SIGNATURE_SUFFIX);
+                let out = PathBuf::from(out);
+                std::fs::write(&out, signature)?;
 
-        let port = Input::<u16>::new()
-            .with_prompt("Port")
-            .with_initial_text(&profile.port.to_string())
-            .allow_empty(true)
-            .interact()?;
+                status!("{} {}", theme::success("✓"), tr!("bundle.signed", theme::highlight(out.display())));
+            },
+        }
 
-        let identity_file = Input::<String>::new()
-            .with_prompt("Identity file")
-            .with_initial_text(profile.identity_file.as_ref().map_or("", |p| p.to_str().unwrap_or("")))
-            .allow_empty(true)
-            .interact()?;
+        Ok(())
+    }
 
-        // Create updated profile
-        let mut updated_profile = profile.clone();
+    /// Handle 'import --url': fetch a shared profile set, preview the changes and merge them
+    async fn handle_import_url(&self, url: &str, replace: bool, verify: bool) -> anyhow::Result<()> {
+        let remote = RemoteImportService::new(ShellBePaths::resolve().data_dir);
+        let bundles = self.bundle_service();
 
-        if !hostname.is_empty() {
-            updated_profile.hostname = hostname;
+        status!("{} {}", theme::heading("→"), tr!("import-url.fetching", theme::highlight(url)));
+        let mut set = remote.fetch(url, verify.then_some(&bundles)).await?;
+
+        if let Some(signer) = &set.signer {
+            status!("{} {}", theme::success("✓"), tr!("bundle.signature-verified", signer.display()));
         }
 
-        if !username.is_empty() {
-            updated_profile.username = username;
+        // Compare what would be saved; violations are reported when the profile is added
+        for profile in &mut set.profiles {
+            let _ = self.profile_service.apply_policy(profile);
         }
 
-        updated_profile.port = port;
+        let local = self.profile_service.list_profiles().await?;
+        let plan = remote.plan(&set, &local, replace)?;
 
-        if !identity_file.is_empty() {
-            updated_profile.identity_file = Some(PathBuf::from(identity_file));
-        } else {
-            updated_profile.identity_file = None;
+        for (profile, change) in &plan {
+            let label = match change {
+                ImportChange::New => theme::success(tr!("import-url.new")),
+                ImportChange::Updated => theme::accent(tr!("import-url.updated")),
+                ImportChange::Unchanged => theme::note(tr!("import-url.unchanged")),
+                ImportChange::Conflict => theme::warning(tr!("import-url.conflict")),
+            };
+            println!("  - {}: {}@{} ({})",
+                     theme::highlight(&profile.name),
+                     profile.username,
+                     profile.hostname,
+                     label);
         }
 
-        // Update options
-        let update_options = Confirm::new()
-            .with_prompt("Update SSH options?")
-            .default(false)
-            .interact()?;
+        let changes = plan.iter()
+            .filter(|(_, change)| matches!(change, ImportChange::New | ImportChange::Updated))
+            .count();
+        let conflicts = plan.iter().filter(|(_, change)| *change == ImportChange::Conflict).count();
 
-        if update_options {
-            // Show current options
-            if !updated_profile.options.is_empty() {
-                println!("{} Current options:", style("→").cyan());
-                for (key, value) in &updated_profile.options {
-                    println!("  {} = {}", key, value);
-                }
-            }
+        if changes == 0 {
+            status!("{} {}", theme::success("✓"), tr!("import-url.up-to-date", plan.len()));
+        } else if !self.prompt.confirm(tr!("import-url.apply-changes", changes), true)? {
+            status!("{} {}", theme::warning("!"), tr!("import.import-cancelled"));
+            return Ok(());
+        }
 
-            // Clear or add options
-            let clear_options = Confirm::new()
-                .with_prompt("Clear all options?")
-                .default(false)
-                .interact()?;
+        let mut imported = Vec::new();
+        for (mut profile, change) in plan {
+            let result = match change {
+                ImportChange::New => self.profile_service.import_profile(profile.clone(), url).await,
+                ImportChange::Updated => {
+                    // Keep local usage data; only the connection settings come from the team
+                    if let Some(existing) = local.iter().find(|local| local.name == profile.name) {
+                        profile.created_at = existing.created_at;
+                        profile.last_used = existing.last_used;
+                    }
+                    self.profile_service.import_profile(profile.clone(), url).await
+                },
+                ImportChange::Unchanged => Ok(()),
+                ImportChange::Conflict => continue,
+            };
 
-            if clear_options {
-                updated_profile.options.clear();
+            match result {
+                Ok(()) => imported.push(profile.name),
+                Err(e) => println!("{} {}", theme::error("✗"), tr!("import.failed-to-import-profile", e)),
             }
+        }
 
-            let add_options = Confirm::new()
-                .with_prompt("Add new options?")
-                .default(true)
-                .interact()?;
-
-            if add_options {
-                loop {
-                    let key = Input::<String>::new()
-                        .with_prompt("Option key (empty to finish)")
-                        .allow_empty(true)
-                        .interact()?;
+        remote.record(url, &imported)?;
 
-                    if key.is_empty() {
-                        break;
-                    }
+        if changes > 0 {
+            status!("{} {}", theme::success("✓"), tr!("import-url.imported", changes));
+        }
+        if conflicts > 0 {
+            status!("{} {}", theme::warning("!"), tr!("import-url.kept-local-profiles", conflicts, theme::accent("--replace")));
+        }
 
-                    let value = Input::<String>::new()
-                        .with_prompt("Option value")
-                        .allow_empty(true)
-                        .interact()?;
+        Ok(())
+    }
 
-                    updated_profile.options.insert(key, value);
+    /// Handle the 'discover' command: find hosts, then add the proposed profiles the user picks
+    async fn handle_discover(&self, args: DiscoverArgs) -> anyhow::Result<()> {
+        let discovery: Arc<dyn HostDiscovery> = match args.command {
+            DiscoverCommands::Ldap { url, base, filter, attribute, bind_dn } => {
+                let ldap = LdapDiscovery::new(url, base, filter, attribute);
+                match bind_dn {
+                    Some(dn) => {
+                        let password = match std::env::var(LDAP_PASSWORD_ENV) {
+                            Ok(password) => Zeroizing::new(password),
+                            Err(_) => self.prompt.password(tr!("discover.ldap-password", &dn))?
+                                .ok_or_else(|| anyhow::anyhow!(tr!("discover.ldap-password-required", LDAP_PASSWORD_ENV)))?,
+                        };
+                        register_secret(&password);
+                        Arc::new(ldap.with_bind(dn, password))
+                    },
+                    None => Arc::new(ldap),
                 }
+            },
+            DiscoverCommands::Mdns { timeout } => Arc::new(MdnsDiscovery::new(std::time::Duration::from_secs(timeout))),
+            DiscoverCommands::Aws { region, aws_profile, private } => Arc::new(AwsDiscovery::new(region, aws_profile, private)),
+            DiscoverCommands::Gcp { project, private } => Arc::new(GcpDiscovery::new(project, private)),
+            DiscoverCommands::Azure { subscription, resource_group, private } => Arc::new(AzureDiscovery::new(subscription, resource_group, private)),
+        };
+        let discovery = DiscoveryService::new(discovery, ShellBePaths::resolve().data_dir);
+
+        status!("{} {}", theme::heading("→"), tr!("discover.searching", discovery.source()));
+
+        let local = self.profile_service.list_profiles().await?;
+        let (found, proposals): (Vec<DiscoveredProfile>, Vec<DiscoveredProfile>) = discovery
+            .propose(&local, args.user.as_deref(), &args.tags).await?
+            .into_iter()
+            .partition(|p| p.change != ImportChange::New);
+
+        // Hosts discovered before keep their profile; only a changed address is applied
+        let mut updated = 0;
+        for proposal in found.iter().filter(|p| p.change == ImportChange::Updated) {
+            let profile = &proposal.profile;
+            match self.profile_service.update_profile(profile.clone()).await {
+                Ok(()) => {
+                    updated += 1;
+                    status!("{} {}", theme::success("✓"), tr!("discover.updated", theme::highlight(&profile.name), format!("{}:{}", profile.hostname, profile.port)));
+                },
+                Err(e) => println!("{} {}", theme::error("✗"), tr!("discover.failed-to-update", &profile.name, e)),
             }
         }
 
-        // Update the profile
-        match self.profile_service.update_profile(updated_profile.clone()).await {
-            Ok(_) => {
-                println!("{} Profile '{}' updated successfully", style("✓").green().bold(), name);
-
-                // Ask if user wants to update SSH config
-                let update_ssh_config = Confirm::new()
-                    .with_prompt("Update this profile in SSH config?")
-                    .default(false)
-                    .interact()?;
-
-                if update_ssh_config {
-                    match self.ssh_config_service.remove_profile_from_ssh_config(&name).await {
-                        Ok(_) => {
-                            match self.ssh_config_service.add_profile_to_ssh_config(&updated_profile).await {
-                                Ok(_) => println!("{} Profile updated in SSH config", style("✓").green().bold()),
-                                Err(e) => println!("{} Failed to update profile in SSH config: {}", style("✗").red().bold(), e),
-                            }
-                        },
-                        Err(e) => println!("{} Failed to remove profile from SSH config: {}", style("✗").red().bold(), e),
-                    }
-                }
-            },
-            Err(e) => {
-                println!("{} Failed to update profile: {}", style("✗").red().bold(), e);
-            },
+        if args.refresh {
+            if updated == 0 {
+                status!("{} {}", theme::success("✓"), tr!("discover.up-to-date", found.len()));
+            }
+            if !proposals.is_empty() {
+                status!("  {}", tr!("discover.new-hosts-skipped", proposals.len()));
+            }
+            return Ok(());
         }
 
-        Ok(())
-    }
+        if proposals.is_empty() {
+            status!("{} {}", theme::success("✓"), tr!("discover.nothing-new"));
+            return Ok(());
+        }
 
-    /// Handle the 'test' command
-    async fn handle_test(&self, name: String) -> anyhow::Result<()> {
-        println!("{} Testing connection to {}...", style("→").cyan().bold(), style(&name).green());
+        let items: Vec<String> = proposals.iter()
+            .map(|p| &p.profile)
+            .map(|p| match p.tags.is_empty() {
+                true => format!("{}  {}:{}", p.name, p.connection_string(), p.port),
+                false => format!("{}  {}:{}  [{}]", p.name, p.connection_string(), p.port, p.tags.join(", ")),
+            })
+            .collect();
+
+        let Some(selected) = self.prompt.multi_select(tr!("discover.pick-profiles", proposals.len()), &items)? else {
+            // Without a terminal, show what was found and how to add it
+            println!("{} {}", theme::heading("→"), tr!("discover.found", proposals.len()));
+            for item in &items {
+                println!("  - {}", item);
+            }
+            println!("{}", tr!("discover.use-yes", theme::accent("--yes")));
+            return Ok(());
+        };
 
-        match self.connection_service.test_connection(&name).await {
-            Ok(true) => {
-                println!("{} Connection successful!", style("✓").green().bold());
-            },
-            Ok(false) => {
-                println!("{} Connection failed!", style("✗").red().bold());
-                println!("{} Troubleshooting tips:", style("!").yellow().bold());
-                println!("  - Check if the server is running and accessible");
-                println!("  - Verify your username and host are correct");
-                println!("  - Make sure your SSH key is properly set up");
-                println!("  - Check if the port is open and SSH is running on it");
-            },
-            Err(e) => {
-                println!("{} Error testing connection: {}", style("✗").red().bold(), e);
-            },
+        let mut added = Vec::new();
+        for proposal in selected.into_iter().map(|i| &proposals[i]) {
+            match self.profile_service.add_profile(proposal.profile.clone()).await {
+                Ok(()) => added.push(proposal),
+                Err(e) => println!("{} {}", theme::error("✗"), tr!("add.failed-to-add-profile", e)),
+            }
         }
 
+        discovery.record(&added)?;
+
+        status!("{} {}", theme::success("✓"), tr!("discover.added", added.len()));
         Ok(())
     }
 
-    /// Handle the 'history' command
-    async fn handle_history(&self, limit: usize) -> anyhow::Result<()> {
-        println!("{}", style("Connection history:").cyan().bold());
-        println!("{}", style("------------------------------------------").yellow());
-        println!("{:<20} {:<8} {:<15} {:<15}",
-                 style("DATE").cyan().bold(),
-                 style("TIME").cyan().bold(),
-                 style("PROFILE").cyan().bold(),
-                 style("HOST").cyan().bold());
-        println!("{}", style("------------------------------------------").yellow());
-
-        let history = self.connection_service.get_recent_history(limit).await?;
-
-        if history.is_empty() {
-            println!("{} No connection history found.", style("!").yellow().bold());
-            return Ok(());
+    /// Handle the 'export' command for the Termius and PuTTY formats
+    async fn handle_export_format(&self, format: ExportFormat, file: Option<PathBuf>, ssh_config_flags: bool) -> anyhow::Result<()> {
+        if ssh_config_flags {
+            anyhow::bail!(tr!("export.ssh-config-only"));
         }
 
-        for entry in history {
-            let date = entry.timestamp.format("%Y-%m-%d").to_string();
-            let time = entry.timestamp.format("%H:%M:%S").to_string();
+        let exporter: Box<dyn ProfileExporter> = match format {
+            ExportFormat::Termius => Box::new(TermiusCsvExporter::new()),
+            ExportFormat::Putty => Box::new(PuttySessionExporter::new()),
+            ExportFormat::SshConfig => unreachable!("SSH config exports are handled by handle_export"),
+        };
 
-            println!("{:<20} {:<8} {:<15} {:<15}",
-                     date,
-                     time,
-                     style(&entry.profile_name).green(),
-                     entry.hostname);
-        }
+        let profiles = self.profile_service.list_profiles().await?;
+        let contents = self.profile_service.export_profiles(&profiles, exporter.as_ref())?;
 
-        // Show stats
-        println!("\n{}", style("Connection statistics:").cyan().bold());
-        println!("{}", style("------------------------------------------").yellow());
-        println!("{:<15} {:<10}",
-                 style("PROFILE").cyan().bold(),
-                 style("CONNECTIONS").cyan().bold());
-        println!("{}", style("------------------------------------------").yellow());
+        let Some(path) = file else {
+            print!("{}", contents);
+            return Ok(());
+        };
 
-        let stats = self.connection_service.get_connection_stats().await?;
+        std::fs::write(&path, contents)
+            .map_err(|e| anyhow::anyhow!(tr!("export.failed-to-write", path.display(), e)))?;
+        status!("{} {}", theme::success("✓"), tr!("export.exported-to-format", profiles.len(), exporter.name(), theme::highlight(path.display())));
 
-        for (profile, count) in stats {
-            println!("{:<15} {:<10}",
-                     style(profile).green(),
-                     count);
+        if format == ExportFormat::Putty && profiles.iter().any(|p| p.identity_file.is_some()) {
+            status!("  {}", tr!("export.putty-keys", theme::accent("puttygen")));
         }
 
         Ok(())
     }
 
-    /// Handle the 'export' command
-    async fn handle_export(&self, replace: bool) -> anyhow::Result<()> {
-        println!("{} Exporting profiles to SSH config...", style("→").cyan().bold());
+    async fn handle_export(&self, replace: bool, with_aliases: bool) -> anyhow::Result<()> {
+        status!("{} {}", theme::heading("→"), tr!("export.exporting-profiles-to-ssh-config"));
 
         // Get all profiles
         let profiles = self.profile_service.list_profiles().await?;
 
         if profiles.is_empty() {
-            println!("{} No profiles found to export.", style("!").yellow().bold());
+            status!("{} {}", theme::warning("!"), tr!("export.no-profiles-found-to-export"));
             return Ok(());
         }
 
@@ -841,30 +3151,47 @@ impl CommandHandler {
         let replace = if replace {
             true
         } else {
-            let options = vec!["Replace existing SSH config", "Append to existing SSH config"];
-            let selection = Select::new()
-                .with_prompt("Export mode")
-                .items(&options)
-                .default(1)  // Default to append
-                .interact()?;
+            let options = [tr!("export.replace-existing-ssh-config"), tr!("export.append-to-existing-ssh-config")];
+            let options: Vec<&str> = options.iter().map(String::as_str).collect();
+            let selection = self.prompt.select(&tr!("export.export-mode"), &options, 1)?;  // Default to append
 
             selection == 0  // true if "Replace" was selected
         };
 
+        // Collect aliases that point directly at a profile
+        let aliases = if with_aliases {
+            let aliases: Vec<_> = self.alias_service.list_aliases().await?
+                .into_iter()
+                .filter(|a| profiles.iter().any(|p| p.name == a.target))
+                .collect();
+
+            for alias in aliases.iter().filter(|a| !a.connection_args().is_empty()) {
+                status!("{} {}", theme::warning("!"), tr!("export.alias-is-exported-without-its", alias.name));
+            }
+
+            aliases
+        } else {
+            Vec::new()
+        };
+
         // Export profiles
-        match self.ssh_config_service.export_profiles(&profiles, replace).await {
+        match self.ssh_config_service.export_profiles_with_aliases(&profiles, &aliases, replace).await {
             Ok(_) => {
-                println!("{} Profiles successfully exported to SSH config", style("✓").green().bold());
+                status!("{} {}", theme::success("✓"), tr!("export.profiles-successfully-exported-to-ssh"));
+
+                if with_aliases {
+                    status!("{} {}", theme::success("✓"), tr!("export.exported-aliases", aliases.len()));
+                }
 
                 // Get SSH config path
-                let ssh_config_path = dirs::home_dir()
+                let ssh_config_path = crate::utils::home_dir()
                     .map(|h| h.join(".ssh").join("config"))
                     .unwrap_or_else(|| PathBuf::from("~/.ssh/config"));
 
-                println!("{} SSH config location: {}", style("→").cyan(), ssh_config_path.display());
+                status!("{} {}", theme::accent("→"), tr!("export.ssh-config-location", ssh_config_path.display()));
             },
             Err(e) => {
-                println!("{} Failed to export profiles: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("export.failed-to-export-profiles", e));
             },
         }
 
@@ -872,49 +3199,49 @@ impl CommandHandler {
     }
 
     /// Handle the 'import' command
-    async fn handle_import(&self, replace: bool) -> anyhow::Result<()> {
-        println!("{} Importing profiles from SSH config...", style("→").cyan().bold());
+    async fn handle_import(&self, replace: bool, with_aliases: bool) -> anyhow::Result<()> {
+        status!("{} {}", theme::heading("→"), tr!("import.importing-profiles-from-ssh-config"));
 
         // Confirm import mode if not specified
         let replace = if replace {
             true
         } else {
-            let options = vec!["Replace existing profiles", "Append new profiles"];
-            let selection = Select::new()
-                .with_prompt("Import mode")
-                .items(&options)
-                .default(1)  // Default to append
-                .interact()?;
+            let options = [tr!("import.replace-existing-profiles"), tr!("import.append-new-profiles")];
+            let options: Vec<&str> = options.iter().map(String::as_str).collect();
+            let selection = self.prompt.select(&tr!("import.import-mode"), &options, 1)?;  // Default to append
 
             selection == 0  // true if "Replace" was selected
         };
 
         // Import profiles
-        match self.ssh_config_service.import_profiles().await {
-            Ok(profiles) => {
+        let imported_config = if with_aliases {
+            self.ssh_config_service.import_profiles_with_aliases().await
+        } else {
+            self.ssh_config_service.import_profiles().await.map(|p| (p, Vec::new()))
+        };
+
+        match imported_config {
+            Ok((profiles, aliases)) => {
                 if profiles.is_empty() {
-                    println!("{} No profiles found to import.", style("!").yellow().bold());
+                    status!("{} {}", theme::warning("!"), tr!("import.no-profiles-found-to-import"));
                     return Ok(());
                 }
 
-                println!("{} Found {} profiles in SSH config", style("→").cyan(), profiles.len());
+                status!("{} {}", theme::accent("→"), tr!("import.found-profiles-in-ssh-config", profiles.len()));
 
                 // Display profiles to import
                 for profile in &profiles {
                     println!("  - {}: {}@{}",
-                             style(&profile.name).green(),
+                             theme::highlight(&profile.name),
                              profile.username,
                              profile.hostname);
                 }
 
                 // Confirm import
-                let confirm = Confirm::new()
-                    .with_prompt(format!("Import {} profiles?", profiles.len()))
-                    .default(true)
-                    .interact()?;
+                let confirm = self.prompt.confirm(tr!("import.import-profiles", profiles.len()), true)?;
 
                 if !confirm {
-                    println!("{} Import cancelled", style("!").yellow().bold());
+                    status!("{} {}", theme::warning("!"), tr!("import.import-cancelled"));
                     return Ok(());
                 }
 
@@ -927,36 +3254,51 @@ impl CommandHandler {
                     let exists = self.profile_service.get_profile(&profile.name).await.is_ok();
 
                     if exists && !replace {
-                        println!("{} Skipping existing profile: {}", style("→").yellow(), profile.name);
+                        status!("{} {}", theme::note("→"), tr!("import.skipping-existing-profile", profile.name));
                         skipped += 1;
                         continue;
                     }
 
                     // Add or update profile
-                    let result = if exists {
-                        println!("{} Updating existing profile: {}", style("→").cyan(), profile.name);
-                        self.profile_service.update_profile(profile).await
+                    if exists {
+                        status!("{} {}", theme::accent("→"), tr!("import.updating-existing-profile", profile.name));
+                    } else if self.alias_instead_of_duplicate(&profile, self.prompt.is_interactive()).await? {
+                        skipped += 1;
+                        continue;
                     } else {
-                        println!("{} Adding new profile: {}", style("→").cyan(), profile.name);
-                        self.profile_service.add_profile(profile).await
-                    };
+                        status!("{} {}", theme::accent("→"), tr!("import.adding-new-profile", profile.name));
+                    }
 
-                    match result {
+                    match self.profile_service.import_profile(profile, "ssh_config").await {
                         Ok(_) => imported += 1,
                         Err(e) => {
-                            println!("{} Failed to import profile: {}", style("✗").red().bold(), e);
+                            println!("{} {}", theme::error("✗"), tr!("import.failed-to-import-profile", e));
                             skipped += 1;
                         },
                     }
                 }
 
-                println!("{} Successfully imported {} profiles, skipped {}",
-                         style("✓").green().bold(),
-                         imported,
-                         skipped);
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("import.successfully-imported-profiles-skipped", imported, skipped));
+
+                if !aliases.is_empty() {
+                    let mut imported_aliases = 0;
+
+                    for alias in aliases {
+                        match self.alias_service.create_alias(&alias.name, &alias.target).await {
+                            Ok(_) => imported_aliases += 1,
+                            Err(e) => {
+                                status!("{} {}", theme::note("→"), tr!("import.skipping-alias", alias.name, e));
+                            },
+                        }
+                    }
+
+                    status!("{} {}", theme::success("✓"), tr!("import.imported-aliases", imported_aliases));
+                }
             },
             Err(e) => {
-                println!("{} Failed to import profiles: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("import.failed-to-import-profiles", e));
             },
         }
 
@@ -969,7 +3311,7 @@ impl CommandHandler {
             PluginCommands::List => self.handle_plugin_list().await?,
             PluginCommands::Available => self.handle_plugin_available().await?,
             PluginCommands::Install { url } => self.handle_plugin_install(url).await?,
-            PluginCommands::Update { name } => self.handle_plugin_update(name).await?,
+            PluginCommands::Update { name, all, jobs } => self.handle_plugin_update(name, all, jobs).await?,
             PluginCommands::Remove { name } => self.handle_plugin_remove(name).await?,
             PluginCommands::Enable { name } => self.handle_plugin_enable(name).await?,
             PluginCommands::Disable { name } => self.handle_plugin_disable(name).await?,
@@ -981,105 +3323,128 @@ impl CommandHandler {
 
     /// Handle the 'plugin list' command
     async fn handle_plugin_list(&self) -> anyhow::Result<()> {
-        println!("{}", style("Installed plugins:").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
-        println!("{:<15} {:<10} {:<10} {:<20}",
-                 style("NAME").cyan().bold(),
-                 style("VERSION").cyan().bold(),
-                 style("STATUS").cyan().bold(),
-                 style("DESCRIPTION").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
+        if self.json_output() {
+            let plugins: Vec<serde_json::Value> = self.plugin_service.list_plugins().await?
+                .into_iter()
+                .map(|plugin| serde_json::json!({
+                    "name": plugin.info.name,
+                    "version": plugin.info.version,
+                    "description": plugin.info.description,
+                    "author": plugin.info.author,
+                    "enabled": plugin.status == crate::domain::PluginStatus::Enabled,
+                    "quarantined": plugin.status == crate::domain::PluginStatus::Quarantined,
+                    "path": plugin.path,
+                    "installed_at": plugin.installed_at,
+                    "updated_at": plugin.updated_at,
+                    "security_findings": plugin.security_findings,
+                }))
+                .collect();
+            return self.print_json(&plugins);
+        }
 
         let plugins = self.plugin_service.list_plugins().await?;
 
         if plugins.is_empty() {
-            println!("{} No plugins installed.", style("!").yellow().bold());
-            println!("Use '{}' to install a plugin.", style("shellbe plugin install <url>").cyan());
+            status!("{} {}", theme::warning("!"), tr!("plugin-list.no-plugins-installed"));
+            println!("{}", tr!("plugin-list.use-to-install-a-plugin", theme::accent("shellbe plugin install <url>")));
             return Ok(());
         }
 
+        let mut table = Table::new(vec![
+            Column::new("name", tr!("column.name")),
+            Column::new("version", tr!("column.version")),
+            Column::new("status", tr!("column.status")),
+            Column::new("description", tr!("column.description")),
+        ]);
+
         for plugin in plugins {
             let status = match plugin.status {
-                crate::domain::PluginStatus::Enabled => style("enabled").green(),
-                crate::domain::PluginStatus::Disabled => style("disabled").yellow(),
+                crate::domain::PluginStatus::Enabled => theme::highlight(tr!("plugin-list.enabled")),
+                crate::domain::PluginStatus::Disabled => theme::note(tr!("plugin-list.disabled")),
+                crate::domain::PluginStatus::Quarantined => theme::error(tr!("plugin-list.quarantined")),
             };
 
-            println!("{:<15} {:<10} {:<10} {:<20}",
-                     style(&plugin.info.name).green(),
-                     style(&plugin.info.version).blue(),
-                     status,
-                     plugin.info.description);
+            table.add_row(vec![
+                theme::highlight(&plugin.info.name).to_string(),
+                theme::info(&plugin.info.version).to_string(),
+                status.to_string(),
+                plugin.info.description.clone(),
+            ]);
         }
 
+        println!("{}", theme::heading(tr!("plugin-list.installed-plugins")));
+        table.print();
+
         Ok(())
     }
 
     /// Handle the 'plugin available' command
     async fn handle_plugin_available(&self) -> anyhow::Result<()> {
-        println!("{} Checking for available plugins...", style("→").cyan().bold());
+        status!("{} {}", theme::heading("→"), tr!("plugin-available.checking-for-available-plugins"));
 
         // This would normally be implemented by querying a plugin registry
         // For now, display a list of example plugins
-        println!("{}", style("-------------------------------------").yellow());
-        println!("{:<20} {:<15} {:<25}",
-                 style("NAME").cyan().bold(),
-                 style("AUTHOR").cyan().bold(),
-                 style("DESCRIPTION").cyan().bold());
-        println!("{}", style("-------------------------------------").yellow());
-
-        println!("{:<20} {:<15} {:<25}",
-                 style("shellbe-stats").green(),
-                 "arash",
-                 "Connection statistics and graphs");
-
-        println!("{:<20} {:<15} {:<25}",
-                 style("shellbe-sync").green(),
-                 "arash",
-                 "Sync profiles across devices");
-
-        println!("{:<20} {:<15} {:<25}",
-                 style("shellbe-menu").green(),
-                 "arash",
-                 "Interactive terminal menu");
-
-        println!("\n{} To install a plugin, use:", style("→").yellow());
-        println!("  {}", style("shellbe plugin install <github-username>/<repository-name>").cyan());
-        println!("For example: {}", style("shellbe plugin install arash/shellbe-stats").cyan());
+        let mut table = Table::new(vec![
+            Column::new("name", tr!("column.name")),
+            Column::new("author", tr!("column.author")),
+            Column::new("description", tr!("column.description")),
+        ]);
+
+        for (name, author, description) in [
+            ("shellbe-stats", "arash", "Connection statistics and graphs"),
+            ("shellbe-sync", "arash", "Sync profiles across devices"),
+            ("shellbe-menu", "arash", "Interactive terminal menu"),
+        ] {
+            table.add_row(vec![theme::highlight(name).to_string(), author.to_string(), description.to_string()]);
+        }
+
+        table.print();
+
+        status!("\n{} {}", theme::note("→"), tr!("plugin-available.to-install-a-plugin-use"));
+        println!("  {}", theme::accent("shellbe plugin install <github-username>/<repository-name>"));
+        println!("{}",
+                 tr!("plugin-available.for-example", theme::accent("shellbe plugin install arash/shellbe-stats")));
 
         Ok(())
     }
 
     /// Handle the 'plugin install' command
     async fn handle_plugin_install(&self, url: String) -> anyhow::Result<()> {
-        println!("{} Installing plugin from {}...", style("→").cyan().bold(), style(&url).blue());
+        status!("{} {}", theme::heading("→"), tr!("plugin-install.installing-plugin-from", theme::info(&url)));
 
         match self.plugin_service.install_from_github(&url).await {
             Ok(metadata) => {
-                println!("{} Plugin '{}' (version {}) installed successfully!",
-                         style("✓").green().bold(),
-                         style(&metadata.info.name).green(),
-                         metadata.info.version);
-                println!("{} Description: {}", style("→").cyan(), metadata.info.description);
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("plugin-install.plugin-version-installed-successfully",
+                            theme::highlight(&metadata.info.name),
+                            metadata.info.version));
+                status!("{} {}", theme::accent("→"), tr!("plugin-install.description", metadata.info.description));
+
+                if !metadata.security_findings.is_empty() {
+                    println!("{} {}", theme::warning("!"), tr!("plugin-install.security-findings", metadata.security_findings.len()));
+                    for finding in &metadata.security_findings {
+                        println!("  {}", theme::note(finding));
+                    }
+                }
 
                 // Ask if user wants to enable the plugin
-                let enable_plugin = Confirm::new()
-                    .with_prompt("Enable this plugin now?")
-                    .default(true)
-                    .interact()?;
+                let enable_plugin = self.prompt.confirm(tr!("plugin-install.enable-this-plugin-now"), true)?;
 
                 if enable_plugin {
                     match self.plugin_service.enable_plugin(&metadata.info.name).await {
-                        Ok(_) => println!("{} Plugin enabled", style("✓").green().bold()),
-                        Err(e) => println!("{} Failed to enable plugin: {}", style("✗").red().bold(), e),
+                        Ok(_) => status!("{} {}", theme::success("✓"), tr!("plugin-install.plugin-enabled")),
+                        Err(e) => println!("{} {}", theme::error("✗"), tr!("common.failed-to-enable-plugin", e)),
                     }
                 } else {
-                    println!("{} Plugin installed but not enabled.", style("!").yellow().bold());
-                    println!("Use '{}' to enable it.",
-                             style(format!("shellbe plugin enable {}", metadata.info.name)).cyan());
+                    status!("{} {}", theme::warning("!"), tr!("plugin-install.plugin-installed-but-not-enabled"));
+                    println!("{}",
+                             tr!("plugin-install.use-to-enable-it",
+                                 theme::accent(format!("shellbe plugin enable {}", metadata.info.name))));
                 }
             },
             Err(e) => {
-                println!("{} Failed to install plugin: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("plugin-install.failed-to-install-plugin", e));
             },
         }
 
@@ -1087,45 +3452,99 @@ impl CommandHandler {
     }
 
     /// Handle the 'plugin update' command
-    async fn handle_plugin_update(&self, name: String) -> anyhow::Result<()> {
-        println!("{} Updating plugin '{}'...", style("→").cyan().bold(), style(&name).green());
+    async fn handle_plugin_update(&self, name: Option<String>, all: bool, jobs: usize) -> anyhow::Result<()> {
+        let name = match name {
+            Some(name) if !all => name,
+            _ => return self.handle_plugin_update_all(jobs).await,
+        };
+
+        status!("{} {}", theme::heading("→"), tr!("plugin-update.updating-plugin", theme::highlight(&name)));
 
         match self.plugin_service.update_plugin(&name).await {
             Ok(metadata) => {
-                println!("{} Plugin '{}' updated successfully to version {}!",
-                         style("✓").green().bold(),
-                         style(&metadata.info.name).green(),
-                         metadata.info.version);
+                status!("{} {}",
+                        theme::success("✓"),
+                        tr!("plugin-update.plugin-updated-successfully-to-version",
+                            theme::highlight(&metadata.info.name),
+                            metadata.info.version));
             },
             Err(e) => {
-                println!("{} Failed to update plugin: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("plugin-update.failed-to-update-plugin", e));
             },
         }
 
         Ok(())
     }
 
+    /// Handle the 'plugin update --all' command
+    async fn handle_plugin_update_all(&self, jobs: usize) -> anyhow::Result<()> {
+        let plugins = self.plugin_service.list_plugins().await?;
+
+        if plugins.is_empty() {
+            status!("{} {}", theme::warning("!"), tr!("plugin-list.no-plugins-installed"));
+            return Ok(());
+        }
+
+        let bar = progress::items_bar(plugins.len() as u64);
+        let mut failed = 0;
+
+        let names: Vec<String> = plugins.iter().map(|p| p.info.name.clone()).collect();
+        let pool = TaskPool::new(jobs);
+        let ctrl_c = pool.canceller().cancel_on_ctrl_c();
+
+        let outcome = pool.run(names, |name| {
+            let bar = &bar;
+            async move {
+                bar.set_message(tr!("plugin-update.updating", theme::highlight(&name)));
+                RetryPolicy::default().run(&format!("Updating plugin {}", name), || self.plugin_service.update_plugin(&name)).await
+            }
+        }, |name, result| {
+            bar.suspend(|| match result {
+                Ok(metadata) => status!("{} {} {}", theme::success("✓"), theme::highlight(name), metadata.info.version),
+                Err(e) => println!("{} {}: {}", theme::error("✗"), theme::highlight(name), e),
+            });
+
+            if result.is_err() {
+                failed += 1;
+            }
+            bar.inc(1);
+        }).await;
+
+        ctrl_c.abort();
+        bar.finish_and_clear();
+
+        if !outcome.cancelled.is_empty() {
+            println!("{} {}", theme::warning("!"), tr!("common.bulk-cancelled", outcome.cancelled.len()));
+            failed += outcome.cancelled.len();
+        }
+
+        if failed == 0 {
+            status!("{} {}", theme::success("✓"), tr!("plugin-update.all-plugins-updated", plugins.len()));
+        } else {
+            println!("{} {}", theme::error("✗"), tr!("plugin-update.updates-failed", failed, plugins.len()));
+        }
+
+        Ok(())
+    }
+
     /// Handle the 'plugin remove' command
     async fn handle_plugin_remove(&self, name: String) -> anyhow::Result<()> {
         // Confirm removal
-        let confirm = Confirm::new()
-            .with_prompt(format!("Are you sure you want to remove plugin '{}'?", name))
-            .default(false)
-            .interact()?;
+        let confirm = self.prompt.confirm(tr!("plugin-remove.are-you-sure-you-want", name), false)?;
 
         if !confirm {
-            println!("{} Removal cancelled", style("!").yellow().bold());
+            status!("{} {}", theme::warning("!"), tr!("plugin-remove.removal-cancelled"));
             return Ok(());
         }
 
-        println!("{} Removing plugin '{}'...", style("→").cyan().bold(), style(&name).green());
+        status!("{} {}", theme::heading("→"), tr!("plugin-remove.removing-plugin", theme::highlight(&name)));
 
         match self.plugin_service.remove_plugin(&name).await {
             Ok(_) => {
-                println!("{} Plugin '{}' removed successfully", style("✓").green().bold(), name);
+                status!("{} {}", theme::success("✓"), tr!("plugin-remove.plugin-removed-successfully", name));
             },
             Err(e) => {
-                println!("{} Failed to remove plugin: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("plugin-remove.failed-to-remove-plugin", e));
             },
         }
 
@@ -1134,14 +3553,14 @@ impl CommandHandler {
 
     /// Handle the 'plugin enable' command
     async fn handle_plugin_enable(&self, name: String) -> anyhow::Result<()> {
-        println!("{} Enabling plugin '{}'...", style("→").cyan().bold(), style(&name).green());
+        status!("{} {}", theme::heading("→"), tr!("plugin-enable.enabling-plugin", theme::highlight(&name)));
 
         match self.plugin_service.enable_plugin(&name).await {
             Ok(_) => {
-                println!("{} Plugin '{}' enabled successfully", style("✓").green().bold(), name);
+                status!("{} {}", theme::success("✓"), tr!("plugin-enable.plugin-enabled-successfully", name));
             },
             Err(e) => {
-                println!("{} Failed to enable plugin: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("common.failed-to-enable-plugin", e));
             },
         }
 
@@ -1150,14 +3569,14 @@ impl CommandHandler {
 
     /// Handle the 'plugin disable' command
     async fn handle_plugin_disable(&self, name: String) -> anyhow::Result<()> {
-        println!("{} Disabling plugin '{}'...", style("→").cyan().bold(), style(&name).green());
+        status!("{} {}", theme::heading("→"), tr!("plugin-disable.disabling-plugin", theme::highlight(&name)));
 
         match self.plugin_service.disable_plugin(&name).await {
             Ok(_) => {
-                println!("{} Plugin '{}' disabled successfully", style("✓").green().bold(), name);
+                status!("{} {}", theme::success("✓"), tr!("plugin-disable.plugin-disabled-successfully", name));
             },
             Err(e) => {
-                println!("{} Failed to disable plugin: {}", style("✗").red().bold(), e);
+                println!("{} {}", theme::error("✗"), tr!("plugin-disable.failed-to-disable-plugin", e));
             },
         }
 
@@ -1166,20 +3585,303 @@ impl CommandHandler {
 
     /// Handle the 'plugin run' command
     async fn handle_plugin_run(&self, name: String, command: String, args: Vec<String>) -> anyhow::Result<()> {
-        println!("{} Running plugin command: {} {}",
-                 style("→").cyan().bold(),
-                 style(format!("{} {}", name, command)).green(),
-                 args.join(" "));
+        status!("{} {}",
+                theme::heading("→"),
+                tr!("plugin-run.running-plugin-command",
+                    theme::highlight(format!("{} {}", name, command)),
+                    args.join(" ")));
 
         match self.plugin_service.execute_command(&name, &command, &args).await {
             Ok(_) => {
-                println!("{} Command executed successfully", style("✓").green().bold());
+                status!("{} {}", theme::success("✓"), tr!("plugin-run.command-executed-successfully"));
             },
             Err(e) => {
-                println!("{} Failed to execute command: {}", style("✗").red().bold(), e);
+                if self.plugin_service.get_plugin(&name).await.is_err() {
+                    return self.report_not_found(tr!("plugin-run.failed-to-execute-command", e),
+                                                 &self.plugin_service.similar_names(&name).await.unwrap_or_default());
+                }
+                println!("{} {}", theme::error("✗"), tr!("plugin-run.failed-to-execute-command", e));
             },
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Report a name that wasn't found along with close matches; with `--output json` it
+    /// becomes the command's error, so it is printed as JSON and exits with its own code
+    fn report_not_found(&self, message: String, suggestions: &[String]) -> anyhow::Result<()> {
+        if self.json_output() {
+            let error = anyhow::Error::new(ShellBeError::NotFound(message));
+            return Err(match suggestions {
+                [] => error,
+                _ => error.context(Suggestion(tr!("common.did-you-mean", suggestions.join(", ")))),
+            });
+        }
+
+        println!("{} {}", theme::error("✗"), message);
+        print_suggestions(suggestions);
+        Ok(())
+    }
+
+    /// Profile names close to one that wasn't found, optionally with alias names
+    async fn profile_suggestions(&self, name: &str, with_aliases: bool) -> Vec<String> {
+        let mut suggestions = self.profile_service.similar_names(name).await.unwrap_or_default();
+
+        if with_aliases {
+            suggestions.extend(self.alias_service.similar_names(name).await.unwrap_or_default());
+            suggestions.sort_by_key(|candidate| levenshtein(name, candidate));
+            suggestions.dedup();
+            suggestions.truncate(3);
+        }
+
+        suggestions
+    }
+}
+
+/// The vault passphrase from `SHELLBE_VAULT_PASSPHRASE`, or asked for on the terminal
+fn vault_passphrase(prompt: &Prompter, message: String) -> anyhow::Result<Zeroizing<String>> {
+    let passphrase = match std::env::var(VAULT_PASSPHRASE_ENV) {
+        Ok(passphrase) => Zeroizing::new(passphrase),
+        Err(_) => prompt.password(message)?
+            .ok_or_else(|| anyhow::anyhow!(tr!("vault.passphrase-needs-a-terminal", VAULT_PASSPHRASE_ENV)))?,
+    };
+
+    register_secret(&passphrase);
+    Ok(passphrase)
+}
+
+/// A new vault passphrase, typed twice; never read from the environment
+fn new_vault_passphrase(prompt: &Prompter) -> anyhow::Result<Zeroizing<String>> {
+    loop {
+        let passphrase = prompt.new_password(
+            tr!("vault.new-passphrase"),
+            tr!("vault.repeat-passphrase"),
+            tr!("vault.passphrases-dont-match"),
+        )?
+            .ok_or_else(|| anyhow::anyhow!(tr!("vault.new-passphrase-needs-a-terminal")))?;
+
+        if !passphrase.is_empty() {
+            register_secret(&passphrase);
+            return Ok(passphrase);
+        }
+
+        println!("{} {}", theme::error("✗"), tr!("vault.passphrase-must-not-be-empty"));
+    }
+}
+
+/// The sync passphrase from the environment or the terminal; typed twice when it's new
+fn sync_passphrase(prompt: &Prompter, new: bool) -> anyhow::Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var(SYNC_PASSPHRASE_ENV) {
+        register_secret(&passphrase);
+        return Ok(Zeroizing::new(passphrase));
+    }
+
+    let passphrase = if new {
+        prompt.new_password(
+            tr!("sync.new-passphrase"),
+            tr!("vault.repeat-passphrase"),
+            tr!("vault.passphrases-dont-match"),
+        )?
+    } else {
+        prompt.password(tr!("sync.passphrase"))?
+    };
+
+    let passphrase = passphrase
+        .ok_or_else(|| anyhow::anyhow!(tr!("sync.passphrase-needs-a-terminal", SYNC_PASSPHRASE_ENV)))?;
+    if passphrase.is_empty() {
+        anyhow::bail!(tr!("vault.passphrase-must-not-be-empty"));
+    }
+
+    register_secret(&passphrase);
+    Ok(passphrase)
+}
+
+/// Print a "did you mean" hint when there are close matches for an unknown name
+fn print_suggestions(suggestions: &[String]) {
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let names: Vec<String> = suggestions.iter().map(|s| theme::highlight(s).to_string()).collect();
+    println!("  {}", tr!("common.did-you-mean", names.join(", ")));
+}
+
+/// Format a duration as a compact human-readable string (e.g. "1h 05m")
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Format alias connection arguments for display
+fn format_connection_args(args: &ConnectionArgs) -> String {
+    let mut parts = args.ssh_args.clone();
+
+    if let Some(command) = &args.command {
+        parts.push(format!("'{}'", command));
+    }
+
+    parts.join(" ")
+}
+
+/// JSON record for a single connection test result
+fn test_result_json(name: &str, result: Result<Option<SocketAddr>, DomainError>) -> serde_json::Value {
+    match result {
+        Ok(Some(address)) => serde_json::json!({ "name": name, "success": true, "address": address.to_string() }),
+        Ok(None) => serde_json::json!({ "name": name, "success": false }),
+        Err(e) => serde_json::json!({ "name": name, "success": false, "error": e.to_string() }),
+    }
+}
+
+/// The vault for a data directory, keeping unlocked keys where `vault.key_store` says
+pub fn vault_service(paths: &ShellBePaths, settings: &Settings) -> VaultService {
+    let mut vault = VaultService::new(paths);
+    vault.set_secret_backend(secret_backend(
+        &settings.vault.key_store,
+        &paths.cache_dir,
+        &VaultService::keychain_service(paths),
+    ));
+    vault.set_idle_timeout(settings.vault.idle_lock_minutes.map(|minutes| chrono::Duration::minutes(minutes.into())));
+    vault
+}
+
+/// Sync service for the `[sync]` settings, over the data files in the given storage
+pub fn sync_service(paths: &ShellBePaths, settings: &Settings, storage: EncryptedStorage) -> anyhow::Result<SyncService> {
+    let backend = sync_backend(&settings.sync)?
+        .ok_or_else(|| anyhow::anyhow!(tr!("sync.not-set-up")))?;
+
+    Ok(SyncService::new(backend, &paths.data_dir, storage))
+}
+
+/// Address of the daemon to use: `SHELLBE_DAEMON`, else `daemon.address` from the settings
+pub fn daemon_address(settings: &Settings) -> Option<String> {
+    std::env::var(DAEMON_ENV).ok()
+        .filter(|address| !address.is_empty())
+        .or_else(|| settings.daemon.address.clone())
+}
+
+/// Default location of the user's SSH config file
+pub fn default_ssh_config_path() -> PathBuf {
+    crate::utils::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".ssh")
+        .join("config")
+}
+
+/// Editable fields of a profile, as shown by 'edit --editor'
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileDocument {
+    hostname: String,
+    username: String,
+    port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identity_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    identity_passphrase: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    vault_role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resolve_to: Option<IpAddr>,
+    #[serde(default)]
+    quiet: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    banner: Option<String>,
+    #[serde(default, skip_serializing_if = "ProfileKind::is_ssh")]
+    kind: ProfileKind,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    options: std::collections::BTreeMap<String, String>,
+}
+
+impl From<&Profile> for ProfileDocument {
+    fn from(profile: &Profile) -> Self {
+        Self {
+            hostname: profile.hostname.clone(),
+            username: profile.username.clone(),
+            port: profile.port,
+            identity_file: profile.identity_file.clone(),
+            identity_passphrase: profile.identity_passphrase.clone(),
+            password: profile.password.clone(),
+            vault_role: profile.vault_role.clone(),
+            resolve_to: profile.resolve_to,
+            quiet: profile.quiet,
+            banner: profile.banner.clone(),
+            kind: profile.kind.clone(),
+            tags: profile.tags.clone(),
+            options: profile.options.clone().into_iter().collect(),
+        }
+    }
+}
+
+impl ProfileDocument {
+    /// Validate the edited fields and apply them to a copy of the profile
+    fn apply(self, profile: &Profile) -> Result<Profile, String> {
+        if self.hostname.trim().is_empty() {
+            return Err("hostname must not be empty".to_string());
+        }
+        if self.username.trim().is_empty() {
+            return Err("username must not be empty".to_string());
+        }
+        if self.port == 0 {
+            return Err("port must be between 1 and 65535".to_string());
+        }
+        for reference in [&self.identity_passphrase, &self.password].into_iter().flatten() {
+            if !is_secret_reference(reference) {
+                return Err(format!("'{}' is not a secret manager reference (op://... or bw://...)", reference));
+            }
+        }
+
+        let mut updated = profile.clone();
+        updated.hostname = self.hostname.trim().to_string();
+        updated.username = self.username.trim().to_string();
+        updated.port = self.port;
+        updated.identity_file = self.identity_file;
+        updated.identity_passphrase = self.identity_passphrase;
+        updated.password = self.password;
+        updated.vault_role = self.vault_role;
+        updated.resolve_to = self.resolve_to;
+        updated.quiet = self.quiet;
+        updated.banner = self.banner.filter(|banner| !banner.trim().is_empty());
+        updated.kind = self.kind;
+        updated.tags = self.tags;
+        updated.options = self.options.into_iter().collect();
+
+        Ok(updated)
+    }
+}
+
+/// Run $VISUAL or $EDITOR (falling back to vi) on a file and wait for it to exit
+fn run_editor(path: &std::path::Path) -> anyhow::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow::anyhow!(tr!("run-editor.failed-to-launch-editor", editor, e)))?;
+
+    if !status.success() {
+        anyhow::bail!(tr!("run-editor.editor-exited-with", editor, status));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Directory under the config directory with extra `<lang>.txt` catalogs
+pub const LOCALES_DIR: &str = "locales";
+
+/// English messages, also used for keys missing from a translation
+const ENGLISH: &str = include_str!("../../../locales/en.txt");
+
+/// Catalogs compiled into the binary; add a `locales/<lang>.txt` file and an entry here to ship one
+const BUILTIN_CATALOGS: &[(&str, &str)] = &[("en", ENGLISH)];
+
+/// Messages for the active locale
+struct Catalog {
+    messages: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Pick the language from settings, then `LC_ALL`, `LC_MESSAGES` and `LANG`
+///
+/// Values like `de_DE.UTF-8` are reduced to the language code (`de`).
+pub fn detect_locale(configured: Option<&str>) -> String {
+    let candidates = configured.map(str::to_string).into_iter()
+        .chain(["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|var| std::env::var(var).ok()));
+
+    for candidate in candidates {
+        let language = candidate.split(['_', '.', '@', '-']).next().unwrap_or("").to_lowercase();
+        if !language.is_empty() && language != "c" && language != "posix" {
+            return language;
+        }
+    }
+
+    "en".to_string()
+}
+
+/// Load the catalog for a locale; only the first call has an effect
+///
+/// A `<lang>.txt` file in `custom_dir` takes precedence over a built-in catalog, so teams
+/// can try translations without rebuilding. Unknown locales fall back to English.
+pub fn set_locale(locale: &str, custom_dir: Option<&Path>) {
+    let custom = custom_dir
+        .map(|dir| dir.join(format!("{}.txt", locale)))
+        .and_then(|path| std::fs::read_to_string(path).ok());
+
+    let messages = match custom {
+        Some(content) => parse_catalog(&content),
+        None => BUILTIN_CATALOGS.iter()
+            .find(|(lang, _)| *lang == locale)
+            .map(|(_, content)| parse_catalog(content))
+            .unwrap_or_default(),
+    };
+
+    let _ = CATALOG.set(Catalog {
+        messages,
+        fallback: parse_catalog(ENGLISH),
+    });
+}
+
+fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| Catalog {
+        messages: HashMap::new(),
+        fallback: parse_catalog(ENGLISH),
+    })
+}
+
+/// Parse `key = value` lines; `#` starts a comment and `\n` in values is a newline
+fn parse_catalog(content: &str) -> HashMap<String, String> {
+    content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), unescape(value.trim())))
+        .collect()
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+
+    out
+}
+
+/// Look up a message and fill in its placeholders
+///
+/// `{}` takes the next argument and `{0}`, `{1}`, ... a specific one, so translations can
+/// reorder them. `{{` and `}}` are literal braces. Unknown keys print the key itself.
+pub fn message(key: &str, args: &[&dyn Display]) -> String {
+    let catalog = catalog();
+    let template = catalog.messages.get(key)
+        .or_else(|| catalog.fallback.get(key))
+        .map(String::as_str)
+        .unwrap_or(key);
+
+    format_message(template, args)
+}
+
+fn format_message(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    let mut next_arg = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            },
+            '{' => {
+                let mut index = String::new();
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                    index.push(c);
+                }
+
+                let index = if index.is_empty() {
+                    next_arg += 1;
+                    next_arg - 1
+                } else {
+                    index.parse().unwrap_or(usize::MAX)
+                };
+
+                if let Some(arg) = args.get(index) {
+                    out.push_str(&arg.to_string());
+                }
+            },
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Translate a message key, filling in `{}` placeholders with the arguments
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::interface::cli::i18n::message($key, &[])
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::interface::cli::i18n::message($key, &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+pub(crate) use tr;
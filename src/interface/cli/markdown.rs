@@ -0,0 +1,84 @@
+use std::fmt::Write as _;
+
+use super::theme;
+
+/// Render Markdown for the terminal, e.g. release notes
+///
+/// Covers what release notes use: headings, lists, quotes, code blocks, inline code,
+/// bold text and links. Anything else is printed as written.
+pub fn render(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        } else if in_code {
+            let _ = writeln!(out, "    {}", theme::accent(line));
+        } else if let Some(text) = heading(trimmed) {
+            let _ = writeln!(out, "{}", theme::heading(inline(text)));
+        } else if let Some(item) = ["- ", "* ", "+ "].iter().find_map(|marker| trimmed.strip_prefix(marker)) {
+            let _ = writeln!(out, "{}  • {}", indent, inline(item));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let _ = writeln!(out, "{} {}", theme::muted("│"), inline(quote.trim_start()));
+        } else {
+            let _ = writeln!(out, "{}", inline(line));
+        }
+    }
+
+    out
+}
+
+/// Text of an ATX heading like `## Fixes`
+fn heading(line: &str) -> Option<&str> {
+    let text = line.trim_start_matches('#');
+    let level = line.len() - text.len();
+    ((1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')))
+        .then(|| text.trim().trim_end_matches('#').trim_end())
+}
+
+/// Style inline code and bold text and show links as `text (url)`
+fn inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(['`', '*', '[']) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some((code, after)) = enclosed(rest, "`", "`") {
+            let _ = write!(out, "{}", theme::accent(code));
+            rest = after;
+        } else if let Some((bold, after)) = enclosed(rest, "**", "**") {
+            let _ = write!(out, "{}", theme::highlight(bold));
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            let _ = write!(out, "{} ({})", label, theme::muted(url));
+            rest = after;
+        } else {
+            // All markers are ASCII, so this is a char boundary
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Text between `open` at the start of `text` and the next `close`, and what follows it
+fn enclosed<'a>(text: &'a str, open: &str, close: &str) -> Option<(&'a str, &'a str)> {
+    let inner = text.strip_prefix(open)?;
+    let end = inner.find(close)?;
+    (end > 0).then(|| (&inner[..end], &inner[end + close.len()..]))
+}
+
+/// `[label](url)` at the start of `text`
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, after) = enclosed(text, "[", "](")?;
+    let (url, after) = after.split_once(')')?;
+    Some((label, url, after))
+}
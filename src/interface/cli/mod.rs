@@ -1,5 +1,14 @@
 pub mod commands;
+pub mod completions;
+pub mod docs;
 pub mod handler;
+pub mod i18n;
+pub mod markdown;
+pub mod output;
+pub mod progress;
+pub mod prompt;
+pub mod table;
+pub mod theme;
 
-pub use commands::Cli;
+pub use commands::{Cli, OutputFormat};
 pub use handler::CommandHandler;
\ No newline at end of file
@@ -0,0 +1,150 @@
+use crate::domain::with_redaction;
+use crate::errors::ErrorReport;
+use crate::utils::redact_json;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use super::i18n::tr;
+
+/// How much decorated console output to print
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only results and errors
+    Quiet,
+    /// Results, errors and status messages
+    Normal,
+    /// Everything, including extra details
+    Verbose,
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Whether errors are printed as JSON, for `--output json`
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+impl Verbosity {
+    /// Pick a verbosity from the `-q` flag and the number of `-v` flags
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose > 0 {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    /// Default tracing filter for this verbosity
+    pub fn tracing_filter(self, verbose: u8) -> &'static str {
+        match self {
+            Verbosity::Quiet => "error",
+            Verbosity::Normal => "info",
+            Verbosity::Verbose if verbose > 1 => "trace",
+            Verbosity::Verbose => "debug",
+        }
+    }
+}
+
+/// Set the verbosity used by `status!` and `detail!`
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Current console verbosity
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        1 => Verbosity::Normal,
+        _ => Verbosity::Verbose,
+    }
+}
+
+/// Print errors as JSON, or as text again
+pub fn set_json_errors(json: bool) {
+    JSON_ERRORS.store(json, Ordering::Relaxed);
+}
+
+/// Print why the run failed on stderr, as JSON with `--output json`
+pub fn print_error(report: &ErrorReport) {
+    if !JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("Error: {}", report);
+        return;
+    }
+
+    match to_json(&report.to_json()) {
+        Ok(json) => eprintln!("{}", json),
+        Err(_) => eprintln!("Error: {}", report),
+    }
+}
+
+/// Print a status message (progress, success, warning) unless running quietly
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if $crate::interface::cli::output::verbosity() >= $crate::interface::cli::output::Verbosity::Normal {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Print extra detail only when running verbosely
+macro_rules! detail {
+    ($($arg:tt)*) => {
+        if $crate::interface::cli::output::verbosity() >= $crate::interface::cli::output::Verbosity::Verbose {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use {detail, status};
+
+/// How timestamps are shown in tables and details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    /// Relative to now, e.g. "2 hours ago"
+    #[default]
+    Relative,
+    /// Exact UTC date and time
+    Absolute,
+}
+
+/// Format a timestamp for display
+pub fn format_time(time: DateTime<Utc>, format: TimeFormat) -> String {
+    match format {
+        TimeFormat::Relative => format_relative(time, Utc::now()),
+        TimeFormat::Absolute => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+    }
+}
+
+/// Format an optional timestamp, showing "-" when it's missing
+pub fn format_optional_time(time: Option<DateTime<Utc>>, format: TimeFormat) -> String {
+    time.map(|t| format_time(t, format)).unwrap_or_else(|| "-".to_string())
+}
+
+/// Describe how long before `now` a timestamp was, using the largest whole unit
+///
+/// Timestamps in the future (clock skew) count as "just now".
+pub fn format_relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - time).num_seconds().max(0);
+
+    let (count, one, many) = match seconds {
+        0..=59 => return tr!("time.just-now"),
+        60..=3599 => (seconds / 60, "time.minute-ago", "time.minutes-ago"),
+        3600..=86_399 => (seconds / 3600, "time.hour-ago", "time.hours-ago"),
+        86_400..=2_591_999 => (seconds / 86_400, "time.day-ago", "time.days-ago"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "time.month-ago", "time.months-ago"),
+        _ => (seconds / 31_536_000, "time.year-ago", "time.years-ago"),
+    };
+
+    if count == 1 {
+        tr!(one)
+    } else {
+        tr!(many, count)
+    }
+}
+
+/// Pretty JSON for command output, with sensitive fields and secrets masked
+pub fn to_json<T: serde::Serialize>(value: &T) -> serde_json::Result<String> {
+    let mut value = with_redaction(|| serde_json::to_value(value))?;
+    redact_json(&mut value);
+    serde_json::to_string_pretty(&value)
+}
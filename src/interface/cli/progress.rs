@@ -0,0 +1,114 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::i18n::tr;
+use super::output::{verbosity, Verbosity};
+use crate::application::{Progress, ProgressStep};
+
+/// Where bars are drawn: stderr, so piped results stay clean, and nowhere when quiet
+fn draw_target() -> ProgressDrawTarget {
+    if verbosity() == Verbosity::Quiet {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// A bar counting items, e.g. hosts in `test --all`
+pub fn items_bar(len: u64) -> ProgressBar {
+    let bar = ProgressBar::with_draw_target(Some(len), draw_target());
+    bar.set_style(ProgressStyle::with_template("{spinner:.cyan} [{bar:30.cyan/blue}] {pos}/{len} {msg}")
+        .expect("valid template")
+        .progress_chars("=> "));
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// A bar counting bytes, or a spinner when the size isn't known
+pub fn bytes_bar(total: Option<u64>, message: String) -> ProgressBar {
+    let bar = match total {
+        Some(total) => {
+            let bar = ProgressBar::with_draw_target(Some(total), draw_target());
+            bar.set_style(ProgressStyle::with_template(
+                "{spinner:.cyan} {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec})"
+            )
+                .expect("valid template")
+                .progress_chars("=> "));
+            bar
+        },
+        None => spinner_bar(),
+    };
+
+    bar.set_message(message);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// A spinner for work without a known size
+pub fn spinner(message: String) -> ProgressBar {
+    let bar = spinner_bar();
+    bar.set_message(message);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+fn spinner_bar() -> ProgressBar {
+    let bar = ProgressBar::with_draw_target(None, draw_target());
+    bar.set_style(ProgressStyle::with_template("{spinner:.cyan} {msg} {elapsed}").expect("valid template"));
+    bar
+}
+
+/// Hide a bar while something else uses the terminal, e.g. an ssh password prompt
+///
+/// The bar is drawn again when the guard is dropped.
+pub fn pause(bar: &ProgressBar) -> PausedBar<'_> {
+    bar.set_draw_target(ProgressDrawTarget::hidden());
+    PausedBar { bar }
+}
+
+/// Guard returned by [`pause`]
+pub struct PausedBar<'a> {
+    bar: &'a ProgressBar,
+}
+
+impl Drop for PausedBar<'_> {
+    fn drop(&mut self) {
+        self.bar.set_draw_target(draw_target());
+    }
+}
+
+/// Shows service progress as progress bars on stderr
+#[derive(Default)]
+pub struct ProgressBars {
+    current: Mutex<Option<ProgressBar>>,
+}
+
+impl Progress for ProgressBars {
+    fn start(&self, step: ProgressStep, total: Option<u64>) {
+        let bar = match step {
+            ProgressStep::Download => bytes_bar(total, tr!("progress.downloading")),
+            ProgressStep::Extract => {
+                let bar = items_bar(total.unwrap_or(0));
+                bar.set_message(tr!("progress.extracting"));
+                bar
+            },
+        };
+
+        if let Some(previous) = self.current.lock().unwrap().replace(bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn advance(&self, amount: u64) {
+        if let Some(bar) = self.current.lock().unwrap().as_ref() {
+            bar.inc(amount);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(bar) = self.current.lock().unwrap().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
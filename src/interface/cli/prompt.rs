@@ -0,0 +1,144 @@
+use dialoguer::{Confirm, FuzzySelect, MultiSelect, Password, Select};
+use std::io::{self, IsTerminal};
+use zeroize::Zeroizing;
+
+/// Environment variable that answers yes to every confirmation, like `--yes`
+pub const ASSUME_YES_ENV: &str = "SHELLBE_ASSUME_YES";
+
+/// Check `SHELLBE_ASSUME_YES` and `CI` for a request to never prompt
+pub fn assume_yes_from_env() -> bool {
+    is_truthy(ASSUME_YES_ENV) || is_truthy("CI")
+}
+
+fn is_truthy(var: &str) -> bool {
+    std::env::var(var)
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Asks questions on the terminal, or answers them itself when prompts can't be shown
+#[derive(Debug, Clone)]
+pub struct Prompter {
+    assume_yes: bool,
+    confirm: bool,
+}
+
+impl Default for Prompter {
+    fn default() -> Self {
+        Self {
+            assume_yes: assume_yes_from_env(),
+            confirm: true,
+        }
+    }
+}
+
+impl Prompter {
+    /// Answer yes to every confirmation (`--yes`); the environment can still turn this on
+    pub fn set_assume_yes(&mut self, assume_yes: bool) {
+        self.assume_yes = assume_yes || assume_yes_from_env();
+    }
+
+    /// Ask before destructive actions; `false` (`ui.confirm = false`) answers yes
+    pub fn set_confirm(&mut self, confirm: bool) {
+        self.confirm = confirm;
+    }
+
+    /// Whether `--yes`, `SHELLBE_ASSUME_YES` or `CI` asked to never prompt
+    pub fn assume_yes(&self) -> bool {
+        self.assume_yes
+    }
+
+    /// Check if prompts can be shown (not assuming yes and stdin is a terminal)
+    pub fn is_interactive(&self) -> bool {
+        !self.assume_yes && io::stdin().is_terminal()
+    }
+
+    /// Ask for confirmation; assume-yes or `ui.confirm = false` answers yes, and without a terminal the default is used
+    pub fn confirm(&self, prompt: impl Into<String>, default: bool) -> anyhow::Result<bool> {
+        if self.assume_yes || !self.confirm {
+            return Ok(true);
+        }
+
+        if !self.is_interactive() {
+            return Ok(default);
+        }
+
+        Ok(Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()?)
+    }
+
+    /// Let the user pick an item, using the default when prompts can't be shown
+    pub fn select(&self, prompt: &str, items: &[&str], default: usize) -> anyhow::Result<usize> {
+        if !self.is_interactive() {
+            return Ok(default);
+        }
+
+        Ok(Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact()?)
+    }
+
+    /// Let the user pick any number of items, all checked to start with
+    ///
+    /// Assume-yes picks everything; `None` when prompts can't be shown otherwise.
+    pub fn multi_select(&self, prompt: impl Into<String>, items: &[String]) -> anyhow::Result<Option<Vec<usize>>> {
+        if self.assume_yes {
+            return Ok(Some((0..items.len()).collect()));
+        }
+
+        if !self.is_interactive() {
+            return Ok(None);
+        }
+
+        Ok(MultiSelect::new()
+            .with_prompt(prompt)
+            .items(items)
+            .defaults(&vec![true; items.len()])
+            .interact_opt()?)
+    }
+
+    /// Let the user search for an item; `None` when cancelled or prompts can't be shown
+    pub fn fuzzy_select(&self, prompt: impl Into<String>, items: &[&str]) -> anyhow::Result<Option<usize>> {
+        if !self.is_interactive() {
+            return Ok(None);
+        }
+
+        Ok(FuzzySelect::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(0)
+            .interact_opt()?)
+    }
+
+    /// Ask for a secret without echoing it; `None` when prompts can't be shown
+    pub fn password(&self, prompt: impl Into<String>) -> anyhow::Result<Option<Zeroizing<String>>> {
+        if !self.is_interactive() {
+            return Ok(None);
+        }
+
+        Ok(Some(Zeroizing::new(Password::new()
+            .with_prompt(prompt)
+            .interact()?)))
+    }
+
+    /// Ask for a new secret twice, repeating until both match; `None` when prompts can't be shown
+    pub fn new_password(
+        &self,
+        prompt: impl Into<String>,
+        confirmation: impl Into<String>,
+        mismatch: impl Into<String>,
+    ) -> anyhow::Result<Option<Zeroizing<String>>> {
+        if !self.is_interactive() {
+            return Ok(None);
+        }
+
+        Ok(Some(Zeroizing::new(Password::new()
+            .with_prompt(prompt)
+            .with_confirmation(confirmation, mismatch)
+            .interact()?)))
+    }
+}
@@ -0,0 +1,147 @@
+use console::{measure_text_width, pad_str, Alignment, Term};
+
+use super::theme;
+
+/// Space between columns
+const COLUMN_GAP: usize = 2;
+
+/// Columns are never shrunk below this width when fitting the terminal
+const MIN_COLUMN_WIDTH: usize = 6;
+
+/// A table column
+#[derive(Debug, Clone)]
+pub struct Column {
+    /// Name used to pick the column with `--columns`
+    pub key: &'static str,
+    /// Header text
+    pub header: String,
+}
+
+impl Column {
+    pub fn new(key: &'static str, header: impl Into<String>) -> Self {
+        Self { key, header: header.into() }
+    }
+}
+
+/// Renders rows in auto-sized columns, truncating cells when the terminal is too narrow
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    visible: Vec<usize>,
+    max_width: Option<usize>,
+}
+
+impl Table {
+    /// Create a table showing all of the given columns
+    pub fn new(columns: Vec<Column>) -> Self {
+        let visible = (0..columns.len()).collect();
+
+        // Only fit to the terminal when there is one; piped output keeps full values
+        let max_width = Term::stdout().size_checked().map(|(_, width)| width as usize);
+
+        Self {
+            columns,
+            rows: Vec::new(),
+            visible,
+            max_width,
+        }
+    }
+
+    /// Show only the given columns, in the given order
+    pub fn select_columns(&mut self, keys: &[String]) -> Result<(), String> {
+        let mut visible = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let key = key.trim().to_lowercase();
+            match self.columns.iter().position(|c| c.key == key) {
+                Some(idx) => visible.push(idx),
+                None => {
+                    return Err(format!(
+                        "Unknown column '{}'. Available columns: {}",
+                        key,
+                        self.columns.iter().map(|c| c.key).collect::<Vec<_>>().join(", ")
+                    ));
+                },
+            }
+        }
+
+        if !visible.is_empty() {
+            self.visible = visible;
+        }
+
+        Ok(())
+    }
+
+    /// Add a row with one cell per column, in column order; cells may be styled
+    pub fn add_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Check if the table has no rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Width of each visible column
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.visible.iter()
+            .map(|&idx| {
+                self.rows.iter()
+                    .map(|row| row.get(idx).map_or(0, |cell| measure_text_width(cell)))
+                    .chain(std::iter::once(measure_text_width(&self.columns[idx].header)))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let Some(max_width) = self.max_width else {
+            return widths;
+        };
+
+        // Shrink the widest column until everything fits
+        let gaps = COLUMN_GAP * widths.len().saturating_sub(1);
+        while widths.iter().sum::<usize>() + gaps > max_width {
+            match widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max() {
+                Some(widest) => *widest -= 1,
+                None => break,
+            }
+        }
+
+        widths
+    }
+
+    /// Render the header, separators and rows
+    pub fn render(&self) -> String {
+        let widths = self.column_widths();
+        let total = widths.iter().sum::<usize>() + COLUMN_GAP * widths.len().saturating_sub(1);
+        let separator = theme::note("-".repeat(total)).to_string();
+
+        let render_line = |cells: Vec<String>| -> String {
+            let line = cells.iter()
+                .zip(&widths)
+                .map(|(cell, &width)| pad_str(cell, width, Alignment::Left, Some("…")).into_owned())
+                .collect::<Vec<_>>()
+                .join(&" ".repeat(COLUMN_GAP));
+            line.trim_end().to_string()
+        };
+
+        let header = render_line(self.visible.iter()
+            .map(|&idx| theme::heading(&self.columns[idx].header).to_string())
+            .collect());
+
+        let mut lines = vec![separator.clone(), header, separator];
+
+        for row in &self.rows {
+            lines.push(render_line(self.visible.iter()
+                .map(|&idx| row.get(idx).cloned().unwrap_or_default())
+                .collect()));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Print the table to stdout
+    pub fn print(&self) {
+        println!("{}", self.render());
+    }
+}
@@ -0,0 +1,201 @@
+use console::{Style, StyledObject};
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// Names of the built-in themes
+pub const THEME_NAMES: &[&str] = &["default", "high-contrast", "monochrome"];
+
+/// When to emit ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Only when writing to a terminal and `NO_COLOR` isn't set
+    Auto,
+    /// Always, even when piped
+    Always,
+    /// Never
+    Never,
+}
+
+impl ColorMode {
+    /// Parse a mode from its settings name (auto, always or never)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+}
+
+/// Enable or disable colors for stdout and stderr
+pub fn configure_colors(mode: ColorMode) {
+    let (stdout, stderr) = match mode {
+        ColorMode::Always => (true, true),
+        ColorMode::Never => (false, false),
+        ColorMode::Auto => {
+            // https://no-color.org: any non-empty value disables colors
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+            (
+                !no_color && std::io::stdout().is_terminal(),
+                !no_color && std::io::stderr().is_terminal(),
+            )
+        },
+    };
+
+    console::set_colors_enabled(stdout);
+    console::set_colors_enabled_stderr(stderr);
+}
+
+/// Styles for each kind of console output
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Success markers (✓)
+    pub success: Style,
+    /// Error markers (✗)
+    pub error: Style,
+    /// Warning markers (!)
+    pub warning: Style,
+    /// Titles, table headers and progress markers (→)
+    pub heading: Style,
+    /// Profile, alias and plugin names
+    pub highlight: Style,
+    /// Labels, paths and commands to run
+    pub accent: Style,
+    /// Separators, hints and previous values
+    pub note: Style,
+    /// Versions
+    pub info: Style,
+    /// De-emphasized details
+    pub muted: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            success: Style::new().green().bold(),
+            error: Style::new().red().bold(),
+            warning: Style::new().yellow().bold(),
+            heading: Style::new().cyan().bold(),
+            highlight: Style::new().green(),
+            accent: Style::new().cyan(),
+            note: Style::new().yellow(),
+            info: Style::new().blue(),
+            muted: Style::new().dim(),
+        }
+    }
+}
+
+impl Theme {
+    /// Bright colors for dark or low-contrast terminals
+    pub fn high_contrast() -> Self {
+        Self {
+            success: Style::new().green().bright().bold(),
+            error: Style::new().red().bright().bold(),
+            warning: Style::new().yellow().bright().bold(),
+            heading: Style::new().white().bright().bold().underlined(),
+            highlight: Style::new().green().bright(),
+            accent: Style::new().cyan().bright(),
+            note: Style::new().yellow().bright(),
+            info: Style::new().blue().bright(),
+            muted: Style::new().white(),
+        }
+    }
+
+    /// Emphasis without colors
+    pub fn monochrome() -> Self {
+        Self {
+            success: Style::new().bold(),
+            error: Style::new().bold(),
+            warning: Style::new().bold(),
+            heading: Style::new().bold(),
+            highlight: Style::new().bold(),
+            accent: Style::new().underlined(),
+            note: Style::new().italic(),
+            info: Style::new(),
+            muted: Style::new().dim(),
+        }
+    }
+
+    /// Look up a built-in theme by name
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "monochrome" => Some(Self::monochrome()),
+            _ => None,
+        }
+    }
+
+    /// Override one role with a dotted style such as `magenta.bold`
+    pub fn set_role(&mut self, role: &str, spec: &str) -> Result<(), String> {
+        let style = Style::from_dotted_str(spec);
+
+        let slot = match role {
+            "success" => &mut self.success,
+            "error" => &mut self.error,
+            "warning" => &mut self.warning,
+            "heading" => &mut self.heading,
+            "highlight" => &mut self.highlight,
+            "accent" => &mut self.accent,
+            "note" => &mut self.note,
+            "info" => &mut self.info,
+            "muted" => &mut self.muted,
+            _ => return Err(format!(
+                "Unknown theme color '{}'. Available: success, error, warning, heading, highlight, accent, note, info, muted",
+                role
+            )),
+        };
+
+        *slot = style;
+        Ok(())
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Set the theme used for console output; only the first call has an effect
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Current console theme
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+pub fn success<D>(val: D) -> StyledObject<D> {
+    theme().success.apply_to(val)
+}
+
+pub fn error<D>(val: D) -> StyledObject<D> {
+    theme().error.apply_to(val)
+}
+
+pub fn warning<D>(val: D) -> StyledObject<D> {
+    theme().warning.apply_to(val)
+}
+
+pub fn heading<D>(val: D) -> StyledObject<D> {
+    theme().heading.apply_to(val)
+}
+
+pub fn highlight<D>(val: D) -> StyledObject<D> {
+    theme().highlight.apply_to(val)
+}
+
+pub fn accent<D>(val: D) -> StyledObject<D> {
+    theme().accent.apply_to(val)
+}
+
+pub fn note<D>(val: D) -> StyledObject<D> {
+    theme().note.apply_to(val)
+}
+
+pub fn info<D>(val: D) -> StyledObject<D> {
+    theme().info.apply_to(val)
+}
+
+pub fn muted<D>(val: D) -> StyledObject<D> {
+    theme().muted.apply_to(val)
+}
@@ -0,0 +1,142 @@
+use super::convert::status_error;
+use super::proto::{self, shell_be_client::ShellBeClient};
+use crate::domain::{DomainError, Profile, ProfileRepository};
+use crate::interface::api::ApiToken;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::{InterceptedService, Interceptor};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+
+/// Environment variable with the address of a running daemon, e.g. `http://127.0.0.1:7879`
+pub const DAEMON_ENV: &str = "SHELLBE_DAEMON";
+
+/// Adds the API token to every call
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    authorization: MetadataValue<Ascii>,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert("authorization", self.authorization.clone());
+        Ok(request)
+    }
+}
+
+/// Connection to a running `shellbe serve --grpc` daemon
+#[derive(Clone)]
+pub struct DaemonClient {
+    client: ShellBeClient<InterceptedService<Channel, AuthInterceptor>>,
+}
+
+impl DaemonClient {
+    /// Connect to the daemon at `address`, authenticating with the API token
+    pub async fn connect(address: &str, token: &ApiToken) -> Result<Self, DomainError> {
+        let channel = Endpoint::from_shared(address.to_string())
+            .map_err(|e| DomainError::ConfigError(format!("Invalid daemon address '{}': {}", address, e)))?
+            .connect()
+            .await
+            .map_err(|e| DomainError::ConfigError(format!(
+                "The ShellBe daemon at {} is not reachable: {}. Start it with `shellbe serve --grpc` or unset {}",
+                address, e, DAEMON_ENV,
+            )))?;
+
+        let authorization = format!("Bearer {}", token.as_str()).parse()
+            .map_err(|_| DomainError::ConfigError("The API token is not a valid header value".to_string()))?;
+
+        Ok(Self {
+            client: ShellBeClient::with_interceptor(channel, AuthInterceptor { authorization }),
+        })
+    }
+
+    /// A client handle for one call; handles share the connection
+    fn client(&self) -> ShellBeClient<InterceptedService<Channel, AuthInterceptor>> {
+        self.client.clone()
+    }
+
+    /// Events published by the daemon from now on
+    pub async fn events(&self) -> Result<tonic::Streaming<proto::Event>, DomainError> {
+        let response = self.client().stream_events(proto::StreamEventsRequest {}).await
+            .map_err(|status| status_error(status, ""))?;
+        Ok(response.into_inner())
+    }
+
+    /// Have the daemon publish its journaled events since `since` again; returns how many
+    pub async fn replay_events(&self, since: DateTime<Utc>, kind: Option<String>, subject: Option<String>) -> Result<usize, DomainError> {
+        let request = proto::ReplayEventsRequest {
+            since: since.to_rfc3339(),
+            kinds: kind.into_iter().collect(),
+            subject,
+        };
+
+        let response = self.client().replay_events(request).await
+            .map_err(|status| status_error(status, ""))?;
+        Ok(response.into_inner().replayed as usize)
+    }
+}
+
+/// Profiles kept by a running daemon instead of local files
+///
+/// Every read and write goes through the daemon, so any number of CLI invocations can
+/// run at once without contending for `profiles.json`.
+pub struct GrpcProfileRepository {
+    daemon: DaemonClient,
+}
+
+impl GrpcProfileRepository {
+    /// Profiles of the daemon behind this client
+    pub fn new(daemon: DaemonClient) -> Self {
+        Self { daemon }
+    }
+
+    fn client(&self) -> ShellBeClient<InterceptedService<Channel, AuthInterceptor>> {
+        self.daemon.client()
+    }
+}
+
+#[async_trait]
+impl ProfileRepository for GrpcProfileRepository {
+    async fn add(&self, profile: Profile) -> Result<(), DomainError> {
+        let name = profile.name.clone();
+        self.client().add_profile(proto::Profile::from(profile)).await
+            .map_err(|status| status_error(status, &name))?;
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Profile>, DomainError> {
+        match self.client().get_profile(proto::ProfileName { name: name.to_string() }).await {
+            Ok(response) => Ok(Some(response.into_inner().try_into()?)),
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(status) => Err(status_error(status, name)),
+        }
+    }
+
+    async fn update(&self, profile: Profile) -> Result<(), DomainError> {
+        let name = profile.name.clone();
+        self.client().update_profile(proto::Profile::from(profile)).await
+            .map_err(|status| status_error(status, &name))?;
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str) -> Result<(), DomainError> {
+        self.client().remove_profile(proto::ProfileName { name: name.to_string() }).await
+            .map_err(|status| status_error(status, name))?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<Profile>, DomainError> {
+        let response = self.client().list_profiles(proto::ListProfilesRequest {}).await
+            .map_err(|status| status_error(status, ""))?;
+
+        response.into_inner().profiles.into_iter().map(Profile::try_from).collect()
+    }
+
+    async fn exists(&self, name: &str) -> Result<bool, DomainError> {
+        let response = self.client().profile_exists(proto::ProfileName { name: name.to_string() }).await
+            .map_err(|status| status_error(status, name))?;
+
+        Ok(response.into_inner().exists)
+    }
+}
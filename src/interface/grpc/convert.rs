@@ -0,0 +1,140 @@
+use super::proto;
+use crate::domain::{DomainError, EventEnvelope, KubernetesPod, Profile, ProfileKind};
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use tonic::{Code, Status};
+
+impl From<Profile> for proto::Profile {
+    fn from(profile: Profile) -> Self {
+        let (docker_container, docker_host_profile, kubernetes) = match profile.kind {
+            ProfileKind::Ssh => (None, None, None),
+            ProfileKind::Docker { container, host_profile } => (Some(container), host_profile, None),
+            ProfileKind::Kubernetes(pod) => (None, None, Some(proto::KubernetesPod {
+                context: pod.context,
+                namespace: pod.namespace,
+                pod: pod.pod,
+                selector: pod.selector,
+                container: pod.container,
+            })),
+        };
+
+        Self {
+            name: profile.name,
+            hostname: profile.hostname,
+            username: profile.username,
+            port: profile.port.into(),
+            identity_file: profile.identity_file.map(|path| path.to_string_lossy().into_owned()),
+            identity_passphrase: profile.identity_passphrase,
+            password: profile.password,
+            vault_role: profile.vault_role,
+            resolve_to: profile.resolve_to.map(|address| address.to_string()),
+            quiet: profile.quiet,
+            banner: profile.banner,
+            docker_container,
+            docker_host_profile,
+            kubernetes,
+            options: profile.options,
+            tags: profile.tags,
+            created_at: profile.created_at.map(|time| time.to_rfc3339()),
+            updated_at: profile.updated_at.map(|time| time.to_rfc3339()),
+            last_used: profile.last_used.map(|time| time.to_rfc3339()),
+        }
+    }
+}
+
+impl TryFrom<proto::Profile> for Profile {
+    type Error = DomainError;
+
+    fn try_from(profile: proto::Profile) -> Result<Self, Self::Error> {
+        let port = u16::try_from(profile.port)
+            .ok()
+            .filter(|port| *port != 0)
+            .ok_or_else(|| DomainError::ConfigError(format!("Invalid port {} for profile '{}'", profile.port, profile.name)))?;
+
+        let resolve_to = profile.resolve_to
+            .map(|address| address.parse::<IpAddr>()
+                .map_err(|e| DomainError::ConfigError(format!("Invalid resolve_to '{}' for profile '{}': {}", address, profile.name, e))))
+            .transpose()?;
+        let kind = match (profile.docker_container, profile.kubernetes) {
+            (Some(container), _) => ProfileKind::Docker { container, host_profile: profile.docker_host_profile },
+            (None, Some(pod)) => ProfileKind::Kubernetes(KubernetesPod {
+                context: pod.context,
+                namespace: pod.namespace,
+                pod: pod.pod,
+                selector: pod.selector,
+                container: pod.container,
+            }),
+            (None, None) => ProfileKind::Ssh,
+        };
+
+        Ok(Self {
+            name: profile.name,
+            hostname: profile.hostname,
+            username: profile.username,
+            port,
+            identity_file: profile.identity_file.map(PathBuf::from),
+            identity_passphrase: profile.identity_passphrase,
+            password: profile.password,
+            vault_role: profile.vault_role,
+            resolve_to,
+            quiet: profile.quiet,
+            banner: profile.banner,
+            kind,
+            options: profile.options,
+            tags: profile.tags,
+            created_at: parse_time(profile.created_at)?,
+            updated_at: parse_time(profile.updated_at)?,
+            last_used: parse_time(profile.last_used)?,
+        })
+    }
+}
+
+fn parse_time(time: Option<String>) -> Result<Option<DateTime<Utc>>, DomainError> {
+    time.map(|time| {
+        DateTime::parse_from_rfc3339(&time)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|e| DomainError::ConfigError(format!("Invalid timestamp '{}': {}", time, e)))
+    })
+    .transpose()
+}
+
+/// gRPC status for a domain error
+pub fn error_status(error: DomainError) -> Status {
+    let code = match &error {
+        DomainError::ProfileNotFound(_) | DomainError::AliasNotFound(_) => Code::NotFound,
+        DomainError::ProfileAlreadyExists(_) | DomainError::AliasAlreadyExists(_) => Code::AlreadyExists,
+        DomainError::PolicyViolation(_) => Code::PermissionDenied,
+        DomainError::ConfigError(_) => Code::InvalidArgument,
+        DomainError::Unavailable(_) => Code::Unavailable,
+        DomainError::SshError(_) | DomainError::IoError(_) => Code::Internal,
+    };
+
+    Status::new(code, crate::utils::redact_text(&error.to_string()))
+}
+
+/// Domain error for a status from the daemon, about the profile `name`
+pub fn status_error(status: Status, name: &str) -> DomainError {
+    match status.code() {
+        Code::NotFound => DomainError::ProfileNotFound(name.to_string()),
+        Code::AlreadyExists => DomainError::ProfileAlreadyExists(name.to_string()),
+        Code::PermissionDenied => DomainError::PolicyViolation(status.message().to_string()),
+        Code::InvalidArgument => DomainError::ConfigError(status.message().to_string()),
+        Code::Unauthenticated => DomainError::ConfigError(format!("The ShellBe daemon refused the API token: {}", status.message())),
+        Code::Unavailable => DomainError::Unavailable(format!("The ShellBe daemon is not reachable: {}", status.message())),
+        _ => DomainError::ConfigError(format!("ShellBe daemon error: {}", status.message())),
+    }
+}
+
+/// Event message for a published event
+pub fn event_message(envelope: &EventEnvelope) -> proto::Event {
+    let json = serde_json::to_value(envelope).unwrap_or_default();
+
+    proto::Event {
+        kind: envelope.event.kind().to_string(),
+        subject: envelope.event.subject().to_string(),
+        timestamp: envelope.timestamp.to_rfc3339(),
+        payload_json: json["data"].to_string(),
+        envelope_json: json.to_string(),
+    }
+}
@@ -0,0 +1,42 @@
+use crate::domain::{Event, EventEnvelope, EventListener};
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+/// Events kept for subscribers that fall behind; older ones are dropped
+const EVENT_BUFFER: usize = 256;
+
+/// Event listener that fans domain events out to any number of streaming clients
+pub struct EventStream {
+    sender: broadcast::Sender<EventEnvelope>,
+}
+
+impl EventStream {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        Self { sender }
+    }
+
+    /// Receive events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<EventEnvelope> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EventListener for EventStream {
+    async fn on_event(&self, event: &Event) {
+        // Fails only when nobody is subscribed
+        let _ = self.sender.send(EventEnvelope::new(event.clone()));
+    }
+
+    /// Sent as journaled, so clients can recognize events they already have by ID
+    async fn on_replay(&self, envelope: &EventEnvelope) {
+        let _ = self.sender.send(envelope.clone());
+    }
+}
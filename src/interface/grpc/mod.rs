@@ -0,0 +1,13 @@
+pub mod client;
+pub mod convert;
+pub mod event_stream;
+pub mod server;
+
+/// Types and stubs generated from `proto/shellbe.proto`
+pub mod proto {
+    tonic::include_proto!("shellbe.v1");
+}
+
+pub use client::{DaemonClient, GrpcProfileRepository, DAEMON_ENV};
+pub use event_stream::EventStream;
+pub use server::{serve, GrpcService};
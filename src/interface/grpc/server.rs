@@ -0,0 +1,157 @@
+use super::convert::{error_status, event_message};
+use super::event_stream::EventStream;
+use super::proto::{self, shell_be_server::{ShellBe, ShellBeServer}};
+use crate::application::{ConnectionService, ProfileService};
+use crate::domain::{DomainError, EventBus, EventFilter, Profile};
+use crate::infrastructure::EventJournal;
+use crate::interface::api::ApiToken;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// The gRPC API over the same services as the HTTP API
+pub struct GrpcService {
+    profiles: Arc<ProfileService>,
+    connections: Arc<ConnectionService>,
+    events: Arc<EventStream>,
+    /// Journal and bus for ReplayEvents, when the journal is on
+    replay: Option<(EventJournal, Arc<EventBus>)>,
+}
+
+impl GrpcService {
+    /// Serve these services; `events` must be registered on the services' event bus
+    pub fn new(profiles: Arc<ProfileService>, connections: Arc<ConnectionService>, events: Arc<EventStream>) -> Self {
+        Self {
+            profiles,
+            connections,
+            events,
+            replay: None,
+        }
+    }
+
+    /// Answer ReplayEvents by publishing events from `journal` again on `bus`
+    pub fn set_replay(&mut self, journal: EventJournal, bus: Arc<EventBus>) {
+        self.replay = Some((journal, bus));
+    }
+}
+
+/// Serve the gRPC API to clients presenting the token, until Ctrl-C
+pub async fn serve(listener: tokio::net::TcpListener, service: GrpcService, token: Arc<ApiToken>) -> Result<(), tonic::transport::Error> {
+    let authenticate = move |request: Request<()>| {
+        let presented = request.metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match presented {
+            Some(presented) if token.matches(presented) => Ok(request),
+            _ => Err(Status::unauthenticated("Missing or wrong bearer token")),
+        }
+    };
+
+    tonic::transport::Server::builder()
+        .add_service(ShellBeServer::with_interceptor(service, authenticate))
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+fn profile_from(profile: proto::Profile) -> Result<Profile, Status> {
+    Profile::try_from(profile).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+#[tonic::async_trait]
+impl ShellBe for GrpcService {
+    async fn list_profiles(&self, _request: Request<proto::ListProfilesRequest>) -> Result<Response<proto::ListProfilesResponse>, Status> {
+        let profiles = self.profiles.list_profiles().await.map_err(error_status)?;
+
+        Ok(Response::new(proto::ListProfilesResponse {
+            profiles: profiles.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_profile(&self, request: Request<proto::ProfileName>) -> Result<Response<proto::Profile>, Status> {
+        let profile = self.profiles.get_profile(&request.into_inner().name).await.map_err(error_status)?;
+        Ok(Response::new(profile.into()))
+    }
+
+    async fn profile_exists(&self, request: Request<proto::ProfileName>) -> Result<Response<proto::ProfileExistsResponse>, Status> {
+        let exists = match self.profiles.get_profile(&request.into_inner().name).await {
+            Ok(_) => true,
+            Err(DomainError::ProfileNotFound(_)) => false,
+            Err(e) => return Err(error_status(e)),
+        };
+
+        Ok(Response::new(proto::ProfileExistsResponse { exists }))
+    }
+
+    async fn add_profile(&self, request: Request<proto::Profile>) -> Result<Response<proto::Profile>, Status> {
+        let profile = profile_from(request.into_inner())?;
+        let name = profile.name.clone();
+
+        self.profiles.add_profile(profile).await.map_err(error_status)?;
+
+        // The profile as saved, with the options the policy added
+        let saved = self.profiles.get_profile(&name).await.map_err(error_status)?;
+        Ok(Response::new(saved.into()))
+    }
+
+    async fn update_profile(&self, request: Request<proto::Profile>) -> Result<Response<proto::Profile>, Status> {
+        let profile = profile_from(request.into_inner())?;
+        let name = profile.name.clone();
+
+        self.profiles.update_profile(profile).await.map_err(error_status)?;
+
+        let saved = self.profiles.get_profile(&name).await.map_err(error_status)?;
+        Ok(Response::new(saved.into()))
+    }
+
+    async fn remove_profile(&self, request: Request<proto::ProfileName>) -> Result<Response<proto::RemoveProfileResponse>, Status> {
+        self.profiles.remove_profile(&request.into_inner().name).await.map_err(error_status)?;
+        Ok(Response::new(proto::RemoveProfileResponse {}))
+    }
+
+    async fn test_connection(&self, request: Request<proto::ProfileName>) -> Result<Response<proto::TestConnectionResponse>, Status> {
+        let name = request.into_inner().name;
+        let address = self.connections.test_connection(&name).await.map_err(error_status)?;
+
+        Ok(Response::new(proto::TestConnectionResponse {
+            name,
+            success: address.is_some(),
+            address: address.map(|address| address.to_string()).unwrap_or_default(),
+        }))
+    }
+
+    type StreamEventsStream = Pin<Box<dyn Stream<Item = Result<proto::Event, Status>> + Send>>;
+
+    async fn stream_events(&self, _request: Request<proto::StreamEventsRequest>) -> Result<Response<Self::StreamEventsStream>, Status> {
+        // Clients that fall too far behind miss events rather than slowing everyone down
+        let stream = BroadcastStream::new(self.events.subscribe())
+            .filter_map(|event| event.ok().map(|event| Ok(event_message(&event))));
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn replay_events(&self, request: Request<proto::ReplayEventsRequest>) -> Result<Response<proto::ReplayEventsResponse>, Status> {
+        let (journal, bus) = self.replay.as_ref()
+            .ok_or_else(|| Status::failed_precondition("The daemon keeps no event journal; set journal = true in its [events] settings"))?;
+
+        let request = request.into_inner();
+        let since = chrono::DateTime::parse_from_rfc3339(&request.since)
+            .map_err(|e| Status::invalid_argument(format!("Invalid time '{}': {}", request.since, e)))?;
+
+        let mut filter = EventFilter::all();
+        for kind in request.kinds {
+            filter = filter.kind(kind);
+        }
+        if let Some(subject) = request.subject {
+            filter = filter.subject(subject);
+        }
+
+        let replayed = journal.replay(bus, since.with_timezone(&chrono::Utc), &filter).await.map_err(error_status)?;
+        Ok(Response::new(proto::ReplayEventsResponse { replayed: replayed as u32 }))
+    }
+}
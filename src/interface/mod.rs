@@ -1,3 +1,5 @@
+pub mod api;
 pub mod cli;
+pub mod grpc;
 
-pub use cli::{Cli, CommandHandler};
\ No newline at end of file
+pub use cli::{Cli, CommandHandler, OutputFormat};
\ No newline at end of file
@@ -1,23 +1,26 @@
-pub mod domain;
-pub mod application;
-pub mod infrastructure;
+// The core lives in the shellbe-core crate; its modules are re-exported so paths like
+// `shellbe::domain` keep working
+pub use shellbe_core::{domain, application, infrastructure, utils, errors};
+
 pub mod interface;
-pub mod utils;
-pub mod errors;
+
+/// Version of the shellbe binary, which update checks compare releases against
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 /// Re-export common types
-pub use domain::{
+pub use shellbe_core::{
     Profile, Alias, HistoryEntry, ConnectionStats,
     Event, EventBus, EventListener,
     Plugin, PluginInfo, PluginCommand, Hook, PluginStatus, PluginMetadata,
 };
 
-pub use application::{
+pub use shellbe_core::{
     ProfileService, ConnectionService, AliasService,
     PluginService, SshConfigService, UpdateService,
+    ShellBe, ShellBeBuilder,
 };
 
-pub use infrastructure::{
+pub use shellbe_core::{
     FileProfileRepository, FileAliasRepository, FileHistoryRepository,
     FilePluginRepository, FileSshConfigRepository, ThrushSshService,
 };
@@ -25,11 +28,11 @@ pub use infrastructure::{
 pub use interface::{Cli, CommandHandler};
 
 // Re-export error and result types
-pub use errors::{ShellBeError, Result, ErrorContext};
+pub use errors::{ShellBeError, Result, ErrorContext, ErrorReport, Suggestion};
 
 // Re-export useful utility functions
 pub use utils::{
     ensure_directory, ensure_file, backup_file,
     shellbe_config_dir, ssh_config_dir,
     FileLock, PluginSecurityValidator, SystemRequirements
-};
\ No newline at end of file
+};
@@ -1,158 +1,339 @@
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, ValueEnum};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::IsTerminal;
+use std::process::ExitCode;
 use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use shellbe::{
     application::{
-        AliasService, ConnectionService, ProfileService, PluginService, SshConfigService,
-    },
-    domain::EventBus,
-    infrastructure::{
-        FileAliasRepository, FileHistoryRepository, FilePluginRepository,
-        FileProfileRepository, FileSshConfigRepository, ThrushSshService,
+        DoctorService, SettingsService, TotpService, UpdateNotifier, UpdateSource, SETTINGS_FILE, UPDATE_CHECK_GRACE,
+        TelemetryService, TELEMETRY_SEND_GRACE,
     },
+    domain::{EventBus, EVENT_FLUSH_TIMEOUT},
+    infrastructure::{EventJournal, answer_prompt, is_askpass},
     interface::{Cli, CommandHandler},
-    utils::{SystemRequirements, PluginSecurityValidator},
-    ShellBeError, Result, ErrorContext,
+    interface::api::ApiToken,
+    interface::grpc::{DaemonClient, EventStream, GrpcProfileRepository},
+    interface::cli::{
+        commands::{Commands, OutputFormat},
+        handler::{daemon_address, default_ssh_config_path, sync_service, vault_service},
+        i18n::{detect_locale, set_locale, LOCALES_DIR},
+        output::{print_error, set_json_errors, set_verbosity, TimeFormat, Verbosity},
+        progress::ProgressBars,
+        prompt::Prompter,
+        theme::{configure_colors, set_theme, ColorMode, Theme, THEME_NAMES},
+    },
+    utils::{configure_http, expand_path, redact_text, HttpOptions, RedactingWriter, SystemRequirements, ShellBePaths, CONFIG_DIR_ENV},
+    errors::exit_code,
+    ShellBeBuilder, ShellBeError, ErrorContext, ErrorReport,
 };
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> ExitCode {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed building the Runtime");
+
+    let code = match runtime.block_on(run()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(report) => {
+            print_error(&report);
+            ExitCode::from(report.error.exit_code())
+        },
+    };
+
+    // A plugin hook that overran its timeout may still hold a blocking thread; don't wait for it
+    runtime.shutdown_background();
+    code
+}
+
+async fn run() -> std::result::Result<(), ErrorReport> {
+    // ssh runs us as SSH_ASKPASS with the prompt as the only argument
+    if is_askpass() {
+        let prompt = std::env::args().nth(1).unwrap_or_default();
+        let secret = answer_prompt(&prompt).map_err(|e| ShellBeError::Config(e.to_string()))?;
+        println!("{}", secret.expose());
+        return Ok(());
+    }
+
     // Initialize error handling and tracing
     color_eyre::install()
         .map_err(|e| ShellBeError::Config(format!("Failed to initialize error handling: {}", e)))?;
 
+    // Parse command line arguments, expanding command aliases from settings
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let aliases = command_aliases(&args);
+    let args = Cli::expand_aliases(args, &aliases);
+    let cli = Cli::try_parse_from(&args).unwrap_or_else(|e| exit_usage(e));
+
+    // --no-color and NO_COLOR disable colors, as does writing to a pipe
+    configure_colors(if cli.no_color { ColorMode::Never } else { ColorMode::Auto });
+    set_json_errors(cli.output == Some(OutputFormat::Json));
+
+    // Explicit -q/-v flags take precedence over RUST_LOG
+    let verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+    set_verbosity(verbosity);
+
+    let env_filter = if cli.quiet || cli.verbose > 0 {
+        tracing_subscriber::EnvFilter::new(verbosity.tracing_filter(cli.verbose))
+    } else {
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| verbosity.tracing_filter(cli.verbose).into())
+    };
+
     tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::try_from_default_env()
-            .unwrap_or_else(|_| "info".into()))
-        .with(tracing_subscriber::fmt::layer())
+        .with(env_filter)
+        .with(tracing_subscriber::fmt::layer()
+            .with_ansi(console::colors_enabled())
+            .with_writer(RedactingWriter::stdout))
         .init();
 
-    // Check system requirements
-    let system_requirements = SystemRequirements::default();
+    // An explicit directory keeps all state in one place, for every part of the app
+    if let Some(dir) = &cli.config_dir {
+        std::env::set_var(CONFIG_DIR_ENV, dir);
+    }
+
+    // Resolve config/data/cache directories, moving files over from ~/.shellbe if needed
+    let paths = ShellBePaths::resolve();
+    match paths.migrate_legacy() {
+        Ok(moved) if !moved.is_empty() => {
+            tracing::info!("Migrated {} entries from ~/.shellbe to {}", moved.len(), paths.data_dir.display());
+        },
+        Ok(_) => {},
+        Err(e) => tracing::warn!("Failed to migrate ~/.shellbe: {}", e),
+    }
+
+    // Doctor runs before anything that would fail on a broken setup
+    if let Some(Commands::Doctor { fix }) = cli.command {
+        let doctor = DoctorService::new(paths.clone(), default_ssh_config_path());
+        let healthy = CommandHandler::handle_doctor(&doctor, cli.output.unwrap_or_default(), fix).await
+            .map_err(|e| ShellBeError::Config(format!("Failed to run diagnostics: {}", e)))?;
+        if !healthy {
+            return Err(ShellBeError::Other("doctor found problems".to_string()).into());
+        }
+        return Ok(());
+    }
+
+    // Load user settings; command line flags take precedence
+    let settings_service = SettingsService::load(paths.config_dir.join(SETTINGS_FILE))
+        .map_err(|e| ShellBeError::Config(e.to_string()))?;
+    let settings = settings_service.settings().clone();
+
+    // Settings choose colors and the theme unless --no-color was given
+    if !cli.no_color {
+        let mode = ColorMode::from_name(&settings.ui.color).unwrap_or(ColorMode::Auto);
+        configure_colors(mode);
+    }
+
+    let mut theme = Theme::named(&settings.ui.theme)
+        .ok_or_else(|| ShellBeError::Config(format!(
+            "Unknown theme '{}' in settings. Available themes: {}",
+            settings.ui.theme,
+            THEME_NAMES.join(", ")
+        )))?;
+    for (role, spec) in &settings.ui.colors {
+        theme.set_role(role, spec)
+            .map_err(|e| ShellBeError::Config(format!("Invalid theme color in settings: {}", e)))?;
+    }
+    set_theme(theme);
+
+    // Messages follow the configured locale, or the environment's
+    let configured_locale = Some(settings.ui.locale.as_str()).filter(|locale| *locale != "auto");
+    set_locale(&detect_locale(configured_locale), Some(&paths.config_dir.join(LOCALES_DIR)));
+
+    // Every network call goes through the proxy and trusts the extra root certificates
+    configure_http(&HttpOptions {
+        proxy: cli.proxy.clone().or_else(|| settings.network.proxy.clone()),
+        ca_certificates: settings.network.ca_certificates.clone(),
+    }).map_err(|e| ShellBeError::Config(e.to_string()))?;
+
+    let output = match cli.output {
+        Some(output) => output,
+        None => OutputFormat::from_str(&settings.ui.output, true)
+            .map_err(|e| ShellBeError::Config(format!("Invalid output format in settings: {}", e)))?,
+    };
+    set_json_errors(output == OutputFormat::Json);
+
+    // Check system requirements; a successful check is cached, 'doctor' always runs the full one
+    let mut system_requirements = SystemRequirements::default();
+    system_requirements.set_cache_dir(&paths.cache_dir);
     system_requirements.all_requirements_met()
-        .with_context(|| "Failed to start: system requirements not met".to_string())?;
+        .with_context(|| "Failed to start: system requirements not met. Run `shellbe doctor` for details".to_string())?;
 
-    // Parse command line arguments
-    let cli = Cli::parse();
+    // Look for a new release on the side while the command runs; interactive runs only, and
+    // not for commands that deal with updates or run until stopped
+    let notify_updates = verbosity != Verbosity::Quiet
+        && std::io::stderr().is_terminal()
+        && !matches!(cli.command, Some(Commands::Update { .. } | Commands::Complete { .. } | Commands::Serve { .. } | Commands::Events(_)));
+    let mut update_notifier = UpdateNotifier::new(&paths.cache_dir, shellbe::VERSION, settings.updates.check, settings.updates.channel);
+    update_notifier.set_source(UpdateSource::from(&settings.updates));
+    let update_check = if notify_updates { update_notifier.spawn_check() } else { None };
 
-    // Initialize config directory
-    let config_dir = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".shellbe");
+    // Opted-in telemetry counts commands by name only; completion runs on every Tab press
+    let telemetry = TelemetryService::new(&paths.data_dir, &settings.telemetry);
+    let command_name = Cli::command_name(&args)
+        .filter(|_| !matches!(cli.command, Some(Commands::Complete { .. } | Commands::GenerateDocs { .. })));
+    let telemetry_send = command_name.as_ref().and_then(|_| telemetry.spawn_send());
 
-    // Create directory if it doesn't exist
-    if !config_dir.exists() {
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| ShellBeError::Io(format!("Failed to create config directory: {}", e)))?;
+    // Create directories if they don't exist
+    for dir in [&paths.config_dir, &paths.data_dir] {
+        if dir.exists() {
+            continue;
+        }
+
+        std::fs::create_dir_all(dir)
+            .map_err(|e| ShellBeError::Io(format!("Failed to create directory {}: {}", dir.display(), e)))?;
 
         // Set proper permissions on Unix platforms
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            let metadata = std::fs::metadata(&config_dir)
+            let metadata = std::fs::metadata(dir)
                 .map_err(|e| ShellBeError::Io(format!("Failed to get directory metadata: {}", e)))?;
 
             let mut permissions = metadata.permissions();
             permissions.set_mode(0o700);
 
-            std::fs::set_permissions(&config_dir, permissions)
+            std::fs::set_permissions(dir, permissions)
                 .map_err(|e| ShellBeError::Io(format!("Failed to set directory permissions: {}", e)))?;
         }
     }
 
-    // Initialize event bus
-    let event_bus = Arc::new(EventBus::new());
-
-    // Initialize repositories
-    let storage_config = FileStorageConfig {
-        config_dir: config_dir.clone(),
-        profiles_file: "profiles.json".to_string(),
-    };
+    let data_dir = paths.data_dir.clone();
 
-    let profile_repository = Arc::new(FileProfileRepository::new(storage_config).await
-        .map_err(|e| ShellBeError::Config(format!("Failed to initialize profile repository: {}", e)))?);
+    let mut prompt = Prompter::default();
+    prompt.set_assume_yes(cli.yes);
 
-    let alias_repository = Arc::new(FileAliasRepository::new(config_dir.clone(), "aliases.json".to_string()).await
-        .map_err(|e| ShellBeError::Config(format!("Failed to initialize alias repository: {}", e)))?);
+    // Vault commands manage the key themselves, so they run before the data files are opened
+    let vault = vault_service(&paths, &settings);
+    if let Some(Commands::Vault(args)) = &cli.command {
+        return CommandHandler::handle_vault(&vault, args, &prompt, output).await
+            .map_err(|e| ShellBeError::Security(redact_text(&e.to_string())).into());
+    }
 
-    let history_repository = Arc::new(FileHistoryRepository::new(config_dir.clone(), "history.json".to_string()).await
-        .map_err(|e| ShellBeError::Config(format!("Failed to initialize history repository: {}", e)))?);
+    // Data files are encrypted when the vault is set up
+    let storage = CommandHandler::vault_storage(&vault, &prompt)
+        .map_err(|e| ShellBeError::Security(redact_text(&e.to_string())))?;
 
-    // Initialize SSH service
-    let ssh_service = Arc::new(ThrushSshService::new());
+    // Sync rewrites the data files, so it runs before the repositories read them
+    if let Some(Commands::Sync(args)) = &cli.command {
+        let sync = sync_service(&paths, &settings, storage)
+            .map_err(|e| ShellBeError::Config(redact_text(&e.to_string())))?;
+        return CommandHandler::handle_sync(&sync, args, &prompt, output).await
+            .map_err(|e| ShellBeError::Config(redact_text(&e.to_string())).into());
+    }
 
-    // Initialize SSH config repository
-    let ssh_config_path = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".ssh")
-        .join("config");
+    // Initialize event bus; 'serve --grpc' streams its events to clients
+    let event_stream = Arc::new(EventStream::new());
+    let event_bus = Arc::new(EventBus::new());
+    event_bus.register(event_stream.clone());
+    if settings.events.journal {
+        let events = &settings.events;
+        event_bus.register(Arc::new(EventJournal::new(&data_dir, events.journal_max_bytes, events.journal_files)));
+    }
 
-    let ssh_config_repository = Arc::new(FileSshConfigRepository::new(ssh_config_path));
+    let mut builder = ShellBeBuilder::new()
+        .paths(paths.clone())
+        .settings(settings.clone())
+        .storage(storage.clone())
+        .ssh_config_path(default_ssh_config_path())
+        .event_bus(event_bus.clone())
+        .strict_host_keys(cli.strict)
+        .progress(Arc::new(ProgressBars::default()));
 
-    // Initialize plugin system
-    let plugins_dir = config_dir.join("plugins");
-    if !plugins_dir.exists() {
-        std::fs::create_dir_all(&plugins_dir)
-            .map_err(|e| ShellBeError::Io(format!("Failed to create plugins directory: {}", e)))?;
+    // With a daemon running, profiles go through it so concurrent runs don't contend for the file;
+    // the daemon itself always uses the files
+    let daemon_address = daemon_address(&settings)
+        .filter(|_| !matches!(cli.command, Some(Commands::Serve { .. })));
+    if let Some(address) = daemon_address {
+        let token = ApiToken::load_or_create(&paths.cache_dir)
+            .map_err(|e| ShellBeError::Config(e.to_string()))?;
+        let daemon = DaemonClient::connect(&address, &token).await
+            .map_err(|e| ShellBeError::Config(e.to_string()))?;
+        builder = builder.profile_repository(Arc::new(GrpcProfileRepository::new(daemon)));
     }
 
-    let plugin_repository = Arc::new(FilePluginRepository::new(config_dir.clone(), "plugins.json".to_string()).await
-        .map_err(|e| ShellBeError::Config(format!("Failed to initialize plugin repository: {}", e)))?);
+    // The CLI runs the same services the library builds for embedding tools
+    let shellbe = builder.build().await?;
 
-    // Create plugin service with security validation
-    let mut plugin_service = PluginService::new(
-        plugin_repository,
-        event_bus.clone(),
-        plugins_dir.clone(),
-    );
+    // Warn loudly when private files or identity files are readable by other users
+    let identity_files: Vec<_> = shellbe.profiles().list_profiles().await
+        .map(|profiles| profiles.into_iter().filter_map(|profile| profile.identity_file).map(expand_path).collect())
+        .unwrap_or_default();
+    CommandHandler::warn_permission_issues(&DoctorService::new(paths.clone(), default_ssh_config_path()), &identity_files);
+
+    // Apply history retention from settings
+    if let Some(days) = settings.history.retention_days {
+        match shellbe.connections().prune_history(days).await {
+            Ok(removed) if removed > 0 => tracing::debug!("Pruned {} history entries older than {} days", removed, days),
+            Ok(_) => {},
+            Err(e) => tracing::warn!("Failed to prune history: {}", e),
+        }
 
-    // Set security validator options - adjust as needed for your security requirements
-    let plugin_security = PluginSecurityValidator::default();
-    plugin_service.set_security_validator(plugin_security);
-
-    // Set system requirements for plugins
-    plugin_service.set_system_requirements(system_requirements);
-
-    // Create the Arc for plugin service
-    let plugin_service = Arc::new(plugin_service);
-
-    // Initialize the plugin system
-    plugin_service.initialize().await
-        .map_err(|e| ShellBeError::Plugin(format!("Failed to initialize plugin system: {}", e)))?;
-
-    // Initialize services
-    let profile_service = Arc::new(ProfileService::new(profile_repository.clone(), event_bus.clone()));
-    let alias_service = Arc::new(AliasService::new(alias_repository, profile_repository.clone()));
-    let connection_service = Arc::new(ConnectionService::new(
-        profile_repository,
-        alias_service.clone(),
-        history_repository,
-        ssh_service,
-        event_bus.clone(),
-        Arc::new(plugin_service.get_loaded_plugins().await),
-    ));
-    let ssh_config_service = Arc::new(SshConfigService::new(ssh_config_repository));
+        // Commands like 'sync pull' and 'vault init' replace the data files themselves
+        if let Err(e) = shellbe.flush().await {
+            tracing::warn!("Failed to save pruned history: {}", e);
+        }
+    }
 
     // Create command handler
-    let command_handler = CommandHandler::new(
-        profile_service,
-        connection_service,
-        alias_service,
-        plugin_service,
-        ssh_config_service,
+    let mut command_handler = CommandHandler::new(
+        shellbe.profiles().clone(),
+        shellbe.connections().clone(),
+        shellbe.aliases().clone(),
+        shellbe.plugins().clone(),
+        shellbe.ssh_config().clone(),
     );
+    command_handler.set_output_format(output);
+    command_handler.set_assume_yes(cli.yes);
+    command_handler.set_time_format(if cli.absolute { TimeFormat::Absolute } else { TimeFormat::Relative });
+    command_handler.set_settings(settings);
+    command_handler.set_totp_service(Arc::new(TotpService::new(&data_dir, storage)));
+    command_handler.set_event_stream(event_stream);
+    command_handler.set_event_bus(event_bus.clone());
 
     // Handle command
     if let Some(command) = cli.command {
-        match command_handler.handle_command(command).await {
-            Ok(_) => {}
-            Err(e) => {
-                tracing::error!("Command error: {}", e);
-                return Err(ShellBeError::Config(format!("Failed to execute command: {}", e)));
+        let result = command_handler.handle_command(command).await;
+
+        // Repositories write behind; get what the command changed on disk before exiting
+        shellbe.flush().await?;
+
+        // Listeners run on their own tasks; give them a moment to handle the command's events
+        if tokio::time::timeout(EVENT_FLUSH_TIMEOUT, event_bus.flush()).await.is_err() {
+            tracing::warn!("Event listeners didn't finish within {:?}", EVENT_FLUSH_TIMEOUT);
+        }
+
+        // Only a command that beat the check waits for it, briefly, and once per interval
+        if let Some(mut update_check) = update_check {
+            if tokio::time::timeout(UPDATE_CHECK_GRACE, &mut update_check).await.is_err() {
+                update_check.abort();
+            }
+        }
+
+        let result = result.map_err(|e| {
+            tracing::error!("Command error: {:#}", e);
+            ErrorReport::from(e)
+        });
+
+        if let Some(name) = &command_name {
+            let category = result.as_ref().err().map(|report| report.error.category());
+            if let Err(e) = telemetry.record(name, category) {
+                tracing::debug!("Failed to record telemetry: {}", e);
+            }
+        }
+        if let Some(mut telemetry_send) = telemetry_send {
+            if tokio::time::timeout(TELEMETRY_SEND_GRACE, &mut telemetry_send).await.is_err() {
+                telemetry_send.abort();
             }
         }
+
+        result?;
+        if let Some(version) = update_notifier.available().filter(|_| notify_updates) {
+            command_handler.notify_update(&version);
+        }
     } else {
         // Print help if no command provided
         println!("No command provided. Use `shellbe help` to see available commands.");
@@ -164,12 +345,24 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-// Helper struct for FileProfileRepository configuration
-// This should be moved to the appropriate module in a full refactoring
-#[derive(Debug, Clone)]
-pub struct FileStorageConfig {
-    /// Directory where configuration files are stored
-    pub config_dir: PathBuf,
-    /// Profile configuration file name
-    pub profiles_file: String,
-}
\ No newline at end of file
+/// Print a command line error, or the help or version text, and exit
+///
+/// Errors exit with their own code, so they can't be mistaken for a missing profile.
+fn exit_usage(error: clap::Error) -> ! {
+    let _ = error.print();
+    std::process::exit(if error.use_stderr() { exit_code::USAGE.into() } else { 0 });
+}
+
+/// Command aliases from settings, read before the command line is parsed
+///
+/// Errors are ignored here; they are reported when settings are loaded properly.
+fn command_aliases(args: &[OsString]) -> HashMap<String, String> {
+    if let Some(dir) = Cli::config_dir_arg(args) {
+        std::env::set_var(CONFIG_DIR_ENV, dir);
+    }
+
+    let paths = ShellBePaths::resolve();
+    SettingsService::load(paths.config_dir.join(SETTINGS_FILE))
+        .map(|service| service.settings().aliases.clone())
+        .unwrap_or_default()
+}
@@ -1,9 +0,0 @@
-pub mod fs;
-pub mod file_lock;
-pub mod plugin_security;
-pub mod system_requirements;
-
-pub use fs::*;
-pub use file_lock::FileLock;
-pub use plugin_security::PluginSecurityValidator;
-pub use system_requirements::SystemRequirements;
\ No newline at end of file